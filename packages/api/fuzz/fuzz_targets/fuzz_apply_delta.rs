@@ -0,0 +1,11 @@
+//! Delta instructions and their base both come from the remote's packfile.
+
+#![no_main]
+
+use api::git_transport::fuzzing::apply_delta;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Vec<u8>, Vec<u8>)| {
+    let (base, delta) = input;
+    let _ = apply_delta(&base, &delta);
+});
@@ -0,0 +1,12 @@
+//! Packfiles come straight off the wire from the git remote: malformed ones
+//! must be rejected with an error, never a panic.
+
+#![no_main]
+
+use api::git_transport::fuzzing::parse_pack;
+use libfuzzer_sys::fuzz_target;
+use store::MemoryStore;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_pack(&MemoryStore::new(), data);
+});
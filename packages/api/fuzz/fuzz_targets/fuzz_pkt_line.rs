@@ -0,0 +1,12 @@
+//! Reads pkt-lines until a flush, an error, or the end of input, as the ref
+//! advertisement and sideband readers do.
+
+#![no_main]
+
+use api::git_transport::fuzzing::read_pkt_line;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = data;
+    while let Ok(Some(_)) = read_pkt_line(&mut reader) {}
+});
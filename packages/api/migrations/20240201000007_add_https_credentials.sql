@@ -0,0 +1,4 @@
+-- Credential type for git sync: 'ssh' (private key) or 'https_token' (access token)
+ALTER TABLE user_git_config ADD COLUMN credential_type TEXT NOT NULL DEFAULT 'ssh';
+ALTER TABLE user_git_config ADD COLUMN https_token_enc BYTEA;
+ALTER TABLE user_git_config ADD COLUMN https_token_nonce BYTEA;
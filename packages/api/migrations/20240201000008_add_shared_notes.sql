@@ -0,0 +1,14 @@
+-- Read-only share links for single notes
+CREATE TABLE IF NOT EXISTS shared_notes (
+    token TEXT PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    note_path TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Index for listing a user's links
+CREATE INDEX IF NOT EXISTS idx_shared_notes_user ON shared_notes(user_id);
+
+-- Index for faster expiry cleanup
+CREATE INDEX IF NOT EXISTS idx_shared_notes_expiry ON shared_notes(expires_at);
@@ -0,0 +1,2 @@
+-- Time of the last successful push from the app, for the storage stats panel
+ALTER TABLE user_git_config ADD COLUMN IF NOT EXISTS last_synced_at TIMESTAMPTZ;
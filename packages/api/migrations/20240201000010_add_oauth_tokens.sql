@@ -0,0 +1,7 @@
+-- OAuth tokens from the sign-in provider, kept so the server can refresh them
+ALTER TABLE user_git_config ADD COLUMN oauth_provider TEXT;
+ALTER TABLE user_git_config ADD COLUMN oauth_access_token_enc BYTEA;
+ALTER TABLE user_git_config ADD COLUMN oauth_access_token_nonce BYTEA;
+ALTER TABLE user_git_config ADD COLUMN oauth_refresh_token_enc BYTEA;
+ALTER TABLE user_git_config ADD COLUMN oauth_refresh_token_nonce BYTEA;
+ALTER TABLE user_git_config ADD COLUMN token_expires_at TIMESTAMPTZ;
@@ -0,0 +1,7 @@
+-- Keep only an HMAC-SHA256 of the OAuth state, and mark states as used instead
+-- of deleting them so a replayed callback can be told apart from an unknown one.
+-- Pending logins are dropped: their plaintext states no longer match.
+DELETE FROM oauth_states;
+
+ALTER TABLE oauth_states RENAME COLUMN state TO state_hash;
+ALTER TABLE oauth_states ADD COLUMN IF NOT EXISTS used_at TIMESTAMPTZ;
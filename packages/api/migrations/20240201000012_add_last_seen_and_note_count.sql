@@ -0,0 +1,4 @@
+-- When the user last opened the app, and how many notes the last sync saw,
+-- so the startup request does not need a git fetch
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ;
+ALTER TABLE user_git_config ADD COLUMN IF NOT EXISTS note_count INTEGER;
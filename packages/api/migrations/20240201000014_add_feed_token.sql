@@ -0,0 +1,3 @@
+-- Secret token in the URL of the user's Atom feed (GET /feeds/{token}),
+-- generated on the first get_feed_token call
+ALTER TABLE user_git_config ADD COLUMN IF NOT EXISTS feed_token TEXT UNIQUE;
@@ -0,0 +1,13 @@
+-- Invite-only registration: with REQUIRE_INVITE_CODE set, `register` needs a
+-- code from this table, created by an admin with create_invite_code
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS invite_codes (
+    code TEXT PRIMARY KEY,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    uses_remaining INTEGER NOT NULL CHECK (uses_remaining >= 0),
+    expires_at TIMESTAMPTZ NOT NULL,
+    -- Most recent user to redeem the code
+    used_by_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
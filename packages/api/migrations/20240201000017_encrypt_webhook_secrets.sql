@@ -0,0 +1,5 @@
+-- Webhook signing secrets are stored encrypted (see api::webhooks); rows
+-- made before this keep their plain-text `secret` until re-registered
+ALTER TABLE user_webhooks ADD COLUMN IF NOT EXISTS secret_enc BYTEA;
+ALTER TABLE user_webhooks ADD COLUMN IF NOT EXISTS secret_nonce BYTEA;
+ALTER TABLE user_webhooks ALTER COLUMN secret DROP NOT NULL;
@@ -0,0 +1,232 @@
+//! # ZIP archives of a user's notes
+//!
+//! Server-side helpers behind the export and import server functions. Each
+//! note is stored at its full repository path (so namespaces become
+//! directories inside the archive), alongside the `typednotes.toml`
+//! configuration at the root.
+//!
+//! [`read_notes_zip`] is the inverse of [`build_notes_zip`]: it extracts the
+//! entries with a note extension (see [`store::models::NOTE_TYPES`]) and
+//! reports, rather than fails on, entries it refuses (path traversal,
+//! oversized or non-UTF-8 files). An archive with more than
+//! [`MAX_IMPORT_ENTRIES`] entries, or whose accepted entries decompress to more
+//! than [`MAX_IMPORT_TOTAL_BYTES`], is refused as a whole, and reading stops
+//! as soon as the budget runs out.
+
+use std::io::{Cursor, Read, Write};
+
+use store::{TypedNoteInfo, TypedNotesConfig};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Build a deflate-compressed ZIP archive of `notes` plus the config file.
+pub fn build_notes_zip(notes: &[TypedNoteInfo], config: &TypedNotesConfig) -> Result<Vec<u8>, String> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    let config_toml = config.to_toml().map_err(|e| e.to_string())?;
+    zip.start_file(TypedNotesConfig::filename(), options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(config_toml.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    for note in notes {
+        zip.start_file(note.path.as_str(), options)
+            .map_err(|e| format!("{}: {e}", note.path))?;
+        zip.write_all(note.note.as_bytes())
+            .map_err(|e| format!("{}: {e}", note.path))?;
+    }
+
+    let cursor = zip.finish().map_err(|e| e.to_string())?;
+    Ok(cursor.into_inner())
+}
+
+/// Largest note accepted from an archive, in bytes (uncompressed).
+pub const MAX_IMPORT_NOTE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Most bytes read from all of an archive's entries together (uncompressed).
+pub const MAX_IMPORT_TOTAL_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Most entries (files and directories) an archive may hold.
+pub const MAX_IMPORT_ENTRIES: usize = 10_000;
+
+/// Notes extracted from an archive by [`read_notes_zip`].
+#[derive(Debug, Default)]
+pub struct ZipNotes {
+    /// `(path, content)` for every accepted note entry, in archive order.
+    pub notes: Vec<(String, String)>,
+    /// One message per rejected entry.
+    pub errors: Vec<String>,
+}
+
+/// Extract the notes from a ZIP archive.
+///
+/// Directories and files with other extensions (including `typednotes.toml`)
+/// are ignored. Only an unreadable archive is an error; problems with single
+/// entries end up in [`ZipNotes::errors`].
+pub fn read_notes_zip(bytes: &[u8]) -> Result<ZipNotes, String> {
+    read_text_entries(bytes, |path| {
+        path.rsplit_once('.').is_some_and(|(_, ext)| store::models::is_note_ext(ext))
+    })
+}
+
+/// Like [`read_notes_zip`], for the entries whose normalised path passes
+/// `accept`, with the same path, size and UTF-8 checks.
+pub(crate) fn read_text_entries(bytes: &[u8], accept: impl Fn(&str) -> bool) -> Result<ZipNotes, String> {
+    read_text_entries_within(bytes, accept, MAX_IMPORT_ENTRIES, MAX_IMPORT_TOTAL_BYTES)
+}
+
+/// [`read_text_entries`] with the archive limits as parameters.
+fn read_text_entries_within(
+    bytes: &[u8],
+    accept: impl Fn(&str) -> bool,
+    max_entries: usize,
+    max_total_bytes: u64,
+) -> Result<ZipNotes, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    if archive.len() > max_entries {
+        return Err(format!("Archive has more than {max_entries} entries"));
+    }
+    let mut result = ZipNotes::default();
+    let mut budget = max_total_bytes;
+
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(entry) => entry,
+            Err(e) => {
+                result.errors.push(format!("entry {i}: {e}"));
+                continue;
+            }
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let Some(path) = safe_note_path(&name) else {
+            result.errors.push(format!("{name}: unsafe path, skipped"));
+            continue;
+        };
+        if !accept(&path) {
+            continue;
+        }
+        if entry.size() > MAX_IMPORT_NOTE_BYTES {
+            result.errors.push(format!("{name}: larger than {MAX_IMPORT_NOTE_BYTES} bytes, skipped"));
+            continue;
+        }
+
+        let mut buf = Vec::new();
+        // `size()` comes from the archive header, so cap the actual read too
+        let limit = MAX_IMPORT_NOTE_BYTES.min(budget) + 1;
+        let read = (&mut entry).take(limit).read_to_end(&mut buf);
+        if buf.len() as u64 > budget {
+            return Err(format!("Archive holds more than {max_total_bytes} bytes of notes"));
+        }
+        budget -= buf.len() as u64;
+        if let Err(e) = read {
+            result.errors.push(format!("{name}: {e}"));
+            continue;
+        }
+        if buf.len() as u64 > MAX_IMPORT_NOTE_BYTES {
+            result.errors.push(format!("{name}: larger than {MAX_IMPORT_NOTE_BYTES} bytes, skipped"));
+            continue;
+        }
+        match String::from_utf8(buf) {
+            Ok(content) => result.notes.push((path, content)),
+            Err(_) => result.errors.push(format!("{name}: not valid UTF-8, skipped")),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Normalise an archive entry name to a repository path, rejecting absolute
+/// paths, backslashes and any `..` component.
+fn safe_note_path(name: &str) -> Option<String> {
+    if name.starts_with('/') || name.contains('\\') || name.contains('\0') {
+        return None;
+    }
+    let mut parts = Vec::new();
+    for part in name.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            part => parts.push(part),
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::note;
+
+    #[test]
+    fn test_build_notes_zip() {
+        let notes = [note("top.md", "# Top"), note("work/ideas/plan.txt", "plan body")];
+
+        let bytes = build_notes_zip(&notes, &TypedNotesConfig::default()).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+
+        let mut names: Vec<_> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(names, ["top.md", "typednotes.toml", "work/ideas/plan.txt"]);
+
+        let mut body = String::new();
+        archive
+            .by_name("work/ideas/plan.txt")
+            .unwrap()
+            .read_to_string(&mut body)
+            .unwrap();
+        assert_eq!(body, "plan body");
+    }
+
+    #[test]
+    fn test_read_notes_zip_roundtrip() {
+        let notes = [
+            note("top.md", "# Top"),
+            note("work/plan.txt", "plan body"),
+            note("work/agenda.org", "* TODO"),
+        ];
+        let bytes = build_notes_zip(&notes, &TypedNotesConfig::default()).unwrap();
+
+        let read = read_notes_zip(&bytes).unwrap();
+        assert_eq!(
+            read.notes,
+            [
+                ("top.md".to_string(), "# Top".to_string()),
+                ("work/plan.txt".to_string(), "plan body".to_string()),
+                ("work/agenda.org".to_string(), "* TODO".to_string()),
+            ]
+        );
+        assert!(read.errors.is_empty());
+    }
+
+    #[test]
+    fn test_read_notes_zip_rejects_traversal() {
+        let options = SimpleFileOptions::default();
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in ["../evil.md", "a/../../evil.md", "/etc/evil.md", "./ok/fine.md", "image.png"] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(b"x").unwrap();
+        }
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let read = read_notes_zip(&bytes).unwrap();
+        assert_eq!(read.notes, [("ok/fine.md".to_string(), "x".to_string())]);
+        assert_eq!(read.errors.len(), 3);
+        assert!(read_notes_zip(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn test_read_text_entries_limits() {
+        let notes = [note("a.md", &"a".repeat(600)), note("b.md", &"b".repeat(600))];
+        let bytes = build_notes_zip(&notes, &TypedNotesConfig::default()).unwrap();
+        let is_md = |path: &str| path.ends_with(".md");
+
+        assert_eq!(read_text_entries_within(&bytes, is_md, 3, 1200).unwrap().notes.len(), 2);
+        // The config file counts as an entry
+        assert!(read_text_entries_within(&bytes, is_md, 2, 1200).is_err());
+        assert!(read_text_entries_within(&bytes, is_md, 3, 1000).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! # Note attachments
+//!
+//! Files dropped into the editor are committed to the user's repository under
+//! [`ATTACHMENTS_DIR`] by the `upload_attachment` server function and linked
+//! from the note as `./assets/<name>`. Only the types in [`ALLOWED_TYPES`] are
+//! accepted, judged by extension and confirmed by the file's first bytes
+//! ([`check_content`]), up to [`MAX_ATTACHMENT_BYTES`]. SVG is not among them:
+//! an SVG can carry script that would run when the attachment is opened.
+
+/// Repository directory that holds attachments.
+pub const ATTACHMENTS_DIR: &str = "assets";
+
+/// Largest attachment accepted: 5 MB.
+pub const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Largest request body the server reads. Server function arguments arrive as
+/// JSON, where each byte of an attachment takes up to four characters
+/// (`255,`), so this leaves room for one [`MAX_ATTACHMENT_BYTES`] upload.
+pub const MAX_REQUEST_BODY_BYTES: usize = 4 * MAX_ATTACHMENT_BYTES + 64 * 1024;
+
+/// Accepted attachments as `(extension, MIME type)`.
+pub const ALLOWED_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("csv", "text/csv"),
+    ("zip", "application/zip"),
+];
+
+/// MIME type of `filename` by extension, if it is an allowed attachment.
+pub fn mime_type(filename: &str) -> Option<&'static str> {
+    let (_, ext) = filename.rsplit_once('.')?;
+    let ext = ext.to_ascii_lowercase();
+    ALLOWED_TYPES.iter().find(|(allowed, _)| *allowed == ext).map(|(_, mime)| *mime)
+}
+
+/// `filename` reduced to a safe single path component: the last component of
+/// any path, with characters other than letters, digits, `.`, `-` and `_`
+/// replaced by `-`. Errors for a name with nothing left, or a disallowed type.
+pub fn sanitize_filename(filename: &str) -> Result<String, String> {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let clean: String = base
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '-' })
+        .collect();
+    let clean = clean.trim_start_matches('.').to_string();
+    if clean.is_empty() || !clean.contains('.') {
+        return Err(format!("\"{filename}\" has no usable name"));
+    }
+    if mime_type(&clean).is_none() {
+        return Err(format!("{clean} is not an allowed attachment type"));
+    }
+    Ok(clean)
+}
+
+/// Check that `bytes` look like the type `name`'s extension claims: the
+/// format's signature for binary types, UTF-8 without NUL bytes for text.
+pub fn check_content(name: &str, bytes: &[u8]) -> Result<(), String> {
+    let matches = match mime_type(name) {
+        Some("image/png") => bytes.starts_with(b"\x89PNG\r\n\x1a\n"),
+        Some("image/jpeg") => bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
+        Some("image/gif") => bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
+        Some("image/webp") => bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP",
+        Some("application/pdf") => bytes.starts_with(b"%PDF-"),
+        // An empty archive is only the end-of-directory record
+        Some("application/zip") => bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06"),
+        Some("text/plain" | "text/csv") => std::str::from_utf8(bytes).is_ok_and(|text| !text.contains('\0')),
+        _ => false,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(format!("{name} does not look like a {} file", name.rsplit_once('.').map_or(name, |(_, ext)| ext)))
+    }
+}
+
+/// `name` with `-1`, `-2`, … before the extension for the `n`th retry
+/// after a clash (`n = 0` is `name` itself).
+pub fn numbered_name(name: &str, n: usize) -> String {
+    if n == 0 {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}-{n}.{ext}"),
+        None => format!("{name}-{n}"),
+    }
+}
+
+/// Relative URL a note uses to link attachment `name`.
+pub fn attachment_url(name: &str) -> String {
+    format!("./{ATTACHMENTS_DIR}/{name}")
+}
+
+/// Markdown for an uploaded attachment: an image embed for images, a link
+/// otherwise.
+pub fn attachment_markdown(name: &str, url: &str) -> String {
+    let is_image = mime_type(name).is_some_and(|mime| mime.starts_with("image/"));
+    if is_image {
+        let alt = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+        format!("![{alt}]({url})")
+    } else {
+        format!("[{name}]({url})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("Screen Shot 1.PNG").unwrap(), "Screen-Shot-1.PNG");
+        assert_eq!(sanitize_filename("../../etc/cat.jpg").unwrap(), "cat.jpg");
+        assert_eq!(sanitize_filename("C:\\Users\\me\\.hidden.pdf").unwrap(), "hidden.pdf");
+        assert!(sanitize_filename("run.exe").unwrap_err().contains("not an allowed"));
+        assert!(sanitize_filename("logo.svg").is_err());
+        assert!(sanitize_filename("../").is_err());
+        assert!(sanitize_filename("README").is_err());
+    }
+
+    #[test]
+    fn test_check_content() {
+        assert!(check_content("cat.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_ok());
+        assert!(check_content("cat.JPG", &[0xFF, 0xD8, 0xFF, 0xE0]).is_ok());
+        assert!(check_content("cat.webp", b"RIFF\x10\0\0\0WEBPVP8 ").is_ok());
+        assert!(check_content("spec.pdf", b"%PDF-1.7\n").is_ok());
+        assert!(check_content("notes.csv", "a,b\n1,é\n".as_bytes()).is_ok());
+        // The extension and the content disagree
+        assert!(check_content("cat.png", b"<svg onload=\"alert(1)\"/>").is_err());
+        assert!(check_content("spec.pdf", b"PK\x03\x04").is_err());
+        assert!(check_content("notes.txt", b"MZ\x90\0").is_err());
+        assert!(check_content("cat.webp", b"RIFF").is_err());
+    }
+
+    #[test]
+    fn test_attachment_markdown() {
+        assert_eq!(numbered_name("cat.png", 0), "cat.png");
+        assert_eq!(numbered_name("cat.png", 2), "cat-2.png");
+        let url = attachment_url("cat.png");
+        assert_eq!(url, "./assets/cat.png");
+        assert_eq!(attachment_markdown("cat.png", &url), "![cat](./assets/cat.png)");
+        assert_eq!(attachment_markdown("spec.pdf", "./assets/spec.pdf"), "[spec.pdf](./assets/spec.pdf)");
+    }
+}
@@ -4,14 +4,16 @@
 //! needed by any OAuth 2.0 Authorization Code flow: client ID, client secret, authorize
 //! URL, token URL, and redirect URL.
 //!
-//! Two constructors are offered:
+//! Three constructors are offered:
 //!
 //! - [`OAuthConfig::github`] — reads `GITHUB_CLIENT_ID`, `GITHUB_CLIENT_SECRET`, and
 //!   optionally `GITHUB_AUTH_REDIRECT_URI` (defaults to `localhost:8080`).
 //! - [`OAuthConfig::google`] — reads `GOOGLE_CLIENT_ID`, `GOOGLE_CLIENT_SECRET`, and
 //!   optionally `GOOGLE_AUTH_REDIRECT_URI`.
+//! - [`OAuthConfig::gitlab`] — reads `GITLAB_CLIENT_ID`, `GITLAB_CLIENT_SECRET`, and
+//!   optionally `GITLAB_AUTH_REDIRECT_URI` and `GITLAB_BASE_URL` (see [`gitlab_base_url`]).
 //!
-//! Both load `.env` via `dotenvy` first so local development works without exporting
+//! All of them load `.env` via `dotenvy` first so local development works without exporting
 //! variables manually. In production the values come from the container environment
 //! injected by the infrastructure layer.
 
@@ -23,6 +25,8 @@ pub struct OAuthConfig {
     pub client_id: ClientId,
     pub client_secret: ClientSecret,
     pub auth_url: AuthUrl,
+    /// Token endpoint, used both for the code exchange and for refreshing
+    /// stored access tokens.
     pub token_url: TokenUrl,
     pub redirect_url: RedirectUrl,
 }
@@ -73,4 +77,39 @@ impl OAuthConfig {
                 .map_err(|e| e.to_string())?,
         })
     }
+
+    /// Create GitLab OAuth config from environment variables.
+    pub fn gitlab() -> Result<Self, String> {
+        dotenvy::dotenv().ok();
+
+        let client_id = std::env::var("GITLAB_CLIENT_ID")
+            .map_err(|_| "GITLAB_CLIENT_ID not set")?;
+        let client_secret = std::env::var("GITLAB_CLIENT_SECRET")
+            .map_err(|_| "GITLAB_CLIENT_SECRET not set")?;
+        let redirect_uri = std::env::var("GITLAB_AUTH_REDIRECT_URI")
+            .unwrap_or_else(|_| "http://localhost:8080/auth/gitlab/callback".to_string());
+        let base = gitlab_base_url();
+
+        Ok(Self {
+            client_id: ClientId::new(client_id),
+            client_secret: ClientSecret::new(client_secret),
+            auth_url: AuthUrl::new(format!("{base}/oauth/authorize"))
+                .map_err(|e| e.to_string())?,
+            token_url: TokenUrl::new(format!("{base}/oauth/token"))
+                .map_err(|e| e.to_string())?,
+            redirect_url: RedirectUrl::new(redirect_uri)
+                .map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+/// Root URL of the GitLab instance: `GITLAB_BASE_URL` for self-hosted
+/// installations, `https://gitlab.com` otherwise. Never ends in `/`.
+pub fn gitlab_base_url() -> String {
+    dotenvy::dotenv().ok();
+    std::env::var("GITLAB_BASE_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "https://gitlab.com".to_string())
 }
@@ -14,29 +14,38 @@
 //!
 //! 1. **[`generate_auth_url`](GitHubOAuth::generate_auth_url)** — builds an authorization
 //!    URL requesting the `user:email` and `read:user` scopes, generates a random PKCE
-//!    challenge, and persists the CSRF state + verifier in the `oauth_states` table with
-//!    a 10-minute expiry.
+//!    challenge, and persists an HMAC of the CSRF state + the verifier in the `oauth_states`
+//!    table with a 10-minute expiry. The caller keeps the verifier in the session.
 //!
 //! 2. **[`exchange_code`](GitHubOAuth::exchange_code)** — called by the `/auth/github/callback`
 //!    route in the `web` crate. It:
-//!    - Retrieves and atomically deletes the matching `oauth_states` row (validating CSRF
-//!      state and expiry in one query).
+//!    - Checks the CSRF state with [`verify_oauth_state`](super::session::verify_oauth_state):
+//!      the `oauth_states` row must be unused, unexpired, and hold the PKCE verifier
+//!      found in the caller's session. The row is then marked as used.
 //!    - Exchanges the authorization code + PKCE verifier for an access token.
 //!    - Fetches the user's profile from `api.github.com/user`; if no email is present,
 //!      falls back to `/user/emails` and picks the primary verified address.
 //!    - Upserts the user in the `users` table (keyed on `provider = 'github'` +
 //!      `provider_id`) so returning users get their profile refreshed.
+//!    - Stores the encrypted access and refresh tokens (see [`super::tokens`]).
+//!
+//! 3. **[`refresh_oauth_token`](GitHubOAuth::refresh_oauth_token)** — renews the
+//!    stored access token. GitHub only issues refresh tokens to apps with token
+//!    expiration enabled; classic OAuth tokens never expire and are not refreshed.
 
 use oauth2::basic::BasicClient;
 use oauth2::{
     AuthorizationCode, CsrfToken, EndpointNotSet, EndpointSet, PkceCodeChallenge,
-    PkceCodeVerifier, Scope, TokenResponse,
+    PkceCodeVerifier, RefreshToken, Scope, TokenResponse,
 };
 use reqwest::Client;
 use serde::Deserialize;
 
 use super::config::OAuthConfig;
+use super::session::{store_oauth_state, verify_oauth_state};
+use super::tokens;
 use crate::db::get_pool;
+use crate::invites;
 use crate::models::User;
 
 /// GitHub user info from API.
@@ -103,22 +112,9 @@ impl GitHubOAuth {
             .set_pkce_challenge(pkce_challenge)
             .url();
 
-        // Store state and verifier in database
-        let pool = get_pool().await.map_err(|e| e.to_string())?;
         let state = csrf_state.secret().clone();
         let verifier = pkce_verifier.secret().clone();
-
-        sqlx::query(
-            r#"
-            INSERT INTO oauth_states (state, provider, pkce_verifier, expires_at)
-            VALUES ($1, 'github', $2, NOW() + INTERVAL '10 minutes')
-            "#,
-        )
-        .bind(&state)
-        .bind(&verifier)
-        .execute(pool)
-        .await
-        .map_err(|e| e.to_string())?;
+        store_oauth_state("github", &state, &verifier).await?;
 
         Ok((auth_url.to_string(), state, verifier))
     }
@@ -128,25 +124,10 @@ impl GitHubOAuth {
         &self,
         code: &str,
         state: &str,
+        code_verifier: &str,
     ) -> Result<User, String> {
         let pool = get_pool().await.map_err(|e| e.to_string())?;
-
-        // Retrieve and delete the state from database
-        let row: Option<(String,)> = sqlx::query_as(
-            r#"
-            DELETE FROM oauth_states
-            WHERE state = $1 AND provider = 'github' AND expires_at > NOW()
-            RETURNING pkce_verifier
-            "#,
-        )
-        .bind(state)
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        let pkce_verifier = row
-            .ok_or("Invalid or expired OAuth state")?
-            .0;
+        let pkce_verifier = verify_oauth_state("github", state, code_verifier).await?;
 
         // Create HTTP client for token exchange
         let http_client = reqwest::ClientBuilder::new()
@@ -203,11 +184,13 @@ impl GitHubOAuth {
             }
         };
 
+        invites::check_oauth_signup(pool, "github", &github_user.id.to_string()).await?;
+
         // Upsert user in database
         let user: User = sqlx::query_as(
             r#"
-            INSERT INTO users (email, name, avatar_url, provider, provider_id)
-            VALUES ($1, $2, $3, 'github', $4)
+            INSERT INTO users (email, name, avatar_url, provider, provider_id, email_verified)
+            VALUES ($1, $2, $3, 'github', $4, TRUE)
             ON CONFLICT (provider, provider_id)
             DO UPDATE SET
                 email = EXCLUDED.email,
@@ -225,6 +208,34 @@ impl GitHubOAuth {
         .await
         .map_err(|e| e.to_string())?;
 
+        // Storing the tokens only matters for later refreshes; the login
+        // itself has succeeded
+        if let Err(e) = tokens::store_tokens(user.id, "github", &token_result).await {
+            tracing::warn!(error = %e, user_id = %user.id, "could not store github OAuth tokens");
+        }
+
         Ok(user)
     }
+
+    /// Trade the user's stored refresh token for a new access token and store
+    /// the result. Fails if no refresh token was issued for this user.
+    pub async fn refresh_oauth_token(&self, user_uuid: uuid::Uuid) -> Result<(), String> {
+        let refresh_token = tokens::load_refresh_token(user_uuid, "github")
+            .await?
+            .ok_or("No GitHub refresh token stored")?;
+
+        let http_client = reqwest::ClientBuilder::new()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let token_result = self
+            .create_client()
+            .exchange_refresh_token(&RefreshToken::new(refresh_token))
+            .request_async(&http_client)
+            .await
+            .map_err(|e| format!("Token refresh failed: {}", e))?;
+
+        tokens::store_tokens(user_uuid, "github", &token_result).await
+    }
 }
@@ -0,0 +1,217 @@
+//! # GitLab OAuth 2.0 implementation
+//!
+//! Implements the GitLab Authorization Code flow with PKCE for TypedNotes. The
+//! structure mirrors [`super::github`]; every endpoint is relative to the instance
+//! root from [`gitlab_base_url`], so self-hosted GitLab works by setting
+//! `GITLAB_BASE_URL`.
+//!
+//! ## Types
+//!
+//! - [`GitLabUser`] — deserialization target for `{base}/api/v4/user`.
+//! - [`ConfiguredClient`] — a fully-typed `oauth2::Client` alias with auth and token
+//!   endpoints set.
+//! - [`GitLabOAuth`] — the public handler that wraps an [`OAuthConfig`].
+//!
+//! ## Flow
+//!
+//! 1. **[`generate_auth_url`](GitLabOAuth::generate_auth_url)** — builds an authorization
+//!    URL on `{base}/oauth/authorize` requesting the `read_user` scope, generates a random
+//!    PKCE challenge, and persists an HMAC of the CSRF state + the verifier in the
+//!    `oauth_states` table with a 10-minute expiry.
+//!
+//! 2. **[`exchange_code`](GitLabOAuth::exchange_code)** — called by the `/auth/gitlab/callback`
+//!    route in the `web` crate. It:
+//!    - Checks the CSRF state with [`verify_oauth_state`](super::session::verify_oauth_state).
+//!    - Exchanges the authorization code + PKCE verifier at `{base}/oauth/token`.
+//!    - Fetches the user's profile from `{base}/api/v4/user`.
+//!    - Upserts the user in the `users` table (keyed on `provider = 'gitlab'` +
+//!      `provider_id`).
+//!    - Stores the encrypted access and refresh tokens (see [`super::tokens`]).
+//!
+//! 3. **[`refresh_oauth_token`](GitLabOAuth::refresh_oauth_token)** — renews the
+//!    stored access token. GitLab access tokens expire after two hours and always
+//!    come with a refresh token.
+
+use oauth2::basic::BasicClient;
+use oauth2::{
+    AuthorizationCode, CsrfToken, EndpointNotSet, EndpointSet, PkceCodeChallenge,
+    PkceCodeVerifier, RefreshToken, Scope, TokenResponse,
+};
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::config::{gitlab_base_url, OAuthConfig};
+use super::session::{store_oauth_state, verify_oauth_state};
+use super::tokens;
+use crate::db::get_pool;
+use crate::invites;
+use crate::models::User;
+
+/// GitLab user info from API.
+#[derive(Debug, Deserialize)]
+struct GitLabUser {
+    id: i64,
+    username: String,
+    email: Option<String>,
+    name: Option<String>,
+    avatar_url: Option<String>,
+}
+
+/// OAuth client type with auth URL and token URL set.
+type ConfiguredClient = oauth2::Client<
+    oauth2::basic::BasicErrorResponse,
+    oauth2::basic::BasicTokenResponse,
+    oauth2::basic::BasicTokenIntrospectionResponse,
+    oauth2::StandardRevocableToken,
+    oauth2::basic::BasicRevocationErrorResponse,
+    EndpointSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointSet,
+>;
+
+/// GitLab OAuth handler.
+pub struct GitLabOAuth {
+    config: OAuthConfig,
+    base_url: String,
+}
+
+impl GitLabOAuth {
+    /// Create a new GitLab OAuth handler.
+    pub fn new() -> Result<Self, String> {
+        let config = OAuthConfig::gitlab()?;
+        Ok(Self {
+            config,
+            base_url: gitlab_base_url(),
+        })
+    }
+
+    fn create_client(&self) -> ConfiguredClient {
+        BasicClient::new(self.config.client_id.clone())
+            .set_client_secret(self.config.client_secret.clone())
+            .set_auth_uri(self.config.auth_url.clone())
+            .set_token_uri(self.config.token_url.clone())
+            .set_redirect_uri(self.config.redirect_url.clone())
+    }
+
+    /// Generate authorization URL with PKCE.
+    pub async fn generate_auth_url(&self) -> Result<(String, String, String), String> {
+        let client = self.create_client();
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        let (auth_url, csrf_state) = client
+            .authorize_url(CsrfToken::new_random)
+            .add_scope(Scope::new("read_user".to_string()))
+            .set_pkce_challenge(pkce_challenge)
+            .url();
+
+        let state = csrf_state.secret().clone();
+        let verifier = pkce_verifier.secret().clone();
+        store_oauth_state("gitlab", &state, &verifier).await?;
+
+        Ok((auth_url.to_string(), state, verifier))
+    }
+
+    /// Exchange authorization code for tokens and get user info.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        state: &str,
+        code_verifier: &str,
+    ) -> Result<User, String> {
+        let pool = get_pool().await.map_err(|e| e.to_string())?;
+        let pkce_verifier = verify_oauth_state("gitlab", state, code_verifier).await?;
+
+        // Create HTTP client for token exchange
+        let http_client = reqwest::ClientBuilder::new()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let client = self.create_client();
+
+        // Exchange code for token
+        let token_result = client
+            .exchange_code(AuthorizationCode::new(code.to_string()))
+            .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier))
+            .request_async(&http_client)
+            .await
+            .map_err(|e| format!("Token exchange failed: {}", e))?;
+
+        let access_token = token_result.access_token().secret();
+
+        // Fetch user info from the GitLab API
+        let gitlab_user: GitLabUser = Client::new()
+            .get(format!("{}/api/v4/user", self.base_url))
+            .header("Authorization", format!("Bearer {}", access_token))
+            .header("User-Agent", "TypedNotes")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .error_for_status()
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let email = gitlab_user
+            .email
+            .filter(|email| !email.is_empty())
+            .ok_or("No email address on the GitLab account")?;
+
+        invites::check_oauth_signup(pool, "gitlab", &gitlab_user.id.to_string()).await?;
+
+        // Upsert user in database
+        let user: User = sqlx::query_as(
+            r#"
+            INSERT INTO users (email, name, avatar_url, provider, provider_id, email_verified)
+            VALUES ($1, $2, $3, 'gitlab', $4, TRUE)
+            ON CONFLICT (provider, provider_id)
+            DO UPDATE SET
+                email = EXCLUDED.email,
+                name = EXCLUDED.name,
+                avatar_url = EXCLUDED.avatar_url,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(&email)
+        .bind(&gitlab_user.name.or(Some(gitlab_user.username)))
+        .bind(&gitlab_user.avatar_url)
+        .bind(gitlab_user.id.to_string())
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        // Storing the tokens only matters for later refreshes; the login
+        // itself has succeeded
+        if let Err(e) = tokens::store_tokens(user.id, "gitlab", &token_result).await {
+            tracing::warn!(error = %e, user_id = %user.id, "could not store gitlab OAuth tokens");
+        }
+
+        Ok(user)
+    }
+
+    /// Trade the user's stored refresh token for a new access token and store
+    /// the result. Fails if no refresh token was issued for this user.
+    pub async fn refresh_oauth_token(&self, user_uuid: uuid::Uuid) -> Result<(), String> {
+        let refresh_token = tokens::load_refresh_token(user_uuid, "gitlab")
+            .await?
+            .ok_or("No GitLab refresh token stored")?;
+
+        let http_client = reqwest::ClientBuilder::new()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let token_result = self
+            .create_client()
+            .exchange_refresh_token(&RefreshToken::new(refresh_token))
+            .request_async(&http_client)
+            .await
+            .map_err(|e| format!("Token refresh failed: {}", e))?;
+
+        tokens::store_tokens(user_uuid, "gitlab", &token_result).await
+    }
+}
@@ -15,28 +15,37 @@
 //!
 //! 1. **[`generate_auth_url`](GoogleOAuth::generate_auth_url)** — builds an authorization
 //!    URL requesting `openid`, `email`, and `profile` scopes, generates a random PKCE
-//!    challenge, and persists the CSRF state + verifier in the `oauth_states` table with
-//!    a 10-minute expiry.
+//!    challenge, and persists an HMAC of the CSRF state + the verifier in the `oauth_states`
+//!    table with a 10-minute expiry. The caller keeps the verifier in the session.
 //!
 //! 2. **[`exchange_code`](GoogleOAuth::exchange_code)** — called by the `/auth/google/callback`
 //!    route in the `web` crate. It:
-//!    - Retrieves and atomically deletes the matching `oauth_states` row (validating CSRF
-//!      state and expiry in one query).
+//!    - Checks the CSRF state with [`verify_oauth_state`](super::session::verify_oauth_state):
+//!      the `oauth_states` row must be unused, unexpired, and hold the PKCE verifier
+//!      found in the caller's session. The row is then marked as used.
 //!    - Exchanges the authorization code + PKCE verifier for an access token.
 //!    - Fetches the user's profile from the Google userinfo endpoint.
 //!    - Upserts the user in the `users` table (keyed on `provider = 'google'` +
 //!      `provider_id`) so returning users get their profile refreshed.
+//!    - Stores the encrypted access and refresh tokens (see [`super::tokens`]).
+//!
+//! 3. **[`refresh_oauth_token`](GoogleOAuth::refresh_oauth_token)** — renews the
+//!    stored access token. The authorization URL asks for `access_type=offline`
+//!    so Google issues a refresh token.
 
 use oauth2::basic::BasicClient;
 use oauth2::{
     AuthorizationCode, CsrfToken, EndpointNotSet, EndpointSet, PkceCodeChallenge,
-    PkceCodeVerifier, Scope, TokenResponse,
+    PkceCodeVerifier, RefreshToken, Scope, TokenResponse,
 };
 use reqwest::Client;
 use serde::Deserialize;
 
 use super::config::OAuthConfig;
+use super::session::{store_oauth_state, verify_oauth_state};
+use super::tokens;
 use crate::db::get_pool;
+use crate::invites;
 use crate::models::User;
 
 /// Google user info from API.
@@ -92,25 +101,14 @@ impl GoogleOAuth {
             .add_scope(Scope::new("openid".to_string()))
             .add_scope(Scope::new("email".to_string()))
             .add_scope(Scope::new("profile".to_string()))
+            // Ask for a refresh token so the server can renew the access token
+            .add_extra_param("access_type", "offline")
             .set_pkce_challenge(pkce_challenge)
             .url();
 
-        // Store state and verifier in database
-        let pool = get_pool().await.map_err(|e| e.to_string())?;
         let state = csrf_state.secret().clone();
         let verifier = pkce_verifier.secret().clone();
-
-        sqlx::query(
-            r#"
-            INSERT INTO oauth_states (state, provider, pkce_verifier, expires_at)
-            VALUES ($1, 'google', $2, NOW() + INTERVAL '10 minutes')
-            "#,
-        )
-        .bind(&state)
-        .bind(&verifier)
-        .execute(pool)
-        .await
-        .map_err(|e| e.to_string())?;
+        store_oauth_state("google", &state, &verifier).await?;
 
         Ok((auth_url.to_string(), state, verifier))
     }
@@ -120,25 +118,10 @@ impl GoogleOAuth {
         &self,
         code: &str,
         state: &str,
+        code_verifier: &str,
     ) -> Result<User, String> {
         let pool = get_pool().await.map_err(|e| e.to_string())?;
-
-        // Retrieve and delete the state from database
-        let row: Option<(String,)> = sqlx::query_as(
-            r#"
-            DELETE FROM oauth_states
-            WHERE state = $1 AND provider = 'google' AND expires_at > NOW()
-            RETURNING pkce_verifier
-            "#,
-        )
-        .bind(state)
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        let pkce_verifier = row
-            .ok_or("Invalid or expired OAuth state")?
-            .0;
+        let pkce_verifier = verify_oauth_state("google", state, code_verifier).await?;
 
         // Create HTTP client for token exchange
         let http_client = reqwest::ClientBuilder::new()
@@ -171,11 +154,13 @@ impl GoogleOAuth {
             .await
             .map_err(|e| e.to_string())?;
 
+        invites::check_oauth_signup(pool, "google", &google_user.id).await?;
+
         // Upsert user in database
         let user: User = sqlx::query_as(
             r#"
-            INSERT INTO users (email, name, avatar_url, provider, provider_id)
-            VALUES ($1, $2, $3, 'google', $4)
+            INSERT INTO users (email, name, avatar_url, provider, provider_id, email_verified)
+            VALUES ($1, $2, $3, 'google', $4, TRUE)
             ON CONFLICT (provider, provider_id)
             DO UPDATE SET
                 email = EXCLUDED.email,
@@ -193,6 +178,34 @@ impl GoogleOAuth {
         .await
         .map_err(|e| e.to_string())?;
 
+        // Storing the tokens only matters for later refreshes; the login
+        // itself has succeeded
+        if let Err(e) = tokens::store_tokens(user.id, "google", &token_result).await {
+            tracing::warn!(error = %e, user_id = %user.id, "could not store google OAuth tokens");
+        }
+
         Ok(user)
     }
+
+    /// Trade the user's stored refresh token for a new access token and store
+    /// the result. Fails if no refresh token was issued for this user.
+    pub async fn refresh_oauth_token(&self, user_uuid: uuid::Uuid) -> Result<(), String> {
+        let refresh_token = tokens::load_refresh_token(user_uuid, "google")
+            .await?
+            .ok_or("No Google refresh token stored")?;
+
+        let http_client = reqwest::ClientBuilder::new()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let token_result = self
+            .create_client()
+            .exchange_refresh_token(&RefreshToken::new(refresh_token))
+            .request_async(&http_client)
+            .await
+            .map_err(|e| format!("Token refresh failed: {}", e))?;
+
+        tokens::store_tokens(user_uuid, "google", &token_result).await
+    }
 }
@@ -12,31 +12,43 @@
 //! | [`config`] | Reads OAuth client credentials from environment variables and builds [`OAuthConfig`] structs |
 //! | [`github`] | GitHub OAuth 2.0 flow — authorization URL generation, code exchange, user upsert |
 //! | [`google`] | Google OAuth 2.0 flow — same pattern as GitHub with OpenID Connect scopes |
+//! | [`gitlab`] | GitLab OAuth 2.0 flow against gitlab.com or a self-hosted instance (`GITLAB_BASE_URL`) |
 //! | [`password`] | Argon2id password hashing and verification for local (email+password) accounts |
-//! | [`session`] | Session data types and the [`SESSION_USER_ID_KEY`] constant used across the crate |
+//! | [`tokens`] | Encrypted storage of provider access/refresh tokens and automatic refresh before they expire |
+//! | [`session`] | Session data types, the [`SESSION_USER_ID_KEY`] constant used across the crate, [`start_user_session`], and OAuth state verification |
 //!
 //! ## OAuth flow overview
 //!
 //! 1. The frontend calls `get_login_url(provider)` which delegates to
-//!    [`GitHubOAuth::generate_auth_url`] or [`GoogleOAuth::generate_auth_url`].
-//! 2. The handler creates a PKCE challenge, persists the CSRF state + verifier in the
-//!    `oauth_states` table (with a 10-minute TTL), and returns the authorization URL.
+//!    [`GitHubOAuth::generate_auth_url`], [`GoogleOAuth::generate_auth_url`] or
+//!    [`GitLabOAuth::generate_auth_url`].
+//! 2. The handler creates a PKCE challenge, persists an HMAC of the CSRF state + the
+//!    verifier in the `oauth_states` table (with a 10-minute TTL), and returns the
+//!    authorization URL. `get_login_url` also keeps the verifier in the session.
 //! 3. After the user consents, the provider redirects to `/auth/{provider}/callback`
-//!    (handled in the `web` crate) which calls `exchange_code` to trade the authorization
-//!    code for an access token, fetch the user profile, and upsert the `users` row.
-//! 4. The callback stores the user ID in the `tower-sessions` session so subsequent
-//!    server functions can authenticate the caller.
+//!    (handled in the `web` crate) which calls `exchange_code` with the session's
+//!    verifier. [`verify_oauth_state`] accepts each state once; the code is then traded
+//!    for an access token, the user profile fetched, and the `users` row upserted —
+//!    already `email_verified`, since the provider vouches for the address.
+//!    The provider tokens are encrypted and kept so they can be refreshed later.
+//! 4. The callback calls [`start_user_session`] to store the user ID (plus login time,
+//!    IP and user agent) in the `tower-sessions` session so subsequent server functions
+//!    can authenticate the caller.
 
 #[cfg(feature = "server")]
 mod config;
 #[cfg(feature = "server")]
 mod github;
 #[cfg(feature = "server")]
+mod gitlab;
+#[cfg(feature = "server")]
 mod google;
 #[cfg(feature = "server")]
 mod password;
 #[cfg(feature = "server")]
 mod session;
+#[cfg(feature = "server")]
+mod tokens;
 
 #[cfg(feature = "server")]
 pub use config::OAuthConfig;
@@ -45,6 +57,14 @@ pub use password::{hash_password, verify_password};
 #[cfg(feature = "server")]
 pub use github::GitHubOAuth;
 #[cfg(feature = "server")]
+pub use gitlab::GitLabOAuth;
+#[cfg(feature = "server")]
 pub use google::GoogleOAuth;
 #[cfg(feature = "server")]
-pub use session::{SessionData, SESSION_USER_ID_KEY};
+pub use tokens::{refresh_if_expiring, REFRESH_MARGIN_SECS};
+#[cfg(feature = "server")]
+pub use session::{
+    start_user_session, verify_oauth_state, SessionData, OAUTH_STATE_TTL_MINS,
+    SESSION_CREATED_AT_KEY, SESSION_INACTIVITY_SECS, SESSION_IP_KEY,
+    SESSION_OAUTH_VERIFIER_KEY, SESSION_USER_AGENT_KEY, SESSION_USER_ID_KEY,
+};
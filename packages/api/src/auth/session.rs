@@ -11,12 +11,48 @@
 //!   optional `user_id`; defaults to `None` (unauthenticated). It is `Serialize + Deserialize`
 //!   so it can be persisted by the PostgreSQL-backed session store
 //!   (`tower-sessions-sqlx-store`).
+//!
+//! - [`start_user_session`] — marks a session as logged in. Besides the user ID it records
+//!   when and from where the login happened ([`SESSION_CREATED_AT_KEY`],
+//!   [`SESSION_IP_KEY`], [`SESSION_USER_AGENT_KEY`]) so the "Active Sessions" list in
+//!   Settings can tell sessions apart. The store itself only keeps an expiry date.
+//!
+//! - [`store_oauth_state`] / [`verify_oauth_state`] — the CSRF check of the OAuth flow.
+//!   Only a SHA-256 of the `state` parameter is kept in `oauth_states`, so logging in
+//!   does not depend on `ENCRYPTION_KEY`. The PKCE verifier is stored there and in the browser's session
+//!   under [`SESSION_OAUTH_VERIFIER_KEY`]; a callback is accepted once, before the TTL,
+//!   and only from the session that started the login.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::db::get_pool;
 
 /// Key for storing user ID in session.
 pub const SESSION_USER_ID_KEY: &str = "user_id";
 
+/// Key for the login time (Unix seconds).
+pub const SESSION_CREATED_AT_KEY: &str = "created_at";
+
+/// Key for the client IP address seen at login.
+pub const SESSION_IP_KEY: &str = "ip_address";
+
+/// Key for the `User-Agent` header seen at login.
+pub const SESSION_USER_AGENT_KEY: &str = "user_agent";
+
+/// Key for the PKCE verifier of an OAuth login in progress.
+pub const SESSION_OAUTH_VERIFIER_KEY: &str = "oauth_pkce_verifier";
+
+/// How long an OAuth login may take, in minutes.
+pub const OAUTH_STATE_TTL_MINS: i32 = 10;
+
+/// Inactivity timeout of a session, in seconds (7 days).
+///
+/// The session store pushes `expiry_date` forward by this much on every
+/// request, so `expiry_date - SESSION_INACTIVITY_SECS` is the last activity.
+pub const SESSION_INACTIVITY_SECS: i64 = 60 * 60 * 24 * 7;
+
 /// Session data stored in the session store.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionData {
@@ -28,3 +64,177 @@ impl Default for SessionData {
         Self { user_id: None }
     }
 }
+
+/// Log `user_id` in on `session`, recording login time, IP and user agent.
+pub async fn start_user_session(
+    session: &tower_sessions::Session,
+    user_id: &str,
+    headers: &dioxus::fullstack::HeaderMap,
+) -> Result<(), tower_sessions::session::Error> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    // Behind the production proxy the client address is the first X-Forwarded-For entry
+    let ip = header("x-forwarded-for")
+        .and_then(|v| v.split(',').next().map(|ip| ip.trim().to_string()))
+        .or_else(|| header("x-real-ip"));
+
+    session.insert(SESSION_USER_ID_KEY, user_id).await?;
+    session
+        .insert(SESSION_CREATED_AT_KEY, chrono::Utc::now().timestamp())
+        .await?;
+    session.insert(SESSION_IP_KEY, ip).await?;
+    session
+        .insert(SESSION_USER_AGENT_KEY, header("user-agent"))
+        .await?;
+    Ok(())
+}
+
+/// Hex SHA-256 of an OAuth `state` value. The state is a random value of its
+/// own, so a plain hash keeps it out of the table without needing a key.
+fn hash_oauth_state(state: &str) -> String {
+    hex::encode(Sha256::digest(state.as_bytes()))
+}
+
+/// Record a new OAuth login for `provider`, expiring after [`OAUTH_STATE_TTL_MINS`].
+///
+/// Expired states are pruned on the way.
+pub(super) async fn store_oauth_state(provider: &str, state: &str, pkce_verifier: &str) -> Result<(), String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+
+    sqlx::query("DELETE FROM oauth_states WHERE expires_at < NOW()")
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO oauth_states (state_hash, provider, pkce_verifier, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(mins => $4))
+        "#,
+    )
+    .bind(hash_oauth_state(state))
+    .bind(provider)
+    .bind(pkce_verifier)
+    .bind(OAUTH_STATE_TTL_MINS)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// A stored OAuth state, as far as [`check_oauth_state`] cares.
+#[derive(Debug, Clone)]
+struct OAuthStateRow {
+    pkce_verifier: String,
+    expires_at: DateTime<Utc>,
+    used_at: Option<DateTime<Utc>>,
+}
+
+/// Accept `row` as the state of the callback being handled, at time `now`.
+fn check_oauth_state(
+    row: Option<&OAuthStateRow>,
+    code_verifier: &str,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    let row = row.ok_or("Unknown OAuth state")?;
+    if row.used_at.is_some() {
+        return Err("OAuth state was already used".to_string());
+    }
+    if row.expires_at <= now {
+        return Err("OAuth state has expired".to_string());
+    }
+    if code_verifier.is_empty() || !constant_time_eq(row.pkce_verifier.as_bytes(), code_verifier.as_bytes()) {
+        return Err("OAuth state was issued to a different session".to_string());
+    }
+    Ok(row.pkce_verifier.clone())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Verify the `state` of an OAuth callback for `provider` and mark it as used.
+///
+/// `code_verifier` is the PKCE verifier kept in the caller's session; it must
+/// match the one stored with the state. Returns the stored verifier for the
+/// token exchange.
+pub async fn verify_oauth_state(provider: &str, state: &str, code_verifier: &str) -> Result<String, String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+    let state_hash = hash_oauth_state(state);
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let row: Option<(String, DateTime<Utc>, Option<DateTime<Utc>>)> = sqlx::query_as(
+        r#"
+        SELECT pkce_verifier, expires_at, used_at FROM oauth_states
+        WHERE state_hash = $1 AND provider = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(&state_hash)
+    .bind(provider)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let row = row.map(|(pkce_verifier, expires_at, used_at)| OAuthStateRow {
+        pkce_verifier,
+        expires_at,
+        used_at,
+    });
+    let verifier = check_oauth_state(row.as_ref(), code_verifier, Utc::now())?;
+
+    sqlx::query("UPDATE oauth_states SET used_at = NOW() WHERE state_hash = $1")
+        .bind(&state_hash)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(verifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fresh_row(now: DateTime<Utc>) -> OAuthStateRow {
+        OAuthStateRow {
+            pkce_verifier: "verifier-123".to_string(),
+            expires_at: now + chrono::Duration::minutes(i64::from(OAUTH_STATE_TTL_MINS)),
+            used_at: None,
+        }
+    }
+
+    #[test]
+    fn test_oauth_state_accepted_once() {
+        let now = Utc::now();
+        let mut row = fresh_row(now);
+        assert_eq!(check_oauth_state(Some(&row), "verifier-123", now).unwrap(), "verifier-123");
+
+        // verify_oauth_state marks the row as used; a replay must fail
+        row.used_at = Some(now);
+        let later = now + chrono::Duration::seconds(5);
+        let err = check_oauth_state(Some(&row), "verifier-123", later).unwrap_err();
+        assert!(err.contains("already used"));
+    }
+
+    #[test]
+    fn test_oauth_state_rejections() {
+        let now = Utc::now();
+        let row = fresh_row(now);
+        assert!(check_oauth_state(None, "verifier-123", now).is_err());
+        assert!(check_oauth_state(Some(&row), "", now).is_err());
+        assert!(check_oauth_state(Some(&row), "verifier-456", now).is_err());
+
+        let expired = row.expires_at + chrono::Duration::seconds(1);
+        assert!(check_oauth_state(Some(&row), "verifier-123", expired)
+            .unwrap_err()
+            .contains("expired"));
+    }
+}
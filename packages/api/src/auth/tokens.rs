@@ -0,0 +1,124 @@
+//! # Stored OAuth tokens
+//!
+//! After a successful code exchange the provider's access token (and refresh
+//! token, when one is issued) are encrypted with [`crate::crypto`] and kept in
+//! the `oauth_*` columns of `user_git_config`, together with the provider name
+//! and `token_expires_at`. The row is created if the user has not set up git
+//! sync yet. Storing them is best-effort at login: without `ENCRYPTION_KEY`
+//! (or with the database briefly unavailable) the login still succeeds and
+//! only later refreshes are lost.
+//!
+//! [`refresh_if_expiring`] is called before git operations; when the stored
+//! access token expires within [`REFRESH_MARGIN_SECS`] it asks the provider
+//! for a new one via [`GitHubOAuth::refresh_oauth_token`],
+//! [`GoogleOAuth::refresh_oauth_token`] or [`GitLabOAuth::refresh_oauth_token`].
+
+use oauth2::basic::BasicTokenResponse;
+use oauth2::TokenResponse;
+use uuid::Uuid;
+
+use super::github::GitHubOAuth;
+use super::gitlab::GitLabOAuth;
+use super::google::GoogleOAuth;
+use crate::crypto;
+use crate::db::get_pool;
+
+/// Refresh access tokens that expire within this many seconds.
+pub const REFRESH_MARGIN_SECS: i64 = 5 * 60;
+
+/// Encrypt and store the tokens of a code exchange or refresh response.
+///
+/// A refresh response without a new refresh token keeps the stored one.
+pub(super) async fn store_tokens(
+    user_id: Uuid,
+    provider: &str,
+    token: &BasicTokenResponse,
+) -> Result<(), String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+
+    let (access_enc, access_nonce) = crypto::encrypt_ssh_key(token.access_token().secret().as_bytes())?;
+    let (refresh_enc, refresh_nonce) = match token.refresh_token() {
+        Some(refresh) => {
+            let (enc, nonce) = crypto::encrypt_ssh_key(refresh.secret().as_bytes())?;
+            (Some(enc), Some(nonce))
+        }
+        None => (None, None),
+    };
+    let expires_in = token.expires_in().map(|d| d.as_secs() as i64);
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_git_config (
+            user_id, oauth_provider, oauth_access_token_enc, oauth_access_token_nonce,
+            oauth_refresh_token_enc, oauth_refresh_token_nonce, token_expires_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, NOW() + make_interval(secs => $7))
+        ON CONFLICT (user_id) DO UPDATE SET
+            oauth_provider = EXCLUDED.oauth_provider,
+            oauth_access_token_enc = EXCLUDED.oauth_access_token_enc,
+            oauth_access_token_nonce = EXCLUDED.oauth_access_token_nonce,
+            oauth_refresh_token_enc = COALESCE(EXCLUDED.oauth_refresh_token_enc, user_git_config.oauth_refresh_token_enc),
+            oauth_refresh_token_nonce = COALESCE(EXCLUDED.oauth_refresh_token_nonce, user_git_config.oauth_refresh_token_nonce),
+            token_expires_at = EXCLUDED.token_expires_at,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(provider)
+    .bind(access_enc)
+    .bind(access_nonce)
+    .bind(refresh_enc)
+    .bind(refresh_nonce)
+    .bind(expires_in.map(|secs| secs as f64))
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Decrypted refresh token stored for `user_id` by `provider`, if any.
+pub(super) async fn load_refresh_token(user_id: Uuid, provider: &str) -> Result<Option<String>, String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+
+    let row: Option<(Option<Vec<u8>>, Option<Vec<u8>>)> = sqlx::query_as(
+        "SELECT oauth_refresh_token_enc, oauth_refresh_token_nonce
+         FROM user_git_config WHERE user_id = $1 AND oauth_provider = $2",
+    )
+    .bind(user_id)
+    .bind(provider)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let Some((Some(enc), Some(nonce))) = row else {
+        return Ok(None);
+    };
+    let bytes = crypto::decrypt_ssh_key(&enc, &nonce)?;
+    String::from_utf8(bytes).map(Some).map_err(|e| e.to_string())
+}
+
+/// Refresh the user's OAuth access token if it expires within
+/// [`REFRESH_MARGIN_SECS`]. Does nothing for users without stored tokens or
+/// whose tokens never expire.
+pub async fn refresh_if_expiring(user_id: Uuid) -> Result<(), String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+
+    let provider: Option<(String,)> = sqlx::query_as(
+        "SELECT oauth_provider FROM user_git_config
+         WHERE user_id = $1 AND oauth_provider IS NOT NULL
+           AND token_expires_at < NOW() + make_interval(secs => $2)",
+    )
+    .bind(user_id)
+    .bind(REFRESH_MARGIN_SECS as f64)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match provider.as_ref().map(|(p,)| p.as_str()) {
+        Some("github") => GitHubOAuth::new()?.refresh_oauth_token(user_id).await,
+        Some("google") => GoogleOAuth::new()?.refresh_oauth_token(user_id).await,
+        Some("gitlab") => GitLabOAuth::new()?.refresh_oauth_token(user_id).await,
+        _ => Ok(()),
+    }
+}
@@ -1,4 +1,4 @@
-//! # Cryptographic utilities — encrypting SSH keys at rest
+//! # Cryptographic utilities — encrypting SSH keys and notes at rest
 //!
 //! When a user configures Git sync they upload an SSH private key that TypedNotes
 //! needs to store in PostgreSQL. This module ensures those keys are encrypted at
@@ -18,15 +18,38 @@
 //! | [`encrypt_ssh_key`] | Encrypts arbitrary bytes with AES-256-GCM using a fresh random 12-byte nonce. Returns `(ciphertext, nonce)` — both stored as `BYTEA` columns in `user_git_config`. |
 //! | [`decrypt_ssh_key`] | Decrypts a `(ciphertext, nonce)` pair back to plaintext. Called at push/fetch time to recover the SSH key for the `ssh` subprocess. |
 //! | [`extract_public_key`] | Parses an OpenSSH-format private key (Ed25519 or RSA) and returns the corresponding public key string. Stored alongside the encrypted private key so the UI can display it without decrypting. |
+//! | [`generate_ed25519_keypair`] | Creates a fresh Ed25519 key pair on the server and returns `(private_key_pem, public_key)` in OpenSSH format. |
+//!
+//! Despite the names, the encrypt/decrypt pair is content-agnostic; HTTPS access
+//! tokens are stored with the same scheme in the `https_token_enc` / `https_token_nonce` columns.
+//! These functions are only compiled with the `server` feature.
+//!
+//! ## Note encryption
+//!
+//! Notes marked as encrypted are encrypted in the client, with a key that never
+//! leaves it: [`derive_note_key`] stretches the user's passphrase with
+//! PBKDF2-HMAC-SHA256 ([`NOTE_KEY_ROUNDS`] rounds). [`encrypt_note`] and
+//! [`decrypt_note`] use AES-256-GCM like the SSH key functions, and
+//! [`seal_note`] / [`open_note`] convert to and from the stored form,
+//! `base64(nonce) "\n" base64(ciphertext)`, kept in a `.enc` file (see
+//! [`store::models::ENCRYPTED_EXT`]). The server only ever sees that form.
 
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, AeadCore, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+#[cfg(feature = "server")]
 use rand::RngCore;
+use sha2::Sha256;
+
+/// PBKDF2 rounds used by [`derive_note_key`].
+pub const NOTE_KEY_ROUNDS: u32 = 600_000;
 
 /// Read and validate the 32-byte master encryption key from `ENCRYPTION_KEY` env var.
-fn get_master_key() -> Result<[u8; 32], String> {
+#[cfg(feature = "server")]
+pub(crate) fn get_master_key() -> Result<[u8; 32], String> {
     let hex_key =
         std::env::var("ENCRYPTION_KEY").map_err(|_| "ENCRYPTION_KEY env var not set".to_string())?;
     let bytes = hex::decode(&hex_key).map_err(|e| format!("Invalid ENCRYPTION_KEY hex: {}", e))?;
@@ -43,6 +66,7 @@ fn get_master_key() -> Result<[u8; 32], String> {
 
 /// Encrypt data using AES-256-GCM with a random 12-byte nonce.
 /// Returns (ciphertext, nonce).
+#[cfg(feature = "server")]
 pub fn encrypt_ssh_key(plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
     let key = get_master_key()?;
     let cipher = Aes256Gcm::new_from_slice(&key)
@@ -60,6 +84,7 @@ pub fn encrypt_ssh_key(plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
 }
 
 /// Decrypt data using AES-256-GCM.
+#[cfg(feature = "server")]
 pub fn decrypt_ssh_key(ciphertext: &[u8], nonce: &[u8]) -> Result<Vec<u8>, String> {
     let key = get_master_key()?;
     let cipher = Aes256Gcm::new_from_slice(&key)
@@ -75,9 +100,119 @@ pub fn decrypt_ssh_key(ciphertext: &[u8], nonce: &[u8]) -> Result<Vec<u8>, Strin
 
 /// Extract the public key from an SSH private key in PEM/OpenSSH format.
 /// Returns the public key in OpenSSH format.
+#[cfg(feature = "server")]
 pub fn extract_public_key(private_key_pem: &str) -> Result<String, String> {
     let private_key = ssh_key::PrivateKey::from_openssh(private_key_pem.trim())
         .map_err(|e| format!("Invalid SSH private key: {}", e))?;
     let public_key = private_key.public_key();
     Ok(public_key.to_openssh().map_err(|e| format!("Failed to format public key: {}", e))?)
 }
+
+/// Generate a new Ed25519 SSH key pair labelled with `comment`.
+/// Returns (private key in OpenSSH PEM format, public key in authorized-keys format).
+#[cfg(feature = "server")]
+pub fn generate_ed25519_keypair(comment: &str) -> Result<(String, String), String> {
+    let mut private_key =
+        ssh_key::PrivateKey::random(&mut rand::rngs::OsRng, ssh_key::Algorithm::Ed25519)
+            .map_err(|e| format!("Failed to generate SSH key: {}", e))?;
+    private_key.set_comment(comment);
+    let private_pem = private_key
+        .to_openssh(ssh_key::LineEnding::LF)
+        .map_err(|e| format!("Failed to encode private key: {}", e))?;
+    let public_key = private_key
+        .public_key()
+        .to_openssh()
+        .map_err(|e| format!("Failed to format public key: {}", e))?;
+    Ok((private_pem.to_string(), public_key))
+}
+
+/// Derive a 256-bit note key from `passphrase` with PBKDF2-HMAC-SHA256.
+/// `salt` should be per user (e.g. the user ID) so equal passphrases of
+/// different users give different keys.
+///
+/// This takes [`NOTE_KEY_ROUNDS`] HMAC rounds on purpose; keep it off the
+/// UI thread.
+pub fn derive_note_key(passphrase: &str, salt: &str) -> [u8; 32] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(passphrase.as_bytes(), salt.as_bytes(), NOTE_KEY_ROUNDS)
+}
+
+/// Encrypt a note's content with AES-256-GCM under `key` and a random
+/// 12-byte nonce. Returns (ciphertext, nonce).
+pub fn encrypt_note(content: &str, key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("Invalid note key: {}", e))?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, content.as_bytes())
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+    Ok((ciphertext, nonce.to_vec()))
+}
+
+/// Decrypt a note encrypted by [`encrypt_note`]. Fails for a wrong key or
+/// tampered ciphertext.
+pub fn decrypt_note(ciphertext: &[u8], nonce: &[u8], key: &[u8]) -> Result<String, String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("Invalid note key: {}", e))?;
+    if nonce.len() != 12 {
+        return Err(format!("Invalid nonce length {}", nonce.len()));
+    }
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong passphrase or damaged note".to_string())?;
+    String::from_utf8(plaintext).map_err(|e| format!("Decrypted note is not UTF-8: {}", e))
+}
+
+/// Encrypt `content` into the stored form of an encrypted note.
+pub fn seal_note(content: &str, key: &[u8]) -> Result<String, String> {
+    let (ciphertext, nonce) = encrypt_note(content, key)?;
+    Ok(format!("{}\n{}", BASE64.encode(nonce), BASE64.encode(ciphertext)))
+}
+
+/// Decrypt the stored form written by [`seal_note`].
+pub fn open_note(stored: &str, key: &[u8]) -> Result<String, String> {
+    let (nonce, ciphertext) = stored
+        .trim()
+        .split_once('\n')
+        .ok_or_else(|| "Not an encrypted note".to_string())?;
+    let nonce = BASE64.decode(nonce.trim()).map_err(|e| format!("Invalid nonce: {}", e))?;
+    let ciphertext = BASE64
+        .decode(ciphertext.trim())
+        .map_err(|e| format!("Invalid ciphertext: {}", e))?;
+    decrypt_note(&ciphertext, &nonce, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// PBKDF2-HMAC-SHA256 of `password`, with few rounds to keep tests fast.
+    fn pbkdf2_sha256(password: &[u8], salt: &[u8], rounds: u32) -> [u8; 32] {
+        pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(password, salt, rounds)
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256() {
+        // RFC 7914, section 11
+        let key = pbkdf2_sha256(b"password", b"salt", 1);
+        assert_eq!(to_hex(&key), "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b");
+        let key = pbkdf2_sha256(b"password", b"salt", 4096);
+        assert_eq!(to_hex(&key), "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a");
+    }
+
+    #[test]
+    fn test_seal_and_open_note() {
+        let key = pbkdf2_sha256(b"correct horse", b"user-1", 2);
+        let sealed = seal_note("# Secret\n\nbody", &key).unwrap();
+        assert!(!sealed.contains("Secret"));
+        assert_eq!(sealed.lines().count(), 2);
+        assert_eq!(open_note(&sealed, &key).unwrap(), "# Secret\n\nbody");
+        // A fresh nonce each time
+        assert_ne!(seal_note("# Secret\n\nbody", &key).unwrap(), sealed);
+
+        let wrong = pbkdf2_sha256(b"wrong", b"user-1", 2);
+        assert_eq!(open_note(&sealed, &wrong).unwrap_err(), "Wrong passphrase or damaged note");
+        assert!(open_note("plain text", &key).is_err());
+    }
+}
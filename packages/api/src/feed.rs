@@ -0,0 +1,237 @@
+//! # Atom feed of note changes
+//!
+//! `GET /feeds/{token}` (an Axum route in the web crate, since it answers with
+//! XML rather than JSON) serves the last [`FEED_ENTRIES`] commits on the
+//! user's branch as an Atom 1.0 feed, one entry per commit. The token is the
+//! `user_git_config.feed_token` handed out by the `get_feed_token` server
+//! function; anyone holding it can read the feed, so it is never logged.
+//!
+//! Entry content is a one-line summary of the notes each commit added,
+//! modified or deleted relative to its first parent.
+
+use std::collections::BTreeMap;
+
+use atom_syndication::{Content, Entry, Feed, FixedDateTime, Person, Text};
+use rand::RngCore;
+use store::{MemoryStore, Repository, Sha};
+use uuid::Uuid;
+
+use crate::db::get_pool;
+
+/// Commits included in the feed, newest first.
+pub const FEED_ENTRIES: usize = 20;
+
+/// One commit as it appears in the feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedCommit {
+    pub sha: String,
+    pub message: String,
+    pub author: String,
+    /// Unix seconds.
+    pub timestamp: i64,
+    pub summary: String,
+}
+
+/// The user's feed token, generating and storing one on first use.
+pub async fn feed_token(user_id: Uuid) -> Result<String, String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+
+    let existing: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT feed_token FROM user_git_config WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    match existing {
+        None => return Err("Set up git sync before subscribing to a feed".to_string()),
+        Some((Some(token),)) => return Ok(token),
+        Some((None,)) => {}
+    }
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+
+    // Two concurrent first calls must agree on one token
+    let (stored,): (String,) = sqlx::query_as(
+        "UPDATE user_git_config SET feed_token = COALESCE(feed_token, $2)
+         WHERE user_id = $1 RETURNING feed_token",
+    )
+    .bind(user_id)
+    .bind(&token)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(stored)
+}
+
+/// The Atom XML for `token`, or `None` if no user has that token.
+pub async fn user_feed(token: &str) -> Result<Option<String>, String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+    let owner: Option<(Uuid, String)> = sqlx::query_as(
+        "SELECT u.id, COALESCE(u.name, u.email)
+         FROM user_git_config g JOIN users u ON u.id = g.user_id
+         WHERE g.feed_token = $1",
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some((user_id, user_name)) = owner else {
+        return Ok(None);
+    };
+
+    let (remote_url, credential, branch) =
+        crate::get_git_context_for_user(user_id).await.map_err(|e| e.to_string())?;
+    let mem = MemoryStore::new();
+    let repo = Repository::new(mem.clone());
+    crate::spawn_blocking_in_span(move || {
+        crate::fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let commits = recent_commits(&repo, FEED_ENTRIES).await;
+    Ok(Some(render_feed(user_id, &user_name, &commits)))
+}
+
+/// Up to `limit` commits on the first-parent chain from `HEAD`, newest first.
+/// A merge is summarised against the branch it was merged into.
+async fn recent_commits(repo: &Repository<MemoryStore>, limit: usize) -> Vec<FeedCommit> {
+    let mut commits = Vec::new();
+    let mut next = repo.get_head().await;
+    let mut notes = match &next {
+        Some(sha) => note_shas(repo, sha).await,
+        None => BTreeMap::new(),
+    };
+
+    while let Some(sha) = next.take() {
+        if commits.len() >= limit {
+            break;
+        }
+        let Some(commit) = repo.get_commit(&sha).await else {
+            break;
+        };
+        let parent = commit.first_parent().cloned();
+        let parent_notes = match &parent {
+            Some(parent) => note_shas(repo, parent).await,
+            None => BTreeMap::new(),
+        };
+        commits.push(FeedCommit {
+            sha: sha.to_hex(),
+            message: commit.message.trim().to_string(),
+            author: commit.author,
+            timestamp: commit.timestamp,
+            summary: diff_summary(&parent_notes, &notes),
+        });
+        next = parent;
+        notes = parent_notes;
+    }
+    commits
+}
+
+/// Path → blob SHA of every note in the commit's tree.
+async fn note_shas(repo: &Repository<MemoryStore>, commit: &Sha) -> BTreeMap<String, String> {
+    repo.list_notes_at(commit)
+        .await
+        .into_iter()
+        .map(|note| (note.path, note.sha))
+        .collect()
+}
+
+/// "Added a.md; modified b.md, c.md; deleted d.md" for the notes that differ.
+fn diff_summary(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> String {
+    let added: Vec<&str> = new.keys().filter(|p| !old.contains_key(*p)).map(String::as_str).collect();
+    let deleted: Vec<&str> = old.keys().filter(|p| !new.contains_key(*p)).map(String::as_str).collect();
+    let modified: Vec<&str> = new
+        .iter()
+        .filter(|(path, sha)| old.get(*path).is_some_and(|old_sha| old_sha != *sha))
+        .map(|(path, _)| path.as_str())
+        .collect();
+
+    let parts: Vec<String> = [("added", added), ("modified", modified), ("deleted", deleted)]
+        .into_iter()
+        .filter(|(_, paths)| !paths.is_empty())
+        .map(|(verb, paths)| format!("{verb} {}", paths.join(", ")))
+        .collect();
+    if parts.is_empty() {
+        return "No note changes".to_string();
+    }
+    let summary = parts.join("; ");
+    let mut chars = summary.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn timestamp(secs: i64) -> FixedDateTime {
+    chrono::DateTime::from_timestamp(secs, 0).unwrap_or_default().fixed_offset()
+}
+
+/// Build the Atom document for `commits` (newest first).
+pub fn render_feed(user_id: Uuid, user_name: &str, commits: &[FeedCommit]) -> String {
+    let entries = commits
+        .iter()
+        .map(|commit| Entry {
+            title: Text::plain(commit.message.lines().next().unwrap_or_default()),
+            id: format!("urn:typednotes:commit:{}", commit.sha),
+            updated: timestamp(commit.timestamp),
+            authors: vec![Person {
+                // "Name <email>" as written in the commit
+                name: commit.author.split(" <").next().unwrap_or_default().to_string(),
+                ..Default::default()
+            }],
+            content: Some(Content {
+                value: Some(commit.summary.clone()),
+                content_type: Some("text".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+    let feed = Feed {
+        title: Text::plain(format!("{user_name}'s notes")),
+        id: format!("urn:typednotes:feed:{user_id}"),
+        updated: timestamp(commits.first().map_or(0, |c| c.timestamp)),
+        entries,
+        ..Default::default()
+    };
+    feed.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries.iter().map(|(p, s)| (p.to_string(), s.to_string())).collect()
+    }
+
+    #[test]
+    fn test_diff_summary() {
+        let old = notes(&[("a.md", "1"), ("b.md", "2"), ("c.txt", "3")]);
+        let new = notes(&[("a.md", "1"), ("b.md", "9"), ("d/e.md", "4")]);
+        assert_eq!(diff_summary(&old, &new), "Added d/e.md; modified b.md; deleted c.txt");
+        assert_eq!(diff_summary(&BTreeMap::new(), &notes(&[("a.md", "1")])), "Added a.md");
+        assert_eq!(diff_summary(&old, &old), "No note changes");
+    }
+
+    #[test]
+    fn test_render_feed() {
+        let commits = vec![FeedCommit {
+            sha: "abc123".to_string(),
+            message: "Update todo.md\n\nmore detail".to_string(),
+            author: "TypedNotes <notes@typednotes.com>".to_string(),
+            timestamp: 1_700_000_000,
+            summary: "Modified todo.md".to_string(),
+        }];
+        let xml = render_feed(Uuid::nil(), "Ada <script>", &commits);
+
+        assert!(xml.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\""));
+        assert!(xml.contains("<title>Ada &lt;script&gt;&apos;s notes</title>"));
+        assert!(xml.contains("<id>urn:typednotes:commit:abc123</id>"));
+        assert!(xml.contains("<title>Update todo.md</title>"));
+        assert!(xml.contains("<updated>2023-11-14T22:13:20+00:00</updated>"));
+        assert!(xml.contains("<name>TypedNotes</name>"));
+        assert!(xml.contains("Modified todo.md</content>"));
+    }
+}
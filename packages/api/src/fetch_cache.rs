@@ -0,0 +1,113 @@
+//! # Remote fetches backed by the configured object store
+//!
+//! Server functions build a fresh [`MemoryStore`] per request and fill it
+//! from the user's git remote. With the default `memory` backend every
+//! request downloads the full history again through [`git_transport::fetch`].
+//!
+//! With `store_backend = "s3"` (and the `s3` feature), [`init`] connects an
+//! [`S3Store`](store::S3Store) at startup and [`fetch`] keeps one pack per
+//! remote in it, under `packs/<key>` where the key is the SHA-256 of the
+//! remote URL, credential and branch. The S3 store is only this pack cache:
+//! requests still work on their own [`MemoryStore`]. A request loads that
+//! pack into its store and fetches only the commits the pack lacks with
+//! [`git_transport::fetch_incremental`]. Refs always come from the remote, so
+//! a stale pack costs a larger download, never stale notes. A pack that
+//! cannot be read or written is logged and the fetch goes ahead without it.
+//!
+//! Rewriting the pack means compressing the whole repository again, so it is
+//! only done once a fetch brought at least [`PACK_REFRESH_OBJECTS`] objects the
+//! pack lacks (or there was no pack), and on a background task after the
+//! request has its objects. At most one rewrite per remote runs at a time.
+
+use store::{MemoryStore, ObjectStore};
+
+use crate::git_transport;
+
+#[cfg(feature = "s3")]
+static S3: std::sync::OnceLock<store::S3Store> = std::sync::OnceLock::new();
+
+/// Keys of the packs being rewritten right now.
+#[cfg(feature = "s3")]
+static REWRITING: std::sync::LazyLock<std::sync::Mutex<std::collections::HashSet<String>>> =
+    std::sync::LazyLock::new(Default::default);
+
+/// Objects a fetch must add to a cached pack before the pack is rewritten.
+#[cfg(feature = "s3")]
+pub const PACK_REFRESH_OBJECTS: usize = 500;
+
+/// Connect the S3 object store from the `AWS_*` environment variables (see
+/// [`S3Store::from_env`](store::S3Store::from_env)) and check that its bucket
+/// is reachable. Later [`fetch`]es cache their packs there.
+#[cfg(feature = "s3")]
+pub async fn init() -> Result<String, String> {
+    let s3 = store::S3Store::from_env().await?;
+    s3.check().await?;
+    let bucket = s3.bucket().to_string();
+    S3.set(s3).map_err(|_| "S3 object store already initialised".to_string())?;
+    Ok(bucket)
+}
+
+/// [`git_transport::fetch`] through the pack cache when one is configured.
+///
+/// Blocking, like the transport itself: call it inside `spawn_blocking`.
+pub(crate) fn fetch(
+    store: &MemoryStore,
+    remote_url: &str,
+    credential: &str,
+    branch: Option<&str>,
+) -> Result<(), String> {
+    #[cfg(feature = "s3")]
+    if let Some(s3) = S3.get() {
+        return fetch_cached(s3, store, remote_url, credential, branch);
+    }
+    git_transport::fetch(store, remote_url, credential, branch)
+}
+
+#[cfg(feature = "s3")]
+fn fetch_cached(
+    s3: &store::S3Store,
+    store: &MemoryStore,
+    remote_url: &str,
+    credential: &str,
+    branch: Option<&str>,
+) -> Result<(), String> {
+    use sha2::{Digest, Sha256};
+    use store::DEFAULT_PACK;
+
+    let key = hex::encode(Sha256::digest(
+        format!("{remote_url}\n{credential}\n{}", branch.unwrap_or_default()).as_bytes(),
+    ));
+    let runtime = tokio::runtime::Handle::current();
+
+    let cached = runtime.block_on(s3.get_pack(&key)).map(|pack| store.add_pack(DEFAULT_PACK, pack));
+    let before = match cached {
+        Some(Ok(count)) => count,
+        Some(Err(e)) => {
+            tracing::warn!(error = %e, "ignoring unreadable cached pack");
+            0
+        }
+        None => 0,
+    };
+
+    git_transport::fetch_incremental(store, remote_url, credential, branch)?;
+
+    let count = runtime.block_on(store.object_count()).unwrap_or_default();
+    let stale = if before == 0 { count > 0 } else { count >= before + PACK_REFRESH_OBJECTS };
+    if stale && REWRITING.lock().unwrap().insert(key.clone()) {
+        // A snapshot, as the request goes on using its store; the cached
+        // pack is shared, so only the newly fetched objects are copied
+        let snapshot = store.clone_deep();
+        let s3 = s3.clone();
+        runtime.spawn_blocking(move || {
+            if snapshot.compact() > 0 {
+                if let Some(pack) = snapshot.pack_data(DEFAULT_PACK) {
+                    if let Err(e) = tokio::runtime::Handle::current().block_on(s3.put_pack(&key, pack)) {
+                        tracing::warn!(error = %e, "could not cache fetched pack");
+                    }
+                }
+            }
+            REWRITING.lock().unwrap().remove(&key);
+        });
+    }
+    Ok(())
+}
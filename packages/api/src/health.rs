@@ -0,0 +1,165 @@
+//! # Liveness and readiness checks
+//!
+//! Backs the web crate's probe routes:
+//!
+//! - `GET /healthz` → [`health`]: the database (`SELECT 1`), the
+//!   `tower_sessions` session table, and, when `HEALTHCHECK_GIT_REMOTE` is
+//!   set, a fetch from that benchmark remote using `HEALTHCHECK_GIT_CREDENTIAL`
+//!   (SSH key PEM or HTTPS token).
+//! - `GET /readyz` → [`readiness`]: the database only.
+//!
+//! Each check gets [`CHECK_TIMEOUT`]. The overall [`Status`] is `down` when the
+//! database fails (nothing works without it) and `degraded` when only the
+//! session table or the git remote does; routes answer 503 for `down`.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::db::get_pool;
+
+/// Time limit for each individual check.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Degraded,
+    Down,
+}
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// `ok` or `down`.
+    pub status: Status,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Check {
+    pub fn is_ok(&self) -> bool {
+        self.status == Status::Ok
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Checks {
+    pub db: Check,
+    pub session: Check,
+    /// `None` when no benchmark remote is configured.
+    pub git: Option<Check>,
+}
+
+/// Body of `GET /healthz`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: Status,
+    pub checks: Checks,
+}
+
+/// Run every check concurrently.
+pub async fn health() -> HealthReport {
+    let (db, session, git) = futures::join!(check_db(), check_session_table(), check_git_remote());
+    HealthReport {
+        status: aggregate(&db, &session, git.as_ref()),
+        checks: Checks { db, session, git },
+    }
+}
+
+/// The readiness probe: can we talk to the database?
+pub async fn readiness() -> Check {
+    check_db().await
+}
+
+fn aggregate(db: &Check, session: &Check, git: Option<&Check>) -> Status {
+    if !db.is_ok() {
+        Status::Down
+    } else if !session.is_ok() || git.is_some_and(|g| !g.is_ok()) {
+        Status::Degraded
+    } else {
+        Status::Ok
+    }
+}
+
+/// Run `check` under [`CHECK_TIMEOUT`] and time it.
+async fn timed(check: impl Future<Output = Result<(), String>>) -> Check {
+    let started = Instant::now();
+    let outcome = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())),
+    };
+    Check {
+        status: if outcome.is_ok() { Status::Ok } else { Status::Down },
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: outcome.err(),
+    }
+}
+
+async fn check_db() -> Check {
+    timed(async {
+        let pool = get_pool().await.map_err(|e| e.to_string())?;
+        sqlx::query("SELECT 1").execute(pool).await.map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+}
+
+async fn check_session_table() -> Check {
+    timed(async {
+        let pool = get_pool().await.map_err(|e| e.to_string())?;
+        let (exists,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM pg_tables WHERE schemaname = 'tower_sessions' AND tablename = 'session')",
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+        if exists {
+            Ok(())
+        } else {
+            Err("tower_sessions.session table is missing".to_string())
+        }
+    })
+    .await
+}
+
+async fn check_git_remote() -> Option<Check> {
+    let remote_url = std::env::var("HEALTHCHECK_GIT_REMOTE").ok().filter(|url| !url.is_empty())?;
+    let credential = std::env::var("HEALTHCHECK_GIT_CREDENTIAL").unwrap_or_default();
+    Some(
+        timed(async move {
+            let mem = store::MemoryStore::new();
+            crate::spawn_blocking_in_span(move || {
+                crate::git_transport::fetch(&mem, &remote_url, &credential, None)
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(ok: bool) -> Check {
+        Check {
+            status: if ok { Status::Ok } else { Status::Down },
+            latency_ms: 1,
+            error: (!ok).then(|| "boom".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        assert_eq!(aggregate(&check(true), &check(true), None), Status::Ok);
+        assert_eq!(aggregate(&check(true), &check(true), Some(&check(true))), Status::Ok);
+        assert_eq!(aggregate(&check(true), &check(false), None), Status::Degraded);
+        assert_eq!(aggregate(&check(true), &check(true), Some(&check(false))), Status::Degraded);
+        assert_eq!(aggregate(&check(false), &check(true), Some(&check(true))), Status::Down);
+    }
+}
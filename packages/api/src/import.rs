@@ -0,0 +1,369 @@
+//! # Importing notes from other apps
+//!
+//! Converters that turn another app's export into plain `(path, content)`
+//! notes, ready for the same write-and-push step as
+//! [`import_notes`](crate::import_notes). They never fail on a single file:
+//! anything that cannot be carried over is reported in
+//! [`ConvertedImport::warnings`] or [`ConvertedImport::errors`].
+//!
+//! ## Obsidian
+//!
+//! [`convert_obsidian_vault`] takes a ZIP of a vault folder. The vault root is
+//! the folder holding `.obsidian/` and its name is used as the vault name;
+//! folders below it become namespaces. Obsidian's `[[target]]` links and
+//! `aliases:` frontmatter are read by TypedNotes as they are. `![[file]]`
+//! embeds have no equivalent and are replaced by an `<!-- embed: file -->`
+//! comment. Each installed community plugin (`.obsidian/plugins/<id>/`) gets a
+//! warning, as do fenced code blocks written in a plugin's language, such as
+//! `dataview` queries, which are kept but no longer rendered.
+//!
+//! ## Notion
+//!
+//! [`convert_notion_export`] takes Notion's "HTML" (or "Markdown & CSV")
+//! export. Notion appends a 32-character id to every page and folder name
+//! (`Roadmap 0b1c…e9.html`) and may percent-encode names (`My%20Page`); both
+//! are undone, and the sub-page folders become namespaces. HTML pages are
+//! converted to Markdown with `html2md`, keeping only the page `<article>`.
+//! Database exports (`.csv`) are skipped with a warning, as are links between
+//! pages, which still point at the exported `.html` files.
+
+use std::collections::BTreeSet;
+use std::io::Cursor;
+
+use crate::archive;
+
+/// Notes converted from another app's export.
+#[derive(Debug, Default)]
+pub struct ConvertedImport {
+    /// Name of the vault or workspace the export came from, if known.
+    pub source: Option<String>,
+    /// `(path, content)` for every converted note.
+    pub notes: Vec<(String, String)>,
+    /// Files left out on purpose (app settings, trash, unsupported formats).
+    pub skipped: u32,
+    /// Features that were dropped or only partly converted.
+    pub warnings: Vec<String>,
+    /// Files that could not be read.
+    pub errors: Vec<String>,
+}
+
+/// Names of every file entry in a ZIP archive.
+fn zip_entry_names(bytes: &[u8]) -> Result<Vec<String>, String> {
+    let archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    Ok(archive.file_names().map(str::to_string).collect())
+}
+
+/// Convert a ZIP of an Obsidian vault.
+pub fn convert_obsidian_vault(bytes: &[u8]) -> Result<ConvertedImport, String> {
+    let names = zip_entry_names(bytes)?;
+
+    // The vault root is wherever `.obsidian/` lives, usually the zipped folder
+    let root = names
+        .iter()
+        .find_map(|name| {
+            name.match_indices(".obsidian/")
+                .map(|(i, _)| i)
+                .find(|&i| i == 0 || name[..i].ends_with('/'))
+                .map(|i| name[..i].to_string())
+        })
+        .unwrap_or_default();
+
+    let plugins: BTreeSet<String> = names
+        .iter()
+        .filter_map(|name| name.strip_prefix(&format!("{root}.obsidian/plugins/")))
+        .filter_map(|rest| rest.split('/').next())
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let mut result = ConvertedImport {
+        source: root.trim_end_matches('/').rsplit('/').next().filter(|n| !n.is_empty()).map(str::to_string),
+        ..Default::default()
+    };
+    for plugin in &plugins {
+        result.warnings.push(format!("Plugin {plugin} is not supported; its syntax is imported as plain text"));
+    }
+
+    let contents = archive::read_notes_zip(bytes)?;
+    result.errors = contents.errors;
+    for (path, content) in contents.notes {
+        let relative = path.strip_prefix(&root).unwrap_or(&path);
+        // `.obsidian/`, `.trash/` and other hidden folders are not notes
+        if relative.split('/').any(|part| part.starts_with('.')) {
+            result.skipped += 1;
+            continue;
+        }
+        let converted = if relative.ends_with(".md") {
+            let (converted, embeds, plugin_blocks) = convert_obsidian_markdown(&content, &plugins);
+            if embeds > 0 {
+                result.warnings.push(format!("{relative}: {embeds} embed(s) replaced with comments"));
+            }
+            for lang in plugin_blocks {
+                result.warnings.push(format!("{relative}: `{lang}` block kept as code"));
+            }
+            converted
+        } else {
+            content
+        };
+        result.notes.push((relative.to_string(), converted));
+    }
+
+    Ok(result)
+}
+
+/// Convert a Notion export ZIP.
+pub fn convert_notion_export(bytes: &[u8]) -> Result<ConvertedImport, String> {
+    let mut result = ConvertedImport::default();
+    for name in zip_entry_names(bytes)? {
+        if name.ends_with(".csv") {
+            result.skipped += 1;
+            result.warnings.push(format!("{}: database export skipped", notion_path(&name)));
+        }
+    }
+
+    let contents = archive::read_text_entries(bytes, |path| {
+        path.ends_with(".html") || path.ends_with(".md") || path.ends_with(".txt")
+    })?;
+    result.errors = contents.errors;
+    for (path, content) in contents.notes {
+        let clean = notion_path(&path);
+        let Some(stem) = clean.strip_suffix(".html") else {
+            result.notes.push((clean, content));
+            continue;
+        };
+        let (markdown, page_links) = notion_html_to_markdown(&content);
+        if page_links > 0 {
+            result.warnings.push(format!("{stem}: {page_links} link(s) to other Notion pages not converted"));
+        }
+        result.notes.push((format!("{stem}.md"), markdown));
+    }
+
+    Ok(result)
+}
+
+/// Percent-decode each component of a Notion export path and drop the page
+/// id Notion appends to file and folder names.
+fn notion_path(path: &str) -> String {
+    path.split('/')
+        .map(|part| {
+            let decoded = percent_encoding::percent_decode_str(part).decode_utf8_lossy();
+            let (stem, ext) = match decoded.rsplit_once('.') {
+                Some((stem, ext)) if !ext.contains(' ') => (stem.to_string(), format!(".{ext}")),
+                _ => (decoded.to_string(), String::new()),
+            };
+            format!("{}{ext}", strip_notion_id(&stem))
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `"Roadmap 0b1c…e9"` → `"Roadmap"`: remove a trailing space plus 32 hex digits.
+fn strip_notion_id(name: &str) -> &str {
+    match name.rsplit_once(' ') {
+        Some((title, id))
+            if !title.is_empty() && id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            title
+        }
+        _ => name,
+    }
+}
+
+/// Convert one exported Notion page to Markdown. Returns the Markdown and
+/// the number of links that point at other exported pages.
+fn notion_html_to_markdown(html: &str) -> (String, usize) {
+    // Skip the <head> and its stylesheet; Notion wraps the page in <article>
+    let body = match (html.find("<article"), html.rfind("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end + "</article>".len()],
+        _ => html,
+    };
+    let markdown = atx_headings(&html2md::parse_html(body));
+    let page_links = markdown
+        .split("](")
+        .skip(1)
+        .filter_map(|rest| rest.split(')').next())
+        .filter(|target| !target.contains("://") && target.ends_with(".html"))
+        .count();
+    (markdown, page_links)
+}
+
+/// Rewrite setext headings (`Title` over `===` or `---`) as ATX (`# Title`),
+/// which is what the outline and table of contents read.
+fn atx_headings(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out = Vec::with_capacity(lines.len());
+    let mut in_fence = false;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let underline = lines.get(i + 1).map(|l| l.trim()).unwrap_or("");
+        let level = if underline.len() >= 3 && underline.bytes().all(|b| b == b'=') {
+            Some("#")
+        } else if underline.len() >= 3 && underline.bytes().all(|b| b == b'-') {
+            Some("##")
+        } else {
+            None
+        };
+        match level {
+            Some(hashes) if !in_fence && !line.trim().is_empty() => {
+                out.push(format!("{hashes} {}", line.trim()));
+                i += 2;
+            }
+            _ => {
+                out.push(line.to_string());
+                i += 1;
+            }
+        }
+    }
+    let mut markdown = out.join("\n");
+    markdown.push('\n');
+    markdown
+}
+
+/// Rewrite `![[file]]` embeds outside code fences as comments.
+///
+/// Returns the new content, the number of embeds replaced, and the languages
+/// of fenced blocks that belong to one of `plugins`.
+fn convert_obsidian_markdown(content: &str, plugins: &BTreeSet<String>) -> (String, usize, Vec<String>) {
+    let mut out = String::with_capacity(content.len());
+    let mut embeds = 0;
+    let mut plugin_blocks = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            let lang = trimmed[marker.len()..].trim();
+            let is_plugin = !lang.is_empty()
+                && plugins.iter().any(|id| id == lang || id.split('-').any(|word| word == lang));
+            if is_plugin && !plugin_blocks.iter().any(|l| l == lang) {
+                plugin_blocks.push(lang.to_string());
+            }
+            out.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(start) = rest.find("![[") {
+            let Some(len) = rest[start + 3..].find("]]") else {
+                break;
+            };
+            let target = &rest[start + 3..start + 3 + len];
+            // Drop the `|size` or `#heading` part of the embed
+            let file = target.split(['|', '#']).next().unwrap_or(target).trim();
+            out.push_str(&rest[..start]);
+            out.push_str(&format!("<!-- embed: {file} -->"));
+            embeds += 1;
+            rest = &rest[start + 3 + len + 2..];
+        }
+        out.push_str(rest);
+    }
+
+    (out, embeds, plugin_blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn zip_of(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_convert_obsidian_vault() {
+        let bytes = zip_of(&[
+            ("Brain/.obsidian/app.json", "{}"),
+            ("Brain/.obsidian/plugins/dataview/main.js", ""),
+            ("Brain/.trash/old.md", "gone"),
+            ("Brain/Home.md", "---\naliases: [Start]\n---\nSee [[Work/Plan]] ![[diagram.png|300]]\n"),
+            ("Brain/Work/Plan.md", "```dataview\nLIST\n```\nAfter the block: ![[Home]]\n"),
+        ]);
+
+        let result = convert_obsidian_vault(&bytes).unwrap();
+        assert_eq!(result.source.as_deref(), Some("Brain"));
+        assert_eq!(result.skipped, 1);
+
+        let paths: Vec<&str> = result.notes.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, ["Home.md", "Work/Plan.md"]);
+        assert_eq!(
+            result.notes[0].1,
+            "---\naliases: [Start]\n---\nSee [[Work/Plan]] <!-- embed: diagram.png -->\n"
+        );
+        assert!(result.notes[1].1.ends_with("After the block: <!-- embed: Home -->\n"));
+
+        assert_eq!(
+            result.warnings,
+            [
+                "Plugin dataview is not supported; its syntax is imported as plain text",
+                "Home.md: 1 embed(s) replaced with comments",
+                "Work/Plan.md: 1 embed(s) replaced with comments",
+                "Work/Plan.md: `dataview` block kept as code",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_notion_export() {
+        let id = "0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e";
+        let page = format!(
+            "<html><head><style>p {{ color: red }}</style></head><body><article><header>\
+             <h1 class=\"page-title\">Roadmap</h1></header><div class=\"page-body\">\
+             <p>See <a href=\"Roadmap%20{id}/Q1%20{id}.html\">Q1</a></p><h2>Goals</h2>\
+             </div></article></body></html>"
+        );
+        let sub = format!("Roadmap%20{id}/Q1%20{id}.html");
+        let csv = format!("Roadmap%20{id}/Tasks%20{id}.csv");
+        let bytes = zip_of(&[
+            (format!("Roadmap%20{id}.html").as_str(), page.as_str()),
+            (sub.as_str(), "<article><p>First quarter</p></article>"),
+            (csv.as_str(), "Name,Status\n"),
+        ]);
+
+        let result = convert_notion_export(&bytes).unwrap();
+        let paths: Vec<&str> = result.notes.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, ["Roadmap.md", "Roadmap/Q1.md"]);
+        assert!(result.notes[0].1.starts_with("# Roadmap\n"), "{}", result.notes[0].1);
+        assert!(result.notes[0].1.contains("## Goals"));
+        assert!(!result.notes[0].1.contains("color: red"));
+        assert_eq!(result.notes[1].1.trim(), "First quarter");
+
+        assert_eq!(result.skipped, 1);
+        assert_eq!(
+            result.warnings,
+            ["Roadmap/Tasks.csv: database export skipped", "Roadmap: 1 link(s) to other Notion pages not converted"]
+        );
+    }
+
+    #[test]
+    fn test_notion_path() {
+        assert_eq!(notion_path("My%20Notes/Plan.md"), "My Notes/Plan.md");
+        assert_eq!(notion_path("Plan abc.md"), "Plan abc.md");
+    }
+
+    #[test]
+    fn test_embeds_inside_code_fences_are_kept() {
+        let plugins = BTreeSet::new();
+        let (out, embeds, _) = convert_obsidian_markdown("~~~\n![[x.png]]\n~~~\n![[y.png]]", &plugins);
+        assert_eq!(out, "~~~\n![[x.png]]\n~~~\n<!-- embed: y.png -->");
+        assert_eq!(embeds, 1);
+    }
+}
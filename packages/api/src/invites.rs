@@ -0,0 +1,167 @@
+//! # Invite-only registration
+//!
+//! Setting the [`REQUIRE_INVITE_CODE`] environment variable closes open
+//! registration: `register` then only creates an account when given a code
+//! from the `invite_codes` table that has uses left and has not expired, and
+//! [`redeem`]s it in the same transaction as the insert. Signing in with an
+//! OAuth provider only works for accounts that already exist (see
+//! [`check_oauth_signup`]), so it cannot be used to get around the code.
+//!
+//! Codes are created by admins (`users.is_admin`, set directly in the
+//! database) with the `create_invite_code` server function. Each one allows
+//! `uses_remaining` registrations within `expires_hours` of its creation.
+
+use rand::Rng;
+use uuid::Uuid;
+
+use crate::db::get_pool;
+
+/// Environment variable that turns on invite-only registration when set to
+/// anything but an empty string, `0` or `false`.
+pub const REQUIRE_INVITE_CODE: &str = "REQUIRE_INVITE_CODE";
+
+/// Characters codes are made of: no `0`/`O`, `1`/`I`, easy to read out.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Longest validity a code can be created with: one year.
+pub const MAX_EXPIRES_HOURS: u32 = 24 * 365;
+
+/// Whether [`REQUIRE_INVITE_CODE`] is set.
+pub fn invite_required() -> bool {
+    std::env::var(REQUIRE_INVITE_CODE).is_ok_and(|value| is_enabled(&value))
+}
+
+fn is_enabled(value: &str) -> bool {
+    !matches!(value.trim().to_ascii_lowercase().as_str(), "" | "0" | "false")
+}
+
+/// A fresh code such as `K7QM-X2RD-9TBW`.
+pub fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+    let chars: Vec<char> = (0..12)
+        .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect();
+    chars.chunks(4).map(|group| group.iter().collect::<String>()).collect::<Vec<_>>().join("-")
+}
+
+/// `code` as stored: trimmed and upper-cased, so codes typed in lower case
+/// still match.
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_ascii_uppercase()
+}
+
+/// Check the arguments of `create_invite_code`.
+pub fn validate(uses_remaining: u32, expires_hours: u32) -> Result<(), String> {
+    if uses_remaining == 0 {
+        return Err("An invite code needs at least one use".to_string());
+    }
+    if expires_hours == 0 || expires_hours > MAX_EXPIRES_HOURS {
+        return Err(format!("Expiry must be between 1 and {MAX_EXPIRES_HOURS} hours"));
+    }
+    Ok(())
+}
+
+/// Create a code allowing `uses_remaining` registrations within
+/// `expires_hours`, if `user_id` is an admin.
+pub async fn create(user_id: Uuid, uses_remaining: u32, expires_hours: u32) -> Result<String, String> {
+    validate(uses_remaining, expires_hours)?;
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+
+    let admin: Option<(bool,)> = sqlx::query_as("SELECT is_admin FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !admin.is_some_and(|(is_admin,)| is_admin) {
+        return Err("Only admins can create invite codes".to_string());
+    }
+
+    let code = generate_code();
+    sqlx::query(
+        "INSERT INTO invite_codes (code, created_by, uses_remaining, expires_at)
+         VALUES ($1, $2, $3, NOW() + make_interval(hours => $4))",
+    )
+    .bind(&code)
+    .bind(user_id)
+    .bind(uses_remaining as i32)
+    .bind(expires_hours as i32)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(code)
+}
+
+/// With invite-only registration on, fail unless `provider` already has an
+/// account for `provider_id`: an OAuth login may sign existing users in but
+/// not create new accounts, which take an invite code and `register`.
+pub async fn check_oauth_signup<'c, E>(executor: E, provider: &str, provider_id: &str) -> Result<(), String>
+where
+    E: sqlx::PgExecutor<'c>,
+{
+    if !invite_required() {
+        return Ok(());
+    }
+    let existing: Option<(Uuid,)> =
+        sqlx::query_as("SELECT id FROM users WHERE provider = $1 AND provider_id = $2")
+            .bind(provider)
+            .bind(provider_id)
+            .fetch_optional(executor)
+            .await
+            .map_err(|e| e.to_string())?;
+    match existing {
+        Some(_) => Ok(()),
+        None => Err("Registration is invite-only: sign up with an invite code first".to_string()),
+    }
+}
+
+/// Use up one registration of `code` for `user_id`, failing if the code does
+/// not exist, has no uses left or has expired.
+pub async fn redeem<'c, E>(executor: E, code: &str, user_id: Uuid) -> Result<(), String>
+where
+    E: sqlx::PgExecutor<'c>,
+{
+    let redeemed: Option<(String,)> = sqlx::query_as(
+        "UPDATE invite_codes
+         SET uses_remaining = uses_remaining - 1, used_by_user_id = $2
+         WHERE code = $1 AND uses_remaining > 0 AND expires_at > NOW()
+         RETURNING code",
+    )
+    .bind(normalize_code(code))
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await
+    .map_err(|e| e.to_string())?;
+    match redeemed {
+        Some(_) => Ok(()),
+        None => Err("Invalid or expired invite code".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_code() {
+        let code = generate_code();
+        assert_eq!(code.len(), 14);
+        let groups: Vec<&str> = code.split('-').collect();
+        assert_eq!(groups.len(), 3);
+        assert!(groups.iter().all(|g| g.len() == 4 && g.bytes().all(|b| CODE_ALPHABET.contains(&b))));
+        assert_eq!(normalize_code(&format!("  {}\n", code.to_lowercase())), code);
+    }
+
+    #[test]
+    fn test_validate_and_flag() {
+        assert!(validate(1, 24).is_ok());
+        assert!(validate(0, 24).is_err());
+        assert!(validate(5, 0).is_err());
+        assert!(validate(5, MAX_EXPIRES_HOURS + 1).is_err());
+
+        assert!(is_enabled("1"));
+        assert!(is_enabled("true"));
+        assert!(!is_enabled(""));
+        assert!(!is_enabled(" FALSE "));
+        assert!(!is_enabled("0"));
+    }
+}
@@ -8,11 +8,23 @@
 //!
 //! | Module | Feature gate | Purpose |
 //! |--------|-------------|---------|
-//! | [`auth`] | — | OAuth (GitHub, Google) and local password authentication, session management, password hashing |
-//! | [`crypto`] | `server` | AES-GCM encryption/decryption of SSH private keys, public key extraction |
+//! | [`attachments`] | — | Allowed attachment types, size limit, file naming and the markdown that links an upload |
+//! | [`archive`] | `server` | ZIP export of all notes plus `typednotes.toml`, and note extraction for import |
+//! | [`auth`] | — | OAuth (GitHub, Google, GitLab) and local password authentication, session management, password hashing |
+//! | [`crypto`] | partly `server` | AES-GCM encryption of SSH private keys and HTTPS tokens and public key extraction (server); passphrase-keyed note encryption (client) |
 //! | [`db`] | — | PostgreSQL connection pool (lazy `OnceCell` singleton) and migrations |
-//! | [`git_transport`] | `server` | Low-level Git fetch/push over SSH using an in-memory object store |
+//! | [`email`] | `server` | Verification tokens for new accounts and the SMTP mail that delivers them |
+//! | [`feed`] | `server` | Atom feed of recent commits, served at `/feeds/{token}` by the web crate |
+//! | [`fetch_cache`] | `server` | Remote fetches that keep a pack per remote in the S3 object store (`s3`) |
+//! | [`health`] | `server` | Database, session table and git remote checks behind `/healthz` and `/readyz` |
+//! | [`import`] | `server` | Converters for other apps' exports (Obsidian vaults, Notion) |
+//! | [`invites`] | `server` | Invite codes for invite-only registration (`REQUIRE_INVITE_CODE`) |
+//! | [`git_transport`] | `server` | Low-level Git fetch/push over SSH or HTTPS using an in-memory object store |
+//! | [`metrics`] | `server` | `sync_note` counters rendered for the Prometheus `/metrics` endpoint |
 //! | [`models`] | — | Database models (`User`) and their client-safe projections (`UserInfo`) |
+//! | [`render`] | — | Sanitised markdown → HTML rendering for shared notes and the editor preview |
+//! | [`search`] | — | Case-insensitive substring search over notes (`SearchResult`) |
+//! | [`webhooks`] | `server` | Signed POST notifications to user-registered URLs after sync events |
 //!
 //! ## Server functions exposed here
 //!
@@ -21,36 +33,114 @@
 //! (behind `#[cfg(feature = "server")]`) and once as a thin client stub that simply
 //! forwards the call over HTTP.
 //!
-//! - **Authentication**: `get_current_user`, `get_login_url`, `logout`, `register`, `login_password`
-//! - **Git credentials**: `save_git_credentials`, `get_git_credentials`
-//! - **Git sync**: `sync_note`, `delete_note_remote`, `pull_notes`
+//! - **Authentication**: `get_current_user`, `get_current_user_extended`, `get_login_url`, `logout`, `register`, `login_password`,
+//!   `update_user_profile`, `verify_email`, `resend_verification_email`
+//! - **Invites**: `invite_code_required`, `create_invite_code`
+//! - **Sessions**: `list_user_sessions`, `revoke_session`
+//! - **Webhooks**: `register_webhook`, `list_webhooks`, `delete_webhook`
+//! - **Git credentials**: `save_git_credentials`, `get_git_credentials`, `generate_ssh_keypair`
+//! - **Git sync**: `sync_note`, `delete_note_remote`, `pull_notes`, `pull_notes_since`, `download_pack`, `ping_git_remote`, `get_storage_stats`, `list_tags`
+//! - **Templates**: `create_note_from_template`
+//! - **Attachments**: `upload_attachment`
+//! - **History**: `get_note_history`, `restore_note_version`, `get_commit_diff`
+//! - **Search**: `search_notes`, `list_notes_by_tag`, `get_backlinks`
+//! - **Backup**: `export_notes_zip`, `import_notes`, `import_obsidian_vault`, `import_notion_zip`, `export_note_html`, `export_note_pdf`
+//! - **Sharing**: `share_note`, `get_shared_note` (public, no session), `get_feed_token`
+//!
+//! Each server-side version runs in a `tracing` span named after the function;
+//! those that reach the git remote record `user_id` and `remote_host` on it.
 
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 
-pub mod auth;
 #[cfg(feature = "server")]
+pub mod archive;
+pub mod attachments;
+pub mod auth;
 pub mod crypto;
 pub mod db;
 #[cfg(feature = "server")]
+pub mod email;
+#[cfg(feature = "server")]
+pub mod feed;
+#[cfg(feature = "server")]
+pub mod fetch_cache;
+#[cfg(feature = "server")]
 pub mod git_transport;
+#[cfg(feature = "server")]
+pub mod health;
+#[cfg(feature = "server")]
+pub mod import;
+#[cfg(feature = "server")]
+pub mod invites;
+#[cfg(feature = "server")]
+pub mod metrics;
 pub mod models;
+pub mod render;
+pub mod search;
+#[cfg(feature = "server")]
+pub mod webhooks;
+#[cfg(test)]
+mod test_util;
 
-pub use models::UserInfo;
+pub use models::{ExtendedUserInfo, UserInfo};
+pub use search::SearchResult;
 pub use store::{NamespaceInfo, TypedNoteInfo};
 
 pub use store::TypedNotesConfig;
 
-/// Git credentials info safe to send to the client (never includes private key).
+/// How the server authenticates against the git remote.
+///
+/// Selected automatically from the remote URL: `https://` remotes use an access
+/// token, SCP-like and `ssh://` remotes use an SSH private key.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CredentialType {
+    #[default]
+    Ssh,
+    HttpsToken,
+}
+
+impl CredentialType {
+    /// Pick the credential type implied by a remote URL.
+    pub fn for_remote_url(url: &str) -> Self {
+        if url.trim().starts_with("https://") {
+            CredentialType::HttpsToken
+        } else {
+            CredentialType::Ssh
+        }
+    }
+
+    /// Value stored in the `user_git_config.credential_type` column.
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            CredentialType::Ssh => "ssh",
+            CredentialType::HttpsToken => "https_token",
+        }
+    }
+
+    /// Parse a `user_git_config.credential_type` value, defaulting to SSH.
+    pub fn from_db_str(s: &str) -> Self {
+        match s {
+            "https_token" => CredentialType::HttpsToken,
+            _ => CredentialType::Ssh,
+        }
+    }
+}
+
+/// Git credentials info safe to send to the client (never includes private key or token).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GitCredentialsInfo {
     pub git_remote_url: Option<String>,
     pub ssh_public_key: Option<String>,
     pub git_branch: Option<String>,
+    pub credential_type: CredentialType,
+    /// Whether an HTTPS access token is stored (the token itself is never returned).
+    pub has_https_token: bool,
 }
 
 /// Get the current authenticated user from the session.
 #[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
 #[get("/api/auth/me", session: tower_sessions::Session)]
 pub async fn get_current_user() -> Result<Option<UserInfo>, ServerFnError> {
     use crate::db::get_pool;
@@ -87,27 +177,128 @@ pub async fn get_current_user() -> Result<Option<UserInfo>, ServerFnError> {
     Ok(None)
 }
 
+/// The logged-in user with git setup and note count, for the first render.
+///
+/// The note count is the one recorded by the last sync, so no git fetch is
+/// needed. Also stamps `users.last_seen_at`; the previous value is returned.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[get("/api/auth/me/extended", session: tower_sessions::Session)]
+pub async fn get_current_user_extended() -> Result<Option<ExtendedUserInfo>, ServerFnError> {
+    use crate::db::get_pool;
+    use crate::models::User;
+
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Ok(None);
+    };
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let user_uuid = uuid::Uuid::parse_str(&user_id)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE id = $1")
+        .bind(user_uuid)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user) = user else {
+        return Ok(None);
+    };
+
+    let last_seen: Option<(Option<chrono::DateTime<chrono::Utc>>,)> = sqlx::query_as(
+        r#"
+        UPDATE users SET last_seen_at = NOW()
+        FROM (SELECT last_seen_at FROM users WHERE id = $1) AS previous
+        WHERE users.id = $1
+        RETURNING previous.last_seen_at
+        "#,
+    )
+    .bind(user_uuid)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let git: Option<(bool, Option<i32>)> = sqlx::query_as(
+        "SELECT git_remote_url IS NOT NULL
+                AND (ssh_private_key_enc IS NOT NULL OR https_token_enc IS NOT NULL),
+                note_count
+         FROM user_git_config WHERE user_id = $1",
+    )
+    .bind(user_uuid)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let (git_configured, note_count) = git.unwrap_or((false, None));
+    Ok(Some(ExtendedUserInfo {
+        user: user.to_info(),
+        git_configured,
+        note_count: note_count.map(|n| n.max(0) as usize),
+        last_seen_at: last_seen.and_then(|(t,)| t).map(|t| t.timestamp()),
+    }))
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/auth/me/extended")]
+pub async fn get_current_user_extended() -> Result<Option<ExtendedUserInfo>, ServerFnError> {
+    Ok(None)
+}
+
 /// Get the OAuth login URL for a provider.
+///
+/// The PKCE verifier is kept in the session so the callback is only accepted
+/// from the browser that started the login.
 #[cfg(feature = "server")]
-#[get("/api/auth/login/:provider")]
+#[tracing::instrument(skip_all)]
+#[get("/api/auth/login/:provider", session: tower_sessions::Session)]
 pub async fn get_login_url(provider: String) -> Result<String, ServerFnError> {
     match provider.as_str() {
         "github" => {
             let oauth = auth::GitHubOAuth::new()
                 .map_err(|e| ServerFnError::new(e))?;
-            let (url, _, _) = oauth
+            let (url, _, verifier) = oauth
                 .generate_auth_url()
                 .await
                 .map_err(|e| ServerFnError::new(e))?;
+            session
+                .insert(auth::SESSION_OAUTH_VERIFIER_KEY, verifier)
+                .await
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
             Ok(url)
         }
         "google" => {
             let oauth = auth::GoogleOAuth::new()
                 .map_err(|e| ServerFnError::new(e))?;
-            let (url, _, _) = oauth
+            let (url, _, verifier) = oauth
+                .generate_auth_url()
+                .await
+                .map_err(|e| ServerFnError::new(e))?;
+            session
+                .insert(auth::SESSION_OAUTH_VERIFIER_KEY, verifier)
+                .await
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
+            Ok(url)
+        }
+        "gitlab" => {
+            let oauth = auth::GitLabOAuth::new()
+                .map_err(|e| ServerFnError::new(e))?;
+            let (url, _, verifier) = oauth
                 .generate_auth_url()
                 .await
                 .map_err(|e| ServerFnError::new(e))?;
+            session
+                .insert(auth::SESSION_OAUTH_VERIFIER_KEY, verifier)
+                .await
+                .map_err(|e| ServerFnError::new(e.to_string()))?;
             Ok(url)
         }
         _ => Err(ServerFnError::new(format!("Unknown provider: {}", provider))),
@@ -122,6 +313,7 @@ pub async fn get_login_url(provider: String) -> Result<String, ServerFnError> {
 
 /// Log out the current user by clearing the session.
 #[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
 #[post("/api/auth/logout", session: tower_sessions::Session)]
 pub async fn logout() -> Result<(), ServerFnError> {
     session
@@ -139,12 +331,20 @@ pub async fn logout() -> Result<(), ServerFnError> {
 }
 
 /// Register a new user with email and password.
+///
+/// The account starts unverified: a link to confirm the address is mailed
+/// (see [`email`]), and git sync stays off until it is opened.
+///
+/// With invite-only registration on (see [`invites`]), `invite_code` must be
+/// a valid code; it is redeemed together with creating the account.
 #[cfg(feature = "server")]
-#[post("/api/auth/register", session: tower_sessions::Session)]
+#[tracing::instrument(skip_all)]
+#[post("/api/auth/register", session: tower_sessions::Session, headers: dioxus::fullstack::HeaderMap)]
 pub async fn register(
     email: String,
     password: String,
     name: String,
+    invite_code: Option<String>,
 ) -> Result<UserInfo, ServerFnError> {
     use crate::db::get_pool;
 
@@ -162,6 +362,10 @@ pub async fn register(
     if name.is_empty() {
         return Err(ServerFnError::new("Name is required"));
     }
+    let invite_code = invite_code.filter(|code| !code.trim().is_empty());
+    if invites::invite_required() && invite_code.is_none() {
+        return Err(ServerFnError::new("An invite code is required to register"));
+    }
 
     let pool = get_pool()
         .await
@@ -183,18 +387,37 @@ pub async fn register(
     let password_hash = auth::hash_password(&password)
         .map_err(|e| ServerFnError::new(e))?;
 
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
     let user: models::User = sqlx::query_as(
         "INSERT INTO users (email, name, provider, provider_id, password_hash) VALUES ($1, $2, 'local', $1, $3) RETURNING *",
     )
     .bind(&email)
     .bind(&name)
     .bind(&password_hash)
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    session
-        .insert(auth::SESSION_USER_ID_KEY, user.id.to_string())
+    // Dropping `tx` on error rolls the new account back
+    if invites::invite_required() {
+        let code = invite_code.as_deref().unwrap_or_default();
+        invites::redeem(&mut *tx, code, user.id)
+            .await
+            .map_err(ServerFnError::new)?;
+    }
+    let token = email::create_verification(&mut *tx, user.id)
+        .await
+        .map_err(ServerFnError::new)?;
+    tx.commit()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    email::send_verification(user.email.clone(), name, token);
+
+    auth::start_user_session(&session, &user.id.to_string(), &headers)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
 
@@ -207,13 +430,133 @@ pub async fn register(
     email: String,
     password: String,
     name: String,
+    invite_code: Option<String>,
 ) -> Result<UserInfo, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
 
+/// Confirm the email address of the account a verification link was sent to.
+///
+/// Needs no session, so the link works from any browser.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/auth/verify-email")]
+pub async fn verify_email(token: String) -> Result<UserInfo, ServerFnError> {
+    use crate::db::get_pool;
+
+    let user_id = email::verify(&token).await.map_err(ServerFnError::new)?;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let user: models::User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(user.to_info())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/auth/verify-email")]
+pub async fn verify_email(token: String) -> Result<UserInfo, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Mail the current user a new verification link.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/auth/resend-verification", session: tower_sessions::Session)]
+pub async fn resend_verification_email() -> Result<(), ServerFnError> {
+    use crate::db::get_pool;
+
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let user: models::User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
+        .bind(user_uuid)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if user.email_verified {
+        return Err(ServerFnError::new("Your email address is already verified"));
+    }
+    email::check_resend_limit(user.id, &user.email)
+        .await
+        .map_err(ServerFnError::new)?;
+
+    let token = email::create_verification(pool, user.id)
+        .await
+        .map_err(ServerFnError::new)?;
+    let name = user.name.clone().unwrap_or_default();
+    email::send_verification(user.email, name, token);
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/auth/resend-verification")]
+pub async fn resend_verification_email() -> Result<(), ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Whether `register` needs an invite code on this server.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[get("/api/auth/invite-required")]
+pub async fn invite_code_required() -> Result<bool, ServerFnError> {
+    Ok(invites::invite_required())
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/auth/invite-required")]
+pub async fn invite_code_required() -> Result<bool, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Create an invite code good for `uses_remaining` registrations within
+/// `expires_hours`. Admins only.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/invites", session: tower_sessions::Session)]
+pub async fn create_invite_code(uses_remaining: u32, expires_hours: u32) -> Result<String, ServerFnError> {
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    invites::create(user_uuid, uses_remaining, expires_hours)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/invites")]
+pub async fn create_invite_code(uses_remaining: u32, expires_hours: u32) -> Result<String, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
 /// Log in with email and password.
 #[cfg(feature = "server")]
-#[post("/api/auth/login-password", session: tower_sessions::Session)]
+#[tracing::instrument(skip_all)]
+#[post("/api/auth/login-password", session: tower_sessions::Session, headers: dioxus::fullstack::HeaderMap)]
 pub async fn login_password(email: String, password: String) -> Result<UserInfo, ServerFnError> {
     use crate::db::get_pool;
 
@@ -246,8 +589,7 @@ pub async fn login_password(email: String, password: String) -> Result<UserInfo,
         return Err(ServerFnError::new("Invalid email or password"));
     }
 
-    session
-        .insert(auth::SESSION_USER_ID_KEY, user.id.to_string())
+    auth::start_user_session(&session, &user.id.to_string(), &headers)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
 
@@ -260,14 +602,21 @@ pub async fn login_password(email: String, password: String) -> Result<UserInfo,
     Err(ServerFnError::new("Server only"))
 }
 
-/// Save git credentials (remote URL, optional SSH key, optional branch).
+/// Longest display name accepted by [`update_user_profile`], in characters.
+pub const MAX_PROFILE_NAME_LEN: usize = 100;
+
+/// Update the current user's display name and/or avatar URL.
+///
+/// `None` leaves a field unchanged. A provided name must be non-empty and at
+/// most [`MAX_PROFILE_NAME_LEN`] characters; an avatar URL must be an absolute
+/// `http(s)` URL.
 #[cfg(feature = "server")]
-#[post("/api/git/credentials", session: tower_sessions::Session)]
-pub async fn save_git_credentials(
-    git_remote_url: String,
-    ssh_private_key: Option<String>,
-    git_branch: Option<String>,
-) -> Result<GitCredentialsInfo, ServerFnError> {
+#[tracing::instrument(skip_all)]
+#[post("/api/auth/profile", session: tower_sessions::Session)]
+pub async fn update_user_profile(
+    name: Option<String>,
+    avatar_url: Option<String>,
+) -> Result<UserInfo, ServerFnError> {
     use crate::db::get_pool;
 
     let user_id: Option<String> = session
@@ -282,115 +631,151 @@ pub async fn save_git_credentials(
     let user_uuid =
         uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    let pool = get_pool()
-        .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
-
-    let git_remote_url = if git_remote_url.trim().is_empty() {
-        None
-    } else {
-        Some(git_remote_url.trim().to_string())
-    };
-
-    // If SSH key provided, normalise line endings, encrypt, and extract public key
-    let (encrypted_key, nonce, public_key) = if let Some(ref key_pem) = ssh_private_key {
-        let normalised = key_pem.replace('\r', "");
-        let normalised = normalised.trim();
-        if normalised.is_empty() {
-            (None, None, None)
-        } else {
-            let with_newline = format!("{normalised}\n");
-            let pub_key = crypto::extract_public_key(&with_newline)
-                .map_err(|e| ServerFnError::new(e))?;
-            let (enc, n) = crypto::encrypt_ssh_key(with_newline.as_bytes())
-                .map_err(|e| ServerFnError::new(e))?;
-            (Some(enc), Some(n), Some(pub_key))
+    let name = name.map(|n| n.trim().to_string());
+    if let Some(ref name) = name {
+        if name.is_empty() {
+            return Err(ServerFnError::new("Name cannot be empty"));
         }
-    } else {
-        (None, None, None)
-    };
+        if name.chars().count() > MAX_PROFILE_NAME_LEN {
+            return Err(ServerFnError::new(format!(
+                "Name must be at most {MAX_PROFILE_NAME_LEN} characters"
+            )));
+        }
+    }
 
-    let branch = git_branch
-        .filter(|b| !b.trim().is_empty())
-        .unwrap_or_else(|| "main".to_string());
+    let avatar_url = avatar_url.map(|u| u.trim().to_string());
+    if let Some(ref avatar_url) = avatar_url {
+        let parsed = url::Url::parse(avatar_url)
+            .map_err(|e| ServerFnError::new(format!("Invalid avatar URL: {e}")))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(ServerFnError::new("Avatar URL must use http or https"));
+        }
+    }
 
-    if encrypted_key.is_some() {
-        // Upsert with new SSH key
-        sqlx::query(
-            "INSERT INTO user_git_config (user_id, git_remote_url, ssh_private_key_enc, ssh_public_key, encryption_nonce, git_branch)
-             VALUES ($1, $2, $3, $4, $5, $6)
-             ON CONFLICT (user_id) DO UPDATE SET
-                git_remote_url = $2,
-                ssh_private_key_enc = $3,
-                ssh_public_key = $4,
-                encryption_nonce = $5,
-                git_branch = $6,
-                updated_at = NOW()",
-        )
-        .bind(user_uuid)
-        .bind(&git_remote_url)
-        .bind(&encrypted_key)
-        .bind(&public_key)
-        .bind(&nonce)
-        .bind(&branch)
-        .execute(pool)
-        .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
-    } else {
-        // Upsert URL + branch only, preserve existing SSH key
-        sqlx::query(
-            "INSERT INTO user_git_config (user_id, git_remote_url, git_branch)
-             VALUES ($1, $2, $3)
-             ON CONFLICT (user_id) DO UPDATE SET
-                git_remote_url = $2,
-                git_branch = $3,
-                updated_at = NOW()",
-        )
-        .bind(user_uuid)
-        .bind(&git_remote_url)
-        .bind(&branch)
-        .execute(pool)
+    let pool = get_pool()
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
-    }
 
-    // Fetch back the saved state
-    let row: Option<(Option<String>, Option<String>, String)> = sqlx::query_as(
-        "SELECT git_remote_url, ssh_public_key, git_branch FROM user_git_config WHERE user_id = $1",
+    let user: models::User = sqlx::query_as(
+        "UPDATE users SET name = COALESCE($1, name), avatar_url = COALESCE($2, avatar_url), updated_at = NOW() WHERE id = $3 RETURNING *",
     )
+    .bind(&name)
+    .bind(&avatar_url)
     .bind(user_uuid)
-    .fetch_optional(pool)
+    .fetch_one(pool)
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    Ok(match row {
-        Some((url, pub_key, branch)) => GitCredentialsInfo {
-            git_remote_url: url,
-            ssh_public_key: pub_key,
-            git_branch: Some(branch),
-        },
-        None => GitCredentialsInfo {
-            git_remote_url: None,
-            ssh_public_key: None,
-            git_branch: Some("main".to_string()),
-        },
-    })
+    Ok(user.to_info())
 }
 
 #[cfg(not(feature = "server"))]
-#[post("/api/git/credentials")]
-pub async fn save_git_credentials(
-    git_remote_url: String,
-    ssh_private_key: Option<String>,
-    git_branch: Option<String>,
-) -> Result<GitCredentialsInfo, ServerFnError> {
+#[post("/api/auth/profile")]
+pub async fn update_user_profile(
+    name: Option<String>,
+    avatar_url: Option<String>,
+) -> Result<UserInfo, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
 
-/// Get git credentials for the current user (URL + public key only).
+/// One logged-in session of the current user, as listed in Settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionInfo {
+    pub id: String,
+    /// Login time (Unix seconds).
+    pub created_at: i64,
+    /// Last request made with this session (Unix seconds).
+    pub last_active: i64,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    /// Whether this is the session making the request.
+    pub is_current: bool,
+}
+
+/// Unexpired sessions in the store that belong to `user_id`
+/// (`is_current` is left `false`).
+///
+/// The PostgreSQL session store keeps each session as an opaque MessagePack
+/// blob, so this decodes every live row and filters on the user ID key.
 #[cfg(feature = "server")]
-#[get("/api/git/credentials", session: tower_sessions::Session)]
-pub async fn get_git_credentials() -> Result<Option<GitCredentialsInfo>, ServerFnError> {
+async fn load_user_sessions(user_id: &str) -> Result<Vec<SessionInfo>, ServerFnError> {
+    use crate::db::get_pool;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let rows: Vec<(String, Vec<u8>, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+        "SELECT id, data, expiry_date FROM tower_sessions.session WHERE expiry_date > NOW()",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, data, expiry)| {
+            let record: tower_sessions::session::Record = rmp_serde::from_slice(&data).ok()?;
+            let owner = record.data.get(auth::SESSION_USER_ID_KEY)?.as_str()?;
+            if owner != user_id {
+                return None;
+            }
+            let last_active = expiry.timestamp() - auth::SESSION_INACTIVITY_SECS;
+            let text = |key: &str| record.data.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            Some(SessionInfo {
+                id,
+                created_at: record
+                    .data
+                    .get(auth::SESSION_CREATED_AT_KEY)
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(last_active),
+                last_active,
+                ip_address: text(auth::SESSION_IP_KEY),
+                user_agent: text(auth::SESSION_USER_AGENT_KEY),
+                is_current: false,
+            })
+        })
+        .collect())
+}
+
+/// List the current user's active sessions, most recently used first.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[get("/api/auth/sessions", session: tower_sessions::Session)]
+pub async fn list_user_sessions() -> Result<Vec<SessionInfo>, ServerFnError> {
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+
+    let current_id = session.id().map(|id| id.to_string());
+
+    let mut sessions = load_user_sessions(&user_id).await?;
+    for info in &mut sessions {
+        info.is_current = current_id.as_deref() == Some(info.id.as_str());
+    }
+
+    sessions.sort_by(|a, b| b.last_active.cmp(&a.last_active));
+    Ok(sessions)
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/auth/sessions")]
+pub async fn list_user_sessions() -> Result<Vec<SessionInfo>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Log out another of the current user's sessions.
+///
+/// The current session cannot be revoked here; use `logout` instead.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/auth/sessions/revoke", session: tower_sessions::Session)]
+pub async fn revoke_session(session_id: String) -> Result<(), ServerFnError> {
     use crate::db::get_pool;
 
     let user_id: Option<String> = session
@@ -402,53 +787,217 @@ pub async fn get_git_credentials() -> Result<Option<GitCredentialsInfo>, ServerF
         return Err(ServerFnError::new("Not authenticated"));
     };
 
+    if session.id().map(|id| id.to_string()).as_deref() == Some(session_id.as_str()) {
+        return Err(ServerFnError::new("Use Log out to end the current session"));
+    }
+
+    // Only delete sessions that belong to the caller
+    let owned = load_user_sessions(&user_id)
+        .await?
+        .iter()
+        .any(|info| info.id == session_id);
+    if !owned {
+        return Err(ServerFnError::new("Session not found"));
+    }
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    sqlx::query("DELETE FROM tower_sessions.session WHERE id = $1")
+        .bind(&session_id)
+        .execute(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/auth/sessions/revoke")]
+pub async fn revoke_session(session_id: String) -> Result<(), ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// A webhook registered by the current user, as listed in Settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookInfo {
+    pub id: String,
+    pub url: String,
+    /// Subscribed events: `note.synced`, `note.deleted`, `notes.pulled`.
+    pub events: Vec<String>,
+    /// Registration time (Unix seconds).
+    pub created_at: i64,
+}
+
+/// Register a URL to be POSTed to on the given sync events.
+///
+/// Returns the signing secret; it is only shown this once.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/webhooks/register", session: tower_sessions::Session)]
+pub async fn register_webhook(url: String, events: Vec<String>) -> Result<String, ServerFnError> {
+    use crate::db::get_pool;
+
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
     let user_uuid =
         uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
 
+    let url = url.trim().to_string();
+    webhooks::validate(&url, &events).map_err(ServerFnError::new)?;
+    webhooks::resolve(&url).await.map_err(ServerFnError::new)?;
+
     let pool = get_pool()
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    let row: Option<(Option<String>, Option<String>, String)> = sqlx::query_as(
-        "SELECT git_remote_url, ssh_public_key, git_branch FROM user_git_config WHERE user_id = $1",
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user_webhooks WHERE user_id = $1")
+        .bind(user_uuid)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if count >= webhooks::MAX_WEBHOOKS_PER_USER {
+        return Err(ServerFnError::new(format!(
+            "At most {} webhooks per account",
+            webhooks::MAX_WEBHOOKS_PER_USER
+        )));
+    }
+
+    let secret = webhooks::generate_secret();
+    let (secret_enc, secret_nonce) = webhooks::seal_secret(&secret).map_err(ServerFnError::new)?;
+    sqlx::query(
+        "INSERT INTO user_webhooks (user_id, url, events, secret_enc, secret_nonce)
+         VALUES ($1, $2, to_jsonb($3::text[]), $4, $5)",
     )
     .bind(user_uuid)
-    .fetch_optional(pool)
+    .bind(&url)
+    .bind(&events)
+    .bind(secret_enc)
+    .bind(secret_nonce)
+    .execute(pool)
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    Ok(row.map(|(url, pub_key, branch)| GitCredentialsInfo {
-        git_remote_url: url,
-        ssh_public_key: pub_key,
-        git_branch: Some(branch),
-    }))
+    Ok(secret)
 }
 
 #[cfg(not(feature = "server"))]
-#[get("/api/git/credentials")]
-pub async fn get_git_credentials() -> Result<Option<GitCredentialsInfo>, ServerFnError> {
+#[post("/api/webhooks/register")]
+pub async fn register_webhook(url: String, events: Vec<String>) -> Result<String, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
 
-/// A file retrieved from the remote git repository.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RemoteFile {
-    pub path: String,
-    pub content: String,
+/// List the current user's webhooks, oldest first.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[get("/api/webhooks", session: tower_sessions::Session)]
+pub async fn list_webhooks() -> Result<Vec<WebhookInfo>, ServerFnError> {
+    use crate::db::get_pool;
+
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let rows: Vec<(uuid::Uuid, String, Vec<String>, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+        "SELECT id, url, ARRAY(SELECT jsonb_array_elements_text(events)), created_at
+         FROM user_webhooks WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(user_uuid)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, url, events, created_at)| WebhookInfo {
+            id: id.to_string(),
+            url,
+            events,
+            created_at: created_at.timestamp(),
+        })
+        .collect())
 }
 
-/// Result of pulling from the git remote: notes + namespaces.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PullResult {
-    pub files: Vec<RemoteFile>,
-    pub namespaces: Vec<String>,
+#[cfg(not(feature = "server"))]
+#[get("/api/webhooks")]
+pub async fn list_webhooks() -> Result<Vec<WebhookInfo>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
 }
 
-/// Helper: get user_id, remote URL, decrypted SSH key, and branch from the session + DB.
+/// Remove one of the current user's webhooks.
 #[cfg(feature = "server")]
-async fn get_user_git_context(
-    session: &tower_sessions::Session,
-) -> Result<(uuid::Uuid, String, String, String), ServerFnError> {
+#[tracing::instrument(skip_all)]
+#[post("/api/webhooks/delete", session: tower_sessions::Session)]
+pub async fn delete_webhook(id: String) -> Result<(), ServerFnError> {
+    use crate::db::get_pool;
+
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+    let webhook_id = uuid::Uuid::parse_str(&id).map_err(|_| ServerFnError::new("Webhook not found"))?;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let deleted = sqlx::query("DELETE FROM user_webhooks WHERE id = $1 AND user_id = $2")
+        .bind(webhook_id)
+        .bind(user_uuid)
+        .execute(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if deleted.rows_affected() == 0 {
+        return Err(ServerFnError::new("Webhook not found"));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/webhooks/delete")]
+pub async fn delete_webhook(id: String) -> Result<(), ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Save git credentials (remote URL, optional SSH key or HTTPS token, optional branch).
+///
+/// The credential type is derived from the URL: `https://` remotes store the
+/// access token, SSH remotes store the private key. Blank secrets keep the
+/// previously stored one.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/git/credentials", session: tower_sessions::Session)]
+pub async fn save_git_credentials(
+    git_remote_url: String,
+    ssh_private_key: Option<String>,
+    git_branch: Option<String>,
+    https_token: Option<String>,
+) -> Result<GitCredentialsInfo, ServerFnError> {
     use crate::db::get_pool;
 
     let user_id: Option<String> = session
@@ -467,92 +1016,1591 @@ async fn get_user_git_context(
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    let row: Option<(Option<String>, Option<Vec<u8>>, Option<Vec<u8>>, String)> = sqlx::query_as(
-        "SELECT git_remote_url, ssh_private_key_enc, encryption_nonce, git_branch FROM user_git_config WHERE user_id = $1",
+    let git_remote_url = if git_remote_url.trim().is_empty() {
+        None
+    } else {
+        Some(git_remote_url.trim().to_string())
+    };
+
+    let credential_type = git_remote_url
+        .as_deref()
+        .map(CredentialType::for_remote_url)
+        .unwrap_or_default();
+
+    // If SSH key provided, normalise line endings, encrypt, and extract public key
+    let ssh_private_key = ssh_private_key.filter(|_| credential_type == CredentialType::Ssh);
+    let (encrypted_key, nonce, public_key) = if let Some(ref key_pem) = ssh_private_key {
+        let normalised = key_pem.replace('\r', "");
+        let normalised = normalised.trim();
+        if normalised.is_empty() {
+            (None, None, None)
+        } else {
+            let with_newline = format!("{normalised}\n");
+            let pub_key = crypto::extract_public_key(&with_newline)
+                .map_err(|e| ServerFnError::new(e))?;
+            let (enc, n) = crypto::encrypt_ssh_key(with_newline.as_bytes())
+                .map_err(|e| ServerFnError::new(e))?;
+            (Some(enc), Some(n), Some(pub_key))
+        }
+    } else {
+        (None, None, None)
+    };
+
+    // If an HTTPS token is provided, encrypt it the same way
+    let encrypted_token = match https_token.as_deref().map(str::trim) {
+        Some(token) if credential_type == CredentialType::HttpsToken && !token.is_empty() => {
+            Some(crypto::encrypt_ssh_key(token.as_bytes()).map_err(|e| ServerFnError::new(e))?)
+        }
+        _ => None,
+    };
+
+    let branch = git_branch
+        .filter(|b| !b.trim().is_empty())
+        .unwrap_or_else(|| "main".to_string());
+
+    if encrypted_key.is_some() {
+        // Upsert with new SSH key
+        sqlx::query(
+            "INSERT INTO user_git_config (user_id, git_remote_url, ssh_private_key_enc, ssh_public_key, encryption_nonce, git_branch, credential_type)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (user_id) DO UPDATE SET
+                git_remote_url = $2,
+                ssh_private_key_enc = $3,
+                ssh_public_key = $4,
+                encryption_nonce = $5,
+                git_branch = $6,
+                credential_type = $7,
+                updated_at = NOW()",
+        )
+        .bind(user_uuid)
+        .bind(&git_remote_url)
+        .bind(&encrypted_key)
+        .bind(&public_key)
+        .bind(&nonce)
+        .bind(&branch)
+        .bind(credential_type.as_db_str())
+        .execute(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    } else if let Some((token_enc, token_nonce)) = encrypted_token {
+        // Upsert with new HTTPS token, preserve any stored SSH key
+        sqlx::query(
+            "INSERT INTO user_git_config (user_id, git_remote_url, https_token_enc, https_token_nonce, git_branch, credential_type)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (user_id) DO UPDATE SET
+                git_remote_url = $2,
+                https_token_enc = $3,
+                https_token_nonce = $4,
+                git_branch = $5,
+                credential_type = $6,
+                updated_at = NOW()",
+        )
+        .bind(user_uuid)
+        .bind(&git_remote_url)
+        .bind(&token_enc)
+        .bind(&token_nonce)
+        .bind(&branch)
+        .bind(credential_type.as_db_str())
+        .execute(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    } else {
+        // Upsert URL + branch only, preserve existing SSH key / token
+        sqlx::query(
+            "INSERT INTO user_git_config (user_id, git_remote_url, git_branch, credential_type)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (user_id) DO UPDATE SET
+                git_remote_url = $2,
+                git_branch = $3,
+                credential_type = $4,
+                updated_at = NOW()",
+        )
+        .bind(user_uuid)
+        .bind(&git_remote_url)
+        .bind(&branch)
+        .bind(credential_type.as_db_str())
+        .execute(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    }
+
+    // Fetch back the saved state
+    let row: Option<(Option<String>, Option<String>, String, String, bool)> = sqlx::query_as(
+        "SELECT git_remote_url, ssh_public_key, git_branch, credential_type, https_token_enc IS NOT NULL
+         FROM user_git_config WHERE user_id = $1",
     )
     .bind(user_uuid)
     .fetch_optional(pool)
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    let Some((Some(remote_url), Some(enc_key), Some(nonce), branch)) = row else {
-        return Err(ServerFnError::new(
-            "Git sync not configured: set remote URL and SSH key in Settings",
-        ));
-    };
+    Ok(match row {
+        Some((url, pub_key, branch, cred_type, has_token)) => GitCredentialsInfo {
+            git_remote_url: url,
+            ssh_public_key: pub_key,
+            git_branch: Some(branch),
+            credential_type: CredentialType::from_db_str(&cred_type),
+            has_https_token: has_token,
+        },
+        None => GitCredentialsInfo {
+            git_remote_url: None,
+            ssh_public_key: None,
+            git_branch: Some("main".to_string()),
+            credential_type: CredentialType::Ssh,
+            has_https_token: false,
+        },
+    })
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/credentials")]
+pub async fn save_git_credentials(
+    git_remote_url: String,
+    ssh_private_key: Option<String>,
+    git_branch: Option<String>,
+    https_token: Option<String>,
+) -> Result<GitCredentialsInfo, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Generate a new Ed25519 SSH key pair on the server and store it as the user's key.
+///
+/// The private key is encrypted like an uploaded one and never leaves the
+/// server; any existing remote URL and branch are kept. The public key is
+/// commented `typednotes-<email>` so it is recognisable on the git host.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/git/generate-keypair", session: tower_sessions::Session)]
+pub async fn generate_ssh_keypair() -> Result<GitCredentialsInfo, ServerFnError> {
+    use crate::db::get_pool;
+
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let (email,): (String,) = sqlx::query_as("SELECT email FROM users WHERE id = $1")
+        .bind(user_uuid)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let (private_pem, public_key) = crypto::generate_ed25519_keypair(&format!("typednotes-{email}"))
+        .map_err(|e| ServerFnError::new(e))?;
+    let (encrypted_key, nonce) = crypto::encrypt_ssh_key(private_pem.as_bytes())
+        .map_err(|e| ServerFnError::new(e))?;
+
+    // Replace only the SSH key, preserve remote URL, branch and any HTTPS token
+    sqlx::query(
+        "INSERT INTO user_git_config (user_id, ssh_private_key_enc, ssh_public_key, encryption_nonce)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (user_id) DO UPDATE SET
+            ssh_private_key_enc = $2,
+            ssh_public_key = $3,
+            encryption_nonce = $4,
+            updated_at = NOW()",
+    )
+    .bind(user_uuid)
+    .bind(&encrypted_key)
+    .bind(&public_key)
+    .bind(&nonce)
+    .execute(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let (url, branch, cred_type, has_token): (Option<String>, String, String, bool) = sqlx::query_as(
+        "SELECT git_remote_url, git_branch, credential_type, https_token_enc IS NOT NULL
+         FROM user_git_config WHERE user_id = $1",
+    )
+    .bind(user_uuid)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(GitCredentialsInfo {
+        git_remote_url: url,
+        ssh_public_key: Some(public_key),
+        git_branch: Some(branch),
+        credential_type: CredentialType::from_db_str(&cred_type),
+        has_https_token: has_token,
+    })
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/generate-keypair")]
+pub async fn generate_ssh_keypair() -> Result<GitCredentialsInfo, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Get git credentials for the current user (URL + public key only, never secrets).
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[get("/api/git/credentials", session: tower_sessions::Session)]
+pub async fn get_git_credentials() -> Result<Option<GitCredentialsInfo>, ServerFnError> {
+    use crate::db::get_pool;
+
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let row: Option<(Option<String>, Option<String>, String, String, bool)> = sqlx::query_as(
+        "SELECT git_remote_url, ssh_public_key, git_branch, credential_type, https_token_enc IS NOT NULL
+         FROM user_git_config WHERE user_id = $1",
+    )
+    .bind(user_uuid)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(row.map(|(url, pub_key, branch, cred_type, has_token)| GitCredentialsInfo {
+        git_remote_url: url,
+        ssh_public_key: pub_key,
+        git_branch: Some(branch),
+        credential_type: CredentialType::from_db_str(&cred_type),
+        has_https_token: has_token,
+    }))
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/git/credentials")]
+pub async fn get_git_credentials() -> Result<Option<GitCredentialsInfo>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// A file retrieved from the remote git repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// Any file of a local repository, as sent by [`push_all_notes`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawFile {
+    pub path: String,
+    /// The bytes, base64 encoded, since attachments need not be text.
+    pub data: String,
+}
+
+/// Result of pulling from the git remote: notes + namespaces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullResult {
+    pub files: Vec<RemoteFile>,
+    pub namespaces: Vec<String>,
+    /// Hex SHA of the remote commit the files come from; pass it as `base`
+    /// to the next [`pull_notes`].
+    #[serde(default)]
+    pub head: Option<String>,
+    /// Unpushed local notes sent to [`pull_notes`] that changed on the
+    /// remote too.
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// A commit that touched a note, as shown in the note history panel.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NoteCommit {
+    pub sha: String,
+    pub message: String,
+    /// Unix timestamp (seconds) of the commit.
+    pub timestamp: i64,
+    pub author: String,
+    /// The commit deleted the note, so there is no version to restore.
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+/// A note that differs between two commits, as returned by [`get_commit_diff`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileDiff {
+    pub path: String,
+    /// Content in the older commit (`None` if the note was added).
+    pub old_content: Option<String>,
+    /// Content in the newer commit (`None` if the note was deleted).
+    pub new_content: Option<String>,
+    /// Line-based unified diff (3 lines of context) from old to new.
+    pub unified_diff: String,
+}
+
+/// Returned by [`sync_note`], instead of pushing, when the note changed on the
+/// remote since the version the client edited, and by [`pull_notes`] for
+/// unpushed notes that changed on both sides.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncConflict {
+    /// Full note path, e.g. "work/todo.md".
+    pub path: String,
+    /// Content the client tried to push.
+    pub local: String,
+    /// Content on the remote now.
+    pub remote: String,
+    /// Content both sides started from, when the remote history still has it.
+    pub base: Option<String>,
+    /// Blob SHA of `remote`; send it as `base_sha` to push a resolution.
+    pub remote_sha: String,
+}
+
+/// Outcome of [`import_notes`] and the imports from other apps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ImportReport {
+    /// Notes written (new or overwritten).
+    pub imported: u32,
+    /// Notes left alone because they already existed and `overwrite` was off.
+    pub skipped: u32,
+    /// One message per archive entry that could not be imported.
+    pub errors: Vec<String>,
+    /// Features of the source app that were dropped or only partly converted.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Name of the imported vault or workspace, when the archive tells.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// A read-only snapshot of a shared note, as returned by [`get_shared_note`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SharedNoteInfo {
+    /// Frontmatter `title`, or the note's file name.
+    pub title: String,
+    pub content: String,
+    /// Sanitised HTML rendering of `content`.
+    pub rendered_html: String,
+    /// Unix timestamp (seconds) after which the link stops working.
+    pub expires_at: i64,
+}
+
+/// Helper: get user_id, remote URL, decrypted credential, and branch from the session + DB.
+///
+/// The credential is the SSH private key PEM or the HTTPS access token,
+/// depending on the stored [`CredentialType`]; [`git_transport`] picks the
+/// matching transport from the URL. Accounts that have not verified their
+/// email address yet are refused.
+#[cfg(feature = "server")]
+async fn get_user_git_context(
+    session: &tower_sessions::Session,
+) -> Result<(uuid::Uuid, String, String, String), ServerFnError> {
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    // Renew the sign-in provider's token before it lapses. Git access uses the
+    // credential from Settings, so a failed refresh must not block the request.
+    let _ = auth::refresh_if_expiring(user_uuid).await;
+
+    if !email::is_verified(user_uuid).await.map_err(ServerFnError::new)? {
+        return Err(ServerFnError::new("Verify your email address to use git sync"));
+    }
+
+    let (remote_url, credential, branch) = get_git_context_for_user(user_uuid).await?;
+
+    let span = tracing::Span::current();
+    span.record("user_id", tracing::field::display(user_uuid));
+    span.record("remote_host", git_transport::remote_host(&remote_url));
+    Ok((user_uuid, remote_url, credential, branch))
+}
+
+/// [`tokio::task::spawn_blocking`] inside the caller's tracing span, so the
+/// `git.*` spans of [`git_transport`] nest under the server function.
+#[cfg(feature = "server")]
+fn spawn_blocking_in_span<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+}
+
+/// Helper: remote URL, decrypted credential, and branch for a user ID, without
+/// a session (used by public endpoints such as shared links).
+#[cfg(feature = "server")]
+async fn get_git_context_for_user(
+    user_uuid: uuid::Uuid,
+) -> Result<(String, String, String), ServerFnError> {
+    use crate::db::get_pool;
+
+    type GitConfigRow = (
+        Option<String>,
+        String,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        Option<Vec<u8>>,
+        String,
+    );
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let row: Option<GitConfigRow> = sqlx::query_as(
+        "SELECT git_remote_url, credential_type, ssh_private_key_enc, encryption_nonce, https_token_enc, https_token_nonce, git_branch
+         FROM user_git_config WHERE user_id = $1",
+    )
+    .bind(user_uuid)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let Some((Some(remote_url), cred_type, key_enc, key_nonce, token_enc, token_nonce, branch)) =
+        row
+    else {
+        return Err(ServerFnError::new(
+            "Git sync not configured: set remote URL in Settings",
+        ));
+    };
+
+    let (enc, nonce) = match CredentialType::from_db_str(&cred_type) {
+        CredentialType::Ssh => key_enc.zip(key_nonce).ok_or_else(|| {
+            ServerFnError::new("Git sync not configured: set remote URL and SSH key in Settings")
+        })?,
+        CredentialType::HttpsToken => token_enc.zip(token_nonce).ok_or_else(|| {
+            ServerFnError::new("Git sync not configured: set remote URL and access token in Settings")
+        })?,
+    };
+
+    let secret_bytes = crypto::decrypt_ssh_key(&enc, &nonce).map_err(|e| ServerFnError::new(e))?;
+    let credential =
+        String::from_utf8(secret_bytes).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok((remote_url, credential, branch))
+}
+
+/// Sync a single note to the git remote: fetch, write note in memory, push.
+///
+/// `base_sha` is the blob SHA of the version the edit started from (the note's
+/// SHA after the last pull). If the remote's copy has moved on since, nothing
+/// is pushed and the [`SyncConflict`] is returned for the user to resolve.
+/// `None` skips the check and overwrites the remote copy.
+///
+/// Counted in the `sync_note_*` metrics (see [`metrics`]).
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/sync-note", session: tower_sessions::Session)]
+pub async fn sync_note(
+    path: String,
+    content: String,
+    note_type: String,
+    base_sha: Option<String>,
+) -> Result<Option<SyncConflict>, ServerFnError> {
+    let started = std::time::Instant::now();
+    let result = sync_note_inner(&session, path, content, note_type, base_sha).await;
+    metrics::record_sync_note(started.elapsed(), result.is_ok());
+    result
+}
+
+#[cfg(feature = "server")]
+async fn sync_note_inner(
+    session: &tower_sessions::Session,
+    path: String,
+    content: String,
+    note_type: String,
+    base_sha: Option<String>,
+) -> Result<Option<SyncConflict>, ServerFnError> {
+    let (user_id, remote_url, credential, branch) = get_user_git_context(session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch current state from remote (blocking I/O)
+    let mem2 = mem.clone();
+    let url = remote_url.clone();
+    let key = credential.clone();
+    let branch2 = branch.clone();
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .map_err(|e| ServerFnError::new(e))?;
+
+    if let Some(base_sha) = base_sha {
+        if let Some(conflict) = find_conflict(&repo, &mem, &path, &content, &note_type, &base_sha).await {
+            return Ok(Some(conflict));
+        }
+    }
+
+    // Snapshot SHAs before modification
+    let pre_shas: std::collections::HashSet<String> =
+        mem.all_object_shas().into_iter().collect();
+
+    // Write note in memory (fast, no I/O)
+    repo.write_note(&path, &content, &note_type).await;
+
+    // Determine new objects to push
+    let new_shas: Vec<String> = mem
+        .all_object_shas()
+        .into_iter()
+        .filter(|s| !pre_shas.contains(s))
+        .collect();
+
+    // Push to remote (blocking I/O)
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    record_sync(user_id, repo.list_notes().await.len()).await;
+    webhooks::notify(user_id, webhooks::NOTE_SYNCED, vec![path]);
+    Ok(None)
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/sync-note")]
+pub async fn sync_note(
+    path: String,
+    content: String,
+    note_type: String,
+    base_sha: Option<String>,
+) -> Result<Option<SyncConflict>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Compare the fetched remote copy of a note with the version the client
+/// edited (`base_sha`). A conflict needs the remote copy to exist, to differ
+/// from the base, and to differ from what the client is pushing.
+#[cfg(feature = "server")]
+async fn find_conflict(
+    repo: &store::Repository<store::MemoryStore>,
+    mem: &store::MemoryStore,
+    path: &str,
+    content: &str,
+    note_type: &str,
+    base_sha: &str,
+) -> Option<SyncConflict> {
+    let full_path = store::models::note_file_path(path, note_type);
+    let remote = repo.get_note(&full_path).await?;
+    if remote.sha == base_sha || remote.note == content {
+        return None;
+    }
+
+    // The base blob is still in the fetched history unless it was never pushed
+    let base = store::Sha::from_hex(base_sha)
+        .and_then(|sha| mem.get_sync(&sha))
+        .and_then(|raw| store::objects::parse_blob(&raw))
+        .and_then(|blob| String::from_utf8(blob.content).ok());
+
+    Some(SyncConflict {
+        path: full_path,
+        local: content.to_string(),
+        remote: remote.note,
+        base,
+        remote_sha: remote.sha,
+    })
+}
+
+/// Best-effort: stamp `user_git_config.last_synced_at` and the repository's
+/// note count after a successful push.
+#[cfg(feature = "server")]
+async fn record_sync(user_id: uuid::Uuid, note_count: usize) {
+    let Ok(pool) = crate::db::get_pool().await else {
+        return;
+    };
+    let _ = sqlx::query(
+        "UPDATE user_git_config SET last_synced_at = NOW(), note_count = $2 WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .bind(note_count as i32)
+    .execute(pool)
+    .await;
+}
+
+/// Sync several notes to the git remote in one commit: fetch once, write
+/// every note in memory, push once. Each tuple is `(path, content, note_type)`.
+///
+/// `base_shas` maps a note's path to the blob SHA its edit started from, as
+/// `base_sha` does for [`sync_note`]. Notes that changed on the remote since
+/// are left out of the commit and returned as [`SyncConflict`]s; the rest
+/// are still pushed.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/batch-sync", session: tower_sessions::Session)]
+pub async fn batch_sync_notes(
+    notes: Vec<(String, String, String)>,
+    base_shas: std::collections::HashMap<String, String>,
+) -> Result<Vec<SyncConflict>, ServerFnError> {
+    if notes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch current state from remote (blocking I/O)
+    let mem2 = mem.clone();
+    let url = remote_url.clone();
+    let key = credential.clone();
+    let branch2 = branch.clone();
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .map_err(|e| ServerFnError::new(e))?;
+
+    let mut conflicts = Vec::new();
+    let mut synced = Vec::new();
+    for (path, content, note_type) in notes {
+        if let Some(base_sha) = base_shas.get(&path) {
+            if let Some(conflict) = find_conflict(&repo, &mem, &path, &content, &note_type, base_sha).await {
+                conflicts.push(conflict);
+                continue;
+            }
+        }
+        synced.push((path, content, note_type));
+    }
+    if synced.is_empty() {
+        return Ok(conflicts);
+    }
+
+    let pre_shas: std::collections::HashSet<String> =
+        mem.all_object_shas().into_iter().collect();
+
+    // Stage every note, then commit them together
+    for (path, content, note_type) in &synced {
+        let full_path = store::models::note_file_path(path, note_type);
+        repo.write_note_batch_raw(&full_path, content.as_bytes()).await;
+    }
+    repo.commit_batch(format!("Batch sync {} notes", synced.len())).await;
+
+    let new_shas: Vec<String> = mem
+        .all_object_shas()
+        .into_iter()
+        .filter(|s| !pre_shas.contains(s))
+        .collect();
+
+    // Push to remote (blocking I/O)
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    record_sync(user_id, repo.list_notes().await.len()).await;
+    let paths = synced.into_iter().map(|(path, _, _)| path).collect();
+    webhooks::notify(user_id, webhooks::NOTE_SYNCED, paths);
+    Ok(conflicts)
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/batch-sync")]
+pub async fn batch_sync_notes(
+    notes: Vec<(String, String, String)>,
+    base_shas: std::collections::HashMap<String, String>,
+) -> Result<Vec<SyncConflict>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Push the whole local tree — notes, attachments, `typednotes.toml` and the
+/// `.gitkeep` files of empty namespaces — to the git remote as a single
+/// commit, for the first sync of a newly configured remote. Starts from an
+/// empty store without fetching, so whatever the branch held before is
+/// replaced.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/push-all", session: tower_sessions::Session)]
+pub async fn push_all_notes(files: Vec<RawFile>) -> Result<(), ServerFnError> {
+    use base64::Engine;
+
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    for file in &files {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&file.data)
+            .map_err(|e| ServerFnError::new(format!("{}: {e}", file.path)))?;
+        repo.write_note_batch_raw(&file.path, &bytes).await;
+    }
+    repo.commit_batch(format!("Initial push of {} files", files.len())).await;
+
+    // Everything in the fresh store is new to the remote
+    let new_shas = mem.all_object_shas();
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem, &remote_url, &credential, &branch, &new_shas)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/push-all")]
+pub async fn push_all_notes(files: Vec<RawFile>) -> Result<(), ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Delete a note from the git remote: fetch, delete in memory, push.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/delete-note", session: tower_sessions::Session)]
+pub async fn delete_note_remote(path: String) -> Result<(), ServerFnError> {
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    let mem2 = mem.clone();
+    let url = remote_url.clone();
+    let key = credential.clone();
+    let branch2 = branch.clone();
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .map_err(|e| ServerFnError::new(e))?;
+
+    let pre_shas: std::collections::HashSet<String> =
+        mem.all_object_shas().into_iter().collect();
+
+    // Delete note in memory
+    repo.delete_note(&path).await;
+
+    let new_shas: Vec<String> = mem
+        .all_object_shas()
+        .into_iter()
+        .filter(|s| !pre_shas.contains(s))
+        .collect();
+
+    // Push
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    webhooks::notify(user_id, webhooks::NOTE_DELETED, vec![path]);
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/delete-note")]
+pub async fn delete_note_remote(path: String) -> Result<(), ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Delete a namespace (directory) from the git remote: fetch, delete in memory, push.
+///
+/// A namespace that still contains notes is only deleted with `force` set,
+/// i.e. after the user confirmed losing them.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/delete-namespace", session: tower_sessions::Session)]
+pub async fn delete_namespace_remote(path: String, force: bool) -> Result<(), ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    let mem2 = mem.clone();
+    let url = remote_url.clone();
+    let key = credential.clone();
+    let branch2 = branch.clone();
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .map_err(|e| ServerFnError::new(e))?;
+
+    if !force {
+        let prefix = format!("{path}/");
+        if repo.list_notes().await.iter().any(|n| n.path.starts_with(&prefix)) {
+            return Err(ServerFnError::new(format!(
+                "Namespace {path} is not empty; confirm to delete its notes"
+            )));
+        }
+    }
+
+    let pre_shas: std::collections::HashSet<String> =
+        mem.all_object_shas().into_iter().collect();
+
+    // Delete namespace in memory
+    repo.delete_namespace(&path).await;
+
+    let new_shas: Vec<String> = mem
+        .all_object_shas()
+        .into_iter()
+        .filter(|s| !pre_shas.contains(s))
+        .collect();
+
+    // Push
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/delete-namespace")]
+pub async fn delete_namespace_remote(path: String, force: bool) -> Result<(), ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Sync a namespace (directory) to the git remote: fetch, create in memory, push.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/sync-namespace", session: tower_sessions::Session)]
+pub async fn sync_namespace(path: String) -> Result<(), ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    let mem2 = mem.clone();
+    let url = remote_url.clone();
+    let key = credential.clone();
+    let branch2 = branch.clone();
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .map_err(|e| ServerFnError::new(e))?;
+
+    let pre_shas: std::collections::HashSet<String> =
+        mem.all_object_shas().into_iter().collect();
+
+    // Create namespace in memory
+    repo.create_namespace(&path).await;
+
+    let new_shas: Vec<String> = mem
+        .all_object_shas()
+        .into_iter()
+        .filter(|s| !pre_shas.contains(s))
+        .collect();
+
+    // Push
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/sync-namespace")]
+pub async fn sync_namespace(path: String) -> Result<(), ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// How long [`ping_git_remote`] waits for the remote before giving up.
+#[cfg(feature = "server")]
+const PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Outcome of [`ping_git_remote`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PingResult {
+    pub reachable: bool,
+    /// Round-trip time of the fetch, when it completed.
+    pub latency_ms: Option<u64>,
+    /// Branch names advertised by the remote, sorted.
+    pub branches: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Check that the stored remote URL and credential currently work.
+///
+/// Runs a fetch (with a 5 second timeout) and reports how long it took and
+/// which branches the remote has. Connection and auth failures are returned
+/// in [`PingResult::error`] rather than as a server function error.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/ping", session: tower_sessions::Session)]
+pub async fn ping_git_remote() -> Result<PingResult, ServerFnError> {
+    let (_user_id, remote_url, credential, _branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let fetched = mem.clone();
+
+    let started = std::time::Instant::now();
+    let outcome = tokio::time::timeout(
+        PING_TIMEOUT,
+        spawn_blocking_in_span(move || {
+            git_transport::fetch(&fetched, &remote_url, &credential, None)
+        }),
+    )
+    .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let error = match outcome {
+        Err(_) => Some(format!("Timed out after {}s", PING_TIMEOUT.as_secs())),
+        Ok(Err(e)) => Some(e.to_string()),
+        Ok(Ok(Err(e))) => Some(e),
+        Ok(Ok(Ok(()))) => None,
+    };
+    if let Some(error) = error {
+        return Ok(PingResult {
+            reachable: false,
+            latency_ms: None,
+            branches: Vec::new(),
+            error: Some(error),
+        });
+    }
+
+    let branches = mem
+        .all_ref_names()
+        .into_iter()
+        .filter_map(|name| name.strip_prefix("refs/heads/").map(str::to_string))
+        .collect();
+
+    Ok(PingResult {
+        reachable: true,
+        latency_ms: Some(latency_ms),
+        branches,
+        error: None,
+    })
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/git/ping")]
+pub async fn ping_git_remote() -> Result<PingResult, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// List the branch names on the git remote, sorted.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/branches", session: tower_sessions::Session)]
+pub async fn list_branches() -> Result<Vec<String>, ServerFnError> {
+    let (_user_id, remote_url, credential, _branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let fetched = mem.clone();
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&fetched, &remote_url, &credential, None)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let mut branches: Vec<String> = mem
+        .all_refs()
+        .into_keys()
+        .filter_map(|name| name.strip_prefix("refs/heads/").map(str::to_string))
+        .collect();
+    branches.sort();
+    Ok(branches)
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/git/branches")]
+pub async fn list_branches() -> Result<Vec<String>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Pull all notes and namespaces from the git remote.
+///
+/// `base` is the [`PullResult::head`] of the client's previous pull and
+/// `local` its notes saved since then but not pushed yet. Those are committed
+/// on top of `base` and merged with the remote (see
+/// [`store::Repository::merge_from`]); notes both sides changed come back as
+/// [`PullResult::conflicts`]. Without a `base` still in the remote history
+/// there is nothing to merge against, and no conflicts are reported.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/pull", session: tower_sessions::Session)]
+pub async fn pull_notes(base: Option<String>, local: Vec<RemoteFile>) -> Result<PullResult, ServerFnError> {
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem2, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let conflicts = match base.as_deref().and_then(store::Sha::from_hex) {
+        Some(base) if !local.is_empty() => merge_local_notes(&mem, &base, &local).await,
+        _ => Vec::new(),
+    };
+
+    // List notes from in-memory repo
+    let notes = repo.list_notes().await;
+    // List namespaces (includes empty directories with .gitkeep)
+    let namespaces = repo.list_namespaces().await;
+
+    webhooks::notify(
+        user_id,
+        webhooks::NOTES_PULLED,
+        notes.iter().map(|n| n.path.clone()).collect(),
+    );
+
+    Ok(PullResult {
+        files: notes
+            .into_iter()
+            .map(|n| RemoteFile {
+                path: n.path,
+                content: n.note,
+            })
+            .collect(),
+        namespaces: namespaces.into_iter().map(|ns| ns.path).collect(),
+        head: repo.get_head().await.map(|sha| sha.to_hex()),
+        conflicts,
+    })
+}
+
+/// Commit the client's unpushed `local` notes on top of the remote commit
+/// `base` in a copy of `remote`, merge the remote's `HEAD` into that, and
+/// return the conflicting notes. Notes the remote deleted are left to
+/// [`sync_note`], which recreates them.
+#[cfg(feature = "server")]
+async fn merge_local_notes(
+    remote: &store::MemoryStore,
+    base: &store::Sha,
+    local: &[RemoteFile],
+) -> Vec<SyncConflict> {
+    let remote_repo = store::Repository::new(remote.clone());
+    if remote_repo.get_commit(base).await.is_none() {
+        return Vec::new();
+    }
+    let mem = remote.clone_deep();
+    mem.set_ref_sync("HEAD", base);
+    let repo = store::Repository::new(mem);
+    for file in local {
+        repo.write_note_batch_raw(&file.path, file.content.as_bytes()).await;
+    }
+    repo.commit_batch("Unpushed local changes".to_string()).await;
+
+    let mut conflicts = Vec::new();
+    for conflict in repo.merge_from(remote).await.conflicts {
+        let Some(remote_note) = remote_repo.get_note(&conflict.path).await else {
+            continue;
+        };
+        conflicts.push(SyncConflict {
+            path: conflict.path,
+            local: conflict.local,
+            remote: conflict.remote,
+            base: conflict.base,
+            remote_sha: remote_note.sha,
+        });
+    }
+    conflicts
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/pull")]
+pub async fn pull_notes(base: Option<String>, local: Vec<RemoteFile>) -> Result<PullResult, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Pull only the notes that differ on the remote from remote commit `base`
+/// (hex SHA), the `head` of the client's last pull, and return the new
+/// `head` to pass next time (see [`store::Repository::list_notes_changed_since`]).
+/// A `base` the remote no longer has, e.g. after a force push, returns every
+/// note. Deleted notes and new empty namespaces are not reported; a full
+/// [`pull_notes`] picks those up.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/pull-since/:base", session: tower_sessions::Session)]
+pub async fn pull_notes_since(base: String) -> Result<PullResult, ServerFnError> {
+    let base = store::Sha::from_hex(&base).ok_or_else(|| ServerFnError::new("Invalid base commit"))?;
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let head = repo.get_head().await;
+    let notes = match &head {
+        Some(head) if *head != base => repo.list_notes_changed_since(&base).await,
+        _ => Vec::new(),
+    };
+    webhooks::notify(
+        user_id,
+        webhooks::NOTES_PULLED,
+        notes.iter().map(|n| n.path.clone()).collect(),
+    );
+
+    Ok(PullResult {
+        files: notes
+            .into_iter()
+            .map(|n| RemoteFile {
+                path: n.path,
+                content: n.note,
+            })
+            .collect(),
+        namespaces: Vec::new(),
+        head: head.map(|sha| sha.to_hex()),
+        conflicts: Vec::new(),
+    })
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/git/pull-since/:base")]
+pub async fn pull_notes_since(base: String) -> Result<PullResult, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// The whole remote repository as a gzip-compressed git pack, for a new
+/// client to import with [`store::Repository::import_from_pack`] instead of
+/// a full [`pull_notes`].
+///
+/// The pack holds every object reachable from the remote `HEAD`, stored
+/// whole (see [`store::pack`]), with the `HEAD` commit as its first entry
+/// ([`store::pack::first_object`]). It is sent as a raw
+/// `application/octet-stream` body rather than a JSON array of bytes.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/pack", session: tower_sessions::Session)]
+pub async fn download_pack() -> Result<dioxus::fullstack::ByteStream, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem2, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let Some(head) = repo.get_head().await else {
+        return Err(ServerFnError::new("Remote has no commits"));
+    };
+    let mut sha_hexes = vec![head.to_hex()];
+    sha_hexes.extend(
+        repo.reachable_objects()
+            .await
+            .into_iter()
+            .filter(|sha| *sha != head)
+            .map(|sha| sha.to_hex()),
+    );
+
+    let compressed = spawn_blocking_in_span(move || {
+        let _span = tracing::info_span!("git.build_pack", objects = sha_hexes.len()).entered();
+        store::pack::build_pack(&mem, &sha_hexes).and_then(|pack| store::pack::gzip(&pack))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+    Ok(dioxus::fullstack::ByteStream::new(futures::stream::once(std::future::ready(
+        compressed.into(),
+    ))))
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/git/pack")]
+pub async fn download_pack() -> Result<dioxus::fullstack::ByteStream, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// List the commits that changed a note, newest first (at most `limit`).
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/history?path&limit", session: tower_sessions::Session)]
+pub async fn get_note_history(path: String, limit: usize) -> Result<Vec<NoteCommit>, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let mut history = Vec::new();
+    for (sha, commit) in repo.note_history(&path, limit).await {
+        let deleted = repo.get_note_in_commit(&sha, &path).await.is_none();
+        history.push(NoteCommit {
+            sha: sha.to_hex(),
+            message: commit.message,
+            timestamp: commit.timestamp,
+            author: commit.author,
+            deleted,
+        });
+    }
+    Ok(history)
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/git/history?path&limit")]
+pub async fn get_note_history(path: String, limit: usize) -> Result<Vec<NoteCommit>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Restore a note to its content at `commit_sha`, committing and pushing the
+/// old content as a new version. Returns the restored content.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/git/restore", session: tower_sessions::Session)]
+pub async fn restore_note_version(path: String, commit_sha: String) -> Result<String, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let target = store::Sha::from_hex(&commit_sha)
+        .ok_or_else(|| ServerFnError::new(format!("Invalid commit SHA: {commit_sha}")))?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    let mem2 = mem.clone();
+    let url = remote_url.clone();
+    let key = credential.clone();
+    let branch2 = branch.clone();
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .map_err(|e| ServerFnError::new(e))?;
+
+    let old = repo.get_note_at(&target, &path).await.ok_or_else(|| {
+        ServerFnError::new(format!("{path} not found at commit {commit_sha}"))
+    })?;
+
+    let pre_shas: std::collections::HashSet<String> =
+        mem.all_object_shas().into_iter().collect();
+
+    // Write the old content back as a new commit
+    let stem = path.trim_end_matches(&format!(".{}", store::models::ext_from_note_type(&old.r#type)));
+    repo.write_note(stem, &old.note, &old.r#type).await;
+
+    let new_shas: Vec<String> = mem
+        .all_object_shas()
+        .into_iter()
+        .filter(|s| !pre_shas.contains(s))
+        .collect();
+
+    // Push
+    let mem2 = mem.clone();
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    Ok(old.note)
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/git/restore")]
+pub async fn restore_note_version(path: String, commit_sha: String) -> Result<String, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Diff the notes of two commits, returning one [`FileDiff`] per changed path
+/// (sorted by path). `commit_a` is treated as the older side.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/diff?commit_a&commit_b", session: tower_sessions::Session)]
+pub async fn get_commit_diff(commit_a: String, commit_b: String) -> Result<Vec<FileDiff>, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let sha_a = store::Sha::from_hex(&commit_a)
+        .ok_or_else(|| ServerFnError::new(format!("Invalid commit SHA: {commit_a}")))?;
+    let sha_b = store::Sha::from_hex(&commit_b)
+        .ok_or_else(|| ServerFnError::new(format!("Invalid commit SHA: {commit_b}")))?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    for (sha, hex) in [(&sha_a, &commit_a), (&sha_b, &commit_b)] {
+        if repo.get_commit(sha).await.is_none() {
+            return Err(ServerFnError::new(format!("Commit {hex} not found")));
+        }
+    }
+
+    let mut diffs = Vec::new();
+    for change in repo.diff_commits(&sha_a, &sha_b).await {
+        let filename = change.path.rsplit('/').next().unwrap_or(&change.path);
+        if store::models::split_note_filename(filename).is_none() {
+            continue;
+        }
+        let old = repo.get_note_in_commit(&sha_a, &change.path).await;
+        let new = repo.get_note_in_commit(&sha_b, &change.path).await;
+        diffs.push((change.path, old, new));
+    }
+
+    Ok(diffs
+        .into_iter()
+        .map(|(path, old, new)| {
+            let old_content = old.map(|n| n.note);
+            let new_content = new.map(|n| n.note);
+            let unified_diff = similar::TextDiff::from_lines(
+                old_content.as_deref().unwrap_or(""),
+                new_content.as_deref().unwrap_or(""),
+            )
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("a/{path}"), &format!("b/{path}"))
+            .to_string();
+            FileDiff {
+                path,
+                old_content,
+                new_content,
+                unified_diff,
+            }
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/git/diff?commit_a&commit_b")]
+pub async fn get_commit_diff(commit_a: String, commit_b: String) -> Result<Vec<FileDiff>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Search the user's notes on the git remote by path and content.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/notes/search?query", session: tower_sessions::Session)]
+pub async fn search_notes(query: String) -> Result<Vec<SearchResult>, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let notes = repo.list_notes().await;
+    Ok(search::search_notes_in(&notes, &query))
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/notes/search?query")]
+pub async fn search_notes(query: String) -> Result<Vec<SearchResult>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// List the notes on the git remote whose frontmatter has `tag`.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/notes/by-tag?tag", session: tower_sessions::Session)]
+pub async fn list_notes_by_tag(tag: String) -> Result<Vec<TypedNoteInfo>, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let tag = tag.trim().trim_start_matches('#');
+    Ok(repo
+        .list_notes()
+        .await
+        .into_iter()
+        .filter(|note| note.tags().iter().any(|t| t == tag))
+        .collect())
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/notes/by-tag?tag")]
+pub async fn list_notes_by_tag(tag: String) -> Result<Vec<TypedNoteInfo>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// List the notes on the git remote that link to the note at `path` (see
+/// [`store::Repository::find_backlinks`]).
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/notes/backlinks?path", session: tower_sessions::Session)]
+pub async fn get_backlinks(path: String) -> Result<Vec<TypedNoteInfo>, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
 
-    let key_bytes =
-        crypto::decrypt_ssh_key(&enc_key, &nonce).map_err(|e| ServerFnError::new(e))?;
-    let ssh_key_pem =
-        String::from_utf8(key_bytes).map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(repo.find_backlinks(&path).await)
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/notes/backlinks?path")]
+pub async fn get_backlinks(path: String) -> Result<Vec<TypedNoteInfo>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Create `new_name` (in `namespace`, or the root) from the template note at
+/// `template_path`, filling in its placeholders (see [`store::templates`]),
+/// and push it. The new note has the template's type; an existing note at
+/// that path is an error.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/notes/from-template", session: tower_sessions::Session)]
+pub async fn create_note_from_template(
+    template_path: String,
+    new_name: String,
+    namespace: Option<String>,
+) -> Result<TypedNoteInfo, ServerFnError> {
+    use crate::db::get_pool;
+    use store::templates::{render_template, today, TEMPLATES_NAMESPACE};
 
-    Ok((user_uuid, remote_url, ssh_key_pem, branch))
-}
+    if !template_path.starts_with(&format!("{TEMPLATES_NAMESPACE}/")) {
+        return Err(ServerFnError::new(format!("{template_path} is not a template")));
+    }
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
 
-/// Sync a single note to the git remote: fetch, write note in memory, push.
-#[cfg(feature = "server")]
-#[post("/api/git/sync-note", session: tower_sessions::Session)]
-pub async fn sync_note(
-    path: String,
-    content: String,
-    note_type: String,
-) -> Result<(), ServerFnError> {
-    let (_user_id, remote_url, ssh_key_pem, branch) = get_user_git_context(&session).await?;
+    let pool = get_pool().await.map_err(|e| ServerFnError::new(e.to_string()))?;
+    let (author,): (String,) = sqlx::query_as("SELECT COALESCE(name, email) FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     let mem = store::MemoryStore::new();
     let repo = store::Repository::new(mem.clone());
 
-    // Fetch current state from remote (blocking I/O)
+    // Fetch
     let mem2 = mem.clone();
     let url = remote_url.clone();
-    let key = ssh_key_pem.clone();
+    let key = credential.clone();
     let branch2 = branch.clone();
-    tokio::task::spawn_blocking(move || git_transport::fetch(&mem2, &url, &key, Some(&branch2)))
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?
         .map_err(|e| ServerFnError::new(e))?;
 
-    // Snapshot SHAs before modification
+    let template = repo
+        .get_note(&template_path)
+        .await
+        .ok_or_else(|| ServerFnError::new(format!("Template {template_path} not found")))?;
+    let stem = match namespace.as_deref().filter(|ns| !ns.is_empty()) {
+        Some(ns) => format!("{ns}/{new_name}"),
+        None => new_name.clone(),
+    };
+    let path = format!("{stem}.{}", store::models::ext_from_note_type(&template.r#type));
+    if repo.get_note(&path).await.is_some() {
+        return Err(ServerFnError::new(format!("{path} already exists")));
+    }
+
     let pre_shas: std::collections::HashSet<String> =
         mem.all_object_shas().into_iter().collect();
-
-    // Write note in memory (fast, no I/O)
-    repo.write_note(&path, &content, &note_type).await;
-
-    // Determine new objects to push
+    let content = render_template(&template.note, &new_name, &author, &today());
+    repo.write_note(&stem, &content, &template.r#type).await;
     let new_shas: Vec<String> = mem
         .all_object_shas()
         .into_iter()
         .filter(|s| !pre_shas.contains(s))
         .collect();
 
-    // Push to remote (blocking I/O)
+    // Push
     let mem2 = mem.clone();
-    tokio::task::spawn_blocking(move || {
-        git_transport::push(&mem2, &remote_url, &ssh_key_pem, &branch, &new_shas)
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
     })
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?
     .map_err(|e| ServerFnError::new(e))?;
 
-    Ok(())
+    webhooks::notify(user_id, webhooks::NOTE_SYNCED, vec![path.clone()]);
+    repo.get_note(&path)
+        .await
+        .ok_or_else(|| ServerFnError::new(format!("{path} was not written")))
 }
 
 #[cfg(not(feature = "server"))]
-#[post("/api/git/sync-note")]
-pub async fn sync_note(
-    path: String,
-    content: String,
-    note_type: String,
-) -> Result<(), ServerFnError> {
+#[post("/api/notes/from-template")]
+pub async fn create_note_from_template(
+    template_path: String,
+    new_name: String,
+    namespace: Option<String>,
+) -> Result<TypedNoteInfo, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
 
-/// Delete a note from the git remote: fetch, delete in memory, push.
+/// Commit a file under `assets/` in the user's repository and return its
+/// relative URL (see [`attachments`]). The name is sanitised and numbered
+/// (`cat-1.png`, …) if a different file already has it; uploading the same
+/// file again reuses it.
 #[cfg(feature = "server")]
-#[post("/api/git/delete-note", session: tower_sessions::Session)]
-pub async fn delete_note_remote(path: String) -> Result<(), ServerFnError> {
-    let (_user_id, remote_url, ssh_key_pem, branch) = get_user_git_context(&session).await?;
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/attachments", session: tower_sessions::Session)]
+pub async fn upload_attachment(filename: String, bytes: Vec<u8>) -> Result<String, ServerFnError> {
+    if bytes.len() > attachments::MAX_ATTACHMENT_BYTES {
+        return Err(ServerFnError::new(format!(
+            "{filename} is larger than {} MB",
+            attachments::MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    let name = attachments::sanitize_filename(&filename).map_err(ServerFnError::new)?;
+    attachments::check_content(&name, &bytes).map_err(ServerFnError::new)?;
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
 
     let mem = store::MemoryStore::new();
     let repo = store::Repository::new(mem.clone());
@@ -560,19 +2608,27 @@ pub async fn delete_note_remote(path: String) -> Result<(), ServerFnError> {
     // Fetch
     let mem2 = mem.clone();
     let url = remote_url.clone();
-    let key = ssh_key_pem.clone();
+    let key = credential.clone();
     let branch2 = branch.clone();
-    tokio::task::spawn_blocking(move || git_transport::fetch(&mem2, &url, &key, Some(&branch2)))
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?
         .map_err(|e| ServerFnError::new(e))?;
 
+    let mut n = 0;
+    let (name, path) = loop {
+        let candidate = attachments::numbered_name(&name, n);
+        let path = format!("{}/{candidate}", attachments::ATTACHMENTS_DIR);
+        match repo.read_raw(&path).await {
+            Some(existing) if existing == bytes => return Ok(attachments::attachment_url(&candidate)),
+            Some(_) => n += 1,
+            None => break (candidate, path),
+        }
+    };
+
     let pre_shas: std::collections::HashSet<String> =
         mem.all_object_shas().into_iter().collect();
-
-    // Delete note in memory
-    repo.delete_note(&path).await;
-
+    repo.write_note_raw(&path, &bytes).await;
     let new_shas: Vec<String> = mem
         .all_object_shas()
         .into_iter()
@@ -581,76 +2637,328 @@ pub async fn delete_note_remote(path: String) -> Result<(), ServerFnError> {
 
     // Push
     let mem2 = mem.clone();
-    tokio::task::spawn_blocking(move || {
-        git_transport::push(&mem2, &remote_url, &ssh_key_pem, &branch, &new_shas)
+    spawn_blocking_in_span(move || {
+        git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
     })
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?
     .map_err(|e| ServerFnError::new(e))?;
 
-    Ok(())
+    webhooks::notify(user_id, webhooks::NOTE_SYNCED, vec![path]);
+    Ok(attachments::attachment_url(&name))
 }
 
 #[cfg(not(feature = "server"))]
-#[post("/api/git/delete-note")]
-pub async fn delete_note_remote(path: String) -> Result<(), ServerFnError> {
+#[post("/api/attachments")]
+pub async fn upload_attachment(filename: String, bytes: Vec<u8>) -> Result<String, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
 
-/// Delete a namespace (directory) from the git remote: fetch, delete in memory, push.
+/// Fetch the note at `path` from the user's git remote.
 #[cfg(feature = "server")]
-#[post("/api/git/delete-namespace", session: tower_sessions::Session)]
-pub async fn delete_namespace_remote(path: String) -> Result<(), ServerFnError> {
-    let (_user_id, remote_url, ssh_key_pem, branch) = get_user_git_context(&session).await?;
+async fn fetch_remote_note(session: &tower_sessions::Session, path: &str) -> Result<TypedNoteInfo, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    repo.get_note(path)
+        .await
+        .ok_or_else(|| ServerFnError::new(format!("{path} not found")))
+}
+
+/// Render the note at `path` on the git remote as a standalone HTML document
+/// (see [`render::render_note_document`]).
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[get("/api/notes/export-html?path", session: tower_sessions::Session)]
+pub async fn export_note_html(path: String) -> Result<String, ServerFnError> {
+    let note = fetch_remote_note(&session, &path).await?;
+    Ok(render::render_note_document(note.title(), &note.note, &note.r#type))
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/notes/export-html?path")]
+pub async fn export_note_html(path: String) -> Result<String, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Render the note at `path` on the git remote to PDF by printing its HTML
+/// export in headless Chromium. Needs a Chrome/Chromium binary on the server
+/// (found on `PATH` or via `CHROME`); set `CHROME_NO_SANDBOX` when running it
+/// as root or in a container.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[get("/api/notes/export-pdf?path", session: tower_sessions::Session)]
+pub async fn export_note_pdf(path: String) -> Result<Vec<u8>, ServerFnError> {
+    let note = fetch_remote_note(&session, &path).await?;
+    let html = render::render_note_document(note.title(), &note.note, &note.r#type);
+    render::print_to_pdf(&html).await.map_err(ServerFnError::new)
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/notes/export-pdf?path")]
+pub async fn export_note_pdf(path: String) -> Result<Vec<u8>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Size of the user's repository on the git remote, for the settings page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StorageStats {
+    pub note_count: usize,
+    pub namespace_count: usize,
+    /// Total size of every blob reachable from `HEAD`, all versions included.
+    pub total_blob_bytes: usize,
+    /// Commits reachable from `HEAD` through any parent link.
+    pub commit_count: usize,
+    /// Unix timestamp (seconds) of the last push made by the app.
+    pub last_sync_at: Option<i64>,
+}
+
+/// Count notes, namespaces, commits and blob bytes on the git remote.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/stats", session: tower_sessions::Session)]
+pub async fn get_storage_stats() -> Result<StorageStats, ServerFnError> {
+    use crate::db::get_pool;
+
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
 
     let mem = store::MemoryStore::new();
     let repo = store::Repository::new(mem.clone());
 
     // Fetch
     let mem2 = mem.clone();
-    let url = remote_url.clone();
-    let key = ssh_key_pem.clone();
-    let branch2 = branch.clone();
-    tokio::task::spawn_blocking(move || git_transport::fetch(&mem2, &url, &key, Some(&branch2)))
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem2, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    // Both sides of merges count
+    let mut commit_count = 0;
+    let mut seen = std::collections::HashSet::new();
+    let mut pending: Vec<store::Sha> = repo.get_head().await.into_iter().collect();
+    while let Some(sha) = pending.pop() {
+        if !seen.insert(sha.clone()) {
+            continue;
+        }
+        let Some(commit) = repo.get_commit(&sha).await else {
+            continue;
+        };
+        commit_count += 1;
+        pending.extend(commit.parents);
+    }
+
+    let reachable = repo.reachable_objects().await;
+    let mut total_blob_bytes = 0;
+    mem.iter_objects(|sha, raw| {
+        if reachable.contains(sha) {
+            if let Some(blob) = store::objects::parse_blob(raw) {
+                total_blob_bytes += blob.content.len();
+            }
+        }
+    });
+
+    let pool = get_pool()
         .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?
-        .map_err(|e| ServerFnError::new(e))?;
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let last_synced: Option<(Option<chrono::DateTime<chrono::Utc>>,)> =
+        sqlx::query_as("SELECT last_synced_at FROM user_git_config WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(StorageStats {
+        note_count: repo.list_notes().await.len(),
+        namespace_count: repo.list_namespaces().await.len(),
+        total_blob_bytes,
+        commit_count,
+        last_sync_at: last_synced.and_then(|(at,)| at).map(|at| at.timestamp()),
+    })
+}
 
-    let pre_shas: std::collections::HashSet<String> =
-        mem.all_object_shas().into_iter().collect();
+#[cfg(not(feature = "server"))]
+#[get("/api/git/stats")]
+pub async fn get_storage_stats() -> Result<StorageStats, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
 
-    // Delete namespace in memory
-    repo.delete_namespace(&path).await;
+/// A tag on the git remote.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TagInfo {
+    pub name: String,
+    /// The tag object for annotated tags, the tagged commit for lightweight ones.
+    pub sha: String,
+    /// Empty for lightweight tags.
+    pub message: String,
+}
 
-    let new_shas: Vec<String> = mem
-        .all_object_shas()
-        .into_iter()
-        .filter(|s| !pre_shas.contains(s))
-        .collect();
+/// List the tags on the git remote, sorted by name.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/git/tags", session: tower_sessions::Session)]
+pub async fn list_tags() -> Result<Vec<TagInfo>, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
 
-    // Push
+    let mem = store::MemoryStore::new();
+
+    // Fetch
     let mem2 = mem.clone();
-    tokio::task::spawn_blocking(move || {
-        git_transport::push(&mem2, &remote_url, &ssh_key_pem, &branch, &new_shas)
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem2, &remote_url, &credential, Some(&branch))
     })
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?
     .map_err(|e| ServerFnError::new(e))?;
 
-    Ok(())
+    Ok(git_transport::list_tags(&mem)
+        .into_iter()
+        .map(|(name, sha)| {
+            let message = mem
+                .get_sync(&sha)
+                .as_deref()
+                .and_then(store::objects::parse_tag)
+                .map(|tag| tag.message)
+                .unwrap_or_default();
+            TagInfo {
+                name,
+                sha: sha.to_hex(),
+                message,
+            }
+        })
+        .collect())
 }
 
 #[cfg(not(feature = "server"))]
-#[post("/api/git/delete-namespace")]
-pub async fn delete_namespace_remote(path: String) -> Result<(), ServerFnError> {
+#[get("/api/git/tags")]
+pub async fn list_tags() -> Result<Vec<TagInfo>, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
 
-/// Sync a namespace (directory) to the git remote: fetch, create in memory, push.
+/// Export every note on the git remote as a ZIP archive (notes at their full
+/// paths, plus `typednotes.toml` at the root).
 #[cfg(feature = "server")]
-#[post("/api/git/sync-namespace", session: tower_sessions::Session)]
-pub async fn sync_namespace(path: String) -> Result<(), ServerFnError> {
-    let (_user_id, remote_url, ssh_key_pem, branch) = get_user_git_context(&session).await?;
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[get("/api/notes/export", session: tower_sessions::Session)]
+pub async fn export_notes_zip() -> Result<Vec<u8>, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+
+    // Fetch
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
+    })
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?
+    .map_err(|e| ServerFnError::new(e))?;
+
+    let notes = repo.list_notes().await;
+    let config = repo.get_config().await;
+    archive::build_notes_zip(&notes, &config).map_err(|e| ServerFnError::new(e))
+}
+
+#[cfg(not(feature = "server"))]
+#[get("/api/notes/export")]
+pub async fn export_notes_zip() -> Result<Vec<u8>, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Import the note files (`.md`, `.txt`, `.org`, …) of a ZIP archive into the
+/// user's notes and push them to the git remote.
+///
+/// Existing notes are only replaced when `overwrite` is set. Requires git sync
+/// to be configured, since the remote is where the server keeps notes.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/notes/import", session: tower_sessions::Session)]
+pub async fn import_notes(zip_bytes: Vec<u8>, overwrite: bool) -> Result<ImportReport, ServerFnError> {
+    let contents = archive::read_notes_zip(&zip_bytes).map_err(|e| ServerFnError::new(e))?;
+    let report = ImportReport {
+        errors: contents.errors,
+        ..Default::default()
+    };
+    write_imported_notes(&session, contents.notes, overwrite, report).await
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/notes/import")]
+pub async fn import_notes(zip_bytes: Vec<u8>, overwrite: bool) -> Result<ImportReport, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Import a ZIP of an Obsidian vault (see [`import::convert_obsidian_vault`]).
+///
+/// Folders become namespaces; embeds and plugin syntax that TypedNotes cannot
+/// show are listed in [`ImportReport::warnings`]. `overwrite` works as in
+/// [`import_notes`].
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/notes/import-obsidian", session: tower_sessions::Session)]
+pub async fn import_obsidian_vault(zip_bytes: Vec<u8>, overwrite: bool) -> Result<ImportReport, ServerFnError> {
+    let converted = import::convert_obsidian_vault(&zip_bytes).map_err(|e| ServerFnError::new(e))?;
+    let report = ImportReport {
+        skipped: converted.skipped,
+        errors: converted.errors,
+        warnings: converted.warnings,
+        source: converted.source,
+        ..Default::default()
+    };
+    write_imported_notes(&session, converted.notes, overwrite, report).await
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/notes/import-obsidian")]
+pub async fn import_obsidian_vault(zip_bytes: Vec<u8>, overwrite: bool) -> Result<ImportReport, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Import a Notion export ZIP (see [`import::convert_notion_export`]).
+///
+/// Sub-pages become namespaces and database CSVs are skipped; `overwrite`
+/// works as in [`import_notes`].
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/notes/import-notion", session: tower_sessions::Session)]
+pub async fn import_notion_zip(zip_bytes: Vec<u8>, overwrite: bool) -> Result<ImportReport, ServerFnError> {
+    let converted = import::convert_notion_export(&zip_bytes).map_err(|e| ServerFnError::new(e))?;
+    let report = ImportReport {
+        skipped: converted.skipped,
+        errors: converted.errors,
+        warnings: converted.warnings,
+        ..Default::default()
+    };
+    write_imported_notes(&session, converted.notes, overwrite, report).await
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/notes/import-notion")]
+pub async fn import_notion_zip(zip_bytes: Vec<u8>, overwrite: bool) -> Result<ImportReport, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Helper: fetch the user's remote, write `notes` (`(path, content)`) into it
+/// as a single commit and push, counting what was written into `report`.
+#[cfg(feature = "server")]
+async fn write_imported_notes(
+    session: &tower_sessions::Session,
+    notes: Vec<(String, String)>,
+    overwrite: bool,
+    mut report: ImportReport,
+) -> Result<ImportReport, ServerFnError> {
+    let (_user_id, remote_url, credential, branch) = get_user_git_context(session).await?;
 
     let mem = store::MemoryStore::new();
     let repo = store::Repository::new(mem.clone());
@@ -658,9 +2966,9 @@ pub async fn sync_namespace(path: String) -> Result<(), ServerFnError> {
     // Fetch
     let mem2 = mem.clone();
     let url = remote_url.clone();
-    let key = ssh_key_pem.clone();
+    let key = credential.clone();
     let branch2 = branch.clone();
-    tokio::task::spawn_blocking(move || git_transport::fetch(&mem2, &url, &key, Some(&branch2)))
+    spawn_blocking_in_span(move || fetch_cache::fetch(&mem2, &url, &key, Some(&branch2)))
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?
         .map_err(|e| ServerFnError::new(e))?;
@@ -668,69 +2976,191 @@ pub async fn sync_namespace(path: String) -> Result<(), ServerFnError> {
     let pre_shas: std::collections::HashSet<String> =
         mem.all_object_shas().into_iter().collect();
 
-    // Create namespace in memory
-    repo.create_namespace(&path).await;
+    for (path, content) in notes {
+        if !overwrite && repo.get_note(&path).await.is_some() {
+            report.skipped += 1;
+            continue;
+        }
+        repo.write_note_batch_raw(&path, content.as_bytes()).await;
+        report.imported += 1;
+    }
 
-    let new_shas: Vec<String> = mem
-        .all_object_shas()
-        .into_iter()
-        .filter(|s| !pre_shas.contains(s))
-        .collect();
+    if report.imported > 0 {
+        // One commit for the whole import
+        repo.commit_batch(format!("Import {} notes", report.imported)).await;
+        let new_shas: Vec<String> = mem
+            .all_object_shas()
+            .into_iter()
+            .filter(|s| !pre_shas.contains(s))
+            .collect();
+
+        // Push
+        let mem2 = mem.clone();
+        spawn_blocking_in_span(move || {
+            git_transport::push(&mem2, &remote_url, &credential, &branch, &new_shas)
+        })
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .map_err(|e| ServerFnError::new(e))?;
+    }
 
-    // Push
-    let mem2 = mem.clone();
-    tokio::task::spawn_blocking(move || {
-        git_transport::push(&mem2, &remote_url, &ssh_key_pem, &branch, &new_shas)
+    Ok(report)
+}
+
+/// Public base URL used to build share links.
+#[cfg(feature = "server")]
+const SHARE_BASE_URL: &str = "https://typednotes.org/shared";
+
+/// Longest lifetime a share link may have (30 days).
+#[cfg(feature = "server")]
+const MAX_SHARE_HOURS: u32 = 24 * 30;
+
+/// Create a read-only share link for a note that expires after
+/// `expires_hours` (1 hour to 30 days). Returns the public URL.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all, fields(user_id, remote_host))]
+#[post("/api/notes/share", session: tower_sessions::Session)]
+pub async fn share_note(path: String, expires_hours: u32) -> Result<String, ServerFnError> {
+    use crate::db::get_pool;
+    use rand::RngCore;
+
+    if !(1..=MAX_SHARE_HOURS).contains(&expires_hours) {
+        return Err(ServerFnError::new(format!(
+            "Expiry must be between 1 and {MAX_SHARE_HOURS} hours"
+        )));
+    }
+
+    // Make sure the note exists on the remote before handing out a link
+    let (user_id, remote_url, credential, branch) = get_user_git_context(&session).await?;
+    let mem = store::MemoryStore::new();
+    let repo = store::Repository::new(mem.clone());
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
     })
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?
     .map_err(|e| ServerFnError::new(e))?;
+    if repo.get_note(&path).await.is_none() {
+        return Err(ServerFnError::new(format!(
+            "{path} is not on the git remote yet: sync it before sharing"
+        )));
+    }
 
-    Ok(())
+    let mut token_bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut token_bytes);
+    let token = hex::encode(token_bytes);
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(i64::from(expires_hours));
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    sqlx::query(
+        "INSERT INTO shared_notes (token, user_id, note_path, expires_at) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(&token)
+    .bind(user_id)
+    .bind(&path)
+    .bind(expires_at)
+    .execute(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(format!("{SHARE_BASE_URL}/{token}"))
 }
 
 #[cfg(not(feature = "server"))]
-#[post("/api/git/sync-namespace")]
-pub async fn sync_namespace(path: String) -> Result<(), ServerFnError> {
+#[post("/api/notes/share")]
+pub async fn share_note(path: String, expires_hours: u32) -> Result<String, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
 
-/// Pull all notes and namespaces from the git remote.
+/// The token for the user's Atom feed at `/feeds/{token}`, created on the
+/// first call. Requires git sync to be set up.
+#[cfg(feature = "server")]
+#[tracing::instrument(skip_all)]
+#[post("/api/feed/token", session: tower_sessions::Session)]
+pub async fn get_feed_token() -> Result<String, ServerFnError> {
+    let user_id: Option<String> = session
+        .get(auth::SESSION_USER_ID_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let Some(user_id) = user_id else {
+        return Err(ServerFnError::new("Not authenticated"));
+    };
+    let user_uuid =
+        uuid::Uuid::parse_str(&user_id).map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    feed::feed_token(user_uuid).await.map_err(ServerFnError::new)
+}
+
+#[cfg(not(feature = "server"))]
+#[post("/api/feed/token")]
+pub async fn get_feed_token() -> Result<String, ServerFnError> {
+    Err(ServerFnError::new("Server only"))
+}
+
+/// Fetch a shared note by its link token. Public: no session required.
+///
+/// The note is read from the owner's git remote at request time, so the
+/// snapshot reflects the latest pushed version until the link expires.
 #[cfg(feature = "server")]
-#[get("/api/git/pull", session: tower_sessions::Session)]
-pub async fn pull_notes() -> Result<PullResult, ServerFnError> {
-    let (_user_id, remote_url, ssh_key_pem, branch) = get_user_git_context(&session).await?;
+#[tracing::instrument(skip_all)]
+#[get("/api/shared/:token")]
+pub async fn get_shared_note(token: String) -> Result<SharedNoteInfo, ServerFnError> {
+    use crate::db::get_pool;
+
+    let pool = get_pool()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let row: Option<(uuid::Uuid, String, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+        "SELECT user_id, note_path, expires_at FROM shared_notes WHERE token = $1",
+    )
+    .bind(&token)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    // Same message for unknown and expired links
+    let Some((user_id, path, expires_at)) = row.filter(|(_, _, exp)| *exp > chrono::Utc::now())
+    else {
+        return Err(ServerFnError::new("This link is invalid or has expired"));
+    };
 
+    let (remote_url, credential, branch) = get_git_context_for_user(user_id).await?;
     let mem = store::MemoryStore::new();
     let repo = store::Repository::new(mem.clone());
-
-    // Fetch
-    tokio::task::spawn_blocking(move || {
-        git_transport::fetch(&mem, &remote_url, &ssh_key_pem, Some(&branch))
+    spawn_blocking_in_span(move || {
+        fetch_cache::fetch(&mem, &remote_url, &credential, Some(&branch))
     })
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?
     .map_err(|e| ServerFnError::new(e))?;
 
-    // List notes from in-memory repo
-    let notes = repo.list_notes().await;
-    // List namespaces (includes empty directories with .gitkeep)
-    let namespaces = repo.list_namespaces().await;
+    let note = repo
+        .get_note(&path)
+        .await
+        .ok_or_else(|| ServerFnError::new("The shared note no longer exists"))?;
 
-    Ok(PullResult {
-        files: notes
-            .into_iter()
-            .map(|n| RemoteFile {
-                path: n.path,
-                content: n.note,
-            })
-            .collect(),
-        namespaces: namespaces.into_iter().map(|ns| ns.path).collect(),
+    let title = store::frontmatter::parse_frontmatter(&note.note)
+        .and_then(|meta| meta.title)
+        .unwrap_or_else(|| note.name.clone());
+    let rendered_html = if note.r#type == "markdown" {
+        render::render_markdown_html(&note.note)
+    } else {
+        render::render_text_html(&note.note)
+    };
+
+    Ok(SharedNoteInfo {
+        title,
+        content: note.note,
+        rendered_html,
+        expires_at: expires_at.timestamp(),
     })
 }
 
 #[cfg(not(feature = "server"))]
-#[get("/api/git/pull")]
-pub async fn pull_notes() -> Result<PullResult, ServerFnError> {
+#[get("/api/shared/:token")]
+pub async fn get_shared_note(token: String) -> Result<SharedNoteInfo, ServerFnError> {
     Err(ServerFnError::new("Server only"))
 }
@@ -0,0 +1,102 @@
+//! # Process-wide counters for the `/metrics` endpoint
+//!
+//! A handful of atomics updated by server functions and rendered in the
+//! Prometheus text exposition format by [`render_prometheus`]. The web crate
+//! serves the result at `GET /metrics`.
+//!
+//! | Metric | Type | Meaning |
+//! |--------|------|---------|
+//! | `sync_note_count` | counter | `sync_note` calls |
+//! | `sync_note_error_count` | counter | `sync_note` calls that returned an error |
+//! | `sync_note_duration_ms` | counter | Total time spent in `sync_note`, in milliseconds |
+//!
+//! Counters start at zero when the process starts.
+//!
+//! The endpoint is off unless [`METRICS_TOKEN`] is set, and then answers only
+//! requests carrying `Authorization: Bearer <token>` (see [`authorize`]).
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+/// Environment variable with the bearer token `/metrics` requires.
+pub const METRICS_TOKEN: &str = "METRICS_TOKEN";
+
+static SYNC_NOTE_COUNT: AtomicU64 = AtomicU64::new(0);
+static SYNC_NOTE_ERROR_COUNT: AtomicU64 = AtomicU64::new(0);
+static SYNC_NOTE_DURATION_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Record one finished `sync_note` call.
+pub fn record_sync_note(elapsed: Duration, ok: bool) {
+    SYNC_NOTE_COUNT.fetch_add(1, Ordering::Relaxed);
+    if !ok {
+        SYNC_NOTE_ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+    SYNC_NOTE_DURATION_MS.fetch_add(elapsed.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// All counters in the Prometheus text format.
+pub fn render_prometheus() -> String {
+    let metrics = [
+        ("sync_note_count", "sync_note calls.", &SYNC_NOTE_COUNT),
+        ("sync_note_error_count", "sync_note calls that failed.", &SYNC_NOTE_ERROR_COUNT),
+        ("sync_note_duration_ms", "Total time spent in sync_note, in milliseconds.", &SYNC_NOTE_DURATION_MS),
+    ];
+    let mut out = String::new();
+    for (name, help, value) in metrics {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} counter");
+        let _ = writeln!(out, "{name} {}", value.load(Ordering::Relaxed));
+    }
+    out
+}
+
+/// Whether a `/metrics` request with this `Authorization` header may read
+/// the counters: `None` if the endpoint is off ([`METRICS_TOKEN`] unset),
+/// otherwise whether the header carries the token.
+pub fn authorize(authorization: Option<&str>) -> Option<bool> {
+    let expected = std::env::var(METRICS_TOKEN).ok().filter(|token| !token.is_empty())?;
+    Some(bearer_matches(&expected, authorization))
+}
+
+fn bearer_matches(expected: &str, authorization: Option<&str>) -> bool {
+    let Some(token) = authorization.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    // Comparing digests keeps the time taken independent of the token
+    Sha256::digest(token.trim().as_bytes()) == Sha256::digest(expected.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus() {
+        record_sync_note(Duration::from_millis(40), true);
+        record_sync_note(Duration::from_millis(60), false);
+
+        let text = render_prometheus();
+        assert!(text.contains("# TYPE sync_note_count counter\n"));
+        let value = |name: &str| -> u64 {
+            text.lines()
+                .find_map(|l| l.strip_prefix(&format!("{name} ")))
+                .and_then(|v| v.parse().ok())
+                .unwrap()
+        };
+        // Counters are global, so other tests may have added to them
+        assert!(value("sync_note_count") >= 2);
+        assert!(value("sync_note_error_count") >= 1);
+        assert!(value("sync_note_duration_ms") >= 100);
+    }
+
+    #[test]
+    fn test_bearer_matches() {
+        assert!(bearer_matches("s3cret", Some("Bearer s3cret")));
+        assert!(!bearer_matches("s3cret", Some("Bearer other")));
+        assert!(!bearer_matches("s3cret", Some("s3cret")));
+        assert!(!bearer_matches("s3cret", None));
+    }
+}
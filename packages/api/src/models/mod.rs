@@ -15,9 +15,10 @@
 //!
 //! - [`User`] (server only) — full user row from the `users` table.
 //! - [`UserInfo`] — lightweight, serializable user data for the client.
+//! - [`ExtendedUserInfo`] — [`UserInfo`] plus git setup and note count, loaded on startup.
 
 mod user;
 
 #[cfg(feature = "server")]
 pub use user::User;
-pub use user::UserInfo;
+pub use user::{ExtendedUserInfo, UserInfo};
@@ -9,9 +9,11 @@
 //!
 //! - `id` — primary key (`UUID v4`).
 //! - `email`, `name`, `avatar_url` — profile fields populated during OAuth or registration.
-//! - `provider` / `provider_id` — identify the auth provider (`"github"`, `"google"`, or
+//! - `provider` / `provider_id` — identify the auth provider (`"github"`, `"google"`, `"gitlab"`, or
 //!   `"local"` for email+password accounts where `provider_id` equals the email).
 //! - `password_hash` — Argon2 hash, present only for `"local"` accounts.
+//! - `is_admin` — may create invite codes (see [`crate::invites`]); set by hand in the database.
+//! - `email_verified` — the address was confirmed (see [`crate::email`]); always set for OAuth accounts.
 //! - `created_at` / `updated_at` — audit timestamps.
 //!
 //! The [`User::to_info`] method projects this into a [`UserInfo`].
@@ -19,10 +21,16 @@
 //! ## [`UserInfo`]
 //!
 //! A client-safe subset that is `Serialize + Deserialize + PartialEq` and can cross the
-//! server/client boundary via Dioxus server functions. It omits the password hash and
-//! timestamps and converts the `Uuid` to a `String` so it works in WASM.
+//! server/client boundary via Dioxus server functions. It omits the password hash,
+//! converts the `Uuid` to a `String` and `updated_at` to a Unix timestamp so it works
+//! in WASM.
 //! The helper [`UserInfo::display_name`] returns the user's name or falls back to their
 //! email address.
+//!
+//! ## [`ExtendedUserInfo`]
+//!
+//! [`UserInfo`] plus what the app wants to know on startup: whether git sync is set
+//! up, the note count recorded by the last sync, and the previous visit.
 
 use serde::{Deserialize, Serialize};
 
@@ -44,6 +52,8 @@ pub struct User {
     pub provider: String,
     pub provider_id: String,
     pub password_hash: Option<String>,
+    pub is_admin: bool,
+    pub email_verified: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -58,6 +68,9 @@ impl User {
             name: self.name.clone(),
             avatar_url: self.avatar_url.clone(),
             provider: self.provider.clone(),
+            updated_at: Some(self.updated_at.timestamp()),
+            is_admin: self.is_admin,
+            email_verified: self.email_verified,
         }
     }
 }
@@ -70,6 +83,27 @@ pub struct UserInfo {
     pub name: Option<String>,
     pub avatar_url: Option<String>,
     pub provider: String,
+    /// Last profile change (Unix seconds).
+    #[serde(default)]
+    pub updated_at: Option<i64>,
+    /// May create invite codes.
+    #[serde(default)]
+    pub is_admin: bool,
+    /// Confirmed the email address; git sync stays off until then.
+    #[serde(default)]
+    pub email_verified: bool,
+}
+
+/// Profile plus account stats, returned by `get_current_user_extended`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExtendedUserInfo {
+    pub user: UserInfo,
+    /// A remote URL and credential are saved in Settings.
+    pub git_configured: bool,
+    /// Notes in the repository after the last sync, if one has happened.
+    pub note_count: Option<usize>,
+    /// Previous visit (Unix seconds), before the one that returned this.
+    pub last_seen_at: Option<i64>,
 }
 
 impl UserInfo {
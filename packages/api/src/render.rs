@@ -0,0 +1,282 @@
+//! # Markdown rendering
+//!
+//! Renders note bodies to HTML for pages that are served to people who are
+//! not the note's author (shared links) and for the editor's preview pane.
+//! Because the output is injected into our own origin, the renderer is
+//! conservative:
+//!
+//! - raw HTML blocks and inline HTML are escaped and shown as text;
+//! - links and images with `javascript:`, `vbscript:` or `data:` URLs are
+//!   neutralised to `#`;
+//! - the YAML frontmatter block is dropped (see [`store::frontmatter`]).
+//!
+//! [`render_note_document`] wraps a rendered note in a standalone page for
+//! download; on the server, [`print_to_pdf`] turns such a page into a PDF.
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+/// Render a markdown note to sanitised HTML.
+pub fn render_markdown_html(content: &str) -> String {
+    let body = store::frontmatter::strip_frontmatter(content);
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let events = Parser::new_ext(body, options).map(|event| match event {
+        Event::Html(raw) => Event::Text(raw),
+        Event::Start(Tag::Link(kind, url, title)) => Event::Start(Tag::Link(kind, safe_url(url), title)),
+        Event::End(Tag::Link(kind, url, title)) => Event::End(Tag::Link(kind, safe_url(url), title)),
+        Event::Start(Tag::Image(kind, url, title)) => Event::Start(Tag::Image(kind, safe_url(url), title)),
+        Event::End(Tag::Image(kind, url, title)) => Event::End(Tag::Image(kind, safe_url(url), title)),
+        other => other,
+    });
+
+    let mut out = String::with_capacity(body.len() * 3 / 2);
+    html::push_html(&mut out, events);
+    out
+}
+
+/// Escape plain text as a `<pre>` block (for non-markdown notes).
+pub fn render_text_html(content: &str) -> String {
+    format!("<pre>{}</pre>", escape_html(content))
+}
+
+/// KaTeX assets linked from exported documents; `$...$` and `$$...$$` are
+/// typeset when the page is opened (and before it is printed to PDF).
+const KATEX_CSS: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.28/dist/katex.min.css";
+const KATEX_JS: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.28/dist/katex.min.js";
+const KATEX_AUTO_RENDER_JS: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.28/dist/contrib/auto-render.min.js";
+
+/// Stylesheet of exported documents, close to the shared-note page.
+const EXPORT_CSS: &str = "body{max-width:46rem;margin:0 auto;padding:3rem 1.5rem;\
+font-family:system-ui,-apple-system,'Segoe UI',sans-serif;line-height:1.7;color:#1f2328}\
+h1,h2,h3{margin:1.5em 0 .5em;line-height:1.3}\
+pre{padding:.75rem 1rem;overflow-x:auto;border-radius:6px;background:#f6f8fa}\
+code{font-size:.875em}\
+blockquote{margin-left:0;padding-left:1rem;border-left:3px solid #d0d7de;color:#59636e}\
+table{border-collapse:collapse}th,td{padding:.25rem .75rem;border:1px solid #d0d7de}\
+img{max-width:100%}@media print{body{padding:0}}";
+
+/// A standalone HTML document for downloading a note: its sanitised body
+/// (see [`render_markdown_html`]) under an `<h1>` title, with an inline
+/// stylesheet and KaTeX loaded from the CDN.
+pub fn render_note_document(title: &str, content: &str, note_type: &str) -> String {
+    let body = if note_type == "markdown" {
+        render_markdown_html(content)
+    } else {
+        render_text_html(content)
+    };
+    let title = escape_html(title);
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<link rel="stylesheet" href="{KATEX_CSS}">
+<style>{EXPORT_CSS}</style>
+<script defer src="{KATEX_JS}"></script>
+<script defer src="{KATEX_AUTO_RENDER_JS}" onload="renderMathInElement(document.body,{{delimiters:[{{left:'$$',right:'$$',display:true}},{{left:'$',right:'$',display:false}}]}})"></script>
+</head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+"#
+    )
+}
+
+/// Environment variable that, when set, starts Chromium with `--no-sandbox`.
+/// Chromium's sandbox cannot start as root or in most containers, so the
+/// container image sets it; elsewhere leave it unset.
+#[cfg(feature = "server")]
+pub const CHROME_NO_SANDBOX: &str = "CHROME_NO_SANDBOX";
+
+/// URLs the page being printed may load: the KaTeX assets (and the fonts
+/// under the same prefix). Every other request — note images included — is
+/// failed, so a note cannot make the server fetch internal addresses.
+#[cfg(feature = "server")]
+const PDF_ALLOWED_PREFIX: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.28/";
+
+/// How long [`print_to_pdf`] waits for the page (and KaTeX) to load before
+/// printing it as it is.
+#[cfg(feature = "server")]
+const PDF_LOAD_TIMEOUT_MS: u32 = 10_000;
+
+/// The headless Chromium [`print_to_pdf`] uses, launched on first use and
+/// shared by every export; `None` until then or after it died.
+#[cfg(feature = "server")]
+static BROWSER: tokio::sync::Mutex<Option<std::sync::Arc<chromiumoxide::Browser>>> =
+    tokio::sync::Mutex::const_new(None);
+
+/// The shared browser, launching it if there is none yet.
+#[cfg(feature = "server")]
+async fn shared_browser() -> Result<std::sync::Arc<chromiumoxide::Browser>, String> {
+    use chromiumoxide::browser::{Browser, BrowserConfig};
+    use futures::StreamExt;
+
+    let mut browser = BROWSER.lock().await;
+    if let Some(browser) = browser.as_ref() {
+        return Ok(browser.clone());
+    }
+
+    let mut config = BrowserConfig::builder()
+        .enable_request_intercept()
+        .disable_cache()
+        .arg("--disable-dev-shm-usage");
+    if std::env::var_os(CHROME_NO_SANDBOX).is_some() {
+        config = config.no_sandbox();
+    }
+    let (launched, mut handler) = Browser::launch(config.build()?)
+        .await
+        .map_err(|e| format!("launch Chromium: {e}"))?;
+    // The CDP connection only makes progress while its handler is polled;
+    // once it ends the browser is gone and the next export relaunches it
+    tokio::spawn(async move {
+        while let Some(event) = handler.next().await {
+            if event.is_err() {
+                break;
+            }
+        }
+        BROWSER.lock().await.take();
+    });
+    let launched = std::sync::Arc::new(launched);
+    *browser = Some(launched.clone());
+    Ok(launched)
+}
+
+/// Print an HTML document to PDF (A4/Letter per Chromium's defaults) in a new
+/// tab of the shared headless Chromium.
+///
+/// Network requests other than the KaTeX assets are blocked, and the page is
+/// printed once it has loaded (or after [`PDF_LOAD_TIMEOUT_MS`]), so math is
+/// typeset.
+#[cfg(feature = "server")]
+pub async fn print_to_pdf(html: &str) -> Result<Vec<u8>, String> {
+    use chromiumoxide::cdp::browser_protocol::fetch::{ContinueRequestParams, EventRequestPaused, FailRequestParams};
+    use chromiumoxide::cdp::browser_protocol::network::ErrorReason;
+    use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+    use futures::StreamExt;
+
+    let browser = shared_browser().await?;
+    let page = match browser.new_page("about:blank").await {
+        Ok(page) => page,
+        Err(e) => {
+            // Most likely Chromium died; drop it so the next call relaunches
+            BROWSER.lock().await.take();
+            return Err(format!("open tab: {e}"));
+        }
+    };
+
+    let mut paused = page.event_listener::<EventRequestPaused>().await.map_err(|e| e.to_string())?;
+    let gate = page.clone();
+    let interception = tokio::spawn(async move {
+        while let Some(event) = paused.next().await {
+            let url = &event.request.url;
+            let allowed = url.starts_with(PDF_ALLOWED_PREFIX) || url.starts_with("data:") || url == "about:blank";
+            let _ = if allowed {
+                gate.execute(ContinueRequestParams::new(event.request_id.clone())).await.map(|_| ())
+            } else {
+                tracing::debug!(%url, "blocked request while printing to PDF");
+                gate.execute(FailRequestParams::new(event.request_id.clone(), ErrorReason::BlockedByClient))
+                    .await
+                    .map(|_| ())
+            };
+        }
+    });
+
+    let result = async {
+        page.set_content(html).await.map_err(|e| e.to_string())?;
+        // Deferred scripts (KaTeX) run before `load`
+        let loaded = format!(
+            "new Promise(function(resolve) {{
+                if (document.readyState === 'complete') resolve(true);
+                else window.addEventListener('load', function() {{ resolve(true); }});
+                setTimeout(function() {{ resolve(false); }}, {PDF_LOAD_TIMEOUT_MS});
+            }})"
+        );
+        page.evaluate_expression(loaded).await.map_err(|e| e.to_string())?;
+        page.pdf(PrintToPdfParams::default()).await.map_err(|e| e.to_string())
+    }
+    .await;
+
+    interception.abort();
+    let _ = page.close().await;
+    result.map_err(|e| format!("print to PDF: {e}"))
+}
+
+/// Escape `&`, `<`, `>` and `"` for use in element content or attributes.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Replace script-capable URL schemes with a harmless `#`.
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    let scheme: String = url
+        .trim_start()
+        .chars()
+        .take_while(|c| *c != ':')
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let has_scheme = url.contains(':');
+    if has_scheme && matches!(scheme.as_str(), "javascript" | "vbscript" | "data") {
+        CowStr::Borrowed("#")
+    } else {
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown_basic() {
+        let html = render_markdown_html("---\ntags: [a]\n---\n# Title\n\n- [x] done\n");
+        assert!(html.starts_with("<h1>Title</h1>"));
+        assert!(html.contains("checkbox"));
+        assert!(!html.contains("tags"));
+    }
+
+    #[test]
+    fn test_render_markdown_escapes_html_and_scripts() {
+        let html = render_markdown_html(
+            "<script>alert(1)</script>\n\nHi <img src=x onerror=alert(1)>\n\n[x](javascript:alert(1)) [ok](https://example.com)",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<img"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("href=\"https://example.com\""));
+    }
+
+    #[test]
+    fn test_render_note_document() {
+        let html = render_note_document("Q&A <1>", "Some **bold** text", "markdown");
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Q&amp;A &lt;1&gt;</title>"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("katex.min.css"));
+
+        let html = render_note_document("t", "<b>", "text");
+        assert!(html.contains("<pre>&lt;b&gt;</pre>"));
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(render_text_html("a < b & \"c\""), "<pre>a &lt; b &amp; &quot;c&quot;</pre>");
+    }
+}
@@ -0,0 +1,160 @@
+//! # Full-text note search
+//!
+//! A deliberately simple case-insensitive substring search over note paths and
+//! contents. It scans every note on each query, which is fine for repos of a
+//! few hundred notes; a proper inverted index can replace [`search_notes_in`]
+//! later without changing [`SearchResult`].
+//!
+//! The module is available on every target so the UI can run the same search
+//! over its local notes when there is no git remote to query.
+
+use serde::{Deserialize, Serialize};
+use store::TypedNoteInfo;
+
+/// Maximum snippet length, in characters.
+pub const SNIPPET_LEN: usize = 200;
+
+/// Characters of context kept before the first match in a snippet.
+const SNIPPET_LEAD: usize = 60;
+
+/// A note matching a search query.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchResult {
+    pub path: String,
+    pub name: String,
+    pub namespace: Option<String>,
+    /// Up to [`SNIPPET_LEN`] characters of content around the first match
+    /// (or from the start of the note if only the path matched).
+    pub snippet: String,
+}
+
+/// Search `notes` for `query`, matching case-insensitively against the path
+/// and the content (path only for encrypted notes). Results keep the order of `notes`; an empty query matches
+/// nothing.
+pub fn search_notes_in(notes: &[TypedNoteInfo], query: &str) -> Vec<SearchResult> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    notes
+        .iter()
+        .filter_map(|note| {
+            // Encrypted notes are ciphertext here: match them by path only
+            let content = if note.encrypted { "" } else { note.note.as_str() };
+            let content_match = find_case_insensitive(content, &needle);
+            if content_match.is_none() && !note.path.to_lowercase().contains(&needle) {
+                return None;
+            }
+            Some(SearchResult {
+                path: note.path.clone(),
+                name: note.name.clone(),
+                namespace: note.namespace.clone(),
+                snippet: snippet_around(content, content_match.unwrap_or(0)),
+            })
+        })
+        .collect()
+}
+
+/// The snippet [`search_notes_in`] would show for `content`: text around the
+/// first case-insensitive occurrence of `query`, or the start of `content`.
+pub fn snippet_for(content: &str, query: &str) -> String {
+    let at = find_case_insensitive(content, &query.trim().to_lowercase());
+    snippet_around(content, at.unwrap_or(0))
+}
+
+/// Byte offset in `haystack` of the first case-insensitive occurrence of
+/// `needle_lower` (which must already be lowercase).
+fn find_case_insensitive(haystack: &str, needle_lower: &str) -> Option<usize> {
+    // Lowercasing can change byte lengths, so remember where each byte of the
+    // lowered string came from in the original.
+    let mut lower = String::with_capacity(haystack.len());
+    let mut origin = Vec::with_capacity(haystack.len());
+    for (i, c) in haystack.char_indices() {
+        for lc in c.to_lowercase() {
+            lower.push(lc);
+            origin.resize(lower.len(), i);
+        }
+    }
+    lower.find(needle_lower).map(|pos| origin[pos])
+}
+
+/// Single-line excerpt of `content` starting a little before byte offset `at`.
+fn snippet_around(content: &str, at: usize) -> String {
+    let start = content[..at]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_LEAD - 1)
+        .map_or(0, |(i, _)| i);
+    content[start..]
+        .chars()
+        .take(SNIPPET_LEN)
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::note;
+
+    #[test]
+    fn test_search_matches_path_and_content() {
+        let notes = vec![
+            note("work/Roadmap.md", "Q3 goals"),
+            note("ideas.md", "Build a ROADMAP viewer"),
+            note("misc.md", "nothing here"),
+        ];
+
+        let results = search_notes_in(&notes, "roadmap");
+        let paths: Vec<_> = results.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, ["work/Roadmap.md", "ideas.md"]);
+        assert_eq!(results[0].namespace.as_deref(), Some("work"));
+        assert_eq!(results[0].snippet, "Q3 goals");
+        assert_eq!(results[1].snippet, "Build a ROADMAP viewer");
+
+        assert!(search_notes_in(&notes, "   ").is_empty());
+    }
+
+    #[test]
+    fn test_search_skips_encrypted_content() {
+        let mut secret = note("secret.md.enc", "roadmap ciphertext");
+        secret.encrypted = true;
+        let notes = vec![secret];
+
+        assert!(search_notes_in(&notes, "roadmap").is_empty());
+        let results = search_notes_in(&notes, "secret");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet, "");
+    }
+
+    #[test]
+    fn test_search_snippet_window() {
+        let content = format!("{}needle{}", "a".repeat(500), "bc\n".repeat(500));
+        let results = search_notes_in(&[note("long.md", &content)], "NEEDLE");
+        let snippet = &results[0].snippet;
+
+        assert_eq!(snippet.chars().count(), SNIPPET_LEN);
+        assert!(snippet.starts_with(&"a".repeat(SNIPPET_LEAD)));
+        assert!(snippet[SNIPPET_LEAD..].starts_with("needle"));
+        assert!(!snippet.contains('\n'));
+    }
+
+    #[test]
+    fn test_snippet_for() {
+        assert_eq!(snippet_for("first line\nsecond", "SECOND"), "first line second");
+        assert_eq!(snippet_for("no match", "zzz"), "no match");
+        let content = format!("{}needle", "a".repeat(500));
+        assert!(snippet_for(&content, "needle").ends_with("needle"));
+    }
+
+    #[test]
+    fn test_find_case_insensitive_multibyte() {
+        // 'İ' lowercases to two chars, shifting byte offsets in the lowered text
+        let haystack = "İİ Straße";
+        let at = find_case_insensitive(haystack, "straße").unwrap();
+        assert_eq!(&haystack[at..], "Straße");
+    }
+}
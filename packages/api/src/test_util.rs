@@ -0,0 +1,21 @@
+//! Fixtures shared by the unit tests of this crate.
+
+use store::TypedNoteInfo;
+
+/// A markdown note at `path`, with its name and namespace taken from the path.
+pub(crate) fn note(path: &str, content: &str) -> TypedNoteInfo {
+    let (namespace, file) = match path.rsplit_once('/') {
+        Some((ns, file)) => (Some(ns.to_string()), file),
+        None => (None, path),
+    };
+    TypedNoteInfo {
+        path: path.to_string(),
+        name: file.trim_end_matches(".md").to_string(),
+        namespace,
+        r#type: "markdown".to_string(),
+        note: content.to_string(),
+        sha: String::new(),
+        metadata: None,
+        encrypted: false,
+    }
+}
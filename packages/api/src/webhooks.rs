@@ -0,0 +1,302 @@
+//! # Outgoing webhooks for git sync events
+//!
+//! Users register URLs in Settings (`register_webhook`) to be told when their
+//! notes change on the remote. Each row of `user_webhooks` holds the URL, the
+//! subscribed [`EVENTS`] (a JSONB array) and a random signing secret, sealed
+//! with [`crate::crypto`] like the stored OAuth tokens. Rows written before
+//! the secret was encrypted still carry it in plain text and keep working.
+//!
+//! Webhook URLs must be `https://` and resolve only to public addresses, so a
+//! user cannot point the server at its own network. The host is resolved when
+//! the webhook is registered and again for every delivery, and the request
+//! goes to the address checked then, so a DNS answer that changes in between
+//! cannot redirect it.
+//!
+//! After a successful push or pull the server function calls [`notify`], which
+//! spawns a task and POSTs a JSON [`WebhookPayload`] to every matching URL. The
+//! request carries two headers:
+//!
+//! - `X-TypedNotes-Event` — the event name.
+//! - `X-TypedNotes-Signature` — `sha256=<hex>`, the HMAC-SHA256 of the body keyed
+//!   with the webhook's secret, so receivers can check where it came from.
+//!
+//! Delivery is fire-and-forget: failures are not retried and never reach the
+//! caller of the server function.
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::crypto;
+use crate::db::get_pool;
+
+/// A note was written to the remote (`sync_note`, `batch_sync_notes`).
+pub const NOTE_SYNCED: &str = "note.synced";
+/// A note was deleted on the remote (`delete_note_remote`).
+pub const NOTE_DELETED: &str = "note.deleted";
+/// The user pulled all notes from the remote (`pull_notes`).
+pub const NOTES_PULLED: &str = "notes.pulled";
+
+/// Every event a webhook can subscribe to.
+pub const EVENTS: [&str; 3] = [NOTE_SYNCED, NOTE_DELETED, NOTES_PULLED];
+
+/// Most webhooks one user may register.
+pub const MAX_WEBHOOKS_PER_USER: i64 = 10;
+
+/// How long to wait for a receiver before giving up.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of a webhook request.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event: String,
+    /// Unix seconds.
+    pub timestamp: i64,
+    /// Note paths the event is about (every pulled note for `notes.pulled`).
+    pub paths: Vec<String>,
+}
+
+/// A fresh 32-byte signing secret, hex encoded.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// `sha256=<hex>` signature of `body` for the `X-TypedNotes-Signature` header.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Encrypt a signing secret for the `secret_enc`/`secret_nonce` columns.
+pub fn seal_secret(secret: &str) -> Result<(Vec<u8>, Vec<u8>), String> {
+    crypto::encrypt_ssh_key(secret.as_bytes())
+}
+
+/// The signing secret of a stored row: decrypted, or the plain-text `secret`
+/// of a row from before encryption.
+fn open_secret(
+    plain: Option<String>,
+    enc: Option<Vec<u8>>,
+    nonce: Option<Vec<u8>>,
+) -> Result<String, String> {
+    match (enc, nonce) {
+        (Some(enc), Some(nonce)) => {
+            String::from_utf8(crypto::decrypt_ssh_key(&enc, &nonce)?).map_err(|e| e.to_string())
+        }
+        _ => plain.ok_or_else(|| "Webhook has no secret".to_string()),
+    }
+}
+
+/// Check a webhook URL and event list before storing them.
+///
+/// This only looks at the text; [`resolve`] checks where the URL leads.
+pub fn validate(url: &str, events: &[String]) -> Result<(), String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL: {e}"))?;
+    if parsed.scheme() != "https" || parsed.host_str().is_none() {
+        return Err("Webhook URL must be an https address".to_string());
+    }
+    if events.is_empty() {
+        return Err("Choose at least one event".to_string());
+    }
+    if let Some(unknown) = events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+        return Err(format!("Unknown event: {unknown}"));
+    }
+    Ok(())
+}
+
+/// Whether `ip` is a public unicast address a webhook may be sent to.
+///
+/// Loopback, private, link-local, shared (CGNAT), documentation, multicast
+/// and other reserved ranges are refused, as are IPv6 addresses that embed
+/// such an IPv4 address.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_unspecified()
+                || v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0)
+                || (a == 198 && (18..20).contains(&b)))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_unspecified()
+                || v6.is_loopback()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || first == 0x2001 && v6.segments()[1] == 0x0db8
+                || first == 0x0064 && v6.segments()[1] == 0xff9b)
+        }
+    }
+}
+
+/// Resolve the host of an `https://` webhook URL and pick the address to
+/// deliver to. Fails unless every address the host resolves to is public, so
+/// a name with both a public and a private answer is refused too.
+pub async fn resolve(url: &str) -> Result<(String, SocketAddr), String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL: {e}"))?;
+    if parsed.scheme() != "https" {
+        return Err("Webhook URL must be an https address".to_string());
+    }
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| "Webhook URL has no host".to_string())?
+        .to_string();
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    // IPv6 literals keep their brackets in `host_str`
+    let lookup = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup, port))
+        .await
+        .map_err(|e| format!("Could not resolve {host}: {e}"))?
+        .collect();
+    if addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err(format!("{host} resolves to a private or reserved address"));
+    }
+    let addr = addrs
+        .first()
+        .copied()
+        .ok_or_else(|| format!("Could not resolve {host}"))?;
+    Ok((host, addr))
+}
+
+/// Send `event` about `paths` to the user's webhooks in the background.
+pub fn notify(user_id: Uuid, event: &'static str, paths: Vec<String>) {
+    tokio::spawn(async move {
+        let _ = deliver(user_id, event, paths).await;
+    });
+}
+
+async fn deliver(user_id: Uuid, event: &str, paths: Vec<String>) -> Result<(), String> {
+    let pool = get_pool().await.map_err(|e| e.to_string())?;
+    let hooks: Vec<(String, Option<String>, Option<Vec<u8>>, Option<Vec<u8>>)> = sqlx::query_as(
+        "SELECT url, secret, secret_enc, secret_nonce FROM user_webhooks
+         WHERE user_id = $1 AND events ? $2",
+    )
+    .bind(user_id)
+    .bind(event)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if hooks.is_empty() {
+        return Ok(());
+    }
+
+    let payload = WebhookPayload {
+        event: event.to_string(),
+        timestamp: chrono::Utc::now().timestamp(),
+        paths,
+    };
+    let body = serde_json::to_vec(&payload).map_err(|e| e.to_string())?;
+
+    for (url, plain, enc, nonce) in hooks {
+        let secret = match open_secret(plain, enc, nonce) {
+            Ok(secret) => secret,
+            Err(e) => {
+                tracing::warn!(error = %e, "skipping webhook whose secret cannot be read");
+                continue;
+            }
+        };
+        // Checked again here: the name may resolve differently than at registration
+        let (host, addr) = match resolve(&url).await {
+            Ok(target) => target,
+            Err(e) => {
+                tracing::warn!(error = %e, "skipping webhook");
+                continue;
+            }
+        };
+        // Pin the checked address so reqwest does not look the name up again
+        let Ok(client) = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .build()
+        else {
+            continue;
+        };
+        let _ = client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "TypedNotes-Webhook")
+            .header("X-TypedNotes-Event", event)
+            .header("X-TypedNotes-Signature", sign(&secret, &body))
+            .body(body.clone())
+            .send()
+            .await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_known_hmac() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_validate_webhook() {
+        let events = vec![NOTE_SYNCED.to_string()];
+        assert!(validate("https://example.com/hook", &events).is_ok());
+        assert!(validate("http://example.com/hook", &events).is_err());
+        assert!(validate("ftp://example.com/hook", &events).is_err());
+        assert!(validate("not a url", &events).is_err());
+        assert!(validate("https://example.com/hook", &[]).is_err());
+        assert!(validate("https://example.com/hook", &["note.renamed".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_open_legacy_secret() {
+        assert_eq!(open_secret(Some("s3cret".to_string()), None, None).unwrap(), "s3cret");
+        assert!(open_secret(None, None, None).is_err());
+    }
+}
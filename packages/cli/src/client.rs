@@ -0,0 +1,125 @@
+//! # HTTP client for the server functions
+//!
+//! Server functions are plain HTTP endpoints: `#[post]` functions take their
+//! arguments as a JSON object, `#[get]` functions as query parameters, and both
+//! answer with JSON. [`Client`] wraps a blocking `reqwest` client whose cookie
+//! jar carries the `tower_sessions` cookie, so authenticated calls work once
+//! `tn login` has saved it with [`Client::save_session`].
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::config::Config;
+
+pub struct Client {
+    http: reqwest::blocking::Client,
+    jar: Arc<Jar>,
+    base: Url,
+}
+
+impl Client {
+    /// A client for `config.server_url`, with the saved session loaded.
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let base = Url::parse(&config.server_url)
+            .map_err(|e| format!("Invalid server URL {}: {e}", config.server_url))?;
+
+        let jar = Arc::new(Jar::default());
+        if let Ok(saved) = fs::read_to_string(&config.session_path) {
+            for cookie in saved.split(';').map(str::trim).filter(|c| !c.is_empty()) {
+                jar.add_cookie_str(cookie, &base);
+            }
+        }
+
+        let http = reqwest::blocking::Client::builder()
+            .cookie_provider(jar.clone())
+            .user_agent(concat!("tn/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self { http, jar, base })
+    }
+
+    /// Call a `#[get]` server function with `query` parameters.
+    pub fn get<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<T, String> {
+        self.send(self.http.get(self.url(endpoint)?).query(query))
+    }
+
+    /// Call a `#[post]` server function with `args` as the JSON body.
+    pub fn post<T: DeserializeOwned>(&self, endpoint: &str, args: Value) -> Result<T, String> {
+        self.send(self.http.post(self.url(endpoint)?).json(&args))
+    }
+
+    /// Write the session cookie to `path`, readable by the owner only.
+    pub fn save_session(&self, path: &Path) -> Result<(), String> {
+        let cookies = self
+            .jar
+            .cookies(&self.base)
+            .and_then(|header| header.to_str().ok().map(str::to_string))
+            .ok_or("Server did not set a session cookie")?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        fs::write(path, cookies).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn url(&self, endpoint: &str) -> Result<Url, String> {
+        self.base.join(endpoint).map_err(|e| e.to_string())
+    }
+
+    fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, String> {
+        let response = request
+            .send()
+            .map_err(|e| format!("Could not reach {}: {e}", self.base))?;
+        decode(response)
+    }
+}
+
+fn decode<T: DeserializeOwned>(response: Response) -> Result<T, String> {
+    let status = response.status();
+    let body = response.text().map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(error_message(&body).unwrap_or_else(|| format!("{status}: {}", body.trim())));
+    }
+    serde_json::from_str(&body).map_err(|e| format!("Unexpected response from server: {e}"))
+}
+
+/// The `message` of a serialized `ServerFnError`, which may sit at the top
+/// level or inside the variant object.
+fn error_message(body: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let message = |v: &Value| v.get("message").and_then(Value::as_str).map(str::to_string);
+    message(&value).or_else(|| value.as_object()?.values().find_map(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(
+            error_message(r#"{"message":"Not authenticated"}"#).as_deref(),
+            Some("Not authenticated")
+        );
+        assert_eq!(
+            error_message(r#"{"ServerError":{"message":"Server only","code":500}}"#).as_deref(),
+            Some("Server only")
+        );
+        assert_eq!(error_message("Internal Server Error"), None);
+        assert_eq!(error_message(r#"{"code":500}"#), None);
+    }
+}
@@ -0,0 +1,86 @@
+//! # CLI configuration
+//!
+//! Everything `tn` keeps on disk lives in `~/.config/typednotes/` (or
+//! `$XDG_CONFIG_HOME/typednotes/`):
+//!
+//! ```text
+//! ~/.config/typednotes/
+//! ├── config.toml   # optional, `server_url = "https://..."`
+//! └── session       # session cookie written by `tn login`
+//! ```
+//!
+//! The server URL is taken from `TN_SERVER_URL` first, then `config.toml`,
+//! then [`DEFAULT_SERVER_URL`].
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// Server used when neither `TN_SERVER_URL` nor `config.toml` name one.
+pub const DEFAULT_SERVER_URL: &str = "http://localhost:8080";
+
+/// Contents of `config.toml`. Every key is optional.
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    server_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub server_url: String,
+    /// Where the session cookie is stored between runs.
+    pub session_path: PathBuf,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, String> {
+        let dir = config_dir()?;
+        let file = match fs::read_to_string(dir.join("config.toml")) {
+            Ok(text) => parse_config(&text)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ConfigFile::default(),
+            Err(e) => return Err(format!("Failed to read config.toml: {e}")),
+        };
+
+        let server_url = std::env::var("TN_SERVER_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .or(file.server_url)
+            .unwrap_or_else(|| DEFAULT_SERVER_URL.to_string());
+
+        Ok(Self {
+            server_url: server_url.trim_end_matches('/').to_string(),
+            session_path: dir.join("session"),
+        })
+    }
+}
+
+fn parse_config(text: &str) -> Result<ConfigFile, String> {
+    toml::from_str(text).map_err(|e| format!("Invalid config.toml: {e}"))
+}
+
+/// `$XDG_CONFIG_HOME/typednotes`, falling back to `$HOME/.config/typednotes`.
+fn config_dir() -> Result<PathBuf, String> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let home = std::env::var_os("HOME").ok_or("HOME not set")?;
+            PathBuf::from(home).join(".config")
+        }
+    };
+    Ok(base.join("typednotes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let file = parse_config("server_url = \"https://notes.example.com\"\n").unwrap();
+        assert_eq!(file.server_url.as_deref(), Some("https://notes.example.com"));
+
+        assert!(parse_config("").unwrap().server_url.is_none());
+        assert!(parse_config("server_url = 3").is_err());
+    }
+}
@@ -0,0 +1,420 @@
+//! # `tn` — TypedNotes from the terminal
+//!
+//! A thin client over the same server functions the apps call. Log in once
+//! with `tn login`; later commands reuse the saved session.
+//!
+//! | Command | Server function |
+//! |---------|-----------------|
+//! | `tn login` | `login_password` |
+//! | `tn notes list` | `pull_notes` |
+//! | `tn notes create <name>` | `sync_note` (empty note) |
+//! | `tn notes edit <path>` | `pull_notes`, then `sync_note` after `$EDITOR` exits |
+//! | `tn notes delete <path>` | `delete_note_remote` |
+//! | `tn sync push <path>` | `sync_note` with the local file |
+//! | `tn sync pull` | `pull_notes`, written under the current directory |
+//! | `tn namespaces create <path>` | `sync_namespace` |
+//! | `tn export <path>` | `export_notes_zip` |
+//!
+//! Every command takes `--json` to print machine-readable output instead.
+
+mod client;
+mod config;
+
+use std::io::{self, BufRead, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use client::Client;
+use config::Config;
+
+#[derive(Parser)]
+#[command(name = "tn", version, about = "Manage TypedNotes from the terminal")]
+struct Cli {
+    /// Print JSON instead of human-readable output
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Log in with email and password and save the session
+    Login {
+        #[arg(long)]
+        email: Option<String>,
+        /// Prompted for when omitted
+        #[arg(long, env = "TN_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// List, create, edit and delete notes
+    #[command(subcommand)]
+    Notes(NotesCommand),
+    /// Copy notes between the remote and the current directory
+    #[command(subcommand)]
+    Sync(SyncCommand),
+    /// Manage namespaces (folders)
+    #[command(subcommand)]
+    Namespaces(NamespacesCommand),
+    /// Download every note as a zip archive
+    Export { path: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum NotesCommand {
+    List {
+        /// Only notes in this namespace (and below)
+        #[arg(long)]
+        namespace: Option<String>,
+    },
+    Create {
+        name: String,
+        #[arg(long = "type", value_enum, default_value_t = NoteType::Md)]
+        note_type: NoteType,
+        #[arg(long)]
+        namespace: Option<String>,
+    },
+    /// Open a note in $EDITOR and push the result
+    Edit { path: String },
+    Delete { path: String },
+}
+
+#[derive(Subcommand)]
+enum SyncCommand {
+    /// Push a local file to the same path on the remote
+    Push { path: String },
+    /// Write every remote note under the current directory
+    Pull,
+}
+
+#[derive(Subcommand)]
+enum NamespacesCommand {
+    Create { path: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NoteType {
+    Md,
+    Txt,
+    Org,
+    Rst,
+    Adoc,
+    Tex,
+}
+
+impl NoteType {
+    fn from_path(path: &str) -> Result<Self, String> {
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("md") => Ok(Self::Md),
+            Some("txt") => Ok(Self::Txt),
+            Some("org") => Ok(Self::Org),
+            Some("rst") => Ok(Self::Rst),
+            Some("adoc") => Ok(Self::Adoc),
+            Some("tex") => Ok(Self::Tex),
+            _ => Err(format!("{path}: notes must end in .md, .txt, .org, .rst, .adoc or .tex")),
+        }
+    }
+
+    fn ext(self) -> &'static str {
+        match self {
+            Self::Md => "md",
+            Self::Txt => "txt",
+            Self::Org => "org",
+            Self::Rst => "rst",
+            Self::Adoc => "adoc",
+            Self::Tex => "tex",
+        }
+    }
+
+    /// The `note_type` argument `sync_note` expects.
+    fn api_name(self) -> &'static str {
+        match self {
+            Self::Md => "markdown",
+            Self::Txt => "text",
+            Self::Org => "org",
+            Self::Rst => "rst",
+            Self::Adoc => "adoc",
+            Self::Tex => "tex",
+        }
+    }
+}
+
+/// Mirrors `api::UserInfo` (only the fields the CLI prints).
+#[derive(Debug, Serialize, Deserialize)]
+struct UserInfo {
+    id: String,
+    email: String,
+    name: Option<String>,
+    provider: String,
+}
+
+/// Mirrors `api::RemoteFile`.
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteFile {
+    path: String,
+    content: String,
+}
+
+/// Mirrors `api::PullResult`.
+#[derive(Debug, Serialize, Deserialize)]
+struct PullResult {
+    files: Vec<RemoteFile>,
+    namespaces: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let config = Config::load()?;
+    let client = Client::new(&config)?;
+    let json = cli.json;
+
+    match cli.command {
+        Command::Login { email, password } => {
+            let email = match email {
+                Some(email) => email,
+                None => prompt("Email: ")?,
+            };
+            let password = match password {
+                Some(password) => password,
+                None => rpassword::prompt_password("Password: ").map_err(|e| e.to_string())?,
+            };
+            let user: UserInfo = client.post(
+                "/api/auth/login-password",
+                json!({ "email": email, "password": password }),
+            )?;
+            client.save_session(&config.session_path)?;
+            output(json, &user, || format!("Logged in as {}", user.email))
+        }
+
+        Command::Notes(NotesCommand::List { namespace }) => {
+            let pulled = pull(&client)?;
+            let paths: Vec<&str> = pulled
+                .files
+                .iter()
+                .map(|f| f.path.as_str())
+                .filter(|p| namespace.as_deref().is_none_or(|ns| in_namespace(p, ns)))
+                .collect();
+            output(json, &paths, || paths.join("\n"))
+        }
+        Command::Notes(NotesCommand::Create {
+            name,
+            note_type,
+            namespace,
+        }) => {
+            let path = match namespace.as_deref().map(|ns| ns.trim_matches('/')) {
+                Some(ns) if !ns.is_empty() => format!("{ns}/{name}.{}", note_type.ext()),
+                _ => format!("{name}.{}", note_type.ext()),
+            };
+            sync_note(&client, &path, "")?;
+            output(json, &json!({ "path": path }), || format!("Created {path}"))
+        }
+        Command::Notes(NotesCommand::Edit { path }) => {
+            NoteType::from_path(&path)?;
+            let pulled = pull(&client)?;
+            let original = pulled
+                .files
+                .into_iter()
+                .find(|f| f.path == path)
+                .ok_or_else(|| format!("No note at {path}"))?
+                .content;
+
+            let edited = edit_in_editor(&path, &original)?;
+            let changed = edited != original;
+            if changed {
+                sync_note(&client, &path, &edited)?;
+            }
+            output(json, &json!({ "path": path, "changed": changed }), || {
+                if changed {
+                    format!("Saved {path}")
+                } else {
+                    format!("No changes to {path}")
+                }
+            })
+        }
+        Command::Notes(NotesCommand::Delete { path }) => {
+            client.post::<()>("/api/git/delete-note", json!({ "path": path }))?;
+            output(json, &json!({ "path": path }), || format!("Deleted {path}"))
+        }
+
+        Command::Sync(SyncCommand::Push { path }) => {
+            let content =
+                std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+            let remote = remote_path(&path)?;
+            sync_note(&client, &remote, &content)?;
+            output(json, &json!({ "path": remote }), || format!("Pushed {remote}"))
+        }
+        Command::Sync(SyncCommand::Pull) => {
+            let pulled = pull(&client)?;
+            for ns in &pulled.namespaces {
+                let dir = local_path(ns)?;
+                std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {ns}: {e}"))?;
+            }
+            for file in &pulled.files {
+                let local = local_path(&file.path)?;
+                if let Some(dir) = local.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&local, &file.content)
+                    .map_err(|e| format!("Failed to write {}: {e}", file.path))?;
+            }
+            let paths: Vec<&str> = pulled.files.iter().map(|f| f.path.as_str()).collect();
+            output(json, &paths, || format!("Pulled {} notes", paths.len()))
+        }
+
+        Command::Namespaces(NamespacesCommand::Create { path }) => {
+            let path = path.trim_matches('/').to_string();
+            client.post::<()>("/api/git/sync-namespace", json!({ "path": path }))?;
+            output(json, &json!({ "path": path }), || format!("Created namespace {path}"))
+        }
+
+        Command::Export { path } => {
+            let zip: Vec<u8> = client.get("/api/notes/export", &[])?;
+            std::fs::write(&path, &zip).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+            let shown = path.display().to_string();
+            output(json, &json!({ "path": shown, "bytes": zip.len() }), || {
+                format!("Exported {} bytes to {shown}", zip.len())
+            })
+        }
+    }
+}
+
+fn pull(client: &Client) -> Result<PullResult, String> {
+    client.post("/api/git/pull", json!({ "base": null, "local": [] }))
+}
+
+fn sync_note(client: &Client, path: &str, content: &str) -> Result<(), String> {
+    let note_type = NoteType::from_path(path)?;
+    client.post(
+        "/api/git/sync-note",
+        json!({ "path": path, "content": content, "note_type": note_type.api_name() }),
+    )
+}
+
+/// Print `value` as JSON, or the human-readable `text`.
+fn output<T: Serialize>(json: bool, value: &T, text: impl FnOnce() -> String) -> Result<(), String> {
+    if json {
+        let out = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+        println!("{out}");
+    } else {
+        let text = text();
+        if !text.is_empty() {
+            println!("{text}");
+        }
+    }
+    Ok(())
+}
+
+fn prompt(label: &str) -> Result<String, String> {
+    print!("{label}");
+    io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).map_err(|e| e.to_string())?;
+    Ok(line.trim().to_string())
+}
+
+fn in_namespace(path: &str, namespace: &str) -> bool {
+    let ns = namespace.trim_matches('/');
+    ns.is_empty() || path.strip_prefix(ns).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// A local relative path as a note path ("work/todo.md").
+fn remote_path(local: &str) -> Result<String, String> {
+    let mut parts = Vec::new();
+    for component in Path::new(local).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str().ok_or("Path is not valid UTF-8")?),
+            Component::CurDir => {}
+            _ => return Err(format!("{local}: use a path relative to the notes directory")),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// Where a remote path is written by `tn sync pull`. Refuses paths that
+/// would escape the current directory.
+fn local_path(remote: &str) -> Result<PathBuf, String> {
+    let path = Path::new(remote);
+    if path.components().all(|c| matches!(c, Component::Normal(_))) {
+        Ok(path.to_path_buf())
+    } else {
+        Err(format!("Refusing to write outside the current directory: {remote}"))
+    }
+}
+
+/// Let the user edit `content` in `$VISUAL` / `$EDITOR` (default `vi`).
+fn edit_in_editor(path: &str, content: &str) -> Result<String, String> {
+    let suffix = format!(".{}", Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("md"));
+    let mut file = tempfile::Builder::new()
+        .prefix("tn-")
+        .suffix(&suffix)
+        .tempfile()
+        .map_err(|e| e.to_string())?;
+    file.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    file.flush().map_err(|e| e.to_string())?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Editors like "code --wait" come with their own arguments
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("EDITOR is empty")?;
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(file.path())
+        .status()
+        .map_err(|e| format!("Failed to start {program}: {e}"))?;
+    if !status.success() {
+        return Err(format!("{program} exited with {status}; note not saved"));
+    }
+
+    std::fs::read_to_string(file.path()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_namespace() {
+        assert!(in_namespace("work/todo.md", "work"));
+        assert!(in_namespace("work/projects/a.md", "work/"));
+        assert!(!in_namespace("workshop/a.md", "work"));
+        assert!(!in_namespace("todo.md", "work"));
+        assert!(in_namespace("todo.md", ""));
+    }
+
+    #[test]
+    fn test_remote_and_local_paths() {
+        assert_eq!(remote_path("./work/todo.md").unwrap(), "work/todo.md");
+        assert!(remote_path("../todo.md").is_err());
+        assert!(remote_path("/etc/passwd").is_err());
+
+        assert_eq!(local_path("work/todo.md").unwrap(), PathBuf::from("work/todo.md"));
+        assert!(local_path("../escape.md").is_err());
+        assert!(local_path("/abs.md").is_err());
+    }
+
+    #[test]
+    fn test_note_type_from_path() {
+        assert_eq!(NoteType::from_path("a/b.md").unwrap().api_name(), "markdown");
+        assert_eq!(NoteType::from_path("b.txt").unwrap().api_name(), "text");
+        assert_eq!(NoteType::from_path("agenda.org").unwrap().ext(), "org");
+        assert_eq!(NoteType::from_path("paper.tex").unwrap().api_name(), "tex");
+        assert!(NoteType::from_path("b.pdf").is_err());
+    }
+}
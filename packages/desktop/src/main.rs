@@ -28,12 +28,13 @@ fn main() {
 
 #[component]
 fn App() -> Element {
-    use_context_provider(|| Signal::new(ui::ActivityLog::default()));
+    let mut activity_log = use_context_provider(|| Signal::new(ui::ActivityLog::default()));
 
     // Theme context: None = system, Some("dark"), Some("light")
     let mut theme: ui::ThemeSignal = use_context_provider(|| Signal::new(Option::<String>::None));
     use_effect(move || {
         ui::load_theme_from_storage(&mut theme);
+        ui::load_activity_log_from_storage(&mut activity_log);
     });
 
     rsx! {
@@ -41,7 +42,9 @@ fn App() -> Element {
         document::Link { rel: "stylesheet", href: ui::DX_COMPONENTS_CSS }
         ui::AuthProvider {
             ui::components::ToastProvider {
-                Router::<Route> {}
+                ui::components::CommandPaletteProvider {
+                    Router::<Route> {}
+                }
             }
         }
     }
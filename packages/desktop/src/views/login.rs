@@ -129,6 +129,12 @@ pub fn Login() -> Element {
                     label: "Continue with Google",
                     class: "flex items-center justify-center px-5 py-2.5 border-none rounded text-[0.9375rem] font-medium cursor-pointer transition-colors duration-150 font-sans bg-[#4285f4] text-white hover:bg-[#357abd] disabled:opacity-50 disabled:cursor-not-allowed",
                 }
+
+                LoginButton {
+                    provider: "gitlab",
+                    label: "Continue with GitLab",
+                    class: "flex items-center justify-center px-5 py-2.5 border-none rounded text-[0.9375rem] font-medium cursor-pointer transition-colors duration-150 font-sans bg-[#fc6d26] text-white hover:bg-[#e24329] disabled:opacity-50 disabled:cursor-not-allowed",
+                }
             }
 
             p {
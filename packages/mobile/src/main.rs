@@ -25,7 +25,12 @@ fn main() {
 fn App() -> Element {
     use_context_provider(|| Signal::new(ui::ActivityLog::default()));
     // Provide a dummy auth state so ui::use_auth() works without AuthProvider
-    use_context_provider(|| Signal::new(ui::AuthState { user: None, loading: false }));
+    use_context_provider(|| Signal::new(ui::AuthState {
+        user: None,
+        loading: false,
+        online: false,
+        extended: None,
+    }));
 
     // Theme context: None = system, Some("dark"), Some("light")
     let mut theme: ui::ThemeSignal = use_context_provider(|| Signal::new(Option::<String>::None));
@@ -37,7 +42,9 @@ fn App() -> Element {
         document::Link { rel: "stylesheet", href: ui::TAILWIND_CSS }
         document::Link { rel: "stylesheet", href: ui::DX_COMPONENTS_CSS }
         ui::components::ToastProvider {
-            Router::<Route> {}
+            ui::components::CommandPaletteProvider {
+                Router::<Route> {}
+            }
         }
     }
 }
@@ -0,0 +1,183 @@
+//! # LRU object cache
+//!
+//! [`CachedStore`] wraps any [`ObjectStore`] and keeps the most recently used
+//! objects in memory. Listing notes reads the HEAD commit, the root tree and
+//! every subtree on each call; with a slow backend such as
+//! [`IdbStore`](crate::IdbStore) those reads dominate, and they hit the same
+//! few objects over and over.
+//!
+//! Objects are content-addressed and never change, so a cached entry can only
+//! go stale if the object is deleted: the cache is cleared after
+//! [`ObjectStore::retain_objects`] and when a transaction is rolled back.
+//! Refs do change and are always read from the inner store.
+//!
+//! Clones share the cache, so a single `CachedStore` can be kept around and
+//! cloned into each short-lived [`Repository`](crate::Repository).
+
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+
+use crate::objects::{HashAlgorithm, Sha};
+use crate::repo::{ObjectStore, TxnGuard};
+
+/// Number of objects kept by [`CachedStore::new`].
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// [`ObjectStore`] wrapper that serves repeated `get`s from an in-memory LRU cache.
+#[derive(Clone)]
+pub struct CachedStore<S: ObjectStore> {
+    inner: S,
+    cache: Arc<Mutex<LruCache<Sha, Vec<u8>>>>,
+}
+
+impl<S: ObjectStore> CachedStore<S> {
+    /// Wrap `inner` with a cache of [`DEFAULT_CACHE_CAPACITY`] objects.
+    pub fn new(inner: S) -> Self {
+        Self::with_capacity(inner, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Wrap `inner` with a cache of `capacity` objects (at least one).
+    pub fn with_capacity(inner: S, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            inner,
+            cache: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Drop every cached object.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+}
+
+impl<S: ObjectStore> ObjectStore for CachedStore<S> {
+    async fn get(&self, sha: &Sha) -> Option<Vec<u8>> {
+        if let Some(data) = self.cache.lock().unwrap().get(sha) {
+            return Some(data.clone());
+        }
+        let data = self.inner.get(sha).await?;
+        self.cache.lock().unwrap().put(sha.clone(), data.clone());
+        Some(data)
+    }
+
+    async fn put(&self, sha: &Sha, data: Vec<u8>) {
+        self.cache.lock().unwrap().put(sha.clone(), data.clone());
+        self.inner.put(sha, data).await;
+    }
+
+    async fn get_ref(&self, name: &str) -> Option<Sha> {
+        self.inner.get_ref(name).await
+    }
+
+    async fn set_ref(&self, name: &str, sha: &Sha) {
+        self.inner.set_ref(name, sha).await;
+    }
+
+    async fn list_refs(&self) -> Option<Vec<(String, Sha)>> {
+        self.inner.list_refs().await
+    }
+
+    async fn object_count(&self) -> Option<usize> {
+        self.inner.object_count().await
+    }
+
+    fn iter_objects_sync(&self, f: &mut dyn FnMut(&Sha, &[u8])) -> bool {
+        self.inner.iter_objects_sync(f)
+    }
+
+    fn try_put_sync(&self, sha: &Sha, data: Vec<u8>) -> bool {
+        self.inner.try_put_sync(sha, data)
+    }
+
+    async fn retain_objects(&self, keep: &HashSet<Sha>) -> usize {
+        let removed = self.inner.retain_objects(keep).await;
+        self.clear();
+        removed
+    }
+
+    async fn set_hash_algorithm(&self, algorithm: HashAlgorithm) -> bool {
+        self.inner.set_hash_algorithm(algorithm).await
+    }
+
+    fn begin_txn(&self) -> TxnGuard {
+        let cache = self.cache.clone();
+        self.inner
+            .begin_txn()
+            .on_rollback(move || cache.lock().unwrap().clear())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+    use crate::repo::Repository;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// MemoryStore that counts calls to `get`.
+    #[derive(Clone, Default)]
+    struct CountingStore {
+        inner: MemoryStore,
+        gets: Arc<AtomicUsize>,
+    }
+
+    impl ObjectStore for CountingStore {
+        async fn get(&self, sha: &Sha) -> Option<Vec<u8>> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            self.inner.get(sha).await
+        }
+
+        async fn put(&self, sha: &Sha, data: Vec<u8>) {
+            self.inner.put(sha, data).await;
+        }
+
+        async fn get_ref(&self, name: &str) -> Option<Sha> {
+            self.inner.get_ref(name).await
+        }
+
+        async fn set_ref(&self, name: &str, sha: &Sha) {
+            self.inner.set_ref(name, sha).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_second_get_hits_cache() {
+        let counting = CountingStore::default();
+        let sha = Sha::from_hex("ce013625030ba8dba906f756967f9e9ca394464a").unwrap();
+        counting.inner.put(&sha, b"blob 6\0hello\n".to_vec()).await;
+
+        let cached = CachedStore::with_capacity(counting.clone(), 4);
+        assert_eq!(cached.get(&sha).await.unwrap(), b"blob 6\0hello\n");
+        assert_eq!(counting.gets.load(Ordering::SeqCst), 1);
+
+        assert_eq!(cached.get(&sha).await.unwrap(), b"blob 6\0hello\n");
+        assert_eq!(counting.gets.load(Ordering::SeqCst), 1);
+
+        // Clearing forces the next read back to the inner store
+        cached.clear();
+        cached.get(&sha).await.unwrap();
+        assert_eq!(counting.gets.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_repository_reads_own_writes() {
+        let counting = CountingStore::default();
+        let repo = Repository::new_cached(counting.clone(), 64);
+        repo.write_note("hello", "Hello!", "markdown").await;
+
+        let notes = repo.list_notes().await;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].note, "Hello!");
+        // Everything list_notes needed was put through the cache
+        assert_eq!(counting.gets.load(Ordering::SeqCst), 0);
+    }
+}
@@ -8,33 +8,190 @@
 //! ## Structure
 //!
 //! ```toml
+//! pinned_notes = ["todo.md", "work/plan.md"]  # shown at the top of the sidebar
+//! store_backend = "memory"                     # server object store: memory | sqlite | s3
+//! hash_algorithm = "sha1"                      # local object keys: sha1 | sha256
+//! paste_image_inline_threshold_bytes = 51200    # smaller pasted images are embedded as data URLs
+//!
 //! [notes]
-//! root = "notes"          # subfolder containing notes (empty = repo root)
+//! root = "notes"                 # subfolder containing notes (empty = repo root)
+//! max_note_size_bytes = 1048576  # optional; at most 10 MB
 //!
 //! [sync]
 //! auto_sync_interval_secs = 300  # 0 to disable auto-sync
+//! push_on_save = true            # false = push only on the periodic sync
+//! gc_object_threshold = 10000    # garbage-collect after a push above this many objects
+//!
+//! [namespace_overrides.work]     # applies to work/ and everything below it
+//! auto_sync_interval_secs = 60
+//! note_type = "markdown"
+//!
+//! [namespace_styles.work]        # how work/ looks in the sidebar
+//! icon = "briefcase"             # FontAwesome icon name
+//! color = "#3b82f6"              # CSS color
 //! ```
 //!
 //! ## Types
 //!
 //! | Struct | Purpose |
 //! |--------|---------|
-//! | [`TypedNotesConfig`] | Top-level config. Provides builder helpers (`new`, `with_sync_interval`), TOML (de)serialisation, and the canonical filename constant. |
-//! | [`NotesConfig`] | Notes section — currently just a `root` path for the notes subfolder. |
-//! | [`SyncConfig`] | Sync section — `auto_sync_interval_secs` with a default of **300 seconds**. |
+//! | [`TypedNotesConfig`] | Top-level config (plus the `pinned_notes` list and the pasted-image inline threshold). Provides builder helpers (`new`, `with_sync_interval`, `with_push_on_save`), TOML (de)serialisation, and the canonical filename constant. |
+//! | [`StoreBackend`] | Which [`ObjectStore`](crate::ObjectStore) the server keeps objects in (default **memory**). |
+//! | [`HashAlgorithm`] | What the app's local stores key objects by (default **sha1**), applied with [`Repository::apply_hash_algorithm`](crate::Repository::apply_hash_algorithm); git objects and the wire protocol are always SHA-1. |
+//! | [`NotesConfig`] | Notes section — a `root` path for the notes subfolder and an optional `max_note_size_bytes`. |
+//! | [`NamespaceConfig`] | Optional per-namespace overrides, resolved with [`TypedNotesConfig::effective_for_namespace`] into an [`EffectiveConfig`] (the longest matching namespace wins). |
+//! | [`NamespaceStyle`] | Sidebar icon and color of one namespace, looked up with [`TypedNotesConfig::get_namespace_style`]. |
+//! | [`SyncConfig`] | Sync section — `auto_sync_interval_secs` with a default of **300 seconds**, `push_on_save` (default **true**) and `gc_object_threshold` (default **10 000**). |
 //!
 //! All structs derive `Default` (with sensible production defaults) so that a
 //! missing or empty config file is equivalent to the default configuration.
+//!
+//! ## Validation
+//!
+//! [`TypedNotesConfig::validate`] lists every problem with a config as a
+//! readable message: a `notes.root` that is absolute or contains `..`, an
+//! auto-sync interval above [`MAX_SYNC_INTERVAL_SECS`], a note size limit
+//! above [`MAX_NOTE_SIZE_LIMIT`], or a namespace style whose icon or color
+//! could break out of the inline style it is rendered into. [`TypedNotesConfig::from_toml`] rejects a
+//! file that fails to parse or to validate with a [`ConfigError`].
+
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::objects::HashAlgorithm;
+
+/// Longest allowed `sync.auto_sync_interval_secs`: one day.
+pub const MAX_SYNC_INTERVAL_SECS: u32 = 86_400;
+
+/// Largest allowed `notes.max_note_size_bytes`: 10 MB.
+pub const MAX_NOTE_SIZE_LIMIT: usize = 10 * 1024 * 1024;
+
+/// Default `paste_image_inline_threshold_bytes`: 50 KB.
+pub const DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD: usize = 50 * 1024;
+
+/// Why a `typednotes.toml` could not be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
 /// Top-level configuration stored in `typednotes.toml`.
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TypedNotesConfig {
+    /// Paths of notes pinned to the top of the sidebar, in pin order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_notes: Vec<String>,
     #[serde(default)]
     pub notes: NotesConfig,
     #[serde(default)]
     pub sync: SyncConfig,
+    /// Object store used by the server; see [`StoreBackend`].
+    #[serde(default, skip_serializing_if = "StoreBackend::is_default")]
+    pub store_backend: StoreBackend,
+    /// Hash the app's local stores address objects by; see [`HashAlgorithm`].
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_default")]
+    pub hash_algorithm: HashAlgorithm,
+    /// Settings that differ below a namespace, keyed by namespace path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespace_overrides: BTreeMap<String, NamespaceConfig>,
+    /// Sidebar appearance of namespaces, keyed by namespace path. A
+    /// `BTreeMap` so the file is written in a stable order.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespace_styles: BTreeMap<String, NamespaceStyle>,
+    /// Images pasted into the editor below this size are embedded in the
+    /// note as `data:` URLs; larger ones are uploaded as attachments.
+    #[serde(
+        default = "default_paste_image_inline_threshold",
+        skip_serializing_if = "is_default_paste_image_inline_threshold"
+    )]
+    pub paste_image_inline_threshold_bytes: usize,
+}
+
+impl Default for TypedNotesConfig {
+    fn default() -> Self {
+        Self {
+            pinned_notes: Vec::new(),
+            notes: NotesConfig::default(),
+            sync: SyncConfig::default(),
+            store_backend: StoreBackend::default(),
+            hash_algorithm: HashAlgorithm::default(),
+            namespace_overrides: BTreeMap::new(),
+            namespace_styles: BTreeMap::new(),
+            paste_image_inline_threshold_bytes: DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD,
+        }
+    }
+}
+
+fn default_paste_image_inline_threshold() -> usize {
+    DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD
+}
+
+fn is_default_paste_image_inline_threshold(bytes: &usize) -> bool {
+    *bytes == DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD
+}
+
+/// Where the server keeps Git objects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    /// Transient [`MemoryStore`](crate::MemoryStore)s filled from the git remote.
+    #[default]
+    Memory,
+    /// A local SQLite database (`sqlite` feature).
+    Sqlite,
+    /// An S3 bucket configured from the environment (`s3` feature).
+    S3,
+}
+
+impl StoreBackend {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Overrides for one namespace and its descendants. Unset fields fall back
+/// to the global config.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_sync_interval_secs: Option<u32>,
+    /// Default type (`"markdown"` or `"text"`) for new notes in the namespace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note_type: Option<String>,
+}
+
+/// How a namespace folder is drawn in the sidebar. Unset fields use the
+/// plain folder icon and the theme's text color.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceStyle {
+    /// FontAwesome icon name, e.g. `"briefcase"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// CSS color, e.g. `"#3b82f6"` or `"teal"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl NamespaceStyle {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The settings that apply to one namespace once overrides are merged in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectiveConfig {
+    pub auto_sync_interval_secs: u32,
+    pub note_type: Option<String>,
 }
 
 /// Notes-specific configuration.
@@ -44,6 +201,9 @@ pub struct NotesConfig {
     /// Empty string means the repository root.
     #[serde(default)]
     pub root: String,
+    /// Largest note the app should accept, in bytes. `None` means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_note_size_bytes: Option<usize>,
 }
 
 /// Sync configuration.
@@ -52,16 +212,34 @@ pub struct SyncConfig {
     /// Auto-sync interval in seconds. 0 disables auto-sync.
     #[serde(default = "default_auto_sync_interval")]
     pub auto_sync_interval_secs: u32,
+    /// Push a note to the remote as soon as it is saved. When false, saved
+    /// notes wait in the sync queue until the next periodic sync.
+    #[serde(default = "default_push_on_save")]
+    pub push_on_save: bool,
+    /// Local object count above which a successful push is followed by
+    /// garbage collection (see `Repository::gc`). 0 disables it.
+    #[serde(default = "default_gc_object_threshold")]
+    pub gc_object_threshold: usize,
 }
 
 fn default_auto_sync_interval() -> u32 {
     300
 }
 
+fn default_push_on_save() -> bool {
+    true
+}
+
+fn default_gc_object_threshold() -> usize {
+    10_000
+}
+
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
             auto_sync_interval_secs: default_auto_sync_interval(),
+            push_on_save: default_push_on_save(),
+            gc_object_threshold: default_gc_object_threshold(),
         }
     }
 }
@@ -70,8 +248,8 @@ impl TypedNotesConfig {
     /// Create a config with the given notes root.
     pub fn new(root: String) -> Self {
         Self {
-            notes: NotesConfig { root },
-            sync: SyncConfig::default(),
+            notes: NotesConfig { root, ..NotesConfig::default() },
+            ..Self::default()
         }
     }
 
@@ -81,14 +259,111 @@ impl TypedNotesConfig {
         self
     }
 
+    /// Builder method to set whether saves are pushed immediately.
+    pub fn with_push_on_save(mut self, push_on_save: bool) -> Self {
+        self.sync.push_on_save = push_on_save;
+        self
+    }
+
+    /// Settings for `ns` (a namespace path, `""` for the root): the override
+    /// of the longest namespace that is `ns` or one of its ancestors, with
+    /// unset fields taken from the global config.
+    pub fn effective_for_namespace(&self, ns: &str) -> EffectiveConfig {
+        let ns = ns.trim_matches('/');
+        let matched = self
+            .namespace_overrides
+            .iter()
+            .filter(|(key, _)| {
+                let key = key.trim_matches('/');
+                !key.is_empty() && (ns == key || ns.starts_with(&format!("{key}/")))
+            })
+            .max_by_key(|(key, _)| key.trim_matches('/').len())
+            .map(|(_, config)| config);
+
+        EffectiveConfig {
+            auto_sync_interval_secs: matched
+                .and_then(|c| c.auto_sync_interval_secs)
+                .unwrap_or(self.sync.auto_sync_interval_secs),
+            note_type: matched.and_then(|c| c.note_type.clone()),
+        }
+    }
+
+    /// Style of namespace `path` itself (styles are not inherited by
+    /// sub-namespaces); the default style when it has none.
+    pub fn get_namespace_style(&self, path: &str) -> NamespaceStyle {
+        self.namespace_styles
+            .get(path.trim_matches('/'))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Shortest non-zero auto-sync interval across the global config and all
+    /// namespace overrides, or `None` when auto-sync is off everywhere.
+    pub fn shortest_sync_interval(&self) -> Option<u32> {
+        std::iter::once(self.sync.auto_sync_interval_secs)
+            .chain(self.namespace_overrides.values().filter_map(|c| c.auto_sync_interval_secs))
+            .filter(|&secs| secs > 0)
+            .min()
+    }
+
     /// The well-known filename for the config file.
     pub fn filename() -> &'static str {
         "typednotes.toml"
     }
 
-    /// Parse from TOML string.
-    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
-        toml::from_str(s)
+    /// Parse from TOML string, rejecting configs that fail [`validate`](Self::validate).
+    pub fn from_toml(s: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(s).map_err(|e| ConfigError {
+            message: format!("Invalid {}: {}", Self::filename(), e.message()),
+        })?;
+        let problems = config.validate();
+        if !problems.is_empty() {
+            return Err(ConfigError { message: problems.join("; ") });
+        }
+        Ok(config)
+    }
+
+    /// Human-readable problems with this config; empty when it is valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let root = &self.notes.root;
+        if root.starts_with('/') {
+            problems.push(format!("Notes root \"{root}\" must be relative to the repository, not start with /"));
+        }
+        if root.split('/').any(|part| part == "..") {
+            problems.push(format!("Notes root \"{root}\" must not contain .."));
+        }
+        if self.sync.auto_sync_interval_secs > MAX_SYNC_INTERVAL_SECS {
+            problems.push(format!(
+                "Auto-sync interval of {} seconds is longer than a day ({MAX_SYNC_INTERVAL_SECS} seconds)",
+                self.sync.auto_sync_interval_secs
+            ));
+        }
+        for (ns, config) in &self.namespace_overrides {
+            if config.auto_sync_interval_secs.is_some_and(|secs| secs > MAX_SYNC_INTERVAL_SECS) {
+                problems.push(format!("Auto-sync interval for {ns} is longer than a day"));
+            }
+        }
+        for (ns, style) in &self.namespace_styles {
+            if style.icon.as_deref().is_some_and(|icon| {
+                !icon.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            }) {
+                problems.push(format!("Icon for {ns} must be a FontAwesome icon name like \"briefcase\""));
+            }
+            if style.color.as_deref().is_some_and(|color| {
+                !color.chars().all(|c| c.is_ascii_alphanumeric() || "#(),.% ".contains(c))
+            }) {
+                problems.push(format!("Color for {ns} must be a CSS color like \"#3b82f6\""));
+            }
+        }
+        if let Some(max) = self.notes.max_note_size_bytes {
+            if max > MAX_NOTE_SIZE_LIMIT {
+                problems.push(format!(
+                    "Maximum note size of {max} bytes is above the 10 MB limit ({MAX_NOTE_SIZE_LIMIT} bytes)"
+                ));
+            }
+        }
+        problems
     }
 
     /// Serialize to TOML string.
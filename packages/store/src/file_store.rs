@@ -25,6 +25,7 @@
 //! | Windows | `C:\Users\<user>\AppData\Roaming\typednotes\` |
 //! | Android | App-internal storage (via `dirs`) |
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use crate::objects::Sha;
@@ -98,6 +99,53 @@ impl ObjectStore for FileStore {
         }
         let _ = std::fs::write(path, sha.to_hex());
     }
+
+    async fn list_refs(&self) -> Option<Vec<(String, Sha)>> {
+        let mut refs = Vec::new();
+        // Ref names like `refs/tags/v1` are nested directories
+        let mut dirs = vec![self.refs_dir()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let Ok(name) = path.strip_prefix(self.refs_dir()) else {
+                    continue;
+                };
+                let name = name.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/");
+                if let Some(sha) = std::fs::read_to_string(&path).ok().and_then(|hex| Sha::from_hex(hex.trim())) {
+                    refs.push((name, sha));
+                }
+            }
+        }
+        Some(refs)
+    }
+
+    async fn object_count(&self) -> Option<usize> {
+        Some(std::fs::read_dir(self.objects_dir()).map_or(0, |dir| dir.count()))
+    }
+
+    async fn retain_objects(&self, keep: &HashSet<Sha>) -> usize {
+        let Ok(dir) = std::fs::read_dir(self.objects_dir()) else {
+            return 0;
+        };
+        let mut removed = 0;
+        for entry in dir.flatten() {
+            let name = entry.file_name();
+            let Some(sha) = name.to_str().and_then(Sha::from_hex) else {
+                continue;
+            };
+            if !keep.contains(&sha) && std::fs::remove_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
 }
 
 #[cfg(test)]
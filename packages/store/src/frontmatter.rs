@@ -0,0 +1,229 @@
+//! # YAML frontmatter
+//!
+//! Notes may start with a YAML block delimited by `---` lines, as popularised by
+//! static site generators and other markdown note apps:
+//!
+//! ```text
+//! ---
+//! title: Weekly review
+//! tags: [rust, programming]
+//! aliases: [review, weekly]
+//! created: 2024-03-01
+//! ---
+//! Body starts here.
+//! ```
+//!
+//! [`parse_frontmatter`] understands the small subset of YAML that appears in
+//! practice — `key: value` scalars, flow lists (`[a, b]`), block lists (`- a`)
+//! and comma-separated tags or aliases — and never fails: unknown keys and malformed lines
+//! are skipped. A missing closing delimiter is tolerated (the block then runs
+//! to the end of the note), but a block with none of the known keys yields
+//! `None` so a leading `---` horizontal rule is not mistaken for metadata.
+//! [`strip_frontmatter`] applies the same rules to return just the body.
+
+use serde::{Deserialize, Serialize};
+
+/// Metadata read from a note's frontmatter.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteMetadata {
+    pub title: Option<String>,
+    /// Tags in source order, without duplicates or a leading `#`.
+    pub tags: Vec<String>,
+    /// Creation date as written (not validated).
+    pub created: Option<String>,
+    /// Other names the note can be linked by, in source order.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Parse the frontmatter block at the start of `content`.
+///
+/// Returns `None` if the note has no frontmatter or the block contains none of
+/// `title`, `tags`, `aliases` and `created`.
+pub fn parse_frontmatter(content: &str) -> Option<NoteMetadata> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    let mut meta = NoteMetadata::default();
+    let mut found = false;
+    // Key whose block list (`- item` lines) we are currently reading
+    let mut list_key: Option<String> = None;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed == "---" || trimmed == "..." {
+            break;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix('-')) {
+            match list_key.as_deref() {
+                Some("tags" | "tag") => push_tag(&mut meta.tags, item),
+                Some("aliases" | "alias") => push_alias(&mut meta.aliases, item),
+                _ => {}
+            }
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            list_key = None;
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        list_key = value.is_empty().then(|| key.clone());
+
+        match key.as_str() {
+            "title" => {
+                found = true;
+                meta.title = non_empty(unquote(value));
+            }
+            "created" | "date" => {
+                found = true;
+                meta.created = non_empty(unquote(value));
+            }
+            "tags" | "tag" => {
+                found = true;
+                let value = value
+                    .strip_prefix('[')
+                    .map(|v| v.strip_suffix(']').unwrap_or(v))
+                    .unwrap_or(value);
+                for tag in value.split(',') {
+                    push_tag(&mut meta.tags, tag);
+                }
+            }
+            "aliases" | "alias" => {
+                found = true;
+                // A bare scalar is a single alias, which may contain commas
+                match value.strip_prefix('[') {
+                    Some(list) => {
+                        for alias in list.strip_suffix(']').unwrap_or(list).split(',') {
+                            push_alias(&mut meta.aliases, alias);
+                        }
+                    }
+                    None => push_alias(&mut meta.aliases, value),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    found.then_some(meta)
+}
+
+/// The part of `content` after its frontmatter block.
+///
+/// Returns `content` unchanged when [`parse_frontmatter`] finds no metadata,
+/// and an empty string when the block is never closed.
+pub fn strip_frontmatter(content: &str) -> &str {
+    if parse_frontmatter(content).is_none() {
+        return content;
+    }
+    // Skip the opening delimiter line, then find the closing one
+    let mut offset = content.find('\n').map_or(content.len(), |i| i + 1);
+    while offset < content.len() {
+        let end = content[offset..].find('\n').map_or(content.len(), |i| offset + i + 1);
+        let trimmed = content[offset..end].trim();
+        if trimmed == "---" || trimmed == "..." {
+            return &content[end..];
+        }
+        offset = end;
+    }
+    ""
+}
+
+/// Remove one pair of matching surrounding quotes.
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn push_tag(tags: &mut Vec<String>, raw: &str) {
+    let tag = unquote(raw).trim_start_matches('#').trim();
+    if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+        tags.push(tag.to_string());
+    }
+}
+
+fn push_alias(aliases: &mut Vec<String>, raw: &str) {
+    let alias = unquote(raw).trim();
+    if !alias.is_empty() && !aliases.iter().any(|a| a == alias) {
+        aliases.push(alias.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flow_list_and_scalars() {
+        let meta = parse_frontmatter(
+            "---\ntitle: \"Weekly review\"\ntags: [rust, 'programming', rust]\ncreated: 2024-03-01\n---\nBody",
+        )
+        .unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Weekly review"));
+        assert_eq!(meta.tags, ["rust", "programming"]);
+        assert_eq!(meta.created.as_deref(), Some("2024-03-01"));
+    }
+
+    #[test]
+    fn test_block_list_and_comma_tags() {
+        let meta = parse_frontmatter("---\ntags:\n  - work\n  - \"#ideas\"\nauthor: me\n---\n").unwrap();
+        assert_eq!(meta.tags, ["work", "ideas"]);
+
+        let meta = parse_frontmatter("---\ntags: a, b ,c\n---").unwrap();
+        assert_eq!(meta.tags, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let meta = parse_frontmatter("---\naliases: [Review, 'Weekly review']\n---\n").unwrap();
+        assert_eq!(meta.aliases, ["Review", "Weekly review"]);
+
+        let meta = parse_frontmatter("---\nalias: Weekly, review\n---\n").unwrap();
+        assert_eq!(meta.aliases, ["Weekly, review"]);
+
+        let meta = parse_frontmatter("---\naliases:\n  - one\n  - \"two\"\n  - one\n---\n").unwrap();
+        assert_eq!(meta.aliases, ["one", "two"]);
+        assert!(meta.tags.is_empty());
+    }
+
+    #[test]
+    fn test_missing_or_malformed() {
+        assert_eq!(parse_frontmatter("# Just a heading\ntags: [x]"), None);
+        assert_eq!(parse_frontmatter(""), None);
+        // Horizontal rule followed by prose is not frontmatter
+        assert_eq!(parse_frontmatter("---\nSome text\n"), None);
+        // Missing closing delimiter is tolerated
+        let meta = parse_frontmatter("---\ntags: [open]\n").unwrap();
+        assert_eq!(meta.tags, ["open"]);
+        // Garbage does not panic
+        let meta = parse_frontmatter("---\ntags: [unclosed\n: :\n- \ntitle:\n---").unwrap();
+        assert_eq!(meta.tags, ["unclosed"]);
+        assert_eq!(meta.title, None);
+    }
+
+    #[test]
+    fn test_strip_frontmatter() {
+        assert_eq!(strip_frontmatter("---\ntitle: T\n---\n# Body\n"), "# Body\n");
+        assert_eq!(strip_frontmatter("---\r\ntags: [a]\r\n---\r\nBody"), "Body");
+        assert_eq!(strip_frontmatter("---\nSome text\n"), "---\nSome text\n");
+        assert_eq!(strip_frontmatter("No frontmatter"), "No frontmatter");
+        assert_eq!(strip_frontmatter("---\ntags: [open]\n"), "");
+    }
+}
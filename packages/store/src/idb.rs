@@ -7,12 +7,21 @@
 //!
 //! ## Database schema
 //!
-//! A single IndexedDB database named `"typednotes"` (version 1) with two object stores:
+//! A single IndexedDB database named `"typednotes"` (version 2) with four object stores:
 //!
 //! | IndexedDB store | Key | Value | Maps to |
 //! |-----------------|-----|-------|---------|
-//! | `"objects"` | SHA-1 hex string | `Vec<u8>` (serialised via `serde_wasm_bindgen`) | Git objects (blobs, trees, commits) |
+//! | `"objects"` | SHA-1 (or SHA-256) hex string | `Vec<u8>` (serialised via `serde_wasm_bindgen`) | Git objects (blobs, trees, commits) |
 //! | `"refs"` | ref name (e.g. `"HEAD"`) | SHA-1 hex string | Named references |
+//! | `"object_ids"` | SHA-1 hex string | SHA-256 hex string | Key of each object once keyed by SHA-256 |
+//! | `"settings"` | setting name | string | `"hash_algorithm"`: `"sha256"` once switched |
+//!
+//! Objects are keyed by SHA-1 until [`ObjectStore::set_hash_algorithm`]
+//! switches the database to [`HashAlgorithm::Sha256`]; lookups go through
+//! `"object_ids"` first, so they work under either key.
+//!
+//! The unscoped database may also hold `migrated_to_<user_id>` refs, written by
+//! [`IdbStore::migrate_from_legacy_if_needed`].
 //!
 //! ## Connection management
 //!
@@ -28,15 +37,23 @@
 //! degrades to "no local data" rather than crashing. The authoritative copy of the
 //! notes always lives on the Git remote.
 
-use crate::objects::Sha;
+use std::collections::{HashMap, HashSet};
+
+use crate::objects::{HashAlgorithm, Sha};
 use crate::repo::ObjectStore;
-use rexie::{ObjectStore as RexieObjectStore, Rexie, TransactionMode};
+use rexie::{ObjectStore as RexieObjectStore, Rexie, Store, TransactionMode};
 use wasm_bindgen::JsValue;
 
 const DEFAULT_DB_NAME: &str = "typednotes";
-const DB_VERSION: u32 = 1;
+const DB_VERSION: u32 = 2;
 const OBJECTS_STORE: &str = "objects";
 const REFS_STORE: &str = "refs";
+const IDS_STORE: &str = "object_ids";
+const SETTINGS_STORE: &str = "settings";
+const HASH_ALGORITHM_SETTING: &str = "hash_algorithm";
+/// Ref written into the legacy DB once its data was copied to a user's DB,
+/// followed by the user ID.
+const MIGRATION_MARKER_PREFIX: &str = "migrated_to_";
 
 /// IndexedDB-backed ObjectStore for web platform.
 ///
@@ -71,6 +88,8 @@ impl IdbStore {
             .version(DB_VERSION)
             .add_object_store(RexieObjectStore::new(OBJECTS_STORE))
             .add_object_store(RexieObjectStore::new(REFS_STORE))
+            .add_object_store(RexieObjectStore::new(IDS_STORE))
+            .add_object_store(RexieObjectStore::new(SETTINGS_STORE))
             .build()
             .await
     }
@@ -86,83 +105,178 @@ impl IdbStore {
         let _ = Rexie::delete(DEFAULT_DB_NAME).await;
     }
 
-    /// Migrate data from the legacy unscoped DB if the scoped DB is empty.
+    /// Copy the legacy unscoped DB into this user-scoped DB.
     ///
-    /// Called once after login to seamlessly transfer existing notes into the
-    /// user's scoped database. Only runs when:
-    /// 1. This store is scoped (not the default DB name)
-    /// 2. The scoped DB has no HEAD ref yet
-    /// 3. The legacy DB has data to migrate
+    /// Notes written before signing in live in `"typednotes"`; this moves them
+    /// to `"typednotes-<user_id>"`. Safe to call on every login: it does
+    /// nothing when
+    /// 1. this store is the unscoped one,
+    /// 2. the legacy DB holds a `migrated_to_<user_id>` marker ref,
+    /// 3. the legacy DB has no objects, or
+    /// 4. the scoped DB already has a `HEAD` other than the legacy one (its
+    ///    history wins).
+    ///
+    /// Every object and ref is copied, the marker is written into the legacy
+    /// DB, and the legacy DB is then deleted. `set_ref` reports no errors, so
+    /// both `HEAD` in this DB and the marker are read back first: if either is
+    /// missing the legacy DB is kept and the next login tries again (a copied
+    /// `HEAD` equal to the legacy one only needs the marker). Deletion
+    /// can be blocked by another open tab; the marker stops the next login
+    /// from copying again.
     pub async fn migrate_from_legacy_if_needed(&self) {
-        // Only migrate if we're using a scoped (non-default) DB
-        if self.db_name == DEFAULT_DB_NAME {
+        let Some(user_id) = self.db_name.strip_prefix(&format!("{DEFAULT_DB_NAME}-")) else {
             return;
-        }
+        };
+        let marker = format!("{MIGRATION_MARKER_PREFIX}{user_id}");
 
-        // Check if scoped DB already has a HEAD → skip if yes
-        if self.get_ref("HEAD").await.is_some() {
+        let legacy = IdbStore::new();
+        if legacy.get_ref(&marker).await.is_some() {
             return;
         }
-
-        // Open legacy DB
-        let legacy = IdbStore::new();
-        let Some(legacy_head) = legacy.get_ref("HEAD").await else {
-            return; // Nothing to migrate
-        };
-
-        // Open legacy DB to read all objects
-        let Ok(legacy_db) = legacy.open_db().await else {
+        if legacy.object_count().await.unwrap_or(0) == 0 {
             return;
-        };
+        }
+        let legacy_head = legacy.get_ref("HEAD").await;
+        match self.get_ref("HEAD").await {
+            // An earlier copy finished but its marker was not written
+            Some(head) if Some(&head) == legacy_head.as_ref() => {
+                return legacy.finish_migration(&marker, &head).await;
+            }
+            Some(_) => return,
+            None => {}
+        }
 
-        // Read all objects from legacy store
-        let objects = {
-            let Ok(tx) = legacy_db.transaction(&[OBJECTS_STORE], TransactionMode::ReadOnly) else {
-                return;
-            };
-            let Ok(store) = tx.store(OBJECTS_STORE) else {
-                return;
-            };
-            let Ok(entries) = store.scan(None, None, None, None).await else {
-                return;
-            };
-            entries
-        };
+        for sha in legacy.object_shas().await {
+            if let Some(data) = legacy.get(&sha).await {
+                self.put(&sha, data).await;
+            }
+        }
 
-        // Write all objects into scoped DB
-        let Ok(scoped_db) = self.open_db().await else {
-            return;
-        };
-        {
-            let Ok(tx) = scoped_db.transaction(&[OBJECTS_STORE], TransactionMode::ReadWrite) else {
-                return;
-            };
-            let Ok(store) = tx.store(OBJECTS_STORE) else {
-                return;
-            };
-            for (key, value) in &objects {
-                let _ = store.put(value, Some(key)).await;
+        // HEAD last, so an interrupted copy never points at missing objects
+        let mut refs = legacy.keys(REFS_STORE).await;
+        refs.retain(|name| !name.starts_with(MIGRATION_MARKER_PREFIX));
+        refs.sort_by_key(|name| name == "HEAD");
+        for name in refs {
+            if let Some(sha) = legacy.get_ref(&name).await {
+                self.set_ref(&name, &sha).await;
             }
-            let _ = tx.done().await;
         }
 
-        // Copy HEAD ref
-        self.set_ref("HEAD", &legacy_head).await;
+        if let Some(head) = legacy_head {
+            if self.get_ref("HEAD").await.as_ref() == Some(&head) {
+                legacy.finish_migration(&marker, &head).await;
+            }
+        }
+    }
 
-        // Delete legacy database
-        let _ = Rexie::delete(DEFAULT_DB_NAME).await;
+    /// Write the migration `marker` into this legacy DB and delete the DB once
+    /// the marker reads back.
+    async fn finish_migration(&self, marker: &str, head: &Sha) {
+        self.set_ref(marker, head).await;
+        if self.get_ref(marker).await.as_ref() == Some(head) {
+            let _ = Rexie::delete(DEFAULT_DB_NAME).await;
+        }
+    }
+
+    /// SHA-1 names of every stored object, under either key.
+    async fn object_shas(&self) -> Vec<Sha> {
+        let mut keys = self.keys(OBJECTS_STORE).await;
+        keys.extend(self.keys(IDS_STORE).await);
+        keys.iter().filter_map(|hex| Sha::from_hex(hex)).collect()
+    }
+
+    /// Every key in one of the object stores.
+    async fn keys(&self, store_name: &str) -> Vec<String> {
+        let Ok(db) = self.open_db().await else {
+            return Vec::new();
+        };
+        let Ok(tx) = db.transaction(&[store_name], TransactionMode::ReadOnly) else {
+            return Vec::new();
+        };
+        let Ok(store) = tx.store(store_name) else {
+            return Vec::new();
+        };
+        let Ok(keys) = store.get_all_keys(None, None).await else {
+            return Vec::new();
+        };
+        keys.into_iter().filter_map(|key| key.as_string()).collect()
+    }
+}
+
+/// The algorithm `"objects"` is keyed by.
+async fn stored_hash_algorithm(settings: &Store) -> HashAlgorithm {
+    let value = settings.get(JsValue::from_str(HASH_ALGORITHM_SETTING)).await.ok().flatten();
+    match value.and_then(|value| value.as_string()).as_deref() {
+        Some("sha256") => HashAlgorithm::Sha256,
+        _ => HashAlgorithm::Sha1,
+    }
+}
+
+/// The `"objects"` key of the object named `sha`.
+async fn object_key(ids: &Store, sha: &Sha) -> String {
+    let hex = sha.to_hex();
+    let id = ids.get(JsValue::from_str(&hex)).await.ok().flatten();
+    id.and_then(|id| id.as_string()).unwrap_or(hex)
+}
+
+/// Re-key `"objects"` by `algorithm`, within one transaction.
+async fn rekey(db: &Rexie, algorithm: HashAlgorithm) -> Result<(), rexie::Error> {
+    let tx = db.transaction(&[SETTINGS_STORE, IDS_STORE, OBJECTS_STORE], TransactionMode::ReadWrite)?;
+    let settings = tx.store(SETTINGS_STORE)?;
+    let ids = tx.store(IDS_STORE)?;
+    let objects = tx.store(OBJECTS_STORE)?;
+    if stored_hash_algorithm(&settings).await == algorithm {
+        return Ok(());
     }
+
+    let setting = JsValue::from_str(HASH_ALGORITHM_SETTING);
+    match algorithm {
+        HashAlgorithm::Sha1 => {
+            for sha in ids.get_all_keys(None, None).await? {
+                let Some(id) = ids.get(sha.clone()).await? else {
+                    continue;
+                };
+                if let Some(data) = objects.get(id.clone()).await? {
+                    objects.put(&data, Some(&sha)).await?;
+                    objects.delete(id).await?;
+                }
+                ids.delete(sha).await?;
+            }
+            settings.delete(setting).await?;
+        }
+        HashAlgorithm::Sha256 => {
+            for key in objects.get_all_keys(None, None).await? {
+                // Only SHA-1 keys are 40 characters long
+                if key.as_string().is_none_or(|hex| hex.len() != 40) {
+                    continue;
+                }
+                let Some(data) = objects.get(key.clone()).await? else {
+                    continue;
+                };
+                let Ok(raw) = serde_wasm_bindgen::from_value::<Vec<u8>>(data.clone()) else {
+                    continue;
+                };
+                let id = JsValue::from_str(&algorithm.object_id(&raw).to_hex());
+                objects.put(&data, Some(&id)).await?;
+                objects.delete(key.clone()).await?;
+                ids.put(&id, Some(&key)).await?;
+            }
+            settings.put(&JsValue::from_str("sha256"), Some(&setting)).await?;
+        }
+    }
+    tx.done().await
 }
 
 impl ObjectStore for IdbStore {
     async fn get(&self, sha: &Sha) -> Option<Vec<u8>> {
         let db = self.open_db().await.ok()?;
         let tx = db
-            .transaction(&[OBJECTS_STORE], TransactionMode::ReadOnly)
+            .transaction(&[IDS_STORE, OBJECTS_STORE], TransactionMode::ReadOnly)
             .ok()?;
+        let ids = tx.store(IDS_STORE).ok()?;
         let store = tx.store(OBJECTS_STORE).ok()?;
 
-        let key = JsValue::from_str(&sha.to_hex());
+        let key = JsValue::from_str(&object_key(&ids, sha).await);
         let value = store.get(key).await.ok()?;
 
         let js_val = value?;
@@ -174,14 +288,23 @@ impl ObjectStore for IdbStore {
         let Ok(db) = self.open_db().await else {
             return;
         };
-        let Ok(tx) = db.transaction(&[OBJECTS_STORE], TransactionMode::ReadWrite) else {
+        let Ok(tx) = db.transaction(&[SETTINGS_STORE, IDS_STORE, OBJECTS_STORE], TransactionMode::ReadWrite) else {
             return;
         };
-        let Ok(store) = tx.store(OBJECTS_STORE) else {
+        let (Ok(settings), Ok(ids), Ok(store)) =
+            (tx.store(SETTINGS_STORE), tx.store(IDS_STORE), tx.store(OBJECTS_STORE))
+        else {
             return;
         };
 
-        let key = JsValue::from_str(&sha.to_hex());
+        let key = match stored_hash_algorithm(&settings).await {
+            HashAlgorithm::Sha1 => JsValue::from_str(&sha.to_hex()),
+            HashAlgorithm::Sha256 => {
+                let id = JsValue::from_str(&HashAlgorithm::Sha256.object_id(&data).to_hex());
+                let _ = ids.put(&id, Some(&JsValue::from_str(&sha.to_hex()))).await;
+                id
+            }
+        };
         let value = serde_wasm_bindgen::to_value(&data).unwrap_or(JsValue::NULL);
         let _ = store.put(&value, Some(&key)).await;
         let _ = tx.done().await;
@@ -218,4 +341,72 @@ impl ObjectStore for IdbStore {
         let _ = store.put(&value, Some(&key)).await;
         let _ = tx.done().await;
     }
+
+    async fn list_refs(&self) -> Option<Vec<(String, Sha)>> {
+        let mut refs = Vec::new();
+        for name in self.keys(REFS_STORE).await {
+            if let Some(sha) = self.get_ref(&name).await {
+                refs.push((name, sha));
+            }
+        }
+        Some(refs)
+    }
+
+    async fn object_count(&self) -> Option<usize> {
+        let db = self.open_db().await.ok()?;
+        let tx = db
+            .transaction(&[OBJECTS_STORE], TransactionMode::ReadOnly)
+            .ok()?;
+        let store = tx.store(OBJECTS_STORE).ok()?;
+        store.count(None).await.ok().map(|n| n as usize)
+    }
+
+    async fn retain_objects(&self, keep: &HashSet<Sha>) -> usize {
+        let Ok(db) = self.open_db().await else {
+            return 0;
+        };
+        let Ok(tx) = db.transaction(&[IDS_STORE, OBJECTS_STORE], TransactionMode::ReadWrite) else {
+            return 0;
+        };
+        let (Ok(ids), Ok(store)) = (tx.store(IDS_STORE), tx.store(OBJECTS_STORE)) else {
+            return 0;
+        };
+        let Ok(keys) = store.get_all_keys(None, None).await else {
+            return 0;
+        };
+
+        // SHA-256 key → SHA-1 name
+        let mut names = HashMap::new();
+        for sha in ids.get_all_keys(None, None).await.unwrap_or_default() {
+            if let (Some(hex), Ok(Some(id))) = (sha.as_string(), ids.get(sha).await) {
+                names.extend(id.as_string().map(|id| (id, hex)));
+            }
+        }
+
+        let mut removed = 0;
+        for key in keys {
+            let Some(hex) = key.as_string() else {
+                continue;
+            };
+            let name = names.get(&hex).unwrap_or(&hex);
+            if Sha::from_hex(name).is_some_and(|sha| keep.contains(&sha)) {
+                continue;
+            }
+            if names.contains_key(&hex) {
+                let _ = ids.delete(JsValue::from_str(name)).await;
+            }
+            if store.delete(key).await.is_ok() {
+                removed += 1;
+            }
+        }
+        let _ = tx.done().await;
+        removed
+    }
+
+    async fn set_hash_algorithm(&self, algorithm: HashAlgorithm) -> bool {
+        let Ok(db) = self.open_db().await else {
+            return false;
+        };
+        rekey(&db, algorithm).await.is_ok()
+    }
 }
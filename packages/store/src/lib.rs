@@ -12,15 +12,23 @@
 //! | [`objects`] | Core Git object types ([`Sha`], [`Blob`](objects::Blob), [`Tree`](objects::Tree), [`Commit`](objects::Commit)) with serialisation, parsing, and SHA-1 hashing. |
 //! | [`repo`] | [`Repository`] — high-level async API for reading/writing notes and namespaces on top of an [`ObjectStore`]. |
 //! | [`config`] | [`TypedNotesConfig`] — the `typednotes.toml` configuration (notes root, sync interval). |
+//! | [`templates`] | Rendering of note templates kept in the reserved `.templates/` namespace. |
+//! | [`frontmatter`] | [`NoteMetadata`] and a tolerant parser for the YAML frontmatter block at the top of a note, plus a helper to strip it. |
 //! | [`models`] | Domain types ([`TypedNoteInfo`], [`NamespaceInfo`]) returned by `Repository` queries. |
+//! | [`cache`] | [`CachedStore`] — LRU cache of recently read objects wrapping any `ObjectStore`. |
 //! | [`memory`] | [`MemoryStore`] — `HashMap`-backed `ObjectStore` used server-side for transient Git sync and in tests. |
+//! | [`pack`] | Minimal pack file writer and reader backing [`MemoryStore::compact`]. |
 //! | [`idb`] | [`IdbStore`] — IndexedDB-backed `ObjectStore` for browser offline persistence (WASM + `web` feature only). |
+//! | `sqlite` | `SqliteStore` — single-file SQLite `ObjectStore` used by the desktop app (native + `sqlite` feature only). |
+//! | `s3` | `S3Store` — `ObjectStore` in an S3 (or compatible) bucket, with optional DynamoDB ref locking (native + `s3` feature only). |
 //!
 //! ## Platform gating
 //!
 //! - [`MemoryStore`] is always available (all targets).
 //! - [`IdbStore`] is gated behind `#[cfg(all(target_arch = "wasm32", feature = "web"))]`,
 //!   so it only compiles for the browser build.
+//! - `SqliteStore` is gated behind `#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite"))]`.
+//! - `S3Store` is gated behind `#[cfg(all(not(target_arch = "wasm32"), feature = "s3"))]`.
 //!
 //! ## Re-exports
 //!
@@ -28,25 +36,41 @@
 //! (`api`, `ui`) can write `use store::{Repository, MemoryStore, TypedNoteInfo, …}`
 //! without reaching into submodules.
 
+pub mod cache;
 pub mod config;
+pub mod frontmatter;
 pub mod models;
 pub mod objects;
+pub mod pack;
 pub mod repo;
+pub mod templates;
 
 mod memory;
-pub use memory::MemoryStore;
+pub use memory::{MemoryStore, DEFAULT_PACK};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod file_store;
 #[cfg(not(target_arch = "wasm32"))]
 pub use file_store::FileStore;
 
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite"))]
+mod sqlite;
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite"))]
+pub use sqlite::SqliteStore;
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "s3"))]
+mod s3;
+#[cfg(all(not(target_arch = "wasm32"), feature = "s3"))]
+pub use s3::S3Store;
+
 #[cfg(all(target_arch = "wasm32", feature = "web"))]
 mod idb;
 #[cfg(all(target_arch = "wasm32", feature = "web"))]
 pub use idb::IdbStore;
 
-pub use config::TypedNotesConfig;
+pub use cache::{CachedStore, DEFAULT_CACHE_CAPACITY};
+pub use config::{ConfigError, EffectiveConfig, NamespaceConfig, NamespaceStyle, StoreBackend, TypedNotesConfig};
+pub use frontmatter::NoteMetadata;
 pub use models::{NamespaceInfo, TypedNoteInfo};
-pub use objects::Sha;
-pub use repo::{ObjectStore, Repository};
+pub use objects::{HashAlgorithm, ObjectId, Sha, Sha256Hash};
+pub use repo::{ChangeKind, MergeConflict, MergeResult, ObjectStore, Repository, TreeDiff, TxnGuard};
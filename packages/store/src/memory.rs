@@ -31,19 +31,297 @@
 //! A convenience method that returns the hex SHA keys of every stored object. Used by
 //! the server functions in [`api`] to compute the set of newly created objects (by
 //! diffing snapshots before and after a `Repository` write) so that only those objects
-//! are included in the push packfile.
+//! are included in the push packfile. Its counterparts `all_ref_names` and `all_refs`
+//! list the refs a fetch stored (e.g. to report the remote's branches).
+//!
+//! Bulk work over the objects themselves (packing, measuring) goes through
+//! [`MemoryStore::iter_objects`] instead, which visits each name and its
+//! bytes in one pass under the lock rather than listing the names and then
+//! looking every object up again.
+//!
+//! ## `clone_deep`
+//!
+//! `Clone` shares the underlying maps, so a clone sees every later write.
+//! [`MemoryStore::clone_deep`] copies them instead, giving an independent
+//! snapshot — tests use it to stand in for a fetch from a "remote" store.
+//!
+//! ## Hash algorithm
+//!
+//! By default objects are keyed by their SHA-1, the name git gives them.
+//! [`MemoryStore::with_hash_algorithm`] with [`HashAlgorithm::Sha256`] keys them
+//! by the SHA-256 of their bytes instead and keeps a SHA-1 → [`ObjectId`] index,
+//! so the [`ObjectStore`] API, `all_object_shas` and the git transport still deal
+//! in SHA-1 names. [`ObjectStore::set_hash_algorithm`] re-keys a store that
+//! already holds objects.
+//!
+//! ## Transactions
+//!
+//! [`ObjectStore::begin_txn`] opens a write log instead of copying the maps:
+//! each `put` that adds a new object and each ref update is recorded in it, and
+//! rolling back (explicitly or by dropping the guard) undoes just those writes.
+//! A ref is only restored if it still holds the value the transaction wrote, so
+//! a later write by someone else survives. Writes made while several
+//! transactions are open cannot be told apart and are left alone; at worst
+//! that leaves orphaned objects for [`Repository::gc`](crate::Repository::gc).
+//!
+//! ## `retain_objects`
+//!
+//! The sweep half of [`Repository::gc`](crate::Repository::gc): drops every object
+//! whose SHA is not in the given set.
+//!
+//! ## Packs
+//!
+//! Every object is stored loose, as its own map entry, until
+//! [`MemoryStore::compact`] folds them all into one [pack](crate::pack) kept
+//! under [`DEFAULT_PACK`], with an index from SHA-1 to entry offset. Reads check
+//! the loose objects first and then the packs; writes are always loose, so
+//! objects written after a compaction wait for the next one.
+//! [`MemoryStore::pack_data`] and [`MemoryStore::add_pack`] move a pack out of
+//! and back into a store, e.g. to keep it between server requests.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
-use crate::objects::Sha;
-use crate::repo::ObjectStore;
+use crate::objects::{HashAlgorithm, ObjectId, Sha};
+use crate::pack::{index_pack, unpack_pack_entry, PackBuilder};
+use crate::repo::{ObjectStore, TxnGuard};
+
+/// Name [`MemoryStore::compact`] stores its pack under.
+pub const DEFAULT_PACK: &str = "pack/default";
+
+/// A pack and its index, as made by [`MemoryStore::compact`].
+#[derive(Clone, Debug, Default)]
+struct Pack {
+    /// Shared, so snapshots for transactions don't copy the whole pack.
+    data: Arc<Vec<u8>>,
+    /// SHA-1 → entry offset. Objects dropped by `retain` leave the index but
+    /// stay in `data` until the next compaction.
+    index: HashMap<Sha, usize>,
+}
+
+/// Objects keyed by [`ObjectId`] under one [`HashAlgorithm`].
+#[derive(Clone, Debug, Default)]
+struct Objects {
+    algorithm: HashAlgorithm,
+    by_id: HashMap<ObjectId, Vec<u8>>,
+    /// SHA-1 name → SHA-256 id; empty for [`HashAlgorithm::Sha1`].
+    sha1_index: HashMap<Sha, ObjectId>,
+    /// Packed objects, by pack name.
+    packs: HashMap<String, Pack>,
+    /// Number of distinct objects, loose or packed, kept up to date by every
+    /// write so [`len`](Self::len) does not walk the packs.
+    count: usize,
+}
+
+impl Objects {
+    fn id(&self, sha: &Sha) -> Option<ObjectId> {
+        match self.algorithm {
+            HashAlgorithm::Sha1 => Some(ObjectId::Sha1(sha.clone())),
+            HashAlgorithm::Sha256 => self.sha1_index.get(sha).cloned(),
+        }
+    }
+
+    fn get(&self, sha: &Sha) -> Option<Vec<u8>> {
+        self.get_loose(sha).or_else(|| self.get_packed(sha))
+    }
+
+    fn get_loose(&self, sha: &Sha) -> Option<Vec<u8>> {
+        self.by_id.get(&self.id(sha)?).cloned()
+    }
+
+    fn get_packed(&self, sha: &Sha) -> Option<Vec<u8>> {
+        self.packs
+            .values()
+            .find_map(|pack| unpack_pack_entry(&pack.data, *pack.index.get(sha)?))
+    }
+
+    fn insert(&mut self, sha: &Sha, data: Vec<u8>) {
+        if !self.contains(sha) {
+            self.count += 1;
+        }
+        let id = match self.algorithm {
+            HashAlgorithm::Sha1 => ObjectId::Sha1(sha.clone()),
+            HashAlgorithm::Sha256 => {
+                let id = self.algorithm.object_id(&data);
+                self.sha1_index.insert(sha.clone(), id.clone());
+                id
+            }
+        };
+        self.by_id.insert(id, data);
+    }
+
+    fn has_loose(&self, sha: &Sha) -> bool {
+        self.id(sha).is_some_and(|id| self.by_id.contains_key(&id))
+    }
+
+    fn is_packed(&self, sha: &Sha) -> bool {
+        self.packs.values().any(|pack| pack.index.contains_key(sha))
+    }
+
+    fn contains(&self, sha: &Sha) -> bool {
+        self.has_loose(sha) || self.is_packed(sha)
+    }
+
+    fn remove_loose(&mut self, sha: &Sha) {
+        let id = match self.algorithm {
+            HashAlgorithm::Sha1 => Some(ObjectId::Sha1(sha.clone())),
+            HashAlgorithm::Sha256 => self.sha1_index.remove(sha),
+        };
+        let removed = id.is_some_and(|id| self.by_id.remove(&id).is_some());
+        if removed && !self.is_packed(sha) {
+            self.count -= 1;
+        }
+    }
+
+    /// Call `f` with the name and bytes of every loose object, then of every
+    /// packed object without a loose copy.
+    fn for_each(&self, mut f: impl FnMut(&Sha, &[u8])) {
+        match self.algorithm {
+            HashAlgorithm::Sha1 => {
+                for (id, data) in &self.by_id {
+                    if let ObjectId::Sha1(sha) = id {
+                        f(sha, data);
+                    }
+                }
+            }
+            HashAlgorithm::Sha256 => {
+                for (sha, id) in &self.sha1_index {
+                    if let Some(data) = self.by_id.get(id) {
+                        f(sha, data);
+                    }
+                }
+            }
+        }
+        for pack in self.packs.values() {
+            for (sha, &offset) in &pack.index {
+                if self.has_loose(sha) {
+                    continue;
+                }
+                if let Some(raw) = unpack_pack_entry(&pack.data, offset) {
+                    f(sha, &raw);
+                }
+            }
+        }
+    }
+
+    /// Names of loose and packed objects, each once.
+    fn shas(&self) -> Vec<Sha> {
+        let mut shas: HashSet<Sha> = self.loose_shas().into_iter().collect();
+        for pack in self.packs.values() {
+            shas.extend(pack.index.keys().cloned());
+        }
+        shas.into_iter().collect()
+    }
+
+    fn loose_shas(&self) -> Vec<Sha> {
+        match self.algorithm {
+            HashAlgorithm::Sha1 => self
+                .by_id
+                .keys()
+                .filter_map(|id| match id {
+                    ObjectId::Sha1(sha) => Some(sha.clone()),
+                    ObjectId::Sha256(_) => None,
+                })
+                .collect(),
+            HashAlgorithm::Sha256 => self.sha1_index.keys().cloned().collect(),
+        }
+    }
+
+    fn retain(&mut self, keep: &HashSet<Sha>) -> usize {
+        let before = self.len();
+        self.retain_loose(keep);
+        for pack in self.packs.values_mut() {
+            pack.index.retain(|sha, _| keep.contains(sha));
+        }
+        self.recount();
+        before - self.len()
+    }
+
+    /// Number of objects, loose or packed.
+    fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Count the objects again after replacing packs or dropping objects in
+    /// bulk, where tracking each one would cost as much.
+    fn recount(&mut self) {
+        self.count = self.shas().len();
+    }
+
+    /// Key the loose objects by `algorithm`; packs are indexed by SHA-1
+    /// either way.
+    fn set_algorithm(&mut self, algorithm: HashAlgorithm) {
+        if algorithm == self.algorithm {
+            return;
+        }
+        let mut by_id = std::mem::take(&mut self.by_id);
+        let loose: Vec<(Sha, Vec<u8>)> = match self.algorithm {
+            HashAlgorithm::Sha1 => by_id
+                .into_iter()
+                .filter_map(|(id, data)| match id {
+                    ObjectId::Sha1(sha) => Some((sha, data)),
+                    ObjectId::Sha256(_) => None,
+                })
+                .collect(),
+            HashAlgorithm::Sha256 => std::mem::take(&mut self.sha1_index)
+                .into_iter()
+                .filter_map(|(sha, id)| Some((sha, by_id.remove(&id)?)))
+                .collect(),
+        };
+        self.algorithm = algorithm;
+        for (sha, data) in loose {
+            self.insert(&sha, data);
+        }
+        self.recount();
+    }
+
+    fn retain_loose(&mut self, keep: &HashSet<Sha>) {
+        match self.algorithm {
+            HashAlgorithm::Sha1 => self
+                .by_id
+                .retain(|id, _| matches!(id, ObjectId::Sha1(sha) if keep.contains(sha))),
+            HashAlgorithm::Sha256 => {
+                self.sha1_index.retain(|sha, _| keep.contains(sha));
+                let live: HashSet<&ObjectId> = self.sha1_index.values().collect();
+                self.by_id.retain(|id, _| live.contains(id));
+            }
+        }
+    }
+}
+
+/// Writes made under one open transaction, for undoing them.
+#[derive(Debug, Default)]
+struct TxnLog {
+    /// Objects that were not stored before.
+    objects: Vec<Sha>,
+    /// Ref name, value before the write, value written.
+    refs: Vec<(String, Option<Sha>, Sha)>,
+}
+
+/// The open transactions, by id.
+#[derive(Debug, Default)]
+struct Txns {
+    next_id: u64,
+    open: HashMap<u64, TxnLog>,
+}
+
+impl Txns {
+    /// The log writes go to: only when a single transaction is open.
+    fn sole_log(&mut self) -> Option<&mut TxnLog> {
+        if self.open.len() == 1 {
+            self.open.values_mut().next()
+        } else {
+            None
+        }
+    }
+}
 
 /// In-memory ObjectStore for testing and desktop fallback.
 #[derive(Clone, Debug, Default)]
 pub struct MemoryStore {
-    objects: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    objects: Arc<Mutex<Objects>>,
     refs: Arc<Mutex<HashMap<String, Sha>>>,
+    txns: Arc<Mutex<Txns>>,
 }
 
 impl MemoryStore {
@@ -51,14 +329,43 @@ impl MemoryStore {
         Self::default()
     }
 
+    /// An empty store keying objects by `algorithm` (see the module docs).
+    pub fn with_hash_algorithm(algorithm: HashAlgorithm) -> Self {
+        let store = Self::default();
+        store.objects.lock().unwrap().algorithm = algorithm;
+        store
+    }
+
+    /// The algorithm objects are keyed by.
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        self.objects.lock().unwrap().algorithm
+    }
+
+    /// The key the object named `sha` is stored under, if it is stored
+    /// (loose, or under its algorithm's name if packed).
+    pub fn object_id(&self, sha: &Sha) -> Option<ObjectId> {
+        let objects = self.objects.lock().unwrap();
+        if let Some(id) = objects.id(sha).filter(|id| objects.by_id.contains_key(id)) {
+            return Some(id);
+        }
+        let raw = objects.get_packed(sha)?;
+        Some(objects.algorithm.object_id(&raw))
+    }
+
     /// Synchronous get — for use in blocking contexts (e.g. git transport).
     pub fn get_sync(&self, sha: &Sha) -> Option<Vec<u8>> {
-        self.objects.lock().unwrap().get(&sha.to_hex()).cloned()
+        self.objects.lock().unwrap().get(sha)
     }
 
     /// Synchronous put — for use in blocking contexts.
     pub fn put_sync(&self, sha: &Sha, data: Vec<u8>) {
-        self.objects.lock().unwrap().insert(sha.to_hex(), data);
+        let mut objects = self.objects.lock().unwrap();
+        if !objects.contains(sha) {
+            if let Some(log) = self.txns.lock().unwrap().sole_log() {
+                log.objects.push(sha.clone());
+            }
+        }
+        objects.insert(sha, data);
     }
 
     /// Synchronous get_ref — for use in blocking contexts.
@@ -68,39 +375,196 @@ impl MemoryStore {
 
     /// Synchronous set_ref — for use in blocking contexts.
     pub fn set_ref_sync(&self, name: &str, sha: &Sha) {
-        self.refs
-            .lock()
-            .unwrap()
-            .insert(name.to_string(), sha.clone());
+        let mut refs = self.refs.lock().unwrap();
+        let old = refs.insert(name.to_string(), sha.clone());
+        if let Some(log) = self.txns.lock().unwrap().sole_log() {
+            log.refs.push((name.to_string(), old, sha.clone()));
+        }
     }
 
-    /// Return the hex SHA strings of all stored objects.
+    /// Return the hex SHA-1 strings of all stored objects, whatever they are
+    /// keyed by.
     pub fn all_object_shas(&self) -> Vec<String> {
-        self.objects.lock().unwrap().keys().cloned().collect()
+        self.objects.lock().unwrap().shas().iter().map(Sha::to_hex).collect()
+    }
+
+    /// Call `f` with the SHA-1 name and bytes of every stored object, without
+    /// listing the names first as [`all_object_shas`](Self::all_object_shas)
+    /// does. The store is locked meanwhile, so `f` must not use it.
+    pub fn iter_objects(&self, f: impl FnMut(&Sha, &[u8])) {
+        self.objects.lock().unwrap().for_each(f);
+    }
+
+    /// Remove every object whose SHA is not in `keep`. Returns how many were removed.
+    pub fn retain_objects(&self, keep: &HashSet<Sha>) -> usize {
+        self.objects.lock().unwrap().retain(keep)
+    }
+
+    /// Fold every object into one pack stored under [`DEFAULT_PACK`],
+    /// replacing any earlier packs, and drop the loose copies. Returns the
+    /// number of objects packed (0 if packing failed, leaving the store as it
+    /// was).
+    pub fn compact(&self) -> usize {
+        let mut builder = PackBuilder::new();
+        let mut built = Ok(());
+        self.iter_objects(|_, raw| {
+            if built.is_ok() {
+                built = builder.add(raw);
+            }
+        });
+        if built.is_err() {
+            return 0;
+        }
+        let data = builder.finish();
+        let Ok(index) = index_pack(&data) else {
+            return 0;
+        };
+
+        let mut objects = self.objects.lock().unwrap();
+        // Objects written while the pack was built stay loose
+        let unpacked: HashSet<Sha> =
+            objects.loose_shas().into_iter().filter(|sha| !index.contains_key(sha)).collect();
+        objects.retain_loose(&unpacked);
+        let packed = index.len();
+        objects.packs = HashMap::from([(DEFAULT_PACK.to_string(), Pack { data: Arc::new(data), index })]);
+        objects.recount();
+        packed
+    }
+
+    /// Store `data`, a pack as made by [`compact`](Self::compact), under
+    /// `name`, replacing any pack of that name. Returns the number of objects
+    /// it holds.
+    pub fn add_pack(&self, name: &str, data: Vec<u8>) -> Result<usize, String> {
+        let index = index_pack(&data)?;
+        let count = index.len();
+        let mut objects = self.objects.lock().unwrap();
+        objects.packs.insert(name.to_string(), Pack { data: Arc::new(data), index });
+        objects.recount();
+        Ok(count)
+    }
+
+    /// The bytes of the pack stored under `name`.
+    pub fn pack_data(&self, name: &str) -> Option<Vec<u8>> {
+        let objects = self.objects.lock().unwrap();
+        Some(objects.packs.get(name)?.data.as_ref().clone())
+    }
+
+    /// Read the object named `sha` from the packs, ignoring loose objects.
+    pub fn unpack_pack_entry(&self, sha: &Sha) -> Option<Vec<u8>> {
+        self.objects.lock().unwrap().get_packed(sha)
+    }
+
+    /// Return the names of all stored refs, sorted.
+    pub fn all_ref_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.refs.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Return every stored ref with the SHA it points to.
+    pub fn all_refs(&self) -> HashMap<String, Sha> {
+        self.refs.lock().unwrap().clone()
+    }
+
+    /// A new store holding copies of every object and ref, sharing nothing
+    /// with `self` (unlike `clone`).
+    pub fn clone_deep(&self) -> MemoryStore {
+        MemoryStore {
+            objects: Arc::new(Mutex::new(self.objects.lock().unwrap().clone())),
+            refs: Arc::new(Mutex::new(self.all_refs())),
+            txns: Arc::default(),
+        }
     }
 }
 
 impl ObjectStore for MemoryStore {
     async fn get(&self, sha: &Sha) -> Option<Vec<u8>> {
-        self.objects.lock().unwrap().get(&sha.to_hex()).cloned()
+        self.get_sync(sha)
     }
 
     async fn put(&self, sha: &Sha, data: Vec<u8>) {
-        self.objects.lock().unwrap().insert(sha.to_hex(), data);
+        self.put_sync(sha, data);
     }
 
     async fn get_ref(&self, name: &str) -> Option<Sha> {
-        self.refs.lock().unwrap().get(name).cloned()
+        self.get_ref_sync(name)
     }
 
     async fn set_ref(&self, name: &str, sha: &Sha) {
-        self.refs.lock().unwrap().insert(name.to_string(), sha.clone());
+        self.set_ref_sync(name, sha);
+    }
+
+    async fn list_refs(&self) -> Option<Vec<(String, Sha)>> {
+        Some(self.all_refs().into_iter().collect())
+    }
+
+    /// Number of stored objects, loose or packed.
+    async fn object_count(&self) -> Option<usize> {
+        Some(self.objects.lock().unwrap().len())
+    }
+
+    fn iter_objects_sync(&self, f: &mut dyn FnMut(&Sha, &[u8])) -> bool {
+        self.iter_objects(f);
+        true
+    }
+
+    fn try_put_sync(&self, sha: &Sha, data: Vec<u8>) -> bool {
+        self.put_sync(sha, data);
+        true
+    }
+
+    async fn retain_objects(&self, keep: &HashSet<Sha>) -> usize {
+        MemoryStore::retain_objects(self, keep)
+    }
+
+    async fn set_hash_algorithm(&self, algorithm: HashAlgorithm) -> bool {
+        self.objects.lock().unwrap().set_algorithm(algorithm);
+        true
+    }
+
+    /// Open a write log; rolling back undoes the writes in it.
+    fn begin_txn(&self) -> TxnGuard {
+        let id = {
+            let mut txns = self.txns.lock().unwrap();
+            let id = txns.next_id;
+            txns.next_id += 1;
+            txns.open.insert(id, TxnLog::default());
+            id
+        };
+        let store = self.clone();
+        let txns = self.txns.clone();
+        TxnGuard::new(move || {
+            let Some(log) = store.txns.lock().unwrap().open.remove(&id) else {
+                return;
+            };
+            let mut refs = store.refs.lock().unwrap();
+            for (name, old, new) in log.refs.into_iter().rev() {
+                if refs.get(&name) != Some(&new) {
+                    continue;
+                }
+                match old {
+                    Some(old) => refs.insert(name, old),
+                    None => refs.remove(&name),
+                };
+            }
+            let mut objects = store.objects.lock().unwrap();
+            for sha in &log.objects {
+                objects.remove_loose(sha);
+            }
+        })
+        .on_commit(move || {
+            txns.lock().unwrap().open.remove(&id);
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::objects::{
+        hash_blob, hash_commit, hash_tag, hash_tree, Blob, Commit, HashAlgorithm, Tag, Tree, TreeEntry,
+    };
+    use crate::pack::build_pack;
     use crate::repo::Repository;
 
     #[tokio::test]
@@ -147,6 +611,20 @@ mod tests {
         assert_eq!(namespaces[0].name, "work");
     }
 
+    #[tokio::test]
+    async fn test_list_files() {
+        let repo = Repository::new(MemoryStore::new());
+        assert!(repo.list_files().await.is_empty());
+
+        repo.write_note("work/plan", "Plan", "markdown").await;
+        repo.create_namespace("empty").await;
+        repo.write_note_raw("assets/logo.png", &[0x89, b'P', b'N', b'G']).await;
+
+        let paths: Vec<String> = repo.list_files().await.into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, ["assets/logo.png", "empty/.gitkeep", "work/plan.md"]);
+        assert_eq!(repo.list_files().await[0].1, [0x89, b'P', b'N', b'G']);
+    }
+
     #[tokio::test]
     async fn test_get_note() {
         let store = MemoryStore::new();
@@ -159,6 +637,71 @@ mod tests {
         assert_eq!(note.r#type, "text");
     }
 
+    #[tokio::test]
+    async fn test_markup_note_types_are_listed() {
+        let repo = Repository::new(MemoryStore::new());
+        repo.write_note("agenda", "* TODO Plan\n#+BEGIN_SRC rust\nfn main() {}\n#+END_SRC", "org").await;
+        repo.write_note("docs/intro", "Intro\n=====", "rst").await;
+        repo.write_note("docs/guide", "= Guide", "adoc").await;
+        repo.write_note("paper", "\\section{Intro}", "tex").await;
+
+        let mut notes: Vec<(String, String)> = repo
+            .list_notes()
+            .await
+            .into_iter()
+            .map(|n| (n.path, n.r#type))
+            .collect();
+        notes.sort();
+        let expected = [
+            ("agenda.org", "org"),
+            ("docs/guide.adoc", "adoc"),
+            ("docs/intro.rst", "rst"),
+            ("paper.tex", "tex"),
+        ];
+        assert_eq!(notes, expected.map(|(p, t)| (p.to_string(), t.to_string())));
+        assert_eq!(repo.get_note("agenda.org").await.unwrap().name, "agenda");
+    }
+
+    #[tokio::test]
+    async fn test_note_metadata() {
+        let repo = Repository::new(MemoryStore::new());
+        let content = "---\ntitle: Weekly review\ntags: [work]\naliases: [review]\n---\nBody";
+        repo.write_note("weekly", content, "markdown").await;
+        repo.write_note("plain", "No frontmatter", "markdown").await;
+
+        let note = repo.get_note("weekly.md").await.unwrap();
+        assert_eq!(note.note, content);
+        assert_eq!(note.title(), "Weekly review");
+        assert_eq!(note.tags(), ["work"]);
+        assert_eq!(note.metadata.unwrap().aliases, ["review"]);
+
+        let notes = repo.list_notes().await;
+        let plain = notes.iter().find(|n| n.name == "plain").unwrap();
+        assert_eq!(plain.metadata, None);
+        assert_eq!(plain.title(), "plain");
+    }
+
+    #[tokio::test]
+    async fn test_find_backlinks() {
+        let repo = Repository::new(MemoryStore::new());
+        repo.write_note("work/plan", "---\naliases: [roadmap]\n---\nThe plan", "markdown").await;
+        repo.write_note("a", "See [[Plan]] for details", "markdown").await;
+        repo.write_note("b", "Also [[work/plan#Goals|the goals]]", "markdown").await;
+        repo.write_note("c", "[link](/work/plan.md)", "markdown").await;
+        repo.write_note("d", "Follow the [[roadmap]]", "markdown").await;
+        repo.write_note("e", "[[planning]] is unrelated, and [[plan\n]] is not a link", "markdown").await;
+
+        let mut paths: Vec<String> = repo
+            .find_backlinks("work/plan.md")
+            .await
+            .into_iter()
+            .map(|n| n.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, ["a.md", "b.md", "c.md", "d.md"]);
+        assert!(repo.find_backlinks("missing.md").await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_delete_note() {
         let store = MemoryStore::new();
@@ -210,6 +753,668 @@ mod tests {
         assert_eq!(loaded.notes.root, "docs/notes");
     }
 
+    #[tokio::test]
+    async fn test_encrypted_note() {
+        let repo = Repository::new(MemoryStore::new());
+        repo.write_note("work/secret.md.enc", "bm9uY2U=\nY2lwaGVy", "markdown").await;
+        repo.write_note("work/plain", "# Plain", "markdown").await;
+
+        let notes = repo.list_notes().await;
+        assert_eq!(notes.len(), 2);
+        let secret = repo.get_note("work/secret.md.enc").await.unwrap();
+        assert!(secret.encrypted);
+        assert_eq!(secret.name, "secret");
+        assert_eq!(secret.r#type, "markdown");
+        assert_eq!(secret.namespace.as_deref(), Some("work"));
+        assert!(notes.iter().any(|n| n.path == "work/secret.md.enc" && n.encrypted));
+        assert!(!repo.get_note("work/plain.md").await.unwrap().encrypted);
+    }
+
+    #[tokio::test]
+    async fn test_read_raw() {
+        let repo = Repository::new(MemoryStore::new());
+        let png = vec![0x89, b'P', b'N', b'G', 0, 0xff];
+        repo.write_note_raw("assets/cat.png", &png).await;
+
+        assert_eq!(repo.read_raw("assets/cat.png").await, Some(png));
+        assert_eq!(repo.read_raw("assets/dog.png").await, None);
+        // Directories are not files
+        assert_eq!(repo.read_raw("assets").await, None);
+        // Attachments are not notes
+        assert!(repo.list_notes().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_merge_from() {
+        use crate::MergeResult;
+
+        let remote = MemoryStore::new();
+        let remote_repo = Repository::new(remote.clone());
+        for name in ["a", "b", "c"] {
+            remote_repo.write_note(name, &format!("base {name}"), "markdown").await;
+        }
+        let local = Repository::new(remote.clone_deep());
+        assert_eq!(local.merge_from(&remote).await, MergeResult::default());
+
+        // Only the remote moved on
+        remote_repo.write_note("a", "remote a", "markdown").await;
+        let result = local.merge_from(&remote).await;
+        assert!(result.fast_forwarded);
+        assert_eq!(result.merged_notes, vec!["a.md"]);
+        assert_eq!(local.get_head().await, remote_repo.get_head().await);
+
+        // Both moved on, touching different notes
+        local.write_note("b", "local b", "markdown").await;
+        remote_repo.write_note("c", "remote c", "markdown").await;
+        let result = local.merge_from(&remote).await;
+        assert!(!result.fast_forwarded);
+        assert_eq!(result.merged_notes, vec!["c.md"]);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(local.get_note("b.md").await.unwrap().note, "local b");
+        assert_eq!(local.get_note("c.md").await.unwrap().note, "remote c");
+        let merge = local.get_commit(&local.get_head().await.unwrap()).await.unwrap();
+        assert_eq!(merge.parents.len(), 2);
+        assert_eq!(local.merge_from(&remote).await, MergeResult::default());
+    }
+
+    #[tokio::test]
+    async fn test_merge_from_conflict() {
+        let remote = MemoryStore::new();
+        let remote_repo = Repository::new(remote.clone());
+        remote_repo.write_note("a", "base a", "markdown").await;
+        let local = Repository::new(remote.clone_deep());
+
+        local.write_note("a", "local a", "markdown").await;
+        remote_repo.write_note("a", "remote a", "markdown").await;
+        remote_repo.write_note("d", "remote d", "markdown").await;
+        let result = local.merge_from(&remote).await;
+        assert_eq!(result.merged_notes, vec!["d.md"]);
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.path, "a.md");
+        assert_eq!(conflict.local, "local a");
+        assert_eq!(conflict.remote, "remote a");
+        assert_eq!(conflict.base.as_deref(), Some("base a"));
+        assert_eq!(local.get_note("a.md").await.unwrap().note, "local a");
+        assert_eq!(local.get_note("d.md").await.unwrap().note, "remote d");
+
+        // The remote stays unmerged, so the conflict is reported again
+        let head = local.get_commit(&local.get_head().await.unwrap()).await.unwrap();
+        assert_eq!(head.parents.len(), 1);
+        let result = local.merge_from(&remote).await;
+        assert!(result.merged_notes.is_empty());
+        assert_eq!(result.conflicts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_notes_modified_since() {
+        use crate::objects::parse_commit;
+        use crate::TypedNoteInfo;
+
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        repo.write_note("a", "alpha", "markdown").await;
+        // Backdate the first commit
+        let head = store.get_ref_sync("HEAD").unwrap();
+        let mut commit = parse_commit(&store.get_sync(&head).unwrap()).unwrap();
+        commit.timestamp = 1_000;
+        let (sha, raw) = hash_commit(&commit);
+        store.put_sync(&sha, raw);
+        store.set_ref_sync("HEAD", &sha);
+
+        repo.write_note("b", "beta", "markdown").await;
+        let paths = |notes: Vec<TypedNoteInfo>| notes.into_iter().map(|n| n.path).collect::<Vec<_>>();
+        assert_eq!(paths(repo.list_notes_modified_since(1_000).await), vec!["b.md"]);
+        assert_eq!(paths(repo.list_notes_modified_since(999).await), vec!["a.md", "b.md"]);
+        assert!(repo.list_notes_modified_since(i64::MAX).await.is_empty());
+
+        repo.write_note("a", "alpha 2", "markdown").await;
+        repo.delete_note("b.md").await;
+        let notes = repo.list_notes_modified_since(1_000).await;
+        assert_eq!(paths(notes.clone()), vec!["a.md"]);
+        assert_eq!(notes[0].note, "alpha 2");
+    }
+
+    #[tokio::test]
+    async fn test_list_notes_changed_since() {
+        let repo = Repository::new(MemoryStore::new());
+        let paths = |notes: Vec<crate::TypedNoteInfo>| notes.into_iter().map(|n| n.path).collect::<Vec<_>>();
+        let base = repo.write_note("a", "alpha", "markdown").await;
+        repo.write_note("b", "beta", "markdown").await;
+        assert!(repo.list_notes_changed_since(&repo.get_head().await.unwrap()).await.is_empty());
+
+        repo.write_note("b", "beta 2", "markdown").await;
+        repo.write_note("c", "gamma", "markdown").await;
+        repo.write_note("c", "gamma", "markdown").await;
+        repo.delete_note("a.md").await;
+        assert_eq!(paths(repo.list_notes_changed_since(&base).await), vec!["b.md", "c.md"]);
+
+        // An unknown base means the client has nothing yet
+        let unknown = Sha::from_hex("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap();
+        assert_eq!(paths(repo.list_notes_changed_since(&unknown).await), vec!["b.md", "c.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_compact() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        repo.write_note("a", "alpha", "markdown").await;
+        repo.write_note("dir/b", "beta", "markdown").await;
+        let shas = store.all_object_shas();
+
+        assert_eq!(store.compact(), shas.len());
+        assert_eq!(store.objects.lock().unwrap().by_id.len(), 0);
+        assert_eq!(store.object_count().await, Some(shas.len()));
+        for hex in &shas {
+            let sha = Sha::from_hex(hex).unwrap();
+            assert!(store.get_sync(&sha).is_some(), "{hex} lost by compact");
+            assert_eq!(store.unpack_pack_entry(&sha), store.get_sync(&sha));
+        }
+        assert_eq!(repo.get_note("dir/b.md").await.unwrap().note, "beta");
+
+        // New writes go loose, and the next compaction packs them with the rest
+        repo.write_note("a", "alpha 2", "markdown").await;
+        let (orphan, raw) = hash_blob(&Blob { content: b"unreachable".to_vec() });
+        store.put_sync(&orphan, raw);
+        let total = store.all_object_shas().len();
+        assert!(total > shas.len());
+        // Loose and packed objects are each visited once
+        let mut visited = Vec::new();
+        store.iter_objects(|sha, raw| {
+            assert_eq!(HashAlgorithm::Sha1.object_id(raw), ObjectId::Sha1(sha.clone()));
+            visited.push(sha.to_hex());
+        });
+        visited.sort();
+        let mut all = store.all_object_shas();
+        all.sort();
+        assert_eq!(visited, all);
+        assert_eq!(store.object_count().await, Some(total));
+        assert_eq!(store.compact(), total);
+        assert_eq!(repo.get_note("a.md").await.unwrap().note, "alpha 2");
+
+        // gc still sweeps packed objects
+        assert_eq!(repo.gc().await, 1);
+        assert!(store.get_sync(&orphan).is_none());
+        assert_eq!(repo.list_notes().await.len(), 2);
+
+        // A pack moved to another store brings every object along
+        let count = store.object_count().await.unwrap();
+        assert_eq!(store.compact(), count);
+        let copy = MemoryStore::new();
+        let pack = store.pack_data(DEFAULT_PACK).unwrap();
+        assert_eq!(copy.add_pack(DEFAULT_PACK, pack).unwrap(), count);
+        assert_eq!(copy.object_count().await, Some(count));
+        copy.set_ref_sync("HEAD", &store.get_ref_sync("HEAD").unwrap());
+        assert_eq!(Repository::new(copy.clone()).get_note("a.md").await.unwrap().note, "alpha 2");
+        assert!(copy.add_pack("broken", b"PACK".to_vec()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_from_pack() {
+        let remote = MemoryStore::new();
+        let remote_repo = Repository::new(remote.clone());
+        remote_repo.write_note("a", "alpha", "markdown").await;
+        remote_repo.write_note("dir/b", "beta", "markdown").await;
+        let head = remote_repo.get_head().await.unwrap();
+        let hexes: Vec<String> = remote_repo.reachable_objects().await.iter().map(Sha::to_hex).collect();
+        let pack = build_pack(&remote, &hexes).unwrap();
+
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        let (blob, _) = hash_blob(&Blob { content: b"alpha".to_vec() });
+        assert!(repo.import_from_pack(&pack, blob).await.is_err());
+        assert!(repo.import_from_pack(b"nope", head.clone()).await.is_err());
+        assert_eq!(repo.get_head().await, None);
+
+        assert_eq!(repo.import_from_pack(&pack, head.clone()).await, Ok(hexes.len()));
+        assert_eq!(repo.get_head().await, Some(head));
+        assert_eq!(repo.get_note("dir/b.md").await.unwrap().note, "beta");
+        assert_eq!(repo.list_notes().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_diff_commits() {
+        use crate::objects::parse_commit;
+        use crate::{ChangeKind, Sha, TreeDiff};
+
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        repo.write_note("a", "alpha", "markdown").await;
+        repo.write_note("dir/b", "beta", "markdown").await;
+        repo.write_note("dir/c", "gamma", "markdown").await;
+        let first = store.get_ref_sync("HEAD").unwrap();
+
+        repo.write_note("dir/b", "beta 2", "markdown").await;
+        repo.delete_note("a.md").await;
+        repo.write_note("new/deep/d", "delta", "markdown").await;
+        let second = store.get_ref_sync("HEAD").unwrap();
+
+        let diff = |path: &str, change| TreeDiff { path: path.to_string(), change };
+        assert_eq!(
+            repo.diff_commits(&first, &second).await,
+            vec![
+                diff("a.md", ChangeKind::Deleted),
+                diff("dir/b.md", ChangeKind::Modified),
+                diff("new/deep/d.md", ChangeKind::Added),
+            ]
+        );
+        assert_eq!(
+            repo.diff_commits(&second, &first).await,
+            vec![
+                diff("a.md", ChangeKind::Added),
+                diff("dir/b.md", ChangeKind::Modified),
+                diff("new/deep/d.md", ChangeKind::Deleted),
+            ]
+        );
+        assert!(repo.diff_commits(&second, &second).await.is_empty());
+
+        // A directory replaced by a file of the same name
+        let tree = |sha: &Sha| parse_commit(&store.get_sync(sha).unwrap()).unwrap().tree;
+        repo.delete_namespace("dir").await;
+        repo.write_note_raw("dir", b"now a file").await;
+        let third = store.get_ref_sync("HEAD").unwrap();
+        assert_eq!(
+            repo.diff_trees(&tree(&second), &tree(&third)).await,
+            vec![
+                diff("dir", ChangeKind::Added),
+                diff("dir/b.md", ChangeKind::Deleted),
+                diff("dir/c.md", ChangeKind::Deleted),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_push_on_save_default() {
+        use crate::config::TypedNotesConfig;
+
+        // Configs written before the flag existed keep pushing on save
+        let config = TypedNotesConfig::from_toml("[sync]\nauto_sync_interval_secs = 60\n").unwrap();
+        assert_eq!(config.sync.auto_sync_interval_secs, 60);
+        assert!(config.sync.push_on_save);
+
+        let config = TypedNotesConfig::default().with_push_on_save(false);
+        let parsed = TypedNotesConfig::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert!(!parsed.sync.push_on_save);
+    }
+
+    #[test]
+    fn test_config_paste_image_threshold() {
+        use crate::config::{TypedNotesConfig, DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD};
+
+        let config = TypedNotesConfig::from_toml("").unwrap();
+        assert_eq!(config.paste_image_inline_threshold_bytes, DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD);
+        assert_eq!(config, TypedNotesConfig::default());
+        // The default is not written out
+        assert!(!config.to_toml().unwrap().contains("paste_image"));
+
+        let config = TypedNotesConfig::from_toml("paste_image_inline_threshold_bytes = 0\n").unwrap();
+        assert_eq!(config.paste_image_inline_threshold_bytes, 0);
+        assert_eq!(TypedNotesConfig::from_toml(&config.to_toml().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        use crate::config::TypedNotesConfig;
+
+        assert!(TypedNotesConfig::new("notes".to_string()).validate().is_empty());
+
+        let mut config = TypedNotesConfig::new("/abs/../up".to_string()).with_sync_interval(90_000);
+        config.notes.max_note_size_bytes = Some(20 * 1024 * 1024);
+        assert_eq!(config.validate().len(), 4);
+
+        // Loading keeps the reason instead of silently falling back
+        let err = TypedNotesConfig::from_toml("[notes]\nroot = \"../outside\"\n").unwrap_err();
+        assert!(err.message.contains(".."), "{err}");
+        let err = TypedNotesConfig::from_toml("[sync]\nauto_sync_interval_secs = \"soon\"\n").unwrap_err();
+        assert!(err.message.starts_with("Invalid typednotes.toml"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_config_is_not_overwritten() {
+        use crate::config::TypedNotesConfig;
+
+        let repo = Repository::new(MemoryStore::new());
+        assert_eq!(repo.load_config().await.unwrap(), TypedNotesConfig::default());
+
+        let broken = b"pinned_notes = [\"a.md\"\n";
+        repo.write_note_raw(TypedNotesConfig::filename(), broken).await;
+        assert!(repo.load_config().await.is_err());
+        assert_eq!(repo.get_config().await, TypedNotesConfig::default());
+
+        // Writers leave the file alone rather than replace it with defaults
+        assert!(repo.pin_note("b.md").await.is_none());
+        assert!(repo.unpin_note("a.md").await.is_none());
+        assert_eq!(repo.read_raw(TypedNotesConfig::filename()).await.unwrap(), broken);
+    }
+
+    #[tokio::test]
+    async fn test_meta_is_kept_outside_history() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        assert!(repo.get_meta("remote-shas").await.is_none());
+
+        repo.write_note("a", "alpha", "markdown").await;
+        let head = repo.get_head().await;
+        repo.set_meta("remote-shas", b"one").await;
+        repo.set_meta("remote-shas", b"two").await;
+        assert_eq!(repo.get_meta("remote-shas").await.unwrap(), b"two");
+        assert_eq!(repo.get_head().await, head);
+        assert_eq!(repo.list_files().await.len(), 1);
+
+        // Only the replaced value is garbage
+        assert_eq!(repo.gc().await, 1);
+        assert_eq!(repo.get_meta("remote-shas").await.unwrap(), b"two");
+    }
+
+    #[test]
+    fn test_namespace_overrides() {
+        use crate::config::TypedNotesConfig;
+
+        let config = TypedNotesConfig::from_toml(
+            "[sync]\nauto_sync_interval_secs = 300\n\n\
+             [namespace_overrides.work]\nauto_sync_interval_secs = 60\nnote_type = \"text\"\n\n\
+             [namespace_overrides.\"work/slow\"]\nauto_sync_interval_secs = 0\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.effective_for_namespace("").auto_sync_interval_secs, 300);
+        assert_eq!(config.effective_for_namespace("workshop").auto_sync_interval_secs, 300);
+        let work = config.effective_for_namespace("work/ideas");
+        assert_eq!(work.auto_sync_interval_secs, 60);
+        assert_eq!(work.note_type.as_deref(), Some("text"));
+        // The longest match wins outright; its unset note type is not inherited from work
+        let slow = config.effective_for_namespace("work/slow");
+        assert_eq!(slow.auto_sync_interval_secs, 0);
+        assert_eq!(slow.note_type, None);
+
+        assert_eq!(config.shortest_sync_interval(), Some(60));
+        assert_eq!(config.store_backend, crate::StoreBackend::Memory);
+        let s3 = TypedNotesConfig::from_toml("store_backend = \"s3\"\n").unwrap();
+        assert_eq!(s3.store_backend, crate::StoreBackend::S3);
+        assert_eq!(config.hash_algorithm, HashAlgorithm::Sha1);
+        let sha256 = TypedNotesConfig::from_toml("hash_algorithm = \"sha256\"\n").unwrap();
+        assert_eq!(sha256.hash_algorithm, HashAlgorithm::Sha256);
+        let parsed = TypedNotesConfig::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed, config);
+    }
+
+    #[tokio::test]
+    async fn test_pin_and_unpin_note() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+        repo.write_note("todo", "T", "markdown").await;
+
+        assert!(repo.pin_note("todo.md").await.is_some());
+        assert!(repo.pin_note("todo.md").await.is_none());
+        assert!(repo.pin_note("work/plan.md").await.is_some());
+        assert_eq!(repo.get_config().await.pinned_notes, ["todo.md", "work/plan.md"]);
+
+        assert!(repo.unpin_note("todo.md").await.is_some());
+        assert!(repo.unpin_note("todo.md").await.is_none());
+        assert_eq!(repo.get_config().await.pinned_notes, ["work/plan.md"]);
+        // Other settings survive the round trip
+        assert_eq!(repo.get_config().await.sync.auto_sync_interval_secs, 300);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_styles() {
+        use crate::config::{NamespaceStyle, TypedNotesConfig};
+
+        let repo = Repository::new(MemoryStore::new());
+        let work = NamespaceStyle { icon: Some("briefcase".to_string()), color: Some("#3b82f6".to_string()) };
+        assert!(repo.set_namespace_style("work/", work.clone()).await.is_some());
+        assert!(repo.set_namespace_style("work", work.clone()).await.is_none());
+
+        let config = repo.get_config().await;
+        assert_eq!(config.get_namespace_style("work"), work);
+        // Sub-namespaces keep the plain folder
+        assert!(config.get_namespace_style("work/ideas").is_default());
+        assert_eq!(TypedNotesConfig::from_toml(&config.to_toml().unwrap()).unwrap(), config);
+
+        assert!(repo.set_namespace_style("work", NamespaceStyle::default()).await.is_some());
+        assert!(repo.get_config().await.namespace_styles.is_empty());
+        assert!(repo.set_namespace_style("work", NamespaceStyle::default()).await.is_none());
+
+        // A color that could escape the inline style is rejected
+        let err = TypedNotesConfig::from_toml("[namespace_styles.work]\ncolor = \"red; background: url(x)\"\n")
+            .unwrap_err();
+        assert!(err.message.contains("Color for work"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_txn_rollback_and_commit() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        repo.write_note("keep", "K", "markdown").await;
+        let head = store.get_ref_sync("HEAD");
+        let objects = store.all_object_shas().len();
+
+        let blob = Blob { content: b"x".to_vec() };
+        let (sha, raw) = hash_blob(&blob);
+
+        let txn = store.begin_txn();
+        store.put(&sha, raw.clone()).await;
+        store.set_ref("HEAD", &sha).await;
+        txn.rollback();
+        assert!(store.get_sync(&sha).is_none());
+        assert_eq!(store.get_ref_sync("HEAD"), head);
+
+        // Dropping an uncommitted guard rolls back too
+        {
+            let _txn = store.begin_txn();
+            store.put(&sha, raw.clone()).await;
+        }
+        assert_eq!(store.all_object_shas().len(), objects);
+
+        let txn = store.begin_txn();
+        store.put(&sha, raw).await;
+        txn.commit();
+        assert!(store.get_sync(&sha).is_some());
+        assert_eq!(repo.get_note("keep.md").await.unwrap().note, "K");
+    }
+
+    #[tokio::test]
+    async fn test_txn_rollback_keeps_later_writes() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        let first = repo.write_note("keep", "K", "markdown").await;
+        let (sha, raw) = hash_blob(&Blob { content: b"x".to_vec() });
+
+        let txn = store.begin_txn();
+        store.put(&sha, raw).await;
+        store.set_ref("HEAD", &sha).await;
+        // Someone else moves HEAD on before the rollback
+        store.set_ref_sync("HEAD", &first);
+        txn.rollback();
+        assert_eq!(store.get_ref_sync("HEAD"), Some(first.clone()));
+        assert!(store.get_sync(&sha).is_none());
+
+        // Writes made while two transactions are open are not undone
+        let (other, other_raw) = hash_blob(&Blob { content: b"y".to_vec() });
+        let outer = store.begin_txn();
+        let inner = store.begin_txn();
+        store.put(&other, other_raw).await;
+        inner.commit();
+        outer.rollback();
+        assert!(store.get_sync(&other).is_some());
+        assert_eq!(repo.get_note("keep.md").await.unwrap().note, "K");
+    }
+
+    #[tokio::test]
+    async fn test_gc_frees_unreachable_objects() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        assert_eq!(repo.gc().await, 0);
+
+        repo.write_note("work/plan", "v1", "markdown").await;
+        repo.write_note("work/plan", "v2", "markdown").await;
+        repo.delete_note("work/plan.md").await;
+        repo.write_note("todo", "T", "markdown").await;
+        // An object nothing points to
+        let orphan = Blob { content: b"orphan".to_vec() };
+        let (orphan_sha, orphan_raw) = hash_blob(&orphan);
+        store.put_sync(&orphan_sha, orphan_raw);
+
+        let before = store.all_object_shas().len();
+        let keep = repo.reachable_objects().await;
+        // Old versions stay reachable through history; only the orphan goes
+        assert_eq!(keep.len(), before - 1);
+        assert_eq!(repo.gc().await, 1);
+        assert_eq!(store.all_object_shas().len(), keep.len());
+        assert!(store.get_sync(&orphan_sha).is_none());
+
+        // History is intact
+        assert_eq!(repo.get_note("todo.md").await.unwrap().note, "T");
+        assert_eq!(repo.note_history("work/plan.md", 10).await.len(), 3);
+        assert_eq!(repo.gc().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_gc_keeps_objects_of_other_refs_and_tags() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        let base = repo.write_note("todo", "T", "markdown").await;
+        let branch = repo.write_note("branch", "on a branch", "markdown").await;
+        store.set_ref_sync("refs/heads/side", &branch);
+        store.set_ref_sync("HEAD", &base);
+        let released = repo.write_note("release", "tagged", "markdown").await;
+        let (tag_sha, tag_raw) = hash_tag(&Tag {
+            object: released.clone(),
+            target_type: "commit".to_string(),
+            tag_name: "v1".to_string(),
+            tagger: "Test User <test@example.com> 1700000000 +0000".to_string(),
+            message: "First release".to_string(),
+        });
+        store.put_sync(&tag_sha, tag_raw);
+        store.set_ref_sync("refs/tags/v1", &tag_sha);
+        store.set_ref_sync("HEAD", &base);
+
+        assert_eq!(repo.gc().await, 0);
+        assert!(store.get_sync(&tag_sha).is_some());
+        assert_eq!(repo.get_note_in_commit(&branch, "branch.md").await.unwrap().note, "on a branch");
+        assert_eq!(repo.get_note_in_commit(&released, "release.md").await.unwrap().note, "tagged");
+
+        // Dropping the tag frees its object, the commit and what only it added
+        store.refs.lock().unwrap().remove("refs/tags/v1");
+        assert_eq!(repo.gc().await, 4);
+        assert!(store.get_sync(&released).is_none());
+        assert_eq!(repo.get_note("todo.md").await.unwrap().note, "T");
+    }
+
+    #[tokio::test]
+    async fn test_copy_note() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+        repo.write_note("work/plan", "# Plan", "markdown").await;
+
+        assert!(repo.copy_note("work/plan.md", "work/plan-copy.md").await.is_some());
+        let copy = repo.get_note("work/plan-copy.md").await.unwrap();
+        assert_eq!(copy.note, "# Plan");
+        assert_eq!(copy.namespace.as_deref(), Some("work"));
+        assert!(repo.get_note("work/plan.md").await.is_some());
+
+        assert!(repo.copy_note("work/plan.md", "work/plan-copy.md").await.is_none());
+        assert!(repo.copy_note("missing.md", "other.md").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_commit_batch() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+        let head = repo.write_note("existing", "E", "markdown").await;
+
+        repo.write_note_batch_raw("a.md", b"A").await;
+        repo.write_note_batch_raw("work/b.txt", b"B").await;
+        // Nothing is committed until commit_batch
+        assert_eq!(repo.get_head().await, Some(head.clone()));
+
+        let sha = repo.commit_batch("Batch sync 2 notes".to_string()).await;
+        let commit = repo.get_commit(&sha).await.unwrap();
+        assert_eq!(commit.parents, vec![head]);
+        assert_eq!(commit.message, "Batch sync 2 notes");
+
+        let mut paths: Vec<String> = repo.list_notes().await.into_iter().map(|n| n.path).collect();
+        paths.sort();
+        assert_eq!(paths, ["a.md", "existing.md", "work/b.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_namespace() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+        repo.write_note("work/plan", "P", "markdown").await;
+        repo.write_note("work/ideas/idea", "I", "text").await;
+        repo.create_namespace("work/empty").await;
+        repo.write_note("other", "O", "markdown").await;
+        let head = repo.get_head().await;
+
+        let sha = repo.rename_namespace("work", "archive/work").await.unwrap();
+        let commit = repo.get_commit(&sha).await.unwrap();
+        assert_eq!(commit.first_parent(), head.as_ref());
+
+        let mut paths: Vec<String> = repo.list_notes().await.into_iter().map(|n| n.path).collect();
+        paths.sort();
+        assert_eq!(paths, ["archive/work/ideas/idea.txt", "archive/work/plan.md", "other.md"]);
+        assert_eq!(repo.get_note("archive/work/plan.md").await.unwrap().note, "P");
+        let mut namespaces: Vec<String> = repo.list_namespaces().await.into_iter().map(|n| n.path).collect();
+        namespaces.sort();
+        assert_eq!(namespaces, ["archive", "archive/work", "archive/work/empty", "archive/work/ideas"]);
+
+        // Missing source, existing target and moving into itself are refused
+        assert!(repo.rename_namespace("work", "w").await.is_none());
+        assert!(repo.rename_namespace("archive/work/ideas", "archive/work/empty").await.is_none());
+        assert!(repo.rename_namespace("archive", "archive/inner").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_move_namespace_single_commit() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+        repo.write_note("work/plan", "P", "markdown").await;
+        repo.write_note("work/ideas/idea", "I", "text").await;
+        repo.write_note("archive/old", "O", "markdown").await;
+        let head = repo.get_head().await;
+
+        // Moving into an existing namespace merges the two
+        let sha = repo.move_namespace("work", "archive").await.unwrap();
+        assert_eq!(repo.get_commit(&sha).await.unwrap().first_parent(), head.as_ref());
+
+        let mut paths: Vec<String> = repo.list_notes().await.into_iter().map(|n| n.path).collect();
+        paths.sort();
+        assert_eq!(paths, ["archive/ideas/idea.txt", "archive/old.md", "archive/plan.md"]);
+
+        assert!(repo.move_namespace("work", "elsewhere").await.is_none());
+        assert!(repo.move_namespace("archive", "archive/inner").await.is_none());
+
+        let sha = repo.delete_namespace("archive").await.unwrap();
+        assert_eq!(repo.get_commit(&sha).await.unwrap().message, "Delete namespace archive");
+        assert!(repo.list_notes().await.is_empty());
+    }
+
+    #[test]
+    fn test_all_ref_names_sorted() {
+        let store = MemoryStore::new();
+        let sha = Sha::from_hex("0123456789abcdef0123456789abcdef01234567").unwrap();
+        store.set_ref_sync("refs/heads/main", &sha);
+        store.set_ref_sync("HEAD", &sha);
+        store.set_ref_sync("refs/heads/drafts", &sha);
+        assert_eq!(store.all_ref_names(), ["HEAD", "refs/heads/drafts", "refs/heads/main"]);
+    }
+
+    #[test]
+    fn test_all_refs() {
+        let store = MemoryStore::new();
+        let main = Sha::from_hex("0123456789abcdef0123456789abcdef01234567").unwrap();
+        let drafts = Sha::from_hex("89abcdef0123456789abcdef0123456789abcdef").unwrap();
+        store.set_ref_sync("refs/heads/main", &main);
+        store.set_ref_sync("refs/heads/drafts", &drafts);
+
+        let refs = store.all_refs();
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs["refs/heads/main"], main);
+        assert_eq!(refs["refs/heads/drafts"], drafts);
+    }
+
     #[tokio::test]
     async fn test_list_notes_in_subtree() {
         let store = MemoryStore::new();
@@ -254,4 +1459,376 @@ mod tests {
         assert_eq!(docs_ns.len(), 1);
         assert_eq!(docs_ns[0].name, "sub");
     }
+
+    #[tokio::test]
+    async fn test_list_namespace_children() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+
+        repo.write_note("root-note", "Root", "markdown").await;
+        repo.write_note("docs/inner", "Inner", "markdown").await;
+        repo.write_note("docs/sub/deep", "Deep", "text").await;
+
+        let (namespaces, notes) = repo.list_namespace_children("").await;
+        assert_eq!(namespaces.len(), 1);
+        assert_eq!(namespaces[0].path, "docs");
+        assert_eq!(namespaces[0].parent, None);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].path, "root-note.md");
+
+        // Full paths, one level only
+        let (namespaces, notes) = repo.list_namespace_children("docs").await;
+        assert_eq!(namespaces[0].path, "docs/sub");
+        assert_eq!(namespaces[0].parent.as_deref(), Some("docs"));
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].path, "docs/inner.md");
+        assert_eq!(notes[0].namespace.as_deref(), Some("docs"));
+
+        assert!(repo.list_namespace_children("nope").await.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_note_history() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+
+        repo.write_note("journal", "v1", "markdown").await;
+        repo.write_note("other", "unrelated", "markdown").await;
+        repo.write_note("journal", "v2", "markdown").await;
+        repo.write_note("journal", "v2", "markdown").await; // no content change
+        repo.delete_note("journal.md").await;
+
+        let history = repo.note_history("journal.md", 10).await;
+        let messages: Vec<&str> = history.iter().map(|(_, c)| c.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["Delete journal.md", "Update journal.md", "Update journal.md"]
+        );
+
+        // Limit is honoured, newest first
+        let limited = repo.note_history("journal.md", 1).await;
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].1.message, "Delete journal.md");
+
+        assert_eq!(repo.note_history("other.md", 10).await.len(), 1);
+        assert!(repo.note_history("missing.md", 10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_note_history_through_merge() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+
+        let base = repo.write_note("journal", "base", "markdown").await;
+        let theirs = repo.write_note("journal", "theirs", "markdown").await;
+        // Branch off `base` again for our side
+        store.set_ref_sync("HEAD", &base);
+        let ours = repo.write_note("journal", "ours", "markdown").await;
+        let resolved = repo.write_note("journal", "merged", "markdown").await;
+
+        let resolved = repo.get_commit(&resolved).await.unwrap();
+        let merge = Commit {
+            parents: vec![ours.clone(), theirs.clone()],
+            message: "Merge theirs".to_string(),
+            ..resolved
+        };
+        let (merge_sha, raw) = hash_commit(&merge);
+        store.put_sync(&merge_sha, raw);
+        store.set_ref_sync("HEAD", &merge_sha);
+
+        let history: Vec<Sha> = repo
+            .note_history("journal.md", 10)
+            .await
+            .into_iter()
+            .map(|(sha, _)| sha)
+            .collect();
+        assert_eq!(history, vec![merge_sha, ours, theirs.clone(), base]);
+
+        // The merged-in side is reachable through the second parent
+        let old = repo.get_note_at(&theirs, "journal.md").await.unwrap();
+        assert_eq!(old.note, "theirs");
+    }
+
+    #[tokio::test]
+    async fn test_unicode_paths_are_normalized() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+        let nfd = "Re\u{301}sume\u{301}/Caf\u{e9}";
+        let nfc = "R\u{e9}sum\u{e9}/Caf\u{e9}";
+
+        repo.write_note(nfd, "mine", "markdown").await;
+        let by_nfc = repo.get_note(&format!("{nfc}.md")).await.unwrap();
+        let by_nfd = repo.get_note(&format!("{nfd}.md")).await.unwrap();
+        assert_eq!(by_nfc.sha, by_nfd.sha);
+        assert_eq!(by_nfc.path, format!("{nfc}.md"));
+
+        // Writing the NFC spelling updates the same note
+        repo.write_note(nfc, "updated", "markdown").await;
+        let notes = repo.list_notes().await;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].note, "updated");
+
+        repo.delete_note(&format!("{nfd}.md")).await;
+        assert!(repo.list_notes().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_decomposed_entries_from_other_clients() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+
+        // A tree as committed by git on macOS, with an NFD file name
+        let (blob_sha, blob_raw) = hash_blob(&Blob { content: b"old".to_vec() });
+        store.put_sync(&blob_sha, blob_raw);
+        let (tree_sha, tree_raw) = hash_tree(&Tree {
+            entries: vec![TreeEntry {
+                mode: "100644".to_string(),
+                name: "Re\u{301}sume\u{301}.md".to_string(),
+                sha: blob_sha.clone(),
+            }],
+        });
+        store.put_sync(&tree_sha, tree_raw);
+        let (commit_sha, commit_raw) = hash_commit(&Commit {
+            tree: tree_sha,
+            parents: Vec::new(),
+            author: "Mac <mac@example.com>".to_string(),
+            message: "From a Mac".to_string(),
+            timestamp: 1700000000,
+        });
+        store.put_sync(&commit_sha, commit_raw);
+        store.set_ref_sync("HEAD", &commit_sha);
+
+        let note = repo.get_note("R\u{e9}sum\u{e9}.md").await.unwrap();
+        assert_eq!(note.sha, blob_sha.to_hex());
+
+        repo.write_note("R\u{e9}sum\u{e9}", "new", "markdown").await;
+        let notes = repo.list_notes().await;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].path, "R\u{e9}sum\u{e9}.md");
+    }
+
+    #[tokio::test]
+    async fn test_submodules_and_symlinks_are_skipped() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+
+        // A note inside a tree that only a gitlink points to
+        let (blob_sha, blob_raw) = hash_blob(&Blob { content: b"hidden".to_vec() });
+        store.put_sync(&blob_sha, blob_raw);
+        let (sub_sha, sub_raw) = hash_tree(&Tree {
+            entries: vec![TreeEntry {
+                mode: "100644".to_string(),
+                name: "inner.md".to_string(),
+                sha: blob_sha.clone(),
+            }],
+        });
+        store.put_sync(&sub_sha, sub_raw);
+        let (tree_sha, tree_raw) = hash_tree(&Tree {
+            entries: vec![
+                TreeEntry {
+                    mode: "100644".to_string(),
+                    name: "note.md".to_string(),
+                    sha: blob_sha.clone(),
+                },
+                TreeEntry {
+                    mode: "120000".to_string(),
+                    name: "link.md".to_string(),
+                    sha: blob_sha.clone(),
+                },
+                TreeEntry {
+                    mode: "160000".to_string(),
+                    name: "vendor".to_string(),
+                    sha: sub_sha,
+                },
+            ],
+        });
+        store.put_sync(&tree_sha, tree_raw);
+        let (commit_sha, commit_raw) = hash_commit(&Commit {
+            tree: tree_sha,
+            parents: Vec::new(),
+            author: "Git <git@example.com>".to_string(),
+            message: "Add submodule".to_string(),
+            timestamp: 1700000000,
+        });
+        store.put_sync(&commit_sha, commit_raw);
+        store.set_ref_sync("HEAD", &commit_sha);
+
+        let paths: Vec<String> = repo.list_notes().await.into_iter().map(|n| n.path).collect();
+        assert_eq!(paths, vec!["note.md"]);
+        assert!(repo.list_namespaces().await.is_empty());
+        let (namespaces, notes) = repo.list_namespace_children("").await;
+        assert!(namespaces.is_empty());
+        assert_eq!(notes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_move_keeps_submodules_and_symlinks() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+
+        let (blob_sha, blob_raw) = hash_blob(&Blob { content: b"plan".to_vec() });
+        store.put_sync(&blob_sha, blob_raw);
+        // A commit of another repository, not in this store
+        let submodule = Sha([9; 20]);
+        let (work_sha, work_raw) = hash_tree(&Tree {
+            entries: vec![
+                TreeEntry {
+                    mode: "100644".to_string(),
+                    name: "plan.md".to_string(),
+                    sha: blob_sha.clone(),
+                },
+                TreeEntry {
+                    mode: "120000".to_string(),
+                    name: "link.md".to_string(),
+                    sha: blob_sha.clone(),
+                },
+                TreeEntry {
+                    mode: "160000".to_string(),
+                    name: "vendor".to_string(),
+                    sha: submodule.clone(),
+                },
+            ],
+        });
+        store.put_sync(&work_sha, work_raw);
+        let (tree_sha, tree_raw) = hash_tree(&Tree {
+            entries: vec![TreeEntry {
+                mode: "40000".to_string(),
+                name: "work".to_string(),
+                sha: work_sha,
+            }],
+        });
+        store.put_sync(&tree_sha, tree_raw);
+        let (commit_sha, commit_raw) = hash_commit(&Commit {
+            tree: tree_sha,
+            parents: Vec::new(),
+            author: "Git <git@example.com>".to_string(),
+            message: "Add submodule".to_string(),
+            timestamp: 1700000000,
+        });
+        store.put_sync(&commit_sha, commit_raw);
+        store.set_ref_sync("HEAD", &commit_sha);
+
+        // The gitlink is not an object of this repository
+        assert!(!repo.reachable_objects().await.contains(&submodule));
+        assert_eq!(repo.gc().await, 0);
+
+        repo.rename_namespace("work", "archive").await.unwrap();
+        let head_raw = store.get_sync(&store.get_ref_sync("HEAD").unwrap()).unwrap();
+        let head = crate::objects::parse_commit(&head_raw).unwrap();
+        let root = crate::objects::parse_tree(&store.get_sync(&head.tree).unwrap()).unwrap();
+        assert_eq!(root.entries.len(), 1);
+        let archive = crate::objects::parse_tree(&store.get_sync(&root.entries[0].sha).unwrap()).unwrap();
+        let modes: Vec<(&str, &str)> = archive
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.mode.as_str()))
+            .collect();
+        assert_eq!(modes, vec![("link.md", "120000"), ("plan.md", "100644"), ("vendor", "160000")]);
+    }
+
+    #[tokio::test]
+    async fn test_get_note_at_commit() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+
+        let first = repo.write_note("draft", "first version", "markdown").await;
+        repo.write_note("draft", "second version", "markdown").await;
+
+        let old = repo.get_note_at(&first, "draft.md").await.unwrap();
+        assert_eq!(old.note, "first version");
+        assert_eq!(repo.get_note("draft.md").await.unwrap().note, "second version");
+
+        // Unknown commits are not reachable from HEAD
+        let foreign = Sha([7; 20]);
+        assert!(repo.get_note_at(&foreign, "draft.md").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_templates() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+        assert!(repo.list_templates().await.is_empty());
+
+        repo.write_note(".templates/meeting", "# {{title}}", "markdown").await;
+        repo.write_note(".templates/daily/journal", "{{date}}", "text").await;
+        repo.write_note("notes/other", "not a template", "markdown").await;
+
+        let mut paths: Vec<String> = repo.list_templates().await.into_iter().map(|n| n.path).collect();
+        paths.sort();
+        assert_eq!(paths, [".templates/daily/journal.txt", ".templates/meeting.md"]);
+        let meeting = repo.get_note(".templates/meeting.md").await.unwrap();
+        assert_eq!(meeting.namespace.as_deref(), Some(".templates"));
+    }
+
+    #[tokio::test]
+    async fn test_list_notes_at_commit() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store);
+
+        let first = repo.write_note("a", "alpha", "markdown").await;
+        repo.write_note("b", "beta", "text").await;
+
+        let notes = repo.list_notes_at(&first).await;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].path, "a.md");
+        assert_eq!(repo.list_notes().await.len(), 2);
+
+        assert!(repo.list_notes_at(&Sha([7; 20])).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sha256_keyed_store() {
+        let store = MemoryStore::with_hash_algorithm(HashAlgorithm::Sha256);
+        let repo = Repository::new(store.clone());
+        repo.write_note("a", "alpha", "markdown").await;
+        repo.write_note("b", "beta", "text").await;
+        assert_eq!(repo.get_note("a.md").await.unwrap().note, "alpha");
+
+        let (blob_sha, raw) = hash_blob(&Blob {
+            content: b"alpha".to_vec(),
+        });
+        let id = store.object_id(&blob_sha).unwrap();
+        assert_eq!(id, HashAlgorithm::Sha256.object_id(&raw));
+        assert_eq!(id.algorithm(), HashAlgorithm::Sha256);
+
+        // SHA-1 names still come back out, and gc still sweeps by them
+        let shas = store.all_object_shas();
+        assert!(shas.contains(&blob_sha.to_hex()));
+        assert!(shas.iter().all(|sha| sha.len() == 40));
+        let keep: HashSet<Sha> = HashSet::from([blob_sha.clone()]);
+        assert_eq!(store.retain_objects(&keep), shas.len() - 1);
+        assert_eq!(store.get_sync(&blob_sha), Some(raw));
+        assert_eq!(store.all_object_shas(), vec![blob_sha.to_hex()]);
+
+        let copy = store.clone_deep();
+        assert_eq!(copy.hash_algorithm(), HashAlgorithm::Sha256);
+        assert_eq!(copy.object_id(&blob_sha), Some(id));
+    }
+
+    #[tokio::test]
+    async fn test_apply_hash_algorithm() {
+        let store = MemoryStore::new();
+        let repo = Repository::new(store.clone());
+        repo.write_note("a", "alpha", "markdown").await;
+        let count = store.object_count().await.unwrap();
+
+        let mut config = repo.get_config().await;
+        config.hash_algorithm = HashAlgorithm::Sha256;
+        repo.set_config(&config).await;
+        assert!(repo.apply_hash_algorithm().await);
+        assert_eq!(store.hash_algorithm(), HashAlgorithm::Sha256);
+        let (blob_sha, raw) = hash_blob(&Blob {
+            content: b"alpha".to_vec(),
+        });
+        assert_eq!(store.object_id(&blob_sha), Some(HashAlgorithm::Sha256.object_id(&raw)));
+        assert_eq!(repo.get_note("a.md").await.unwrap().note, "alpha");
+        // The config write added a blob, a tree and a commit
+        assert_eq!(store.object_count().await, Some(count + 3));
+
+        // And back
+        assert!(store.set_hash_algorithm(HashAlgorithm::Sha1).await);
+        assert_eq!(store.object_id(&blob_sha), Some(ObjectId::Sha1(blob_sha.clone())));
+        assert_eq!(repo.get_note("a.md").await.unwrap().note, "alpha");
+        assert_eq!(store.object_count().await, Some(count + 3));
+    }
 }
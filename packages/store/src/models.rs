@@ -8,20 +8,36 @@
 //!
 //! | Struct | Represents |
 //! |--------|-----------|
-//! | [`TypedNoteInfo`] | A single note file in the repository. Carries the full tree path, a human-friendly `name` (filename without extension), an optional `namespace` (parent directory), the note `type` (`"markdown"` or `"text"`), the body content, and the blob SHA for change detection. |
+//! | [`TypedNoteInfo`] | A single note file in the repository. Carries the full tree path, a human-friendly `name` (filename without extension), an optional `namespace` (parent directory), the note `type` (one of [`NOTE_TYPES`]), the body content, the blob SHA for change detection, the parsed frontmatter `metadata` (`tags()` and `title()` read from it), and whether the note is `encrypted`. |
 //! | [`NamespaceInfo`] | A directory in the repository's note tree. Stores its full path, display name, and optional parent — used by the UI to render a folder hierarchy. |
 //!
 //! ## Helper functions
 //!
 //! - [`note_type_from_ext`] — maps a file extension to a note type (`"md"` → `"markdown"`,
-//!   everything else → `"text"`).
+//!   `"org"` → `"org"`, unknown extensions → `"text"`).
 //! - [`ext_from_note_type`] — the inverse mapping (`"markdown"` → `"md"`, default `"txt"`).
 //!
+//! [`NOTE_TYPES`] lists every type: Markdown (`.md`), plain text (`.txt`),
+//! Org-mode (`.org`), reStructuredText (`.rst`), AsciiDoc (`.adoc`) and LaTeX
+//! (`.tex`). Only Markdown is rendered; the others are edited as text, Org-mode
+//! as preformatted text (see [`is_verbatim_note_type`]).
+//! - [`split_note_filename`], [`note_type_from_path`] and [`note_file_path`] —
+//!   the same, aware of the [`ENCRYPTED_EXT`] suffix of encrypted notes.
+//!
+//! ## Encrypted notes
+//!
+//! A note encrypted on the client is stored as `name.md.enc` (or `name.txt.enc`),
+//! its blob holding the base64 nonce and ciphertext rather than the note text;
+//! see `api::crypto`. The store never sees the key, so such notes are listed
+//! with `encrypted: true` and their stored text as `note`.
+//!
 //! These are used by [`crate::Repository`] when reading notes from the tree and when
 //! constructing file paths for new or updated notes.
 
 use serde::{Deserialize, Serialize};
 
+use crate::frontmatter::NoteMetadata;
+
 /// Information about a note stored in the git tree.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TypedNoteInfo {
@@ -31,12 +47,35 @@ pub struct TypedNoteInfo {
     pub name: String,
     /// Directory path or None for root: Some("work")
     pub namespace: Option<String>,
-    /// Note type derived from extension: "markdown" or "text"
+    /// Note type derived from extension: "markdown", "text", "org", "rst", "adoc" or "tex"
     pub r#type: String,
     /// Body content of the note
     pub note: String,
     /// Blob SHA hex string for change detection
     pub sha: String,
+    /// The note's YAML frontmatter, if it has any (see [`crate::frontmatter`]).
+    /// `note` still holds the full content, frontmatter included.
+    #[serde(default)]
+    pub metadata: Option<NoteMetadata>,
+    /// Stored with the [`ENCRYPTED_EXT`] suffix: `note` is ciphertext.
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+impl TypedNoteInfo {
+    /// Tags from the frontmatter (empty without frontmatter).
+    pub fn tags(&self) -> &[String] {
+        self.metadata.as_ref().map_or(&[], |m| &m.tags)
+    }
+
+    /// Name to show for the note: the frontmatter `title` when set, otherwise
+    /// the file name.
+    pub fn title(&self) -> &str {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.title.as_deref())
+            .unwrap_or(&self.name)
+    }
 }
 
 /// Information about a namespace (directory) in the git tree.
@@ -50,11 +89,62 @@ pub struct NamespaceInfo {
     pub parent: Option<String>,
 }
 
+/// Extension appended to the file name of an encrypted note.
+pub const ENCRYPTED_EXT: &str = "enc";
+
+/// Split a note file name into `(name, extension, encrypted)`:
+/// `"plan.md"` → `("plan", "md", false)`, `"plan.md.enc"` → `("plan", "md", true)`.
+/// `None` unless the extension is one of [`NOTE_TYPES`].
+pub fn split_note_filename(filename: &str) -> Option<(&str, &str, bool)> {
+    let (filename, encrypted) = match filename.strip_suffix(ENCRYPTED_EXT) {
+        Some(rest) if rest.ends_with('.') => (&rest[..rest.len() - 1], true),
+        _ => (filename, false),
+    };
+    let (name, ext) = filename.rsplit_once('.')?;
+    is_note_ext(ext).then_some((name, ext, encrypted))
+}
+
+/// Note type of the file at `path`, looking past an [`ENCRYPTED_EXT`] suffix.
+pub fn note_type_from_path(path: &str) -> &str {
+    let filename = path.rsplit('/').next().unwrap_or(path);
+    split_note_filename(filename).map_or("text", |(_, ext, _)| note_type_from_ext(ext))
+}
+
+/// `path` with the extension of `note_type` appended, unless it already ends
+/// in it (optionally followed by [`ENCRYPTED_EXT`]).
+pub fn note_file_path(path: &str, note_type: &str) -> String {
+    let ext = ext_from_note_type(note_type);
+    if path.ends_with(&format!(".{ext}")) || path.ends_with(&format!(".{ext}.{ENCRYPTED_EXT}")) {
+        path.to_string()
+    } else {
+        format!("{path}.{ext}")
+    }
+}
+
+/// Every note type as `(type, file extension, label)`.
+pub const NOTE_TYPES: [(&str, &str, &str); 6] = [
+    ("markdown", "md", "Markdown"),
+    ("text", "txt", "Plain text"),
+    ("org", "org", "Org-mode"),
+    ("rst", "rst", "reStructuredText"),
+    ("adoc", "adoc", "AsciiDoc"),
+    ("tex", "tex", "LaTeX"),
+];
+
+/// Whether files with extension `ext` are notes.
+pub fn is_note_ext(ext: &str) -> bool {
+    NOTE_TYPES.iter().any(|(_, note_ext, _)| *note_ext == ext)
+}
+
 /// Derive note type from file extension.
 pub fn note_type_from_ext(ext: &str) -> &str {
     match ext {
         "md" => "markdown",
         "txt" => "text",
+        "org" => "org",
+        "rst" => "rst",
+        "adoc" => "adoc",
+        "tex" => "tex",
         _ => "text",
     }
 }
@@ -64,6 +154,56 @@ pub fn ext_from_note_type(note_type: &str) -> &str {
     match note_type {
         "markdown" => "md",
         "text" => "txt",
+        "org" => "org",
+        "rst" => "rst",
+        "adoc" => "adoc",
+        "tex" => "tex",
         _ => "txt",
     }
 }
+
+/// Whether notes of `note_type` are shown preformatted — monospace, no
+/// wrapping — because their markup lines up in columns (Org-mode tables and
+/// `#+BEGIN_SRC` blocks).
+pub fn is_verbatim_note_type(note_type: &str) -> bool {
+    note_type == "org"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_note_paths() {
+        assert_eq!(split_note_filename("plan.md"), Some(("plan", "md", false)));
+        assert_eq!(split_note_filename("plan.md.enc"), Some(("plan", "md", true)));
+        assert_eq!(split_note_filename("v1.2.txt.enc"), Some(("v1.2", "txt", true)));
+        assert_eq!(split_note_filename("plan.enc"), None);
+        assert_eq!(split_note_filename("cat.png"), None);
+
+        assert_eq!(note_type_from_path("work/plan.md.enc"), "markdown");
+        assert_eq!(note_file_path("work/plan", "markdown"), "work/plan.md");
+        assert_eq!(note_file_path("work/plan.md.enc", "markdown"), "work/plan.md.enc");
+        assert_eq!(note_file_path("work/plan.md.enc", "text"), "work/plan.md.enc.txt");
+    }
+
+    #[test]
+    fn test_note_type_ext_round_trip() {
+        for (note_type, ext, _) in NOTE_TYPES {
+            assert_eq!(ext_from_note_type(note_type), ext);
+            assert_eq!(note_type_from_ext(ext), note_type);
+            assert_eq!(note_type_from_ext(ext_from_note_type(note_type)), note_type);
+            assert_eq!(ext_from_note_type(note_type_from_ext(ext)), ext);
+
+            let filename = format!("agenda.{ext}");
+            assert_eq!(split_note_filename(&filename), Some(("agenda", ext, false)));
+            assert_eq!(note_type_from_path(&format!("work/{filename}.enc")), note_type);
+            assert_eq!(note_file_path("work/agenda", note_type), format!("work/{filename}"));
+        }
+        assert_eq!(note_type_from_ext("pdf"), "text");
+        assert_eq!(ext_from_note_type("docx"), "txt");
+        assert!(!is_note_ext("png"));
+        assert!(is_verbatim_note_type("org"));
+        assert!(!is_verbatim_note_type("markdown"));
+    }
+}
@@ -11,8 +11,11 @@
 //! |--------|----------|-------------|
 //! | [`Blob`] | `blob` | Raw file content (a note's body). |
 //! | [`Tree`] | `tree` | A sorted directory listing of [`TreeEntry`] items, each carrying a mode, name, and child SHA. |
-//! | [`Commit`] | `commit` | Points to a root [`Tree`] SHA, an optional parent commit, author/timestamp metadata, and a message. |
-//! | [`Sha`] | — | A 20-byte SHA-1 hash that uniquely identifies any object. Supports hex round-tripping via [`Sha::from_hex`] / [`Sha::to_hex`]. |
+//! | [`Commit`] | `commit` | Points to a root [`Tree`] SHA, its parent commits (none for a root commit, several for a merge), author/timestamp metadata, and a message. |
+//! | [`Tag`] | `tag` | An annotated tag: the tagged object's SHA, the tag name, the tagger line, and a message. |
+//! | [`Sha`] | — | A 20-byte SHA-1 hash that uniquely identifies any object. Supports hex round-tripping via [`Sha::from_hex`] / [`Sha::to_hex`], formats as hex with `{}` and `{:x}`, and orders by its bytes. |
+//! | [`Sha256Hash`] | — | A 32-byte SHA-256 hash of the same serialised object, for local addressing (see below). |
+//! | [`ObjectId`] | — | Either of the two, tagged with its [`HashAlgorithm`]. |
 //!
 //! ## Hashing (write path)
 //!
@@ -25,6 +28,17 @@
 //!   matching Git's collation), then encodes `"{mode} {name}\0{20-byte sha}"` per entry.
 //! - [`hash_commit`] — produces the standard `tree`/`parent`/`author`/`committer`
 //!   header block followed by a blank line and the commit message.
+//! - [`hash_tag`] — produces the `object`/`type`/`tag`/`tagger` header block
+//!   followed by a blank line and the tag message.
+//!
+//! ### SHA-256
+//!
+//! [`hash_blob_sha256`], [`hash_tree_sha256`] and [`hash_commit_sha256`] produce
+//! the same bytes as their SHA-1 counterparts but digest them with SHA-256. Trees
+//! and commits still name their children by SHA-1, so these are not git's
+//! SHA-256 object format: the wire protocol and every object stay SHA-1, and the
+//! stronger hash only addresses objects inside a local store configured with
+//! [`HashAlgorithm::Sha256`].
 //!
 //! ## Parsing (read path)
 //!
@@ -32,16 +46,23 @@
 //! and return the corresponding struct, or `None` if the data is malformed or the
 //! type tag does not match:
 //!
-//! - [`parse_blob`], [`parse_tree`], [`parse_commit`]
+//! - [`parse_blob`], [`parse_tree`], [`parse_commit`], [`parse_tag`]
+//!
+//! [`parse_tree`] also rejects entries whose mode is not one of the five git
+//! writes (see [`EntryKind`]).
 //!
 //! All parsers delegate header validation to [`parse_header`], which checks the
 //! type tag and verifies that the declared size matches the actual content length.
 
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 
 /// A 20-byte SHA-1 hash identifying a git object.
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Hashing and ordering use the raw bytes, so a `Sha` can key a `HashSet` or
+/// `BTreeMap` directly; byte order matches the order of the hex strings.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Sha(pub [u8; 20]);
 
 impl Sha {
@@ -69,6 +90,83 @@ impl std::fmt::Display for Sha {
     }
 }
 
+impl std::fmt::LowerHex for Sha {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// A 32-byte SHA-256 hash of a serialised object.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Sha256Hash(pub [u8; 32]);
+
+impl Sha256Hash {
+    /// Return the hex string representation.
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+impl std::fmt::Display for Sha256Hash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// The hash a store addresses objects by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    /// Git's own object names; what the wire protocol uses.
+    #[default]
+    Sha1,
+    /// SHA-256 of the same object bytes, for local stores only.
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The id of a serialised object (header included) under this algorithm.
+    pub fn object_id(&self, raw: &[u8]) -> ObjectId {
+        match self {
+            HashAlgorithm::Sha1 => ObjectId::Sha1(digest_sha1(raw)),
+            HashAlgorithm::Sha256 => ObjectId::Sha256(digest_sha256(raw)),
+        }
+    }
+}
+
+/// An object's name under either [`HashAlgorithm`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ObjectId {
+    Sha1(Sha),
+    Sha256(Sha256Hash),
+}
+
+impl ObjectId {
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            ObjectId::Sha1(_) => HashAlgorithm::Sha1,
+            ObjectId::Sha256(_) => HashAlgorithm::Sha256,
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        match self {
+            ObjectId::Sha1(sha) => sha.to_hex(),
+            ObjectId::Sha256(hash) => hash.to_hex(),
+        }
+    }
+}
+
+impl std::fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
 /// A git blob (file content).
 #[derive(Clone, Debug)]
 pub struct Blob {
@@ -83,6 +181,47 @@ pub struct TreeEntry {
     pub sha: Sha,
 }
 
+impl TreeEntry {
+    /// What the entry's mode says it is, `None` for a mode git never writes.
+    pub fn kind(&self) -> Option<EntryKind> {
+        EntryKind::from_mode(&self.mode)
+    }
+}
+
+/// The kinds of tree entry, one per mode string git writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// `100644`: a regular file.
+    File,
+    /// `100755`: an executable file.
+    Executable,
+    /// `40000`: a subtree (directory).
+    Tree,
+    /// `120000`: a symbolic link; the blob holds the link target.
+    SymLink,
+    /// `160000`: a submodule; the SHA names a commit of another repository.
+    GitLink,
+}
+
+impl EntryKind {
+    pub fn from_mode(mode: &str) -> Option<Self> {
+        match mode {
+            "100644" => Some(Self::File),
+            "100755" => Some(Self::Executable),
+            "40000" => Some(Self::Tree),
+            "120000" => Some(Self::SymLink),
+            "160000" => Some(Self::GitLink),
+            _ => None,
+        }
+    }
+
+    /// Whether entries of this kind hold file content (a regular or
+    /// executable file).
+    pub fn is_file(self) -> bool {
+        matches!(self, Self::File | Self::Executable)
+    }
+}
+
 /// A git tree (directory listing).
 #[derive(Clone, Debug)]
 pub struct Tree {
@@ -93,26 +232,61 @@ pub struct Tree {
 #[derive(Clone, Debug)]
 pub struct Commit {
     pub tree: Sha,
-    pub parent: Option<Sha>,
+    /// Empty for a root commit; a merge has two or more, the branch it was
+    /// merged into first.
+    pub parents: Vec<Sha>,
     pub author: String,
     pub message: String,
     pub timestamp: i64,
 }
 
-/// Hash raw data with a git object header: "{type} {size}\0{content}"
-fn hash_with_header(obj_type: &str, content: &[u8]) -> (Sha, Vec<u8>) {
+impl Commit {
+    /// The parent followed by `git log --first-parent`.
+    pub fn first_parent(&self) -> Option<&Sha> {
+        self.parents.first()
+    }
+}
+
+/// An annotated git tag.
+#[derive(Clone, Debug)]
+pub struct Tag {
+    /// The tagged object, usually a commit.
+    pub object: Sha,
+    /// Type of the tagged object: `commit`, `tree`, `blob` or `tag`.
+    pub target_type: String,
+    pub tag_name: String,
+    /// "Name <email> timestamp +zone", as written in the tag.
+    pub tagger: String,
+    pub message: String,
+}
+
+/// Prefix `content` with a git object header: "{type} {size}\0{content}"
+fn with_header(obj_type: &str, content: &[u8]) -> Vec<u8> {
     let header = format!("{} {}\0", obj_type, content.len());
     let mut full = Vec::with_capacity(header.len() + content.len());
     full.extend_from_slice(header.as_bytes());
     full.extend_from_slice(content);
+    full
+}
+
+fn digest_sha1(data: &[u8]) -> Sha {
+    Sha(Sha1::digest(data).into())
+}
+
+fn digest_sha256(data: &[u8]) -> Sha256Hash {
+    Sha256Hash(Sha256::digest(data).into())
+}
 
-    let mut hasher = Sha1::new();
-    hasher.update(&full);
-    let result = hasher.finalize();
-    let mut sha_bytes = [0u8; 20];
-    sha_bytes.copy_from_slice(&result);
+/// Hash raw data with a git object header: "{type} {size}\0{content}"
+fn hash_with_header(obj_type: &str, content: &[u8]) -> (Sha, Vec<u8>) {
+    let full = with_header(obj_type, content);
+    (digest_sha1(&full), full)
+}
 
-    (Sha(sha_bytes), full)
+/// Like [`hash_with_header`], digesting with SHA-256.
+fn hash_with_header_sha256(obj_type: &str, content: &[u8]) -> (Sha256Hash, Vec<u8>) {
+    let full = with_header(obj_type, content);
+    (digest_sha256(&full), full)
 }
 
 /// Serialize a blob and compute its SHA-1.
@@ -120,10 +294,24 @@ pub fn hash_blob(blob: &Blob) -> (Sha, Vec<u8>) {
     hash_with_header("blob", &blob.content)
 }
 
+/// Serialize a blob and compute its SHA-256.
+pub fn hash_blob_sha256(blob: &Blob) -> (Sha256Hash, Vec<u8>) {
+    hash_with_header_sha256("blob", &blob.content)
+}
+
 /// Serialize a tree in git format and compute its SHA-1.
 ///
 /// Git tree format: for each entry: "{mode} {name}\0{20-byte sha}"
 pub fn hash_tree(tree: &Tree) -> (Sha, Vec<u8>) {
+    hash_with_header("tree", &tree_content(tree))
+}
+
+/// Serialize a tree in git format and compute its SHA-256.
+pub fn hash_tree_sha256(tree: &Tree) -> (Sha256Hash, Vec<u8>) {
+    hash_with_header_sha256("tree", &tree_content(tree))
+}
+
+fn tree_content(tree: &Tree) -> Vec<u8> {
     let mut content = Vec::new();
     // Git trees require entries sorted by name
     let mut sorted_entries: Vec<&TreeEntry> = tree.entries.iter().collect();
@@ -149,14 +337,23 @@ pub fn hash_tree(tree: &Tree) -> (Sha, Vec<u8>) {
         content.push(0);
         content.extend_from_slice(&entry.sha.0);
     }
-    hash_with_header("tree", &content)
+    content
 }
 
 /// Serialize a commit in git format and compute its SHA-1.
 pub fn hash_commit(commit: &Commit) -> (Sha, Vec<u8>) {
+    hash_with_header("commit", commit_content(commit).as_bytes())
+}
+
+/// Serialize a commit in git format and compute its SHA-256.
+pub fn hash_commit_sha256(commit: &Commit) -> (Sha256Hash, Vec<u8>) {
+    hash_with_header_sha256("commit", commit_content(commit).as_bytes())
+}
+
+fn commit_content(commit: &Commit) -> String {
     let mut content = String::new();
     content.push_str(&format!("tree {}\n", commit.tree.to_hex()));
-    if let Some(ref parent) = commit.parent {
+    for parent in &commit.parents {
         content.push_str(&format!("parent {}\n", parent.to_hex()));
     }
     content.push_str(&format!(
@@ -170,8 +367,21 @@ pub fn hash_commit(commit: &Commit) -> (Sha, Vec<u8>) {
     content.push('\n');
     content.push_str(&commit.message);
     content.push('\n');
+    content
+}
 
-    hash_with_header("commit", content.as_bytes())
+/// Serialize an annotated tag of a commit in git format and compute its SHA-1.
+pub fn hash_tag(tag: &Tag) -> (Sha, Vec<u8>) {
+    let mut content = String::new();
+    content.push_str(&format!("object {}\n", tag.object.to_hex()));
+    content.push_str(&format!("type {}\n", tag.target_type));
+    content.push_str(&format!("tag {}\n", tag.tag_name));
+    content.push_str(&format!("tagger {}\n", tag.tagger));
+    content.push('\n');
+    content.push_str(&tag.message);
+    content.push('\n');
+
+    hash_with_header("tag", content.as_bytes())
 }
 
 /// Parse a blob from raw git object data (after header).
@@ -192,6 +402,7 @@ pub fn parse_tree(raw: &[u8]) -> Option<Tree> {
         // Find space after mode
         let space_pos = content[i..].iter().position(|&b| b == b' ')? + i;
         let mode = std::str::from_utf8(&content[i..space_pos]).ok()?;
+        EntryKind::from_mode(mode)?;
 
         // Find null after name
         let null_pos = content[space_pos + 1..]
@@ -226,7 +437,7 @@ pub fn parse_commit(raw: &[u8]) -> Option<Commit> {
     let text = std::str::from_utf8(content).ok()?;
 
     let mut tree = None;
-    let mut parent = None;
+    let mut parents = Vec::new();
     let mut author = String::new();
     let mut timestamp = 0i64;
     let mut in_headers = true;
@@ -241,7 +452,7 @@ pub fn parse_commit(raw: &[u8]) -> Option<Commit> {
             if let Some(rest) = line.strip_prefix("tree ") {
                 tree = Sha::from_hex(rest);
             } else if let Some(rest) = line.strip_prefix("parent ") {
-                parent = Sha::from_hex(rest);
+                parents.push(Sha::from_hex(rest)?);
             } else if let Some(rest) = line.strip_prefix("author ") {
                 // Format: "Name <email> timestamp +0000"
                 if let Some(ts_start) = rest.rfind('>') {
@@ -264,13 +475,61 @@ pub fn parse_commit(raw: &[u8]) -> Option<Commit> {
 
     Some(Commit {
         tree: tree?,
-        parent,
+        parents,
         author,
         message: message_lines.join("\n"),
         timestamp,
     })
 }
 
+/// Parse an annotated tag from raw git object data.
+///
+/// Returns `None` without an `object` or `tag` header. A PGP signature, if
+/// present, is left in the message.
+pub fn parse_tag(raw: &[u8]) -> Option<Tag> {
+    let (_, content) = parse_header(raw, "tag")?;
+    let text = std::str::from_utf8(content).ok()?;
+
+    let mut object = None;
+    let mut target_type = None;
+    let mut tag_name = None;
+    let mut tagger = String::new();
+    let mut in_headers = true;
+    let mut message_lines = Vec::new();
+
+    for line in text.lines() {
+        if in_headers {
+            if line.is_empty() {
+                in_headers = false;
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("object ") {
+                object = Sha::from_hex(rest);
+            } else if let Some(rest) = line.strip_prefix("type ") {
+                target_type = Some(rest.to_string());
+            } else if let Some(rest) = line.strip_prefix("tag ") {
+                tag_name = Some(rest.to_string());
+            } else if let Some(rest) = line.strip_prefix("tagger ") {
+                tagger = rest.to_string();
+            }
+        } else {
+            message_lines.push(line);
+        }
+    }
+
+    while message_lines.last() == Some(&"") {
+        message_lines.pop();
+    }
+
+    Some(Tag {
+        object: object?,
+        target_type: target_type?,
+        tag_name: tag_name?,
+        tagger,
+        message: message_lines.join("\n"),
+    })
+}
+
 /// Parse the header of a raw git object, returning (size, content_bytes).
 fn parse_header<'a>(raw: &'a [u8], expected_type: &str) -> Option<(usize, &'a [u8])> {
     let null_pos = raw.iter().position(|&b| b == 0)?;
@@ -296,6 +555,23 @@ mod tests {
         let hex = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
         let sha = Sha::from_hex(hex).unwrap();
         assert_eq!(sha.to_hex(), hex);
+        assert_eq!(format!("{sha}"), hex);
+        assert_eq!(format!("{sha:x}"), hex);
+    }
+
+    #[test]
+    fn test_sha_ordering() {
+        let low = Sha::from_hex("0fffffffffffffffffffffffffffffffffffffff").unwrap();
+        let high = Sha::from_hex("f000000000000000000000000000000000000000").unwrap();
+        assert!(low < high);
+
+        let mut shas = vec![high.clone(), low.clone(), high.clone()];
+        shas.sort();
+        shas.dedup();
+        assert_eq!(shas, vec![low.clone(), high.clone()]);
+
+        let set: std::collections::HashSet<Sha> = [low.clone(), low].into_iter().collect();
+        assert_eq!(set.len(), 1);
     }
 
     #[test]
@@ -308,6 +584,55 @@ mod tests {
         assert_eq!(sha.to_hex(), "ce013625030ba8dba906f756967f9e9ca394464a");
     }
 
+    #[test]
+    fn test_hash_blob_sha256() {
+        // `git hash-object --object-format=sha256` of "hello\n"
+        let blob = Blob {
+            content: b"hello\n".to_vec(),
+        };
+        let (hash, raw) = hash_blob_sha256(&blob);
+        assert_eq!(
+            hash.to_hex(),
+            "2cf8d83d9ee29543b34a87727421fdecb7e3f3a183d337639025de576db9ebb4"
+        );
+        assert_eq!(raw, hash_blob(&blob).1);
+        assert_eq!(HashAlgorithm::Sha256.object_id(&raw), ObjectId::Sha256(hash));
+        assert_eq!(
+            HashAlgorithm::Sha1.object_id(&raw),
+            ObjectId::Sha1(hash_blob(&blob).0)
+        );
+    }
+
+    #[test]
+    fn test_tree_and_commit_sha256() {
+        let (blob_sha, _) = hash_blob(&Blob {
+            content: b"hello".to_vec(),
+        });
+        let tree = Tree {
+            entries: vec![TreeEntry {
+                mode: "100644".to_string(),
+                name: "hello.txt".to_string(),
+                sha: blob_sha,
+            }],
+        };
+        let (tree_hash, tree_raw) = hash_tree_sha256(&tree);
+        let (tree_sha, sha1_raw) = hash_tree(&tree);
+        assert_eq!(tree_raw, sha1_raw);
+        assert_eq!(tree_hash.to_hex().len(), 64);
+
+        let commit = Commit {
+            tree: tree_sha,
+            parents: Vec::new(),
+            author: "Test User <test@example.com>".to_string(),
+            message: "initial commit".to_string(),
+            timestamp: 1700000000,
+        };
+        let (commit_hash, commit_raw) = hash_commit_sha256(&commit);
+        assert_eq!(commit_raw, hash_commit(&commit).1);
+        assert_ne!(commit_hash, tree_hash);
+        assert_eq!(parse_commit(&commit_raw).unwrap().message, "initial commit");
+    }
+
     #[test]
     fn test_blob_roundtrip() {
         let blob = Blob {
@@ -338,12 +663,41 @@ mod tests {
         assert_eq!(parsed.entries[0].name, "hello.txt");
     }
 
+    #[test]
+    fn test_parse_tree_modes() {
+        let (sha, _) = hash_blob(&Blob { content: b"x".to_vec() });
+        let tree_with_mode = |mode: &str| {
+            let (_, raw) = hash_tree(&Tree {
+                entries: vec![TreeEntry {
+                    mode: mode.to_string(),
+                    name: "entry".to_string(),
+                    sha: sha.clone(),
+                }],
+            });
+            raw
+        };
+
+        for (mode, kind) in [
+            ("100644", EntryKind::File),
+            ("100755", EntryKind::Executable),
+            ("40000", EntryKind::Tree),
+            ("120000", EntryKind::SymLink),
+            ("160000", EntryKind::GitLink),
+        ] {
+            let tree = parse_tree(&tree_with_mode(mode)).unwrap();
+            assert_eq!(tree.entries[0].kind(), Some(kind), "mode {mode}");
+        }
+        for mode in ["100664", "040000", "644", "", "10064x"] {
+            assert!(parse_tree(&tree_with_mode(mode)).is_none(), "mode {mode:?} accepted");
+        }
+    }
+
     #[test]
     fn test_commit_roundtrip() {
         let sha = Sha::from_hex("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap();
         let commit = Commit {
             tree: sha.clone(),
-            parent: None,
+            parents: Vec::new(),
             author: "Test User <test@example.com>".to_string(),
             message: "initial commit".to_string(),
             timestamp: 1700000000,
@@ -351,7 +705,67 @@ mod tests {
         let (_, raw) = hash_commit(&commit);
         let parsed = parse_commit(&raw).unwrap();
         assert_eq!(parsed.tree, commit.tree);
-        assert_eq!(parsed.parent, None);
+        assert!(parsed.parents.is_empty());
         assert_eq!(parsed.message, "initial commit");
     }
+
+    #[test]
+    fn test_merge_commit_roundtrip() {
+        let tree = Sha::from_hex("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap();
+        let ours = Sha([1; 20]);
+        let theirs = Sha([2; 20]);
+        let commit = Commit {
+            tree,
+            parents: vec![ours.clone(), theirs.clone()],
+            author: "Test User <test@example.com>".to_string(),
+            message: "Merge branch 'phone'".to_string(),
+            timestamp: 1700000000,
+        };
+        let (_, raw) = hash_commit(&commit);
+        let text = String::from_utf8_lossy(&raw);
+        assert!(text.contains(&format!("parent {ours}\nparent {theirs}\n")));
+
+        let parsed = parse_commit(&raw).unwrap();
+        assert_eq!(parsed.parents, vec![ours.clone(), theirs]);
+        assert_eq!(parsed.first_parent(), Some(&ours));
+    }
+
+    #[test]
+    fn test_tag_roundtrip() {
+        let tag = Tag {
+            object: Sha::from_hex("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap(),
+            target_type: "tree".to_string(),
+            tag_name: "v1.0".to_string(),
+            tagger: "Test User <test@example.com> 1700000000 +0000".to_string(),
+            message: "First release\n\nWith notes".to_string(),
+        };
+        let (_, raw) = hash_tag(&tag);
+        let parsed = parse_tag(&raw).unwrap();
+        assert_eq!(parsed.object, tag.object);
+        assert_eq!(parsed.target_type, "tree");
+        assert_eq!(parsed.tag_name, "v1.0");
+        assert_eq!(parsed.tagger, tag.tagger);
+        assert_eq!(parsed.message, tag.message);
+    }
+
+    #[test]
+    fn test_parse_tag_git_output() {
+        // `git cat-file tag v1.0` for `git tag -a v1.0 -m "Release"`
+        let content = "object da39a3ee5e6b4b0d3255bfef95601890afd80709\n\
+                       type commit\n\
+                       tag v1.0\n\
+                       tagger Ada <ada@example.com> 1700000000 +0100\n\
+                       \n\
+                       Release\n";
+        let raw = format!("tag {}\0{content}", content.len());
+        let tag = parse_tag(raw.as_bytes()).unwrap();
+        assert_eq!(tag.target_type, "commit");
+        assert_eq!(tag.tag_name, "v1.0");
+        assert_eq!(tag.tagger, "Ada <ada@example.com> 1700000000 +0100");
+        assert_eq!(tag.message, "Release");
+        assert_eq!(hash_tag(&tag).1, raw.as_bytes());
+
+        assert!(parse_tag(b"tag 4\0tag ").is_none());
+        assert!(parse_commit(raw.as_bytes()).is_none());
+    }
 }
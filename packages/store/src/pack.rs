@@ -0,0 +1,274 @@
+//! # Pack files
+//!
+//! A minimal writer and reader for git's pack format (version 2), used by
+//! [`MemoryStore::compact`] to fold loose objects into a single blob, the way
+//! `git gc` moves `.git/objects/xx/...` files into a pack.
+//!
+//! Every entry is stored whole, never as a delta, so reading one back is a
+//! header parse and a zlib inflate. The packs exchanged with git remotes —
+//! with deltas and thin-pack bases — are built and parsed by
+//! `api::git_transport`.
+//!
+//! The server also hands a whole repository to a new client this way: a pack
+//! from [`build_pack`], gzip-compressed with [`gzip`], that the client reads
+//! back with [`gunzip`] and [`read_pack`] (see
+//! [`Repository::import_from_pack`](crate::Repository::import_from_pack)).
+//!
+//! | Function | Description |
+//! |----------|-------------|
+//! | [`build_pack`] | Pack the given objects of a [`MemoryStore`]. |
+//! | [`PackBuilder`] | Write a pack one loose object at a time. |
+//! | [`index_pack`] | Scan a pack, mapping each object's SHA-1 to the offset of its entry (git's `.idx`). |
+//! | [`unpack_pack_entry`] | Read the entry at an offset back as a loose object (`"{type} {size}\0{content}"`). |
+//! | [`read_pack`] | Every object of a pack with its SHA-1, in pack order. |
+//! | [`first_object`] | SHA-1 of the first object, without reading the rest. |
+//! | [`gzip`] / [`gunzip`] | Compress a pack for transfer and back. |
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+
+use crate::memory::MemoryStore;
+use crate::objects::{HashAlgorithm, ObjectId, Sha};
+
+/// Length of the pack header: `PACK`, version, object count.
+const HEADER_LEN: usize = 12;
+
+/// Pack type numbers of the object types git stores whole.
+const OBJECT_TYPES: [(u8, &str); 4] = [(1, "commit"), (2, "tree"), (3, "blob"), (4, "tag")];
+
+/// Build a pack holding the objects of `store` named by `sha_hexes`.
+pub fn build_pack(store: &MemoryStore, sha_hexes: &[String]) -> Result<Vec<u8>, String> {
+    let mut builder = PackBuilder::new();
+    for sha_hex in sha_hexes {
+        let sha = Sha::from_hex(sha_hex).ok_or_else(|| format!("Invalid SHA hex: {sha_hex}"))?;
+        let raw = store
+            .get_sync(&sha)
+            .ok_or_else(|| format!("Object {sha_hex} not in store"))?;
+        builder.add(&raw).map_err(|e| format!("Object {sha_hex}: {e}"))?;
+    }
+    Ok(builder.finish())
+}
+
+/// A pack being written one object at a time, for callers that visit
+/// objects rather than name them (see [`MemoryStore::iter_objects`]).
+pub struct PackBuilder {
+    pack: Vec<u8>,
+    count: u32,
+}
+
+impl Default for PackBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PackBuilder {
+    pub fn new() -> Self {
+        let mut pack = Vec::new();
+        pack.extend_from_slice(b"PACK");
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        // Object count, filled in by `finish`
+        pack.extend_from_slice(&0u32.to_be_bytes());
+        Self { pack, count: 0 }
+    }
+
+    /// Append the loose object `raw` (`"{type} {size}\0{content}"`).
+    pub fn add(&mut self, raw: &[u8]) -> Result<(), String> {
+        let (type_name, content) = split_object(raw).ok_or_else(|| "malformed object".to_string())?;
+        let type_num = OBJECT_TYPES
+            .iter()
+            .find(|(_, name)| *name == type_name)
+            .map(|(num, _)| *num)
+            .ok_or_else(|| format!("Cannot pack type {type_name}"))?;
+
+        write_entry_header(&mut self.pack, type_num, content.len());
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).map_err(|e| e.to_string())?;
+        self.pack.extend_from_slice(&encoder.finish().map_err(|e| e.to_string())?);
+        self.count += 1;
+        Ok(())
+    }
+
+    /// The finished pack, with its object count and SHA-1 trailer.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.pack[8..HEADER_LEN].copy_from_slice(&self.count.to_be_bytes());
+        let checksum = sha1(&self.pack);
+        self.pack.extend_from_slice(&checksum.0);
+        self.pack
+    }
+}
+
+/// SHA-1 of every object in `pack` → offset of its entry.
+pub fn index_pack(pack: &[u8]) -> Result<HashMap<Sha, usize>, String> {
+    let mut index = HashMap::new();
+    for_each_entry(pack, |sha, offset, _| {
+        index.insert(sha, offset);
+    })?;
+    Ok(index)
+}
+
+/// Every object in `pack` as `(SHA-1, loose object bytes)`, in pack order.
+pub fn read_pack(pack: &[u8]) -> Result<Vec<(Sha, Vec<u8>)>, String> {
+    let mut objects = Vec::new();
+    for_each_entry(pack, |sha, _, raw| objects.push((sha, raw)))?;
+    Ok(objects)
+}
+
+/// SHA-1 of the first object in `pack`, if it has one.
+pub fn first_object(pack: &[u8]) -> Option<Sha> {
+    object_count(pack).ok().filter(|&count| count > 0)?;
+    let (raw, _) = read_entry(pack, HEADER_LEN).ok()?;
+    Some(sha1(&raw))
+}
+
+/// Gzip-compress `data`, e.g. a pack before sending it to a client.
+pub fn gzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).map_err(|e| e.to_string())?;
+    encoder.finish().map_err(|e| e.to_string())
+}
+
+/// Reverse [`gzip`].
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    GzDecoder::new(data)
+        .read_to_end(&mut out)
+        .map_err(|e| format!("Corrupt gzip data: {e}"))?;
+    Ok(out)
+}
+
+/// Number of objects the header of `pack` announces.
+fn object_count(pack: &[u8]) -> Result<usize, String> {
+    if pack.len() < HEADER_LEN || &pack[..4] != b"PACK" {
+        return Err("Not a pack".to_string());
+    }
+    Ok(u32::from_be_bytes(pack[8..12].try_into().unwrap()) as usize)
+}
+
+/// Call `f` with the SHA-1, offset and loose bytes of each object in `pack`.
+fn for_each_entry(pack: &[u8], mut f: impl FnMut(Sha, usize, Vec<u8>)) -> Result<(), String> {
+    let mut offset = HEADER_LEN;
+    for _ in 0..object_count(pack)? {
+        let (raw, next) = read_entry(pack, offset)?;
+        f(sha1(&raw), offset, raw);
+        offset = next;
+    }
+    Ok(())
+}
+
+fn sha1(raw: &[u8]) -> Sha {
+    let ObjectId::Sha1(sha) = HashAlgorithm::Sha1.object_id(raw) else {
+        unreachable!("SHA-1 digests are ObjectId::Sha1")
+    };
+    sha
+}
+
+/// The object whose entry starts at `offset` in `pack`, as loose object bytes.
+pub fn unpack_pack_entry(pack: &[u8], offset: usize) -> Option<Vec<u8>> {
+    read_entry(pack, offset).ok().map(|(raw, _)| raw)
+}
+
+/// Object at `offset` and the offset of the entry after it.
+fn read_entry(pack: &[u8], offset: usize) -> Result<(Vec<u8>, usize), String> {
+    let (type_num, size, data_start) = read_entry_header(pack, offset)?;
+    let type_name = OBJECT_TYPES
+        .iter()
+        .find(|(num, _)| *num == type_num)
+        .map(|(_, name)| *name)
+        .ok_or_else(|| format!("Unsupported pack entry type {type_num} at {offset}"))?;
+
+    let mut decoder = ZlibDecoder::new(&pack[data_start..]);
+    let mut content = Vec::with_capacity(size);
+    decoder
+        .by_ref()
+        .take(size as u64)
+        .read_to_end(&mut content)
+        .map_err(|e| format!("Corrupt pack entry at {offset}: {e}"))?;
+    if content.len() != size {
+        return Err(format!("Truncated pack entry at {offset}"));
+    }
+    // Finish the stream so `total_in` covers the zlib trailer
+    decoder.read_to_end(&mut Vec::new()).map_err(|e| e.to_string())?;
+    let next = data_start + decoder.total_in() as usize;
+
+    let mut raw = format!("{type_name} {size}\0").into_bytes();
+    raw.extend_from_slice(&content);
+    Ok((raw, next))
+}
+
+/// Type and size varint: `1ttt ssss`, then 7 more size bits per byte.
+fn write_entry_header(pack: &mut Vec<u8>, type_num: u8, size: usize) {
+    let mut byte = (type_num << 4) | (size & 0x0f) as u8;
+    let mut rest = size >> 4;
+    while rest > 0 {
+        pack.push(byte | 0x80);
+        byte = (rest & 0x7f) as u8;
+        rest >>= 7;
+    }
+    pack.push(byte);
+}
+
+/// `(type, size, offset of the zlib data)` of the entry at `offset`.
+fn read_entry_header(pack: &[u8], offset: usize) -> Result<(u8, usize, usize), String> {
+    let truncated = || format!("Truncated pack entry header at {offset}");
+    let mut pos = offset;
+    let mut byte = *pack.get(pos).ok_or_else(truncated)?;
+    let type_num = (byte >> 4) & 0x07;
+    let mut size = (byte & 0x0f) as usize;
+    let mut shift = 4;
+    while byte & 0x80 != 0 {
+        pos += 1;
+        byte = *pack.get(pos).ok_or_else(truncated)?;
+        size |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+    }
+    Ok((type_num, size, pos + 1))
+}
+
+/// `("blob", content)` for the loose object `"blob 12\0content"`.
+fn split_object(raw: &[u8]) -> Option<(&str, &[u8])> {
+    let nul = raw.iter().position(|&b| b == 0)?;
+    let header = std::str::from_utf8(&raw[..nul]).ok()?;
+    let (type_name, _) = header.split_once(' ')?;
+    Some((type_name, &raw[nul + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{hash_blob, Blob};
+
+    #[test]
+    fn test_pack_round_trip() {
+        let store = MemoryStore::new();
+        let mut shas = Vec::new();
+        // A blob long enough for a multi-byte size varint
+        for content in [b"hi".to_vec(), vec![b'x'; 5000]] {
+            let (sha, raw) = hash_blob(&Blob { content });
+            store.put_sync(&sha, raw);
+            shas.push(sha);
+        }
+        let hexes: Vec<String> = shas.iter().map(Sha::to_hex).collect();
+
+        let pack = build_pack(&store, &hexes).unwrap();
+        let index = index_pack(&pack).unwrap();
+        assert_eq!(index.len(), 2);
+        for sha in &shas {
+            assert_eq!(unpack_pack_entry(&pack, index[sha]), store.get_sync(sha));
+        }
+
+        let objects = read_pack(&pack).unwrap();
+        assert_eq!(objects.iter().map(|(sha, _)| sha.clone()).collect::<Vec<_>>(), shas);
+        assert_eq!(first_object(&pack), Some(shas[0].clone()));
+        assert_eq!(gunzip(&gzip(&pack).unwrap()).unwrap(), pack);
+
+        assert!(index_pack(b"nope").is_err());
+        assert!(first_object(b"nope").is_none());
+        assert!(gunzip(b"nope").is_err());
+        assert!(build_pack(&store, &["00".repeat(20)]).is_err());
+    }
+}
@@ -10,7 +10,11 @@
 //!
 //! An async interface with four methods — `get`/`put` for SHA-keyed object blobs, and
 //! `get_ref`/`set_ref` for named references (e.g. `"HEAD"`). Implementations live in
-//! sibling modules ([`crate::memory`], [`crate::idb`]).
+//! sibling modules ([`crate::memory`], [`crate::idb`]). Three optional methods,
+//! `list_refs`, `object_count` and `retain_objects`, let backends that can
+//! enumerate their refs and objects take part in
+//! [garbage collection](Repository::gc); two more, `iter_objects_sync` and
+//! `try_put_sync`, let bulk work visit or copy every object in one pass.
 //!
 //! ## Read path
 //!
@@ -18,11 +22,26 @@
 //! |--------|-------------|
 //! | [`get_head`](Repository::get_head) | Returns the SHA the `HEAD` ref points to. |
 //! | [`get_root_tree`](Repository::get_root_tree) | Follows `HEAD` → commit → root tree. |
-//! | [`list_notes`](Repository::list_notes) | Recursively walks the root tree, collecting every `.md`/`.txt` blob as a [`TypedNoteInfo`]. |
+//! | [`list_notes`](Repository::list_notes) | Recursively walks the root tree, collecting every note blob (`.md`, `.txt`, `.org`, `.rst`, `.adoc`, `.tex`) as a [`TypedNoteInfo`] (with metadata read from its frontmatter). |
 //! | [`list_namespaces`](Repository::list_namespaces) | Same walk, but collects directories as [`NamespaceInfo`]. |
 //! | [`list_notes_in`](Repository::list_notes_in) / [`list_namespaces_in`](Repository::list_namespaces_in) | Scoped variants that start from a subtree (e.g. a configured notes root). |
+//! | [`list_namespace_children`](Repository::list_namespace_children) | One level of a namespace (child namespaces + notes), for lazy loading. |
 //! | [`get_note`](Repository::get_note) | Resolves a full path (e.g. `"work/ideas/project.md"`) to its blob and returns a [`TypedNoteInfo`]. |
+//! | [`get_note_at`](Repository::get_note_at) | Same, but reads the tree of an earlier commit reachable from `HEAD` (through any merge parent). |
+//! | [`get_note_in_commit`](Repository::get_note_in_commit) | Same for any commit present in the store. |
+//! | [`read_raw`](Repository::read_raw) | The bytes of any file by path, e.g. an attachment under `assets/`. |
+//! | [`list_files`](Repository::list_files) | Every file of the tree with its bytes — notes, attachments, `typednotes.toml` and `.gitkeep` files alike. |
 //! | [`get_config`](Repository::get_config) | Reads `typednotes.toml` from the repo root, falling back to [`TypedNotesConfig::default`]. |
+//! | [`load_config`](Repository::load_config) | Same, but reports an invalid file as a [`ConfigError`](crate::config::ConfigError) instead of hiding it. |
+//! | [`get_commit`](Repository::get_commit) | Loads and parses a commit by SHA. |
+//! | [`list_notes_at`](Repository::list_notes_at) | Lists the notes in the tree of any commit present in the store. |
+//! | [`list_notes_modified_since`](Repository::list_notes_modified_since) | Notes changed by commits newer than a timestamp, for incremental sync. |
+//! | [`list_notes_changed_since`](Repository::list_notes_changed_since) | Notes that differ between an earlier commit and `HEAD`, for incremental sync from a known remote head. |
+//! | [`diff_trees`](Repository::diff_trees) / [`diff_commits`](Repository::diff_commits) | Files added, modified or deleted between two trees (or the trees of two commits), skipping identical subtrees. |
+//! | [`note_history`](Repository::note_history) | Walks history from `HEAD`, newest first and into every merge parent, returning the commits that changed a given path. |
+//! | [`find_backlinks`](Repository::find_backlinks) | Notes whose content links to a given note (`[[wiki]]` or markdown link), by string scan. |
+//! | [`list_templates`](Repository::list_templates) | Notes in the reserved `.templates/` namespace (see [`crate::templates`]). |
+//! | [`get_meta`](Repository::get_meta) | Client state saved with [`set_meta`](Repository::set_meta), outside the note history. |
 //!
 //! ## Write path
 //!
@@ -30,14 +49,41 @@
 //! the tree hierarchy from the leaf up to the root (via [`update_tree_at_path`](Repository::update_tree_at_path)),
 //! create a new commit pointing to the new root tree with the current `HEAD` as parent,
 //! and advance `HEAD`. This mirrors how `git commit` works, but entirely in memory.
+//! The note writes run inside an [`ObjectStore::begin_txn`] transaction that is
+//! only committed once `HEAD` has moved, so an interrupted write is rolled back on
+//! stores that support it.
 //!
 //! | Method | Description |
 //! |--------|-------------|
 //! | [`write_note`](Repository::write_note) | Creates/updates a note, auto-appending the correct extension (`.md`/`.txt`). |
 //! | [`write_note_raw`](Repository::write_note_raw) | Writes arbitrary bytes at an exact path (used internally for `.gitkeep` and config). |
 //! | [`delete_note`](Repository::delete_note) | Removes a blob from the tree and commits the result. |
+//! | [`copy_note`](Repository::copy_note) | Writes the content of one path at another (refuses to overwrite). |
+//! | [`rename_namespace`](Repository::rename_namespace) | Moves every file below a namespace to a new path in a single commit. |
+//! | [`move_namespace`](Repository::move_namespace) | Like `rename_namespace`, but merges into an existing destination namespace. |
+//! | [`delete_namespace`](Repository::delete_namespace) | Removes a directory and everything below it in one commit. |
+//! | [`import_from_pack`](Repository::import_from_pack) | Stores every object of a git pack and points `HEAD` at a given commit. |
 //! | [`create_namespace`](Repository::create_namespace) | Creates a directory by writing a `.gitkeep` file inside it. |
 //! | [`set_config`](Repository::set_config) | Serialises a [`TypedNotesConfig`] to TOML and commits it at the repo root. |
+//! | [`pin_note`](Repository::pin_note) / [`unpin_note`](Repository::unpin_note) | Read-modify-write of `pinned_notes` in the config; `None` when nothing changed. |
+//! | [`set_namespace_style`](Repository::set_namespace_style) | Same for one entry of `namespace_styles`; a default style removes the entry. |
+//! | [`write_note_batch_raw`](Repository::write_note_batch_raw) / [`commit_batch`](Repository::commit_batch) | Stage several writes on a pending root tree, then commit them all at once. |
+//! | [`set_meta`](Repository::set_meta) | Stores bytes as a blob under a ref in [`META_REF_PREFIX`], with no commit, so they stay local and never reach the remote. |
+//!
+//! ## Merging
+//!
+//! [`merge_from`](Repository::merge_from) brings another store's `HEAD` (e.g. a
+//! freshly fetched remote) into this repository. When one side is an ancestor
+//! of the other it fast-forwards; otherwise it finds a common ancestor and
+//! compares every file three ways, taking changes made on one side only and
+//! reporting files both sides changed as [`MergeConflict`]s.
+//!
+//! ## Garbage collection
+//!
+//! Every write leaves the previous trees (and replaced blobs) behind. [`gc`](Repository::gc)
+//! marks everything reachable from `HEAD` and every other ref — annotated tags, each
+//! commit along the parent links, and the trees and blobs it references — and asks
+//! the store to drop the rest.
 //!
 //! ## Tree manipulation
 //!
@@ -47,15 +93,34 @@
 //! the way back up, and stores them in the object store. The result is a new root
 //! [`Tree`] ready to be committed.
 //!
+//! ## Unicode paths
+//!
+//! Paths are normalised to NFC before they are split into tree entry names, both
+//! when writing and when resolving, so a name typed on macOS (NFD) and the same
+//! name typed on Linux (NFC) are one entry. Entries another git client wrote in
+//! NFD still resolve, and are renamed to NFC the next time they are written.
+//!
 //! ## Timestamps
 //!
 //! [`current_timestamp`] is platform-aware: it uses `js_sys::Date::now()` on WASM and
 //! `std::time::SystemTime` on native, ensuring commits get sensible timestamps in both
 //! environments.
 
-use crate::config::TypedNotesConfig;
-use crate::models::{ext_from_note_type, note_type_from_ext, NamespaceInfo, TypedNoteInfo};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::cache::CachedStore;
+use crate::config::{ConfigError, NamespaceStyle, TypedNotesConfig};
+use crate::frontmatter::parse_frontmatter;
+use crate::memory::MemoryStore;
+use crate::models::{
+    ext_from_note_type, note_file_path, note_type_from_ext, split_note_filename, NamespaceInfo, TypedNoteInfo,
+};
 use crate::objects::*;
+use crate::templates::TEMPLATES_NAMESPACE;
 
 /// Async trait for storing and retrieving git objects.
 pub trait ObjectStore {
@@ -77,16 +142,203 @@ pub trait ObjectStore {
         name: &str,
         sha: &Sha,
     ) -> impl std::future::Future<Output = ()>;
+
+    /// Every ref with the SHA it points to, or `None` if the backend cannot
+    /// list its refs.
+    fn list_refs(&self) -> impl std::future::Future<Output = Option<Vec<(String, Sha)>>> {
+        async { None }
+    }
+
+    /// Number of stored objects, or `None` if the backend cannot count them.
+    fn object_count(&self) -> impl std::future::Future<Output = Option<usize>> {
+        async { None }
+    }
+
+    /// Call `f` with every stored object, synchronously, returning `false`
+    /// without calling it if the backend cannot enumerate its objects that
+    /// way (only [`MemoryStore`] can).
+    fn iter_objects_sync(&self, f: &mut dyn FnMut(&Sha, &[u8])) -> bool {
+        let _ = f;
+        false
+    }
+
+    /// Store an object synchronously, returning `false` without storing it
+    /// if the backend only writes asynchronously (only [`MemoryStore`] can).
+    fn try_put_sync(&self, sha: &Sha, data: Vec<u8>) -> bool {
+        let _ = (sha, data);
+        false
+    }
+
+    /// Delete every object whose SHA is not in `keep`, returning how many were
+    /// deleted. Backends that cannot enumerate their objects keep everything.
+    fn retain_objects(
+        &self,
+        keep: &HashSet<Sha>,
+    ) -> impl std::future::Future<Output = usize> {
+        let _ = keep;
+        async { 0 }
+    }
+
+    /// Key every stored object by `algorithm` from now on, re-keying the
+    /// objects already stored. Returns `false` if the backend can only key
+    /// objects by their SHA-1 name. Either way objects are still looked up
+    /// by SHA-1.
+    fn set_hash_algorithm(&self, algorithm: HashAlgorithm) -> impl std::future::Future<Output = bool> {
+        async move { algorithm == HashAlgorithm::Sha1 }
+    }
+
+    /// Start a write transaction. Dropping the returned guard without calling
+    /// [`TxnGuard::commit`] rolls the store back to its state at this point.
+    ///
+    /// The default keeps nothing to roll back to: objects are content-addressed,
+    /// so an interrupted write only leaves orphans behind (see
+    /// [`Repository::gc`]), and `HEAD` is only moved as the final step.
+    fn begin_txn(&self) -> TxnGuard {
+        TxnGuard::noop()
+    }
+}
+
+/// Pending write transaction returned by [`ObjectStore::begin_txn`].
+///
+/// Rolls back on drop unless committed, so a write that panics or whose
+/// future is cancelled half-way leaves the store as it was.
+#[must_use = "dropping a TxnGuard rolls the transaction back"]
+pub struct TxnGuard {
+    rollback: Option<Box<dyn FnOnce() + Send>>,
+    on_commit: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl TxnGuard {
+    /// A transaction that restores the store by calling `rollback`.
+    pub fn new(rollback: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            rollback: Some(Box::new(rollback)),
+            on_commit: None,
+        }
+    }
+
+    /// A transaction with nothing to roll back.
+    pub fn noop() -> Self {
+        Self {
+            rollback: None,
+            on_commit: None,
+        }
+    }
+
+    /// Run `f` if this transaction is committed instead, e.g. to drop the
+    /// state kept for a rollback.
+    pub fn on_commit(mut self, f: impl FnOnce() + Send + 'static) -> Self {
+        self.on_commit = Some(Box::new(f));
+        self
+    }
+
+    /// Also run `f` (after the existing rollback) if this transaction is
+    /// rolled back. Lets a wrapping store undo its own state alongside the
+    /// inner store's.
+    pub fn on_rollback(mut self, f: impl FnOnce() + Send + 'static) -> Self {
+        let first = self.rollback.take();
+        let on_commit = self.on_commit.take();
+        Self {
+            rollback: Some(Box::new(move || {
+                if let Some(first) = first {
+                    first();
+                }
+                f();
+            })),
+            on_commit,
+        }
+    }
+
+    /// Keep every change made since the transaction began.
+    pub fn commit(mut self) {
+        self.rollback = None;
+        if let Some(on_commit) = self.on_commit.take() {
+            on_commit();
+        }
+    }
+
+    /// Undo every change made since the transaction began.
+    pub fn rollback(mut self) {
+        if let Some(rollback) = self.rollback.take() {
+            rollback();
+        }
+    }
+}
+
+impl Drop for TxnGuard {
+    fn drop(&mut self) {
+        if let Some(rollback) = self.rollback.take() {
+            rollback();
+        }
+    }
+}
+
+/// Outcome of [`Repository::merge_from`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeResult {
+    /// `HEAD` simply moved to the remote commit.
+    pub fast_forwarded: bool,
+    /// Paths whose content changed here because of the merge, sorted.
+    pub merged_notes: Vec<String>,
+    /// Paths both sides changed, left as they were locally.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// A file changed differently on both sides of a merge. A side that deleted
+/// the file has empty content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeConflict {
+    pub path: String,
+    pub local: String,
+    pub remote: String,
+    /// Content at the common ancestor, `None` if the file did not exist there
+    /// (or no common ancestor was found).
+    pub base: Option<String>,
+}
+
+/// How a file differs between the two sides of a [`TreeDiff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Only in the newer tree.
+    Added,
+    /// In both, with different blobs.
+    Modified,
+    /// Only in the older tree.
+    Deleted,
 }
 
+/// A file that differs between two trees, as returned by
+/// [`Repository::diff_trees`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeDiff {
+    /// Full path, e.g. `"work/todo.md"`.
+    pub path: String,
+    pub change: ChangeKind,
+}
+
+/// Refs holding [`Repository::set_meta`] blobs live under this prefix, away
+/// from `HEAD` and the branches that are pushed.
+pub const META_REF_PREFIX: &str = "refs/typednotes/";
+
 /// A git repository backed by an ObjectStore.
 pub struct Repository<S: ObjectStore> {
     store: S,
+    /// Root tree with the writes staged by [`Repository::write_note_batch_raw`]
+    /// that [`Repository::commit_batch`] has not committed yet.
+    pending: std::sync::Mutex<Option<Tree>>,
 }
 
 impl<S: ObjectStore> Repository<S> {
     pub fn new(store: S) -> Self {
-        Self { store }
+        Self {
+            store,
+            pending: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Wrap `store` in a [`CachedStore`] holding up to `capacity` objects.
+    pub fn new_cached(store: S, capacity: usize) -> Repository<CachedStore<S>> {
+        Repository::new(CachedStore::with_capacity(store, capacity))
     }
 
     /// Get the current HEAD commit SHA.
@@ -112,7 +364,7 @@ impl<S: ObjectStore> Repository<S> {
         notes
     }
 
-    /// Recursively walk a tree to find notes (files with .md or .txt extension).
+    /// Recursively walk a tree to find notes (files with a [`NOTE_TYPES`](crate::models::NOTE_TYPES) extension).
     fn walk_tree_for_notes<'a>(
         &'a self,
         tree: &'a Tree,
@@ -121,7 +373,10 @@ impl<S: ObjectStore> Repository<S> {
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + 'a>> {
         Box::pin(async move {
             for entry in &tree.entries {
-                if entry.mode == "40000" {
+                if entry.kind() == Some(EntryKind::GitLink) {
+                    // Submodule: its commit lives in another repository
+                    tracing::warn!(path = %entry.name, prefix, "skipping submodule entry");
+                } else if entry.mode == "40000" {
                     // Directory — recurse
                     let sub_prefix = if prefix.is_empty() {
                         entry.name.clone()
@@ -134,51 +389,56 @@ impl<S: ObjectStore> Repository<S> {
                                 .await;
                         }
                     }
-                } else {
-                    // File — check extension
-                    let path = if prefix.is_empty() {
-                        entry.name.clone()
-                    } else {
-                        format!("{}/{}", prefix, entry.name)
-                    };
-
-                    if let Some(ext) = entry.name.rsplit('.').next() {
-                        if ext == "md" || ext == "txt" {
-                            let name = entry.name
-                                [..entry.name.len() - ext.len() - 1]
-                                .to_string();
-                            let namespace = if prefix.is_empty() {
-                                None
-                            } else {
-                                Some(prefix.to_string())
-                            };
-
-                            // Read blob content
-                            let note = if let Some(raw) = self.store.get(&entry.sha).await {
-                                if let Some(blob) = parse_blob(&raw) {
-                                    String::from_utf8(blob.content).unwrap_or_default()
-                                } else {
-                                    String::new()
-                                }
-                            } else {
-                                String::new()
-                            };
-
-                            notes.push(TypedNoteInfo {
-                                path,
-                                name,
-                                namespace,
-                                r#type: note_type_from_ext(ext).to_string(),
-                                note,
-                                sha: entry.sha.to_hex(),
-                            });
-                        }
-                    }
+                } else if let Some(note) = self.note_info(entry, prefix).await {
+                    notes.push(note);
                 }
             }
         })
     }
 
+    /// Build a [`TypedNoteInfo`] for a file entry of the tree at `prefix`, or
+    /// `None` if it is not a note, encrypted or not (see
+    /// [`split_note_filename`]). Symlinks and submodules never are.
+    async fn note_info(&self, entry: &TreeEntry, prefix: &str) -> Option<TypedNoteInfo> {
+        if !entry.kind().is_some_and(EntryKind::is_file) {
+            return None;
+        }
+        let (name, ext, encrypted) = split_note_filename(&entry.name)?;
+        let path = if prefix.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", prefix, entry.name)
+        };
+        let name = name.to_string();
+        let namespace = if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix.to_string())
+        };
+
+        // Read blob content
+        let note = if let Some(raw) = self.store.get(&entry.sha).await {
+            if let Some(blob) = parse_blob(&raw) {
+                String::from_utf8(blob.content).unwrap_or_default()
+            } else {
+                String::new()
+            }
+        } else {
+            String::new()
+        };
+
+        Some(TypedNoteInfo {
+            path,
+            name,
+            namespace,
+            r#type: note_type_from_ext(ext).to_string(),
+            metadata: parse_frontmatter(&note),
+            note,
+            sha: entry.sha.to_hex(),
+            encrypted,
+        })
+    }
+
     /// List all namespaces (directories) in the repository.
     pub async fn list_namespaces(&self) -> Vec<NamespaceInfo> {
         let mut namespaces = Vec::new();
@@ -231,15 +491,62 @@ impl<S: ObjectStore> Repository<S> {
     /// Get a note by its path. Returns (content, note_type).
     pub async fn get_note(&self, path: &str) -> Option<TypedNoteInfo> {
         let tree = self.get_root_tree().await?;
-        let (blob_sha, _) = self.resolve_path(&tree, path).await?;
+        self.get_note_in_tree(&tree, path).await
+    }
+
+    /// Get a note as it was at `commit_sha`.
+    ///
+    /// The commit must be an ancestor of `HEAD` (or `HEAD` itself); foreign
+    /// SHAs return `None`.
+    pub async fn get_note_at(&self, commit_sha: &Sha, path: &str) -> Option<TypedNoteInfo> {
+        let commit = self.find_ancestor(commit_sha).await?;
+        let raw = self.store.get(&commit.tree).await?;
+        let tree = parse_tree(&raw)?;
+        self.get_note_in_tree(&tree, path).await
+    }
+
+    /// Get a note as it was at `commit_sha`, which (like for
+    /// [`list_notes_at`](Self::list_notes_at)) only has to be present in the
+    /// store.
+    pub async fn get_note_in_commit(&self, commit_sha: &Sha, path: &str) -> Option<TypedNoteInfo> {
+        let commit = self.get_commit(commit_sha).await?;
+        let raw = self.store.get(&commit.tree).await?;
+        let tree = parse_tree(&raw)?;
+        self.get_note_in_tree(&tree, path).await
+    }
+
+    /// The commit `target` if `HEAD` can reach it along any parent links.
+    async fn find_ancestor(&self, target: &Sha) -> Option<Commit> {
+        let mut seen = HashSet::new();
+        let mut pending: Vec<Sha> = self.get_head().await.into_iter().collect();
+        while let Some(sha) = pending.pop() {
+            if !seen.insert(sha.clone()) {
+                continue;
+            }
+            let Some(commit) = self.get_commit(&sha).await else {
+                continue;
+            };
+            if sha == *target {
+                return Some(commit);
+            }
+            pending.extend(commit.parents);
+        }
+        None
+    }
+
+    async fn get_note_in_tree(&self, tree: &Tree, path: &str) -> Option<TypedNoteInfo> {
+        let (blob_sha, _) = self.resolve_path(tree, path).await?;
 
         let raw = self.store.get(&blob_sha).await?;
         let blob = parse_blob(&raw)?;
         let content = String::from_utf8(blob.content).ok()?;
 
         let filename = path.rsplit('/').next().unwrap_or(path);
-        let ext = filename.rsplit('.').next().unwrap_or("txt");
-        let name = filename[..filename.len() - ext.len() - 1].to_string();
+        let (name, ext, encrypted) = split_note_filename(filename).unwrap_or_else(|| {
+            let ext = filename.rsplit('.').next().unwrap_or("txt");
+            (&filename[..filename.len() - ext.len() - 1], ext, false)
+        });
+        let name = name.to_string();
         let namespace = if path.contains('/') {
             Some(path.rsplitn(2, '/').nth(1).unwrap_or("").to_string())
         } else {
@@ -251,8 +558,10 @@ impl<S: ObjectStore> Repository<S> {
             name,
             namespace,
             r#type: note_type_from_ext(ext).to_string(),
+            metadata: parse_frontmatter(&content),
             note: content,
             sha: blob_sha.to_hex(),
+            encrypted,
         })
     }
 
@@ -263,18 +572,19 @@ impl<S: ObjectStore> Repository<S> {
         path: &'a str,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<(Sha, String)>> + 'a>> {
         Box::pin(async move {
+            let path = nfc(path);
             let parts: Vec<&str> = path.splitn(2, '/').collect();
             match parts.as_slice() {
                 [name] => {
                     // Leaf: find entry in current tree
                     tree.entries
                         .iter()
-                        .find(|e| e.name == *name)
+                        .find(|e| entry_is(&e.name, name))
                         .map(|e| (e.sha.clone(), e.name.clone()))
                 }
                 [dir, rest] => {
                     // Intermediate: find subtree and recurse
-                    let entry = tree.entries.iter().find(|e| e.name == *dir)?;
+                    let entry = tree.entries.iter().find(|e| entry_is(&e.name, dir))?;
                     let raw = self.store.get(&entry.sha).await?;
                     let sub_tree = parse_tree(&raw)?;
                     self.resolve_path(&sub_tree, rest).await
@@ -293,12 +603,9 @@ impl<S: ObjectStore> Repository<S> {
         note_type: &str,
     ) -> Sha {
         // Ensure path has the right extension
-        let ext = ext_from_note_type(note_type);
-        let full_path = if path.ends_with(&format!(".{ext}")) {
-            path.to_string()
-        } else {
-            format!("{path}.{ext}")
-        };
+        let full_path = note_file_path(&nfc(path), note_type);
+
+        let txn = self.store.begin_txn();
 
         // Create blob
         let blob = Blob {
@@ -320,51 +627,23 @@ impl<S: ObjectStore> Repository<S> {
         let new_root = self
             .update_tree_at_path(&root_tree, &full_path, Some(blob_sha))
             .await;
-        let (tree_sha, tree_raw) = hash_tree(&new_root);
-        self.store.put(&tree_sha, tree_raw).await;
-
-        // Create commit
-        let parent = self.get_head().await;
-        let commit = Commit {
-            tree: tree_sha,
-            parent,
-            author: "TypedNotes <notes@typednotes.com>".to_string(),
-            message: format!("Update {full_path}"),
-            timestamp: current_timestamp(),
-        };
-        let (commit_sha, commit_raw) = hash_commit(&commit);
-        self.store.put(&commit_sha, commit_raw).await;
-        self.store.set_ref("HEAD", &commit_sha).await;
 
-        commit_sha
+        self.commit_root(&new_root, format!("Update {full_path}"), txn).await
     }
 
     /// Delete a note at the given path. Returns the new commit SHA.
     pub async fn delete_note(&self, path: &str) -> Option<Sha> {
+        let path = nfc(path);
         let root_tree = self.get_root_tree().await?;
+        let txn = self.store.begin_txn();
 
-        let new_root = self.update_tree_at_path(&root_tree, path, None).await;
-        let (tree_sha, tree_raw) = hash_tree(&new_root);
-        self.store.put(&tree_sha, tree_raw).await;
-
-        let parent = self.get_head().await;
-        let commit = Commit {
-            tree: tree_sha,
-            parent,
-            author: "TypedNotes <notes@typednotes.com>".to_string(),
-            message: format!("Delete {path}"),
-            timestamp: current_timestamp(),
-        };
-        let (commit_sha, commit_raw) = hash_commit(&commit);
-        self.store.put(&commit_sha, commit_raw).await;
-        self.store.set_ref("HEAD", &commit_sha).await;
-
-        Some(commit_sha)
+        let new_root = self.update_tree_at_path(&root_tree, &path, None).await;
+        Some(self.commit_root(&new_root, format!("Delete {path}"), txn).await)
     }
 
     /// Create a namespace (directory) with a .gitkeep file.
     pub async fn create_namespace(&self, path: &str) -> Sha {
-        let gitkeep_path = format!("{path}/.gitkeep");
+        let gitkeep_path = format!("{}/.gitkeep", nfc(path));
         self.write_note_raw(&gitkeep_path, b"").await
     }
 
@@ -377,10 +656,9 @@ impl<S: ObjectStore> Repository<S> {
         let (tree_sha, tree_raw) = hash_tree(&new_root);
         self.store.put(&tree_sha, tree_raw).await;
 
-        let parent = self.get_head().await;
         let commit = Commit {
             tree: tree_sha,
-            parent,
+            parents: self.get_head().await.into_iter().collect(),
             author: "TypedNotes <notes@typednotes.com>".to_string(),
             message: format!("Delete namespace {path}"),
             timestamp: current_timestamp(),
@@ -399,17 +677,18 @@ impl<S: ObjectStore> Repository<S> {
         path: &'a str,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Tree> + 'a>> {
         Box::pin(async move {
+            let path = nfc(path);
             let parts: Vec<&str> = path.splitn(2, '/').collect();
             let mut entries: Vec<TreeEntry> = tree.entries.clone();
 
             match parts.as_slice() {
                 [dir_name] => {
                     // Leaf: remove the directory entry (mode "40000")
-                    entries.retain(|e| !(e.name == *dir_name && e.mode == "40000"));
+                    entries.retain(|e| !(entry_is(&e.name, dir_name) && e.mode == "40000"));
                 }
                 [dir, rest] => {
                     // Intermediate: recurse into subtree
-                    if let Some(entry) = entries.iter().find(|e| e.name == *dir && e.mode == "40000")
+                    if let Some(entry) = entries.iter().find(|e| entry_is(&e.name, dir) && e.mode == "40000")
                     {
                         if let Some(raw) = self.store.get(&entry.sha).await {
                             if let Some(sub_tree) = parse_tree(&raw) {
@@ -418,7 +697,7 @@ impl<S: ObjectStore> Repository<S> {
                                 self.store.put(&sub_sha, sub_raw).await;
 
                                 if let Some(existing) =
-                                    entries.iter_mut().find(|e| e.name == *dir)
+                                    entries.iter_mut().find(|e| entry_is(&e.name, dir))
                                 {
                                     existing.sha = sub_sha;
                                 }
@@ -435,8 +714,9 @@ impl<S: ObjectStore> Repository<S> {
 
     /// Rename a note: reads content from old_path, writes to new_path, deletes old_path.
     pub async fn rename_note(&self, old_path: &str, new_path: &str) -> Option<Sha> {
+        let (old_path, new_path) = (nfc(old_path), nfc(new_path));
         // Read existing content
-        let note = self.get_note(old_path).await?;
+        let note = self.get_note(&old_path).await?;
         // Write to new path
         self.write_note(
             new_path.trim_end_matches(&format!(".{}", ext_from_note_type(&note.r#type))),
@@ -445,49 +725,745 @@ impl<S: ObjectStore> Repository<S> {
         )
         .await;
         // Delete old path
-        self.delete_note(old_path).await
+        self.delete_note(&old_path).await
     }
 
-    /// Move a namespace and all its contents to a new parent path.
-    /// `new_path` is the full new namespace path (e.g. "A/B" to move B under A).
+    /// Copy the file at `src_path` to `dst_path`.
+    ///
+    /// Returns `None` if the source does not exist or the destination does.
+    pub async fn copy_note(&self, src_path: &str, dst_path: &str) -> Option<Sha> {
+        let root_tree = self.get_root_tree().await?;
+        if self.resolve_path(&root_tree, dst_path).await.is_some() {
+            return None;
+        }
+        let (blob_sha, _) = self.resolve_path(&root_tree, src_path).await?;
+        let blob = parse_blob(&self.store.get(&blob_sha).await?)?;
+        Some(self.write_note_raw(dst_path, &blob.content).await)
+    }
+
+    /// Move a namespace and all its contents to a new parent path, in one
+    /// commit. `new_path` is the full new namespace path (e.g. "A/B" to move B
+    /// under A); if it already exists the two are merged, with files from
+    /// `old_path` replacing any at the same path.
+    ///
+    /// Returns `None` if `old_path` is not a namespace or `new_path` lies
+    /// inside it.
     pub async fn move_namespace(&self, old_path: &str, new_path: &str) -> Option<Sha> {
-        // 1. Move all notes under old_path
-        let all_notes = self.list_notes().await;
-        for note in &all_notes {
-            let prefix = format!("{old_path}/");
-            if note.path.starts_with(&prefix) {
-                let relative = &note.path[prefix.len()..];
-                let dest = format!("{new_path}/{relative}");
-                self.rename_note(&note.path, &dest).await;
+        if old_path == new_path || new_path.starts_with(&format!("{old_path}/")) {
+            return None;
+        }
+        let root_tree = self.get_root_tree().await?;
+        self.relocate_namespace(root_tree, old_path, new_path, format!("Move namespace {old_path} to {new_path}"))
+            .await
+    }
+
+    /// Rename (or move) a namespace, carrying every file below it — notes,
+    /// nested namespaces and their `.gitkeep`s — to `new_path` in one commit.
+    ///
+    /// Returns `None` if `old_path` is not a namespace, `new_path` already
+    /// exists, or `new_path` lies inside `old_path`.
+    pub async fn rename_namespace(&self, old_path: &str, new_path: &str) -> Option<Sha> {
+        if old_path == new_path || new_path.starts_with(&format!("{old_path}/")) {
+            return None;
+        }
+        let root_tree = self.get_root_tree().await?;
+        if self.resolve_path(&root_tree, new_path).await.is_some() {
+            return None;
+        }
+        self.relocate_namespace(root_tree, old_path, new_path, format!("Rename namespace {old_path} to {new_path}"))
+            .await
+    }
+
+    /// Copy every file below `old_path` to `new_path`, drop `old_path`, and
+    /// commit the resulting tree with `message`.
+    async fn relocate_namespace(
+        &self,
+        root_tree: Tree,
+        old_path: &str,
+        new_path: &str,
+        message: String,
+    ) -> Option<Sha> {
+        let (subtree_sha, _) = self.resolve_path(&root_tree, old_path).await?;
+        let subtree = parse_tree(&self.store.get(&subtree_sha).await?)?;
+
+        let mut files = Vec::new();
+        self.collect_files(&subtree, "", &mut files).await;
+
+        // Build the whole new tree first so the move is a single commit;
+        // symlinks and submodules keep their mode
+        let mut new_root = root_tree;
+        for (relative, mode, sha) in files {
+            let dest = format!("{new_path}/{relative}");
+            let updated = self.update_tree_entry_at_path(&new_root, &dest, Some((&mode, sha))).await;
+            new_root = updated;
+        }
+        let new_root = self.remove_subtree(&new_root, old_path).await;
+        let (tree_sha, tree_raw) = hash_tree(&new_root);
+        self.store.put(&tree_sha, tree_raw).await;
+
+        let commit = Commit {
+            tree: tree_sha,
+            parents: self.get_head().await.into_iter().collect(),
+            author: "TypedNotes <notes@typednotes.com>".to_string(),
+            message,
+            timestamp: current_timestamp(),
+        };
+        let (commit_sha, commit_raw) = hash_commit(&commit);
+        self.store.put(&commit_sha, commit_raw).await;
+        self.store.set_ref("HEAD", &commit_sha).await;
+
+        Some(commit_sha)
+    }
+
+    /// Recursively collect `(path, mode, sha)` for every entry in a tree that
+    /// is not a subtree, with paths relative to it. Symlinks and submodules
+    /// (whose SHA names a commit of another repository) are included.
+    fn collect_files<'a>(
+        &'a self,
+        tree: &'a Tree,
+        prefix: &'a str,
+        files: &'a mut Vec<(String, String, Sha)>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + 'a>> {
+        Box::pin(async move {
+            for entry in &tree.entries {
+                let path = if prefix.is_empty() {
+                    entry.name.clone()
+                } else {
+                    format!("{}/{}", prefix, entry.name)
+                };
+                if entry.kind() == Some(EntryKind::Tree) {
+                    if let Some(raw) = self.store.get(&entry.sha).await {
+                        if let Some(sub_tree) = parse_tree(&raw) {
+                            self.collect_files(&sub_tree, &path, files).await;
+                        }
+                    }
+                } else {
+                    files.push((path, entry.mode.clone(), entry.sha.clone()));
+                }
             }
+        })
+    }
+
+    /// Number of objects in the underlying store, if it can count them.
+    pub async fn object_count(&self) -> Option<usize> {
+        self.store.object_count().await
+    }
+
+    /// Every object reachable from `HEAD` and the other refs the store lists:
+    /// annotated tags and what they point at, the commits along all parent
+    /// links, and the trees and blobs they reference.
+    pub async fn reachable_objects(&self) -> HashSet<Sha> {
+        let mut pending: Vec<Sha> = self.get_head().await.into_iter().collect();
+        if let Some(refs) = self.store.list_refs().await {
+            pending.extend(refs.into_iter().map(|(_, sha)| sha));
         }
 
-        // 2. Create the new namespace (ensures .gitkeep exists)
-        self.create_namespace(new_path).await;
+        let mut reachable = HashSet::new();
+        while let Some(sha) = pending.pop() {
+            if reachable.contains(&sha) {
+                continue;
+            }
+            // History may be cut short (e.g. a shallow fetch)
+            let Some(raw) = self.store.get(&sha).await else {
+                reachable.insert(sha);
+                continue;
+            };
+            if let Some(commit) = parse_commit(&raw) {
+                reachable.insert(sha);
+                self.mark_tree(&commit.tree, &mut reachable).await;
+                pending.extend(commit.parents);
+            } else if let Some(tag) = parse_tag(&raw) {
+                reachable.insert(sha);
+                pending.push(tag.object);
+            } else if parse_tree(&raw).is_some() {
+                self.mark_tree(&sha, &mut reachable).await;
+            } else {
+                reachable.insert(sha);
+            }
+        }
+        reachable
+    }
 
-        // 3. Delete the old namespace
-        self.delete_namespace(old_path).await
+    /// Add a tree and everything below it to `reachable`.
+    fn mark_tree<'a>(
+        &'a self,
+        sha: &'a Sha,
+        reachable: &'a mut HashSet<Sha>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + 'a>> {
+        Box::pin(async move {
+            if !reachable.insert(sha.clone()) {
+                return;
+            }
+            let Some(tree) = self.store.get(sha).await.and_then(|raw| parse_tree(&raw)) else {
+                return;
+            };
+            for entry in &tree.entries {
+                match entry.kind() {
+                    Some(EntryKind::Tree) => self.mark_tree(&entry.sha, reachable).await,
+                    // A commit of another repository, never in this store
+                    Some(EntryKind::GitLink) => {}
+                    _ => {
+                        reachable.insert(entry.sha.clone());
+                    }
+                }
+            }
+        })
     }
 
-    /// Read the `typednotes.toml` configuration from the repo root.
-    pub async fn get_config(&self) -> TypedNotesConfig {
-        let Some(tree) = self.get_root_tree().await else {
-            return TypedNotesConfig::default();
+    /// Key the store's objects by the `hash_algorithm` of `typednotes.toml`
+    /// (see [`ObjectStore::set_hash_algorithm`]). Returns `false` if the store
+    /// cannot.
+    pub async fn apply_hash_algorithm(&self) -> bool {
+        let algorithm = self.get_config().await.hash_algorithm;
+        self.store.set_hash_algorithm(algorithm).await
+    }
+
+    /// Mark-and-sweep garbage collection: delete every object that is not
+    /// reachable from a ref. Returns the number of objects freed.
+    ///
+    /// Does nothing when there is no `HEAD`, or when the store cannot list its
+    /// refs, since objects only another ref (a fetched branch, a tag) points at
+    /// would be lost.
+    pub async fn gc(&self) -> usize {
+        if self.get_head().await.is_none() || self.store.list_refs().await.is_none() {
+            return 0;
+        }
+        let keep = self.reachable_objects().await;
+        // A store that can be walked cheaply is only swept when it holds garbage
+        let mut garbage = false;
+        if self.store.iter_objects_sync(&mut |sha, _| garbage |= !keep.contains(sha)) && !garbage {
+            return 0;
+        }
+        self.store.retain_objects(&keep).await
+    }
+
+    /// Store every object of a git pack and point `HEAD` at `head_sha`,
+    /// returning the number of objects imported.
+    ///
+    /// Used to bootstrap a new client from the pack the server builds of the
+    /// remote (see [`crate::pack`]) instead of pulling note by note. Only
+    /// whole (non-delta) entries are supported. Fails without touching `HEAD`
+    /// if the pack is malformed or `head_sha` is not a commit it holds.
+    pub async fn import_from_pack(&self, pack_data: &[u8], head_sha: Sha) -> Result<usize, String> {
+        let objects = crate::pack::read_pack(pack_data)?;
+        let head_is_commit = objects
+            .iter()
+            .any(|(sha, raw)| *sha == head_sha && parse_commit(raw).is_some());
+        if !head_is_commit {
+            return Err(format!("Pack has no commit {}", head_sha.to_hex()));
+        }
+
+        let txn = self.store.begin_txn();
+        let count = objects.len();
+        for (sha, raw) in objects {
+            self.store.put(&sha, raw).await;
+        }
+        self.store.set_ref("HEAD", &head_sha).await;
+        txn.commit();
+        Ok(count)
+    }
+
+    /// Merge the `HEAD` of `remote_store` into this repository.
+    ///
+    /// Every object of `remote_store` is copied here first. If `HEAD` is an
+    /// ancestor of the remote commit (or there is no `HEAD`), `HEAD` moves to
+    /// it. Otherwise each file is compared against the newest common ancestor:
+    /// a change made on one side only is kept, and a file both sides changed
+    /// differently becomes a [`MergeConflict`] and keeps its local content.
+    ///
+    /// Without conflicts the result is committed as a merge with both heads as
+    /// parents. With conflicts, the one-sided remote changes are committed on
+    /// top of `HEAD` as an ordinary commit instead, so the remote commit stays
+    /// unmerged and the conflicted files come up again on the next merge.
+    pub async fn merge_from(&self, remote_store: &MemoryStore) -> MergeResult {
+        let mut result = MergeResult::default();
+        let Some(remote_head) = remote_store.get_ref_sync("HEAD") else {
+            return result;
+        };
+        let local_head = self.get_head().await;
+        if local_head.as_ref() == Some(&remote_head) {
+            return result;
+        }
+
+        let txn = self.store.begin_txn();
+        // Copied as they are visited where the store takes synchronous
+        // writes; otherwise collected and written afterwards
+        let mut remote_objects = Vec::new();
+        remote_store.iter_objects(|sha, raw| {
+            if !self.store.try_put_sync(sha, raw.to_vec()) {
+                remote_objects.push((sha.clone(), raw.to_vec()));
+            }
+        });
+        for (sha, raw) in remote_objects {
+            self.store.put(&sha, raw).await;
+        }
+
+        let remote_files = self.commit_files(&remote_head).await;
+        let Some(local_head) = local_head else {
+            self.store.set_ref("HEAD", &remote_head).await;
+            txn.commit();
+            result.fast_forwarded = true;
+            result.merged_notes = remote_files.into_keys().collect::<BTreeSet<_>>().into_iter().collect();
+            return result;
+        };
+
+        let local_ancestors = self.ancestors(&local_head).await;
+        if local_ancestors.contains(&remote_head) {
+            // Already merged
+            txn.commit();
+            return result;
+        }
+        let local_files = self.commit_files(&local_head).await;
+        let remote_ancestors = self.ancestors(&remote_head).await;
+        if remote_ancestors.contains(&local_head) {
+            self.store.set_ref("HEAD", &remote_head).await;
+            txn.commit();
+            result.fast_forwarded = true;
+            result.merged_notes = changed_paths(&local_files, &remote_files);
+            return result;
+        }
+
+        let base_files = match self.merge_base(&remote_head, &local_ancestors).await {
+            Some(base) => self.commit_files(&base).await,
+            None => HashMap::new(),
+        };
+        let mut root = self.get_root_tree().await.unwrap_or(Tree {
+            entries: Vec::new(),
+        });
+        for path in changed_paths(&local_files, &remote_files) {
+            let local = local_files.get(&path);
+            let remote = remote_files.get(&path);
+            let base = base_files.get(&path);
+            if local == base {
+                root = self.update_tree_at_path(&root, &path, remote.cloned()).await;
+                result.merged_notes.push(path);
+            } else if remote != base {
+                result.conflicts.push(MergeConflict {
+                    local: self.blob_text(local).await,
+                    remote: self.blob_text(remote).await,
+                    base: match base {
+                        Some(sha) => Some(self.blob_text(Some(sha)).await),
+                        None => None,
+                    },
+                    path,
+                });
+            }
+        }
+
+        if result.conflicts.is_empty() {
+            self.commit_tree(
+                &root,
+                vec![local_head, remote_head],
+                "Merge remote changes".to_string(),
+                txn,
+            )
+            .await;
+        } else if !result.merged_notes.is_empty() {
+            self.commit_root(&root, "Merge non-conflicting remote changes".to_string(), txn)
+                .await;
+        } else {
+            txn.commit();
+        }
+        result
+    }
+
+    /// `head` and every commit it reaches along any parent links.
+    async fn ancestors(&self, head: &Sha) -> HashSet<Sha> {
+        let mut seen = HashSet::new();
+        let mut pending = vec![head.clone()];
+        while let Some(sha) = pending.pop() {
+            if !seen.insert(sha.clone()) {
+                continue;
+            }
+            // History may be cut short (e.g. a shallow fetch)
+            if let Some(commit) = self.get_commit(&sha).await {
+                pending.extend(commit.parents);
+            }
+        }
+        seen
+    }
+
+    /// The newest commit reachable from `head` that is in `other_ancestors`.
+    /// As in [`note_history`](Self::note_history), equal timestamps go in
+    /// discovery order so a child is visited before its parents.
+    async fn merge_base(&self, head: &Sha, other_ancestors: &HashSet<Sha>) -> Option<Sha> {
+        let commit = self.get_commit(head).await?;
+        let mut queue = BinaryHeap::from([(commit.timestamp, Reverse(0), head.clone())]);
+        let mut seen = HashSet::from([head.clone()]);
+        while let Some((_, _, sha)) = queue.pop() {
+            if other_ancestors.contains(&sha) {
+                return Some(sha);
+            }
+            let Some(commit) = self.get_commit(&sha).await else {
+                continue;
+            };
+            for parent in commit.parents {
+                if seen.insert(parent.clone()) {
+                    if let Some(parent_commit) = self.get_commit(&parent).await {
+                        queue.push((parent_commit.timestamp, Reverse(seen.len()), parent));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Files that differ between trees `tree_a` (the older side) and
+    /// `tree_b`, sorted by path.
+    ///
+    /// Walks both trees together, comparing entries by name; subtrees with the
+    /// same SHA on both sides are skipped without being read. A tree missing
+    /// from the store counts as empty.
+    pub async fn diff_trees(&self, tree_a: &Sha, tree_b: &Sha) -> Vec<TreeDiff> {
+        let a = self.tree_or_empty(Some(tree_a)).await;
+        let b = self.tree_or_empty(Some(tree_b)).await;
+        self.diff_tree_objects(a, b).await
+    }
+
+    /// [`diff_trees`](Self::diff_trees) of the root trees of two commits. A
+    /// commit missing from the store (e.g. cut off by a shallow fetch) counts
+    /// as an empty tree.
+    pub async fn diff_commits(&self, commit_a: &Sha, commit_b: &Sha) -> Vec<TreeDiff> {
+        let a = self.commit_tree_or_empty(commit_a).await;
+        let b = self.commit_tree_or_empty(commit_b).await;
+        self.diff_tree_objects(a, b).await
+    }
+
+    async fn diff_tree_objects(&self, a: Tree, b: Tree) -> Vec<TreeDiff> {
+        let mut diffs = Vec::new();
+        self.diff_entries(a, b, String::new(), &mut diffs).await;
+        diffs.sort_by(|x, y| x.path.cmp(&y.path));
+        diffs
+    }
+
+    /// Root tree of commit `sha`, or an empty tree.
+    async fn commit_tree_or_empty(&self, sha: &Sha) -> Tree {
+        let tree = self.get_commit(sha).await.map(|commit| commit.tree);
+        self.tree_or_empty(tree.as_ref()).await
+    }
+
+    /// Tree `sha`, or an empty tree for `None` or a missing object.
+    async fn tree_or_empty(&self, sha: Option<&Sha>) -> Tree {
+        let tree = match sha {
+            Some(sha) => self.store.get(sha).await.and_then(|raw| parse_tree(&raw)),
+            None => None,
+        };
+        tree.unwrap_or(Tree { entries: Vec::new() })
+    }
+
+    /// Push a [`TreeDiff`] for every file below `prefix` that differs between
+    /// `a` and `b`.
+    fn diff_entries<'a>(
+        &'a self,
+        a: Tree,
+        b: Tree,
+        prefix: String,
+        diffs: &'a mut Vec<TreeDiff>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + 'a>> {
+        Box::pin(async move {
+            let old_entries: HashMap<&str, &TreeEntry> = a.entries.iter().map(|e| (e.name.as_str(), e)).collect();
+            let new_entries: HashMap<&str, &TreeEntry> = b.entries.iter().map(|e| (e.name.as_str(), e)).collect();
+            let names: BTreeSet<&str> = old_entries.keys().chain(new_entries.keys()).copied().collect();
+
+            for name in names {
+                let old = old_entries.get(name).copied();
+                let new = new_entries.get(name).copied();
+                if let (Some(old), Some(new)) = (old, new) {
+                    if old.sha == new.sha {
+                        continue;
+                    }
+                }
+                let path = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{prefix}/{name}")
+                };
+
+                let old_file = old.filter(|e| e.mode != "40000");
+                let new_file = new.filter(|e| e.mode != "40000");
+                let change = match (old_file, new_file) {
+                    (Some(_), Some(_)) => Some(ChangeKind::Modified),
+                    (Some(_), None) => Some(ChangeKind::Deleted),
+                    (None, Some(_)) => Some(ChangeKind::Added),
+                    (None, None) => None,
+                };
+                if let Some(change) = change {
+                    diffs.push(TreeDiff { path: path.clone(), change });
+                }
+
+                // A subtree on either side is walked against the other side's
+                // subtree, or an empty one where that side has a file or nothing
+                let old_dir = old.filter(|e| e.mode == "40000");
+                let new_dir = new.filter(|e| e.mode == "40000");
+                if old_dir.is_some() || new_dir.is_some() {
+                    let old_sub = self.tree_or_empty(old_dir.map(|e| &e.sha)).await;
+                    let new_sub = self.tree_or_empty(new_dir.map(|e| &e.sha)).await;
+                    self.diff_entries(old_sub, new_sub, path, diffs).await;
+                }
+            }
+        })
+    }
+
+    /// Path → blob SHA of every file in the tree of commit `sha`.
+    async fn commit_files(&self, sha: &Sha) -> HashMap<String, Sha> {
+        let mut files = Vec::new();
+        let tree = match self.get_commit(sha).await {
+            Some(commit) => self.store.get(&commit.tree).await.and_then(|raw| parse_tree(&raw)),
+            None => None,
         };
-        let Some((blob_sha, _)) = self.resolve_path(&tree, TypedNotesConfig::filename()).await
-        else {
-            return TypedNotesConfig::default();
+        if let Some(tree) = tree {
+            self.collect_files(&tree, "", &mut files).await;
+        }
+        files.into_iter().map(|(path, _, sha)| (path, sha)).collect()
+    }
+
+    /// Content of blob `sha` as (lossy) UTF-8, empty for `None`.
+    async fn blob_text(&self, sha: Option<&Sha>) -> String {
+        let Some(sha) = sha else {
+            return String::new();
         };
-        let Some(raw) = self.store.get(&blob_sha).await else {
-            return TypedNotesConfig::default();
+        self.store
+            .get(sha)
+            .await
+            .and_then(|raw| parse_blob(&raw))
+            .map(|blob| String::from_utf8_lossy(&blob.content).into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Load and parse the commit with the given SHA.
+    pub async fn get_commit(&self, sha: &Sha) -> Option<Commit> {
+        let raw = self.store.get(sha).await?;
+        parse_commit(&raw)
+    }
+
+    /// List every note in the tree of the commit with the given SHA.
+    ///
+    /// Unlike [`get_note_at`](Self::get_note_at), the commit only has to be
+    /// present in the store, not reachable from `HEAD`.
+    pub async fn list_notes_at(&self, commit_sha: &Sha) -> Vec<TypedNoteInfo> {
+        let mut notes = Vec::new();
+        let Some(commit) = self.get_commit(commit_sha).await else {
+            return notes;
         };
-        let Some(blob) = parse_blob(&raw) else {
-            return TypedNotesConfig::default();
+        let Some(tree) = self.store.get(&commit.tree).await.and_then(|raw| parse_tree(&raw)) else {
+            return notes;
         };
-        let Ok(text) = String::from_utf8(blob.content) else {
-            return TypedNotesConfig::default();
+        self.walk_tree_for_notes(&tree, "", &mut notes).await;
+        notes
+    }
+
+    /// Notes changed by any commit made after `since` (Unix seconds), as they
+    /// are at `HEAD`.
+    ///
+    /// Walks back from `HEAD` along every parent link, stopping at commits
+    /// with `timestamp <= since`, and [diffs](Self::diff_commits) each newer
+    /// commit against its parents. Notes deleted since then are not included.
+    pub async fn list_notes_modified_since(&self, since: i64) -> Vec<TypedNoteInfo> {
+        let mut changed = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut pending: Vec<Sha> = self.get_head().await.into_iter().collect();
+        while let Some(sha) = pending.pop() {
+            if !seen.insert(sha.clone()) {
+                continue;
+            }
+            let Some(commit) = self.get_commit(&sha).await else {
+                continue;
+            };
+            if commit.timestamp <= since {
+                continue;
+            }
+            if commit.parents.is_empty() {
+                let tree = self.tree_or_empty(Some(&commit.tree)).await;
+                let diffs = self.diff_tree_objects(Tree { entries: Vec::new() }, tree).await;
+                changed.extend(diffs.into_iter().map(|diff| diff.path));
+            }
+            for parent in &commit.parents {
+                // A parent cut off by a shallow fetch counts as empty
+                changed.extend(self.diff_commits(parent, &sha).await.into_iter().map(|diff| diff.path));
+            }
+            pending.extend(commit.parents);
+        }
+
+        let mut notes = self.list_notes().await;
+        notes.retain(|note| changed.contains(&note.path));
+        notes
+    }
+
+    /// Notes in `HEAD` that differ from their version in commit `base`, such
+    /// as the remote head a client last pulled. Unlike
+    /// [`list_notes_modified_since`](Self::list_notes_modified_since) this
+    /// does not depend on commit timestamps, and a note changed and changed
+    /// back in between is not reported. A `base` missing from the store
+    /// counts as an empty tree, so every note is returned. Deleted notes are
+    /// not included.
+    pub async fn list_notes_changed_since(&self, base: &Sha) -> Vec<TypedNoteInfo> {
+        let Some(head) = self.get_head().await else {
+            return Vec::new();
         };
-        TypedNotesConfig::from_toml(&text).unwrap_or_default()
+        let changed: HashSet<String> = self
+            .diff_commits(base, &head)
+            .await
+            .into_iter()
+            .filter(|diff| diff.change != ChangeKind::Deleted)
+            .map(|diff| diff.path)
+            .collect();
+        let mut notes = self.list_notes().await;
+        notes.retain(|note| changed.contains(&note.path));
+        notes
+    }
+
+    /// Resolve `path` in the tree of `commit` to its blob SHA.
+    async fn blob_at(&self, commit: &Commit, path: &str) -> Option<Sha> {
+        let raw = self.store.get(&commit.tree).await?;
+        let tree = parse_tree(&raw)?;
+        let (sha, _) = self.resolve_path(&tree, path).await?;
+        Some(sha)
+    }
+
+    /// History of a single note, newest first.
+    ///
+    /// Visits every commit reachable from `HEAD` in timestamp order (following
+    /// all parents of a merge) and keeps each one whose blob at `path` differs
+    /// from its parents' — from every parent, for a merge, so merges that just
+    /// carry one side's version over are skipped. This includes the commits
+    /// that created or deleted it. Stops after `limit` matches.
+    pub async fn note_history(&self, path: &str, limit: usize) -> Vec<(Sha, Commit)> {
+        let mut history = Vec::new();
+        let Some(head) = self.get_head().await else {
+            return history;
+        };
+        let Some(head_commit) = self.get_commit(&head).await else {
+            return history;
+        };
+
+        // Indexes into `discovered`, newest first; among equal timestamps
+        // (one-second resolution) the commit found first, so a child still
+        // precedes its parents.
+        let mut queue = BinaryHeap::from([(head_commit.timestamp, Reverse(0))]);
+        let mut discovered = vec![Some((head.clone(), head_commit))];
+        let mut seen = HashSet::from([head]);
+
+        while let Some((_, Reverse(index))) = queue.pop() {
+            if history.len() >= limit {
+                break;
+            }
+            let Some((sha, commit)) = discovered[index].take() else {
+                continue;
+            };
+            let blob = self.blob_at(&commit, path).await;
+
+            let mut parent_blobs = Vec::new();
+            for parent_sha in &commit.parents {
+                // History may be cut short (e.g. a shallow fetch)
+                let Some(parent) = self.get_commit(parent_sha).await else {
+                    continue;
+                };
+                parent_blobs.push(self.blob_at(&parent, path).await);
+                if seen.insert(parent_sha.clone()) {
+                    queue.push((parent.timestamp, Reverse(discovered.len())));
+                    discovered.push(Some((parent_sha.clone(), parent)));
+                }
+            }
+
+            let changed = if parent_blobs.is_empty() {
+                blob.is_some()
+            } else {
+                parent_blobs.iter().all(|parent_blob| *parent_blob != blob)
+            };
+            if changed {
+                history.push((sha, commit));
+            }
+        }
+
+        history
+    }
+
+    /// Notes whose content links to the note at `target_path`.
+    ///
+    /// Recognises `[[name]]` and `[[namespace/name]]` wiki links (ignoring a
+    /// `#heading` or `|alias` suffix, and also matching the target's frontmatter
+    /// aliases), plus markdown links to `/{target_path}`, `./{file}` and
+    /// `./{name}`. This is a plain string scan, so links inside code blocks
+    /// count too. The target itself is never included.
+    pub async fn find_backlinks(&self, target_path: &str) -> Vec<TypedNoteInfo> {
+        let notes = self.list_notes().await;
+        let Some(target) = notes.iter().find(|n| n.path == target_path) else {
+            return Vec::new();
+        };
+
+        let mut wiki_names = vec![target.name.to_lowercase()];
+        if let Some(ns) = &target.namespace {
+            wiki_names.push(format!("{ns}/{}", target.name).to_lowercase());
+        }
+        if let Some(meta) = &target.metadata {
+            wiki_names.extend(meta.aliases.iter().map(|a| a.to_lowercase()));
+        }
+        let file = target_path.rsplit('/').next().unwrap_or(target_path);
+        let md_targets = [
+            format!("](/{target_path})"),
+            format!("](./{file})"),
+            format!("](./{})", target.name),
+        ];
+
+        notes
+            .iter()
+            .filter(|n| n.path != target_path)
+            .filter(|n| {
+                md_targets.iter().any(|t| n.note.contains(t.as_str()))
+                    || wiki_link_targets(&n.note).any(|t| wiki_names.contains(&t.to_lowercase()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The bytes of the file at `path` (any file, not just notes).
+    pub async fn read_raw(&self, path: &str) -> Option<Vec<u8>> {
+        let tree = self.get_root_tree().await?;
+        let (blob_sha, _) = self.resolve_path(&tree, path).await?;
+        let raw = self.store.get(&blob_sha).await?;
+        Some(parse_blob(&raw)?.content)
+    }
+
+    /// Every file in the `HEAD` tree with its bytes, sorted by path. Unlike
+    /// [`list_notes`](Self::list_notes) this includes attachments, the config
+    /// and the `.gitkeep` files that keep empty namespaces.
+    pub async fn list_files(&self) -> Vec<(String, Vec<u8>)> {
+        let Some(tree) = self.get_root_tree().await else {
+            return Vec::new();
+        };
+        let mut entries = Vec::new();
+        self.collect_files(&tree, "", &mut entries).await;
+        entries.sort();
+
+        let mut files = Vec::with_capacity(entries.len());
+        for (path, _, sha) in entries {
+            if let Some(blob) = self.store.get(&sha).await.and_then(|raw| parse_blob(&raw)) {
+                files.push((path, blob.content));
+            }
+        }
+        files
+    }
+
+    /// Read the `typednotes.toml` configuration from the repo root, falling
+    /// back to [`TypedNotesConfig::default`] if it is missing or invalid.
+    ///
+    /// Writes go through [`load_config`](Self::load_config) instead, so an
+    /// invalid file is never silently replaced by the defaults.
+    pub async fn get_config(&self) -> TypedNotesConfig {
+        self.load_config().await.unwrap_or_default()
+    }
+
+    /// Read the `typednotes.toml` configuration from the repo root: the
+    /// default if there is none, an error if it is not UTF-8, does not parse
+    /// or fails [`TypedNotesConfig::validate`].
+    pub async fn load_config(&self) -> Result<TypedNotesConfig, ConfigError> {
+        let Some(raw) = self.read_raw(TypedNotesConfig::filename()).await else {
+            return Ok(TypedNotesConfig::default());
+        };
+        let text = String::from_utf8(raw).map_err(|_| ConfigError {
+            message: format!("{} is not valid UTF-8", TypedNotesConfig::filename()),
+        })?;
+        TypedNotesConfig::from_toml(&text)
     }
 
     /// Write the `typednotes.toml` configuration into the repo root.
@@ -497,6 +1473,61 @@ impl<S: ObjectStore> Repository<S> {
             .await
     }
 
+    /// The bytes last stored under `name` by [`set_meta`](Self::set_meta).
+    pub async fn get_meta(&self, name: &str) -> Option<Vec<u8>> {
+        let sha = self.store.get_ref(&format!("{META_REF_PREFIX}{name}")).await?;
+        Some(parse_blob(&self.store.get(&sha).await?)?.content)
+    }
+
+    /// Keep `data` in the store under `name`, replacing the previous value.
+    ///
+    /// The blob is reachable from its ref only, so [`gc`](Self::gc) keeps the
+    /// latest value and sweeps the ones it replaced.
+    pub async fn set_meta(&self, name: &str, data: &[u8]) {
+        let (sha, raw) = hash_blob(&Blob { content: data.to_vec() });
+        self.store.put(&sha, raw).await;
+        self.store.set_ref(&format!("{META_REF_PREFIX}{name}"), &sha).await;
+    }
+
+    /// Pin a note to the top of the sidebar (no-op if already pinned, or if
+    /// `typednotes.toml` is invalid, so it is not overwritten).
+    pub async fn pin_note(&self, path: &str) -> Option<Sha> {
+        let mut config = self.load_config().await.ok()?;
+        if config.pinned_notes.iter().any(|p| p == path) {
+            return None;
+        }
+        config.pinned_notes.push(path.to_string());
+        Some(self.set_config(&config).await)
+    }
+
+    /// Unpin a note (no-op if it is not pinned, or if `typednotes.toml` is
+    /// invalid).
+    pub async fn unpin_note(&self, path: &str) -> Option<Sha> {
+        let mut config = self.load_config().await.ok()?;
+        let before = config.pinned_notes.len();
+        config.pinned_notes.retain(|p| p != path);
+        if config.pinned_notes.len() == before {
+            return None;
+        }
+        Some(self.set_config(&config).await)
+    }
+
+    /// Set how namespace `path` looks in the sidebar (no-op if unchanged, or
+    /// if `typednotes.toml` is invalid).
+    pub async fn set_namespace_style(&self, path: &str, style: NamespaceStyle) -> Option<Sha> {
+        let mut config = self.load_config().await.ok()?;
+        let key = path.trim_matches('/').to_string();
+        let previous = if style.is_default() {
+            config.namespace_styles.remove(&key)
+        } else {
+            config.namespace_styles.insert(key, style.clone())
+        };
+        if previous.unwrap_or_default() == style {
+            return None;
+        }
+        Some(self.set_config(&config).await)
+    }
+
     /// List notes scoped to a subtree (e.g. a notes root folder).
     /// If `root` is empty, behaves like `list_notes`.
     pub async fn list_notes_in(&self, root: &str) -> Vec<TypedNoteInfo> {
@@ -516,6 +1547,19 @@ impl<S: ObjectStore> Repository<S> {
         notes
     }
 
+    /// Notes under [`TEMPLATES_NAMESPACE`], at any depth, with full paths
+    /// (e.g. `".templates/meeting.md"`).
+    pub async fn list_templates(&self) -> Vec<TypedNoteInfo> {
+        let mut notes = Vec::new();
+        let Some(root_tree) = self.get_root_tree().await else {
+            return notes;
+        };
+        if let Some(tree) = self.resolve_subtree(&root_tree, TEMPLATES_NAMESPACE).await {
+            self.walk_tree_for_notes(&tree, TEMPLATES_NAMESPACE, &mut notes).await;
+        }
+        notes
+    }
+
     /// List namespaces scoped to a subtree.
     /// If `root` is empty, behaves like `list_namespaces`.
     pub async fn list_namespaces_in(&self, root: &str) -> Vec<NamespaceInfo> {
@@ -536,6 +1580,41 @@ impl<S: ObjectStore> Repository<S> {
         namespaces
     }
 
+    /// Direct child namespaces and notes of `path` (`""` for the root), with
+    /// full paths. Unlike the `_in` variants this does not recurse, so a large
+    /// tree can be loaded one level at a time.
+    pub async fn list_namespace_children(&self, path: &str) -> (Vec<NamespaceInfo>, Vec<TypedNoteInfo>) {
+        let mut namespaces = Vec::new();
+        let mut notes = Vec::new();
+        let Some(root_tree) = self.get_root_tree().await else {
+            return (namespaces, notes);
+        };
+        let tree = if path.is_empty() {
+            root_tree
+        } else {
+            match self.resolve_subtree(&root_tree, path).await {
+                Some(t) => t,
+                None => return (namespaces, notes),
+            }
+        };
+        let parent = (!path.is_empty()).then(|| path.to_string());
+        for entry in &tree.entries {
+            if entry.mode == "40000" {
+                namespaces.push(NamespaceInfo {
+                    path: match &parent {
+                        Some(parent) => format!("{parent}/{}", entry.name),
+                        None => entry.name.clone(),
+                    },
+                    name: entry.name.clone(),
+                    parent: parent.clone(),
+                });
+            } else if let Some(note) = self.note_info(entry, path).await {
+                notes.push(note);
+            }
+        }
+        (namespaces, notes)
+    }
+
     /// Resolve a subtree path from a root tree.
     fn resolve_subtree<'a>(
         &'a self,
@@ -563,6 +1642,8 @@ impl<S: ObjectStore> Repository<S> {
 
     /// Write raw bytes at a path.
     pub async fn write_note_raw(&self, path: &str, content: &[u8]) -> Sha {
+        let path = nfc(path);
+        let txn = self.store.begin_txn();
         let blob = Blob {
             content: content.to_vec(),
         };
@@ -578,62 +1659,124 @@ impl<S: ObjectStore> Repository<S> {
         };
 
         let new_root = self
-            .update_tree_at_path(&root_tree, path, Some(blob_sha))
+            .update_tree_at_path(&root_tree, &path, Some(blob_sha))
             .await;
-        let (tree_sha, tree_raw) = hash_tree(&new_root);
+
+        self.commit_root(&new_root, format!("Create {path}"), txn).await
+    }
+
+    /// Write raw bytes at a path without committing. The change is staged on a
+    /// pending root tree; [`commit_batch`](Self::commit_batch) commits every
+    /// staged write in one commit. Returns the blob SHA.
+    pub async fn write_note_batch_raw(&self, path: &str, content: &[u8]) -> Sha {
+        let blob = Blob {
+            content: content.to_vec(),
+        };
+        let (blob_sha, blob_raw) = hash_blob(&blob);
+        self.store.put(&blob_sha, blob_raw).await;
+
+        let pending = self.pending.lock().unwrap().take();
+        let root_tree = match pending {
+            Some(tree) => tree,
+            None => self.get_root_tree().await.unwrap_or(Tree {
+                entries: Vec::new(),
+            }),
+        };
+
+        let new_root = self
+            .update_tree_at_path(&root_tree, path, Some(blob_sha.clone()))
+            .await;
+        *self.pending.lock().unwrap() = Some(new_root);
+        blob_sha
+    }
+
+    /// Commit everything staged by [`write_note_batch_raw`](Self::write_note_batch_raw)
+    /// as a single commit on top of `HEAD`. With nothing staged, commits the
+    /// current root tree unchanged. Returns the commit SHA.
+    pub async fn commit_batch(&self, message: String) -> Sha {
+        let txn = self.store.begin_txn();
+        let pending = self.pending.lock().unwrap().take();
+        let root = match pending {
+            Some(tree) => tree,
+            None => self.get_root_tree().await.unwrap_or(Tree {
+                entries: Vec::new(),
+            }),
+        };
+        self.commit_root(&root, message, txn).await
+    }
+
+    /// Store `root` and a commit of it on top of `HEAD`, advance `HEAD`, then
+    /// commit `txn`. Returns the commit SHA.
+    async fn commit_root(&self, root: &Tree, message: String, txn: TxnGuard) -> Sha {
+        let parents = self.get_head().await.into_iter().collect();
+        self.commit_tree(root, parents, message, txn).await
+    }
+
+    /// Like [`commit_root`](Self::commit_root), with explicit `parents`.
+    async fn commit_tree(&self, root: &Tree, parents: Vec<Sha>, message: String, txn: TxnGuard) -> Sha {
+        let (tree_sha, tree_raw) = hash_tree(root);
         self.store.put(&tree_sha, tree_raw).await;
 
-        let parent = self.get_head().await;
         let commit = Commit {
             tree: tree_sha,
-            parent,
+            parents,
             author: "TypedNotes <notes@typednotes.com>".to_string(),
-            message: format!("Create {path}"),
+            message,
             timestamp: current_timestamp(),
         };
         let (commit_sha, commit_raw) = hash_commit(&commit);
         self.store.put(&commit_sha, commit_raw).await;
         self.store.set_ref("HEAD", &commit_sha).await;
+        txn.commit();
 
         commit_sha
     }
 
     /// Update a tree by inserting or removing an entry at a path.
     /// If `blob_sha` is Some, inserts/updates. If None, removes.
-    fn update_tree_at_path<'a>(
+    async fn update_tree_at_path(&self, tree: &Tree, path: &str, blob_sha: Option<Sha>) -> Tree {
+        self.update_tree_entry_at_path(tree, path, blob_sha.map(|sha| ("100644", sha)))
+            .await
+    }
+
+    /// [`update_tree_at_path`](Self::update_tree_at_path) with the mode a new
+    /// entry gets; an existing entry keeps its own.
+    fn update_tree_entry_at_path<'a>(
         &'a self,
         tree: &'a Tree,
         path: &'a str,
-        blob_sha: Option<Sha>,
+        new_entry: Option<(&'a str, Sha)>,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Tree> + 'a>> {
         Box::pin(async move {
+            let path = nfc(path);
             let parts: Vec<&str> = path.splitn(2, '/').collect();
             let mut entries: Vec<TreeEntry> = tree.entries.clone();
 
             match parts.as_slice() {
                 [filename] => {
-                    if let Some(sha) = blob_sha {
-                        // Insert or update entry
+                    if let Some((mode, sha)) = new_entry {
+                        // Insert or update entry (renaming a decomposed one to NFC)
                         if let Some(existing) =
-                            entries.iter_mut().find(|e| e.name == *filename)
+                            entries.iter_mut().find(|e| entry_is(&e.name, filename))
                         {
+                            existing.name = filename.to_string();
                             existing.sha = sha;
                         } else {
                             entries.push(TreeEntry {
-                                mode: "100644".to_string(),
+                                mode: mode.to_string(),
                                 name: filename.to_string(),
                                 sha,
                             });
                         }
                     } else {
                         // Remove entry
-                        entries.retain(|e| e.name != *filename);
+                        entries.retain(|e| !entry_is(&e.name, filename));
                     }
                 }
                 [dir, rest] => {
                     // Find or create subtree
                     let sub_tree = if let Some(entry) =
-                        entries.iter().find(|e| e.name == *dir)
+                        entries.iter().find(|e| entry_is(&e.name, dir))
                     {
                         if let Some(raw) = self.store.get(&entry.sha).await {
                             parse_tree(&raw).unwrap_or(Tree {
@@ -650,11 +1793,12 @@ impl<S: ObjectStore> Repository<S> {
                         }
                     };
 
-                    let new_sub = self.update_tree_at_path(&sub_tree, rest, blob_sha).await;
+                    let new_sub = self.update_tree_entry_at_path(&sub_tree, rest, new_entry).await;
                     let (sub_sha, sub_raw) = hash_tree(&new_sub);
                     self.store.put(&sub_sha, sub_raw).await;
 
-                    if let Some(existing) = entries.iter_mut().find(|e| e.name == *dir) {
+                    if let Some(existing) = entries.iter_mut().find(|e| entry_is(&e.name, dir)) {
+                        existing.name = dir.to_string();
                         existing.sha = sub_sha;
                     } else {
                         entries.push(TreeEntry {
@@ -672,7 +1816,49 @@ impl<S: ObjectStore> Repository<S> {
     }
 }
 
-fn current_timestamp() -> i64 {
+/// Targets of the `[[...]]` wiki links in `content`, without any `#heading`
+/// or `|alias` suffix.
+fn wiki_link_targets(content: &str) -> impl Iterator<Item = &str> {
+    content.split("[[").skip(1).filter_map(|rest| {
+        let inner = &rest[..rest.find("]]")?];
+        if inner.contains('\n') {
+            return None;
+        }
+        Some(inner.split(['#', '|']).next().unwrap_or_default().trim())
+    })
+}
+
+/// Paths whose blob differs between `a` and `b` (including paths in only
+/// one of them), sorted.
+fn changed_paths(a: &HashMap<String, Sha>, b: &HashMap<String, Sha>) -> Vec<String> {
+    a.keys()
+        .chain(b.keys())
+        .filter(|path| a.get(*path) != b.get(*path))
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// `path` in Unicode NFC, the form tree entry names are written in.
+///
+/// macOS hands out file names decomposed (NFD), so without this a "Résumé"
+/// created there and one created on Linux would be two different entries.
+fn nfc(path: &str) -> Cow<'_, str> {
+    if is_nfc(path) {
+        Cow::Borrowed(path)
+    } else {
+        Cow::Owned(path.nfc().collect())
+    }
+}
+
+/// Whether the tree entry `name` is `wanted` (an NFC name), also matching
+/// entries another git client wrote decomposed.
+fn entry_is(name: &str, wanted: &str) -> bool {
+    name == wanted || (!is_nfc(name) && nfc(name) == wanted)
+}
+
+pub(crate) fn current_timestamp() -> i64 {
     #[cfg(target_arch = "wasm32")]
     {
         (js_sys::Date::now() / 1000.0) as i64
@@ -0,0 +1,300 @@
+//! # S3-backed object store
+//!
+//! [`S3Store`] is an [`ObjectStore`] implementation that keeps Git objects and
+//! refs in an Amazon S3 (or S3-compatible) bucket, behind the `s3` feature.
+//!
+//! ## Layout
+//!
+//! ```text
+//! s3://<bucket>/
+//! ├── objects/<sha_hex>     # raw Git object bytes
+//! ├── refs/<ref_name>       # the SHA hex string
+//! └── packs/<name>          # whole packfiles, see S3Store::put_pack
+//! ```
+//!
+//! ## Ref locking
+//!
+//! S3 has no transactions, so two writers moving the same ref could overwrite
+//! each other. When a DynamoDB table is configured (see [`S3Store::with_lock_table`]),
+//! [`set_ref`](ObjectStore::set_ref) first takes a lease on the item
+//! `lock_id = "ref#<name>"` with a conditional put, writes the ref, and
+//! releases the lease. A lease left behind by a crashed writer expires after
+//! [`LOCK_LEASE_SECS`]. The table needs a string partition key `lock_id`.
+//!
+//! ## Error handling
+//!
+//! Like [`SqliteStore`](crate::SqliteStore), trait methods swallow errors: a
+//! failed read returns `None` and a failed write is dropped. A ref update that
+//! cannot get the lock within [`LOCK_ATTEMPTS`] tries is dropped as well.
+
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use aws_config::BehaviorVersion;
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_s3::config::{Credentials, Region};
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+
+use crate::objects::Sha;
+use crate::repo::ObjectStore;
+
+/// How long a ref lock is held before other writers may take it over.
+pub const LOCK_LEASE_SECS: u64 = 30;
+
+/// Attempts to take a ref lock before giving up on the ref update.
+pub const LOCK_ATTEMPTS: u32 = 20;
+
+/// Largest batch accepted by S3's `DeleteObjects`.
+const DELETE_BATCH: usize = 1000;
+
+/// S3-backed ObjectStore for server-side persistence.
+#[derive(Clone, Debug)]
+pub struct S3Store {
+    s3: aws_sdk_s3::Client,
+    bucket: String,
+    lock: Option<RefLock>,
+}
+
+/// DynamoDB table used to serialise ref updates.
+#[derive(Clone, Debug)]
+struct RefLock {
+    client: aws_sdk_dynamodb::Client,
+    table: String,
+}
+
+impl S3Store {
+    /// Use `bucket` through an already configured client.
+    pub fn new(s3: aws_sdk_s3::Client, bucket: impl Into<String>) -> Self {
+        Self {
+            s3,
+            bucket: bucket.into(),
+            lock: None,
+        }
+    }
+
+    /// Build a store from `AWS_BUCKET`, `AWS_REGION`, `AWS_ACCESS_KEY_ID` and
+    /// `AWS_SECRET_ACCESS_KEY`. `AWS_ENDPOINT_URL` points it at an
+    /// S3-compatible service, and `AWS_DYNAMODB_LOCK_TABLE` turns on ref
+    /// locking.
+    pub async fn from_env() -> Result<Self, String> {
+        let var = |name: &str| std::env::var(name).map_err(|_| format!("{name} not set"));
+        let bucket = var("AWS_BUCKET")?;
+        let credentials = Credentials::new(
+            var("AWS_ACCESS_KEY_ID")?,
+            var("AWS_SECRET_ACCESS_KEY")?,
+            std::env::var("AWS_SESSION_TOKEN").ok(),
+            None,
+            "typednotes-env",
+        );
+
+        let mut loader = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(var("AWS_REGION")?))
+            .credentials_provider(credentials);
+        if let Ok(endpoint) = std::env::var("AWS_ENDPOINT_URL") {
+            loader = loader.endpoint_url(endpoint);
+        }
+        let shared = loader.load().await;
+
+        // S3-compatible services generally want path-style URLs
+        let s3_config = aws_sdk_s3::config::Builder::from(&shared)
+            .force_path_style(std::env::var("AWS_ENDPOINT_URL").is_ok())
+            .build();
+        let store = Self::new(aws_sdk_s3::Client::from_conf(s3_config), bucket);
+
+        Ok(match std::env::var("AWS_DYNAMODB_LOCK_TABLE") {
+            Ok(table) => store.with_lock_table(aws_sdk_dynamodb::Client::new(&shared), table),
+            Err(_) => store,
+        })
+    }
+
+    /// Serialise ref updates through the DynamoDB table `table`.
+    pub fn with_lock_table(mut self, client: aws_sdk_dynamodb::Client, table: impl Into<String>) -> Self {
+        self.lock = Some(RefLock {
+            client,
+            table: table.into(),
+        });
+        self
+    }
+
+    /// The bucket this store writes to.
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// Check that the bucket exists and the credentials can reach it.
+    pub async fn check(&self) -> Result<(), String> {
+        self.s3
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("S3 bucket {}: {}", self.bucket, aws_sdk_s3::error::DisplayErrorContext(e)))
+    }
+
+    /// The packfile stored as `name` by [`put_pack`](Self::put_pack).
+    pub async fn get_pack(&self, name: &str) -> Option<Vec<u8>> {
+        self.get_key(&format!("packs/{name}")).await
+    }
+
+    /// Store a whole packfile as `name`, replacing any earlier one.
+    ///
+    /// Packs sit beside the loose objects rather than in them: they are
+    /// what the server keeps of a remote between requests (see
+    /// [`MemoryStore::add_pack`](crate::MemoryStore::add_pack)), not part of
+    /// this store's own object graph.
+    pub async fn put_pack(&self, name: &str, data: Vec<u8>) -> Result<(), String> {
+        if self.put_key(&format!("packs/{name}"), data).await {
+            Ok(())
+        } else {
+            Err(format!("S3 bucket {}: could not write pack {name}", self.bucket))
+        }
+    }
+
+    async fn get_key(&self, key: &str) -> Option<Vec<u8>> {
+        let output = self.s3.get_object().bucket(&self.bucket).key(key).send().await.ok()?;
+        let body = output.body.collect().await.ok()?;
+        Some(body.into_bytes().to_vec())
+    }
+
+    async fn put_key(&self, key: &str, data: Vec<u8>) -> bool {
+        self.s3
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .is_ok()
+    }
+
+    /// Every key under `prefix`, or `None` if listing fails part-way.
+    async fn list_keys(&self, prefix: &str) -> Option<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut pages = self
+            .s3
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+        while let Some(page) = pages.next().await {
+            let page = page.ok()?;
+            keys.extend(page.contents().iter().filter_map(|o| o.key().map(str::to_string)));
+        }
+        Some(keys)
+    }
+}
+
+impl RefLock {
+    fn item_key(name: &str) -> AttributeValue {
+        AttributeValue::S(format!("ref#{name}"))
+    }
+
+    /// Take the lease on `name`, retrying while another writer holds it.
+    async fn acquire(&self, name: &str) -> bool {
+        for attempt in 0..LOCK_ATTEMPTS {
+            let now = unix_now();
+            let taken = self
+                .client
+                .put_item()
+                .table_name(&self.table)
+                .item("lock_id", Self::item_key(name))
+                .item("expires_at", AttributeValue::N((now + LOCK_LEASE_SECS).to_string()))
+                .condition_expression("attribute_not_exists(lock_id) OR expires_at < :now")
+                .expression_attribute_values(":now", AttributeValue::N(now.to_string()))
+                .send()
+                .await;
+            if taken.is_ok() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(50 * u64::from(attempt + 1))).await;
+        }
+        false
+    }
+
+    async fn release(&self, name: &str) {
+        let _ = self
+            .client
+            .delete_item()
+            .table_name(&self.table)
+            .key("lock_id", Self::item_key(name))
+            .send()
+            .await;
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+impl ObjectStore for S3Store {
+    async fn get(&self, sha: &Sha) -> Option<Vec<u8>> {
+        self.get_key(&format!("objects/{}", sha.to_hex())).await
+    }
+
+    async fn put(&self, sha: &Sha, data: Vec<u8>) {
+        self.put_key(&format!("objects/{}", sha.to_hex()), data).await;
+    }
+
+    async fn get_ref(&self, name: &str) -> Option<Sha> {
+        let data = self.get_key(&format!("refs/{name}")).await?;
+        Sha::from_hex(String::from_utf8(data).ok()?.trim())
+    }
+
+    async fn set_ref(&self, name: &str, sha: &Sha) {
+        let key = format!("refs/{name}");
+        let Some(lock) = &self.lock else {
+            self.put_key(&key, sha.to_hex().into_bytes()).await;
+            return;
+        };
+        if lock.acquire(name).await {
+            self.put_key(&key, sha.to_hex().into_bytes()).await;
+            lock.release(name).await;
+        }
+    }
+
+    async fn list_refs(&self) -> Option<Vec<(String, Sha)>> {
+        let mut refs = Vec::new();
+        for key in self.list_keys("refs/").await? {
+            let name = key.trim_start_matches("refs/");
+            if let Some(sha) = self.get_ref(name).await {
+                refs.push((name.to_string(), sha));
+            }
+        }
+        Some(refs)
+    }
+
+    async fn object_count(&self) -> Option<usize> {
+        self.list_keys("objects/").await.map(|keys| keys.len())
+    }
+
+    async fn retain_objects(&self, keep: &HashSet<Sha>) -> usize {
+        let Some(keys) = self.list_keys("objects/").await else {
+            return 0;
+        };
+        let doomed: Vec<String> = keys
+            .into_iter()
+            .filter(|key| {
+                let hex = key.trim_start_matches("objects/");
+                !Sha::from_hex(hex).is_some_and(|sha| keep.contains(&sha))
+            })
+            .collect();
+
+        let mut removed = 0;
+        for batch in doomed.chunks(DELETE_BATCH) {
+            let objects: Vec<ObjectIdentifier> = batch
+                .iter()
+                .filter_map(|key| ObjectIdentifier::builder().key(key).build().ok())
+                .collect();
+            let Ok(delete) = Delete::builder().set_objects(Some(objects)).quiet(true).build() else {
+                continue;
+            };
+            if let Ok(output) = self.s3.delete_objects().bucket(&self.bucket).delete(delete).send().await {
+                removed += batch.len() - output.errors().len();
+            }
+        }
+        removed
+    }
+}
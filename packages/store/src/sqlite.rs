@@ -0,0 +1,640 @@
+//! # SQLite-backed object store
+//!
+//! [`SqliteStore`] is an [`ObjectStore`] implementation that keeps every Git
+//! object and ref in a single SQLite database file. It is used by the desktop
+//! app (behind the `sqlite` feature) instead of one file per object.
+//!
+//! ## Schema
+//!
+//! ```sql
+//! CREATE TABLE IF NOT EXISTS objects (sha BLOB PRIMARY KEY, data BLOB NOT NULL);
+//! CREATE TABLE IF NOT EXISTS refs (name TEXT PRIMARY KEY, sha BLOB NOT NULL);
+//! CREATE TABLE IF NOT EXISTS packs (name TEXT PRIMARY KEY, data BLOB NOT NULL);
+//! CREATE TABLE IF NOT EXISTS packed (sha BLOB PRIMARY KEY, pack TEXT NOT NULL, start INTEGER NOT NULL, len INTEGER NOT NULL);
+//! CREATE TABLE IF NOT EXISTS object_ids (sha BLOB PRIMARY KEY, id BLOB NOT NULL);
+//! CREATE TABLE IF NOT EXISTS settings (name TEXT PRIMARY KEY, value TEXT NOT NULL);
+//! ```
+//!
+//! SHAs are stored as their raw 20 bytes.
+//!
+//! ## Hash algorithm
+//!
+//! `objects` and `packed` are keyed by SHA-1 until
+//! [`ObjectStore::set_hash_algorithm`] switches the store to
+//! [`HashAlgorithm::Sha256`] (kept in `settings`). From then on they are keyed
+//! by the raw 32-byte SHA-256 of each object, and `object_ids` maps every
+//! SHA-1 name to it; lookups go through `object_ids` first, so they work under
+//! either key.
+//!
+//! ## Packs
+//!
+//! Like [`MemoryStore::compact`](crate::MemoryStore::compact),
+//! [`SqliteStore::compact`] folds every object into one [pack](crate::pack),
+//! stored as a single BLOB row of `packs` under [`DEFAULT_PACK`], and deletes
+//! the `objects` rows. `packed` is its index: where each object's entry lies
+//! in the BLOB. Entries are never deltas, so a read takes just that slice of
+//! the BLOB. Reads check `objects` first; writes always go there.
+//!
+//! ## Connection management
+//!
+//! [`SqliteStore::open`] is cheap and synchronous: the connection pool is created
+//! lazily on first use and shared by every store opened on the same path, so the
+//! per-call `make_repo` pattern does not reconnect each time.
+//!
+//! ## Legacy import
+//!
+//! When the database file is created next to an existing [`FileStore`](crate::FileStore)
+//! layout (`objects/` and `refs/` directories), their contents are copied in, so
+//! switching backends keeps the user's notes. The old files are left in place.
+//!
+//! ## Error handling
+//!
+//! Like [`IdbStore`](crate::IdbStore), trait methods swallow errors: a failed
+//! read returns `None` and a failed write is dropped.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use tokio::sync::OnceCell;
+
+use crate::memory::DEFAULT_PACK;
+use crate::objects::{HashAlgorithm, ObjectId, Sha};
+use crate::pack::{index_pack, read_pack, unpack_pack_entry, PackBuilder};
+use crate::repo::ObjectStore;
+
+/// Pools shared by all stores opened on the same database path.
+static POOLS: LazyLock<Mutex<HashMap<PathBuf, Arc<OnceCell<SqlitePool>>>>> =
+    LazyLock::new(Default::default);
+
+/// SQLite-backed ObjectStore for desktop persistence.
+#[derive(Clone, Debug)]
+pub struct SqliteStore {
+    path: PathBuf,
+    pool: Arc<OnceCell<SqlitePool>>,
+}
+
+impl SqliteStore {
+    /// Open (or, on first use, create) the database at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let pool = POOLS.lock().unwrap().entry(path.clone()).or_default().clone();
+        Self { path, pool }
+    }
+
+    /// Close the shared pool for `path` (if open) and delete the database file.
+    pub async fn delete(path: &Path) {
+        Self::close(path).await;
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.as_os_str().to_owned();
+            file.push(suffix);
+            let _ = std::fs::remove_file(PathBuf::from(file));
+        }
+    }
+
+    /// Close the shared pool for `path`, e.g. before moving the file.
+    pub async fn close(path: &Path) {
+        let cell = POOLS.lock().unwrap().remove(path);
+        if let Some(pool) = cell.as_ref().and_then(|cell| cell.get()) {
+            pool.close().await;
+        }
+    }
+
+    async fn pool(&self) -> Option<&SqlitePool> {
+        self.pool.get_or_try_init(|| self.connect()).await.ok()
+    }
+
+    async fn connect(&self) -> Result<SqlitePool, sqlx::Error> {
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let is_new = !self.path.exists();
+        let options = SqliteConnectOptions::new()
+            .filename(&self.path)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+
+        sqlx::query("CREATE TABLE IF NOT EXISTS objects (sha BLOB PRIMARY KEY, data BLOB NOT NULL)")
+            .execute(&pool)
+            .await?;
+        sqlx::query("CREATE TABLE IF NOT EXISTS refs (name TEXT PRIMARY KEY, sha BLOB NOT NULL)")
+            .execute(&pool)
+            .await?;
+        sqlx::query("CREATE TABLE IF NOT EXISTS packs (name TEXT PRIMARY KEY, data BLOB NOT NULL)")
+            .execute(&pool)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS packed \
+             (sha BLOB PRIMARY KEY, pack TEXT NOT NULL, start INTEGER NOT NULL, len INTEGER NOT NULL)",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query("CREATE TABLE IF NOT EXISTS object_ids (sha BLOB PRIMARY KEY, id BLOB NOT NULL)")
+            .execute(&pool)
+            .await?;
+        sqlx::query("CREATE TABLE IF NOT EXISTS settings (name TEXT PRIMARY KEY, value TEXT NOT NULL)")
+            .execute(&pool)
+            .await?;
+
+        if is_new {
+            if let Some(dir) = self.path.parent() {
+                import_file_store(&pool, dir).await;
+            }
+        }
+        Ok(pool)
+    }
+
+    /// Fold every object into one pack stored under [`DEFAULT_PACK`],
+    /// replacing the one before, and delete the loose rows. Returns the
+    /// number of objects packed (0 if packing failed, leaving the database as
+    /// it was).
+    pub async fn compact(&self) -> usize {
+        let Some(pool) = self.pool().await else {
+            return 0;
+        };
+        compact(pool).await.unwrap_or(0)
+    }
+}
+
+async fn compact(pool: &SqlitePool) -> Result<usize, String> {
+    let loose: Vec<(Vec<u8>, Vec<u8>)> = sqlx::query_as("SELECT sha, data FROM objects")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let old_pack: Option<(Vec<u8>,)> = sqlx::query_as("SELECT data FROM packs WHERE name = ?")
+        .bind(DEFAULT_PACK)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let live: HashSet<Vec<u8>> = sqlx::query_as::<_, (Vec<u8>,)>("SELECT sha FROM packed")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(sha,)| sha)
+        .collect();
+    // SHA-1 → key, for a store keyed by SHA-256
+    let ids: HashMap<Vec<u8>, Vec<u8>> = sqlx::query_as("SELECT sha, id FROM object_ids")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+    let key_of = |sha: &Sha| ids.get(&sha.0[..]).cloned().unwrap_or_else(|| sha.0.to_vec());
+
+    // Loose objects, then packed ones still indexed (`retain_objects` drops
+    // entries from the index only)
+    let mut builder = PackBuilder::new();
+    let mut seen = HashSet::new();
+    for (key, data) in &loose {
+        if seen.insert(key.clone()) {
+            builder.add(data)?;
+        }
+    }
+    if let Some((old_pack,)) = old_pack {
+        for (sha, raw) in read_pack(&old_pack)? {
+            let key = key_of(&sha);
+            if live.contains(&key) && seen.insert(key) {
+                builder.add(&raw)?;
+            }
+        }
+    }
+    let data = builder.finish();
+    let spans = entry_spans(&data)?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM packed").execute(&mut *tx).await.map_err(|e| e.to_string())?;
+    for (sha, (start, len)) in &spans {
+        sqlx::query("INSERT INTO packed (sha, pack, start, len) VALUES (?, ?, ?, ?)")
+            .bind(key_of(sha))
+            .bind(DEFAULT_PACK)
+            .bind(*start as i64)
+            .bind(*len as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    sqlx::query("INSERT OR REPLACE INTO packs (name, data) VALUES (?, ?)")
+        .bind(DEFAULT_PACK)
+        .bind(data)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Only the rows packed above: objects written meanwhile stay loose
+    for (key, _) in loose {
+        sqlx::query("DELETE FROM objects WHERE sha = ?")
+            .bind(key)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(spans.len())
+}
+
+/// SHA-1 of every object in `pack` → `(start, len)` of its entry.
+fn entry_spans(pack: &[u8]) -> Result<HashMap<Sha, (usize, usize)>, String> {
+    let index = index_pack(pack)?;
+    let mut starts: Vec<usize> = index.values().copied().collect();
+    starts.sort_unstable();
+    // The last entry ends where the 20-byte checksum starts
+    let end_of = |start: usize| {
+        let next = starts.partition_point(|&s| s <= start);
+        starts.get(next).copied().unwrap_or(pack.len() - 20)
+    };
+    Ok(index
+        .into_iter()
+        .map(|(sha, start)| (sha, (start, end_of(start) - start)))
+        .collect())
+}
+
+/// Copy the objects and refs of a [`FileStore`](crate::FileStore) layout in `dir`.
+async fn import_file_store(pool: &SqlitePool, dir: &Path) {
+    if let Ok(entries) = std::fs::read_dir(dir.join("objects")) {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(sha) = name.to_str().and_then(Sha::from_hex) else {
+                continue;
+            };
+            if let Ok(data) = std::fs::read(entry.path()) {
+                let _ = put_object(pool, &sha, data).await;
+            }
+        }
+    }
+    if let Ok(entries) = std::fs::read_dir(dir.join("refs")) {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let (Some(name), Ok(hex)) = (name.to_str(), std::fs::read_to_string(entry.path())) else {
+                continue;
+            };
+            if let Some(sha) = Sha::from_hex(hex.trim()) {
+                let _ = put_ref(pool, name, &sha).await;
+            }
+        }
+    }
+}
+
+async fn put_object(pool: &SqlitePool, sha: &Sha, data: Vec<u8>) -> Result<(), sqlx::Error> {
+    let key = match hash_algorithm(pool).await? {
+        HashAlgorithm::Sha1 => sha.0.to_vec(),
+        HashAlgorithm::Sha256 => {
+            let key = object_key(HashAlgorithm::Sha256, &data);
+            sqlx::query("INSERT OR IGNORE INTO object_ids (sha, id) VALUES (?, ?)")
+                .bind(sha.0.to_vec())
+                .bind(key.clone())
+                .execute(pool)
+                .await?;
+            key
+        }
+    };
+    sqlx::query("INSERT OR IGNORE INTO objects (sha, data) VALUES (?, ?)")
+        .bind(key)
+        .bind(data)
+        .execute(pool)
+        .await
+        .map(|_| ())
+}
+
+/// The algorithm `objects` and `packed` are keyed by.
+async fn hash_algorithm(pool: &SqlitePool) -> Result<HashAlgorithm, sqlx::Error> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE name = 'hash_algorithm'")
+        .fetch_optional(pool)
+        .await?;
+    Ok(match row {
+        Some((value,)) if value == "sha256" => HashAlgorithm::Sha256,
+        _ => HashAlgorithm::Sha1,
+    })
+}
+
+/// Raw key of the serialised object `raw` under `algorithm`.
+fn object_key(algorithm: HashAlgorithm, raw: &[u8]) -> Vec<u8> {
+    match algorithm.object_id(raw) {
+        ObjectId::Sha1(sha) => sha.0.to_vec(),
+        ObjectId::Sha256(hash) => hash.0.to_vec(),
+    }
+}
+
+/// Re-key `objects` and `packed` by `algorithm` in one transaction.
+async fn set_hash_algorithm(pool: &SqlitePool, algorithm: HashAlgorithm) -> Result<(), sqlx::Error> {
+    if hash_algorithm(pool).await? == algorithm {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    match algorithm {
+        HashAlgorithm::Sha1 => {
+            for table in ["objects", "packed"] {
+                sqlx::query(&format!(
+                    "UPDATE {table} SET sha = (SELECT sha FROM object_ids WHERE id = {table}.sha) \
+                     WHERE sha IN (SELECT id FROM object_ids)"
+                ))
+                .execute(&mut *tx)
+                .await?;
+            }
+            sqlx::query("DELETE FROM object_ids").execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM settings WHERE name = 'hash_algorithm'")
+                .execute(&mut *tx)
+                .await?;
+        }
+        HashAlgorithm::Sha256 => {
+            let loose: Vec<(Vec<u8>, Vec<u8>)> = sqlx::query_as("SELECT sha, data FROM objects")
+                .fetch_all(&mut *tx)
+                .await?;
+            let packed: Vec<(Vec<u8>, Vec<u8>)> = sqlx::query_as(
+                "SELECT packed.sha, substr(packs.data, packed.start + 1, packed.len) FROM packed \
+                 JOIN packs ON packs.name = packed.pack",
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            let loose = loose.into_iter().map(|(sha, data)| ("objects", sha, Some(data)));
+            let packed = packed
+                .into_iter()
+                .map(|(sha, entry)| ("packed", sha, unpack_pack_entry(&entry, 0)));
+            for (table, sha, raw) in loose.chain(packed) {
+                let Some(raw) = raw else {
+                    continue;
+                };
+                let key = object_key(HashAlgorithm::Sha256, &raw);
+                sqlx::query("INSERT OR IGNORE INTO object_ids (sha, id) VALUES (?, ?)")
+                    .bind(sha.clone())
+                    .bind(key.clone())
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(&format!("UPDATE {table} SET sha = ? WHERE sha = ?"))
+                    .bind(key)
+                    .bind(sha)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            sqlx::query("INSERT OR REPLACE INTO settings (name, value) VALUES ('hash_algorithm', 'sha256')")
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await
+}
+
+async fn put_ref(pool: &SqlitePool, name: &str, sha: &Sha) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO refs (name, sha) VALUES (?, ?) ON CONFLICT(name) DO UPDATE SET sha = excluded.sha")
+        .bind(name.to_string())
+        .bind(sha.0.to_vec())
+        .execute(pool)
+        .await
+        .map(|_| ())
+}
+
+/// SQL for the key of the object whose SHA-1 is bound as `?1`.
+const KEY_OF_SHA: &str = "coalesce((SELECT id FROM object_ids WHERE sha = ?1), ?1)";
+
+/// Build a [`Sha`] from a raw 20-byte column value.
+fn sha_from_bytes(bytes: &[u8]) -> Option<Sha> {
+    Some(Sha(bytes.try_into().ok()?))
+}
+
+impl ObjectStore for SqliteStore {
+    async fn get(&self, sha: &Sha) -> Option<Vec<u8>> {
+        let pool = self.pool().await?;
+        let row: Option<(Vec<u8>,)> = sqlx::query_as(&format!("SELECT data FROM objects WHERE sha = {KEY_OF_SHA}"))
+            .bind(sha.0.to_vec())
+            .fetch_optional(pool)
+            .await
+            .ok()?;
+        if let Some((data,)) = row {
+            return Some(data);
+        }
+        // SQLite's substr works on bytes for BLOBs and is 1-based
+        let (entry,): (Vec<u8>,) = sqlx::query_as(&format!(
+            "SELECT substr(packs.data, packed.start + 1, packed.len) FROM packed \
+             JOIN packs ON packs.name = packed.pack WHERE packed.sha = {KEY_OF_SHA}"
+        ))
+        .bind(sha.0.to_vec())
+        .fetch_optional(pool)
+        .await
+        .ok()??;
+        unpack_pack_entry(&entry, 0)
+    }
+
+    async fn put(&self, sha: &Sha, data: Vec<u8>) {
+        if let Some(pool) = self.pool().await {
+            let _ = put_object(pool, sha, data).await;
+        }
+    }
+
+    async fn get_ref(&self, name: &str) -> Option<Sha> {
+        let pool = self.pool().await?;
+        let row: Option<(Vec<u8>,)> = sqlx::query_as("SELECT sha FROM refs WHERE name = ?")
+            .bind(name.to_string())
+            .fetch_optional(pool)
+            .await
+            .ok()?;
+        sha_from_bytes(&row?.0)
+    }
+
+    async fn set_ref(&self, name: &str, sha: &Sha) {
+        if let Some(pool) = self.pool().await {
+            let _ = put_ref(pool, name, sha).await;
+        }
+    }
+
+    async fn list_refs(&self) -> Option<Vec<(String, Sha)>> {
+        let pool = self.pool().await?;
+        let rows: Vec<(String, Vec<u8>)> = sqlx::query_as("SELECT name, sha FROM refs")
+            .fetch_all(pool)
+            .await
+            .ok()?;
+        Some(
+            rows.into_iter()
+                .filter_map(|(name, raw)| Some((name, sha_from_bytes(&raw)?)))
+                .collect(),
+        )
+    }
+
+    async fn object_count(&self) -> Option<usize> {
+        let pool = self.pool().await?;
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM objects) \
+             + (SELECT COUNT(*) FROM packed WHERE sha NOT IN (SELECT sha FROM objects))",
+        )
+        .fetch_one(pool)
+        .await
+        .ok()?;
+        usize::try_from(count).ok()
+    }
+
+    async fn retain_objects(&self, keep: &HashSet<Sha>) -> usize {
+        let Some(pool) = self.pool().await else {
+            return 0;
+        };
+        // Packed objects leave the index only; their bytes stay in the pack
+        // BLOB until the next `compact`
+        let Ok(rows) =
+            sqlx::query_as::<_, (Vec<u8>,)>("SELECT sha FROM objects UNION SELECT sha FROM packed")
+                .fetch_all(pool)
+                .await
+        else {
+            return 0;
+        };
+
+        // Key → SHA-1, for a store keyed by SHA-256
+        let Ok(names) = sqlx::query_as::<_, (Vec<u8>, Vec<u8>)>("SELECT id, sha FROM object_ids")
+            .fetch_all(pool)
+            .await
+        else {
+            return 0;
+        };
+        let names: HashMap<Vec<u8>, Vec<u8>> = names.into_iter().collect();
+
+        let mut removed = 0;
+        for (key,) in rows {
+            let name = names.get(&key).unwrap_or(&key);
+            if sha_from_bytes(name).is_some_and(|sha| keep.contains(&sha)) {
+                continue;
+            }
+            let loose = sqlx::query("DELETE FROM objects WHERE sha = ?")
+                .bind(key.clone())
+                .execute(pool)
+                .await;
+            let packed = sqlx::query("DELETE FROM packed WHERE sha = ?")
+                .bind(key.clone())
+                .execute(pool)
+                .await;
+            let id = sqlx::query("DELETE FROM object_ids WHERE id = ?")
+                .bind(key)
+                .execute(pool)
+                .await;
+            if loose.is_ok() && packed.is_ok() && id.is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    async fn set_hash_algorithm(&self, algorithm: HashAlgorithm) -> bool {
+        let Some(pool) = self.pool().await else {
+            return false;
+        };
+        set_hash_algorithm(pool, algorithm).await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::Repository;
+
+    #[tokio::test]
+    async fn test_sqlite_store_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("typednotes_sqlite_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let db = dir.join("notes.db");
+
+        let repo = Repository::new(SqliteStore::open(&db));
+        repo.write_note("hello", "Hello from SQLite!", "markdown").await;
+
+        // Re-open after closing the shared pool
+        SqliteStore::close(&db).await;
+        let repo2 = Repository::new(SqliteStore::open(&db));
+        let notes = repo2.list_notes().await;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].note, "Hello from SQLite!");
+
+        SqliteStore::delete(&db).await;
+        assert!(!db.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_compact() {
+        let dir = std::env::temp_dir().join(format!("typednotes_sqlite_compact_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let db = dir.join("notes.db");
+
+        let store = SqliteStore::open(&db);
+        let repo = Repository::new(store.clone());
+        repo.write_note("hello", "Hello", "markdown").await;
+        repo.write_note("work/plan", "Plan", "markdown").await;
+        let count = store.object_count().await.unwrap();
+
+        assert_eq!(store.compact().await, count);
+        let pool = store.pool().await.unwrap();
+        let (loose,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM objects").fetch_one(pool).await.unwrap();
+        assert_eq!(loose, 0);
+        assert_eq!(store.object_count().await, Some(count));
+        assert_eq!(repo.get_note("work/plan.md").await.unwrap().note, "Plan");
+
+        // New writes stay loose and are folded in by the next compaction
+        repo.write_note("hello", "Hello again", "markdown").await;
+        assert!(store.compact().await > count);
+        assert_eq!(repo.get_note("hello.md").await.unwrap().note, "Hello again");
+        assert_eq!(repo.list_notes().await.len(), 2);
+
+        SqliteStore::delete(&db).await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_sha256_keys() {
+        let dir = std::env::temp_dir().join(format!("typednotes_sqlite_sha256_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let db = dir.join("notes.db");
+
+        let store = SqliteStore::open(&db);
+        let repo = Repository::new(store.clone());
+        repo.write_note("hello", "Hello", "markdown").await;
+        store.compact().await;
+        repo.write_note("work/plan", "Plan", "markdown").await;
+        let count = store.object_count().await.unwrap();
+
+        assert!(store.set_hash_algorithm(HashAlgorithm::Sha256).await);
+        let pool = store.pool().await.unwrap();
+        let (short,): (i64,) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM objects WHERE length(sha) <> 32) \
+             + (SELECT COUNT(*) FROM packed WHERE length(sha) <> 32)",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        assert_eq!(short, 0);
+        assert_eq!(store.object_count().await, Some(count));
+        assert_eq!(repo.get_note("hello.md").await.unwrap().note, "Hello");
+
+        // Writes, compaction and gc keep working under the new keys
+        repo.write_note("hello", "Hello again", "markdown").await;
+        store.compact().await;
+        assert_eq!(repo.get_note("hello.md").await.unwrap().note, "Hello again");
+        let mut keep = repo.reachable_objects().await;
+        assert_eq!(store.retain_objects(&keep).await, 0);
+        let (old_blob, _) = crate::objects::hash_blob(&crate::objects::Blob {
+            content: b"Hello".to_vec(),
+        });
+        assert!(keep.remove(&old_blob));
+        assert_eq!(store.retain_objects(&keep).await, 1);
+        assert_eq!(repo.get_note("work/plan.md").await.unwrap().note, "Plan");
+
+        assert!(store.set_hash_algorithm(HashAlgorithm::Sha1).await);
+        let (ids,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM object_ids").fetch_one(pool).await.unwrap();
+        assert_eq!(ids, 0);
+        assert_eq!(store.object_count().await, Some(keep.len()));
+        assert_eq!(repo.list_notes().await.len(), 2);
+
+        SqliteStore::delete(&db).await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_imports_file_store() {
+        let dir = std::env::temp_dir().join(format!("typednotes_sqlite_import_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        Repository::new(crate::FileStore::new(dir.clone()))
+            .write_note("work/plan", "From files", "markdown")
+            .await;
+
+        let db = dir.join("notes.db");
+        let repo = Repository::new(SqliteStore::open(&db));
+        assert_eq!(repo.get_note("work/plan.md").await.unwrap().note, "From files");
+
+        SqliteStore::delete(&db).await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
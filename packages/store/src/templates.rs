@@ -0,0 +1,85 @@
+//! # Note templates
+//!
+//! Templates are ordinary notes kept in the reserved [`TEMPLATES_NAMESPACE`]
+//! (`.templates/`) and listed with
+//! [`Repository::list_templates`](crate::Repository::list_templates). Creating
+//! a note from one copies its content through [`render_template`], which fills
+//! in these placeholders:
+//!
+//! | Placeholder | Replaced with |
+//! |-------------|---------------|
+//! | `{{date}}` | Today's date, `YYYY-MM-DD` (UTC; see [`today`]). |
+//! | `{{title}}` | The new note's name. |
+//! | `{{author}}` | The creating user's display name. |
+//!
+//! Any other `{{…}}` is left as written.
+
+use crate::repo::current_timestamp;
+
+/// Namespace holding template notes.
+pub const TEMPLATES_NAMESPACE: &str = ".templates";
+
+/// `template` with every placeholder replaced, in a single pass (so a title
+/// containing `{{date}}` is not expanded again).
+pub fn render_template(template: &str, title: &str, author: &str, date: &str) -> String {
+    let placeholders = [("{{date}}", date), ("{{title}}", title), ("{{author}}", author)];
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let (value, len) = placeholders
+            .iter()
+            .find(|(placeholder, _)| tail.starts_with(placeholder))
+            .map_or(("{{", 2), |(placeholder, value)| (*value, placeholder.len()));
+        out.push_str(value);
+        rest = &tail[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The UTC calendar date of a Unix timestamp, as `YYYY-MM-DD`.
+pub fn iso_date(timestamp: i64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms
+    let z = timestamp.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Today's UTC date, as `YYYY-MM-DD`.
+pub fn today() -> String {
+    iso_date(current_timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let template = "# {{title}}\n\nBy {{author}} on {{date}}. {{unknown}} {{";
+        assert_eq!(
+            render_template(template, "Standup", "Ada", "2024-03-01"),
+            "# Standup\n\nBy Ada on 2024-03-01. {{unknown}} {{"
+        );
+        // Substituted values are not expanded again
+        assert_eq!(render_template("{{title}}", "{{date}}", "", "2024-03-01"), "{{date}}");
+    }
+
+    #[test]
+    fn test_iso_date() {
+        assert_eq!(iso_date(0), "1970-01-01");
+        assert_eq!(iso_date(951_782_400), "2000-02-29");
+        assert_eq!(iso_date(1_700_000_000), "2023-11-14");
+        assert_eq!(iso_date(-86_400), "1969-12-31");
+        assert_eq!(today().len(), 10);
+    }
+}
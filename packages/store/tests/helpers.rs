@@ -0,0 +1,44 @@
+//! Shared setup for the integration tests; include with `mod helpers;`.
+//!
+//! [`mock_fetch`] and [`mock_push`] stand in for `api::git_transport`'s fetch
+//! and push, moving objects between two [`MemoryStore`]s instead of talking to
+//! a git remote over SSH.
+
+#![allow(dead_code)]
+
+use store::{MemoryStore, Repository, Sha};
+
+/// A repository with one commit per `(path, content)` note, in order.
+/// Paths carry their extension (`"work/plan.md"`).
+pub async fn make_test_repo_with_notes(notes: &[(&str, &str)]) -> (MemoryStore, Repository<MemoryStore>) {
+    let store = MemoryStore::new();
+    let repo = Repository::new(store.clone());
+    for (path, content) in notes {
+        let note_type = if path.ends_with(".txt") { "text" } else { "markdown" };
+        repo.write_note(path, content, note_type).await;
+    }
+    (store, repo)
+}
+
+/// Copy every object and ref of `remote` into `local`, as a full fetch would.
+pub fn mock_fetch(remote: &MemoryStore, local: &MemoryStore) {
+    let snapshot = remote.clone_deep();
+    for hex in snapshot.all_object_shas() {
+        let sha = Sha::from_hex(&hex).unwrap();
+        local.put_sync(&sha, snapshot.get_sync(&sha).unwrap());
+    }
+    for (name, sha) in snapshot.all_refs() {
+        local.set_ref_sync(&name, &sha);
+    }
+}
+
+/// Send `new_object_shas` from `local` to `remote` and move the remote's
+/// `HEAD` to the local one, as a push of the branch would.
+pub fn mock_push(local: &MemoryStore, remote: &MemoryStore, new_object_shas: &[String]) {
+    for hex in new_object_shas {
+        let sha = Sha::from_hex(hex).unwrap();
+        remote.put_sync(&sha, local.get_sync(&sha).expect("pushed object is in the local store"));
+    }
+    let head = local.get_ref_sync("HEAD").expect("nothing to push");
+    remote.set_ref_sync("HEAD", &head);
+}
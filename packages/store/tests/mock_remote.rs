@@ -0,0 +1,74 @@
+//! The fetch → write → push cycle of the `sync_note` server function, run
+//! against an in-memory "remote" instead of a git server.
+
+mod helpers;
+
+use std::collections::HashSet;
+
+use helpers::{make_test_repo_with_notes, mock_fetch, mock_push};
+use store::{MemoryStore, Repository};
+
+/// SHAs in `store` that are not in the `before` snapshot: what a push sends.
+fn new_objects(store: &MemoryStore, before: &HashSet<String>) -> Vec<String> {
+    store
+        .all_object_shas()
+        .into_iter()
+        .filter(|sha| !before.contains(sha))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_clone_deep_is_independent() {
+    let (store, repo) = make_test_repo_with_notes(&[("a.md", "alpha")]).await;
+    let copy = store.clone_deep();
+    let shallow = store.clone();
+
+    repo.write_note("b.md", "beta", "markdown").await;
+
+    assert_eq!(Repository::new(copy.clone()).list_notes().await.len(), 1);
+    assert_eq!(Repository::new(shallow).list_notes().await.len(), 2);
+    assert_ne!(copy.get_ref_sync("HEAD"), store.get_ref_sync("HEAD"));
+    assert!(copy.all_object_shas().len() < store.all_object_shas().len());
+}
+
+#[tokio::test]
+async fn test_sync_pushes_only_new_objects() {
+    let (remote, remote_repo) = make_test_repo_with_notes(&[("work/plan.md", "v1"), ("todo.txt", "milk")]).await;
+
+    let local = MemoryStore::new();
+    let local_repo = Repository::new(local.clone());
+    mock_fetch(&remote, &local);
+
+    let before: HashSet<String> = local.all_object_shas().into_iter().collect();
+    local_repo.write_note("work/plan.md", "v2", "markdown").await;
+    let pushed = new_objects(&local, &before);
+
+    // New blob, two trees (root and work/) and the commit
+    assert_eq!(pushed.len(), 4);
+    assert!(pushed.iter().all(|sha| remote.get_sync(&store::Sha::from_hex(sha).unwrap()).is_none()));
+
+    mock_push(&local, &remote, &pushed);
+
+    assert_eq!(remote_repo.get_note("work/plan.md").await.unwrap().note, "v2");
+    assert_eq!(remote_repo.get_note("todo.txt").await.unwrap().note, "milk");
+    // Everything the new HEAD needs made it across
+    for sha in remote_repo.reachable_objects().await {
+        assert!(remote.get_sync(&sha).is_some(), "remote is missing {sha}");
+    }
+}
+
+#[tokio::test]
+async fn test_delete_then_push() {
+    let (remote, remote_repo) = make_test_repo_with_notes(&[("a.md", "alpha"), ("b.md", "beta")]).await;
+
+    let local = MemoryStore::new();
+    let local_repo = Repository::new(local.clone());
+    mock_fetch(&remote, &local);
+
+    let before: HashSet<String> = local.all_object_shas().into_iter().collect();
+    local_repo.delete_note("a.md").await;
+    mock_push(&local, &remote, &new_objects(&local, &before));
+
+    let paths: Vec<String> = remote_repo.list_notes().await.into_iter().map(|n| n.path).collect();
+    assert_eq!(paths, vec!["b.md"]);
+}
@@ -0,0 +1,173 @@
+//! Randomized tests of [`Repository`] note operations.
+//!
+//! Each property runs against a [`MemoryStore`] and, with the `sqlite`
+//! feature, against a fresh [`SqliteStore`](store::SqliteStore) database per case.
+
+use std::collections::BTreeSet;
+use std::future::Future;
+
+use proptest::prelude::*;
+use store::models::note_type_from_ext;
+use store::{MemoryStore, ObjectStore, Repository};
+
+/// Note paths without their extension: 1-3 components from a safe alphabet,
+/// e.g. `"a/b/c"`.
+fn arb_path() -> impl Strategy<Value = String> {
+    prop::collection::vec("[a-z0-9_-]{1,8}", 1..=3).prop_map(|parts| parts.join("/"))
+}
+
+/// Any UTF-8 text up to 10KB.
+fn arb_content() -> impl Strategy<Value = String> {
+    prop::collection::vec(any::<char>(), 0..2_560).prop_map(|chars| {
+        let mut content: String = chars.into_iter().collect();
+        while content.len() > 10_240 {
+            content.pop();
+        }
+        content
+    })
+}
+
+/// A note's extension, `"md"` or `"txt"`.
+fn arb_note_type() -> impl Strategy<Value = &'static str> {
+    prop_oneof![Just("md"), Just("txt")]
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Write(String, &'static str),
+    /// Delete the n-th (modulo) note written so far.
+    Delete(usize),
+}
+
+fn arb_ops() -> impl Strategy<Value = Vec<Op>> {
+    let op = prop_oneof![
+        3 => (arb_path(), arb_note_type()).prop_map(|(path, ext)| Op::Write(path, ext)),
+        1 => any::<usize>().prop_map(Op::Delete),
+    ];
+    prop::collection::vec(op, 1..20)
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn with_memory<F: Future<Output = ()>>(check: impl FnOnce(Repository<MemoryStore>) -> F) {
+    block_on(check(Repository::new(MemoryStore::new())));
+}
+
+#[cfg(feature = "sqlite")]
+fn with_sqlite<F: Future<Output = ()>>(check: impl FnOnce(Repository<store::SqliteStore>) -> F) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static CASE: AtomicUsize = AtomicUsize::new(0);
+
+    let db = std::env::temp_dir().join(format!(
+        "typednotes_proptest_{}_{}.db",
+        std::process::id(),
+        CASE.fetch_add(1, Ordering::Relaxed)
+    ));
+    block_on(async {
+        store::SqliteStore::delete(&db).await;
+        check(Repository::new(store::SqliteStore::open(&db))).await;
+        store::SqliteStore::delete(&db).await;
+    });
+}
+
+/// Run a generic check against every store backend.
+macro_rules! for_each_store {
+    ($check:expr) => {
+        with_memory($check);
+        #[cfg(feature = "sqlite")]
+        with_sqlite($check);
+    };
+}
+
+/// Commits on the first-parent chain from `HEAD`.
+async fn commit_count<S: ObjectStore>(repo: &Repository<S>) -> usize {
+    let mut count = 0;
+    let mut next = repo.get_head().await;
+    while let Some(sha) = next {
+        count += 1;
+        next = repo.get_commit(&sha).await.and_then(|commit| commit.first_parent().cloned());
+    }
+    count
+}
+
+async fn check_roundtrip<S: ObjectStore>(repo: Repository<S>, path: &str, ext: &str, content: &str) {
+    repo.write_note(path, content, note_type_from_ext(ext)).await;
+    let full_path = format!("{path}.{ext}");
+    let note = repo.get_note(&full_path).await.expect("note written");
+    assert_eq!(note.note, content);
+    assert_eq!(note.path, full_path);
+    assert_eq!(note.r#type, note_type_from_ext(ext));
+}
+
+async fn check_commit_counts<S: ObjectStore>(repo: Repository<S>, writes: &[(String, &str, String)]) {
+    let mut last = commit_count(&repo).await;
+    for (path, ext, content) in writes {
+        repo.write_note(path, content, note_type_from_ext(ext)).await;
+        let count = commit_count(&repo).await;
+        assert!(count > last, "commit count went from {last} to {count}");
+        last = count;
+    }
+}
+
+async fn check_delete<S: ObjectStore>(repo: Repository<S>, path: &str, ext: &str, content: &str) {
+    repo.write_note(path, content, note_type_from_ext(ext)).await;
+    let full_path = format!("{path}.{ext}");
+    repo.delete_note(&full_path).await;
+    assert!(repo.get_note(&full_path).await.is_none());
+    assert!(repo.list_notes().await.iter().all(|note| note.path != full_path));
+}
+
+async fn check_list_notes<S: ObjectStore>(repo: Repository<S>, ops: &[Op]) {
+    let mut written: Vec<String> = Vec::new();
+    let mut expected = BTreeSet::new();
+    for op in ops {
+        match op {
+            Op::Write(path, ext) => {
+                repo.write_note(path, path, note_type_from_ext(ext)).await;
+                let full_path = format!("{path}.{ext}");
+                written.push(full_path.clone());
+                expected.insert(full_path);
+            }
+            Op::Delete(n) if !written.is_empty() => {
+                let full_path = &written[n % written.len()];
+                repo.delete_note(full_path).await;
+                expected.remove(full_path);
+            }
+            Op::Delete(_) => {}
+        }
+        let listed: BTreeSet<String> = repo.list_notes().await.into_iter().map(|note| note.path).collect();
+        assert_eq!(listed, expected);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn write_then_read(path in arb_path(), ext in arb_note_type(), content in arb_content()) {
+        for_each_store!(|repo| check_roundtrip(repo, &path, ext, &content));
+    }
+
+    #[test]
+    fn writes_increase_commit_count(
+        writes in prop::collection::vec((arb_path(), arb_note_type(), arb_content()), 1..8)
+    ) {
+        for_each_store!(|repo| check_commit_counts(repo, &writes));
+    }
+
+    #[test]
+    fn delete_after_write(path in arb_path(), ext in arb_note_type(), content in arb_content()) {
+        for_each_store!(|repo| check_delete(repo, &path, ext, &content));
+    }
+
+    #[test]
+    fn list_notes_tracks_writes_and_deletes(ops in arb_ops()) {
+        for_each_store!(|repo| check_list_notes(repo, &ops));
+    }
+}
@@ -0,0 +1,115 @@
+import { EditorView, GutterMarker, gutter } from "@codemirror/view";
+import { RangeSetBuilder } from "@codemirror/state";
+import { syntaxTree } from "@codemirror/language";
+
+/**
+ * Top-level markdown blocks (paragraphs, headings, whole lists, fenced code,
+ * tables, ...) as full-line `{ from, to }` ranges. Dragging works on these
+ * units, so a multi-line block always moves as one piece and nothing can be
+ * dropped inside it.
+ */
+function topLevelBlocks(state) {
+  const blocks = [];
+  for (let node = syntaxTree(state).topNode.firstChild; node; node = node.nextSibling) {
+    const from = state.doc.lineAt(node.from).from;
+    const to = state.doc.lineAt(Math.max(node.from, node.to - 1)).to;
+    if (blocks.length && from <= blocks[blocks.length - 1].to) continue;
+    blocks.push({ from, to });
+  }
+  return blocks;
+}
+
+/** Block being dragged, set on dragstart of a handle. */
+let dragSource = null;
+
+class DragHandleMarker extends GutterMarker {
+  constructor(block) {
+    super();
+    this.block = block;
+  }
+
+  eq(other) {
+    return this.block.from === other.block.from && this.block.to === other.block.to;
+  }
+
+  toDOM() {
+    const handle = document.createElement("span");
+    handle.className = "md-drag-handle";
+    handle.textContent = "⠿";
+    handle.draggable = true;
+    handle.title = "Drag to move block";
+    handle.addEventListener("dragstart", (e) => {
+      dragSource = this.block;
+      e.dataTransfer.effectAllowed = "move";
+      e.dataTransfer.setData("text/plain", "");
+    });
+    handle.addEventListener("dragend", () => {
+      dragSource = null;
+    });
+    return handle;
+  }
+}
+
+/**
+ * Move `src` before or after `target`, keeping a blank line between blocks.
+ */
+function moveBlock(view, blocks, src, target, after) {
+  if (src.from === target.from) return;
+  const { doc } = view.state;
+  const text = doc.sliceString(src.from, src.to);
+  const index = blocks.findIndex((b) => b.from === src.from);
+  const next = blocks[index + 1];
+  const prev = blocks[index - 1];
+  // Take the separating blank lines with the block
+  const del = next ? { from: src.from, to: next.from } : { from: prev ? prev.to : 0, to: src.to };
+  const at = after ? target.to : target.from;
+  if (at > del.from && at < del.to) return;
+
+  view.dispatch({
+    changes: [del, after ? { from: at, insert: "\n\n" + text } : { from: at, insert: text + "\n\n" }],
+    userEvent: "move.drop",
+    scrollIntoView: true,
+  });
+}
+
+/** Drag handles in a gutter, one per top-level block, plus the drop logic. */
+export const blockDrag = [
+  gutter({
+    class: "cm-md-drag-gutter",
+    markers(view) {
+      const builder = new RangeSetBuilder();
+      for (const block of topLevelBlocks(view.state)) {
+        builder.add(block.from, block.from, new DragHandleMarker(block));
+      }
+      return builder.finish();
+    },
+  }),
+  EditorView.domEventHandlers({
+    dragover(e) {
+      if (!dragSource) return false;
+      e.preventDefault();
+      e.dataTransfer.dropEffect = "move";
+      return true;
+    },
+    drop(e, view) {
+      if (!dragSource) return false;
+      e.preventDefault();
+      const src = dragSource;
+      dragSource = null;
+
+      const pos = view.posAtCoords({ x: e.clientX, y: e.clientY }, false);
+      const blocks = topLevelBlocks(view.state);
+      const target = blocks.find((b) => pos >= b.from && pos <= b.to)
+        ?? blocks.filter((b) => b.to < pos).pop()
+        ?? blocks[0];
+      if (!target) return true;
+
+      // Upper half of the target → before it, lower half → after it
+      const top = view.lineBlockAt(target.from).top;
+      const bottom = view.lineBlockAt(target.to).bottom;
+      const y = e.clientY - view.documentTop;
+      moveBlock(view, blocks, src, target, y > (top + bottom) / 2);
+      return true;
+    },
+  }),
+];
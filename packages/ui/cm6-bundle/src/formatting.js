@@ -0,0 +1,30 @@
+import { EditorSelection } from "@codemirror/state";
+
+/**
+ * Command that wraps every selection range in `marker` (e.g. `**`).
+ * An empty selection gets both markers with the cursor placed between them.
+ */
+function wrapSelection(marker) {
+  return (view) => {
+    const len = marker.length;
+    const tr = view.state.changeByRange((range) => ({
+      changes: [
+        { from: range.from, insert: marker },
+        { from: range.to, insert: marker },
+      ],
+      range: EditorSelection.range(range.anchor + len, range.head + len),
+    }));
+    view.dispatch(tr, { scrollIntoView: true, userEvent: "input.format" });
+    return true;
+  };
+}
+
+/**
+ * Inline formatting shortcuts. Must take precedence over the default keymap,
+ * which binds Mod-i to selectParentSyntax.
+ */
+export const formattingKeymap = [
+  { key: "Mod-b", run: wrapSelection("**") },
+  { key: "Mod-i", run: wrapSelection("*") },
+  { key: "Mod-`", run: wrapSelection("`") },
+];
@@ -26,7 +26,7 @@ export class HighlightWidget extends WidgetType {
     if (typeof hljs !== "undefined") {
       try {
         let result;
-        if (this.language) {
+        if (this.language && hljs.getLanguage(this.language)) {
           result = hljs.highlight(this.code, { language: this.language });
         } else {
           result = hljs.highlightAuto(this.code);
@@ -42,6 +42,11 @@ export class HighlightWidget extends WidgetType {
     if (this.isBlock) {
       const pre = document.createElement("pre");
       pre.className = "cm-hljs-widget cm-hljs-block";
+      if (this.language) {
+        // Fence info string (```rust → rust), as hljs and CSS expect it
+        pre.dataset.lang = this.language;
+        codeEl.className = `language-${this.language}`;
+      }
       pre.appendChild(codeEl);
       return pre;
     } else {
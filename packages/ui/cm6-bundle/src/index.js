@@ -1,6 +1,6 @@
-import { EditorState } from "@codemirror/state";
+import { Compartment, EditorState, Prec } from "@codemirror/state";
 import { EditorView, placeholder as cmPlaceholder, keymap } from "@codemirror/view";
-import { defaultKeymap, history, historyKeymap, indentWithTab } from "@codemirror/commands";
+import { defaultKeymap, history, historyKeymap, indentWithTab, undo, redo } from "@codemirror/commands";
 import { markdown, markdownLanguage } from "@codemirror/lang-markdown";
 import { Strikethrough, Table } from "@lezer/markdown";
 
@@ -35,6 +35,11 @@ const InlineMathExtension = {
 };
 import { typedNotesTheme } from "./theme.js";
 import { livePreviewPlugin } from "./live-preview.js";
+import { formattingKeymap } from "./formatting.js";
+import { tableKeymap } from "./tables.js";
+import { blockDrag } from "./block-drag.js";
+import { WikiLinkExtension, wikiLinks } from "./wiki-links.js";
+import { vimMode as vimModeExtension } from "./vim.js";
 
 /**
  * Create a CodeMirror 6 editor instance inside the given container.
@@ -45,7 +50,12 @@ import { livePreviewPlugin } from "./live-preview.js";
  * @param {string} options.placeholder - Placeholder text
  * @param {function} options.onChange - Called with (content: string) on doc changes
  * @param {function} options.onBlur - Called when editor loses focus
- * @returns {{ view: EditorView, setContent: (s: string) => void, getContent: () => string, destroy: () => void }}
+ * @param {number} options.historyDepth - Undo steps kept (Mod-z / Mod-Shift-z / Mod-y)
+ * @param {function} options.getWikiTargets - Returns note names offered after `[[`
+ * @param {function} options.onWikiLink - Called with (target: string) when a `[[link]]` is clicked
+ * @param {boolean} options.vimMode - Start with Vim keybindings enabled
+ * @param {function} options.onVimModeChange - Called with ("normal" | "insert" | "visual") on mode switches
+ * @returns {{ view: EditorView, setContent: (s: string) => void, getContent: () => string, setVimMode: (on: boolean) => void, undo: () => boolean, redo: () => boolean, destroy: () => void }}
  */
 function createEditor(container, options = {}) {
   const {
@@ -53,25 +63,47 @@ function createEditor(container, options = {}) {
     placeholder = "Start writing...",
     onChange = null,
     onBlur = null,
+    historyDepth = 100,
+    getWikiTargets = () => [],
+    onWikiLink = null,
+    vimMode = false,
+    onVimModeChange = null,
   } = options;
 
+  // Vim keybindings can be switched on and off without recreating the editor
+  const vimCompartment = new Compartment();
+  const vimExtension = () => vimModeExtension({ onModeChange: onVimModeChange });
+
   const extensions = [
-    // Core
-    history(),
+    vimCompartment.of(vimMode ? vimExtension() : []),
+
+    // Core. The history covers the whole document, so undo works across
+    // lines and blocks, and setContent() is undoable like any other edit.
+    history({ minDepth: historyDepth }),
     keymap.of([...defaultKeymap, ...historyKeymap, indentWithTab]),
+    // Mod-b / Mod-i / Mod-` wrap the selection in **, * or `
+    Prec.high(keymap.of(formattingKeymap)),
+    // Tab / Shift-Tab jump between cells inside tables
+    Prec.high(keymap.of(tableKeymap)),
 
     // Markdown language with GFM extensions
     markdown({
       base: markdownLanguage,
-      extensions: [Strikethrough, Table, InlineMathExtension],
+      extensions: [Strikethrough, Table, InlineMathExtension, WikiLinkExtension],
     }),
 
+    // [[wiki links]]: click to navigate, note-name completion after [[
+    wikiLinks({ getTargets: getWikiTargets, onNavigate: onWikiLink }),
+
     // Our theme
     typedNotesTheme,
 
     // Live preview (formatting + syntax dimming)
     livePreviewPlugin,
 
+    // Drag handles for reordering blocks
+    blockDrag,
+
     // Placeholder
     cmPlaceholder(placeholder),
 
@@ -130,6 +162,15 @@ function createEditor(container, options = {}) {
     focus() {
       view.focus();
     },
+    setVimMode(on) {
+      view.dispatch({ effects: vimCompartment.reconfigure(on ? vimExtension() : []) });
+    },
+    undo() {
+      return undo(view);
+    },
+    redo() {
+      return redo(view);
+    },
     destroy() {
       view.destroy();
     },
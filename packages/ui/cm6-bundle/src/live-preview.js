@@ -7,8 +7,10 @@ import { RangeSetBuilder, StateField } from "@codemirror/state";
 import { syntaxTree } from "@codemirror/language";
 import { KatexWidget } from "./katex-widget.js";
 import { HighlightWidget } from "./highlight-widget.js";
+import { MermaidWidget } from "./mermaid-widget.js";
 import { TableWidget } from "./table-widget.js";
 import { FrontmatterWidget } from "./frontmatter-widget.js";
+import { WikiLinkWidget } from "./wiki-links.js";
 
 /**
  * CM6 StateField for live markdown preview.
@@ -44,6 +46,9 @@ const HEADING_CLASSES = {
   ATXHeading6: "cm-md-h6",
 };
 
+// Obsidian-style callout on the first line of a blockquote: `> [!NOTE]`
+const CALLOUT_RE = /^(\s*>\s*)(\[!(note|warning|tip|important|caution)\])/i;
+
 /**
  * Check if any cursor (selection head) is inside the given range [from, to].
  */
@@ -252,7 +257,7 @@ function buildDecorations(state) {
           let language = null;
           node.node.cursor().iterate((child) => {
             if (child.name === "CodeInfo") {
-              language = state.doc.sliceString(child.from, child.to).trim() || null;
+              language = state.doc.sliceString(child.from, child.to).trim().split(/\s+/)[0] || null;
             }
           });
 
@@ -276,11 +281,14 @@ function buildDecorations(state) {
               if (code.endsWith("\n")) code = code.slice(0, -1);
             }
 
-            // Replace entire FencedCode node with highlighted widget
+            // Replace entire FencedCode node with a diagram or highlighted widget
+            const widget = language === "mermaid"
+              ? new MermaidWidget(code)
+              : new HighlightWidget(code, language, true);
             decos.push({
               from,
               to,
-              deco: Decoration.replace({ widget: new HighlightWidget(code, language, true) }),
+              deco: Decoration.replace({ widget }),
             });
           }
         } else {
@@ -320,12 +328,31 @@ function buildDecorations(state) {
         const cursorInside = cursorOnSameLine(state, from, to);
         const startLine = state.doc.lineAt(from).number;
         const endLine = state.doc.lineAt(Math.min(to, state.doc.length)).number;
+        const firstLine = state.doc.line(startLine);
+        const callout = CALLOUT_RE.exec(firstLine.text);
+        const calloutType = callout ? callout[3].toLowerCase() : null;
         for (let ln = startLine; ln <= endLine; ln++) {
           const line = state.doc.line(ln);
+          const cls = calloutType
+            ? `cm-md-callout cm-md-callout-${calloutType}${ln === startLine ? " cm-md-callout-first" : ""}`
+            : "cm-md-blockquote";
           decos.push({
             from: line.from,
             to: line.from,
-            deco: Decoration.line({ class: "cm-md-blockquote" }),
+            deco: Decoration.line({ class: cls }),
+          });
+        }
+
+        // The [!TYPE] tag: dimmed while editing, a titled icon otherwise
+        if (calloutType) {
+          const tagFrom = firstLine.from + callout[1].length;
+          const tagTo = tagFrom + callout[2].length;
+          decos.push({
+            from: tagFrom,
+            to: tagTo,
+            deco: cursorInside
+              ? Decoration.mark({ class: "cm-md-syntax" })
+              : Decoration.replace({ widget: new CalloutTitleWidget(calloutType) }),
           });
         }
 
@@ -423,6 +450,23 @@ function buildDecorations(state) {
         return false;
       }
 
+      // --- WikiLink ([[target]]) ---
+      if (name === "WikiLink") {
+        if (cursorInRange(state, from, to)) {
+          decos.push({ from, to: from + 2, deco: Decoration.mark({ class: "cm-md-syntax" }) });
+          decos.push({ from: from + 2, to: to - 2, deco: Decoration.mark({ class: "cm-md-wiki-link" }) });
+          decos.push({ from: to - 2, to, deco: Decoration.mark({ class: "cm-md-syntax" }) });
+        } else {
+          const target = state.doc.sliceString(from + 2, to - 2).trim();
+          decos.push({
+            from,
+            to,
+            deco: Decoration.replace({ widget: new WikiLinkWidget(target) }),
+          });
+        }
+        return false;
+      }
+
       // --- HorizontalRule (ThematicBreak) ---
       if (name === "HorizontalRule") {
         // Skip if inside frontmatter range (first --- is not a real HR)
@@ -446,6 +490,19 @@ function buildDecorations(state) {
         return false;
       }
 
+      // --- Task list marker ([ ] / [x]) ---
+      if (name === "TaskMarker") {
+        if (!cursorOnSameLine(state, from, to)) {
+          const checked = /x/i.test(state.doc.sliceString(from, to));
+          decos.push({
+            from,
+            to,
+            deco: Decoration.replace({ widget: new TaskCheckboxWidget(checked) }),
+          });
+        }
+        return false;
+      }
+
       // --- ListItem ---
       if (name === "ListItem") {
         const cursorInside = cursorOnSameLine(state, from, to);
@@ -636,6 +693,64 @@ class HRWidget extends WidgetType {
   eq() { return true; }
 }
 
+/** Icon + type name shown in place of a callout's `[!TYPE]` tag. */
+class CalloutTitleWidget extends WidgetType {
+  constructor(type) {
+    super();
+    this.type = type;
+  }
+
+  eq(other) {
+    return this.type === other.type;
+  }
+
+  toDOM() {
+    const span = document.createElement("span");
+    span.className = "callout-title";
+    span.textContent = this.type.charAt(0).toUpperCase() + this.type.slice(1);
+    return span;
+  }
+}
+
+/**
+ * Clickable checkbox for a task marker. Clicking toggles the `[ ]` / `[x]`
+ * source in place; the position is looked up from the DOM so the widget can
+ * be reused when the document shifts around it.
+ */
+class TaskCheckboxWidget extends WidgetType {
+  constructor(checked) {
+    super();
+    this.checked = checked;
+  }
+
+  eq(other) {
+    return this.checked === other.checked;
+  }
+
+  toDOM(view) {
+    const input = document.createElement("input");
+    input.type = "checkbox";
+    input.className = "cm-md-task";
+    input.checked = this.checked;
+    input.addEventListener("mousedown", (e) => e.preventDefault());
+    input.addEventListener("click", (e) => {
+      e.preventDefault();
+      const pos = view.posAtDOM(input);
+      const marker = view.state.doc.sliceString(pos, pos + 3);
+      if (!/^\[[ xX]\]$/.test(marker)) return;
+      view.dispatch({
+        changes: { from: pos + 1, to: pos + 2, insert: this.checked ? " " : "x" },
+        userEvent: "input.toggle",
+      });
+    });
+    return input;
+  }
+
+  ignoreEvent() {
+    return true;
+  }
+}
+
 // StateField-based decorations (can replace across line breaks)
 const livePreviewField = StateField.define({
   create(state) {
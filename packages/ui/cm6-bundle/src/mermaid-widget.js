@@ -0,0 +1,53 @@
+import { WidgetType } from "@codemirror/view";
+
+let diagramCounter = 0;
+
+/**
+ * CM6 WidgetType that renders a ```mermaid fenced block as a diagram.
+ * Mermaid is loaded lazily by the Rust side; until the `typednotes:mermaid-ready`
+ * event fires the source is shown as plain text.
+ */
+export class MermaidWidget extends WidgetType {
+  constructor(source) {
+    super();
+    this.source = source;
+  }
+
+  eq(other) {
+    return this.source === other.source;
+  }
+
+  toDOM() {
+    const wrap = document.createElement("div");
+    wrap.className = "cm-mermaid-widget";
+    const pre = document.createElement("pre");
+    pre.className = "mermaid";
+    pre.textContent = this.source;
+    wrap.appendChild(pre);
+
+    if (typeof mermaid !== "undefined") {
+      renderDiagram(wrap, this.source);
+    } else {
+      window.addEventListener("typednotes:mermaid-ready", () => renderDiagram(wrap, this.source), { once: true });
+    }
+    return wrap;
+  }
+
+  ignoreEvent() {
+    return false;
+  }
+}
+
+function renderDiagram(wrap, source) {
+  const id = `tn-mermaid-${++diagramCounter}`;
+  mermaid
+    .render(id, source)
+    .then(({ svg }) => {
+      wrap.innerHTML = svg;
+    })
+    .catch(() => {
+      // Keep the source visible; mermaid leaves its scratch element behind on errors
+      wrap.classList.add("cm-mermaid-error");
+      document.getElementById(`d${id}`)?.remove();
+    });
+}
@@ -0,0 +1,64 @@
+import { EditorSelection } from "@codemirror/state";
+import { syntaxTree } from "@codemirror/language";
+
+/** The GFM Table node around `pos`, if any. */
+function tableAt(state, pos) {
+  for (let node = syntaxTree(state).resolveInner(pos, -1); node; node = node.parent) {
+    if (node.name === "Table") return node;
+  }
+  return null;
+}
+
+/**
+ * Start offsets of every cell in the table (just after each `|`, skipping
+ * the leading space), excluding the `|---|` delimiter row.
+ */
+function cellStarts(state, table) {
+  const starts = [];
+  const first = state.doc.lineAt(table.from).number;
+  const last = state.doc.lineAt(table.to).number;
+  for (let ln = first; ln <= last; ln++) {
+    const line = state.doc.line(ln);
+    if (/^\s*\|?\s*:?-+:?\s*(\|\s*:?-+:?\s*)*\|?\s*$/.test(line.text)) continue;
+    let text = line.text;
+    // Rows without a leading pipe start with a cell
+    if (!text.trimStart().startsWith("|")) starts.push(line.from + (text.length - text.trimStart().length));
+    for (let i = 0; i < text.length; i++) {
+      if (text[i] !== "|" || (i > 0 && text[i - 1] === "\\")) continue;
+      let at = i + 1;
+      if (at >= text.trimEnd().length) continue; // trailing pipe
+      if (text[at] === " ") at++;
+      starts.push(line.from + at);
+    }
+  }
+  return starts;
+}
+
+/** Command moving the cursor to the next (`dir` = 1) or previous (-1) cell. */
+function moveCell(dir) {
+  return (view) => {
+    const { state } = view;
+    const pos = state.selection.main.head;
+    const table = tableAt(state, pos);
+    if (!table) return false;
+
+    const starts = cellStarts(state, table);
+    // Index of the cell containing the cursor
+    let current = -1;
+    for (let i = 0; i < starts.length && starts[i] <= pos; i++) current = i;
+    const target = starts[current + dir];
+    if (target === undefined) return dir > 0 ? false : true;
+
+    view.dispatch({ selection: EditorSelection.cursor(target), scrollIntoView: true });
+    return true;
+  };
+}
+
+/**
+ * Tab / Shift-Tab move between table cells. Tab in the last cell falls
+ * through to the default indent binding.
+ */
+export const tableKeymap = [
+  { key: "Tab", run: moveCell(1) },
+  { key: "Shift-Tab", run: moveCell(-1) },
+];
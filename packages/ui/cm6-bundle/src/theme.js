@@ -1,9 +1,11 @@
 import { EditorView } from "@codemirror/view";
 
-// Theme that matches the TypedNotes app CSS variables
+// Theme that matches the TypedNotes app CSS variables. Colors go through the
+// --md-* variables defined (with light and dark values) in
+// ui/src/markdown_editor/style.css, so they can be themed from Rust.
 export const typedNotesTheme = EditorView.theme({
   "&": {
-    color: "var(--secondary-color-4)",
+    color: "var(--md-text-color)",
     backgroundColor: "transparent",
     fontFamily: "inherit",
     fontSize: "1rem",
@@ -16,7 +18,7 @@ export const typedNotesTheme = EditorView.theme({
     fontFamily: "inherit",
   },
   ".cm-content": {
-    caretColor: "var(--secondary-color-4)",
+    caretColor: "var(--md-text-color)",
     padding: "0",
     whiteSpace: "pre-wrap",
     wordWrap: "break-word",
@@ -25,19 +27,44 @@ export const typedNotesTheme = EditorView.theme({
     padding: "0",
   },
   ".cm-cursor": {
-    borderLeftColor: "var(--secondary-color-4)",
+    borderLeftColor: "var(--md-text-color)",
+  },
+  // Vim Normal / Visual mode: block cursor
+  "&.cm-vim-normal .cm-cursor, &.cm-vim-visual .cm-cursor": {
+    borderLeftWidth: "0.6em",
+    opacity: "0.5",
   },
   ".cm-selectionBackground": {
-    backgroundColor: "var(--primary-color-5, rgba(59,130,246,0.3)) !important",
+    backgroundColor: "var(--md-selection-bg) !important",
   },
   "&.cm-focused .cm-selectionBackground": {
-    backgroundColor: "var(--primary-color-5, rgba(59,130,246,0.3)) !important",
+    backgroundColor: "var(--md-selection-bg) !important",
   },
   ".cm-activeLine": {
     backgroundColor: "transparent",
   },
+  // Only the block drag gutter is used; keep it unobtrusive
   ".cm-gutters": {
-    display: "none",
+    backgroundColor: "transparent",
+    border: "none",
+  },
+  ".cm-md-drag-gutter .cm-gutterElement": {
+    width: "1.25rem",
+    display: "flex",
+    justifyContent: "center",
+  },
+  ".md-drag-handle": {
+    color: "var(--secondary-color-5)",
+    cursor: "grab",
+    opacity: "0",
+    transition: "opacity 150ms ease",
+    userSelect: "none",
+  },
+  "&:hover .md-drag-handle": {
+    opacity: "0.5",
+  },
+  ".md-drag-handle:hover": {
+    opacity: "1",
   },
   // Placeholder styling
   ".cm-placeholder": {
@@ -52,6 +79,9 @@ export const typedNotesTheme = EditorView.theme({
   ".cm-md-h4": { fontSize: "1.125rem", fontWeight: "600", lineHeight: "1.45" },
   ".cm-md-h5": { fontSize: "1rem", fontWeight: "600", lineHeight: "1.5" },
   ".cm-md-h6": { fontSize: "0.875rem", fontWeight: "600", lineHeight: "1.5" },
+  ".cm-md-h1, .cm-md-h2, .cm-md-h3, .cm-md-h4, .cm-md-h5, .cm-md-h6": {
+    color: "var(--md-heading-color)",
+  },
 
   // Inline formatting
   ".cm-md-bold": { fontWeight: "700" },
@@ -60,7 +90,7 @@ export const typedNotesTheme = EditorView.theme({
   ".cm-md-code": {
     fontFamily: 'ui-monospace, "SF Mono", "Cascadia Code", Menlo, Consolas, monospace',
     fontSize: "0.875em",
-    backgroundColor: "var(--primary-color-3)",
+    backgroundColor: "var(--md-code-bg)",
     padding: "0.1em 0.3em",
     borderRadius: "3px",
   },
@@ -69,7 +99,7 @@ export const typedNotesTheme = EditorView.theme({
   ".cm-md-codeblock": {
     fontFamily: 'ui-monospace, "SF Mono", "Cascadia Code", Menlo, Consolas, monospace',
     fontSize: "0.875rem",
-    backgroundColor: "var(--primary-color-3)",
+    backgroundColor: "var(--md-code-bg)",
     borderRadius: "0",
     padding: "0 1em",
   },
@@ -89,12 +119,18 @@ export const typedNotesTheme = EditorView.theme({
 
   // Block quote
   ".cm-md-blockquote": {
-    borderLeft: "3px solid var(--secondary-color-6, var(--secondary-color-5))",
+    borderLeft: "3px solid var(--md-blockquote-border)",
     paddingLeft: "1em",
-    color: "var(--secondary-color-5)",
+    color: "var(--md-blockquote-color)",
     fontStyle: "italic",
   },
 
+  // Callout lines (colors, border and icon are in markdown_editor/style.css;
+  // the padding has to beat the base .cm-line rule, so it lives here)
+  ".cm-md-callout": {
+    paddingLeft: "1em",
+  },
+
   // List item
   ".cm-md-list-item": {
     paddingLeft: "0",
@@ -114,17 +150,26 @@ export const typedNotesTheme = EditorView.theme({
 
   // Links
   ".cm-md-link-text": {
-    color: "var(--accent-color, #3b82f6)",
+    color: "var(--md-link-color)",
     textDecoration: "underline",
   },
+  ".cm-md-wiki-link, .wiki-link": {
+    color: "var(--md-link-color)",
+  },
+  ".wiki-link": {
+    textDecoration: "none",
+    borderBottom: "1px dashed currentColor",
+    cursor: "pointer",
+  },
   ".cm-md-link-url": {
-    color: "var(--secondary-color-6, var(--secondary-color-5))",
+    color: "var(--md-syntax-color)",
     fontSize: "0.9em",
   },
 
   // Syntax markers (dimmed when visible near cursor)
   ".cm-md-syntax": {
-    color: "var(--secondary-color-6, var(--secondary-color-5))",
+    color: "var(--md-syntax-color)",
+    opacity: "var(--md-syntax-opacity)",
     fontWeight: "400",
     fontStyle: "normal",
   },
@@ -135,7 +180,7 @@ export const typedNotesTheme = EditorView.theme({
     fontSize: "0.875em",
   },
   ".cm-hljs-block": {
-    backgroundColor: "var(--primary-color-3)",
+    backgroundColor: "var(--md-code-bg)",
     borderRadius: "6px",
     padding: "0.75em 1em",
     margin: "0.5em 0",
@@ -143,11 +188,38 @@ export const typedNotesTheme = EditorView.theme({
     overflowX: "auto",
   },
   ".cm-hljs-inline": {
-    backgroundColor: "var(--primary-color-3)",
+    backgroundColor: "var(--md-code-bg)",
     padding: "0.1em 0.3em",
     borderRadius: "3px",
   },
 
+  // Task list checkbox
+  ".cm-md-task": {
+    margin: "0 0.25em 0 0",
+    verticalAlign: "middle",
+    cursor: "pointer",
+    accentColor: "var(--focused-border-color)",
+  },
+
+  // Mermaid widget
+  ".cm-mermaid-widget": {
+    display: "flex",
+    justifyContent: "center",
+    margin: "0.5em 0",
+    overflowX: "auto",
+  },
+  ".cm-mermaid-widget pre.mermaid, .cm-mermaid-error pre": {
+    fontFamily: 'ui-monospace, "SF Mono", "Cascadia Code", Menlo, Consolas, monospace',
+    fontSize: "0.875em",
+    whiteSpace: "pre-wrap",
+    width: "100%",
+  },
+  ".cm-mermaid-error": {
+    justifyContent: "flex-start",
+    borderLeft: "3px solid var(--secondary-error-color)",
+    paddingLeft: "0.75em",
+  },
+
   // Table
   ".cm-md-table-wrap": {
     display: "block",
@@ -160,18 +232,18 @@ export const typedNotesTheme = EditorView.theme({
     fontSize: "0.95em",
   },
   ".cm-md-table th": {
-    borderBottom: "2px solid var(--primary-color-6, #d1d5db)",
+    borderBottom: "2px solid var(--md-table-border)",
     padding: "0.4em 0.75em",
     textAlign: "left",
     fontWeight: "600",
-    backgroundColor: "var(--primary-color-3, #f3f4f6)",
+    backgroundColor: "var(--md-table-header-bg)",
   },
   ".cm-md-table td": {
-    borderBottom: "1px solid var(--primary-color-5, #e5e7eb)",
+    borderBottom: "1px solid var(--md-table-border)",
     padding: "0.35em 0.75em",
   },
   ".cm-md-table tbody tr:hover": {
-    backgroundColor: "var(--primary-color-2, #f9fafb)",
+    backgroundColor: "var(--md-table-row-hover)",
   },
   ".cm-md-table-raw": {
     fontFamily: 'ui-monospace, "SF Mono", "Cascadia Code", Menlo, Consolas, monospace',
@@ -187,15 +259,15 @@ export const typedNotesTheme = EditorView.theme({
     display: "inline-block",
     fontSize: "0.75em",
     fontFamily: 'ui-monospace, "SF Mono", "Cascadia Code", Menlo, Consolas, monospace',
-    color: "var(--secondary-color-6, var(--secondary-color-5))",
-    backgroundColor: "var(--primary-color-3, #f3f4f6)",
+    color: "var(--md-syntax-color)",
+    backgroundColor: "var(--md-table-header-bg)",
     padding: "0.15em 0.5em",
     borderRadius: "4px",
   },
   ".cm-md-frontmatter-raw": {
     fontFamily: 'ui-monospace, "SF Mono", "Cascadia Code", Menlo, Consolas, monospace',
     fontSize: "0.875em",
-    color: "var(--secondary-color-6, var(--secondary-color-5))",
+    color: "var(--md-syntax-color)",
   },
 
   // KaTeX widget
@@ -0,0 +1,215 @@
+import { EditorSelection, Facet, Prec, StateEffect, StateField } from "@codemirror/state";
+import { EditorView } from "@codemirror/view";
+import {
+  cursorCharLeft,
+  cursorCharRight,
+  cursorLineDown,
+  cursorLineUp,
+  selectCharLeft,
+  selectCharRight,
+  selectLineDown,
+  selectLineUp,
+} from "@codemirror/commands";
+
+/**
+ * A small modal editing layer with the Vim basics:
+ *
+ * - Normal mode: `h` `j` `k` `l` (with an optional count, e.g. `3j`), `i` / `a`
+ *   to insert before / after the cursor, `o` / `O` to open a line below /
+ *   above, `dd` to delete the current line(s), `v` for Visual mode.
+ * - Visual mode: `h` `j` `k` `l` extend the selection, `d` / `x` delete it.
+ * - `Escape` returns to Normal mode from Insert or Visual.
+ *
+ * Other printable keys are swallowed outside Insert mode; shortcuts with
+ * Ctrl / Cmd / Alt pass through untouched.
+ */
+
+/** Switch mode and reset the pending count / operator. */
+const setMode = StateEffect.define();
+/** Update the pending count / operator without changing mode. */
+const setPending = StateEffect.define();
+
+const initial = { mode: "normal", count: "", operator: "" };
+
+const vimState = StateField.define({
+  create: () => initial,
+  update(value, tr) {
+    for (const e of tr.effects) {
+      if (e.is(setMode)) value = { mode: e.value, count: "", operator: "" };
+      else if (e.is(setPending)) value = { ...value, ...e.value };
+    }
+    return value;
+  },
+});
+
+/** Called with the mode name ("normal" | "insert" | "visual") when it changes. */
+export const vimModeHandler = Facet.define({
+  combine: (values) => values[0] || null,
+});
+
+/** Run `command` `count` times. */
+function repeat(view, command, count) {
+  for (let i = 0; i < count; i++) command(view);
+}
+
+const motions = {
+  h: [cursorCharLeft, selectCharLeft],
+  j: [cursorLineDown, selectLineDown],
+  k: [cursorLineUp, selectLineUp],
+  l: [cursorCharRight, selectCharRight],
+};
+
+/** Delete `count` lines starting at the cursor line (Vim `dd`). */
+function deleteLines(view, count) {
+  const { doc } = view.state;
+  const first = doc.lineAt(view.state.selection.main.head);
+  const last = doc.line(Math.min(first.number + count - 1, doc.lines));
+  let from = first.from;
+  let to = last.to;
+  // Take a line break with the deleted lines so no empty line is left behind
+  if (to < doc.length) to += 1;
+  else if (from > 0) from -= 1;
+  view.dispatch({
+    changes: { from, to },
+    selection: { anchor: Math.min(from, doc.length - (to - from)) },
+    scrollIntoView: true,
+    userEvent: "delete.line",
+  });
+}
+
+/** Insert an empty line below (or above) the cursor line and enter Insert mode. */
+function openLine(view, above) {
+  const line = view.state.doc.lineAt(view.state.selection.main.head);
+  const pos = above ? line.from : line.to;
+  view.dispatch({
+    changes: { from: pos, insert: "\n" },
+    selection: { anchor: above ? line.from : line.to + 1 },
+    effects: setMode.of("insert"),
+    scrollIntoView: true,
+    userEvent: "input",
+  });
+}
+
+function handleKey(view, event) {
+  const state = view.state.field(vimState);
+  const key = event.key;
+
+  if (state.mode === "insert") {
+    if (key !== "Escape") return false;
+    // Like Vim, the cursor steps back onto the last inserted character
+    const head = view.state.selection.main.head;
+    const line = view.state.doc.lineAt(head);
+    view.dispatch({
+      selection: { anchor: head > line.from ? head - 1 : head },
+      effects: setMode.of("normal"),
+    });
+    return true;
+  }
+
+  if (event.ctrlKey || event.metaKey || event.altKey) return false;
+
+  if (key === "Escape") {
+    const head = view.state.selection.main.head;
+    view.dispatch({ selection: { anchor: head }, effects: setMode.of("normal") });
+    return true;
+  }
+
+  // Count prefix: 1-9, then any digit
+  if (/^[1-9]$/.test(key) || (key === "0" && state.count)) {
+    view.dispatch({ effects: setPending.of({ count: state.count + key }) });
+    return true;
+  }
+  const count = Math.max(parseInt(state.count || "1", 10), 1);
+  const visual = state.mode === "visual";
+
+  if (motions[key]) {
+    repeat(view, motions[key][visual ? 1 : 0], count);
+    view.dispatch({ effects: setPending.of({ count: "", operator: "" }) });
+    return true;
+  }
+
+  if (visual) {
+    if (key === "d" || key === "x") {
+      const range = view.state.selection.main;
+      view.dispatch({
+        changes: { from: range.from, to: range.to },
+        selection: { anchor: range.from },
+        effects: setMode.of("normal"),
+        userEvent: "delete.selection",
+      });
+    } else if (key === "v") {
+      view.dispatch({ selection: { anchor: view.state.selection.main.head }, effects: setMode.of("normal") });
+    }
+    return key.length === 1;
+  }
+
+  switch (key) {
+    case "i":
+      view.dispatch({ effects: setMode.of("insert") });
+      return true;
+    case "a": {
+      const head = view.state.selection.main.head;
+      const line = view.state.doc.lineAt(head);
+      view.dispatch({
+        selection: { anchor: Math.min(head + 1, line.to) },
+        effects: setMode.of("insert"),
+      });
+      return true;
+    }
+    case "o":
+    case "O":
+      openLine(view, key === "O");
+      return true;
+    case "v": {
+      const head = view.state.selection.main.head;
+      view.dispatch({
+        selection: EditorSelection.single(head, Math.min(head + 1, view.state.doc.length)),
+        effects: setMode.of("visual"),
+      });
+      return true;
+    }
+    case "d":
+      if (state.operator === "d") {
+        deleteLines(view, count);
+        view.dispatch({ effects: setPending.of({ count: "", operator: "" }) });
+      } else {
+        view.dispatch({ effects: setPending.of({ operator: "d" }) });
+      }
+      return true;
+    default:
+      // Unknown command: drop any pending count / operator
+      if (state.count || state.operator) {
+        view.dispatch({ effects: setPending.of({ count: "", operator: "" }) });
+      }
+      return key.length === 1;
+  }
+}
+
+/**
+ * @param {Object} options
+ * @param {function} options.onModeChange - Called with the new mode name
+ */
+export function vimMode({ onModeChange } = {}) {
+  return [
+    vimState,
+    vimModeHandler.of(onModeChange),
+    Prec.highest(EditorView.domEventHandlers({
+      keydown(event, view) {
+        if (!handleKey(view, event)) return false;
+        event.preventDefault();
+        return true;
+      },
+    })),
+    // Block cursor outside Insert mode
+    EditorView.editorAttributes.compute([vimState], (state) => ({
+      class: `cm-vim-${state.field(vimState).mode}`,
+    })),
+    EditorView.updateListener.of((update) => {
+      const before = update.startState.field(vimState, false);
+      const after = update.state.field(vimState);
+      if (before && before.mode === after.mode) return;
+      const handler = update.state.facet(vimModeHandler);
+      if (handler) handler(after.mode);
+    }),
+  ];
+}
@@ -0,0 +1,110 @@
+import { WidgetType } from "@codemirror/view";
+import { Facet } from "@codemirror/state";
+import { autocompletion } from "@codemirror/autocomplete";
+
+/**
+ * Obsidian-style `[[note name]]` links.
+ *
+ * - `WikiLinkExtension` teaches the Lezer markdown parser a `WikiLink` node
+ *   (with `WikiLinkMark` children for the brackets); it runs before the
+ *   standard `Link` parser so `[[` is not taken as a link label.
+ * - `WikiLinkWidget` is the rendered link shown by the live preview when the
+ *   cursor is elsewhere; clicking it calls the `onNavigate` handler.
+ * - `wikiLinks()` installs the handler and an autocompletion source that
+ *   offers note names after `[[`.
+ */
+export const WikiLinkExtension = {
+  defineNodes: ["WikiLink", "WikiLinkMark"],
+  parseInline: [{
+    name: "WikiLink",
+    before: "Link",
+    parse(cx, next, pos) {
+      if (next !== 91 || cx.char(pos + 1) !== 91) return -1; // not [[
+      let end = pos + 2;
+      while (end + 1 < cx.end) {
+        const ch = cx.char(end);
+        if (ch === 10 || ch === 91) return -1; // no newlines or nested [
+        if (ch === 93) { // ]
+          if (cx.char(end + 1) !== 93 || end === pos + 2) return -1;
+          cx.addElement(cx.elt("WikiLink", pos, end + 2, [
+            cx.elt("WikiLinkMark", pos, pos + 2),
+            cx.elt("WikiLinkMark", end, end + 2),
+          ]));
+          return end + 2;
+        }
+        end++;
+      }
+      return -1;
+    },
+  }],
+};
+
+/** Called with the link target when a rendered wiki link is clicked. */
+export const wikiLinkHandler = Facet.define({
+  combine: (values) => values[0] || null,
+});
+
+/** Rendered `[[target]]`: `<a class="wiki-link" data-wiki-target="...">`. */
+export class WikiLinkWidget extends WidgetType {
+  constructor(target) {
+    super();
+    this.target = target;
+  }
+
+  eq(other) {
+    return this.target === other.target;
+  }
+
+  toDOM(view) {
+    const a = document.createElement("a");
+    a.className = "wiki-link";
+    a.href = "#";
+    a.dataset.wikiTarget = this.target;
+    a.textContent = this.target;
+    // Keep the cursor where it is instead of moving into the link source
+    a.addEventListener("mousedown", (e) => e.preventDefault());
+    a.addEventListener("click", (e) => {
+      e.preventDefault();
+      const navigate = view.state.facet(wikiLinkHandler);
+      if (navigate) navigate(this.target);
+    });
+    return a;
+  }
+
+  ignoreEvent() {
+    return true;
+  }
+}
+
+/**
+ * Completion source for the text after an unclosed `[[`. Picking a target
+ * inserts it with the closing `]]` (unless one already follows the cursor).
+ */
+function wikiLinkCompletions(getTargets) {
+  return (context) => {
+    const match = context.matchBefore(/\[\[[^\[\]\n]*/);
+    if (!match) return null;
+    const closed = context.state.sliceDoc(context.pos, context.pos + 2) === "]]";
+    return {
+      from: match.from + 2,
+      options: getTargets().map((target) => ({
+        label: target,
+        type: "text",
+        apply: closed ? target : `${target}]]`,
+      })),
+      validFor: /^[^\[\]\n]*$/,
+    };
+  };
+}
+
+/**
+ * @param {Object} options
+ * @param {function} options.getTargets - Returns the note names to offer
+ * @param {function} options.onNavigate - Called with (target: string) on click
+ */
+export function wikiLinks({ getTargets, onNavigate }) {
+  return [
+    wikiLinkHandler.of(onNavigate),
+    autocompletion({ override: [wikiLinkCompletions(getTargets)], icons: false }),
+  ];
+}
@@ -1,24 +1,105 @@
 use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq)]
+/// Entries an [`ActivityLog`] keeps by default before evicting the oldest.
+pub const DEFAULT_MAX_LOG_ENTRIES: usize = 500;
+
+/// `sessionStorage` key the log is persisted under, so it survives a reload
+/// but not closing the tab.
+#[cfg(target_arch = "wasm32")]
+const STORAGE_KEY: &str = "activity_log";
+
+/// How long [`persist_activity_log`] waits for more entries before saving.
+#[cfg(target_arch = "wasm32")]
+const PERSIST_DEBOUNCE_MS: u64 = 500;
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// Whether a save is already scheduled.
+    static PERSIST_SCHEDULED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Severity of a [`LogEntry`], least severe first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum LogLevel {
+    /// Verbose output, hidden unless the panel's filter is lowered to it.
+    Debug,
     Info,
     Success,
     Warning,
     Error,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Success,
+        LogLevel::Warning,
+        LogLevel::Error,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Success => "success",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+        }
+    }
+
+    /// Inverse of [`LogLevel::as_str`].
+    pub fn from_name(s: &str) -> Option<LogLevel> {
+        LogLevel::ALL.into_iter().find(|level| level.as_str() == s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LogEntry {
     pub timestamp: String,
     pub level: LogLevel,
     pub message: String,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct ActivityLog {
+    /// Oldest first.
     pub entries: Vec<LogEntry>,
     pub visible: bool,
+    /// Entries kept; pushing past this drops the oldest.
+    pub max_entries: usize,
+}
+
+impl Default for ActivityLog {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            visible: false,
+            max_entries: DEFAULT_MAX_LOG_ENTRIES,
+        }
+    }
+}
+
+impl ActivityLog {
+    /// Append `entry`, evicting the oldest entries beyond `max_entries`.
+    pub fn push(&mut self, entry: LogEntry) {
+        self.entries.push(entry);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.max_entries);
+        self.entries.drain(..excess);
+    }
+
+    /// The log as plain text, one `HH:MM:SS [LEVEL] message` line per entry.
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| format!("{} [{}] {}\n", e.timestamp, e.level.as_str().to_uppercase(), e.message))
+            .collect()
+    }
 }
 
 pub fn use_activity_log() -> Signal<ActivityLog> {
@@ -27,15 +108,100 @@ pub fn use_activity_log() -> Signal<ActivityLog> {
 
 pub fn log_activity(log: &mut Signal<ActivityLog>, level: LogLevel, message: &str) {
     let ts = current_time();
-    log.write().entries.push(LogEntry {
+    log.write().push(LogEntry {
         timestamp: ts,
         level,
         message: message.to_string(),
     });
+    persist_activity_log(*log);
+}
+
+/// [`log_activity`] at [`LogLevel::Error`] with a `format!` message:
+/// `log_error!(&mut log, "Sync error for {path}: {e}")`.
+#[macro_export]
+macro_rules! log_error {
+    ($log:expr, $($arg:tt)+) => {
+        $crate::log_activity($log, $crate::LogLevel::Error, &format!($($arg)+))
+    };
+}
+
+/// [`log_activity`] at [`LogLevel::Debug`] with a `format!` message.
+#[macro_export]
+macro_rules! log_debug {
+    ($log:expr, $($arg:tt)+) => {
+        $crate::log_activity($log, $crate::LogLevel::Debug, &format!($($arg)+))
+    };
+}
+
+/// Save the log's entries to `sessionStorage` (web only).
+///
+/// The save runs [`PERSIST_DEBOUNCE_MS`] later and writes whatever the log
+/// holds then, so a burst of entries (a sync of many notes) serialises the
+/// log once rather than once per entry.
+pub fn persist_activity_log(log: Signal<ActivityLog>) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        if PERSIST_SCHEDULED.with(|scheduled| scheduled.replace(true)) {
+            return;
+        }
+        // Not tied to the calling component, which may unmount before it runs
+        spawn_forever(async move {
+            gloo_timers::future::sleep(std::time::Duration::from_millis(PERSIST_DEBOUNCE_MS)).await;
+            PERSIST_SCHEDULED.with(|scheduled| scheduled.set(false));
+            let eval = document::eval(
+                r#"var key = await dioxus.recv();
+                var entries = await dioxus.recv();
+                try { sessionStorage.setItem(key, JSON.stringify(entries)); } catch (e) {}"#,
+            );
+            let _ = eval.send(STORAGE_KEY);
+            let _ = eval.send(log.peek().entries.clone());
+        });
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = log;
+}
+
+/// Restore entries saved by [`persist_activity_log`] before this page load,
+/// ahead of anything logged since (web only).
+pub fn load_activity_log_from_storage(log: &mut Signal<ActivityLog>) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let mut log = *log;
+        spawn(async move {
+            let eval = document::eval(
+                r#"var key = await dioxus.recv();
+                try {
+                    var entries = JSON.parse(sessionStorage.getItem(key) || '[]');
+                    return Array.isArray(entries) ? entries : [];
+                } catch (e) {
+                    return [];
+                }"#,
+            );
+            let _ = eval.send(STORAGE_KEY);
+            let Ok(mut stored) = eval.join::<Vec<LogEntry>>().await else {
+                return;
+            };
+            if stored.is_empty() {
+                return;
+            }
+            let mut current = log.write();
+            stored.append(&mut current.entries);
+            current.entries = stored;
+            current.evict();
+        });
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = log;
+}
+
+/// Save the log as a `.txt` file with [`download_bytes`](crate::download::download_bytes).
+/// Returns where it went.
+pub fn download_activity_log(log: &ActivityLog) -> Result<String, String> {
+    crate::download::download_bytes(log.to_text().into_bytes(), "typednotes-activity-log.txt", "text/plain")
 }
 
 #[cfg(target_arch = "wasm32")]
-fn current_time() -> String {
+pub(crate) fn current_time() -> String {
     let date = js_sys::Date::new_0();
     let h = date.get_hours();
     let m = date.get_minutes();
@@ -44,6 +210,6 @@ fn current_time() -> String {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn current_time() -> String {
+pub(crate) fn current_time() -> String {
     "00:00:00".to_string()
 }
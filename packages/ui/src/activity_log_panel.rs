@@ -1,6 +1,6 @@
 use dioxus::prelude::*;
 
-use crate::activity_log::{LogLevel, use_activity_log};
+use crate::activity_log::{LogEntry, LogLevel, download_activity_log, log_activity, persist_activity_log, use_activity_log};
 use crate::components::{Badge, BadgeVariant};
 
 const LOG_PANEL_CSS: Asset = asset!("/src/views/log_panel.css");
@@ -8,12 +8,14 @@ const LOG_PANEL_CSS: Asset = asset!("/src/views/log_panel.css");
 #[component]
 pub fn ActivityLogPanel() -> Element {
     let mut log = use_activity_log();
+    // Entries below this level are hidden
+    let mut min_level = use_signal(|| LogLevel::Info);
 
     if !log().visible {
         return rsx! {};
     }
 
-    let entries = log().entries.clone();
+    let entries: Vec<LogEntry> = log().entries.into_iter().filter(|e| e.level >= min_level()).collect();
 
     rsx! {
         document::Link { rel: "stylesheet", href: LOG_PANEL_CSS }
@@ -26,9 +28,35 @@ pub fn ActivityLogPanel() -> Element {
                 span { "Activity Log" }
                 div {
                     class: "log-panel-actions",
+                    select {
+                        class: "log-panel-filter",
+                        title: "Minimum level shown",
+                        value: min_level().as_str(),
+                        onchange: move |evt: FormEvent| {
+                            if let Some(level) = LogLevel::from_name(&evt.value()) {
+                                min_level.set(level);
+                            }
+                        },
+                        for level in LogLevel::ALL {
+                            option { key: "{level.as_str()}", value: level.as_str(), "{level.as_str()}" }
+                        }
+                    }
+                    button {
+                        class: "log-panel-action",
+                        onclick: move |_| {
+                            let saved = download_activity_log(&log.peek());
+                            if let Err(e) = saved {
+                                log_activity(&mut log, LogLevel::Error, &format!("Could not save the log: {e}"));
+                            }
+                        },
+                        "Download log"
+                    }
                     button {
                         class: "log-panel-action",
-                        onclick: move |_| log.write().entries.clear(),
+                        onclick: move |_| {
+                            log.write().entries.clear();
+                            persist_activity_log(log);
+                        },
                         "Clear"
                     }
                     button {
@@ -42,12 +70,7 @@ pub fn ActivityLogPanel() -> Element {
                 class: "log-panel-body",
                 for entry in entries.iter().rev() {
                     div {
-                        class: match entry.level {
-                            LogLevel::Error => "log-panel-entry log-entry-error",
-                            LogLevel::Warning => "log-panel-entry log-entry-warning",
-                            LogLevel::Success => "log-panel-entry log-entry-success",
-                            LogLevel::Info => "log-panel-entry log-entry-info",
-                        },
+                        class: "log-panel-entry log-entry-{entry.level.as_str()}",
                         span { class: "log-panel-timestamp", "{entry.timestamp}" }
                         span { "{entry.message}" }
                     }
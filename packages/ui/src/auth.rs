@@ -1,6 +1,6 @@
 //! Authentication context and hooks for the UI.
 
-use api::UserInfo;
+use api::{ExtendedUserInfo, UserInfo};
 use dioxus::prelude::*;
 
 /// Authentication state for the application.
@@ -10,6 +10,8 @@ pub struct AuthState {
     pub loading: bool,
     /// Whether the server is reachable (last connectivity check succeeded).
     pub online: bool,
+    /// Git setup, note count and previous visit, loaded once on startup.
+    pub extended: Option<ExtendedUserInfo>,
 }
 
 impl Default for AuthState {
@@ -18,6 +20,7 @@ impl Default for AuthState {
             user: None,
             loading: true,
             online: false,
+            extended: None,
         }
     }
 }
@@ -34,15 +37,16 @@ pub fn use_auth() -> Signal<AuthState> {
 pub fn AuthProvider(children: Element) -> Element {
     let mut auth_state = use_signal(AuthState::default);
 
-    // Fetch the current user on mount
+    // Fetch the current user (with git setup and note count) on mount
     let _ = use_resource(move || async move {
-        match api::get_current_user().await {
-            Ok(user) => {
-                let online = user.is_some();
+        match api::get_current_user_extended().await {
+            Ok(extended) => {
+                let online = extended.is_some();
                 auth_state.set(AuthState {
-                    user,
+                    user: extended.as_ref().map(|e| e.user.clone()),
                     loading: false,
                     online,
+                    extended,
                 });
             }
             Err(_) => {
@@ -50,6 +54,7 @@ pub fn AuthProvider(children: Element) -> Element {
                     user: None,
                     loading: false,
                     online: false,
+                    extended: None,
                 });
             }
         }
@@ -73,10 +78,15 @@ pub fn AuthProvider(children: Element) -> Element {
                         let online = user.is_some();
                         let current = auth_state();
                         if current.user != user || current.online != online {
+                            // The startup stats belong to the user they were loaded for
+                            let extended = current
+                                .extended
+                                .filter(|e| Some(&e.user.id) == user.as_ref().map(|u| &u.id));
                             auth_state.set(AuthState {
                                 user,
                                 loading: false,
                                 online,
+                                extended,
                             });
                         }
                     }
@@ -167,6 +177,7 @@ pub fn LogoutButton(
                 user: None,
                 loading: false,
                 online: auth_state().online,
+                extended: None,
             });
             // Redirect to login
             #[cfg(target_arch = "wasm32")]
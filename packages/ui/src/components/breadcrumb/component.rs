@@ -0,0 +1,70 @@
+use dioxus::prelude::*;
+
+/// Path of a note shown as `/ › work › projects › note`, with every
+/// namespace clickable.
+///
+/// On small screens only the root, the innermost namespace and the note stay
+/// visible; the namespaces in between collapse into a `…` node.
+#[component]
+pub fn Breadcrumb(
+    /// Note path, e.g. `"work/projects/note.md"`.
+    path: String,
+    /// Called with the clicked namespace's path (`""` for the root).
+    on_navigate_namespace: EventHandler<String>,
+) -> Element {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let Some((file, namespaces)) = segments.split_last() else {
+        return rsx! {};
+    };
+    let note_name = store::models::split_note_filename(file).map_or(*file, |(name, _, _)| name);
+    // Every namespace but the innermost can collapse into the `…` node
+    let collapsible = namespaces.len().saturating_sub(1);
+    let crumbs: Vec<(String, String)> = namespaces
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (namespaces[..=i].join("/"), name.to_string()))
+        .collect();
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: asset!("./style.css") }
+        nav {
+            class: "breadcrumb",
+            aria_label: "Note location",
+            ol {
+                li {
+                    button {
+                        class: "breadcrumb-link",
+                        title: "All notes",
+                        onclick: move |_| on_navigate_namespace.call(String::new()),
+                        "/"
+                    }
+                }
+                if collapsible > 0 {
+                    li {
+                        class: "breadcrumb-ellipsis",
+                        title: crumbs[collapsible - 1].0.clone(),
+                        "…"
+                    }
+                }
+                for (i, (ns_path, name)) in crumbs.into_iter().enumerate() {
+                    li {
+                        key: "{ns_path}",
+                        class: if i < collapsible { "breadcrumb-collapsible" } else { "" },
+                        button {
+                            class: "breadcrumb-link",
+                            title: ns_path.clone(),
+                            onclick: {
+                                let ns_path = ns_path.clone();
+                                move |_| on_navigate_namespace.call(ns_path.clone())
+                            },
+                            "{name}"
+                        }
+                    }
+                }
+                li {
+                    span { class: "breadcrumb-current", aria_current: "page", "{note_name}" }
+                }
+            }
+        }
+    }
+}
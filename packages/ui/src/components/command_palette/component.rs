@@ -0,0 +1,225 @@
+use dioxus::prelude::*;
+
+use crate::note_tree::{use_note_tree_search, SearchMatch};
+
+/// An entry in the command palette.
+#[derive(Clone, PartialEq)]
+pub struct PaletteAction {
+    /// Stable identifier; registering an action with an existing id replaces it.
+    pub id: String,
+    pub label: String,
+    /// Shortcut hint shown next to the label (display only).
+    pub shortcut: Option<String>,
+    pub action: Callback<()>,
+}
+
+impl PaletteAction {
+    pub fn new(id: impl Into<String>, label: impl Into<String>, action: impl FnMut(()) + 'static) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            shortcut: None,
+            action: Callback::new(action),
+        }
+    }
+
+    pub fn with_shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+}
+
+/// Handle to the palette provided by [`CommandPaletteProvider`].
+#[derive(Clone, Copy)]
+pub struct CommandPaletteContext {
+    open: Signal<bool>,
+    actions: Signal<Vec<PaletteAction>>,
+}
+
+impl CommandPaletteContext {
+    pub fn is_open(&self) -> bool {
+        (self.open)()
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open.set(open);
+    }
+
+    pub fn toggle(&mut self) {
+        let open = *self.open.peek();
+        self.open.set(!open);
+    }
+
+    /// Add actions, replacing any already registered with the same id.
+    pub fn register(&mut self, actions: Vec<PaletteAction>) {
+        let mut current = self.actions.write();
+        for action in actions {
+            match current.iter_mut().find(|a| a.id == action.id) {
+                Some(existing) => *existing = action,
+                None => current.push(action),
+            }
+        }
+    }
+
+    /// Remove every action whose id starts with `prefix`.
+    pub fn unregister_prefix(&mut self, prefix: &str) {
+        self.actions.write().retain(|a| !a.id.starts_with(prefix));
+    }
+}
+
+/// Consume the [`CommandPaletteContext`] from context.
+pub fn use_command_palette() -> CommandPaletteContext {
+    use_context::<CommandPaletteContext>()
+}
+
+/// Provides the command palette to its children and opens it on
+/// `Ctrl+K` / `Cmd+K` anywhere in the document.
+#[component]
+pub fn CommandPaletteProvider(children: Element) -> Element {
+    let mut palette = use_context_provider(|| CommandPaletteContext {
+        open: Signal::new(false),
+        actions: Signal::new(Vec::new()),
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            let mut eval = document::eval(
+                r#"document.addEventListener('keydown', function(e) {
+                    if ((e.ctrlKey || e.metaKey) && !e.altKey && e.key.toLowerCase() === 'k') {
+                        e.preventDefault();
+                        dioxus.send(true);
+                    }
+                });"#,
+            );
+            while let Ok(true) = eval.recv::<bool>().await {
+                palette.toggle();
+            }
+        });
+    });
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: asset!("./style.css") }
+        {children}
+        if palette.is_open() {
+            CommandPalette {}
+        }
+    }
+}
+
+/// Actions whose label contains `query` (case-insensitive).
+fn filter_actions<'a>(actions: &'a [PaletteAction], query: &str) -> Vec<&'a PaletteAction> {
+    let query = query.trim().to_lowercase();
+    actions
+        .iter()
+        .filter(|a| query.is_empty() || a.label.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// Actions for `query`: matching commands, then the `note:{path}` action of
+/// each hit in `note_hits` in score order. Note actions are filtered by label
+/// like commands when there are no hits (or no note tree to search).
+fn palette_matches<'a>(actions: &'a [PaletteAction], query: &str, note_hits: &[SearchMatch]) -> Vec<&'a PaletteAction> {
+    if query.trim().is_empty() || note_hits.is_empty() {
+        return filter_actions(actions, query);
+    }
+    let mut matches = filter_actions(actions, query);
+    matches.retain(|a| !a.id.starts_with("note:"));
+    matches.extend(note_hits.iter().filter_map(|hit| {
+        let id = format!("note:{}", hit.note.path);
+        actions.iter().find(|a| a.id == id)
+    }));
+    matches
+}
+
+/// Centered search overlay listing the registered actions.
+///
+/// Notes are ranked with the local note tree's fuzzy search, so "rdmp"
+/// finds `work/roadmap.md`.
+#[component]
+fn CommandPalette() -> Element {
+    let mut palette = use_command_palette();
+    let mut query = use_signal(String::new);
+    let mut selected = use_signal(|| 0usize);
+    let note_hits = use_note_tree_search(query);
+
+    let actions = palette.actions.read();
+    let matches: Vec<PaletteAction> = palette_matches(&actions, &query(), &note_hits.read())
+        .into_iter()
+        .cloned()
+        .collect();
+    drop(actions);
+    let count = matches.len();
+
+    let mut run = move |action: &PaletteAction| {
+        palette.set_open(false);
+        action.action.call(());
+    };
+
+    rsx! {
+        div {
+            class: "command-palette-overlay",
+            onclick: move |_| palette.set_open(false),
+            div {
+                class: "command-palette",
+                onclick: move |evt: Event<MouseData>| evt.stop_propagation(),
+                input {
+                    class: "command-palette-input",
+                    r#type: "text",
+                    placeholder: "Type a command or note name...",
+                    autofocus: true,
+                    value: "{query}",
+                    oninput: move |evt: FormEvent| {
+                        query.set(evt.value());
+                        selected.set(0);
+                    },
+                    onkeydown: {
+                        let matches = matches.clone();
+                        move |evt: KeyboardEvent| match evt.key() {
+                            Key::ArrowDown if count > 0 => {
+                                evt.prevent_default();
+                                selected.set((selected() + 1) % count);
+                            }
+                            Key::ArrowUp if count > 0 => {
+                                evt.prevent_default();
+                                selected.set((selected() + count - 1) % count);
+                            }
+                            Key::Enter => {
+                                evt.prevent_default();
+                                if let Some(action) = matches.get(selected()) {
+                                    run(action);
+                                }
+                            }
+                            Key::Escape => {
+                                evt.prevent_default();
+                                palette.set_open(false);
+                            }
+                            _ => {}
+                        }
+                    },
+                }
+                ul {
+                    class: "command-palette-list",
+                    if matches.is_empty() {
+                        li { class: "command-palette-empty", "No matching commands" }
+                    }
+                    for (i, action) in matches.into_iter().enumerate() {
+                        li {
+                            key: "{action.id}",
+                            class: "command-palette-item",
+                            "data-selected": if i == selected() { "true" } else { "false" },
+                            onmouseenter: move |_| selected.set(i),
+                            onclick: {
+                                let action = action.clone();
+                                move |_| run(&action)
+                            },
+                            span { class: "command-palette-label", "{action.label}" }
+                            if let Some(shortcut) = &action.shortcut {
+                                kbd { class: "command-palette-shortcut", "{shortcut}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
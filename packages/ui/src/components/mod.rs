@@ -13,6 +13,9 @@ pub mod dropdown_menu;
 pub mod sheet;
 pub mod skeleton;
 pub mod sidebar;
+pub mod virtual_list;
+pub mod command_palette;
+pub mod breadcrumb;
 
 pub use button::*;
 pub use input::*;
@@ -29,3 +32,6 @@ pub use dropdown_menu::*;
 pub use sheet::*;
 pub use skeleton::*;
 pub use sidebar::*;
+pub use virtual_list::*;
+pub use command_palette::*;
+pub use breadcrumb::*;
@@ -0,0 +1,55 @@
+use dioxus::prelude::*;
+
+/// Scrollable list that only mounts the rows in view (plus `overscan` rows on
+/// each side). Every row must be exactly `item_height_px` tall.
+#[component]
+pub fn VirtualList(
+    item_count: usize,
+    item_height_px: f64,
+    /// Renders the row at the given index.
+    render_item: Callback<usize, Element>,
+    #[props(default = 5)] overscan: usize,
+    #[props(default)] class: String,
+) -> Element {
+    let mut scroll_top = use_signal(|| 0.0f64);
+    // Updated from the first scroll event; a guess until then
+    let mut viewport_height = use_signal(|| 600.0f64);
+
+    let visible_count = (viewport_height() / item_height_px).ceil() as usize;
+    let first_visible = (scroll_top() / item_height_px).floor() as usize;
+    let start_idx = first_visible.saturating_sub(overscan).min(item_count);
+    let end_idx = (first_visible + visible_count + overscan).min(item_count);
+    let total_height = item_count as f64 * item_height_px;
+    let offset = start_idx as f64 * item_height_px;
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: asset!("./style.css") }
+        div {
+            class: "virtual-list {class}",
+            onmounted: move |evt| async move {
+                if let Ok(rect) = evt.get_client_rect().await {
+                    viewport_height.set(rect.height());
+                }
+            },
+            onscroll: move |evt| {
+                scroll_top.set(evt.scroll_top());
+                viewport_height.set(f64::from(evt.client_height()));
+            },
+            div {
+                class: "virtual-list-spacer",
+                style: "height: {total_height}px",
+                div {
+                    class: "virtual-list-window",
+                    style: "transform: translateY({offset}px)",
+                    for idx in start_idx..end_idx {
+                        div {
+                            key: "{idx}",
+                            style: "height: {item_height_px}px",
+                            {render_item.call(idx)}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
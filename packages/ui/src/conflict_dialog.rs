@@ -0,0 +1,123 @@
+use dioxus::prelude::*;
+use similar::{ChangeTag, TextDiff};
+
+use crate::components::{Button, ButtonVariant};
+
+/// One side of the comparison: each line (`None` for padding opposite a line
+/// the other side has) and whether only this side has it.
+type DiffColumn = Vec<(Option<String>, bool)>;
+
+/// Modal content for a note that changed both here and on the remote.
+///
+/// Shows the two versions side by side, with lines only one side has
+/// highlighted, and calls `on_resolve` with the content to push: the local
+/// version, the remote one, or both joined with conflict markers.
+#[component]
+pub fn ConflictResolutionDialog(
+    conflict: api::SyncConflict,
+    on_resolve: EventHandler<String>,
+    /// Close without choosing; the note stays queued.
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let (mine, theirs) = side_by_side(&conflict.local, &conflict.remote);
+    let local = conflict.local.clone();
+    let remote = conflict.remote.clone();
+    let merged = merge_with_markers(&conflict.local, &conflict.remote);
+
+    rsx! {
+        div {
+            class: "modal-body conflict-dialog",
+            h2 { class: "modal-title", "Sync conflict" }
+            p {
+                class: "modal-text",
+                strong { "{conflict.path}" }
+                " was changed on another device since you started editing."
+            }
+
+            div {
+                class: "conflict-columns",
+                ConflictColumn { title: "Mine", lines: mine, changed_class: "diff-add" }
+                ConflictColumn { title: "Remote", lines: theirs, changed_class: "diff-remove" }
+            }
+
+            div {
+                class: "modal-actions",
+                Button {
+                    variant: ButtonVariant::Primary,
+                    onclick: move |_| on_resolve.call(local.clone()),
+                    "Keep mine"
+                }
+                Button {
+                    variant: ButtonVariant::Secondary,
+                    onclick: move |_| on_resolve.call(remote.clone()),
+                    "Use remote"
+                }
+                Button {
+                    variant: ButtonVariant::Outline,
+                    onclick: move |_| on_resolve.call(merged.clone()),
+                    "Merge"
+                }
+                Button {
+                    variant: ButtonVariant::Ghost,
+                    onclick: move |_| on_cancel.call(()),
+                    "Later"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ConflictColumn(title: String, lines: DiffColumn, changed_class: String) -> Element {
+    rsx! {
+        div {
+            class: "conflict-column",
+            div { class: "diff-file-path", "{title}" }
+            pre {
+                class: "diff-body",
+                for (i, (line, changed)) in lines.iter().enumerate() {
+                    span {
+                        key: "{i}",
+                        class: if *changed { changed_class.clone() } else { "diff-context".to_string() },
+                        {line.clone().unwrap_or_default()}
+                        "\n"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Line-aligned columns for `local` and `remote`.
+fn side_by_side(local: &str, remote: &str) -> (DiffColumn, DiffColumn) {
+    let mut mine = Vec::new();
+    let mut theirs = Vec::new();
+    for change in TextDiff::from_lines(remote, local).iter_all_changes() {
+        let text = change.value().trim_end_matches(['\n', '\r']).to_string();
+        match change.tag() {
+            ChangeTag::Equal => {
+                mine.push((Some(text.clone()), false));
+                theirs.push((Some(text), false));
+            }
+            ChangeTag::Insert => {
+                mine.push((Some(text), true));
+                theirs.push((None, false));
+            }
+            ChangeTag::Delete => {
+                mine.push((None, false));
+                theirs.push((Some(text), true));
+            }
+        }
+    }
+    (mine, theirs)
+}
+
+/// Both versions, one after the other, between git-style conflict markers.
+fn merge_with_markers(local: &str, remote: &str) -> String {
+    let newline = |s: &str| if s.ends_with('\n') { "" } else { "\n" };
+    format!(
+        "<<<<<<< mine\n{local}{}=======\n{remote}{}>>>>>>> remote\n",
+        newline(local),
+        newline(remote),
+    )
+}
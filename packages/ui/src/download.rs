@@ -0,0 +1,57 @@
+//! Saving generated files (exports, logs) where the user can find them.
+//!
+//! In the browser the bytes become a regular download. Desktop and mobile
+//! builds have no browser download manager, so the file is written to the
+//! user's downloads folder instead.
+
+#[cfg(target_arch = "wasm32")]
+use dioxus::prelude::*;
+
+/// Hand `bytes` to the browser as a file download (Blob + object URL).
+///
+/// Returns where the file went, for status messages: the file name here,
+/// since the browser decides the folder.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn download_bytes(bytes: Vec<u8>, filename: &str, mime: &str) -> Result<String, String> {
+    let eval = document::eval(&format!(
+        r#"
+        const data = await dioxus.recv();
+        const blob = new Blob([new Uint8Array(data)], {{ type: "{mime}" }});
+        const url = URL.createObjectURL(blob);
+        const a = document.createElement("a");
+        a.href = url;
+        a.download = "{filename}";
+        document.body.appendChild(a);
+        a.click();
+        a.remove();
+        URL.revokeObjectURL(url);
+        "#
+    ));
+    let _ = eval.send(bytes);
+    Ok(filename.to_string())
+}
+
+/// Write `bytes` to the downloads folder as `filename`, adding ` (1)`,
+/// ` (2)`, … before the extension rather than overwriting a file.
+///
+/// Returns the full path written.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn download_bytes(bytes: Vec<u8>, filename: &str, _mime: &str) -> Result<String, String> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "No downloads folder on this device".to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let (stem, ext) = match filename.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (filename, String::new()),
+    };
+    let mut path = dir.join(filename);
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{stem} ({n}){ext}"));
+        n += 1;
+    }
+    std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
@@ -0,0 +1,61 @@
+use dioxus::prelude::*;
+
+use crate::components::{Button, ButtonVariant};
+use crate::icons::{FaCompress, FaExpand};
+use crate::Icon;
+
+/// Distraction-free editing flag, provided as `Signal<FocusMode>` via context
+/// in `SidebarLayoutView`.
+///
+/// While set, the layout hides the sidebar, the header bar and the activity
+/// log, and the editor gets a larger font. The flag only lives in memory: it
+/// survives switching notes but not a page reload.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FocusMode(pub bool);
+
+/// The focus mode flag of the enclosing layout.
+pub fn use_focus_mode() -> Signal<FocusMode> {
+    use_context::<Signal<FocusMode>>()
+}
+
+/// Enter or leave focus mode. On web, entering also requests browser
+/// fullscreen and leaving exits it.
+pub fn set_focus_mode(mut focus_mode: Signal<FocusMode>, on: bool) {
+    if focus_mode.peek().0 == on {
+        return;
+    }
+    focus_mode.set(FocusMode(on));
+    #[cfg(target_arch = "wasm32")]
+    {
+        let js = if on {
+            "if (document.documentElement.requestFullscreen) document.documentElement.requestFullscreen().catch(function() {});"
+        } else {
+            "if (document.fullscreenElement) document.exitFullscreen().catch(function() {});"
+        };
+        document::eval(js);
+    }
+}
+
+/// Button in the note header that toggles focus mode (`Ctrl+Shift+F`).
+///
+/// Renders nothing outside a layout that provides the focus mode context.
+#[component]
+pub fn FocusModeToggle() -> Element {
+    let Some(focus_mode) = try_use_context::<Signal<FocusMode>>() else {
+        return rsx! {};
+    };
+    let on = focus_mode().0;
+
+    rsx! {
+        Button {
+            variant: ButtonVariant::Ghost,
+            title: if on { "Exit focus mode (Esc)" } else { "Focus mode (Ctrl+Shift+F)" },
+            onclick: move |_| set_focus_mode(focus_mode, !on),
+            if on {
+                Icon { icon: FaCompress, width: 14, height: 14 }
+            } else {
+                Icon { icon: FaExpand, width: 14, height: 14 }
+            }
+        }
+    }
+}
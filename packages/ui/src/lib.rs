@@ -27,25 +27,44 @@ mod auth;
 pub use auth::{use_auth, AuthProvider, AuthState, LoginButton, LogoutButton};
 
 mod online_indicator;
-pub use online_indicator::OnlineIndicator;
+pub use online_indicator::{OnlineIndicator, use_online_status};
 
 mod sidebar;
-pub use sidebar::{AppSidebar, ThemeSignal, load_theme_from_storage, apply_theme};
+pub use sidebar::{AppSidebar, RevealNamespace, ThemeSignal, load_theme_from_storage, apply_theme};
 
 mod note_editor;
 pub use note_editor::NoteEditor;
 
 pub mod markdown_editor;
-pub use markdown_editor::MarkdownEditor;
+pub use markdown_editor::{MarkdownEditor, MarkdownEditorTheme, MobileToolbar, ToolbarEvent};
 
 mod new_note_dialog;
 pub use new_note_dialog::NewNoteDialog;
 
+mod conflict_dialog;
+pub use conflict_dialog::ConflictResolutionDialog;
+
 pub mod activity_log;
-pub use activity_log::{ActivityLog, LogLevel, log_activity, use_activity_log};
+pub use activity_log::{ActivityLog, LogLevel, log_activity, use_activity_log, load_activity_log_from_storage};
+
+mod namespace_style;
+pub use namespace_style::{NamespaceIcon, NamespaceStyleSheet, StylingNamespace, NAMESPACE_COLORS, NAMESPACE_ICONS};
 
 mod note_tree;
-pub use note_tree::{NoteTree, use_note_tree};
+pub use note_tree::{NoteTree, SearchMatch, use_note_tree, use_note_tree_search};
+
+mod recent_notes;
+pub use recent_notes::{RecentNotes, use_recent_notes, MAX_RECENT_NOTES};
+
+mod focus_mode;
+pub use focus_mode::{FocusMode, FocusModeToggle, use_focus_mode, set_focus_mode};
+
+mod sync_queue;
+pub use sync_queue::{SyncQueue, use_sync_queue, flush_sync_queue, load_remote_shas, pull_and_merge, pull_changes, save_remote_shas};
+
+mod note_crypto;
+
+mod download;
 
 mod activity_log_panel;
 pub use activity_log_panel::{ActivityLogPanel, ActivityLogToggle};
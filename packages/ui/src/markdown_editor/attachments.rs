@@ -0,0 +1,79 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use dioxus::prelude::*;
+
+use super::component::js_string_escape;
+
+/// Upload a file with [`api::upload_attachment`] and return the markdown
+/// that links it (an image embed for images).
+pub(super) async fn upload_attachment(name: String, bytes: Vec<u8>) -> Result<String, String> {
+    let url = api::upload_attachment(name.clone(), bytes)
+        .await
+        .map_err(|e| format!("Could not attach {name}: {e}"))?;
+    let stored = url.rsplit('/').next().unwrap_or(&name);
+    Ok(api::attachments::attachment_markdown(stored, &url))
+}
+
+/// Markdown for an image pasted as the base64 `data_url`: embedded as the data
+/// URL itself when it decodes to fewer than `inline_max` bytes, otherwise
+/// uploaded as attachment `name`.
+pub(super) async fn pasted_image_markdown(
+    name: String,
+    data_url: &str,
+    inline_max: usize,
+) -> Result<String, String> {
+    let (_, encoded) = data_url
+        .split_once(";base64,")
+        .ok_or_else(|| "Pasted image is not base64 data".to_string())?;
+    let bytes = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Could not read pasted image: {e}"))?;
+    if bytes.len() < inline_max {
+        return Ok(format!("![pasted image]({data_url})"));
+    }
+    upload_attachment(name, bytes).await
+}
+
+/// Replace the selection of the CM6 editor in `editor_id` with `text`.
+pub(super) fn insert_at_cursor(editor_id: &str, text: &str) {
+    let js = format!(
+        r#"(function() {{
+            var container = document.getElementById({eid_js});
+            if (!container || !container._cm) return;
+            var view = container._cm.view;
+            view.dispatch(view.state.replaceSelection({text_js}), {{
+                scrollIntoView: true,
+                userEvent: 'input.paste',
+            }});
+            view.focus();
+        }})();"#,
+        eid_js = js_string_escape(editor_id),
+        text_js = js_string_escape(text),
+    );
+    document::eval(&js);
+}
+
+/// Insert `text` into the CM6 editor in `editor_id` at the document position
+/// under the viewport point `(x, y)`, or at the cursor if there is none.
+pub(super) fn insert_at_coords(editor_id: &str, x: f64, y: f64, text: &str) {
+    let js = format!(
+        r#"(function() {{
+            var container = document.getElementById({eid_js});
+            if (!container || !container._cm) return;
+            var view = container._cm.view;
+            var text = {text_js};
+            var pos = view.posAtCoords({{ x: {x}, y: {y} }});
+            if (pos == null) pos = view.state.selection.main.head;
+            view.dispatch({{
+                changes: {{ from: pos, insert: text }},
+                selection: {{ anchor: pos + text.length }},
+                scrollIntoView: true,
+                userEvent: 'input.drop',
+            }});
+            view.focus();
+        }})();"#,
+        eid_js = js_string_escape(editor_id),
+        text_js = js_string_escape(text),
+    );
+    document::eval(&js);
+}
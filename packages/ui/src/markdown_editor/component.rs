@@ -1,5 +1,16 @@
 use dioxus::prelude::*;
 
+use super::attachments::{insert_at_coords, insert_at_cursor, pasted_image_markdown, upload_attachment};
+use super::find::{highlight_matches, FindBar, FindState};
+use super::table::{insert_block_at_cursor, InsertTableDialog};
+use super::theme::{inject_editor_theme, MarkdownEditorTheme};
+use super::toolbar::{apply_toolbar_event, MobileToolbar, ToolbarEvent};
+use crate::icons::FaTable;
+use crate::note_tree::NoteTree;
+use crate::views::ModalOverlay;
+use crate::Icon;
+use store::config::DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD;
+
 const STYLE_CSS: Asset = asset!("./style.css");
 
 /// KaTeX CDN URLs
@@ -10,11 +21,14 @@ const KATEX_JS: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.28/dist/katex.mi
 const HLJS_CSS: &str = "https://cdn.jsdelivr.net/gh/highlightjs/cdn-release@11.11.1/build/styles/github.min.css";
 const HLJS_JS: &str = "https://cdn.jsdelivr.net/gh/highlightjs/cdn-release@11.11.1/build/highlight.min.js";
 
+/// Mermaid CDN URL (loaded after the editor is up; diagrams render once it arrives)
+const MERMAID_JS: &str = "https://cdn.jsdelivr.net/npm/mermaid@11.4.1/dist/mermaid.min.js";
+
 /// Simple counter for unique IDs
 static EDITOR_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
 /// Escape a string so it's safe to embed inside a JS string literal (double-quoted).
-fn js_string_escape(s: &str) -> String {
+pub(super) fn js_string_escape(s: &str) -> String {
     let mut out = String::with_capacity(s.len() + 2);
     out.push('"');
     for c in s.chars() {
@@ -34,12 +48,56 @@ fn js_string_escape(s: &str) -> String {
     out
 }
 
+/// Live-preview markdown editor backed by CodeMirror 6.
+///
+/// Undo/redo (`Ctrl/Cmd+Z`, `Ctrl/Cmd+Shift+Z`, `Ctrl+Y`) come from the CM6
+/// history extension and span the whole document; `history_depth` sets how
+/// many steps are kept. `Ctrl/Cmd+B`, `Ctrl/Cmd+I` and ``Ctrl/Cmd+` `` wrap the
+/// selection in bold, italic or inline-code markers.
+///
+/// `Ctrl/Cmd+F` opens a find bar and `Ctrl+H` a find/replace bar (see
+/// [`FindState`]). The toolbar's table button opens an [`InsertTableDialog`];
+/// inside a table `Tab`/`Shift+Tab` move between cells.
+///
+/// With `vim_mode`, the editor starts in Vim Normal mode (`h/j/k/l`, `i`/`a`,
+/// `o`/`O`, `dd`, `v`, counts) and a status bar below it shows the current
+/// [`VimModeKind`]. The flag can be toggled without recreating the editor.
+///
+/// `[[note name]]` wiki links render as links outside the cursor; typing `[[`
+/// offers `link_targets` as completions, and clicking a link calls
+/// `on_navigate_note` with its target.
+///
+/// Files dropped from the OS are uploaded as attachments (see
+/// [`api::upload_attachment`]) and linked where they land: images as
+/// `![name](./assets/…)`, anything else as a link. A pasted image is inserted
+/// at the cursor, embedded as a `data:` URL when smaller than the config's
+/// `paste_image_inline_threshold_bytes` and uploaded like a dropped file
+/// otherwise. Failures go to `on_attachment_error`.
+///
+/// With `mobile_toolbar` (always on Android and iOS) a [`MobileToolbar`] of
+/// formatting buttons sits above the editor in place of the shortcuts.
+///
+/// Block colors come from `--md-*` CSS variables with light and dark
+/// defaults; a `theme` overrides them for this editor.
 #[component]
 pub fn MarkdownEditor(
     mut content: Signal<String>,
     on_change: EventHandler<String>,
     #[props(default)] on_blur: EventHandler<()>,
     #[props(default = "Start writing...".to_string())] placeholder: String,
+    #[props(default = 100)] history_depth: u32,
+    /// Note names offered when typing `[[`.
+    #[props(default)] link_targets: Vec<String>,
+    /// Called with the target of a clicked `[[wiki link]]`.
+    #[props(default)] on_navigate_note: EventHandler<String>,
+    /// Enable Vim keybindings.
+    #[props(default)] vim_mode: bool,
+    /// Show the touch formatting toolbar.
+    #[props(default)] mobile_toolbar: bool,
+    /// Called with a message when a dropped or pasted file could not be attached.
+    #[props(default)] on_attachment_error: EventHandler<String>,
+    /// Custom block colors; `None` keeps the default light/dark variables.
+    #[props(default)] theme: Option<MarkdownEditorTheme>,
 ) -> Element {
     let editor_id = use_signal(|| {
         let n = EDITOR_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -52,6 +110,27 @@ pub fn MarkdownEditor(
     let mut libs_ready = use_signal(|| false);
     // Track latest content to avoid echo loops
     let mut last_pushed = use_signal(|| String::new());
+    // Find/replace bar state (None = closed)
+    let mut find: Signal<Option<FindState>> = use_context_provider(|| Signal::new(None));
+    let mut show_table_dialog = use_signal(|| false);
+    let mut toolbar_event: Signal<Option<ToolbarEvent>> = use_signal(|| None);
+    let show_mobile_toolbar = mobile_toolbar || cfg!(any(target_os = "android", target_os = "ios"));
+    // Mirror the prop so the effect below re-runs when the note list changes
+    let mut wiki_targets = use_signal(|| link_targets.clone());
+    if *wiki_targets.peek() != link_targets {
+        wiki_targets.set(link_targets.clone());
+    }
+    let mut vim_enabled = use_signal(|| vim_mode);
+    if *vim_enabled.peek() != vim_mode {
+        vim_enabled.set(vim_mode);
+    }
+    let mut vim_state = use_signal(|| VimModeKind::Normal);
+    let mut custom_theme = use_signal(|| theme.clone());
+    if *custom_theme.peek() != theme {
+        custom_theme.set(theme.clone());
+    }
+    // Source of the paste-inline threshold, when inside the note views
+    let note_tree = try_use_context::<Signal<NoteTree>>();
 
     // ── Load KaTeX + highlight.js CSS + JS once (with onload tracking) ──
     use_effect(move || {
@@ -109,6 +188,26 @@ pub fn MarkdownEditor(
         });
     });
 
+    // ── Load Mermaid once, without holding up the editor ──
+    use_effect(move || {
+        let js = format!(
+            r#"(function() {{
+                if (document.getElementById('mermaid-js')) return;
+                var script = document.createElement('script');
+                script.id = 'mermaid-js';
+                script.src = '{MERMAID_JS}';
+                script.onload = function() {{
+                    var theme = document.documentElement.getAttribute('data-theme');
+                    var dark = theme ? theme === 'dark' : window.matchMedia('(prefers-color-scheme: dark)').matches;
+                    mermaid.initialize({{ startOnLoad: false, theme: dark ? 'dark' : 'default' }});
+                    window.dispatchEvent(new Event('typednotes:mermaid-ready'));
+                }};
+                document.head.appendChild(script);
+            }})();"#,
+        );
+        document::eval(&js);
+    });
+
     // ── Load CM6 bundle script once ──
     use_effect(move || {
         let js = r#"(function() {
@@ -157,6 +256,7 @@ pub fn MarkdownEditor(
                     var editor = TypedNotesCM.createEditor(container, {{
                         content: {content_js},
                         placeholder: {ph_js},
+                        historyDepth: {history_depth},
                         onChange: function(text) {{
                             container._cmLastContent = text;
                             if (container._cmChangeTimer) clearTimeout(container._cmChangeTimer);
@@ -166,13 +266,67 @@ pub fn MarkdownEditor(
                         }},
                         onBlur: function() {{
                             if (container._cmOnBlur) container._cmOnBlur();
+                        }},
+                        getWikiTargets: function() {{
+                            return container._cmWikiTargets || [];
+                        }},
+                        onWikiLink: function(target) {{
+                            if (container._cmOnWikiLink) container._cmOnWikiLink(target);
+                        }},
+                        vimMode: {vim},
+                        onVimModeChange: function(mode) {{
+                            if (container._cmOnVimMode) container._cmOnVimMode(mode);
                         }}
                     }});
                     container._cm = editor;
+                    // Dropped files become attachments (handled in Rust);
+                    // keep CM6 from inserting them as text
+                    container.addEventListener('drop', function(e) {{
+                        if (e.dataTransfer && e.dataTransfer.files.length) e.preventDefault();
+                    }}, true);
+                    // Pasted images likewise go to Rust as data URLs
+                    function pastedImage(data) {{
+                        if (!data) return null;
+                        for (var i = 0; i < data.items.length; i++) {{
+                            var item = data.items[i];
+                            if (item.kind === 'file' && item.type.indexOf('image/') === 0) return item.getAsFile();
+                        }}
+                        return null;
+                    }}
+                    function sendImage(blob) {{
+                        var ext = blob.type.split('/')[1].replace('jpeg', 'jpg').replace('svg+xml', 'svg');
+                        var reader = new FileReader();
+                        reader.onload = function() {{
+                            if (container._cmOnPasteImage) {{
+                                container._cmOnPasteImage(['pasted-' + Date.now() + '.' + ext, reader.result]);
+                            }}
+                        }};
+                        reader.readAsDataURL(blob);
+                    }}
+                    container.addEventListener('paste', function(e) {{
+                        var image = pastedImage(e.clipboardData);
+                        if (image) {{ e.preventDefault(); e.stopPropagation(); sendImage(image); }}
+                    }}, true);
+                    container.addEventListener('beforeinput', function(e) {{
+                        if (e.inputType !== 'insertFromPaste') return;
+                        if (e.dataTransfer) {{
+                            var image = pastedImage(e.dataTransfer);
+                            if (image) {{ e.preventDefault(); sendImage(image); }}
+                        }} else if (navigator.clipboard && navigator.clipboard.read) {{
+                            // No data on the event (some mobile browsers): ask the clipboard
+                            navigator.clipboard.read().then(function(items) {{
+                                for (var i = 0; i < items.length; i++) {{
+                                    var type = items[i].types.find(function(t) {{ return t.indexOf('image/') === 0; }});
+                                    if (type) return items[i].getType(type).then(sendImage);
+                                }}
+                            }}).catch(function() {{}});
+                        }}
+                    }}, true);
                 }})();"#,
                 eid_js = js_string_escape(&eid),
                 content_js = js_string_escape(&text),
                 ph_js = js_string_escape(&ph),
+                vim = *vim_enabled.peek(),
             );
             document::eval(&js);
 
@@ -230,9 +384,119 @@ pub fn MarkdownEditor(
                     }
                 }
             });
+
+            // Wiki link click bridge
+            let eid4 = eid.clone();
+            spawn(async move {
+                let bridge_js = format!(
+                    r#"(function() {{
+                        var container = document.getElementById({eid_js});
+                        if (!container) return;
+                        container._cmOnWikiLink = function(target) {{
+                            dioxus.send(target);
+                        }};
+                    }})();"#,
+                    eid_js = js_string_escape(&eid4),
+                );
+                let mut eval = document::eval(&bridge_js);
+                while let Ok(target) = eval.recv::<String>().await {
+                    on_navigate_note.call(target);
+                }
+            });
+
+            // Vim mode bridge
+            let eid5 = eid.clone();
+            spawn(async move {
+                let bridge_js = format!(
+                    r#"(function() {{
+                        var container = document.getElementById({eid_js});
+                        if (!container) return;
+                        container._cmOnVimMode = function(mode) {{
+                            dioxus.send(mode);
+                        }};
+                    }})();"#,
+                    eid_js = js_string_escape(&eid5),
+                );
+                let mut eval = document::eval(&bridge_js);
+                while let Ok(mode) = eval.recv::<String>().await {
+                    vim_state.set(VimModeKind::from_name(&mode));
+                }
+            });
+
+            // Pasted image bridge
+            let eid6 = eid.clone();
+            spawn(async move {
+                let bridge_js = format!(
+                    r#"(function() {{
+                        var container = document.getElementById({eid_js});
+                        if (!container) return;
+                        container._cmOnPasteImage = function(image) {{
+                            dioxus.send(image);
+                        }};
+                    }})();"#,
+                    eid_js = js_string_escape(&eid6),
+                );
+                let mut eval = document::eval(&bridge_js);
+                while let Ok((name, data_url)) = eval.recv::<(String, String)>().await {
+                    let inline_max = note_tree.map_or(DEFAULT_PASTE_IMAGE_INLINE_THRESHOLD, |tree| {
+                        tree.peek().config.paste_image_inline_threshold_bytes
+                    });
+                    let eid = eid6.clone();
+                    spawn(async move {
+                        match pasted_image_markdown(name, &data_url, inline_max).await {
+                            Ok(markdown) => insert_at_cursor(&eid, &markdown),
+                            Err(e) => on_attachment_error.call(e),
+                        }
+                    });
+                }
+            });
         });
     }
 
+    // ── Switch Vim keybindings on/off in the live editor ──
+    use_effect(move || {
+        let on = vim_enabled();
+        if !initialized() {
+            return;
+        }
+        // A freshly enabled editor always starts in Normal mode
+        vim_state.set(VimModeKind::Normal);
+        let js = format!(
+            r#"(function() {{
+                var container = document.getElementById({eid_js});
+                // Bundles built before `make cm6` picked up vim.js lack it
+                if (container && container._cm && container._cm.setVimMode) container._cm.setVimMode({on});
+            }})();"#,
+            eid_js = js_string_escape(&editor_id.peek()),
+        );
+        document::eval(&js);
+    });
+
+    // ── Inject the custom theme, or drop a previous one ──
+    use_effect(move || {
+        inject_editor_theme(&editor_id.peek(), custom_theme.read().as_ref());
+    });
+    let theme_owner = editor_id.peek().clone();
+    use_drop(move || inject_editor_theme(&theme_owner, None));
+
+    // ── Hand the current note names to the `[[` completion source ──
+    use_effect(move || {
+        let targets = wiki_targets();
+        if !initialized() {
+            return;
+        }
+        let list: Vec<String> = targets.iter().map(|t| js_string_escape(t)).collect();
+        let js = format!(
+            r#"(function() {{
+                var container = document.getElementById({eid_js});
+                if (container) container._cmWikiTargets = [{list}];
+            }})();"#,
+            eid_js = js_string_escape(&editor_id.peek()),
+            list = list.join(","),
+        );
+        document::eval(&js);
+    });
+
     // ── Sync external content changes into CM6 ──
     // When content signal changes from outside (e.g., navigating to another note),
     // push the new content into the CM6 editor.
@@ -260,11 +524,162 @@ pub fn MarkdownEditor(
         document::eval(&js);
     });
 
+    // ── Apply mobile toolbar actions ──
+    use_effect(move || {
+        let Some(event) = toolbar_event() else {
+            return;
+        };
+        toolbar_event.set(None);
+        if !*initialized.peek() {
+            return;
+        }
+        match event {
+            ToolbarEvent::Table => show_table_dialog.set(true),
+            _ => apply_toolbar_event(&editor_id.peek(), event),
+        }
+    });
+
+    // ── Find/replace: rescan on edits, repaint on every state change ──
+    use_effect(move || {
+        let text = content();
+        if find.peek().is_some() {
+            if let Some(state) = find.write().as_mut() {
+                state.update_matches(&text);
+            }
+        }
+    });
+    use_effect(move || {
+        let state = find();
+        if !initialized() {
+            return;
+        }
+        highlight_matches(&editor_id.peek(), &content.peek(), state.as_ref());
+    });
+
+    let open_find = move |evt: KeyboardEvent| {
+        let modifiers = evt.modifiers();
+        let replace_mode = match evt.key() {
+            Key::Character(c) if c.eq_ignore_ascii_case("f") && (modifiers.ctrl() || modifiers.meta()) => false,
+            Key::Character(c) if c.eq_ignore_ascii_case("h") && modifiers.ctrl() => true,
+            _ => return,
+        };
+        evt.prevent_default();
+        let mut state = find.peek().clone().unwrap_or_else(|| FindState::new(replace_mode));
+        state.replace_mode = replace_mode;
+        state.update_matches(&content.peek());
+        find.set(Some(state));
+    };
+
     rsx! {
         document::Link { rel: "stylesheet", href: STYLE_CSS }
         div {
-            id: "{editor_id}",
-            class: "cm-wrapper",
+            class: "cm-host",
+            "data-editor": "{editor_id}",
+            onkeydown: open_find,
+            div {
+                class: "md-editor-toolbar",
+                button {
+                    class: "md-editor-toolbar-button",
+                    title: "Insert table",
+                    onclick: move |_| show_table_dialog.set(true),
+                    Icon { icon: FaTable, width: 12, height: 12 }
+                }
+            }
+            if show_mobile_toolbar {
+                MobileToolbar { events: toolbar_event }
+            }
+            FindBar {
+                content: content,
+                on_replace: move |text: String| {
+                    content.set(text.clone());
+                    on_change.call(text);
+                },
+            }
+            div {
+                id: "{editor_id}",
+                class: "cm-wrapper",
+                ondrop: move |evt: Event<DragData>| {
+                    let files = evt.files();
+                    if files.is_empty() {
+                        return;
+                    }
+                    evt.prevent_default();
+                    let point = evt.client_coordinates();
+                    spawn(async move {
+                        let mut links = Vec::new();
+                        for file in files {
+                            let name = file.name();
+                            let result = match file.read_bytes().await {
+                                Ok(bytes) => upload_attachment(name.clone(), bytes.to_vec()).await,
+                                Err(e) => Err(format!("Could not read {name}: {e}")),
+                            };
+                            match result {
+                                Ok(markdown) => links.push(markdown),
+                                Err(e) => on_attachment_error.call(e),
+                            }
+                        }
+                        if !links.is_empty() {
+                            insert_at_coords(&editor_id.peek(), point.x, point.y, &links.join("\n"));
+                        }
+                    });
+                },
+            }
+            if vim_enabled() {
+                div {
+                    class: "md-vim-status",
+                    "data-vim-mode": vim_state().name(),
+                    "-- {vim_state().label()} --"
+                }
+            }
+        }
+        if show_table_dialog() {
+            ModalOverlay {
+                on_close: move |_| show_table_dialog.set(false),
+                InsertTableDialog {
+                    on_insert: move |markdown: String| {
+                        show_table_dialog.set(false);
+                        insert_block_at_cursor(&editor_id.peek(), &markdown);
+                    },
+                    on_cancel: move |_| show_table_dialog.set(false),
+                }
+            }
+        }
+    }
+}
+
+/// Vim mode of a [`MarkdownEditor`] with `vim_mode` enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VimModeKind {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+}
+
+impl VimModeKind {
+    /// Parse the mode name reported by the CM6 bundle; unknown names map to Normal.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "insert" => Self::Insert,
+            "visual" => Self::Visual,
+            _ => Self::Normal,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Insert => "insert",
+            Self::Visual => "visual",
+        }
+    }
+
+    /// Status bar text, as Vim shows it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Insert => "INSERT",
+            Self::Visual => "VISUAL",
         }
     }
 }
@@ -0,0 +1,333 @@
+use dioxus::prelude::*;
+
+use super::component::js_string_escape;
+
+/// State of the find/replace bar, provided as `Signal<Option<FindState>>`
+/// context by [`super::MarkdownEditor`] (`None` = bar closed).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FindState {
+    pub query: String,
+    pub replacement: String,
+    pub case_sensitive: bool,
+    /// Byte offsets of every (non-overlapping) match in the content.
+    pub match_indices: Vec<usize>,
+    /// Index into `match_indices` of the selected match.
+    pub current_match: usize,
+    /// Whether the replace row is shown (opened with `Ctrl+H`).
+    pub replace_mode: bool,
+}
+
+impl FindState {
+    pub fn new(replace_mode: bool) -> Self {
+        Self {
+            replace_mode,
+            ..Self::default()
+        }
+    }
+
+    /// Rescan `content` for the query, keeping the current match in range.
+    pub fn update_matches(&mut self, content: &str) {
+        self.match_indices.clear();
+        if !self.query.is_empty() {
+            let mut at = 0;
+            while at < content.len() {
+                match match_len(&content[at..], &self.query, self.case_sensitive) {
+                    Some(len) => {
+                        self.match_indices.push(at);
+                        at += len;
+                    }
+                    None => at += content[at..].chars().next().map_or(1, char::len_utf8),
+                }
+            }
+        }
+        if self.current_match >= self.match_indices.len() {
+            self.current_match = 0;
+        }
+    }
+
+    pub fn next(&mut self) {
+        if !self.match_indices.is_empty() {
+            self.current_match = (self.current_match + 1) % self.match_indices.len();
+        }
+    }
+
+    pub fn prev(&mut self) {
+        if !self.match_indices.is_empty() {
+            let n = self.match_indices.len();
+            self.current_match = (self.current_match + n - 1) % n;
+        }
+    }
+
+    /// Byte ranges of all matches in `content`.
+    fn ranges(&self, content: &str) -> Vec<(usize, usize)> {
+        self.match_indices
+            .iter()
+            .filter_map(|&at| {
+                let len = match_len(content.get(at..)?, &self.query, self.case_sensitive)?;
+                Some((at, at + len))
+            })
+            .collect()
+    }
+
+    /// `content` with the current match replaced, or `None` if there is none.
+    pub fn replace_current(&self, content: &str) -> Option<String> {
+        let at = *self.match_indices.get(self.current_match)?;
+        let len = match_len(content.get(at..)?, &self.query, self.case_sensitive)?;
+        let mut out = String::with_capacity(content.len() + self.replacement.len());
+        out.push_str(&content[..at]);
+        out.push_str(&self.replacement);
+        out.push_str(&content[at + len..]);
+        Some(out)
+    }
+
+    /// `content` with every match replaced.
+    pub fn replace_all(&self, content: &str) -> String {
+        let mut out = String::with_capacity(content.len());
+        let mut last = 0;
+        for (start, end) in self.ranges(content) {
+            out.push_str(&content[last..start]);
+            out.push_str(&self.replacement);
+            last = end;
+        }
+        out.push_str(&content[last..]);
+        out
+    }
+}
+
+/// Length in bytes of `needle` matched at the start of `haystack`, comparing
+/// char by char (lowercased unless `case_sensitive`).
+fn match_len(haystack: &str, needle: &str, case_sensitive: bool) -> Option<usize> {
+    let mut hay = haystack.char_indices();
+    for n in needle.chars() {
+        let (_, h) = hay.next()?;
+        let same = if case_sensitive {
+            h == n
+        } else {
+            h == n || h.to_lowercase().eq(n.to_lowercase())
+        };
+        if !same {
+            return None;
+        }
+    }
+    Some(hay.next().map_or(haystack.len(), |(i, _)| i))
+}
+
+/// Convert a byte offset in `text` to the UTF-16 offset CodeMirror uses.
+fn utf16_offset(text: &str, byte: usize) -> usize {
+    text[..byte].encode_utf16().count()
+}
+
+/// Select the current match in the CM6 editor and paint all visible matches
+/// with the CSS Custom Highlight API (`::highlight(tn-find)`), leaving focus in
+/// the find bar. Passing `None` clears the highlights.
+pub(super) fn highlight_matches(editor_id: &str, content: &str, state: Option<&FindState>) {
+    let (ranges, current) = match state {
+        Some(state) => {
+            let ranges: Vec<String> = state
+                .ranges(content)
+                .into_iter()
+                .map(|(s, e)| format!("[{},{}]", utf16_offset(content, s), utf16_offset(content, e)))
+                .collect();
+            (ranges, state.current_match)
+        }
+        None => (Vec::new(), 0),
+    };
+    let js = format!(
+        r#"(function() {{
+            var container = document.getElementById({eid_js});
+            if (typeof CSS === 'undefined' || !CSS.highlights) return;
+            CSS.highlights.delete('tn-find');
+            CSS.highlights.delete('tn-find-current');
+            if (!container || !container._cm) return;
+            var view = container._cm.view;
+            var ranges = [{ranges}];
+            var current = ranges[{current}];
+            // Don't move the cursor while the user is typing in the editor
+            if (current && !view.hasFocus) {{
+                view.dispatch({{
+                    selection: {{ anchor: current[0], head: current[1] }},
+                    scrollIntoView: true,
+                    userEvent: 'select.search'
+                }});
+            }}
+            // Only rendered lines can be mapped to DOM ranges
+            requestAnimationFrame(function() {{
+                var all = new Highlight();
+                var cur = new Highlight();
+                ranges.forEach(function(r, i) {{
+                    if (r[0] < view.viewport.from || r[1] > view.viewport.to) return;
+                    try {{
+                        var start = view.domAtPos(r[0]);
+                        var end = view.domAtPos(r[1]);
+                        var range = document.createRange();
+                        range.setStart(start.node, start.offset);
+                        range.setEnd(end.node, end.offset);
+                        (i === {current} ? cur : all).add(range);
+                    }} catch (e) {{}}
+                }});
+                CSS.highlights.set('tn-find', all);
+                CSS.highlights.set('tn-find-current', cur);
+            }});
+        }})();"#,
+        eid_js = js_string_escape(editor_id),
+        ranges = ranges.join(","),
+    );
+    document::eval(&js);
+}
+
+/// Floating find/replace bar shown at the top of the editor.
+#[component]
+pub(super) fn FindBar(
+    /// Editor content the bar searches and edits.
+    content: Signal<String>,
+    /// Called with the new content after a replacement.
+    on_replace: EventHandler<String>,
+) -> Element {
+    let mut find = use_context::<Signal<Option<FindState>>>();
+    let Some(state) = find() else {
+        return rsx! {};
+    };
+
+    let mut update = move |f: &dyn Fn(&mut FindState)| {
+        let text = content.peek().clone();
+        if let Some(state) = find.write().as_mut() {
+            f(state);
+            state.update_matches(&text);
+        }
+    };
+
+    let count_label = if state.query.is_empty() {
+        String::new()
+    } else if state.match_indices.is_empty() {
+        "No results".to_string()
+    } else {
+        format!("{} of {}", state.current_match + 1, state.match_indices.len())
+    };
+
+    let replace_one = move |_| {
+        let Some(state) = find() else { return };
+        if let Some(new_content) = state.replace_current(&content.peek()) {
+            on_replace.call(new_content);
+        }
+    };
+
+    let replace_all = move |_| {
+        let Some(state) = find() else { return };
+        if !state.match_indices.is_empty() {
+            on_replace.call(state.replace_all(&content.peek()));
+        }
+    };
+
+    rsx! {
+        div {
+            class: "md-find-anchor",
+            div {
+                class: "md-find-bar",
+                onkeydown: move |evt: KeyboardEvent| {
+                    if evt.key() == Key::Escape {
+                        evt.prevent_default();
+                        find.set(None);
+                    }
+                },
+                div {
+                    class: "md-find-row",
+                    input {
+                        class: "md-find-input",
+                        r#type: "text",
+                        placeholder: "Find",
+                        autofocus: true,
+                        value: "{state.query}",
+                        oninput: move |evt: FormEvent| {
+                            let query = evt.value();
+                            update(&|s: &mut FindState| {
+                                s.query = query.clone();
+                                s.current_match = 0;
+                            });
+                        },
+                        onkeydown: move |evt: KeyboardEvent| {
+                            if evt.key() == Key::Enter {
+                                evt.prevent_default();
+                                let back = evt.modifiers().shift();
+                                if let Some(state) = find.write().as_mut() {
+                                    if back { state.prev() } else { state.next() }
+                                }
+                            }
+                        },
+                    }
+                    span { class: "md-find-count", "{count_label}" }
+                    button {
+                        class: "md-find-button",
+                        title: "Match case",
+                        "data-active": "{state.case_sensitive}",
+                        onclick: move |_| update(&|s: &mut FindState| s.case_sensitive = !s.case_sensitive),
+                        "Aa"
+                    }
+                    button {
+                        class: "md-find-button",
+                        title: "Previous match (Shift+Enter)",
+                        onclick: move |_| {
+                            if let Some(state) = find.write().as_mut() {
+                                state.prev();
+                            }
+                        },
+                        "↑"
+                    }
+                    button {
+                        class: "md-find-button",
+                        title: "Next match (Enter)",
+                        onclick: move |_| {
+                            if let Some(state) = find.write().as_mut() {
+                                state.next();
+                            }
+                        },
+                        "↓"
+                    }
+                    button {
+                        class: "md-find-button",
+                        title: "Toggle replace",
+                        onclick: move |_| {
+                            if let Some(state) = find.write().as_mut() {
+                                state.replace_mode = !state.replace_mode;
+                            }
+                        },
+                        "⇄"
+                    }
+                    button {
+                        class: "md-find-button",
+                        title: "Close (Esc)",
+                        onclick: move |_| find.set(None),
+                        "✕"
+                    }
+                }
+                if state.replace_mode {
+                    div {
+                        class: "md-find-row",
+                        input {
+                            class: "md-find-input",
+                            r#type: "text",
+                            placeholder: "Replace",
+                            value: "{state.replacement}",
+                            oninput: move |evt: FormEvent| {
+                                if let Some(state) = find.write().as_mut() {
+                                    state.replacement = evt.value();
+                                }
+                            },
+                        }
+                        button {
+                            class: "md-find-button md-find-text-button",
+                            disabled: state.match_indices.is_empty(),
+                            onclick: replace_one,
+                            "Replace"
+                        }
+                        button {
+                            class: "md-find-button md-find-text-button",
+                            disabled: state.match_indices.is_empty(),
+                            onclick: replace_all,
+                            "Replace all"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
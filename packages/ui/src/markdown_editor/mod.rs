@@ -1,3 +1,12 @@
+mod attachments;
 mod component;
+mod find;
+mod table;
+mod theme;
+mod toolbar;
 
-pub use component::MarkdownEditor;
+pub use component::{MarkdownEditor, VimModeKind};
+pub use find::FindState;
+pub use table::InsertTableDialog;
+pub use theme::MarkdownEditorTheme;
+pub use toolbar::{MobileToolbar, ToolbarEvent};
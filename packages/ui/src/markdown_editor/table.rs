@@ -0,0 +1,109 @@
+use dioxus::prelude::*;
+
+use super::component::js_string_escape;
+use crate::components::{Button, ButtonVariant, Input, Label};
+
+/// Largest table the dialog will generate in either direction.
+const MAX_TABLE_SIZE: usize = 20;
+
+/// Markdown source for an empty table with a header row and `rows` body rows.
+pub fn table_markdown(rows: usize, cols: usize) -> String {
+    let cols = cols.max(1);
+    let header: Vec<String> = (1..=cols).map(|i| format!("Column {i}")).collect();
+    let mut out = format!("| {} |\n|{}\n", header.join(" | "), "---|".repeat(cols));
+    for _ in 0..rows {
+        out.push('|');
+        out.push_str(&"  |".repeat(cols));
+        out.push('\n');
+    }
+    out
+}
+
+/// Insert `text` at the cursor of the CM6 editor in `editor_id`, on a line of
+/// its own, and focus the editor.
+pub(super) fn insert_block_at_cursor(editor_id: &str, text: &str) {
+    let js = format!(
+        r#"(function() {{
+            var container = document.getElementById({eid_js});
+            if (!container || !container._cm) return;
+            var view = container._cm.view;
+            var text = {text_js};
+            var pos = view.state.selection.main.head;
+            var line = view.state.doc.lineAt(pos);
+            if (line.length > 0) text = '\n' + text;
+            view.dispatch(view.state.replaceSelection(text), {{ scrollIntoView: true }});
+            view.focus();
+        }})();"#,
+        eid_js = js_string_escape(editor_id),
+        text_js = js_string_escape(text),
+    );
+    document::eval(&js);
+}
+
+/// Modal form asking for the size of a new table.
+#[component]
+pub fn InsertTableDialog(
+    /// Called with the generated markdown.
+    on_insert: EventHandler<String>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut rows = use_signal(|| 2usize);
+    let mut cols = use_signal(|| 3usize);
+
+    let parse_size = |value: String| value.parse::<usize>().ok().map(|n| n.clamp(1, MAX_TABLE_SIZE));
+
+    rsx! {
+        div {
+            class: "modal-body",
+            h2 { class: "modal-title", "Insert Table" }
+
+            div {
+                class: "modal-field",
+                Label { html_for: "insert-table-rows", "Rows" }
+                Input {
+                    id: "insert-table-rows",
+                    r#type: "number",
+                    min: "1",
+                    max: "{MAX_TABLE_SIZE}",
+                    value: "{rows}",
+                    oninput: move |evt: FormEvent| {
+                        if let Some(n) = parse_size(evt.value()) {
+                            rows.set(n);
+                        }
+                    },
+                }
+            }
+
+            div {
+                class: "modal-field",
+                Label { html_for: "insert-table-cols", "Columns" }
+                Input {
+                    id: "insert-table-cols",
+                    r#type: "number",
+                    min: "1",
+                    max: "{MAX_TABLE_SIZE}",
+                    value: "{cols}",
+                    oninput: move |evt: FormEvent| {
+                        if let Some(n) = parse_size(evt.value()) {
+                            cols.set(n);
+                        }
+                    },
+                }
+            }
+
+            div {
+                class: "modal-actions",
+                Button {
+                    variant: ButtonVariant::Primary,
+                    onclick: move |_| on_insert.call(table_markdown(rows(), cols())),
+                    "Insert"
+                }
+                Button {
+                    variant: ButtonVariant::Outline,
+                    onclick: move |_| on_cancel.call(()),
+                    "Cancel"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use dioxus::prelude::*;
+
+use super::component::js_string_escape;
+
+/// Prefix of the `id` of the `<style>` element each editor's custom theme is
+/// injected as, followed by the editor's id.
+const THEME_STYLE_ID_PREFIX: &str = "md-editor-theme-";
+
+/// Colors of the [`MarkdownEditor`](super::MarkdownEditor) blocks.
+///
+/// Each field sets one `--md-*` CSS variable; fields left `None` keep the
+/// default from `style.css`, which already has light and dark
+/// (`[data-theme="dark"]`) values. A set field applies in both modes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarkdownEditorTheme {
+    /// `--md-text-color`: body text and caret.
+    pub text_color: Option<String>,
+    /// `--md-heading-color`: `#` headings.
+    pub heading_color: Option<String>,
+    /// `--md-code-bg`: inline code, code blocks and highlighted code.
+    pub code_bg: Option<String>,
+    /// `--md-syntax-color`: markdown markers shown around the cursor.
+    pub syntax_color: Option<String>,
+    /// `--md-syntax-opacity`: how strongly those markers show, `0`–`1`.
+    pub syntax_opacity: Option<String>,
+    /// `--md-blockquote-border`: left rule of `>` quotes.
+    pub blockquote_border: Option<String>,
+    /// `--md-blockquote-color`: text of `>` quotes.
+    pub blockquote_color: Option<String>,
+    /// `--md-link-color`: link text and `[[wiki links]]`.
+    pub link_color: Option<String>,
+    /// `--md-selection-bg`: selected text.
+    pub selection_bg: Option<String>,
+    /// `--md-table-border`: rule under table headers.
+    pub table_border: Option<String>,
+    /// `--md-table-header-bg`: table header cells and frontmatter chips.
+    pub table_header_bg: Option<String>,
+    /// `--md-table-row-hover`: table row under the pointer.
+    pub table_row_hover: Option<String>,
+    /// `--md-callout-note`: `> [!NOTE]` callouts.
+    pub callout_note: Option<String>,
+    /// `--md-callout-tip`: `> [!TIP]` callouts.
+    pub callout_tip: Option<String>,
+    /// `--md-callout-important`: `> [!IMPORTANT]` callouts.
+    pub callout_important: Option<String>,
+    /// `--md-callout-warning`: `> [!WARNING]` callouts.
+    pub callout_warning: Option<String>,
+    /// `--md-callout-caution`: `> [!CAUTION]` callouts.
+    pub callout_caution: Option<String>,
+    /// `--md-find-match-bg`: find bar matches.
+    pub find_match_bg: Option<String>,
+    /// `--md-find-current-bg`: the current find bar match.
+    pub find_current_bg: Option<String>,
+    /// `--md-shadow-color`: shadow of the find bar.
+    pub shadow_color: Option<String>,
+}
+
+impl MarkdownEditorTheme {
+    /// `(variable, value)` for every field that is set.
+    fn vars(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("--md-text-color", &self.text_color),
+            ("--md-heading-color", &self.heading_color),
+            ("--md-code-bg", &self.code_bg),
+            ("--md-syntax-color", &self.syntax_color),
+            ("--md-syntax-opacity", &self.syntax_opacity),
+            ("--md-blockquote-border", &self.blockquote_border),
+            ("--md-blockquote-color", &self.blockquote_color),
+            ("--md-link-color", &self.link_color),
+            ("--md-selection-bg", &self.selection_bg),
+            ("--md-table-border", &self.table_border),
+            ("--md-table-header-bg", &self.table_header_bg),
+            ("--md-table-row-hover", &self.table_row_hover),
+            ("--md-callout-note", &self.callout_note),
+            ("--md-callout-tip", &self.callout_tip),
+            ("--md-callout-important", &self.callout_important),
+            ("--md-callout-warning", &self.callout_warning),
+            ("--md-callout-caution", &self.callout_caution),
+            ("--md-find-match-bg", &self.find_match_bg),
+            ("--md-find-current-bg", &self.find_current_bg),
+            ("--md-shadow-color", &self.shadow_color),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value.as_deref()?.trim())))
+        // A value that could close the rule or the element is dropped
+        .filter(|(_, value)| !value.is_empty() && !value.contains([';', '{', '}', '<', '>']))
+        .collect()
+    }
+
+    /// The set variables as CSS declarations, e.g.
+    /// `--md-heading-color: #c2410c; --md-code-bg: #f5f5f4;`.
+    pub fn to_css_vars(&self) -> String {
+        self.vars()
+            .into_iter()
+            .map(|(name, value)| format!("{name}: {value};"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The theme as a `<style>` element setting its variables on the editor
+    /// `editor_id` only, so several editors can each have their own.
+    fn to_style_element(&self, editor_id: &str) -> String {
+        format!(
+            "<style id=\"{THEME_STYLE_ID_PREFIX}{editor_id}\">.cm-host[data-editor=\"{editor_id}\"] {{ {} }}</style>",
+            self.to_css_vars()
+        )
+    }
+}
+
+/// Replace the theme injected for editor `editor_id` with `theme`, or remove
+/// it for `None`. Other editors' themes are left alone.
+pub(super) fn inject_editor_theme(editor_id: &str, theme: Option<&MarkdownEditorTheme>) {
+    let html = theme.map(|theme| theme.to_style_element(editor_id)).unwrap_or_default();
+    let js = format!(
+        r#"(function() {{
+            var old = document.getElementById({id_js});
+            if (old) old.remove();
+            var html = {html_js};
+            if (html) document.head.insertAdjacentHTML('beforeend', html);
+        }})();"#,
+        id_js = js_string_escape(&format!("{THEME_STYLE_ID_PREFIX}{editor_id}")),
+        html_js = js_string_escape(&html),
+    );
+    document::eval(&js);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_css_vars() {
+        assert_eq!(MarkdownEditorTheme::default().to_css_vars(), "");
+
+        let theme = MarkdownEditorTheme {
+            heading_color: Some("#c2410c".to_string()),
+            code_bg: Some(" #f5f5f4 ".to_string()),
+            link_color: Some(String::new()),
+            // Could close the rule and inject another
+            text_color: Some("red; } body { display: none".to_string()),
+            selection_bg: Some("</style><script>".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(theme.to_css_vars(), "--md-heading-color: #c2410c; --md-code-bg: #f5f5f4;");
+        assert_eq!(
+            theme.to_style_element("cm-editor-3"),
+            "<style id=\"md-editor-theme-cm-editor-3\">.cm-host[data-editor=\"cm-editor-3\"] \
+             { --md-heading-color: #c2410c; --md-code-bg: #f5f5f4; }</style>"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use dioxus::prelude::*;
+use dioxus_free_icons::IconShape;
+
+use super::component::js_string_escape;
+use crate::icons::{
+    FaBold, FaCode, FaHeading, FaItalic, FaLink, FaListOl, FaListUl, FaRotateLeft, FaRotateRight,
+    FaSquareRootVariable, FaTable,
+};
+use crate::Icon;
+
+/// An editing action requested from outside the editor's keymap, e.g. by a
+/// [`MobileToolbar`] button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolbarEvent {
+    Bold,
+    Italic,
+    Code,
+    Link,
+    Heading,
+    BulletList,
+    NumberList,
+    Math,
+    Table,
+    Undo,
+    Redo,
+}
+
+impl ToolbarEvent {
+    /// Every action, in toolbar order.
+    pub const ALL: [ToolbarEvent; 11] = [
+        ToolbarEvent::Bold,
+        ToolbarEvent::Italic,
+        ToolbarEvent::Code,
+        ToolbarEvent::Link,
+        ToolbarEvent::Heading,
+        ToolbarEvent::BulletList,
+        ToolbarEvent::NumberList,
+        ToolbarEvent::Math,
+        ToolbarEvent::Table,
+        ToolbarEvent::Undo,
+        ToolbarEvent::Redo,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ToolbarEvent::Bold => "Bold",
+            ToolbarEvent::Italic => "Italic",
+            ToolbarEvent::Code => "Code",
+            ToolbarEvent::Link => "Link",
+            ToolbarEvent::Heading => "Heading",
+            ToolbarEvent::BulletList => "Bullet list",
+            ToolbarEvent::NumberList => "Numbered list",
+            ToolbarEvent::Math => "Math",
+            ToolbarEvent::Table => "Insert table",
+            ToolbarEvent::Undo => "Undo",
+            ToolbarEvent::Redo => "Redo",
+        }
+    }
+
+    /// JS run against `view` (the CM6 `EditorView`) and `sel` (its main
+    /// selection), or `None` for actions the editor handles in Rust.
+    fn script(self) -> Option<String> {
+        let script = match self {
+            ToolbarEvent::Bold => wrap_script("**"),
+            ToolbarEvent::Italic => wrap_script("*"),
+            ToolbarEvent::Code => wrap_script("`"),
+            ToolbarEvent::Math => wrap_script("$"),
+            ToolbarEvent::Heading => line_prefix_script("# "),
+            ToolbarEvent::BulletList => line_prefix_script("- "),
+            ToolbarEvent::NumberList => line_prefix_script("1. "),
+            ToolbarEvent::Link => LINK_SCRIPT.to_string(),
+            // Bundles built before undo/redo were exported lack them
+            ToolbarEvent::Undo => "if (container._cm.undo) container._cm.undo();".to_string(),
+            ToolbarEvent::Redo => "if (container._cm.redo) container._cm.redo();".to_string(),
+            ToolbarEvent::Table => return None,
+        };
+        Some(script)
+    }
+}
+
+/// Same edit as the `Mod-b`/`Mod-i`/``Mod-` `` keymap: put `marker` on both
+/// sides of the selection and keep the selected text selected.
+fn wrap_script(marker: &str) -> String {
+    format!(
+        r#"var m = {marker_js};
+            view.dispatch({{
+                changes: [{{ from: sel.from, insert: m }}, {{ from: sel.to, insert: m }}],
+                selection: {{ anchor: sel.anchor + m.length, head: sel.head + m.length }},
+                scrollIntoView: true,
+                userEvent: 'input.format',
+            }});"#,
+        marker_js = js_string_escape(marker),
+    )
+}
+
+/// Toggle `prefix` at the start of every line the selection touches.
+fn line_prefix_script(prefix: &str) -> String {
+    format!(
+        r#"var prefix = {prefix_js};
+            var doc = view.state.doc;
+            var last = doc.lineAt(sel.to).number;
+            var changes = [];
+            for (var n = doc.lineAt(sel.from).number; n <= last; n++) {{
+                var line = doc.line(n);
+                if (line.text.startsWith(prefix)) {{
+                    changes.push({{ from: line.from, to: line.from + prefix.length, insert: '' }});
+                }} else {{
+                    changes.push({{ from: line.from, insert: prefix }});
+                }}
+            }}
+            view.dispatch({{ changes: changes, scrollIntoView: true, userEvent: 'input.format' }});"#,
+        prefix_js = js_string_escape(prefix),
+    )
+}
+
+/// `[selection](url)` with `url` selected, ready to be typed over.
+const LINK_SCRIPT: &str = r#"var text = view.state.sliceDoc(sel.from, sel.to) || 'link';
+            var url = sel.from + text.length + 3;
+            view.dispatch({
+                changes: { from: sel.from, to: sel.to, insert: '[' + text + '](url)' },
+                selection: { anchor: url, head: url + 3 },
+                scrollIntoView: true,
+                userEvent: 'input.format',
+            });"#;
+
+/// Apply `event` to the CM6 editor in `editor_id` and give it back focus.
+/// [`ToolbarEvent::Table`] is a no-op here: it needs the editor's dialog.
+pub(super) fn apply_toolbar_event(editor_id: &str, event: ToolbarEvent) {
+    let Some(script) = event.script() else {
+        return;
+    };
+    let js = format!(
+        r#"(function() {{
+            var container = document.getElementById({eid_js});
+            if (!container || !container._cm) return;
+            var view = container._cm.view;
+            var sel = view.state.selection.main;
+            {script}
+            view.focus();
+        }})();"#,
+        eid_js = js_string_escape(editor_id),
+    );
+    document::eval(&js);
+}
+
+/// A horizontally scrolling row of formatting buttons for touch screens,
+/// where the keyboard shortcuts are out of reach. Each button sets `events`
+/// to its [`ToolbarEvent`]; the owning [`MarkdownEditor`](super::MarkdownEditor)
+/// applies it and clears the signal.
+#[component]
+pub fn MobileToolbar(mut events: Signal<Option<ToolbarEvent>>) -> Element {
+    rsx! {
+        div {
+            class: "md-mobile-toolbar",
+            role: "toolbar",
+            for event in ToolbarEvent::ALL {
+                button {
+                    key: "{event:?}",
+                    class: "md-mobile-toolbar-button",
+                    title: event.label(),
+                    "aria-label": event.label(),
+                    // Keep focus (and the on-screen keyboard) in the editor
+                    onmousedown: move |evt| evt.prevent_default(),
+                    onclick: move |_| events.set(Some(event)),
+                    match event {
+                        ToolbarEvent::Bold => toolbar_icon(FaBold),
+                        ToolbarEvent::Italic => toolbar_icon(FaItalic),
+                        ToolbarEvent::Code => toolbar_icon(FaCode),
+                        ToolbarEvent::Link => toolbar_icon(FaLink),
+                        ToolbarEvent::Heading => toolbar_icon(FaHeading),
+                        ToolbarEvent::BulletList => toolbar_icon(FaListUl),
+                        ToolbarEvent::NumberList => toolbar_icon(FaListOl),
+                        ToolbarEvent::Math => toolbar_icon(FaSquareRootVariable),
+                        ToolbarEvent::Table => toolbar_icon(FaTable),
+                        ToolbarEvent::Undo => toolbar_icon(FaRotateLeft),
+                        ToolbarEvent::Redo => toolbar_icon(FaRotateRight),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn toolbar_icon<I: IconShape + Clone + PartialEq + 'static>(icon: I) -> Element {
+    rsx! {
+        Icon { icon, width: 16, height: 16 }
+    }
+}
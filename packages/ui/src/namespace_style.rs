@@ -0,0 +1,190 @@
+use dioxus::prelude::*;
+use store::NamespaceStyle;
+
+use crate::components::{Button, ButtonVariant};
+use crate::icons::{
+    FaBook, FaBoxArchive, FaBriefcase, FaCalendar, FaCamera, FaCode, FaFlask, FaFolder, FaFolderOpen,
+    FaGlobe, FaGraduationCap, FaHeart, FaHouse, FaLightbulb, FaListCheck, FaLock, FaMusic, FaPen,
+    FaStar, FaUser,
+};
+use crate::note_tree::NoteTree;
+use crate::Icon;
+
+/// Colors offered by [`NamespaceStyleSheet`], as `(name, CSS color)`.
+pub const NAMESPACE_COLORS: [(&str, &str); 12] = [
+    ("Red", "#ef4444"),
+    ("Orange", "#f97316"),
+    ("Amber", "#f59e0b"),
+    ("Yellow", "#eab308"),
+    ("Lime", "#84cc16"),
+    ("Green", "#22c55e"),
+    ("Teal", "#14b8a6"),
+    ("Cyan", "#06b6d4"),
+    ("Blue", "#3b82f6"),
+    ("Indigo", "#6366f1"),
+    ("Purple", "#a855f7"),
+    ("Pink", "#ec4899"),
+];
+
+/// FontAwesome icon names offered by [`NamespaceStyleSheet`]; the first is
+/// the default folder.
+pub const NAMESPACE_ICONS: [&str; 20] = [
+    "folder",
+    "folder-open",
+    "book",
+    "briefcase",
+    "house",
+    "star",
+    "heart",
+    "lightbulb",
+    "code",
+    "flask",
+    "graduation-cap",
+    "music",
+    "camera",
+    "calendar",
+    "list-check",
+    "pen",
+    "globe",
+    "user",
+    "lock",
+    "box-archive",
+];
+
+/// The namespace whose [`NamespaceStyleSheet`] is open, if any.
+///
+/// Provided as `Signal<StylingNamespace>` by `SidebarLayoutView`, which shows
+/// the sheet; sidebar rows set it from their `•••` button or a right-click
+/// on the folder icon.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StylingNamespace(pub Option<String>);
+
+/// Style of namespace `path` from the note tree's config, or the default
+/// style outside a `SidebarLayoutView`.
+pub(crate) fn namespace_style(path: &str) -> NamespaceStyle {
+    try_use_context::<Signal<NoteTree>>()
+        .map(|tree| tree.read().config.get_namespace_style(path))
+        .unwrap_or_default()
+}
+
+/// A namespace folder icon in its configured icon and color.
+#[component]
+pub fn NamespaceIcon(style: NamespaceStyle, #[props(default = 12)] size: u32) -> Element {
+    let color = style.color.map(|color| format!("color: {color};")).unwrap_or_default();
+    rsx! {
+        span {
+            class: "namespace-icon",
+            style: "{color}",
+            {icon_by_name(style.icon.as_deref().unwrap_or(NAMESPACE_ICONS[0]), size)}
+        }
+    }
+}
+
+/// The icon called `name` in [`NAMESPACE_ICONS`], or the folder for any
+/// other name.
+fn icon_by_name(name: &str, size: u32) -> Element {
+    match name {
+        "folder-open" => rsx! { Icon { icon: FaFolderOpen, width: size, height: size } },
+        "book" => rsx! { Icon { icon: FaBook, width: size, height: size } },
+        "briefcase" => rsx! { Icon { icon: FaBriefcase, width: size, height: size } },
+        "house" => rsx! { Icon { icon: FaHouse, width: size, height: size } },
+        "star" => rsx! { Icon { icon: FaStar, width: size, height: size } },
+        "heart" => rsx! { Icon { icon: FaHeart, width: size, height: size } },
+        "lightbulb" => rsx! { Icon { icon: FaLightbulb, width: size, height: size } },
+        "code" => rsx! { Icon { icon: FaCode, width: size, height: size } },
+        "flask" => rsx! { Icon { icon: FaFlask, width: size, height: size } },
+        "graduation-cap" => rsx! { Icon { icon: FaGraduationCap, width: size, height: size } },
+        "music" => rsx! { Icon { icon: FaMusic, width: size, height: size } },
+        "camera" => rsx! { Icon { icon: FaCamera, width: size, height: size } },
+        "calendar" => rsx! { Icon { icon: FaCalendar, width: size, height: size } },
+        "list-check" => rsx! { Icon { icon: FaListCheck, width: size, height: size } },
+        "pen" => rsx! { Icon { icon: FaPen, width: size, height: size } },
+        "globe" => rsx! { Icon { icon: FaGlobe, width: size, height: size } },
+        "user" => rsx! { Icon { icon: FaUser, width: size, height: size } },
+        "lock" => rsx! { Icon { icon: FaLock, width: size, height: size } },
+        "box-archive" => rsx! { Icon { icon: FaBoxArchive, width: size, height: size } },
+        _ => rsx! { Icon { icon: FaFolder, width: size, height: size } },
+    }
+}
+
+/// Modal content for picking a namespace's sidebar icon and color from the
+/// preset [`NAMESPACE_ICONS`] and [`NAMESPACE_COLORS`].
+#[component]
+pub fn NamespaceStyleSheet(
+    /// Namespace path, shown in the title.
+    path: String,
+    /// The current style, preselected.
+    style: NamespaceStyle,
+    on_save: EventHandler<NamespaceStyle>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut icon = use_signal(|| style.icon.clone());
+    let mut color = use_signal(|| style.color.clone());
+
+    rsx! {
+        div {
+            class: "modal-body namespace-style-sheet",
+            h2 { class: "modal-title", "Customize {path}" }
+
+            div {
+                class: "modal-field",
+                span { class: "label", "Color" }
+                div {
+                    class: "namespace-style-swatches",
+                    button {
+                        class: "namespace-style-swatch namespace-style-swatch-none",
+                        title: "Default",
+                        "aria-pressed": color().is_none(),
+                        onclick: move |_| color.set(None),
+                    }
+                    for (name, css) in NAMESPACE_COLORS {
+                        button {
+                            key: "{name}",
+                            class: "namespace-style-swatch",
+                            style: "background: {css};",
+                            title: name,
+                            "aria-pressed": color().as_deref() == Some(css),
+                            onclick: move |_| color.set(Some(css.to_string())),
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "modal-field",
+                span { class: "label", "Icon" }
+                div {
+                    class: "namespace-style-icons",
+                    for name in NAMESPACE_ICONS {
+                        button {
+                            key: "{name}",
+                            class: "namespace-style-icon",
+                            title: name,
+                            "aria-pressed": icon().as_deref().unwrap_or(NAMESPACE_ICONS[0]) == name,
+                            // The plain folder is stored as "no icon"
+                            onclick: move |_| icon.set((name != NAMESPACE_ICONS[0]).then(|| name.to_string())),
+                            NamespaceIcon {
+                                style: NamespaceStyle { icon: Some(name.to_string()), color: color() },
+                                size: 16,
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "modal-actions",
+                Button {
+                    variant: ButtonVariant::Primary,
+                    onclick: move |_| on_save.call(NamespaceStyle { icon: icon(), color: color() }),
+                    "Save"
+                }
+                Button {
+                    variant: ButtonVariant::Ghost,
+                    onclick: move |_| on_cancel.call(()),
+                    "Cancel"
+                }
+            }
+        }
+    }
+}
@@ -1,19 +1,28 @@
 use dioxus::prelude::*;
-use store::NamespaceInfo;
+use store::templates::TEMPLATES_NAMESPACE;
+use store::{NamespaceInfo, TypedNoteInfo};
 
 use crate::components::{Button, ButtonVariant, Input, Label};
 
 /// Inline form for creating a new note.
+///
+/// `on_create` receives `(name, namespace, note_type, template_path)`; with a
+/// template the note type is the template's.
 #[component]
 pub fn NewNoteDialog(
     namespaces: Vec<NamespaceInfo>,
     default_namespace: Option<String>,
-    on_create: EventHandler<(String, Option<String>, String)>,
+    /// Template notes offered under "From template" (see `Repository::list_templates`).
+    #[props(default)]
+    templates: Vec<TypedNoteInfo>,
+    on_create: EventHandler<(String, Option<String>, String, Option<String>)>,
     on_cancel: EventHandler<()>,
 ) -> Element {
     let mut name = use_signal(|| String::new());
     let mut namespace = use_signal(|| String::new());
     let mut note_type = use_signal(|| "markdown".to_string());
+    // Path of the chosen template ("" = blank note)
+    let mut template = use_signal(|| String::new());
 
     // Sync default_namespace prop into the signal each time the dialog mounts.
     // use_effect re-runs when the captured prop value changes.
@@ -34,7 +43,8 @@ pub fn NewNoteDialog(
         } else {
             Some(namespace())
         };
-        on_create.call((n, ns, note_type()));
+        let template = Some(template()).filter(|t| !t.is_empty());
+        on_create.call((n, ns, note_type(), template));
     };
 
     rsx! {
@@ -73,16 +83,39 @@ pub fn NewNoteDialog(
                 }
             }
 
+            if !templates.is_empty() {
+                div {
+                    class: "modal-field",
+                    Label { html_for: "new-note-template", "From template" }
+                    select {
+                        id: "new-note-template",
+                        class: "modal-select",
+                        value: template(),
+                        onchange: move |evt| template.set(evt.value()),
+                        option { value: "", "Blank note" }
+                        for t in &templates {
+                            option {
+                                key: "{t.path}",
+                                value: "{t.path}",
+                                {t.path.strip_prefix(&format!("{TEMPLATES_NAMESPACE}/")).unwrap_or(&t.path).to_string()}
+                            }
+                        }
+                    }
+                }
+            }
+
             div {
                 class: "modal-field",
+                hidden: !template().is_empty(),
                 Label { html_for: "new-note-type", "Type" }
                 select {
                     id: "new-note-type",
                     class: "modal-select",
                     value: note_type(),
                     onchange: move |evt| note_type.set(evt.value()),
-                    option { value: "markdown", "Markdown (.md)" }
-                    option { value: "text", "Text (.txt)" }
+                    for (value, ext, label) in store::models::NOTE_TYPES {
+                        option { value: "{value}", "{label} (.{ext})" }
+                    }
                 }
             }
 
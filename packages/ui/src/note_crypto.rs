@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+#[cfg(target_arch = "wasm32")]
+use base64::engine::general_purpose::STANDARD as BASE64;
+#[cfg(target_arch = "wasm32")]
+use base64::Engine;
+#[cfg(target_arch = "wasm32")]
+use dioxus::prelude::*;
+
+/// Key derived from each user's passphrase, keyed by user, so the slow
+/// derivation runs once per session. The passphrase itself is never kept.
+static DERIVED_KEYS: LazyLock<Mutex<HashMap<String, [u8; 32]>>> = LazyLock::new(Default::default);
+
+fn user_key(user_id: Option<&str>) -> String {
+    user_id.unwrap_or("local").to_string()
+}
+
+/// Salt of `user_id`'s note key, so equal passphrases of different users
+/// give different keys.
+fn salt(user: &str) -> String {
+    format!("typednotes:{user}")
+}
+
+/// The note key of `user_id`, or `None` until it is unlocked (see
+/// [`unlock_note_key`]).
+///
+/// The key lives in memory for the session. On web it is also found in
+/// `localStorage` under `note_key_{user_id}` if the user chose to remember
+/// it on this device.
+pub(crate) async fn note_key(user_id: Option<&str>) -> Option<[u8; 32]> {
+    let user = user_key(user_id);
+    if let Some(key) = DERIVED_KEYS.lock().unwrap().get(&user) {
+        return Some(*key);
+    }
+    let key = load_remembered_key(&user).await?;
+    DERIVED_KEYS.lock().unwrap().insert(user, key);
+    Some(key)
+}
+
+/// Derive `user_id`'s note key from `passphrase` and keep it for the session.
+///
+/// `sealed` is the stored form of an existing encrypted note, if there is
+/// one: a passphrase that cannot open it is refused, so notes never end up
+/// under two different keys. With `remember` the derived key (not the
+/// passphrase) is also stored on this device; without it any key stored
+/// before is removed.
+pub(crate) async fn unlock_note_key(
+    user_id: Option<&str>,
+    passphrase: &str,
+    sealed: Option<&str>,
+    remember: bool,
+) -> Result<[u8; 32], String> {
+    let user = user_key(user_id);
+    let key = derive_key(passphrase, &salt(&user)).await?;
+    if let Some(sealed) = sealed {
+        api::crypto::open_note(sealed, &key).map_err(|_| {
+            "This passphrase does not open your other encrypted notes".to_string()
+        })?;
+    }
+    remember_key(&user, remember.then_some(&key)).await;
+    DERIVED_KEYS.lock().unwrap().insert(user, key);
+    Ok(key)
+}
+
+/// [`api::crypto::derive_note_key`] with the browser's WebCrypto, which runs
+/// PBKDF2 off the page's thread. Falls back to the Rust implementation where
+/// WebCrypto is unavailable (pages not served over https).
+#[cfg(target_arch = "wasm32")]
+async fn derive_key(passphrase: &str, salt: &str) -> Result<[u8; 32], String> {
+    let eval = document::eval(
+        r#"var passphrase = await dioxus.recv();
+        var salt = await dioxus.recv();
+        var rounds = await dioxus.recv();
+        try {
+            var enc = new TextEncoder();
+            var base = await crypto.subtle.importKey("raw", enc.encode(passphrase), "PBKDF2", false, ["deriveBits"]);
+            var bits = await crypto.subtle.deriveBits(
+                { name: "PBKDF2", hash: "SHA-256", salt: enc.encode(salt), iterations: rounds }, base, 256);
+            return Array.from(new Uint8Array(bits));
+        } catch (e) { return null; }"#,
+    );
+    let _ = eval.send(passphrase.to_string());
+    let _ = eval.send(salt.to_string());
+    let _ = eval.send(api::crypto::NOTE_KEY_ROUNDS);
+    match eval.join::<Option<Vec<u8>>>().await.ok().flatten() {
+        Some(bytes) => bytes.try_into().map_err(|_| "Unexpected key length".to_string()),
+        None => Ok(api::crypto::derive_note_key(passphrase, salt)),
+    }
+}
+
+/// [`api::crypto::derive_note_key`] on a blocking thread.
+#[cfg(not(target_arch = "wasm32"))]
+async fn derive_key(passphrase: &str, salt: &str) -> Result<[u8; 32], String> {
+    let (passphrase, salt) = (passphrase.to_string(), salt.to_string());
+    tokio::task::spawn_blocking(move || api::crypto::derive_note_key(&passphrase, &salt))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn load_remembered_key(user: &str) -> Option<[u8; 32]> {
+    let eval = document::eval(
+        r#"var user = await dioxus.recv();
+        try {
+            // Older builds kept the passphrase itself
+            localStorage.removeItem("note_passphrase_" + user);
+            return localStorage.getItem("note_key_" + user);
+        } catch (e) { return null; }"#,
+    );
+    let _ = eval.send(user.to_string());
+    let stored = eval.join::<Option<String>>().await.ok().flatten()?;
+    BASE64.decode(stored).ok()?.try_into().ok()
+}
+
+/// Store `key` as `user`'s remembered key, or remove it for `None`.
+#[cfg(target_arch = "wasm32")]
+async fn remember_key(user: &str, key: Option<&[u8; 32]>) {
+    let eval = document::eval(
+        r#"var user = await dioxus.recv();
+        var key = await dioxus.recv();
+        try {
+            if (key) localStorage.setItem("note_key_" + user, key);
+            else localStorage.removeItem("note_key_" + user);
+        } catch (e) {}"#,
+    );
+    let _ = eval.send(user.to_string());
+    let _ = eval.send(key.map(|key| BASE64.encode(key)));
+}
+
+// Native builds keep the key in memory only
+#[cfg(not(target_arch = "wasm32"))]
+async fn load_remembered_key(_user: &str) -> Option<[u8; 32]> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn remember_key(_user: &str, _key: Option<&[u8; 32]>) {}
@@ -1,9 +1,9 @@
 use dioxus::prelude::*;
 use store::TypedNoteInfo;
-use crate::components::{Button, ButtonVariant, Input, Textarea, TextareaVariant};
-use crate::markdown_editor::MarkdownEditor;
-use crate::Icon;
-use crate::icons::FaTrashCan;
+use crate::components::{Breadcrumb, Button, ButtonVariant, Input, Textarea, TextareaVariant};
+use crate::views::{extract_headings, NoteExportMenu, SplitEditorView, TableOfContents, ViewMode};
+use crate::{FocusModeToggle, Icon};
+use crate::icons::{FaEye, FaList, FaLock, FaLockOpen, FaPen, FaTableColumns, FaTrashCan};
 
 const VIEWS_CSS: Asset = asset!("/src/views/views.css");
 
@@ -14,6 +14,23 @@ pub fn NoteEditor(
     on_delete: EventHandler<()>,
     #[props(default)] on_rename: EventHandler<String>,
     #[props(default = 300)] auto_sync_interval_secs: u32,
+    /// Called with the full content after every edit.
+    #[props(default)] on_change: EventHandler<String>,
+    /// Save this long after the last edit; `None` saves only on blur/timer.
+    #[props(default)] auto_save_delay_ms: Option<u64>,
+    /// Note names offered when typing `[[` (markdown notes only).
+    #[props(default)] link_targets: Vec<String>,
+    /// Called with the target of a clicked `[[wiki link]]`.
+    #[props(default)] on_navigate_note: EventHandler<String>,
+    /// Vim keybindings in the markdown editor.
+    #[props(default)] vim_mode: bool,
+    /// Show the HTML/PDF export menu (needs a git remote).
+    #[props(default)] export_enabled: bool,
+    /// Called with the current content when the lock button is pressed, to
+    /// encrypt the note (or decrypt it, when `note.encrypted`).
+    #[props(default)] on_toggle_encryption: EventHandler<String>,
+    /// Called with a namespace path clicked in the breadcrumb above the title.
+    #[props(default)] on_navigate_namespace: EventHandler<String>,
 ) -> Element {
     let mut content = use_signal({
         let initial = note.note.clone();
@@ -24,6 +41,37 @@ pub fn NoteEditor(
         move || initial
     });
     let mut dirty = use_signal(|| false);
+    let mut view_mode = use_signal(ViewMode::default);
+    let mut show_toc = use_signal(|| false);
+    let is_markdown = note.r#type == "markdown";
+    let verbatim = store::models::is_verbatim_note_type(&note.r#type);
+    let encrypted = note.encrypted;
+    let headings = use_memo(move || {
+        if is_markdown { extract_headings(&content()) } else { Vec::new() }
+    });
+    let mut pending_save = use_signal(|| Option::<Task>::None);
+
+    // Restart the auto-save countdown on every edit
+    let mut schedule_save = move || {
+        let Some(delay_ms) = auto_save_delay_ms else {
+            return;
+        };
+        if let Some(task) = pending_save.write().take() {
+            task.cancel();
+        }
+        pending_save.set(Some(spawn(async move {
+            #[cfg(target_arch = "wasm32")]
+            gloo_timers::future::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+
+            pending_save.set(None);
+            if dirty() {
+                on_save.call(content());
+                dirty.set(false);
+            }
+        })));
+    };
 
     let handle_blur = move |_| {
         if dirty() {
@@ -71,6 +119,11 @@ pub fn NoteEditor(
         div {
             class: "editor-container",
 
+            div {
+                class: "editor-breadcrumb",
+                Breadcrumb { path: note.path.clone(), on_navigate_namespace }
+            }
+
             // Title row: full-width header
             div {
                 class: "editor-header flex items-start justify-between gap-4",
@@ -89,6 +142,57 @@ pub fn NoteEditor(
                             "Unsaved"
                         }
                     }
+                    if is_markdown {
+                        div {
+                            class: "view-mode-selector",
+                            role: "group",
+                            aria_label: "View mode",
+                            for mode in ViewMode::ALL {
+                                Button {
+                                    variant: ButtonVariant::Ghost,
+                                    title: mode.label(),
+                                    aria_pressed: view_mode() == mode,
+                                    onclick: move |_| view_mode.set(mode),
+                                    match mode {
+                                        ViewMode::Edit => rsx! { Icon { icon: FaPen, width: 14, height: 14 } },
+                                        ViewMode::Split => rsx! { Icon { icon: FaTableColumns, width: 14, height: 14 } },
+                                        ViewMode::Preview => rsx! { Icon { icon: FaEye, width: 14, height: 14 } },
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if headings.read().len() > 1 {
+                        Button {
+                            variant: ButtonVariant::Ghost,
+                            title: if show_toc() { "Hide table of contents" } else { "Table of contents" },
+                            aria_pressed: show_toc(),
+                            onclick: move |_| show_toc.set(!show_toc()),
+                            Icon { icon: FaList, width: 14, height: 14 }
+                        }
+                    }
+                    if export_enabled {
+                        NoteExportMenu {
+                            note_path: note.path.clone(),
+                            file_stem: note.title().to_string(),
+                        }
+                    }
+                    Button {
+                        variant: ButtonVariant::Ghost,
+                        title: if encrypted { "Decrypt this note" } else { "Encrypt this note" },
+                        aria_pressed: encrypted,
+                        onclick: move |_| {
+                            // The content is handed over, so it no longer needs saving here
+                            dirty.set(false);
+                            on_toggle_encryption.call(content());
+                        },
+                        if encrypted {
+                            Icon { icon: FaLock, width: 14, height: 14 }
+                        } else {
+                            Icon { icon: FaLockOpen, width: 14, height: 14 }
+                        }
+                    }
+                    FocusModeToggle {}
                     Button {
                         variant: ButtonVariant::Ghost,
                         title: "Delete note",
@@ -98,14 +202,29 @@ pub fn NoteEditor(
                 }
             }
 
+            if show_toc() && headings.read().len() > 1 {
+                TableOfContents {
+                    headings: headings(),
+                    on_jump_to_heading: move |idx: usize| {
+                        if let Some(heading) = headings.peek().get(idx) {
+                            jump_to_heading(heading.line, idx);
+                        }
+                    },
+                    on_close: move |_| show_toc.set(false),
+                }
+            }
+
             // Content area — centered with max-width for readability
             div {
-                class: "editor-content",
-                if note.r#type == "markdown" {
-                    MarkdownEditor {
+                class: if view_mode() == ViewMode::Edit { "editor-content" } else { "editor-content editor-content-wide" },
+                if is_markdown {
+                    SplitEditorView {
                         content: content,
-                        on_change: move |_: String| {
+                        mode: view_mode(),
+                        on_change: move |text: String| {
                             dirty.set(true);
+                            schedule_save();
+                            on_change.call(text);
                         },
                         on_blur: move |_| {
                             if dirty() {
@@ -113,17 +232,25 @@ pub fn NoteEditor(
                                 dirty.set(false);
                             }
                         },
-                        placeholder: "Start writing...".to_string(),
+                        link_targets: link_targets,
+                        on_navigate_note: on_navigate_note,
+                        vim_mode: vim_mode,
                     }
                 } else {
                     Textarea {
                         variant: TextareaVariant::Ghost,
-                        class: "flex-1 w-full p-0 font-sans text-base leading-[1.7] resize-none",
+                        class: if verbatim {
+                            "flex-1 w-full p-0 font-mono text-sm leading-[1.6] whitespace-pre overflow-x-auto resize-none"
+                        } else {
+                            "flex-1 w-full p-0 font-sans text-base leading-[1.7] resize-none"
+                        },
                         value: content(),
                         placeholder: "Start writing...",
                         oninput: move |evt: FormEvent| {
                             content.set(evt.value());
                             dirty.set(true);
+                            schedule_save();
+                            on_change.call(evt.value());
                         },
                         onblur: handle_blur,
                     }
@@ -132,3 +259,24 @@ pub fn NoteEditor(
         }
     }
 }
+
+/// Scroll the note's editor to the heading on `line` (1-based) and put the
+/// cursor there. When the rendered preview is showing, its `index`-th heading
+/// is scrolled into view as well.
+fn jump_to_heading(line: usize, index: usize) {
+    let js = format!(
+        r#"(function() {{
+            var wrapper = document.querySelector('.editor-content .cm-wrapper');
+            if (wrapper && wrapper._cm && wrapper.offsetParent !== null) {{
+                var view = wrapper._cm.view;
+                var pos = view.state.doc.line(Math.min({line}, view.state.doc.lines)).from;
+                view.dispatch({{ selection: {{ anchor: pos }}, scrollIntoView: true }});
+                view.focus();
+            }}
+            var preview = document.querySelector('.editor-content .md-preview');
+            var heading = preview && preview.querySelectorAll('h1, h2, h3, h4, h5, h6')[{index}];
+            if (heading) heading.scrollIntoView({{ behavior: 'smooth', block: 'start' }});
+        }})();"#
+    );
+    document::eval(&js);
+}
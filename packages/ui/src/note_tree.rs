@@ -1,5 +1,7 @@
+use std::collections::HashSet;
+
 use dioxus::prelude::*;
-use store::{NamespaceInfo, TypedNoteInfo};
+use store::{NamespaceInfo, TypedNoteInfo, TypedNotesConfig};
 
 use crate::make_repo_for_user;
 
@@ -12,6 +14,15 @@ use crate::make_repo_for_user;
 pub struct NoteTree {
     pub notes: Vec<TypedNoteInfo>,
     pub namespaces: Vec<NamespaceInfo>,
+    /// Namespaces (`""` = root) whose direct children have been fetched, when
+    /// the tree was loaded lazily with [`NoteTree::load_root_for`]. `None`
+    /// means everything is loaded.
+    pub loaded: Option<HashSet<String>>,
+    /// Pinned note paths, in pin order (from `typednotes.toml`).
+    pub pinned: Vec<String>,
+    /// The repository's `typednotes.toml` as of the last load (namespace
+    /// styles are read from here).
+    pub config: TypedNotesConfig,
 }
 
 impl NoteTree {
@@ -26,14 +37,142 @@ impl NoteTree {
     /// or `None` for the default unscoped store.
     pub async fn refresh_for(user_id: Option<&str>) -> Self {
         let repo = make_repo_for_user(user_id);
+        let config = repo.get_config().await;
         NoteTree {
             notes: repo.list_notes().await,
             namespaces: repo.list_namespaces().await,
+            loaded: None,
+            pinned: config.pinned_notes.clone(),
+            config,
+        }
+    }
+
+    /// Load only the top level of a user-scoped repository; deeper levels are
+    /// fetched with [`NoteTree::children_for`] as namespaces are expanded.
+    pub async fn load_root_for(user_id: Option<&str>) -> Self {
+        let repo = make_repo_for_user(user_id);
+        let (namespaces, notes) = repo.list_namespace_children("").await;
+        let config = repo.get_config().await;
+        NoteTree {
+            notes,
+            namespaces,
+            loaded: Some(HashSet::from([String::new()])),
+            pinned: config.pinned_notes.clone(),
+            config,
+        }
+    }
+
+    /// Fetch the direct children of namespace `path` (`""` = root).
+    pub async fn children_for(user_id: Option<&str>, path: &str) -> (Vec<NamespaceInfo>, Vec<TypedNoteInfo>) {
+        make_repo_for_user(user_id).list_namespace_children(path).await
+    }
+
+    /// Whether the direct children of namespace `path` are in the tree.
+    pub fn is_loaded(&self, path: &str) -> bool {
+        self.loaded.as_ref().is_none_or(|loaded| loaded.contains(path))
+    }
+
+    /// Replace the direct children of namespace `path` with freshly fetched ones.
+    pub fn merge_children(&mut self, path: &str, namespaces: Vec<NamespaceInfo>, notes: Vec<TypedNoteInfo>) {
+        let parent = (!path.is_empty()).then_some(path);
+        self.namespaces.retain(|ns| ns.parent.as_deref() != parent);
+        self.namespaces.extend(namespaces);
+        self.notes.retain(|n| n.namespace.as_deref() != parent);
+        self.notes.extend(notes);
+        if let Some(loaded) = self.loaded.as_mut() {
+            loaded.insert(path.to_string());
+        }
+    }
+
+    /// Loaded notes matching `query`, best first (ties by path).
+    ///
+    /// Matching is case-insensitive and scores each note once, by the best
+    /// rule it meets: the query appears in its name or content
+    /// ([`SCORE_EXACT`]), its characters appear in order in the name
+    /// ([`SCORE_SUBSEQUENCE`]), or in order anywhere in the path
+    /// ([`SCORE_PATH`]). An empty query matches nothing.
+    pub fn search(&self, query: &str) -> Vec<SearchMatch> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return Vec::new();
         }
+        let mut matches: Vec<SearchMatch> = self
+            .notes
+            .iter()
+            .filter_map(|note| {
+                Some(SearchMatch {
+                    score: match_score(note, &needle)?,
+                    snippet: if note.encrypted {
+                        String::new()
+                    } else {
+                        api::search::snippet_for(&note.note, &needle)
+                    },
+                    note: note.clone(),
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.note.path.cmp(&b.note.path)));
+        matches
+    }
+}
+
+/// Score of a note whose name or content contains the query.
+pub const SCORE_EXACT: u32 = 100;
+/// Score of a note whose name contains the query's characters in order.
+pub const SCORE_SUBSEQUENCE: u32 = 50;
+/// Score of a note whose path contains the query's characters in order.
+pub const SCORE_PATH: u32 = 30;
+
+/// A note found by [`NoteTree::search`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    pub note: TypedNoteInfo,
+    pub score: u32,
+    /// Single-line excerpt around the first occurrence of the query in the
+    /// content, or its opening text.
+    pub snippet: String,
+}
+
+fn match_score(note: &TypedNoteInfo, needle: &str) -> Option<u32> {
+    let name = note.name.to_lowercase();
+    // Encrypted content is ciphertext, never worth matching
+    let content_matches = !note.encrypted && note.note.to_lowercase().contains(needle);
+    if name.contains(needle) || content_matches {
+        Some(SCORE_EXACT)
+    } else if is_subsequence(needle, &name) {
+        Some(SCORE_SUBSEQUENCE)
+    } else if is_subsequence(needle, &note.path.to_lowercase()) {
+        Some(SCORE_PATH)
+    } else {
+        None
     }
 }
 
+/// Whether every non-whitespace character of `needle` occurs in `haystack`
+/// in the same order.
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| rest.any(|h| h == c))
+}
+
 /// Consume the `Signal<NoteTree>` from context.
 pub fn use_note_tree() -> Signal<NoteTree> {
     use_context::<Signal<NoteTree>>()
 }
+
+/// [`NoteTree::search`] results for `query`, recomputed whenever the query or
+/// the tree changes. Always empty outside `SidebarLayoutView`, where there is
+/// no tree in context.
+pub fn use_note_tree_search(query: Signal<String>) -> Signal<Vec<SearchMatch>> {
+    let tree = try_use_context::<Signal<NoteTree>>();
+    let mut matches = use_signal(Vec::new);
+    use_effect(move || {
+        if let Some(tree) = tree {
+            matches.set(tree.read().search(&query.read()));
+        }
+    });
+    matches
+}
@@ -1,4 +1,5 @@
-//! Online/offline status indicator for the sidebar header.
+//! Online/offline status indicator for the sidebar header, and the
+//! [`use_online_status`] connectivity hook.
 
 use dioxus::prelude::*;
 
@@ -44,3 +45,53 @@ pub fn OnlineIndicator() -> Element {
         },
     }
 }
+
+/// How often native builds probe for connectivity.
+#[cfg(not(target_arch = "wasm32"))]
+const PROBE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Whether the device has a network connection, updated as it changes.
+///
+/// On web this follows `navigator.onLine` and the window's `online` /
+/// `offline` events. Native builds have no such events, so they open a TCP
+/// connection to a public DNS server every [`PROBE_INTERVAL`]. Starts as
+/// `true` until the first reading.
+pub fn use_online_status() -> Signal<bool> {
+    let mut online = use_signal(|| true);
+    use_hook(move || {
+        spawn(async move {
+            #[cfg(target_arch = "wasm32")]
+            {
+                let mut eval = document::eval(
+                    r#"dioxus.send(navigator.onLine);
+                    window.addEventListener('online', function() { dioxus.send(true); });
+                    window.addEventListener('offline', function() { dioxus.send(false); });"#,
+                );
+                while let Ok(now) = eval.recv::<bool>().await {
+                    if *online.peek() != now {
+                        online.set(now);
+                    }
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            loop {
+                let now = probe().await;
+                if *online.peek() != now {
+                    online.set(now);
+                }
+                tokio::time::sleep(PROBE_INTERVAL).await;
+            }
+        });
+    });
+    online
+}
+
+/// Can we open a TCP connection to Google's public DNS within a few seconds?
+#[cfg(not(target_arch = "wasm32"))]
+async fn probe() -> bool {
+    let connect = tokio::net::TcpStream::connect("8.8.8.8:53");
+    matches!(
+        tokio::time::timeout(std::time::Duration::from_secs(3), connect).await,
+        Ok(Ok(_))
+    )
+}
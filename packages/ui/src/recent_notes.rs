@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use dioxus::prelude::*;
+
+/// Most notes remembered per user; opening one more evicts the oldest.
+pub const MAX_RECENT_NOTES: usize = 20;
+
+/// Per-user list of recently opened note paths, most recent first.
+///
+/// Persisted in `localStorage` under `recent_notes_{user_id}` on web; native
+/// builds keep the list in memory for the lifetime of the process. The list
+/// for the current user is provided as `Signal<Vec<String>>` via context in
+/// `SidebarLayoutView` (see [`use_recent_notes`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecentNotes {
+    key: String,
+}
+
+impl RecentNotes {
+    /// Recent notes of `user_id` (`None` = the local, signed-out store).
+    pub fn for_user(user_id: Option<&str>) -> Self {
+        Self {
+            key: format!("recent_notes_{}", user_id.unwrap_or("local")),
+        }
+    }
+
+    /// Storage key of this user's list.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Paths of recently opened notes, most recent first.
+    pub async fn get_recent(&self) -> Vec<String> {
+        load(&self.key).await.into()
+    }
+
+    /// Record that `path` was opened and return the updated list.
+    pub async fn push_recent(&self, path: &str) -> Vec<String> {
+        let mut recent = load(&self.key).await;
+        push_front(&mut recent, path);
+        save(&self.key, &recent).await;
+        recent.into()
+    }
+}
+
+/// Move `path` to the front, evicting the least recently used entries.
+fn push_front(recent: &mut VecDeque<String>, path: &str) {
+    recent.retain(|p| p != path);
+    recent.push_front(path.to_string());
+    recent.truncate(MAX_RECENT_NOTES);
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn load(key: &str) -> VecDeque<String> {
+    let eval = document::eval(
+        r#"var key = await dioxus.recv();
+        try {
+            var list = JSON.parse(localStorage.getItem(key) || '[]');
+            return Array.isArray(list) ? list.filter(function(p) { return typeof p === 'string'; }) : [];
+        } catch (e) {
+            return [];
+        }"#,
+    );
+    let _ = eval.send(key.to_string());
+    eval.join::<VecDeque<String>>().await.unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn save(key: &str, recent: &VecDeque<String>) {
+    let eval = document::eval(
+        r#"var key = await dioxus.recv();
+        var list = await dioxus.recv();
+        try { localStorage.setItem(key, JSON.stringify(list)); } catch (e) {}"#,
+    );
+    let _ = eval.send(key.to_string());
+    let _ = eval.send(recent.clone());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static RECENT: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<String, VecDeque<String>>>> =
+    std::sync::LazyLock::new(Default::default);
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn load(key: &str) -> VecDeque<String> {
+    RECENT.lock().unwrap().get(key).cloned().unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn save(key: &str, recent: &VecDeque<String>) {
+    RECENT.lock().unwrap().insert(key.to_string(), recent.clone());
+}
+
+/// Consume the recent-notes `Signal<Vec<String>>` from context.
+pub fn use_recent_notes() -> Signal<Vec<String>> {
+    use_context::<Signal<Vec<String>>>()
+}
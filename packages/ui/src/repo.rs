@@ -1,8 +1,10 @@
 //! Shared repository constructor for all platforms.
 //!
 //! Returns a [`store::Repository`] backed by the appropriate [`store::ObjectStore`]:
-//! - **Web** (WASM + `web` feature): IndexedDB via [`store::IdbStore`]
-//! - **Desktop / Mobile** (native): filesystem via [`store::FileStore`]
+//! - **Web** (WASM + `web` feature): IndexedDB via [`store::IdbStore`], behind
+//!   a [`store::CachedStore`] shared by every repository opened on the same database
+//! - **Desktop / Mobile** (native): filesystem via [`store::FileStore`], or a
+//!   single SQLite database per user with the `sqlite` feature (desktop)
 
 /// Create a platform-appropriate repository (unscoped, default store).
 ///
@@ -19,6 +21,7 @@ pub fn make_repo() -> store::Repository<impl store::ObjectStore> {
 pub async fn detach_user(user_id: &str) {
     #[cfg(all(target_arch = "wasm32", feature = "web"))]
     {
+        IDB_CACHES.with(|caches| caches.borrow_mut().clear());
         store::IdbStore::delete_scoped(user_id).await;
         store::IdbStore::delete_anonymous().await;
     }
@@ -27,6 +30,11 @@ pub async fn detach_user(user_id: &str) {
         let base = dirs::data_dir()
             .unwrap_or_else(|| std::path::PathBuf::from("."))
             .join("typednotes");
+        #[cfg(feature = "sqlite")]
+        {
+            store::SqliteStore::delete(&base.join(user_id).join(SQLITE_DB_FILE)).await;
+            store::SqliteStore::delete(&base.join(SQLITE_DB_FILE)).await;
+        }
         store::FileStore::delete_scoped(&base, user_id);
         store::FileStore::delete_anonymous(&base);
     }
@@ -47,6 +55,19 @@ pub async fn migrate_anonymous_to_user(user_id: &str) {
         .join("typednotes");
     let scoped = base.join(user_id);
 
+    // Move the anonymous database over wholesale; the file copy below then
+    // only matters for notes still in the legacy one-file-per-object layout
+    #[cfg(feature = "sqlite")]
+    {
+        let anon_db = base.join(SQLITE_DB_FILE);
+        let user_db = scoped.join(SQLITE_DB_FILE);
+        if anon_db.exists() && !user_db.exists() {
+            store::SqliteStore::close(&anon_db).await;
+            let _ = std::fs::create_dir_all(&scoped);
+            let _ = std::fs::rename(&anon_db, &user_db);
+        }
+    }
+
     // Skip if user-scoped store already has refs (already has data)
     if scoped.join("refs").join("HEAD").exists() {
         return;
@@ -92,12 +113,21 @@ pub async fn migrate_anonymous_to_user(user_id: &str) {
 /// When `user_id` is `Some("uuid")`:
 /// - **Web**: opens IndexedDB database `"typednotes-uuid"`
 /// - **Desktop/Mobile**: uses filesystem path `<data_dir>/typednotes/uuid/`
+///   (`notes.db` in that directory with the `sqlite` feature)
 ///
 /// When `user_id` is `None`, falls back to the default unscoped store.
 pub fn make_repo_for_user(user_id: Option<&str>) -> store::Repository<impl store::ObjectStore> {
     #[cfg(all(target_arch = "wasm32", feature = "web"))]
     {
-        store::Repository::new(store::IdbStore::with_namespace(user_id))
+        let key = user_id.map(str::to_string);
+        let cached = IDB_CACHES.with(|caches| {
+            caches
+                .borrow_mut()
+                .entry(key)
+                .or_insert_with(|| store::CachedStore::new(store::IdbStore::with_namespace(user_id)))
+                .clone()
+        });
+        store::Repository::new(cached)
     }
     #[cfg(not(all(target_arch = "wasm32", feature = "web")))]
     {
@@ -108,6 +138,25 @@ pub fn make_repo_for_user(user_id: Option<&str>) -> store::Repository<impl store
             Some(id) => base.join(id),
             None => base,
         };
-        store::Repository::new(store::FileStore::new(scoped))
+        #[cfg(feature = "sqlite")]
+        {
+            store::Repository::new(store::SqliteStore::open(scoped.join(SQLITE_DB_FILE)))
+        }
+        #[cfg(not(feature = "sqlite"))]
+        {
+            store::Repository::new(store::FileStore::new(scoped))
+        }
     }
 }
+
+#[cfg(all(target_arch = "wasm32", feature = "web"))]
+thread_local! {
+    /// Object caches per IndexedDB namespace, kept across `make_repo` calls.
+    static IDB_CACHES: std::cell::RefCell<
+        std::collections::HashMap<Option<String>, store::CachedStore<store::IdbStore>>,
+    > = Default::default();
+}
+
+/// Database file inside a (scoped) data directory when using SQLite storage.
+#[cfg(all(feature = "sqlite", not(all(target_arch = "wasm32", feature = "web"))))]
+const SQLITE_DB_FILE: &str = "notes.db";
@@ -1,5 +1,6 @@
-use api::UserInfo;
+use api::{SearchResult, UserInfo};
 use dioxus::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use store::{NamespaceInfo, TypedNoteInfo};
 
 use crate::activity_log_panel::ActivityLogToggle;
@@ -10,16 +11,18 @@ use crate::components::{
     SidebarContent, SidebarFooter, SidebarGroup, SidebarGroupLabel,
     SidebarHeader, SidebarMenu, SidebarMenuAction, SidebarMenuButton,
     SidebarMenuButtonSize, SidebarMenuItem, SidebarMenuSub, SidebarMenuSubButton,
-    SidebarMenuSubItem, SidebarRail, SidebarSeparator,
+    SidebarMenuSubItem, SidebarRail, SidebarSeparator, VirtualList,
 };
 use crate::Icon;
 use crate::icons::{
     FaFolderPlus, FaPlus, FaList, FaFolderTree, FaGear, FaTerminal,
-    FaFolder, FaFileLines, FaCaretLeft, FaCaretRight,
+    FaFileLines, FaCaretLeft, FaCaretRight,
     FaCircleHalfStroke, FaMoon, FaSun, FaRightFromBracket,
-    FaTrashCan, FaArrowRightToBracket,
+    FaTrashCan, FaArrowRightToBracket, FaThumbtack, FaCopy, FaEllipsis,
 };
+use crate::namespace_style::{namespace_style, NamespaceIcon, StylingNamespace};
 use crate::OnlineIndicator;
+use crate::{SearchMatch, use_note_tree_search};
 
 #[derive(Clone, Copy, PartialEq)]
 enum ViewMode {
@@ -41,8 +44,100 @@ pub enum DragItem {
     Namespace { path: String },
 }
 
+/// A request from outside the sidebar (e.g. Settings) to show a namespace in
+/// the explorer, `""` for the top level. Provided as `Signal<RevealNamespace>` via context in
+/// `SidebarLayoutView`; [`AppSidebar`] handles it and resets it to `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RevealNamespace(pub Option<String>);
+
+/// An explorer row the arrow keys can focus, by path.
+#[derive(Clone, Debug, PartialEq)]
+enum NavItem {
+    Namespace(String),
+    Note(String),
+}
+
+/// Keyboard navigation state that [`AppSidebar`] shares with the explorer
+/// rows via context.
+#[derive(Clone, Copy)]
+struct SidebarNav {
+    /// The row the arrow keys are on, if any.
+    focused: Memo<Option<NavItem>>,
+    /// Tree-view namespaces the user opened or closed; the rest keep their
+    /// default (open unless the tree is loaded lazily).
+    open: Signal<HashMap<String, bool>>,
+}
+
+/// Whether `item` is the keyboard-focused row (subscribes the caller).
+fn is_nav_focused(item: NavItem) -> bool {
+    try_use_context::<SidebarNav>().is_some_and(|nav| nav.focused.read().as_ref() == Some(&item))
+}
+
+/// Explorer rows in display order: the children of `flat_namespace` in the
+/// flat view, or every row not hidden in a closed namespace in the tree view.
+fn nav_items(
+    view_mode: ViewMode,
+    flat_namespace: Option<&str>,
+    namespaces: &[NamespaceInfo],
+    notes: &[TypedNoteInfo],
+    is_open: &dyn Fn(&str) -> bool,
+) -> Vec<NavItem> {
+    fn push_level(
+        parent: Option<&str>,
+        recurse: bool,
+        namespaces: &[NamespaceInfo],
+        notes: &[TypedNoteInfo],
+        is_open: &dyn Fn(&str) -> bool,
+        out: &mut Vec<NavItem>,
+    ) {
+        for ns in namespaces.iter().filter(|ns| ns.parent.as_deref() == parent) {
+            out.push(NavItem::Namespace(ns.path.clone()));
+            if recurse && is_open(&ns.path) {
+                push_level(Some(&ns.path), true, namespaces, notes, is_open, out);
+            }
+        }
+        for note in notes.iter().filter(|n| n.namespace.as_deref() == parent) {
+            out.push(NavItem::Note(note.path.clone()));
+        }
+    }
+
+    let mut out = Vec::new();
+    match view_mode {
+        ViewMode::Tree => push_level(None, true, namespaces, notes, is_open, &mut out),
+        ViewMode::Flat => push_level(flat_namespace, false, namespaces, notes, is_open, &mut out),
+    }
+    out
+}
+
+/// Forwards arrow keys, `Enter` and `Escape` to the sidebar while focus is
+/// in it (or nowhere), never from a text field or the editor. `Escape` also
+/// hands focus back to the editor. `Enter` is only taken over while a row
+/// is keyboard-focused, so it still activates tabbed-to buttons otherwise.
+const SIDEBAR_KEYS_JS: &str = r#"document.addEventListener('keydown', function(e) {
+    var keys = ['ArrowDown', 'ArrowUp', 'ArrowRight', 'ArrowLeft', 'Enter', 'Escape'];
+    if (keys.indexOf(e.key) < 0 || e.ctrlKey || e.metaKey || e.altKey || e.shiftKey) return;
+    var active = document.activeElement;
+    var sidebar = document.querySelector('[data-sidebar="sidebar"]');
+    var inSidebar = !!(sidebar && active && sidebar.contains(active));
+    if (!inSidebar && active && active !== document.body) return;
+    if (active && (active.tagName === 'INPUT' || active.tagName === 'TEXTAREA' || active.isContentEditable)) return;
+    if (e.key === 'Enter' && !document.querySelector('[data-sidebar-focused="true"]')) return;
+    if (e.key === 'Escape') {
+        if (!inSidebar) return;
+        var editor = document.querySelector('.cm-editor .cm-content');
+        if (editor) editor.focus(); else active.blur();
+    }
+    e.preventDefault();
+    dioxus.send(e.key);
+});"#;
+
 /// The application-specific sidebar content (VS Code-style explorer).
 /// Placed inside a `SidebarProvider` + `SidebarLayout` in the platform layouts.
+///
+/// The arrow keys move a focus marker through the explorer: up/down between
+/// rows, right to open a namespace (or enter it in the flat view), left to
+/// close it or go to its parent, `Enter` to open the focused note, and
+/// `Escape` to return to the editor.
 #[component]
 pub fn AppSidebar(
     namespaces: Vec<NamespaceInfo>,
@@ -71,6 +166,26 @@ pub fn AppSidebar(
     /// Called when a namespace is renamed inline: (old_path, new_name).
     #[props(default)]
     on_rename_namespace: EventHandler<(String, String)>,
+    /// Namespaces whose children are loaded, when the tree is loaded lazily
+    /// (`None` = everything is loaded). See `NoteTree::loaded`.
+    #[props(default)]
+    loaded_namespaces: Option<HashSet<String>>,
+    /// Called with a namespace path the first time it is opened while its
+    /// children are not loaded yet.
+    #[props(default)]
+    expand_namespace: EventHandler<String>,
+    /// Paths of pinned notes, in pin order (`TypedNotesConfig::pinned_notes`).
+    #[props(default)]
+    pinned_notes: Vec<String>,
+    /// Called with a note path to pin or unpin it.
+    #[props(default)]
+    toggle_note_pin: EventHandler<String>,
+    /// Called with a note path to duplicate it.
+    #[props(default)]
+    on_copy_note: EventHandler<String>,
+    /// Recently opened note paths, most recent first (shown in the flat view).
+    #[props(default)]
+    recent_notes: Vec<String>,
 ) -> Element {
     let mut view_mode = use_signal(|| ViewMode::Flat);
     let mut flat_namespace = use_signal(|| Option::<String>::None);
@@ -80,6 +195,145 @@ pub fn AppSidebar(
     // Holds the path of the namespace currently being renamed inline
     let renaming: Signal<Option<String>> = use_signal(|| None);
 
+    // ── Keyboard navigation ──
+    let mut focused_idx: Signal<Option<usize>> = use_signal(|| None);
+    let mut open_namespaces: Signal<HashMap<String, bool>> = use_signal(HashMap::new);
+    let default_open = loaded_namespaces.is_none();
+    let items = nav_items(view_mode(), flat_namespace().as_deref(), &namespaces, &notes, &|path| {
+        open_namespaces.read().get(path).copied().unwrap_or(default_open)
+    });
+    // Mirror the visible rows and props for the key handler below
+    let mut visible = use_signal(Vec::new);
+    if *visible.peek() != items {
+        visible.set(items);
+    }
+    let mut loaded = use_signal(|| loaded_namespaces.clone());
+    if *loaded.peek() != loaded_namespaces {
+        loaded.set(loaded_namespaces.clone());
+    }
+    let focused = use_memo(move || focused_idx().and_then(|i| visible.read().get(i).cloned()));
+    use_context_provider(|| SidebarNav { focused, open: open_namespaces });
+
+    let mut navigate_into = move |ns: String| {
+        if loaded.peek().as_ref().is_some_and(|loaded| !loaded.contains(&ns)) {
+            expand_namespace.call(ns.clone());
+        }
+        slide_dir.set(SlideDir::Right);
+        flat_namespace.set(Some(ns));
+        nav_counter += 1;
+    };
+    let mut navigate_up = move || {
+        slide_dir.set(SlideDir::Left);
+        let current = flat_namespace();
+        if let Some(ref ns) = current {
+            if let Some(parent_end) = ns.rfind('/') {
+                flat_namespace.set(Some(ns[..parent_end].to_string()));
+            } else {
+                flat_namespace.set(None);
+            }
+        }
+        nav_counter += 1;
+    };
+
+    use_effect(move || {
+        spawn(async move {
+            let mut eval = document::eval(SIDEBAR_KEYS_JS);
+            while let Ok(key) = eval.recv::<String>().await {
+                let items = visible.peek().clone();
+                let current = (*focused_idx.peek()).filter(|&i| i < items.len());
+                let last = items.len().checked_sub(1);
+                match key.as_str() {
+                    "ArrowDown" => focused_idx.set(current.map(|i| (i + 1).min(items.len() - 1)).or(last.map(|_| 0))),
+                    "ArrowUp" => focused_idx.set(current.map(|i| i.saturating_sub(1)).or(last)),
+                    "ArrowRight" | "Enter" => match current.map(|i| (i, &items[i])) {
+                        Some((_, NavItem::Namespace(path))) if *view_mode.peek() == ViewMode::Flat => {
+                            navigate_into(path.clone());
+                            focused_idx.set(Some(0));
+                        }
+                        Some((i, NavItem::Namespace(path))) => {
+                            let is_open = open_namespaces.peek().get(path).copied().unwrap_or(default_open);
+                            if is_open {
+                                // Already open: step onto its first child
+                                focused_idx.set(Some((i + 1).min(items.len() - 1)));
+                            } else {
+                                if loaded.peek().as_ref().is_some_and(|loaded| !loaded.contains(path)) {
+                                    expand_namespace.call(path.clone());
+                                }
+                                open_namespaces.write().insert(path.clone(), true);
+                            }
+                        }
+                        Some((_, NavItem::Note(path))) if key == "Enter" => on_select_note.call(path.clone()),
+                        _ => {}
+                    },
+                    "ArrowLeft" if *view_mode.peek() == ViewMode::Flat => {
+                        if flat_namespace.peek().is_some() {
+                            navigate_up();
+                            focused_idx.set(Some(0));
+                        }
+                    }
+                    "ArrowLeft" => {
+                        let Some(item) = current.map(|i| &items[i]) else {
+                            continue;
+                        };
+                        if let NavItem::Namespace(path) = item {
+                            if open_namespaces.peek().get(path).copied().unwrap_or(default_open) {
+                                open_namespaces.write().insert(path.clone(), false);
+                                continue;
+                            }
+                        }
+                        let (NavItem::Namespace(path) | NavItem::Note(path)) = item;
+                        if let Some((parent, _)) = path.rsplit_once('/') {
+                            let parent = NavItem::Namespace(parent.to_string());
+                            if let Some(i) = items.iter().position(|item| *item == parent) {
+                                focused_idx.set(Some(i));
+                            }
+                        }
+                    }
+                    "Escape" => focused_idx.set(None),
+                    _ => {}
+                }
+            }
+        });
+    });
+    // Keep the focused row on screen
+    use_effect(move || {
+        if focused().is_some() {
+            document::eval(
+                r#"requestAnimationFrame(function() {
+                    var row = document.querySelector('[data-sidebar-focused="true"]');
+                    if (row) row.scrollIntoView({ block: 'nearest' });
+                });"#,
+            );
+        }
+    });
+
+    let reveal = try_use_context::<Signal<RevealNamespace>>();
+    {
+        let loaded_namespaces = loaded_namespaces.clone();
+        use_effect(move || {
+            let Some(mut reveal) = reveal else {
+                return;
+            };
+            let Some(ns) = reveal().0 else {
+                return;
+            };
+            reveal.set(RevealNamespace(None));
+            view_mode.set(ViewMode::Flat);
+            if ns.is_empty() {
+                slide_dir.set(SlideDir::Left);
+                flat_namespace.set(None);
+                nav_counter += 1;
+                return;
+            }
+            if loaded_namespaces.as_ref().is_some_and(|loaded| !loaded.contains(&ns)) {
+                expand_namespace.call(ns.clone());
+            }
+            slide_dir.set(SlideDir::Right);
+            flat_namespace.set(Some(ns));
+            nav_counter += 1;
+        });
+    }
+
     rsx! {
         // ── Header: user info + action buttons ──
         SidebarHeader {
@@ -140,12 +394,25 @@ pub fn AppSidebar(
                     Icon { icon: FaPlus }
                 }
             }
+            SearchBar {
+                remote: user.is_some(),
+                on_select_note: on_select_note,
+            }
         }
 
         SidebarSeparator {}
 
         // ── Content: explorer tree or flat view ──
         SidebarContent {
+            PinnedSection {
+                pinned_notes: pinned_notes.clone(),
+                notes: notes.clone(),
+                active_path: active_path.clone(),
+                on_select_note: on_select_note,
+                drag_item: drag_item,
+                toggle_note_pin: toggle_note_pin,
+                on_copy_note: on_copy_note,
+            }
             SidebarGroup {
                 div {
                     class: "flex items-center justify-between",
@@ -154,6 +421,7 @@ pub fn AppSidebar(
                         class: "sidebar-icon-btn mr-2",
                         title: if view_mode() == ViewMode::Tree { "Switch to flat view" } else { "Switch to tree view" },
                         onclick: move |_| {
+                            focused_idx.set(None);
                             if view_mode() == ViewMode::Tree {
                                 view_mode.set(ViewMode::Flat);
                                 flat_namespace.set(None);
@@ -184,6 +452,11 @@ pub fn AppSidebar(
                             on_move_namespace: on_move_namespace,
                             renaming: renaming,
                             on_rename_namespace: on_rename_namespace,
+                            loaded_namespaces: loaded_namespaces.clone(),
+                            expand_namespace: expand_namespace,
+                            pinned_notes: pinned_notes.clone(),
+                            toggle_note_pin: toggle_note_pin,
+                            on_copy_note: on_copy_note,
                         }
                     }
                 } else {
@@ -201,26 +474,24 @@ pub fn AppSidebar(
                         on_move_namespace: on_move_namespace,
                         renaming: renaming,
                         on_rename_namespace: on_rename_namespace,
-                        on_navigate_into: move |ns: String| {
-                            slide_dir.set(SlideDir::Right);
-                            flat_namespace.set(Some(ns));
-                            nav_counter += 1;
-                        },
-                        on_navigate_up: move |_| {
-                            slide_dir.set(SlideDir::Left);
-                            let current = flat_namespace();
-                            if let Some(ref ns) = current {
-                                if let Some(parent_end) = ns.rfind('/') {
-                                    flat_namespace.set(Some(ns[..parent_end].to_string()));
-                                } else {
-                                    flat_namespace.set(None);
-                                }
-                            }
-                            nav_counter += 1;
-                        },
+                        on_navigate_into: move |ns: String| navigate_into(ns),
+                        recent_notes: recent_notes.clone(),
+                        on_navigate_up: move |_| navigate_up(),
+                        pinned_notes: pinned_notes.clone(),
+                        toggle_note_pin: toggle_note_pin,
+                        on_copy_note: on_copy_note,
                     }
                 }
             }
+            TagsSection {
+                notes: notes.clone(),
+                active_path: active_path.clone(),
+                on_select_note: on_select_note,
+                drag_item: drag_item,
+                pinned_notes: pinned_notes.clone(),
+                toggle_note_pin: toggle_note_pin,
+                on_copy_note: on_copy_note,
+            }
         }
 
         SidebarSeparator {}
@@ -270,6 +541,9 @@ pub fn AppSidebar(
                     ThemeToggleItem {}
                 }
                 if user.is_some() {
+                    SidebarMenuItem {
+                        LastSyncItem {}
+                    }
                     SidebarMenuItem {
                         DetachItem { on_detach: on_detach }
                     }
@@ -288,6 +562,313 @@ pub fn AppSidebar(
     }
 }
 
+// ---------------------------------------------------------------------------
+// Search bar (header overlay)
+// ---------------------------------------------------------------------------
+
+/// Delay between the last keystroke and running the search.
+const SEARCH_DEBOUNCE_MS: u64 = 300;
+
+/// Search input with a results overlay.
+///
+/// Signed-in users search the git remote via `api::search_notes` after a
+/// short debounce; anonymous users get instant fuzzy matches from the local
+/// note tree ([`use_note_tree_search`]).
+#[component]
+fn SearchBar(
+    /// Whether to query the git remote instead of the local notes.
+    remote: bool,
+    on_select_note: EventHandler<String>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    let mut results = use_signal(|| Option::<Result<Vec<SearchResult>, String>>::None);
+    let local_hits = use_note_tree_search(query);
+    // Bumped on every keystroke; a pending search only runs if it is still current
+    let mut generation = use_signal(|| 0u64);
+
+    let mut clear = move || {
+        query.set(String::new());
+        results.set(None);
+        generation += 1;
+    };
+    let shown = if remote { results() } else { local_results(&query(), &local_hits()) };
+
+    rsx! {
+        div {
+            class: "sidebar-search",
+            input {
+                class: "sidebar-search-input",
+                r#type: "search",
+                placeholder: "Search notes...",
+                value: query(),
+                oninput: move |evt: FormEvent| {
+                    let q = evt.value();
+                    query.set(q.clone());
+                    generation += 1;
+                    let current = generation();
+                    if q.trim().is_empty() || !remote {
+                        results.set(None);
+                        return;
+                    }
+                    spawn(async move {
+                        #[cfg(target_arch = "wasm32")]
+                        gloo_timers::future::sleep(std::time::Duration::from_millis(SEARCH_DEBOUNCE_MS)).await;
+                        #[cfg(not(target_arch = "wasm32"))]
+                        tokio::time::sleep(std::time::Duration::from_millis(SEARCH_DEBOUNCE_MS)).await;
+
+                        if generation() != current {
+                            return;
+                        }
+                        let found = api::search_notes(q).await.map_err(|e| e.to_string());
+                        // Drop results for a query the user has already changed
+                        if generation() == current {
+                            results.set(Some(found));
+                        }
+                    });
+                },
+                onkeydown: move |evt: Event<KeyboardData>| {
+                    if evt.key() == Key::Escape {
+                        clear();
+                    }
+                },
+            }
+            if let Some(found) = shown {
+                div {
+                    class: "sidebar-search-results",
+                    match found {
+                        Err(e) => rsx! {
+                            p { class: "sidebar-search-empty text-danger", "Search failed: {e}" }
+                        },
+                        Ok(hits) if hits.is_empty() => rsx! {
+                            p { class: "sidebar-search-empty", "No matching notes" }
+                        },
+                        Ok(hits) => rsx! {
+                            for hit in hits {
+                                button {
+                                    key: "{hit.path}",
+                                    class: "sidebar-search-result",
+                                    onclick: {
+                                        let path = hit.path.clone();
+                                        move |_| {
+                                            on_select_note.call(path.clone());
+                                            clear();
+                                        }
+                                    },
+                                    div {
+                                        class: "sidebar-search-result-title",
+                                        Icon { icon: FaFileLines, width: 10, height: 10 }
+                                        span { "{hit.name}" }
+                                        if let Some(ref ns) = hit.namespace {
+                                            span { class: "sidebar-search-result-ns", "{ns}" }
+                                        }
+                                    }
+                                    if !hit.snippet.is_empty() {
+                                        div { class: "sidebar-search-result-snippet", "{hit.snippet}" }
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Local hits in the shape of remote results; `None` while the query is empty.
+fn local_results(query: &str, hits: &[SearchMatch]) -> Option<Result<Vec<SearchResult>, String>> {
+    if query.trim().is_empty() {
+        return None;
+    }
+    Some(Ok(hits
+        .iter()
+        .map(|hit| SearchResult {
+            path: hit.note.path.clone(),
+            name: hit.note.name.clone(),
+            namespace: hit.note.namespace.clone(),
+            snippet: hit.snippet.clone(),
+        })
+        .collect()))
+}
+
+// ---------------------------------------------------------------------------
+// Tags (notes grouped by frontmatter tag)
+// ---------------------------------------------------------------------------
+
+/// Sidebar group listing every frontmatter tag with its note count.
+/// Selecting a tag filters the group down to the notes carrying it.
+/// Renders nothing when no note is tagged.
+#[component]
+fn TagsSection(
+    notes: Vec<TypedNoteInfo>,
+    active_path: Option<String>,
+    on_select_note: EventHandler<String>,
+    drag_item: Signal<Option<DragItem>>,
+    pinned_notes: Vec<String>,
+    toggle_note_pin: EventHandler<String>,
+    on_copy_note: EventHandler<String>,
+) -> Element {
+    let mut selected_tag = use_signal(|| Option::<String>::None);
+
+    let mut by_tag: BTreeMap<&str, Vec<&TypedNoteInfo>> = BTreeMap::new();
+    for note in &notes {
+        for tag in note.tags() {
+            by_tag.entry(tag.as_str()).or_default().push(note);
+        }
+    }
+    if by_tag.is_empty() {
+        return rsx! {};
+    }
+    let tagged: Vec<TypedNoteInfo> = selected_tag()
+        .and_then(|tag| by_tag.get(tag.as_str()).cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .cloned()
+        .collect();
+
+    rsx! {
+        SidebarSeparator {}
+        SidebarGroup {
+            SidebarGroupLabel { "TAGS" }
+            div {
+                class: "sidebar-tags",
+                for (tag, tag_notes) in by_tag.iter() {
+                    button {
+                        key: "{tag}",
+                        class: "sidebar-tag",
+                        "data-active": selected_tag().as_deref() == Some(*tag),
+                        onclick: {
+                            let tag = tag.to_string();
+                            move |_| {
+                                if selected_tag().as_ref() == Some(&tag) {
+                                    selected_tag.set(None);
+                                } else {
+                                    selected_tag.set(Some(tag.clone()));
+                                }
+                            }
+                        },
+                        "#{tag}"
+                        span { class: "sidebar-tag-count", "{tag_notes.len()}" }
+                    }
+                }
+            }
+            if !tagged.is_empty() {
+                SidebarMenu {
+                    for note in tagged {
+                        NoteItem {
+                            key: "{note.path}",
+                            note: note.clone(),
+                            active_path: active_path.clone(),
+                            on_select_note: on_select_note,
+                            drag_item: drag_item,
+                            pinned: pinned_notes.contains(&note.path),
+                            toggle_note_pin: toggle_note_pin,
+                            on_copy_note: on_copy_note,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pinned notes
+// ---------------------------------------------------------------------------
+
+/// Pinned notes, shown above the explorer in both tree and flat views.
+#[component]
+fn PinnedSection(
+    pinned_notes: Vec<String>,
+    notes: Vec<TypedNoteInfo>,
+    active_path: Option<String>,
+    on_select_note: EventHandler<String>,
+    drag_item: Signal<Option<DragItem>>,
+    toggle_note_pin: EventHandler<String>,
+    on_copy_note: EventHandler<String>,
+) -> Element {
+    // Pins may outlive their note (deleted elsewhere); skip those
+    let pinned: Vec<&TypedNoteInfo> = pinned_notes
+        .iter()
+        .filter_map(|path| notes.iter().find(|n| &n.path == path))
+        .collect();
+    if pinned.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        SidebarGroup {
+            SidebarGroupLabel { "PINNED" }
+            SidebarMenu {
+                for note in pinned {
+                    NoteItem {
+                        key: "{note.path}",
+                        note: note.clone(),
+                        active_path: active_path.clone(),
+                        on_select_note: on_select_note,
+                        drag_item: drag_item,
+                        pinned: true,
+                        toggle_note_pin: toggle_note_pin,
+                        on_copy_note: on_copy_note,
+                    }
+                }
+            }
+        }
+        SidebarSeparator {}
+    }
+}
+
+/// Hover action that pins or unpins a note; always visible (as a pushpin
+/// badge) once the note is pinned.
+#[component]
+fn NotePinAction(path: String, pinned: bool, toggle_note_pin: EventHandler<String>) -> Element {
+    rsx! {
+        SidebarMenuAction {
+            show_on_hover: !pinned,
+            as: move |attrs: Vec<Attribute>| {
+                let path = path.clone();
+                rsx! {
+                    button {
+                        class: "sidebar-pin-action",
+                        "data-pinned": "{pinned}",
+                        onclick: move |evt: Event<MouseData>| {
+                            evt.stop_propagation();
+                            toggle_note_pin.call(path.clone());
+                        },
+                        title: if pinned { "Unpin note" } else { "Pin note" },
+                        ..attrs,
+                        Icon { icon: FaThumbtack, width: 10, height: 10 }
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Hover action that duplicates a note.
+#[component]
+fn NoteDuplicateAction(path: String, on_copy_note: EventHandler<String>) -> Element {
+    rsx! {
+        SidebarMenuAction {
+            show_on_hover: true,
+            as: move |attrs: Vec<Attribute>| {
+                let path = path.clone();
+                rsx! {
+                    button {
+                        onclick: move |evt: Event<MouseData>| {
+                            evt.stop_propagation();
+                            on_copy_note.call(path.clone());
+                        },
+                        title: "Duplicate note",
+                        ..attrs,
+                        Icon { icon: FaCopy, width: 10, height: 10 }
+                    }
+                }
+            },
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Explorer tree (recursive namespace + note tree)
 // ---------------------------------------------------------------------------
@@ -306,6 +887,11 @@ fn ExplorerTree(
     on_move_namespace: EventHandler<(String, Option<String>)>,
     renaming: Signal<Option<String>>,
     on_rename_namespace: EventHandler<(String, String)>,
+    loaded_namespaces: Option<HashSet<String>>,
+    expand_namespace: EventHandler<String>,
+    pinned_notes: Vec<String>,
+    toggle_note_pin: EventHandler<String>,
+    on_copy_note: EventHandler<String>,
 ) -> Element {
     let root_namespaces: Vec<&NamespaceInfo> =
         namespaces.iter().filter(|ns| ns.parent.is_none()).collect();
@@ -331,6 +917,11 @@ fn ExplorerTree(
                 on_move_namespace: on_move_namespace,
                 renaming: renaming,
                 on_rename_namespace: on_rename_namespace,
+                loaded_namespaces: loaded_namespaces.clone(),
+                expand_namespace: expand_namespace,
+                pinned_notes: pinned_notes.clone(),
+                toggle_note_pin: toggle_note_pin,
+                on_copy_note: on_copy_note,
             }
         }
 
@@ -341,6 +932,9 @@ fn ExplorerTree(
                 active_path: active_path.clone(),
                 on_select_note: on_select_note,
                 drag_item: drag_item,
+                pinned: pinned_notes.contains(&note.path),
+                toggle_note_pin: toggle_note_pin,
+                on_copy_note: on_copy_note,
             }
         }
 
@@ -387,6 +981,11 @@ fn NamespaceNode(
     on_move_namespace: EventHandler<(String, Option<String>)>,
     renaming: Signal<Option<String>>,
     on_rename_namespace: EventHandler<(String, String)>,
+    loaded_namespaces: Option<HashSet<String>>,
+    expand_namespace: EventHandler<String>,
+    pinned_notes: Vec<String>,
+    toggle_note_pin: EventHandler<String>,
+    on_copy_note: EventHandler<String>,
 ) -> Element {
     let child_namespaces: Vec<&NamespaceInfo> = all_namespaces
         .iter()
@@ -398,9 +997,19 @@ fn NamespaceNode(
         .collect();
 
     let ns_path = namespace.path.clone();
+    let style = namespace_style(&ns_path);
+    let styling = try_use_context::<Signal<StylingNamespace>>();
     let mut drag_counter = use_signal(|| 0i32);
     let mut rename_value = use_signal(|| namespace.name.clone());
     let is_renaming = renaming().as_ref() == Some(&ns_path);
+    // Lazily loaded trees start collapsed and fetch children on first open
+    let children_loaded = loaded_namespaces.as_ref().is_none_or(|loaded| loaded.contains(&ns_path));
+    let mut expand_requested = use_signal(|| false);
+    let nav = try_use_context::<SidebarNav>();
+    let default_open = loaded_namespaces.is_none();
+    // Controlled by the sidebar so the arrow keys can open and close it
+    let open = nav.map(|nav| nav.open.read().get(&ns_path).copied().unwrap_or(default_open));
+    let nav_focused = is_nav_focused(NavItem::Namespace(ns_path.clone()));
 
     rsx! {
         // Wrapper div for drag events (components can't receive event handlers directly)
@@ -438,8 +1047,21 @@ fn NamespaceNode(
                 }
             },
         Collapsible {
-            default_open: true,
+            default_open: default_open,
+            open: open,
             keep_mounted: true,
+            on_open_change: {
+                let ns_path = ns_path.clone();
+                move |open: bool| {
+                    if let Some(mut nav) = nav {
+                        nav.open.write().insert(ns_path.clone(), open);
+                    }
+                    if open && !children_loaded && !expand_requested() {
+                        expand_requested.set(true);
+                        expand_namespace.call(ns_path.clone());
+                    }
+                }
+            },
             SidebarMenuItem {
                 if is_renaming {
                     // Inline rename input (raw <input> for onmounted support)
@@ -449,7 +1071,7 @@ fn NamespaceNode(
                         rsx! {
                             div {
                                 class: "flex items-center gap-1 px-2 py-1",
-                                Icon { icon: FaFolder, width: 12, height: 12 }
+                                NamespaceIcon { style: style.clone() }
                                 input {
                                     class: "flex-1 text-sm h-6 bg-transparent border border-current/20 rounded px-1 outline-none",
                                     r#type: "text",
@@ -493,19 +1115,46 @@ fn NamespaceNode(
                     as: {
                         let namespace_name = namespace.name.clone();
                         let ns_path_dbl = ns_path.clone();
+                        let style = style.clone();
                         move |attrs: Vec<Attribute>| {
                             let namespace_name = namespace_name.clone();
                             let ns_path_dbl = ns_path_dbl.clone();
+                            let ns_path_style = ns_path_dbl.clone();
                             rsx! {
                                 SidebarMenuButton {
                                     attributes: attrs,
+                                    "data-sidebar-focused": nav_focused,
                                     tooltip: rsx! { "{namespace_name}" },
-                                    Icon { icon: FaFolder, width: 12, height: 12 }
                                     span {
-                                        ondoubleclick: move |evt: Event<MouseData>| {
+                                        class: "inline-flex",
+                                        oncontextmenu: move |evt: Event<MouseData>| {
+                                            evt.prevent_default();
                                             evt.stop_propagation();
-                                            rename_value.set(namespace_name.clone());
-                                            renaming.set(Some(ns_path_dbl.clone()));
+                                            if let Some(mut styling) = styling {
+                                                styling.set(StylingNamespace(Some(ns_path_style.clone())));
+                                            }
+                                        },
+                                        NamespaceIcon { style: style.clone() }
+                                    }
+                                    span {
+                                        ondoubleclick: {
+                                            let namespace_name = namespace_name.clone();
+                                            let ns_path_dbl = ns_path_dbl.clone();
+                                            move |evt: Event<MouseData>| {
+                                                evt.stop_propagation();
+                                                rename_value.set(namespace_name.clone());
+                                                renaming.set(Some(ns_path_dbl.clone()));
+                                            }
+                                        },
+                                        // Right-click on the name renames (on the icon it customizes)
+                                        oncontextmenu: {
+                                            let namespace_name = namespace_name.clone();
+                                            move |evt: Event<MouseData>| {
+                                                evt.prevent_default();
+                                                evt.stop_propagation();
+                                                rename_value.set(namespace_name.clone());
+                                                renaming.set(Some(ns_path_dbl.clone()));
+                                            }
                                         },
                                         "{namespace_name}"
                                     }
@@ -514,6 +1163,28 @@ fn NamespaceNode(
                         }
                     },
                 }
+                if let Some(mut styling) = styling {
+                    SidebarMenuAction {
+                        show_on_hover: true,
+                        as: {
+                            let ns_path = ns_path.clone();
+                            move |attrs: Vec<Attribute>| {
+                                let ns_path = ns_path.clone();
+                                rsx! {
+                                    button {
+                                        onclick: move |evt: Event<MouseData>| {
+                                            evt.stop_propagation();
+                                            styling.set(StylingNamespace(Some(ns_path.clone())));
+                                        },
+                                        title: "Customize namespace",
+                                        ..attrs,
+                                        Icon { icon: FaEllipsis }
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
                 SidebarMenuAction {
                     show_on_hover: true,
                     as: {
@@ -592,6 +1263,18 @@ fn NamespaceNode(
                                 on_move_namespace: on_move_namespace,
                                 renaming: renaming,
                                 on_rename_namespace: on_rename_namespace,
+                                loaded_namespaces: loaded_namespaces.clone(),
+                                expand_namespace: expand_namespace,
+                                pinned_notes: pinned_notes.clone(),
+                                toggle_note_pin: toggle_note_pin,
+                                on_copy_note: on_copy_note,
+                            }
+                        }
+                        if !children_loaded {
+                            div {
+                                class: "sidebar-loading",
+                                span { class: "sidebar-spinner" }
+                                "Loading..."
                             }
                         }
                         for note in child_notes {
@@ -601,6 +1284,9 @@ fn NamespaceNode(
                                 active_path: active_path.clone(),
                                 on_select_note: on_select_note,
                                 drag_item: drag_item,
+                                pinned: pinned_notes.contains(&note.path),
+                                toggle_note_pin: toggle_note_pin,
+                                on_copy_note: on_copy_note,
                             }
                         }
                     }
@@ -618,12 +1304,19 @@ fn NoteItem(
     active_path: Option<String>,
     on_select_note: EventHandler<String>,
     drag_item: Signal<Option<DragItem>>,
+    #[props(default)]
+    pinned: bool,
+    #[props(default)]
+    toggle_note_pin: EventHandler<String>,
+    #[props(default)]
+    on_copy_note: EventHandler<String>,
 ) -> Element {
     let is_active = active_path.as_ref() == Some(&note.path);
     let path = note.path.clone();
     let path_for_drag = path.clone();
-    let note_name = note.name.clone();
+    let note_name = note.title().to_string();
     let note_type = note.r#type.clone();
+    let nav_focused = is_nav_focused(NavItem::Note(note.path.clone()));
 
     rsx! {
         div {
@@ -643,6 +1336,7 @@ fn NoteItem(
                         rsx! {
                             button {
                                 onclick: move |_| on_select_note.call(path.clone()),
+                                "data-sidebar-focused": nav_focused,
                                 ..attrs,
                                 Icon { icon: FaFileLines, width: 12, height: 12 }
                                 span { "{note_name}" }
@@ -655,6 +1349,8 @@ fn NoteItem(
                         }
                     },
                 }
+                NoteDuplicateAction { path: note.path.clone(), on_copy_note: on_copy_note }
+                NotePinAction { path: note.path.clone(), pinned: pinned, toggle_note_pin: toggle_note_pin }
             }
         }
     }
@@ -666,11 +1362,18 @@ fn NoteSubItem(
     active_path: Option<String>,
     on_select_note: EventHandler<String>,
     drag_item: Signal<Option<DragItem>>,
+    #[props(default)]
+    pinned: bool,
+    #[props(default)]
+    toggle_note_pin: EventHandler<String>,
+    #[props(default)]
+    on_copy_note: EventHandler<String>,
 ) -> Element {
     let is_active = active_path.as_ref() == Some(&note.path);
     let path = note.path.clone();
     let path_for_drag = path.clone();
-    let note_name = note.name.clone();
+    let note_name = note.title().to_string();
+    let nav_focused = is_nav_focused(NavItem::Note(note.path.clone()));
 
     rsx! {
         div {
@@ -688,6 +1391,7 @@ fn NoteSubItem(
                     rsx! {
                         button {
                             onclick: move |_| on_select_note.call(path.clone()),
+                            "data-sidebar-focused": nav_focused,
                             ..attrs,
                             Icon { icon: FaFileLines, width: 12, height: 12 }
                             span { "{note_name}" }
@@ -695,6 +1399,8 @@ fn NoteSubItem(
                     }
                 },
             }
+            NoteDuplicateAction { path: note.path.clone(), on_copy_note: on_copy_note }
+            NotePinAction { path: note.path.clone(), pinned: pinned, toggle_note_pin: toggle_note_pin }
         }
         } // close wrapper div
     }
@@ -721,7 +1427,18 @@ fn FlatExplorerView(
     on_rename_namespace: EventHandler<(String, String)>,
     on_navigate_into: EventHandler<String>,
     on_navigate_up: EventHandler<()>,
+    pinned_notes: Vec<String>,
+    toggle_note_pin: EventHandler<String>,
+    on_copy_note: EventHandler<String>,
+    recent_notes: Vec<String>,
 ) -> Element {
+    // Recently opened notes that still exist, newest first
+    let recent: Vec<&TypedNoteInfo> = recent_notes
+        .iter()
+        .filter_map(|path| notes.iter().find(|n| &n.path == path))
+        .take(RECENT_NOTES_SHOWN)
+        .collect();
+
     // Filter to direct children of current namespace
     let child_namespaces: Vec<&NamespaceInfo> = namespaces
         .iter()
@@ -754,6 +1471,28 @@ fn FlatExplorerView(
     });
 
     rsx! {
+        if !recent.is_empty() {
+            div {
+                class: "px-2 py-1 text-xs opacity-50 font-medium",
+                "Recent"
+            }
+            SidebarMenu {
+                for note in recent {
+                    FlatNoteItem {
+                        key: "recent-{note.path}",
+                        path: note.path.clone(),
+                        name: note.name.clone(),
+                        note_type: note.r#type.clone(),
+                        is_active: active_path.as_ref() == Some(&note.path),
+                        drag_item: drag_item,
+                        on_select_note: on_select_note,
+                        pinned: pinned_notes.contains(&note.path),
+                        toggle_note_pin: toggle_note_pin,
+                        on_copy_note: on_copy_note,
+                    }
+                }
+            }
+        }
         div {
             class: "overflow-hidden",
             div {
@@ -842,47 +1581,46 @@ fn FlatExplorerView(
                         }
                     }
 
-                    // Notes
-                    for note in child_notes {
-                        {
-                            let is_active = active_path.as_ref() == Some(&note.path);
-                            let path = note.path.clone();
-                            let note_name = note.name.clone();
-                            let note_type = note.r#type.clone();
-                            let path_for_drag = path.clone();
-                            rsx! {
-                                div {
-                                    key: "{path}",
-                                    draggable: "true",
-                                    ondragstart: move |_| {
-                                        drag_item.set(Some(DragItem::Note { path: path_for_drag.clone() }));
-                                    },
-                                    ondragend: move |_| drag_item.set(None),
-                                    SidebarMenuItem {
-                                        SidebarMenuButton {
-                                            is_active: is_active,
-                                            tooltip: rsx! { "{note_name}" },
-                                            as: move |attrs: Vec<Attribute>| {
-                                                let path = path.clone();
-                                                let note_name = note_name.clone();
-                                                let note_type = note_type.clone();
-                                                rsx! {
-                                                    button {
-                                                        onclick: move |_| on_select_note.call(path.clone()),
-                                                        ..attrs,
-                                                        Icon { icon: FaFileLines, width: 12, height: 12 }
-                                                        span { "{note_name}" }
-                                                        Badge {
-                                                            variant: BadgeVariant::Secondary,
-                                                            class: "ml-auto text-[0.625rem]",
-                                                            "{note_type}"
-                                                        }
-                                                    }
-                                                }
-                                            },
+                    // Notes (windowed once the namespace gets large)
+                    if child_notes.len() > VIRTUAL_LIST_THRESHOLD {
+                        VirtualList {
+                            item_count: child_notes.len(),
+                            item_height_px: FLAT_NOTE_ITEM_HEIGHT_PX,
+                            render_item: {
+                                let notes: Vec<TypedNoteInfo> = child_notes.iter().map(|n| (*n).clone()).collect();
+                                let active_path = active_path.clone();
+                                let pinned_notes = pinned_notes.clone();
+                                move |idx: usize| {
+                                    let note = &notes[idx];
+                                    rsx! {
+                                        FlatNoteItem {
+                                            path: note.path.clone(),
+                                            name: note.name.clone(),
+                                            note_type: note.r#type.clone(),
+                                            is_active: active_path.as_ref() == Some(&note.path),
+                                            drag_item: drag_item,
+                                            on_select_note: on_select_note,
+                                            pinned: pinned_notes.contains(&note.path),
+                                            toggle_note_pin: toggle_note_pin,
+                                            on_copy_note: on_copy_note,
                                         }
-                                    } // close SidebarMenuItem
-                                } // close wrapper div
+                                    }
+                                }
+                            },
+                        }
+                    } else {
+                        for note in child_notes {
+                            FlatNoteItem {
+                                key: "{note.path}",
+                                path: note.path.clone(),
+                                name: note.name.clone(),
+                                note_type: note.r#type.clone(),
+                                is_active: active_path.as_ref() == Some(&note.path),
+                                drag_item: drag_item,
+                                on_select_note: on_select_note,
+                                pinned: pinned_notes.contains(&note.path),
+                                toggle_note_pin: toggle_note_pin,
+                                on_copy_note: on_copy_note,
                             }
                         }
                     }
@@ -892,6 +1630,70 @@ fn FlatExplorerView(
     }
 }
 
+/// Recent notes listed at the top of the flat view.
+const RECENT_NOTES_SHOWN: usize = 5;
+
+/// Notes in one flat-view namespace above which the list is virtualised.
+const VIRTUAL_LIST_THRESHOLD: usize = 100;
+
+/// Height of a [`FlatNoteItem`] row (a default-size menu button).
+const FLAT_NOTE_ITEM_HEIGHT_PX: f64 = 32.0;
+
+/// A single note row in the flat view — drag source.
+#[component]
+fn FlatNoteItem(
+    path: String,
+    name: String,
+    note_type: String,
+    is_active: bool,
+    drag_item: Signal<Option<DragItem>>,
+    on_select_note: EventHandler<String>,
+    pinned: bool,
+    toggle_note_pin: EventHandler<String>,
+    on_copy_note: EventHandler<String>,
+) -> Element {
+    let path_for_drag = path.clone();
+    let path_for_pin = path.clone();
+    let path_for_copy = path.clone();
+    let nav_focused = is_nav_focused(NavItem::Note(path.clone()));
+    rsx! {
+        div {
+            draggable: "true",
+            ondragstart: move |_| {
+                drag_item.set(Some(DragItem::Note { path: path_for_drag.clone() }));
+            },
+            ondragend: move |_| drag_item.set(None),
+            SidebarMenuItem {
+                SidebarMenuButton {
+                    is_active: is_active,
+                    tooltip: rsx! { "{name}" },
+                    as: move |attrs: Vec<Attribute>| {
+                        let path = path.clone();
+                        let name = name.clone();
+                        let note_type = note_type.clone();
+                        rsx! {
+                            button {
+                                onclick: move |_| on_select_note.call(path.clone()),
+                                "data-sidebar-focused": nav_focused,
+                                ..attrs,
+                                Icon { icon: FaFileLines, width: 12, height: 12 }
+                                span { "{name}" }
+                                Badge {
+                                    variant: BadgeVariant::Secondary,
+                                    class: "ml-auto text-[0.625rem]",
+                                    "{note_type}"
+                                }
+                            }
+                        }
+                    },
+                }
+                NoteDuplicateAction { path: path_for_copy, on_copy_note: on_copy_note }
+                NotePinAction { path: path_for_pin, pinned: pinned, toggle_note_pin: toggle_note_pin }
+            }
+        }
+    }
+}
+
 /// A single namespace row in the flat view — drag source + drop target.
 #[component]
 fn FlatNsItem(
@@ -905,6 +1707,9 @@ fn FlatNsItem(
     renaming: Signal<Option<String>>,
     on_rename_namespace: EventHandler<(String, String)>,
 ) -> Element {
+    let style = namespace_style(&ns_path);
+    let styling = try_use_context::<Signal<StylingNamespace>>();
+    let nav_focused = is_nav_focused(NavItem::Namespace(ns_path.clone()));
     let mut drag_counter = use_signal(|| 0i32);
     let mut rename_value = use_signal(|| ns_name.clone());
     let is_renaming = renaming().as_ref() == Some(&ns_path);
@@ -948,7 +1753,7 @@ fn FlatNsItem(
                 SidebarMenuItem {
                     div {
                         class: "flex items-center gap-1 px-2 py-1 w-full",
-                        Icon { icon: FaFolder, width: 12, height: 12 }
+                        NamespaceIcon { style: style.clone() }
                         input {
                             class: "flex-1 text-sm h-6 bg-transparent border border-current/20 rounded px-1 outline-none",
                             r#type: "text",
@@ -995,20 +1800,46 @@ fn FlatNsItem(
                         let ns_path = ns_path.clone();
                         let ns_name = ns_name.clone();
                         let ns_path_dbl = ns_path.clone();
+                        let style = style.clone();
                         move |attrs: Vec<Attribute>| {
                             let ns_path = ns_path.clone();
                             let ns_name = ns_name.clone();
                             let ns_path_dbl = ns_path_dbl.clone();
+                            let ns_path_style = ns_path.clone();
                             rsx! {
                                 button {
                                     onclick: move |_| on_navigate_into.call(ns_path.clone()),
+                                    "data-sidebar-focused": nav_focused,
                                     ..attrs,
-                                    Icon { icon: FaFolder, width: 12, height: 12 }
                                     span {
-                                        ondoubleclick: move |evt: Event<MouseData>| {
+                                        class: "inline-flex",
+                                        oncontextmenu: move |evt: Event<MouseData>| {
+                                            evt.prevent_default();
                                             evt.stop_propagation();
-                                            rename_value.set(ns_name.clone());
-                                            renaming.set(Some(ns_path_dbl.clone()));
+                                            if let Some(mut styling) = styling {
+                                                styling.set(StylingNamespace(Some(ns_path_style.clone())));
+                                            }
+                                        },
+                                        NamespaceIcon { style: style.clone() }
+                                    }
+                                    span {
+                                        ondoubleclick: {
+                                            let ns_name = ns_name.clone();
+                                            let ns_path_dbl = ns_path_dbl.clone();
+                                            move |evt: Event<MouseData>| {
+                                                evt.stop_propagation();
+                                                rename_value.set(ns_name.clone());
+                                                renaming.set(Some(ns_path_dbl.clone()));
+                                            }
+                                        },
+                                        oncontextmenu: {
+                                            let ns_name = ns_name.clone();
+                                            move |evt: Event<MouseData>| {
+                                                evt.prevent_default();
+                                                evt.stop_propagation();
+                                                rename_value.set(ns_name.clone());
+                                                renaming.set(Some(ns_path_dbl.clone()));
+                                            }
                                         },
                                         "{ns_name}"
                                     }
@@ -1018,6 +1849,28 @@ fn FlatNsItem(
                         }
                     },
                 }
+                if let Some(mut styling) = styling {
+                    SidebarMenuAction {
+                        show_on_hover: true,
+                        as: {
+                            let ns_path = ns_path.clone();
+                            move |attrs: Vec<Attribute>| {
+                                let ns_path = ns_path.clone();
+                                rsx! {
+                                    button {
+                                        onclick: move |evt: Event<MouseData>| {
+                                            evt.stop_propagation();
+                                            styling.set(StylingNamespace(Some(ns_path.clone())));
+                                        },
+                                        title: "Customize namespace",
+                                        ..attrs,
+                                        Icon { icon: FaEllipsis }
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
                 SidebarMenuAction {
                 show_on_hover: true,
                 as: {
@@ -1070,14 +1923,8 @@ fn ThemeToggleItem() -> Element {
                 rsx! {
                     button {
                         onclick: move |_| {
-                            let next = match theme().as_deref() {
-                                None => Some("dark".to_string()),
-                                Some("dark") => Some("light".to_string()),
-                                _ => None,
-                            };
-                            apply_theme(next.as_deref());
                             let mut theme = theme;
-                            theme.set(next);
+                            cycle_theme(&mut theme);
                         },
                         ..attrs,
                         {icon.clone()}
@@ -1089,6 +1936,17 @@ fn ThemeToggleItem() -> Element {
     }
 }
 
+/// Switch to the next theme (system → dark → light → system) and apply it.
+pub(crate) fn cycle_theme(theme: &mut ThemeSignal) {
+    let next = match theme().as_deref() {
+        None => Some("dark".to_string()),
+        Some("dark") => Some("light".to_string()),
+        _ => None,
+    };
+    apply_theme(next.as_deref());
+    theme.set(next);
+}
+
 /// Apply theme to the document and persist to localStorage.
 pub fn apply_theme(theme: Option<&str>) {
     #[cfg(target_arch = "wasm32")]
@@ -1143,6 +2001,7 @@ fn LogoutItem() -> Element {
                                 user: None,
                                 loading: false,
                                 online: auth_state().online,
+                                extended: None,
                             });
                         }
                     },
@@ -1173,6 +2032,27 @@ fn LoginItem(on_navigate_login: EventHandler<()>) -> Element {
     }
 }
 
+#[component]
+fn LastSyncItem() -> Element {
+    let queue = crate::use_sync_queue();
+    let pending = queue().dirty_paths.len();
+    let label = match queue().last_sync {
+        Some(time) => format!("Last sync {time}"),
+        None => "Not synced yet".to_string(),
+    };
+
+    rsx! {
+        div {
+            class: "sidebar-last-sync",
+            title: "Notes are pulled and queued saves pushed at the auto-sync interval",
+            span { "{label}" }
+            if pending > 0 {
+                span { class: "sidebar-last-sync-pending", "{pending} pending" }
+            }
+        }
+    }
+}
+
 #[component]
 fn DetachItem(on_detach: EventHandler<()>) -> Element {
     rsx! {
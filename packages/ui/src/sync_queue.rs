@@ -0,0 +1,283 @@
+use std::collections::{HashMap, HashSet};
+
+use dioxus::prelude::*;
+
+use crate::activity_log::current_time;
+use crate::{make_repo_for_user, log_activity, ActivityLog, LogLevel};
+
+/// Notes saved locally that the git remote has not seen yet.
+///
+/// Provided as `Signal<SyncQueue>` via context in `SidebarLayoutView`. Paths are
+/// deduplicated, so saving a note many times between flushes costs a single
+/// push of its latest content.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SyncQueue {
+    /// Paths of notes with unpushed local changes.
+    pub dirty_paths: HashSet<String>,
+    /// Time of the last sync that left nothing pending (`HH:MM:SS`).
+    pub last_sync: Option<String>,
+    /// Blob SHA of each note as last seen on the remote (after a pull or a
+    /// push), sent as `base_sha` so the server can spot edits made elsewhere.
+    /// Kept in the local store across reloads, see [`load_remote_shas`].
+    pub remote_shas: HashMap<String, String>,
+    /// Notes the server refused to overwrite, waiting for the user to choose.
+    pub conflicts: Vec<api::SyncConflict>,
+    /// Bumped after each resolved conflict so an open editor reloads the note.
+    pub resolved: u64,
+    /// Remote commit of the last pull, the base unpushed notes are merged
+    /// from on the next one.
+    pub remote_head: Option<String>,
+}
+
+impl SyncQueue {
+    /// Queue a note for the next push.
+    pub fn mark_dirty(&mut self, path: &str) {
+        self.dirty_paths.insert(path.to_string());
+    }
+
+    /// Drop a note from the queue (e.g. after it was deleted).
+    pub fn forget(&mut self, path: &str) {
+        self.dirty_paths.remove(path);
+        self.remote_shas.remove(path);
+        self.conflicts.retain(|c| c.path != path);
+    }
+
+    /// Remember that the remote holds `content` at `path`.
+    pub fn record_remote(&mut self, path: &str, content: &str) {
+        let (sha, _) = store::objects::hash_blob(&store::objects::Blob {
+            content: content.as_bytes().to_vec(),
+        });
+        self.remote_shas.insert(path.to_string(), sha.to_hex());
+    }
+
+    /// Hold `conflict` for the user, replacing an older one for the same note.
+    pub fn add_conflict(&mut self, conflict: api::SyncConflict) {
+        self.conflicts.retain(|c| c.path != conflict.path);
+        self.conflicts.push(conflict);
+    }
+
+    pub fn is_dirty(&self, path: &str) -> bool {
+        self.dirty_paths.contains(path)
+    }
+
+    /// Remove and return every queued path, sorted.
+    pub fn take_batch(&mut self) -> Vec<String> {
+        let mut batch: Vec<String> = self.dirty_paths.drain().collect();
+        batch.sort();
+        batch
+    }
+
+    /// Record a completed sync.
+    pub fn mark_synced(&mut self) {
+        self.last_sync = Some(current_time());
+    }
+}
+
+/// [`store::Repository::set_meta`] entry holding [`SyncQueue::remote_shas`].
+const REMOTE_SHAS_META: &str = "remote-shas";
+
+/// [`store::Repository::set_meta`] entry holding the remote head of the last
+/// pull, the base of the next [`pull_changes`].
+const REMOTE_HEAD_META: &str = "remote-head";
+
+/// The `remote_shas` last saved in `user_id`'s local store by
+/// [`save_remote_shas`]; empty if there are none.
+pub async fn load_remote_shas(user_id: Option<&str>) -> HashMap<String, String> {
+    let Some(data) = make_repo_for_user(user_id).get_meta(REMOTE_SHAS_META).await else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&data)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(sha, path)| (path.to_string(), sha.to_string()))
+        .collect()
+}
+
+/// Save `shas` in `user_id`'s local store, one `<sha> <path>` line per note.
+pub async fn save_remote_shas(user_id: Option<&str>, shas: &HashMap<String, String>) {
+    let mut lines: Vec<String> = shas.iter().map(|(path, sha)| format!("{sha} {path}\n")).collect();
+    lines.sort();
+    make_repo_for_user(user_id)
+        .set_meta(REMOTE_SHAS_META, lines.concat().as_bytes())
+        .await;
+}
+
+/// Consume the `Signal<SyncQueue>` from context.
+pub fn use_sync_queue() -> Signal<SyncQueue> {
+    use_context::<Signal<SyncQueue>>()
+}
+
+/// Pull from the git remote, sending the notes still queued in `queue` so the
+/// server can merge them with the remote history. Notes changed on both sides
+/// are added to the queue's conflicts, and the pulled commit becomes the base
+/// of the next pull. Without a queue this is a plain pull.
+pub async fn pull_and_merge(
+    queue: Option<Signal<SyncQueue>>,
+    user_id: Option<&str>,
+) -> Result<api::PullResult, ServerFnError> {
+    let mut base = None;
+    let mut local = Vec::new();
+    if let Some(queue) = queue {
+        let mut paths: Vec<String> = queue.peek().dirty_paths.iter().cloned().collect();
+        paths.sort();
+        base = queue.peek().remote_head.clone();
+        let repo = make_repo_for_user(user_id);
+        for path in paths {
+            if let Some(note) = repo.get_note(&path).await {
+                local.push(api::RemoteFile { path, content: note.note });
+            }
+        }
+    }
+
+    let result = api::pull_notes(base, local).await?;
+    if let Some(mut queue) = queue {
+        let mut queue = queue.write();
+        queue.remote_head = result.head.clone();
+        for conflict in &result.conflicts {
+            queue.add_conflict(conflict.clone());
+        }
+    }
+    Ok(result)
+}
+
+/// Pull what changed on the remote since `user_id`'s last successful pull,
+/// with [`api::pull_notes_since`]. The first pull (no remote head stored yet)
+/// is a full [`pull_and_merge`]; only that one fills in `namespaces`. When the
+/// local store is still empty, the first pull imports the whole remote from
+/// [`api::download_pack`] instead (see [`bootstrap_from_pack`]).
+///
+/// The remote `head` each pull returns is kept in the local store (see
+/// [`store::Repository::set_meta`]) and becomes the queue's `remote_head`;
+/// the next pull asks for the notes that differ from it, so nothing pushed
+/// meanwhile is missed, whatever the commit timestamps say.
+pub async fn pull_changes(
+    queue: Option<Signal<SyncQueue>>,
+    user_id: Option<&str>,
+) -> Result<api::PullResult, ServerFnError> {
+    let repo = make_repo_for_user(user_id);
+    let last_head = repo
+        .get_meta(REMOTE_HEAD_META)
+        .await
+        .and_then(|data| String::from_utf8(data).ok());
+    let result = match last_head {
+        Some(base) => api::pull_notes_since(base).await?,
+        None if repo.get_head().await.is_none() => match bootstrap_from_pack(queue, user_id).await {
+            Ok(result) => result,
+            Err(_) => pull_and_merge(queue, user_id).await?,
+        },
+        None => pull_and_merge(queue, user_id).await?,
+    };
+    if let Some(head) = &result.head {
+        repo.set_meta(REMOTE_HEAD_META, head.as_bytes()).await;
+        if let Some(mut queue) = queue {
+            queue.write().remote_head = Some(head.clone());
+        }
+    }
+    Ok(result)
+}
+
+/// Fill `user_id`'s empty local store with the remote's objects and history
+/// in one download, rather than writing the pulled notes one by one.
+///
+/// The notes are already committed locally, so the result carries no files,
+/// only the remote `head`; they are recorded as seen on the remote in `queue`.
+async fn bootstrap_from_pack(
+    queue: Option<Signal<SyncQueue>>,
+    user_id: Option<&str>,
+) -> Result<api::PullResult, ServerFnError> {
+    let mut stream = api::download_pack().await?;
+    let mut compressed = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        compressed.extend_from_slice(&chunk.map_err(|e| ServerFnError::new(e.to_string()))?);
+    }
+    let pack = store::pack::gunzip(&compressed).map_err(|e| ServerFnError::new(e))?;
+    let head = store::pack::first_object(&pack).ok_or_else(|| ServerFnError::new("Empty pack"))?;
+    let repo = make_repo_for_user(user_id);
+    repo.import_from_pack(&pack, head.clone())
+        .await
+        .map_err(|e| ServerFnError::new(e))?;
+
+    if let Some(mut queue) = queue {
+        let notes = repo.list_notes().await;
+        let mut queue = queue.write();
+        queue.remote_head = Some(head.to_hex());
+        for note in &notes {
+            queue.record_remote(&note.path, &note.note);
+        }
+    }
+    Ok(api::PullResult {
+        files: Vec::new(),
+        namespaces: Vec::new(),
+        head: Some(head.to_hex()),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Push every queued note to the remote with its current local content, in
+/// a single [`api::batch_sync_notes`] commit, so a burst of edits to many
+/// notes costs one fetch and one push.
+///
+/// Notes that fail to push go back into the queue; notes that changed on the
+/// remote meanwhile move to `conflicts` instead. Returns `(pushed, failed)`,
+/// conflicts counting as failed.
+pub async fn flush_sync_queue(
+    mut queue: Signal<SyncQueue>,
+    user_id: Option<&str>,
+    log: &mut Signal<ActivityLog>,
+) -> (usize, usize) {
+    let batch = queue.write().take_batch();
+    if batch.is_empty() {
+        return (0, 0);
+    }
+
+    let repo = make_repo_for_user(user_id);
+    let mut notes = Vec::new();
+    let mut local_shas = Vec::new();
+    let mut base_shas = HashMap::new();
+    for path in batch {
+        // Deleted locally since it was queued
+        let Some(note) = repo.get_note(&path).await else {
+            continue;
+        };
+        if let Some(base_sha) = queue.peek().remote_shas.get(&path) {
+            base_shas.insert(path.clone(), base_sha.clone());
+        }
+        local_shas.push((path.clone(), note.sha));
+        notes.push((path, note.note, note.r#type));
+    }
+    if notes.is_empty() {
+        return (0, 0);
+    }
+
+    let mut pushed = 0;
+    let mut failed = 0;
+    match api::batch_sync_notes(notes, base_shas).await {
+        Ok(conflicts) => {
+            for (path, sha) in local_shas {
+                // Not retried: the user picks a version in the conflict dialog
+                if let Some(conflict) = conflicts.iter().find(|c| c.path == path) {
+                    failed += 1;
+                    queue.write().add_conflict(conflict.clone());
+                    log_activity(log, LogLevel::Warning, &format!("Conflict in {path}: it changed on the remote"));
+                } else {
+                    pushed += 1;
+                    queue.write().remote_shas.insert(path.clone(), sha);
+                    log_activity(log, LogLevel::Success, &format!("Synced {path}"));
+                }
+            }
+        }
+        Err(e) => {
+            failed = local_shas.len();
+            let mut queue = queue.write();
+            for (path, _) in &local_shas {
+                queue.mark_dirty(path);
+            }
+            log_activity(log, LogLevel::Error, &format!("Sync error for {failed} notes: {e}"));
+        }
+    }
+
+    if failed == 0 {
+        queue.write().mark_synced();
+    }
+    (pushed, failed)
+}
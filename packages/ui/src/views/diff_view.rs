@@ -0,0 +1,79 @@
+use dioxus::prelude::*;
+
+const VIEWS_CSS: Asset = asset!("/src/views/views.css");
+
+/// Unified diff between two commits, fetched from the git remote (web only).
+///
+/// Each changed note is rendered as a `<pre>` block with added and removed
+/// lines coloured via the `diff-add` / `diff-remove` classes.
+#[component]
+pub fn DiffView(
+    /// Older commit SHA (hex).
+    commit_a: String,
+    /// Newer commit SHA (hex).
+    commit_b: String,
+    /// Only show the diff for this note path, if set.
+    #[props(default)]
+    path: Option<String>,
+) -> Element {
+    let commits = use_signal(|| (commit_a.clone(), commit_b.clone()));
+    let filter = use_signal(|| path.clone());
+
+    let diffs = use_resource(move || async move {
+        let (a, b) = commits();
+        api::get_commit_diff(a, b).await.map(|diffs| {
+            diffs
+                .into_iter()
+                .filter(|d| filter().is_none_or(|p| p == d.path))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        div {
+            class: "diff-view",
+            match &*diffs.read() {
+                None => rsx! {
+                    p { class: "view-muted px-3 py-2", "Loading changes..." }
+                },
+                Some(Err(e)) => rsx! {
+                    p { class: "view-muted px-3 py-2 text-danger", "Could not load changes: {e}" }
+                },
+                Some(Ok(diffs)) if diffs.is_empty() => rsx! {
+                    p { class: "view-muted px-3 py-2", "No changes." }
+                },
+                Some(Ok(diffs)) => rsx! {
+                    for diff in diffs.iter() {
+                        div {
+                            key: "{diff.path}",
+                            class: "diff-file",
+                            div { class: "diff-file-path", "{diff.path}" }
+                            pre {
+                                class: "diff-body",
+                                for (i, line) in diff.unified_diff.lines().enumerate() {
+                                    span { key: "{i}", class: diff_line_class(line), "{line}\n" }
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// CSS class for one line of a unified diff.
+fn diff_line_class(line: &str) -> &'static str {
+    if line.starts_with("+++") || line.starts_with("---") {
+        "diff-header"
+    } else if line.starts_with("@@") {
+        "diff-hunk"
+    } else if line.starts_with('+') {
+        "diff-add"
+    } else if line.starts_with('-') {
+        "diff-remove"
+    } else {
+        "diff-context"
+    }
+}
@@ -7,8 +7,18 @@ pub use notes_placeholder::NotesPlaceholder;
 mod sidebar_layout;
 pub use sidebar_layout::SidebarLayoutView;
 
+mod diff_view;
+pub use diff_view::DiffView;
+
 mod note_detail;
-pub use note_detail::NoteDetailView;
+pub use note_detail::{NoteDetailView, NoteStats, NoteBacklinksPanel, NoteExportMenu, ContentStats, SplitEditorView, ViewMode,
+    TableOfContents, TocHeading, extract_headings};
+
+mod shared_note;
+pub use shared_note::{NoteSharePanel, SharedNoteView};
 
 mod settings;
 pub use settings::SettingsView;
+
+mod verify_email;
+pub use verify_email::VerifyEmailView;
@@ -1,8 +1,16 @@
 use dioxus::prelude::*;
 
-use crate::components::{use_toast, ToastOptions};
-use crate::{NoteEditor, NoteTree, use_note_tree, LogLevel, log_activity, use_activity_log, use_auth};
-use crate::make_repo_for_user;
+use crate::components::{
+    use_toast, Button, ButtonVariant, DropdownMenu, DropdownMenuContent, DropdownMenuItem,
+    DropdownMenuTrigger, Input, Label, ToastOptions,
+};
+use crate::icons::FaFileExport;
+use crate::{NoteEditor, NoteTree, RevealNamespace, use_note_tree, LogLevel, log_activity, use_activity_log, use_auth};
+use crate::{flush_sync_queue, pull_and_merge, use_sync_queue};
+use crate::{make_repo_for_user, Icon};
+use crate::download::download_bytes;
+use crate::note_crypto::{note_key, unlock_note_key};
+use super::{DiffView, ModalOverlay, NoteSharePanel};
 
 const VIEWS_CSS: Asset = asset!("/src/views/views.css");
 
@@ -27,6 +35,18 @@ pub fn NoteDetailView(
     /// Whether rename is enabled.
     #[props(default = true)]
     enable_rename: bool,
+    /// Whether to show word count and reading time below the editor.
+    #[props(default = true)]
+    show_stats: bool,
+    /// Whether to save automatically once typing pauses.
+    #[props(default = true)]
+    auto_save_enabled: bool,
+    /// Pause after the last edit before auto-saving, in milliseconds.
+    #[props(default = 2000)]
+    auto_save_delay_ms: u64,
+    /// Whether the markdown editor uses Vim keybindings.
+    #[props(default)]
+    vim_mode: bool,
 ) -> Element {
     // Track decoded path in a signal so use_resource re-runs on route param change
     let mut path_signal = use_signal(|| note_path.clone());
@@ -37,36 +57,64 @@ pub fn NoteDetailView(
     let mut tree = use_note_tree();
     let mut current_note = use_signal(|| Option::<store::TypedNoteInfo>::None);
     let mut auto_sync_secs = use_signal(|| 300u32);
+    let mut push_on_save = use_signal(|| true);
+    let mut sync_queue = use_sync_queue();
     let mut activity_log = use_activity_log();
     let toast_api = use_toast();
     let auth = use_auth();
     let mut load_generation: Signal<u64> = use_signal(|| 0);
+    // Latest edited content for the stats bar, tagged with the SHA of the note
+    // it was typed into so a reload falls back to the stored content
+    let mut stats_content = use_signal(|| Option::<(String, String)>::None);
+    let mut stats_generation: Signal<u64> = use_signal(|| 0);
+    let mut save_status = use_signal(|| SaveStatus::Idle);
+    // Key for encrypted notes, once the passphrase is known on this device
+    let mut encryption_key = use_signal(|| Option::<[u8; 32]>::None);
+    // Content waiting for the user to confirm encrypting it
+    let mut confirm_encryption = use_signal(|| Option::<String>::None);
+    // Content waiting to be encrypted until a passphrase is entered
+    let mut pending_encryption = use_signal(|| Option::<String>::None);
+    let mut passphrase_error = use_signal(|| Option::<String>::None);
+    let readable = use_memo(move || current_note().map(|note| readable_note(note, encryption_key())));
+
+    // Reload after a sync conflict was resolved elsewhere in the layout
+    let resolved_conflicts = use_memo(move || sync_queue().resolved);
 
     // Load current note and optionally refresh from remote
     let _loader = use_resource(move || {
         let path = path_signal();
+        let _ = resolved_conflicts();
         async move {
             load_generation += 1;
             let gen = load_generation();
 
             let user_id = auth().user.as_ref().map(|u| u.id.clone());
             let repo = make_repo_for_user(user_id.as_deref());
-            current_note.set(repo.get_note(&path).await);
+            let note = repo.get_note(&path).await;
+            if note.as_ref().is_some_and(|n| n.encrypted) && encryption_key.peek().is_none() {
+                encryption_key.set(note_key(user_id.as_deref()).await);
+            }
+            current_note.set(note);
             let config = repo.get_config().await;
-            auto_sync_secs.set(config.sync.auto_sync_interval_secs);
+            let namespace = path.rsplit_once('/').map_or("", |(ns, _)| ns);
+            auto_sync_secs.set(config.effective_for_namespace(namespace).auto_sync_interval_secs);
+            push_on_save.set(config.sync.push_on_save);
 
             if enable_pull_on_load && auth().user.is_some() {
                 spawn(async move {
                     log_activity(&mut activity_log, LogLevel::Info, &format!("Pulling latest for {path}..."));
-                    match api::pull_notes().await {
+                    let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                    match pull_and_merge(Some(sync_queue), user_id.as_deref()).await {
                         Ok(result) => {
-                            let user_id = auth().user.as_ref().map(|u| u.id.clone());
                             let repo = make_repo_for_user(user_id.as_deref());
                             for file in &result.files {
-                                let ext = file.path.rsplit('.').next().unwrap_or("md");
-                                let note_type = store::models::note_type_from_ext(ext);
-                                let stem = file.path.trim_end_matches(&format!(".{ext}"));
-                                repo.write_note(stem, &file.content, note_type).await;
+                                // Keep local edits that are still waiting to be pushed
+                                if sync_queue.peek().is_dirty(&file.path) {
+                                    continue;
+                                }
+                                let note_type = store::models::note_type_from_path(&file.path);
+                                repo.write_note(&file.path, &file.content, note_type).await;
+                                sync_queue.write().record_remote(&file.path, &file.content);
                             }
                             for ns in &result.namespaces {
                                 repo.create_namespace(ns).await;
@@ -95,6 +143,21 @@ pub fn NoteDetailView(
             let user_id = auth().user.as_ref().map(|u| u.id.clone());
             let repo = make_repo_for_user(user_id.as_deref());
             if let Some(note) = current_note() {
+                let content = if note.encrypted {
+                    let Some(key) = encryption_key() else {
+                        return;
+                    };
+                    match api::crypto::seal_note(&content, &key) {
+                        Ok(sealed) => sealed,
+                        Err(e) => {
+                            toast_api.error(format!("Could not encrypt {path}: {e}"), ToastOptions::new());
+                            return;
+                        }
+                    }
+                } else {
+                    content
+                };
+                save_status.set(SaveStatus::Saving);
                 let stem = path.trim_end_matches(&format!(
                     ".{}",
                     store::models::ext_from_note_type(&note.r#type)
@@ -103,21 +166,26 @@ pub fn NoteDetailView(
                 // Don't re-fetch: the editor's content Signal is already up to date.
                 // Re-fetching would change the SHA → change the key → remount the editor.
                 tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
+                save_status.set(SaveStatus::Saved);
                 log_activity(&mut activity_log, LogLevel::Info, &format!("Saved {path}"));
-                toast_api.success("Saved".to_string(), ToastOptions::new());
+                // With auto-save on, the status bar reports saves instead
+                if !auto_save_enabled {
+                    toast_api.success("Saved".to_string(), ToastOptions::new());
+                }
 
-                // Git sync (if enabled and logged in)
+                // Git sync (if enabled and logged in): queue the note, and push
+                // right away unless the config defers pushes to the periodic sync
                 if enable_git_sync && auth().user.is_some() {
-                    match api::sync_note(path.clone(), content.clone(), note.r#type.clone()).await {
-                        Ok(()) => {
-                            log_activity(&mut activity_log, LogLevel::Success, &format!("Synced {path}"));
-                            toast_api.success("Synced".to_string(), ToastOptions::new());
-                        }
-                        Err(e) => {
-                            log_activity(&mut activity_log, LogLevel::Error, &format!("Sync error: {e}"));
-                            toast_api.error(format!("Sync failed: {e}"), ToastOptions::new());
-                            #[cfg(target_arch = "wasm32")]
-                            web_sys::console::warn_1(&format!("Git sync: {e}").into());
+                    sync_queue.write().mark_dirty(&path);
+                    if push_on_save() {
+                        match flush_sync_queue(sync_queue, user_id.as_deref(), &mut activity_log).await {
+                            (_, 0) if auto_save_enabled => {}
+                            (_, 0) => toast_api.success("Synced".to_string(), ToastOptions::new()),
+                            (_, failed) => {
+                                toast_api.error(format!("Sync failed for {failed} note(s), will retry"), ToastOptions::new());
+                                #[cfg(target_arch = "wasm32")]
+                                web_sys::console::warn_1(&format!("Git sync: {failed} note(s) failed").into());
+                            }
                         }
                     }
                 }
@@ -129,7 +197,10 @@ pub fn NoteDetailView(
         let old_path = path_signal();
         spawn(async move {
             if let Some(note) = current_note() {
-                let ext = store::models::ext_from_note_type(&note.r#type);
+                let mut ext = store::models::ext_from_note_type(&note.r#type).to_string();
+                if note.encrypted {
+                    ext = format!("{ext}.{}", store::models::ENCRYPTED_EXT);
+                }
                 let new_path = if let Some(ns) = &note.namespace {
                     format!("{ns}/{new_name}.{ext}")
                 } else {
@@ -143,6 +214,11 @@ pub fn NoteDetailView(
                 let user_id = auth().user.as_ref().map(|u| u.id.clone());
                 let repo = make_repo_for_user(user_id.as_deref());
                 repo.rename_note(&old_path, &new_path).await;
+                if sync_queue.peek().is_dirty(&old_path) {
+                    let mut queue = sync_queue.write();
+                    queue.forget(&old_path);
+                    queue.mark_dirty(&new_path);
+                }
                 current_note.set(repo.get_note(&new_path).await);
                 tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
 
@@ -155,12 +231,157 @@ pub fn NoteDetailView(
         });
     };
 
+    // A version was restored on the remote: mirror it locally and reload the editor
+    let handle_restore = move |content: String| {
+        let path = path_signal();
+        spawn(async move {
+            let user_id = auth().user.as_ref().map(|u| u.id.clone());
+            let repo = make_repo_for_user(user_id.as_deref());
+            if let Some(note) = current_note() {
+                let stem = path.trim_end_matches(&format!(
+                    ".{}",
+                    store::models::ext_from_note_type(&note.r#type)
+                ));
+                repo.write_note(stem, &content, &note.r#type).await;
+                // New blob SHA → new editor key → editor remounts with restored content
+                current_note.set(repo.get_note(&path).await);
+                tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
+                log_activity(&mut activity_log, LogLevel::Success, &format!("Restored {path}"));
+                toast_api.success("Restored".to_string(), ToastOptions::new());
+            }
+        });
+    };
+
+    // Move the note between `name.md` and `name.md.enc`, sealing or opening
+    // the editor's content on the way. Only the current version moves: the
+    // commits before it keep whatever they stored.
+    let apply_encryption = move |content: String| {
+        let old_path = path_signal();
+        spawn(async move {
+            let Some(note) = current_note() else {
+                return;
+            };
+            let (new_path, stored) = if note.encrypted {
+                let suffix = format!(".{}", store::models::ENCRYPTED_EXT);
+                (old_path.strip_suffix(&suffix).unwrap_or(&old_path).to_string(), content)
+            } else {
+                let Some(key) = encryption_key() else {
+                    pending_encryption.set(Some(content));
+                    return;
+                };
+                match api::crypto::seal_note(&content, &key) {
+                    Ok(sealed) => (format!("{old_path}.{}", store::models::ENCRYPTED_EXT), sealed),
+                    Err(e) => {
+                        toast_api.error(format!("Could not encrypt {old_path}: {e}"), ToastOptions::new());
+                        return;
+                    }
+                }
+            };
+
+            let user_id = auth().user.as_ref().map(|u| u.id.clone());
+            let repo = make_repo_for_user(user_id.as_deref());
+            repo.write_note(&new_path, &stored, &note.r#type).await;
+            repo.delete_note(&old_path).await;
+            sync_queue.write().forget(&old_path);
+
+            if enable_git_sync && auth().user.is_some() {
+                // Remove the old file from the remote's tree too. Its earlier
+                // commits still hold the plaintext, as the confirmation says.
+                if let Err(e) = api::delete_note_remote(old_path.clone()).await {
+                    log_activity(&mut activity_log, LogLevel::Error, &format!("Delete sync error: {e}"));
+                }
+                sync_queue.write().mark_dirty(&new_path);
+                flush_sync_queue(sync_queue, user_id.as_deref(), &mut activity_log).await;
+            }
+
+            current_note.set(repo.get_note(&new_path).await);
+            tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
+            path_signal.set(new_path.clone());
+            let action = if note.encrypted { "Decrypted" } else { "Encrypted" };
+            log_activity(&mut activity_log, LogLevel::Info, &format!("{action} {new_path}"));
+            toast_api.success(action.to_string(), ToastOptions::new());
+
+            on_navigate_note.call(new_path);
+        });
+    };
+
+    // Decrypting needs no warning; encrypting is confirmed first
+    let toggle_encryption = move |content: String| {
+        if current_note.peek().as_ref().is_some_and(|note| note.encrypted) {
+            apply_encryption(content);
+        } else {
+            confirm_encryption.set(Some(content));
+        }
+    };
+
+    let handle_passphrase = move |(passphrase, remember): (String, bool)| {
+        spawn(async move {
+            let user_id = auth().user.as_ref().map(|u| u.id.clone());
+            // Check the passphrase against a note it should open: this one,
+            // or any other encrypted note when encrypting the first time here
+            let sealed = match current_note().filter(|note| note.encrypted) {
+                Some(note) => Some(note.note),
+                None => make_repo_for_user(user_id.as_deref())
+                    .list_notes()
+                    .await
+                    .into_iter()
+                    .find(|note| note.encrypted)
+                    .map(|note| note.note),
+            };
+            match unlock_note_key(user_id.as_deref(), &passphrase, sealed.as_deref(), remember).await {
+                Ok(key) => {
+                    passphrase_error.set(None);
+                    encryption_key.set(Some(key));
+                    if let Some(content) = pending_encryption.write().take() {
+                        apply_encryption(content);
+                    }
+                }
+                Err(e) => passphrase_error.set(Some(e)),
+            }
+        });
+    };
+
+    // Recompute stats only once typing pauses
+    let handle_change = move |content: String| {
+        let Some(sha) = current_note.peek().as_ref().map(|n| n.sha.clone()) else {
+            return;
+        };
+        stats_generation += 1;
+        let current = stats_generation();
+        spawn(async move {
+            #[cfg(target_arch = "wasm32")]
+            gloo_timers::future::sleep(std::time::Duration::from_millis(STATS_DEBOUNCE_MS)).await;
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::time::sleep(std::time::Duration::from_millis(STATS_DEBOUNCE_MS)).await;
+
+            if stats_generation() == current {
+                stats_content.set(Some((sha, content)));
+            }
+        });
+    };
+
+    // `[[wiki link]]` targets: every note as "namespace/name"
+    let link_targets: Vec<String> = tree.read().notes.iter().map(wiki_link_target).collect();
+    let handle_wiki_link = move |target: String| match resolve_wiki_link(&tree.peek().notes, &target) {
+        Some(path) => on_navigate_note.call(path),
+        None => toast_api.error(format!("No note named \"{target}\""), ToastOptions::new()),
+    };
+
+    // Breadcrumb clicks open the namespace in the sidebar explorer
+    let reveal = try_use_context::<Signal<RevealNamespace>>();
+    let handle_navigate_namespace = move |ns: String| {
+        if let Some(mut reveal) = reveal {
+            reveal.set(RevealNamespace(Some(ns)));
+        }
+    };
+
     let handle_delete = move |_| {
         let path = path_signal();
         spawn(async move {
             let user_id = auth().user.as_ref().map(|u| u.id.clone());
             let repo = make_repo_for_user(user_id.as_deref());
             repo.delete_note(&path).await;
+            sync_queue.write().forget(&path);
             tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
             log_activity(&mut activity_log, LogLevel::Info, &format!("Deleted {path}"));
 
@@ -180,7 +401,7 @@ pub fn NoteDetailView(
     };
 
     rsx! {
-        if let Some(note) = current_note() {
+        if let Some(Ok(note)) = readable() {
             if enable_rename {
                 NoteEditor {
                     key: "{note.sha}",
@@ -189,6 +410,14 @@ pub fn NoteDetailView(
                     on_delete: handle_delete,
                     on_rename: handle_rename,
                     auto_sync_interval_secs: auto_sync_secs(),
+                    on_change: handle_change,
+                    auto_save_delay_ms: auto_save_enabled.then_some(auto_save_delay_ms),
+                    link_targets: link_targets.clone(),
+                    on_navigate_note: handle_wiki_link,
+                    vim_mode: vim_mode,
+                    export_enabled: enable_git_sync && auth().user.is_some(),
+                    on_toggle_encryption: toggle_encryption,
+                    on_navigate_namespace: handle_navigate_namespace,
                 }
             } else {
                 NoteEditor {
@@ -197,8 +426,53 @@ pub fn NoteDetailView(
                     on_save: handle_save,
                     on_delete: handle_delete,
                     auto_sync_interval_secs: auto_sync_secs(),
+                    on_change: handle_change,
+                    auto_save_delay_ms: auto_save_enabled.then_some(auto_save_delay_ms),
+                    link_targets: link_targets.clone(),
+                    on_navigate_note: handle_wiki_link,
+                    vim_mode: vim_mode,
+                    export_enabled: enable_git_sync && auth().user.is_some(),
+                    on_toggle_encryption: toggle_encryption,
+                    on_navigate_namespace: handle_navigate_namespace,
                 }
             }
+            if show_stats || save_status() != SaveStatus::Idle {
+                div {
+                    class: "note-status-bar",
+                    if show_stats {
+                        NoteStats {
+                            content: match stats_content() {
+                                Some((sha, content)) if sha == note.sha => content,
+                                _ => note.note.clone(),
+                            },
+                        }
+                    }
+                    match save_status() {
+                        SaveStatus::Idle => rsx! {},
+                        SaveStatus::Saving => rsx! { span { class: "note-save-status", "Saving…" } },
+                        SaveStatus::Saved => rsx! { span { class: "note-save-status", "Saved" } },
+                    }
+                }
+            }
+            NoteBacklinksPanel {
+                key: "backlinks-{note.path}",
+                note_path: note.path.clone(),
+                on_select: move |path: String| on_navigate_note.call(path),
+            }
+            if enable_git_sync && auth().user.is_some() {
+                NoteSharePanel { key: "share-{note.path}", note_path: note.path.clone() }
+                NoteHistoryPanel {
+                    key: "{note.path}",
+                    note_path: note.path.clone(),
+                    on_restore: handle_restore,
+                }
+            }
+        } else if let Some(Err(message)) = readable() {
+            document::Link { rel: "stylesheet", href: VIEWS_CSS }
+            div {
+                class: "view-placeholder",
+                NotePassphraseForm { message, error: passphrase_error(), on_submit: handle_passphrase }
+            }
         } else {
             document::Link { rel: "stylesheet", href: VIEWS_CSS }
             div {
@@ -206,5 +480,798 @@ pub fn NoteDetailView(
                 h2 { "Loading..." }
             }
         }
+        if confirm_encryption().is_some() {
+            ModalOverlay {
+                on_close: move |_| confirm_encryption.set(None),
+                div {
+                    class: "modal-body",
+                    h2 { class: "modal-title", "Encrypt Note" }
+                    p {
+                        class: "modal-text",
+                        "From now on this note is stored encrypted with your passphrase. Without the passphrase it cannot be opened again; it cannot be recovered."
+                    }
+                    p {
+                        class: "modal-detail",
+                        "Earlier versions in the note's history stay readable, on this device and on your git remote."
+                    }
+                    div {
+                        class: "modal-actions",
+                        Button {
+                            variant: ButtonVariant::Primary,
+                            onclick: move |_| {
+                                if let Some(content) = confirm_encryption.write().take() {
+                                    apply_encryption(content);
+                                }
+                            },
+                            "Encrypt"
+                        }
+                        Button {
+                            variant: ButtonVariant::Outline,
+                            onclick: move |_| confirm_encryption.set(None),
+                            "Cancel"
+                        }
+                    }
+                }
+            }
+        }
+        if pending_encryption().is_some() {
+            ModalOverlay {
+                on_close: move |_| {
+                    pending_encryption.set(None);
+                    passphrase_error.set(None);
+                },
+                NotePassphraseForm {
+                    message: "Enter the passphrase your encrypted notes are locked with. It never leaves this device.",
+                    error: passphrase_error(),
+                    on_submit: handle_passphrase,
+                }
+            }
+        }
+    }
+}
+
+/// `note` with its content decrypted for display, or why it cannot be.
+fn readable_note(note: store::TypedNoteInfo, key: Option<[u8; 32]>) -> Result<store::TypedNoteInfo, String> {
+    if !note.encrypted {
+        return Ok(note);
+    }
+    let key = key.ok_or_else(|| "This note is encrypted. Enter your passphrase to open it.".to_string())?;
+    let plaintext = api::crypto::open_note(&note.note, &key)?;
+    Ok(store::TypedNoteInfo {
+        metadata: store::frontmatter::parse_frontmatter(&plaintext),
+        note: plaintext,
+        ..note
+    })
+}
+
+/// Asks for the passphrase of encrypted notes; `message` says why and
+/// `error` why the last one was refused. Submits `(passphrase, remember)`.
+#[component]
+fn NotePassphraseForm(message: String, error: Option<String>, on_submit: EventHandler<(String, bool)>) -> Element {
+    let mut passphrase = use_signal(String::new);
+    let mut remember = use_signal(|| false);
+
+    rsx! {
+        div {
+            class: "modal-body",
+            h2 { class: "modal-title", "Passphrase" }
+            p { class: "view-muted", "{message}" }
+            div {
+                class: "modal-field",
+                Label { html_for: "note-passphrase", "Passphrase" }
+                Input {
+                    id: "note-passphrase",
+                    r#type: "password",
+                    value: passphrase(),
+                    oninput: move |evt: FormEvent| passphrase.set(evt.value()),
+                }
+            }
+            // Only the browser has somewhere to keep the key between sessions
+            if cfg!(target_arch = "wasm32") {
+                label {
+                    class: "flex items-center gap-1 text-sm",
+                    input {
+                        r#type: "checkbox",
+                        checked: remember(),
+                        onchange: move |evt: FormEvent| remember.set(evt.checked()),
+                    }
+                    "Remember on this device"
+                }
+            }
+            if let Some(e) = error {
+                p { class: "text-[0.8125rem] text-danger mt-2", "{e}" }
+            }
+            div {
+                class: "modal-actions",
+                Button {
+                    variant: ButtonVariant::Primary,
+                    disabled: passphrase().is_empty(),
+                    onclick: move |_| on_submit.call((passphrase(), remember())),
+                    "Unlock"
+                }
+            }
+        }
+    }
+}
+
+/// Save indicator shown in the status bar under the editor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SaveStatus {
+    /// Nothing saved since the note was opened.
+    Idle,
+    Saving,
+    Saved,
+}
+
+/// Delay after the last keystroke before [`NoteStats`] is recomputed.
+const STATS_DEBOUNCE_MS: u64 = 500;
+
+/// Average reading speed used for the reading time estimate.
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Content statistics shown in the [`NoteStats`] bar.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ContentStats {
+    pub word_count: usize,
+    /// Unicode scalar values in the body, excluding frontmatter.
+    pub char_count: usize,
+    /// `word_count / 200`, rounded up.
+    pub reading_time_minutes: usize,
+    /// Fenced code blocks (```` ``` ```` or `~~~`).
+    pub code_block_count: usize,
+}
+
+impl ContentStats {
+    pub fn from_content(content: &str) -> Self {
+        let body = store::frontmatter::strip_frontmatter(content);
+        let word_count = body.split_whitespace().count();
+
+        // Count opening fences; a closing fence must use the same marker
+        let mut code_block_count = 0;
+        let mut open_fence: Option<&str> = None;
+        for line in body.lines() {
+            let line = line.trim_start();
+            match open_fence {
+                Some(fence) if line.starts_with(fence) => open_fence = None,
+                Some(_) => {}
+                None => {
+                    if let Some(fence) = ["```", "~~~"].into_iter().find(|f| line.starts_with(f)) {
+                        open_fence = Some(fence);
+                        code_block_count += 1;
+                    }
+                }
+            }
+        }
+
+        Self {
+            word_count,
+            char_count: body.chars().count(),
+            reading_time_minutes: word_count.div_ceil(WORDS_PER_MINUTE),
+            code_block_count,
+        }
+    }
+}
+
+/// Subtle status bar with word count, reading time and code blocks.
+#[component]
+pub fn NoteStats(
+    /// Note content, frontmatter included.
+    content: String,
+) -> Element {
+    let stats = ContentStats::from_content(&content);
+    let plural = |n: usize, one: &str, many: &str| {
+        if n == 1 { format!("{n} {one}") } else { format!("{n} {many}") }
+    };
+
+    let mut parts = vec![
+        plural(stats.word_count, "word", "words"),
+        format!("{} min read", stats.reading_time_minutes),
+    ];
+    if stats.code_block_count > 0 {
+        parts.push(plural(stats.code_block_count, "code block", "code blocks"));
+    }
+    let summary = parts.join(" · ");
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        span {
+            class: "note-stats",
+            title: "{stats.char_count} characters",
+            "{summary}"
+        }
+    }
+}
+
+/// How a markdown note is shown in the editor area.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ViewMode {
+    /// Live-preview editor only.
+    #[default]
+    Edit,
+    /// Rendered HTML only.
+    Preview,
+    /// Editor on the left, rendered HTML on the right.
+    Split,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 3] = [ViewMode::Edit, ViewMode::Split, ViewMode::Preview];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewMode::Edit => "Edit",
+            ViewMode::Preview => "Preview",
+            ViewMode::Split => "Split",
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ViewMode::Edit => "edit",
+            ViewMode::Preview => "preview",
+            ViewMode::Split => "split",
+        }
+    }
+}
+
+/// Narrowest either pane of [`SplitEditorView`] can be dragged to, in percent.
+const MIN_PANE_PCT: f64 = 20.0;
+
+static SPLIT_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// [`MarkdownEditor`](crate::MarkdownEditor) with a rendered preview pane.
+///
+/// The preview is rendered from the same `content` signal with
+/// [`api::render::render_markdown_html`], so it follows every keystroke. In
+/// [`ViewMode::Split`] the divider between the panes can be dragged (double
+/// click resets it) and scrolling the editor scrolls the preview to the same
+/// relative position. The editor stays mounted in [`ViewMode::Preview`] so its
+/// undo history survives switching modes.
+#[component]
+pub fn SplitEditorView(
+    content: Signal<String>,
+    mode: ViewMode,
+    on_change: EventHandler<String>,
+    #[props(default)] on_blur: EventHandler<()>,
+    /// Note names offered when typing `[[`.
+    #[props(default)] link_targets: Vec<String>,
+    /// Called with the target of a clicked `[[wiki link]]`.
+    #[props(default)] on_navigate_note: EventHandler<String>,
+    /// Enable Vim keybindings in the editor.
+    #[props(default)] vim_mode: bool,
+) -> Element {
+    let toast_api = use_toast();
+    let split_id = use_hook(|| {
+        let n = SPLIT_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        format!("md-split-{n}")
+    });
+
+    // Track the mode in a signal so the memo below stops rendering in Edit mode
+    let mut mode_signal = use_signal(|| mode);
+    if *mode_signal.peek() != mode {
+        mode_signal.set(mode);
+    }
+    let preview_html = use_memo(move || match mode_signal() {
+        ViewMode::Edit => String::new(),
+        _ => api::render::render_markdown_html(&content()),
+    });
+
+    // Editor pane width in percent, and the split's client rect while dragging
+    let mut editor_pct = use_signal(|| 50.0f64);
+    let mut dragging = use_signal(|| false);
+    let mut container = use_signal(|| Option::<std::rc::Rc<MountedData>>::None);
+    let mut bounds = use_signal(|| (0.0f64, 0.0f64));
+
+    let start_drag = move |evt: PointerEvent| {
+        evt.prevent_default();
+        dragging.set(true);
+        spawn(async move {
+            if let Some(el) = container() {
+                if let Ok(rect) = el.get_client_rect().await {
+                    bounds.set((rect.origin.x, rect.size.width));
+                }
+            }
+        });
+    };
+
+    let drag = move |evt: PointerEvent| {
+        if !dragging() {
+            return;
+        }
+        let (left, width) = bounds();
+        if width > 0.0 {
+            let pct = (evt.client_coordinates().x - left) / width * 100.0;
+            editor_pct.set(pct.clamp(MIN_PANE_PCT, 100.0 - MIN_PANE_PCT));
+        }
+    };
+
+    let on_mounted = {
+        let split_id = split_id.clone();
+        move |evt: MountedEvent| {
+            container.set(Some(evt.data()));
+            // Proportional scroll sync: whatever scrolls inside the editor pane
+            // drags the preview along
+            let js = format!(
+                r#"var root = document.getElementById('{split_id}');
+                if (root) root.addEventListener('scroll', function(e) {{
+                    var editor = root.querySelector('.md-split-editor');
+                    var preview = root.querySelector('.md-preview');
+                    if (!editor || !preview || !editor.contains(e.target)) return;
+                    var max = e.target.scrollHeight - e.target.clientHeight;
+                    if (max <= 0) return;
+                    preview.scrollTop = e.target.scrollTop / max * (preview.scrollHeight - preview.clientHeight);
+                }}, true);"#,
+            );
+            document::eval(&js);
+        }
+    };
+
+    let editor_style = match mode {
+        ViewMode::Split => format!("flex-basis: {}%", editor_pct()),
+        _ => String::new(),
+    };
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        div {
+            id: "{split_id}",
+            class: "md-split",
+            "data-view-mode": mode.as_str(),
+            "data-dragging": dragging(),
+            onmounted: on_mounted,
+            onpointermove: drag,
+            onpointerup: move |_| dragging.set(false),
+            onpointerleave: move |_| dragging.set(false),
+
+            div {
+                class: "md-split-editor",
+                style: "{editor_style}",
+                crate::MarkdownEditor {
+                    content: content,
+                    on_change: on_change,
+                    on_blur: on_blur,
+                    placeholder: "Start writing...".to_string(),
+                    link_targets: link_targets,
+                    on_navigate_note: on_navigate_note,
+                    vim_mode: vim_mode,
+                    on_attachment_error: move |message: String| {
+                        toast_api.error(message, ToastOptions::new());
+                    },
+                }
+            }
+            if mode == ViewMode::Split {
+                div {
+                    class: "md-split-divider",
+                    role: "separator",
+                    aria_orientation: "vertical",
+                    title: "Drag to resize",
+                    onpointerdown: start_drag,
+                    ondoubleclick: move |_| editor_pct.set(50.0),
+                }
+            }
+            if mode != ViewMode::Edit {
+                div {
+                    class: "md-preview",
+                    dangerous_inner_html: "{preview_html}",
+                }
+            }
+        }
     }
 }
+
+/// A heading listed in the [`TableOfContents`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TocHeading {
+    /// 1 for `#` through 6 for `######`.
+    pub level: usize,
+    pub text: String,
+    /// 1-based line number in the full note, frontmatter included.
+    pub line: usize,
+}
+
+/// ATX headings (`## Title`) of a markdown note, in document order.
+///
+/// Frontmatter and fenced code blocks are skipped; setext headings
+/// (underlined with `===` / `---`) are not recognised.
+pub fn extract_headings(content: &str) -> Vec<TocHeading> {
+    let body = store::frontmatter::strip_frontmatter(content);
+    let first_line = content[..content.len() - body.len()].matches('\n').count() + 1;
+
+    let mut headings = Vec::new();
+    let mut open_fence: Option<&str> = None;
+    for (i, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+        match open_fence {
+            Some(fence) if trimmed.starts_with(fence) => open_fence = None,
+            Some(_) => {}
+            None => {
+                if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+                    open_fence = Some(fence);
+                    continue;
+                }
+                // At most three spaces of indentation, then 1-6 `#` and a space
+                if line.len() - trimmed.len() > 3 {
+                    continue;
+                }
+                let level = trimmed.bytes().take_while(|b| *b == b'#').count();
+                let rest = &trimmed[level..];
+                if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+                    continue;
+                }
+                let text = rest.trim().trim_end_matches('#').trim_end();
+                if !text.is_empty() {
+                    headings.push(TocHeading {
+                        level,
+                        text: text.to_string(),
+                        line: first_line + i,
+                    });
+                }
+            }
+        }
+    }
+    headings
+}
+
+/// Floating outline of a note's headings.
+///
+/// Entries are indented by level relative to the shallowest heading; clicking
+/// one calls `on_jump_to_heading` with its index in `headings`.
+#[component]
+pub fn TableOfContents(
+    headings: Vec<TocHeading>,
+    on_jump_to_heading: EventHandler<usize>,
+    #[props(default)] on_close: EventHandler<()>,
+) -> Element {
+    let top_level = headings.iter().map(|h| h.level).min().unwrap_or(1);
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        nav {
+            class: "toc-panel",
+            aria_label: "Table of contents",
+            div {
+                class: "toc-panel-header",
+                span { "Contents" }
+                button {
+                    class: "toc-panel-close",
+                    title: "Hide table of contents",
+                    onclick: move |_| on_close.call(()),
+                    "×"
+                }
+            }
+            ol {
+                class: "toc-list",
+                for (idx, heading) in headings.into_iter().enumerate() {
+                    li {
+                        key: "{idx}",
+                        class: "toc-item",
+                        "data-level": heading.level,
+                        style: "padding-left: {(heading.level - top_level) as f32 * 0.75}rem",
+                        a {
+                            href: "#",
+                            onclick: move |evt: MouseEvent| {
+                                evt.prevent_default();
+                                on_jump_to_heading.call(idx);
+                            },
+                            "{heading.text}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Collapsible "Linked from" list of the notes that link to this one.
+///
+/// Backlinks are found in the local repository with
+/// [`store::Repository::find_backlinks`], so the panel works offline; the list
+/// is refreshed whenever the note tree changes.
+#[component]
+pub fn NoteBacklinksPanel(
+    /// Full note path (e.g. "folder/note.md").
+    note_path: String,
+    /// Called with the path of a clicked backlink.
+    on_select: EventHandler<String>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let path = use_signal(|| note_path.clone());
+    let tree = use_note_tree();
+    let auth = use_auth();
+
+    let backlinks = use_resource(move || async move {
+        if !open() {
+            return None;
+        }
+        // Subscribe to the tree so edits elsewhere refresh the list
+        let _ = tree.read();
+        let user_id = auth().user.as_ref().map(|u| u.id.clone());
+        let repo = make_repo_for_user(user_id.as_deref());
+        Some(repo.find_backlinks(&path()).await)
+    });
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        div {
+            class: "note-history",
+            button {
+                class: "note-history-header",
+                onclick: move |_| open.toggle(),
+                span { "Linked from" }
+                span { class: "note-history-chevron", if open() { "▾" } else { "▸" } }
+            }
+            if open() {
+                div {
+                    class: "note-history-list",
+                    match &*backlinks.read() {
+                        None | Some(None) => rsx! {
+                            p { class: "view-muted px-3 py-2", "Finding backlinks..." }
+                        },
+                        Some(Some(notes)) if notes.is_empty() => rsx! {
+                            p { class: "view-muted px-3 py-2", "No other notes link here." }
+                        },
+                        Some(Some(notes)) => rsx! {
+                            for note in notes.iter().cloned() {
+                                button {
+                                    key: "{note.path}",
+                                    class: "note-history-item note-backlink",
+                                    onclick: {
+                                        let path = note.path.clone();
+                                        move |_| on_select.call(path.clone())
+                                    },
+                                    div { class: "note-history-message", "{note.title()}" }
+                                    if let Some(ns) = &note.namespace {
+                                        div { class: "note-history-meta", "{ns}" }
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Header dropdown that downloads the note from the git remote as HTML or PDF
+/// (see [`api::export_note_html`] and [`api::export_note_pdf`]).
+#[component]
+pub fn NoteExportMenu(
+    /// Full note path (e.g. "folder/note.md").
+    note_path: String,
+    /// Download name, without extension.
+    file_stem: String,
+) -> Element {
+    let toast_api = use_toast();
+    let mut activity_log = use_activity_log();
+    let mut exporting = use_signal(|| false);
+    let path = use_signal(|| note_path.clone());
+    let stem = use_signal(|| download_stem(&file_stem));
+
+    let export = use_callback(move |format: String| {
+        if exporting() {
+            return;
+        }
+        spawn(async move {
+            exporting.set(true);
+            let result = match format.as_str() {
+                "pdf" => api::export_note_pdf(path())
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| download_bytes(bytes, &format!("{}.pdf", stem()), "application/pdf")),
+                _ => api::export_note_html(path())
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|html| download_bytes(html.into_bytes(), &format!("{}.html", stem()), "text/html")),
+            };
+            match result {
+                Ok(saved) => log_activity(&mut activity_log, LogLevel::Success, &format!("Exported {} to {saved}", path())),
+                Err(e) => toast_api.error(format!("Export failed: {e}"), ToastOptions::new()),
+            }
+            exporting.set(false);
+        });
+    });
+
+    rsx! {
+        DropdownMenu {
+            class: "note-export-menu",
+            DropdownMenuTrigger {
+                class: "note-export-trigger",
+                title: if exporting() { "Exporting…" } else { "Export" },
+                Icon { icon: FaFileExport, width: 14, height: 14 }
+            }
+            DropdownMenuContent {
+                DropdownMenuItem::<String> {
+                    value: "html".to_string(),
+                    index: 0usize,
+                    on_select: export,
+                    "Export as HTML"
+                }
+                DropdownMenuItem::<String> {
+                    value: "pdf".to_string(),
+                    index: 1usize,
+                    on_select: export,
+                    "Export as PDF"
+                }
+            }
+        }
+    }
+}
+
+/// A file name stem that is safe to download (and to embed in the download script).
+fn download_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    match stem.trim() {
+        "" => "note".to_string(),
+        stem => stem.to_string(),
+    }
+}
+
+/// Maximum number of commits requested for the history panel.
+const HISTORY_LIMIT: usize = 50;
+
+/// Collapsible list of the commits that changed a note (web only).
+///
+/// History is fetched from the git remote the first time the panel is opened.
+/// Each entry can expand a [`DiffView`] against the previous version, and all
+/// but deletions have a "Restore" action that rolls the note back to that
+/// version.
+#[component]
+pub fn NoteHistoryPanel(
+    /// Full note path (e.g. "folder/note.md").
+    note_path: String,
+    /// Called with the restored content after a successful restore.
+    #[props(default)]
+    on_restore: EventHandler<String>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let path = use_signal(|| note_path.clone());
+    let mut restoring = use_signal(|| Option::<String>::None);
+    // SHA of the entry whose changes are expanded
+    let mut expanded = use_signal(|| Option::<String>::None);
+    let mut activity_log = use_activity_log();
+
+    let mut history = use_resource(move || async move {
+        if !open() {
+            return None;
+        }
+        Some(api::get_note_history(path(), HISTORY_LIMIT).await)
+    });
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        div {
+            class: "note-history",
+            button {
+                class: "note-history-header",
+                onclick: move |_| open.toggle(),
+                span { "History" }
+                span { class: "note-history-chevron", if open() { "▾" } else { "▸" } }
+            }
+            if open() {
+                div {
+                    class: "note-history-list",
+                    match &*history.read() {
+                        None | Some(None) => rsx! {
+                            p { class: "view-muted px-3 py-2", "Loading history..." }
+                        },
+                        Some(Some(Err(e))) => rsx! {
+                            p { class: "view-muted px-3 py-2 text-danger", "Could not load history: {e}" }
+                        },
+                        Some(Some(Ok(commits))) if commits.is_empty() => rsx! {
+                            p { class: "view-muted px-3 py-2", "No history yet." }
+                        },
+                        Some(Some(Ok(commits))) => rsx! {
+                            for (i, commit) in commits.iter().cloned().enumerate() {
+                                div {
+                                    key: "{commit.sha}",
+                                    class: "note-history-item",
+                                    div {
+                                        class: "note-history-message",
+                                        "{commit.message}"
+                                    }
+                                    div {
+                                        class: "note-history-meta",
+                                        span { class: "note-history-sha", "{&commit.sha[..7]}" }
+                                        span { "{format_timestamp(commit.timestamp)}" }
+                                        // The oldest entry has no earlier version to diff against
+                                        if i + 1 < commits.len() {
+                                            button {
+                                                class: "note-history-action",
+                                                onclick: {
+                                                    let sha = commit.sha.clone();
+                                                    move |_| {
+                                                        if expanded().as_ref() == Some(&sha) {
+                                                            expanded.set(None);
+                                                        } else {
+                                                            expanded.set(Some(sha.clone()));
+                                                        }
+                                                    }
+                                                },
+                                                if expanded().as_ref() == Some(&commit.sha) { "Hide changes" } else { "Changes" }
+                                            }
+                                        }
+                                        // A deletion has no content to go back to
+                                        if !commit.deleted {
+                                            button {
+                                                class: "note-history-action",
+                                                disabled: restoring().is_some(),
+                                                onclick: {
+                                                    let sha = commit.sha.clone();
+                                                    move |_| {
+                                                        let sha = sha.clone();
+                                                        spawn(async move {
+                                                            restoring.set(Some(sha.clone()));
+                                                            match api::restore_note_version(path(), sha).await {
+                                                                Ok(content) => {
+                                                                    on_restore.call(content);
+                                                                    history.restart();
+                                                                }
+                                                                Err(e) => log_activity(
+                                                                    &mut activity_log,
+                                                                    LogLevel::Error,
+                                                                    &format!("Restore failed: {e}"),
+                                                                ),
+                                                            }
+                                                            restoring.set(None);
+                                                        });
+                                                    }
+                                                },
+                                                if restoring().as_deref() == Some(commit.sha.as_str()) { "Restoring..." } else { "Restore" }
+                                            }
+                                        }
+                                    }
+                                }
+                                if expanded().as_ref() == Some(&commit.sha) {
+                                    if let Some(previous) = commits.get(i + 1) {
+                                        DiffView {
+                                            key: "{previous.sha}..{commit.sha}",
+                                            commit_a: previous.sha.clone(),
+                                            commit_b: commit.sha.clone(),
+                                            path: Some(path()),
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Unix timestamp (seconds) → locale date/time string.
+#[cfg(target_arch = "wasm32")]
+pub(super) fn format_timestamp(ts: i64) -> String {
+    let date = js_sys::Date::new(&js_sys::wasm_bindgen::JsValue::from_f64(ts as f64 * 1000.0));
+    String::from(date.to_locale_string("default", &js_sys::wasm_bindgen::JsValue::UNDEFINED))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn format_timestamp(ts: i64) -> String {
+    ts.to_string()
+}
+
+/// How a note is written inside `[[...]]`: its path without the extension.
+fn wiki_link_target(note: &store::TypedNoteInfo) -> String {
+    match &note.namespace {
+        Some(ns) => format!("{ns}/{}", note.name),
+        None => note.name.clone(),
+    }
+}
+
+/// Path of the note a `[[target]]` points to. Ignores a `#heading` or
+/// `|alias` suffix, then matches the full "namespace/name" first and falls
+/// back to a bare note name (case-insensitive) anywhere in the tree.
+fn resolve_wiki_link(notes: &[store::TypedNoteInfo], target: &str) -> Option<String> {
+    let target = target.split(['#', '|']).next().unwrap_or_default().trim();
+    notes
+        .iter()
+        .find(|n| wiki_link_target(n) == target)
+        .or_else(|| notes.iter().find(|n| n.name.eq_ignore_ascii_case(target)))
+        .map(|n| n.path.clone())
+}
@@ -0,0 +1,112 @@
+use dioxus::prelude::*;
+
+use super::note_detail::format_timestamp;
+
+const VIEWS_CSS: Asset = asset!("/src/views/views.css");
+
+/// Public, read-only page for a note shared by link.
+///
+/// The HTML comes pre-rendered and sanitised from `api::get_shared_note`.
+#[component]
+pub fn SharedNoteView(
+    /// Share token from the link (`/shared/<token>`).
+    token: String,
+) -> Element {
+    let token = use_signal(|| token.clone());
+    let shared = use_resource(move || async move { api::get_shared_note(token()).await });
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        match &*shared.read() {
+            None => rsx! {
+                div { class: "view-placeholder", h2 { "Loading..." } }
+            },
+            Some(Err(e)) => rsx! {
+                div {
+                    class: "view-placeholder",
+                    h2 { "Note unavailable" }
+                    p { "{e}" }
+                }
+            },
+            Some(Ok(note)) => rsx! {
+                document::Title { "{note.title} · TypedNotes" }
+                article {
+                    class: "shared-note",
+                    h1 { class: "shared-note-title", "{note.title}" }
+                    p { class: "shared-note-meta", "Shared read-only · link expires {format_timestamp(note.expires_at)}" }
+                    div {
+                        class: "shared-note-body",
+                        dangerous_inner_html: "{note.rendered_html}",
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Share-link creator shown under a note (web only).
+#[component]
+pub fn NoteSharePanel(
+    /// Full note path (e.g. "folder/note.md").
+    note_path: String,
+) -> Element {
+    let path = use_signal(|| note_path.clone());
+    let mut hours = use_signal(|| 24u32);
+    let mut link = use_signal(|| Option::<Result<String, String>>::None);
+    let mut creating = use_signal(|| false);
+
+    let handle_share = move |_| {
+        spawn(async move {
+            creating.set(true);
+            let result = api::share_note(path(), hours()).await.map_err(|e| e.to_string());
+            link.set(Some(result));
+            creating.set(false);
+        });
+    };
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        div {
+            class: "note-share",
+            span { class: "note-share-label", "Share" }
+            select {
+                class: "note-share-select",
+                value: "{hours}",
+                onchange: move |evt: FormEvent| {
+                    if let Ok(h) = evt.value().parse() {
+                        hours.set(h);
+                    }
+                },
+                for (label, h) in SHARE_EXPIRY_CHOICES {
+                    option { value: "{h}", selected: hours() == h, "{label}" }
+                }
+            }
+            button {
+                class: "note-history-action",
+                disabled: creating(),
+                onclick: handle_share,
+                if creating() { "Creating..." } else { "Create link" }
+            }
+            match link() {
+                Some(Ok(url)) => rsx! {
+                    input {
+                        class: "note-share-link",
+                        r#type: "text",
+                        readonly: true,
+                        value: "{url}",
+                        onfocus: move |_| {
+                            _ = document::eval("document.activeElement.select()");
+                        },
+                    }
+                },
+                Some(Err(e)) => rsx! {
+                    span { class: "text-danger", "{e}" }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// Expiry options offered by [`NoteSharePanel`], in hours.
+const SHARE_EXPIRY_CHOICES: [(&str, u32); 3] = [("1 hour", 1), ("1 day", 24), ("7 days", 24 * 7)];
@@ -1,15 +1,20 @@
 use dioxus::prelude::*;
+use std::collections::HashMap;
 
-use crate::components::{Button, ButtonVariant, Input, Label, use_toast, ToastOptions};
+use crate::components::{
+    Button, ButtonVariant, Input, Label, use_toast, ToastOptions,
+    PaletteAction, use_command_palette,
+};
 use crate::{
-    ActivityLogPanel, AppSidebar, NewNoteDialog, use_auth,
-    NoteTree,
-    LogLevel, log_activity, use_activity_log,
+    ActivityLogPanel, AppSidebar, ConflictResolutionDialog, NewNoteDialog, use_auth,
+    NoteTree, SyncQueue, flush_sync_queue, load_remote_shas, pull_changes, save_remote_shas, RecentNotes, RevealNamespace, FocusMode, set_focus_mode, use_online_status,
+    LogLevel, log_activity, log_debug, log_error, use_activity_log,
     SidebarProvider, SidebarInset, SidebarTrigger,
-    SidebarCollapsible, SidebarVariant,
+    SidebarCollapsible, SidebarVariant, NamespaceStyleSheet, StylingNamespace,
 };
 use crate::components::sidebar::SidebarLayout as SidebarShell;
 use crate::{make_repo_for_user};
+use store::templates::{render_template, today};
 
 use super::ModalOverlay;
 
@@ -40,6 +45,11 @@ pub fn SidebarLayoutView(
     enable_git_pull: bool,
 ) -> Element {
     let mut tree: Signal<NoteTree> = use_context_provider(|| Signal::new(NoteTree::default()));
+    let mut sync_queue: Signal<SyncQueue> = use_context_provider(|| Signal::new(SyncQueue::default()));
+    let mut recent_notes: Signal<Vec<String>> = use_context_provider(|| Signal::new(Vec::new()));
+    let focus_mode: Signal<FocusMode> = use_context_provider(|| Signal::new(FocusMode::default()));
+    use_context_provider(|| Signal::new(RevealNamespace::default()));
+    let mut styling: Signal<StylingNamespace> = use_context_provider(|| Signal::new(StylingNamespace::default()));
 
     let mut show_new_note = use_signal(|| false);
     let mut new_note_namespace = use_signal(|| Option::<String>::None);
@@ -55,6 +65,10 @@ pub fn SidebarLayoutView(
     let mut show_detach_confirm = use_signal(|| false);
     let auth = use_auth();
     let mut activity_log = use_activity_log();
+    let mut verify_banner_dismissed = use_signal(|| false);
+    let mut verification_resent = use_signal(|| false);
+    // `remote_shas` as last saved to the local store; `None` until loaded
+    let mut saved_remote_shas = use_signal(|| Option::<HashMap<String, String>>::None);
 
     // Load notes/namespaces from store + optional background git pull
     let _loader = use_resource(move || async move {
@@ -73,42 +87,45 @@ pub fn SidebarLayoutView(
             crate::migrate_anonymous_to_user(uid).await;
         }
 
-        tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
+        // Key local objects by the hash typednotes.toml asks for
+        make_repo_for_user(user_id.as_deref()).apply_hash_algorithm().await;
+
+        // Only the top level is loaded; a namespace's children are fetched
+        // when it is first expanded
+        tree.set(NoteTree::load_root_for(user_id.as_deref()).await);
+        recent_notes.set(RecentNotes::for_user(user_id.as_deref()).get_recent().await);
+
+        // Base SHAs from before the reload, so the next push can still spot remote edits
+        let mut remote_shas = load_remote_shas(user_id.as_deref()).await;
+        remote_shas.extend(sync_queue.peek().remote_shas.clone());
+        saved_remote_shas.set(Some(remote_shas.clone()));
+        sync_queue.write().remote_shas = remote_shas;
 
         if enable_git_pull && auth().user.is_some() && auth().online {
             spawn(async move {
                 log_activity(&mut activity_log, LogLevel::Info, "Pulling from git...");
-                match api::pull_notes().await {
-                    Ok(result) => {
-                        let user_id = auth().user.as_ref().map(|u| u.id.clone());
-                        let repo = make_repo_for_user(user_id.as_deref());
-                        let count = result.files.len();
-                        for file in &result.files {
-                            let ext = file.path.rsplit('.').next().unwrap_or("md");
-                            let note_type = store::models::note_type_from_ext(ext);
-                            let stem = file.path.trim_end_matches(&format!(".{ext}"));
-                            repo.write_note(stem, &file.content, note_type).await;
-                        }
-                        for ns in &result.namespaces {
-                            repo.create_namespace(ns).await;
-                        }
-                        if !result.files.is_empty() || !result.namespaces.is_empty() {
-                            let user_id = auth().user.as_ref().map(|u| u.id.clone());
-                            tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
-                        }
-                        log_activity(&mut activity_log, LogLevel::Success, &format!("Pulled {count} notes"));
-                    }
-                    Err(e) => {
-                        log_activity(&mut activity_log, LogLevel::Warning, &format!("Git pull: {e}"));
-                        #[cfg(target_arch = "wasm32")]
-                        web_sys::console::warn_1(&format!("Git pull: {e}").into());
-                    }
-                }
+                let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                // Notes edited before the reload stay queued and win over the pull
+                pull_then_push("Load", user_id.as_deref(), tree, sync_queue, &mut activity_log).await;
             });
         }
     });
 
-    // Periodic sync timer (web only): pull remote changes at the configured interval
+    // Save `remote_shas` to the local store whenever they change
+    use_effect(move || {
+        let shas = sync_queue.read().remote_shas.clone();
+        if saved_remote_shas.peek().as_ref().is_none_or(|saved| *saved == shas) {
+            return;
+        }
+        saved_remote_shas.set(Some(shas.clone()));
+        let user_id = auth.peek().user.as_ref().map(|u| u.id.clone());
+        spawn(async move {
+            save_remote_shas(user_id.as_deref(), &shas).await;
+        });
+    });
+
+    // Periodic sync timer (web only): pull remote changes, then push queued local
+    // saves, at the configured interval
     #[cfg(target_arch = "wasm32")]
     {
         use_effect(move || {
@@ -116,38 +133,29 @@ pub fn SidebarLayoutView(
                 return;
             }
             spawn(async move {
-                // Read sync interval from config
+                // Pulls cover every namespace, so tick at the most eager
+                // namespace's effective interval
                 let user_id = auth().user.as_ref().map(|u| u.id.clone());
                 let repo = make_repo_for_user(user_id.as_deref());
                 let config = repo.get_config().await;
-                let interval_secs = config.sync.auto_sync_interval_secs;
-                if interval_secs == 0 {
+                let Some(interval_secs) = config.shortest_sync_interval() else {
                     return;
-                }
+                };
                 loop {
                     gloo_timers::future::sleep(std::time::Duration::from_secs(interval_secs as u64)).await;
-                    log_activity(&mut activity_log, LogLevel::Info, "Periodic pull...");
-                    match api::pull_notes().await {
-                        Ok(result) => {
-                            let user_id = auth().user.as_ref().map(|u| u.id.clone());
-                            let repo = make_repo_for_user(user_id.as_deref());
-                            for file in &result.files {
-                                let ext = file.path.rsplit('.').next().unwrap_or("md");
-                                let note_type = store::models::note_type_from_ext(ext);
-                                let stem = file.path.trim_end_matches(&format!(".{ext}"));
-                                repo.write_note(stem, &file.content, note_type).await;
-                            }
-                            for ns in &result.namespaces {
-                                repo.create_namespace(ns).await;
-                            }
-                            if !result.files.is_empty() || !result.namespaces.is_empty() {
-                                let user_id = auth().user.as_ref().map(|u| u.id.clone());
-                                tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
-                            }
-                            log_activity(&mut activity_log, LogLevel::Success, &format!("Periodic pull: {} notes", result.files.len()));
-                        }
-                        Err(e) => {
-                            log_activity(&mut activity_log, LogLevel::Warning, &format!("Periodic pull: {e}"));
+                    log_debug!(&mut activity_log, "Periodic pull...");
+                    let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                    let Some((pushed, failed)) =
+                        pull_then_push("Periodic", user_id.as_deref(), tree, sync_queue, &mut activity_log).await
+                    else {
+                        continue;
+                    };
+                    // Everything is on the remote now; drop superseded local objects
+                    if pushed > 0 && failed == 0 && config.sync.gc_object_threshold > 0 {
+                        let repo = make_repo_for_user(user_id.as_deref());
+                        if repo.object_count().await.is_some_and(|n| n > config.sync.gc_object_threshold) {
+                            let freed = repo.gc().await;
+                            log_activity(&mut activity_log, LogLevel::Info, &format!("Garbage collected {freed} objects"));
                         }
                     }
                 }
@@ -155,10 +163,43 @@ pub fn SidebarLayoutView(
         });
     }
 
+    // Reconnect sync: coming back online runs one pull + push right away
+    // rather than waiting for the next periodic tick
+    let online = use_online_status();
+    let mut was_online = use_signal(|| true);
+    let reconnect_toast = use_toast();
+    use_effect(move || {
+        let now = online();
+        let before = *was_online.peek();
+        if now == before {
+            return;
+        }
+        was_online.set(now);
+        if !now {
+            log_activity(&mut activity_log, LogLevel::Warning, "Offline — changes will sync when reconnected");
+            return;
+        }
+        if !enable_git_pull || auth.peek().user.is_none() {
+            return;
+        }
+        reconnect_toast.info("Back online — syncing...".to_string(), ToastOptions::new());
+        spawn(async move {
+            let user_id = auth().user.as_ref().map(|u| u.id.clone());
+            pull_then_push("Reconnect", user_id.as_deref(), tree, sync_queue, &mut activity_log).await;
+        });
+    });
+
     // Sidebar callbacks
     let on_select_note = move |path: String| {
         show_new_note.set(false);
         show_new_namespace.set(false);
+        spawn({
+            let path = path.clone();
+            async move {
+                let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                recent_notes.set(RecentNotes::for_user(user_id.as_deref()).push_recent(&path).await);
+            }
+        });
         on_navigate_note.call(path);
     };
 
@@ -186,29 +227,191 @@ pub fn SidebarLayoutView(
         on_navigate_settings.call(());
     };
 
+    // Command palette entries: fixed commands once, plus one per note whenever
+    // the tree changes
+    let mut palette = use_command_palette();
+    let theme = try_use_context::<crate::ThemeSignal>();
+    use_hook(move || {
+        let (mut new_note, mut new_namespace, mut settings) = (on_create_note, on_create_namespace, on_settings);
+        palette.register(vec![
+            PaletteAction::new("cmd:new-note", "New note", move |_| new_note(None)),
+            PaletteAction::new("cmd:new-namespace", "New namespace", move |_| new_namespace(None)),
+            PaletteAction::new("cmd:settings", "Go to settings", move |_| settings(())),
+            PaletteAction::new("cmd:toggle-theme", "Toggle theme", move |_| {
+                if let Some(mut theme) = theme {
+                    crate::sidebar::cycle_theme(&mut theme);
+                }
+            }),
+            PaletteAction::new("cmd:focus-mode", "Toggle focus mode", move |_| {
+                set_focus_mode(focus_mode, !focus_mode().0);
+            }),
+        ]);
+    });
+
+    // Focus mode: Ctrl+Shift+F toggles, Escape leaves (as does leaving
+    // browser fullscreen, which swallows the Escape key itself)
+    use_effect(move || {
+        spawn(async move {
+            let mut eval = document::eval(
+                r#"window.__focusModeKeyHandler = function(e) {
+                    if ((e.ctrlKey || e.metaKey) && e.shiftKey && !e.altKey && e.key.toLowerCase() === 'f') {
+                        e.preventDefault();
+                        dioxus.send('toggle');
+                    } else if (e.key === 'Escape' && !e.defaultPrevented) {
+                        dioxus.send('exit');
+                    }
+                };
+                window.__focusModeFullscreenHandler = function() {
+                    if (!document.fullscreenElement) dioxus.send('exit');
+                };
+                document.addEventListener('keydown', window.__focusModeKeyHandler);
+                document.addEventListener('fullscreenchange', window.__focusModeFullscreenHandler);"#,
+            );
+            while let Ok(command) = eval.recv::<String>().await {
+                let on = command == "toggle" && !focus_mode().0;
+                set_focus_mode(focus_mode, on);
+            }
+        });
+    });
+    use_drop(|| {
+        _ = document::eval(
+            r#"
+            document.removeEventListener('keydown', window.__focusModeKeyHandler);
+            document.removeEventListener('fullscreenchange', window.__focusModeFullscreenHandler);
+            delete window.__focusModeKeyHandler;
+            delete window.__focusModeFullscreenHandler;
+            "#,
+        );
+    });
+    use_effect(move || {
+        let notes = tree().notes;
+        palette.unregister_prefix("note:");
+        palette.register(
+            notes
+                .into_iter()
+                .map(|note| {
+                    let path = note.path.clone();
+                    let mut select = on_select_note;
+                    PaletteAction::new(format!("note:{}", note.path), format!("Open {}", note.path), move |_| {
+                        select(path.clone())
+                    })
+                })
+                .collect(),
+        );
+    });
+    use_drop(move || {
+        palette.unregister_prefix("cmd:");
+        palette.unregister_prefix("note:");
+    });
+
+    // Templates for the new-note dialog, reloaded each time it opens
+    let templates = use_resource(move || async move {
+        if !show_new_note() {
+            return Vec::new();
+        }
+        let user_id = auth().user.as_ref().map(|u| u.id.clone());
+        make_repo_for_user(user_id.as_deref()).list_templates().await
+    });
+
     // Handle creating a note from the dialog
     let toast = use_toast();
     let handle_create_note =
-        move |(name, ns, note_type): (String, Option<String>, String)| {
+        move |(name, ns, note_type, template): (String, Option<String>, String, Option<String>)| {
             spawn(async move {
                 let path = if let Some(ref ns) = ns {
                     format!("{ns}/{name}")
                 } else {
-                    name
+                    name.clone()
                 };
-                let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                let user = auth().user;
+                let user_id = user.as_ref().map(|u| u.id.clone());
                 let repo = make_repo_for_user(user_id.as_deref());
-                repo.write_note(&path, "", &note_type).await;
+                let full_path = match template {
+                    None => {
+                        repo.write_note(&path, "", &note_type).await;
+                        format!("{path}.{}", store::models::ext_from_note_type(&note_type))
+                    }
+                    // Signed in: the server renders and pushes it
+                    Some(template) if user.is_some() => {
+                        match api::create_note_from_template(template, name, ns).await {
+                            Ok(note) => {
+                                repo.write_note(&path, &note.note, &note.r#type).await;
+                                sync_queue.write().record_remote(&note.path, &note.note);
+                                note.path
+                            }
+                            Err(e) => {
+                                toast.error(format!("Could not create {path}: {e}"), ToastOptions::new());
+                                return;
+                            }
+                        }
+                    }
+                    Some(template) => {
+                        let Some(template) = repo.get_note(&template).await else {
+                            toast.error(format!("Template {template} not found"), ToastOptions::new());
+                            return;
+                        };
+                        let content = render_template(&template.note, &name, "", &today());
+                        repo.write_note(&path, &content, &template.r#type).await;
+                        format!("{path}.{}", store::models::ext_from_note_type(&template.r#type))
+                    }
+                };
                 tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
                 show_new_note.set(false);
-                let ext = store::models::ext_from_note_type(&note_type);
-                let full_path = format!("{path}.{ext}");
                 log_activity(&mut activity_log, LogLevel::Info, &format!("Created note {full_path}"));
                 toast.success("Note created".to_string(), ToastOptions::new());
                 on_navigate_note.call(full_path);
             });
         };
 
+    // Only re-render for the conflict dialog, not every queue change
+    let open_conflict = use_memo(move || sync_queue().conflicts.first().cloned());
+
+    // A sync conflict was resolved: store the chosen content locally and push it
+    // on top of the remote version the user was shown
+    let handle_resolve_conflict = move |content: String| {
+        let Some(conflict) = sync_queue.peek().conflicts.first().cloned() else {
+            return;
+        };
+        let path = conflict.path.clone();
+        spawn(async move {
+            {
+                let mut queue = sync_queue.write();
+                queue.conflicts.retain(|c| c.path != path);
+                queue.remote_shas.insert(path.clone(), conflict.remote_sha.clone());
+            }
+            let user_id = auth().user.as_ref().map(|u| u.id.clone());
+            let repo = make_repo_for_user(user_id.as_deref());
+            let note_type = store::models::note_type_from_path(&path);
+            repo.write_note(&path, &content, note_type).await;
+            sync_queue.write().resolved += 1;
+            tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
+
+            match api::sync_note(path.clone(), content.clone(), note_type.to_string(), Some(conflict.remote_sha)).await {
+                Ok(None) => {
+                    sync_queue.write().record_remote(&path, &content);
+                    log_activity(&mut activity_log, LogLevel::Success, &format!("Resolved conflict in {path}"));
+                    toast.success("Conflict resolved".to_string(), ToastOptions::new());
+                }
+                // Changed on the remote yet again
+                Ok(Some(conflict)) => sync_queue.write().add_conflict(conflict),
+                Err(e) => {
+                    sync_queue.write().mark_dirty(&path);
+                    log_error!(&mut activity_log, "Sync error for {path}: {e}");
+                }
+            }
+        });
+    };
+
+    // Leave the conflict for later; the note is pushed (and conflicts) again on
+    // the next sync
+    let handle_defer_conflict = move |_| {
+        let mut queue = sync_queue.write();
+        if !queue.conflicts.is_empty() {
+            let conflict = queue.conflicts.remove(0);
+            queue.mark_dirty(&conflict.path);
+        }
+    };
+
     // Handle creating a namespace
     let handle_create_namespace = move |_| {
         let name = new_ns_name().trim().to_string();
@@ -231,7 +434,7 @@ pub fn SidebarLayoutView(
                         log_activity(&mut activity_log, LogLevel::Success, &format!("Synced namespace {name}"));
                     }
                     Err(e) => {
-                        log_activity(&mut activity_log, LogLevel::Error, &format!("Namespace sync error: {e}"));
+                        log_error!(&mut activity_log, "Namespace sync error: {e}");
                     }
                 }
             }
@@ -252,12 +455,13 @@ pub fn SidebarLayoutView(
             // Sync deletion to remote
             if enable_git_pull {
                 log_activity(&mut activity_log, LogLevel::Info, &format!("Syncing deletion of {path}..."));
-                match api::delete_namespace_remote(path.clone()).await {
+                // The dialog already warned that the notes inside go too
+                match api::delete_namespace_remote(path.clone(), true).await {
                     Ok(()) => {
                         log_activity(&mut activity_log, LogLevel::Success, &format!("Deleted remote namespace {path}"));
                     }
                     Err(e) => {
-                        log_activity(&mut activity_log, LogLevel::Error, &format!("Delete namespace sync error: {e}"));
+                        log_error!(&mut activity_log, "Delete namespace sync error: {e}");
                     }
                 }
             }
@@ -284,6 +488,36 @@ pub fn SidebarLayoutView(
         });
     };
 
+    // Duplicate a note next to the original as `<stem>-copy.<ext>` (or
+    // `-copy-2`, `-copy-3`, ... if taken) and open the copy
+    let on_copy_note = move |src: String| {
+        let mut select = on_select_note;
+        spawn(async move {
+            let user_id = auth().user.as_ref().map(|u| u.id.clone());
+            let repo = make_repo_for_user(user_id.as_deref());
+            let Some((stem, ext)) = src.rsplit_once('.') else {
+                return;
+            };
+            let mut dst = format!("{stem}-copy.{ext}");
+            let mut n = 1;
+            while repo.get_note(&dst).await.is_some() {
+                n += 1;
+                dst = format!("{stem}-copy-{n}.{ext}");
+            }
+            if repo.copy_note(&src, &dst).await.is_none() {
+                toast.error(format!("Could not duplicate {src}"), ToastOptions::new());
+                return;
+            }
+            if auth().user.is_some() {
+                sync_queue.write().mark_dirty(&dst);
+            }
+            tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
+            log_activity(&mut activity_log, LogLevel::Info, &format!("Duplicated {src} -> {dst}"));
+            toast.success("Note duplicated".to_string(), ToastOptions::new());
+            select(dst);
+        });
+    };
+
     // Handle moving a namespace via drag-and-drop (shows confirmation)
     let on_move_namespace = move |(ns_path, target_ns): (String, Option<String>)| {
         let ns_name = ns_path.rsplit('/').next().unwrap_or(&ns_path).to_string();
@@ -325,7 +559,10 @@ pub fn SidebarLayoutView(
             } else {
                 new_name
             };
-            repo.move_namespace(&old_path, &new_path).await;
+            if repo.rename_namespace(&old_path, &new_path).await.is_none() {
+                toast.error(format!("Cannot rename to {new_path}"), ToastOptions::new());
+                return;
+            }
             tree.set(NoteTree::refresh_for(user_id.as_deref()).await);
             log_activity(&mut activity_log, LogLevel::Info, &format!("Renamed namespace {old_path} -> {new_path}"));
             toast.success("Namespace renamed".to_string(), ToastOptions::new());
@@ -349,54 +586,150 @@ pub fn SidebarLayoutView(
     };
 
     rsx! {
-        SidebarProvider {
-            SidebarShell {
-                variant: SidebarVariant::Inset,
-                collapsible: SidebarCollapsible::Offcanvas,
-                AppSidebar {
-                    namespaces: tree().namespaces,
-                    notes: tree().notes,
-                    active_path: active_path,
-                    user: auth().user,
-                    on_select_note: on_select_note,
-                    on_create_note: on_create_note,
-                    on_create_namespace: on_create_namespace,
-                    on_delete_namespace: on_delete_namespace,
-                    on_navigate_settings: on_settings,
-                    on_navigate_login: on_navigate_login,
-                    on_detach: move |_| show_detach_confirm.set(true),
-                    on_move_note: on_move_note,
-                    on_move_namespace: on_move_namespace,
-                    on_rename_namespace: on_rename_namespace,
+        div {
+            class: "focus-mode-root",
+            "data-focus-mode": "{focus_mode().0}",
+            SidebarProvider {
+                SidebarShell {
+                    variant: SidebarVariant::Inset,
+                    collapsible: SidebarCollapsible::Offcanvas,
+                    AppSidebar {
+                        namespaces: tree().namespaces,
+                        notes: tree().notes,
+                        active_path: active_path,
+                        user: auth().user,
+                        on_select_note: on_select_note,
+                        on_create_note: on_create_note,
+                        on_create_namespace: on_create_namespace,
+                        on_delete_namespace: on_delete_namespace,
+                        on_navigate_settings: on_settings,
+                        on_navigate_login: on_navigate_login,
+                        on_detach: move |_| show_detach_confirm.set(true),
+                        on_move_note: on_move_note,
+                        on_move_namespace: on_move_namespace,
+                        on_rename_namespace: on_rename_namespace,
+                        loaded_namespaces: tree().loaded,
+                        expand_namespace: move |path: String| {
+                            spawn(async move {
+                                let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                                let (namespaces, notes) = NoteTree::children_for(user_id.as_deref(), &path).await;
+                                // A full refresh may have landed meanwhile
+                                if !tree.peek().is_loaded(&path) {
+                                    tree.write().merge_children(&path, namespaces, notes);
+                                }
+                            });
+                        },
+                        pinned_notes: tree().pinned,
+                        recent_notes: recent_notes(),
+                        on_copy_note: on_copy_note,
+                        toggle_note_pin: move |path: String| {
+                            spawn(async move {
+                                let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                                let repo = make_repo_for_user(user_id.as_deref());
+                                if tree.peek().pinned.contains(&path) {
+                                    repo.unpin_note(&path).await;
+                                } else {
+                                    repo.pin_note(&path).await;
+                                }
+                                tree.write().pinned = repo.get_config().await.pinned_notes;
+                            });
+                        },
+                    }
                 }
-            }
 
-            SidebarInset {
-                document::Link { rel: "stylesheet", href: VIEWS_CSS }
-                // Top header with sidebar trigger
-                header {
-                    class: "view-header-bar",
-                    SidebarTrigger {}
-                    span { class: "text-sm font-semibold", "TypedNotes" }
-                }
+                SidebarInset {
+                    document::Link { rel: "stylesheet", href: VIEWS_CSS }
+                    // Top header with sidebar trigger
+                    header {
+                        class: "view-header-bar",
+                        SidebarTrigger {}
+                        span { class: "text-sm font-semibold", "TypedNotes" }
+                    }
 
-                // Main content
-                div {
-                    class: "flex-1 overflow-y-auto",
-                    {children}
-                }
+                    if auth().user.as_ref().is_some_and(|u| !u.email_verified) && !verify_banner_dismissed() {
+                        div {
+                            class: "verify-email-banner",
+                            role: "status",
+                            span {
+                                "Please verify your email address — we sent you a link. Git sync is off until then."
+                            }
+                            if verification_resent() {
+                                span { class: "verify-email-banner-note", "Link sent" }
+                            } else {
+                                Button {
+                                    variant: ButtonVariant::Ghost,
+                                    onclick: move |_| {
+                                        spawn(async move {
+                                            match api::resend_verification_email().await {
+                                                Ok(()) => verification_resent.set(true),
+                                                Err(e) => log_activity(&mut activity_log, LogLevel::Error, &format!("Resend verification: {e}")),
+                                            }
+                                        });
+                                    },
+                                    "Resend"
+                                }
+                            }
+                            button {
+                                class: "verify-email-banner-dismiss",
+                                "aria-label": "Dismiss",
+                                onclick: move |_| verify_banner_dismissed.set(true),
+                                "×"
+                            }
+                        }
+                    }
+
+                    // Main content
+                    div {
+                        class: "flex-1 overflow-y-auto",
+                        {children}
+                    }
 
-                ActivityLogPanel {}
+                    ActivityLogPanel {}
+                }
             }
         }
 
         // Modal overlays (always float on top)
+        if let Some(conflict) = open_conflict() {
+            ModalOverlay {
+                on_close: handle_defer_conflict,
+                ConflictResolutionDialog {
+                    key: "{conflict.path}",
+                    conflict,
+                    on_resolve: handle_resolve_conflict,
+                    on_cancel: handle_defer_conflict,
+                }
+            }
+        }
+        if let Some(path) = styling().0 {
+            ModalOverlay {
+                on_close: move |_| styling.set(StylingNamespace(None)),
+                NamespaceStyleSheet {
+                    key: "{path}",
+                    style: tree().config.get_namespace_style(&path),
+                    path: path.clone(),
+                    on_save: move |style: store::NamespaceStyle| {
+                        let path = path.clone();
+                        styling.set(StylingNamespace(None));
+                        spawn(async move {
+                            let user_id = auth().user.as_ref().map(|u| u.id.clone());
+                            let repo = make_repo_for_user(user_id.as_deref());
+                            if repo.set_namespace_style(&path, style).await.is_some() {
+                                tree.write().config = repo.get_config().await;
+                            }
+                        });
+                    },
+                    on_cancel: move |_| styling.set(StylingNamespace(None)),
+                }
+            }
+        }
         if show_new_note() {
             ModalOverlay {
                 on_close: move |_| show_new_note.set(false),
                 NewNoteDialog {
                     namespaces: tree().namespaces,
                     default_namespace: new_note_namespace(),
+                    templates: templates().unwrap_or_default(),
                     on_create: handle_create_note,
                     on_cancel: move |_| show_new_note.set(false),
                 }
@@ -538,6 +871,7 @@ pub fn SidebarLayoutView(
                                         user: None,
                                         loading: false,
                                         online,
+                                        extended: None,
                                     });
                                     // Refresh tree (now empty anonymous store)
                                     tree.set(NoteTree::refresh_for(None).await);
@@ -558,3 +892,57 @@ pub fn SidebarLayoutView(
         }
     }
 }
+
+/// Pull remote changes into the local store, then push the queued local
+/// saves. Unpushed local edits win over pulled content. `label` prefixes the
+/// activity log lines. Returns `(pushed, failed)`, or `None` if the pull
+/// failed and nothing was pushed.
+async fn pull_then_push(
+    label: &str,
+    user_id: Option<&str>,
+    mut tree: Signal<NoteTree>,
+    mut sync_queue: Signal<SyncQueue>,
+    activity_log: &mut Signal<crate::ActivityLog>,
+) -> Option<(usize, usize)> {
+    match pull_changes(Some(sync_queue), user_id).await {
+        Ok(result) => {
+            let repo = make_repo_for_user(user_id);
+            for file in &result.files {
+                if sync_queue.peek().is_dirty(&file.path) {
+                    continue;
+                }
+                let note_type = store::models::note_type_from_path(&file.path);
+                repo.write_note(&file.path, &file.content, note_type).await;
+                sync_queue.write().record_remote(&file.path, &file.content);
+            }
+            for ns in &result.namespaces {
+                repo.create_namespace(ns).await;
+            }
+            // The pull may have changed typednotes.toml
+            repo.apply_hash_algorithm().await;
+            if !result.files.is_empty() || !result.namespaces.is_empty() || result.head.is_some() {
+                tree.set(NoteTree::refresh_for(user_id).await);
+            }
+            log_activity(activity_log, LogLevel::Success, &format!("{label} pull: {} notes", result.files.len()));
+            if !result.conflicts.is_empty() {
+                log_activity(
+                    activity_log,
+                    LogLevel::Warning,
+                    &format!("{label} pull: {} notes changed on both sides", result.conflicts.len()),
+                );
+            }
+        }
+        Err(e) => {
+            log_activity(activity_log, LogLevel::Warning, &format!("{label} pull: {e}"));
+            return None;
+        }
+    }
+    let (pushed, failed) = flush_sync_queue(sync_queue, user_id, activity_log).await;
+    if pushed > 0 || failed > 0 {
+        log_activity(activity_log, LogLevel::Info, &format!("{label} push: {pushed} pushed, {failed} failed"));
+    }
+    if failed == 0 {
+        sync_queue.write().mark_synced();
+    }
+    Some((pushed, failed))
+}
@@ -0,0 +1,52 @@
+use dioxus::prelude::*;
+
+use crate::use_auth;
+
+const VIEWS_CSS: Asset = asset!("/src/views/views.css");
+
+/// Page behind the link mailed on registration; confirms the address with
+/// `api::verify_email`. Works signed out, e.g. opened in another browser.
+#[component]
+pub fn VerifyEmailView(
+    /// Verification token from the link (`/verify-email/<token>`).
+    token: String,
+) -> Element {
+    let mut auth = use_auth();
+    let token = use_signal(|| token.clone());
+    let verified = use_resource(move || async move { api::verify_email(token()).await });
+
+    // Lift the banner right away if the link was opened signed in as that user
+    use_effect(move || {
+        if let Some(Ok(user)) = &*verified.read() {
+            let mut state = auth.peek().clone();
+            if state.user.as_ref().is_some_and(|current| current.id == user.id) {
+                state.user = Some(user.clone());
+                auth.set(state);
+            }
+        }
+    });
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: VIEWS_CSS }
+        document::Title { "Verify email · TypedNotes" }
+        match &*verified.read() {
+            None => rsx! {
+                div { class: "view-placeholder", h2 { "Verifying..." } }
+            },
+            Some(Err(e)) => rsx! {
+                div {
+                    class: "view-placeholder",
+                    h2 { "Could not verify your email" }
+                    p { "{e}" }
+                }
+            },
+            Some(Ok(user)) => rsx! {
+                div {
+                    class: "view-placeholder",
+                    h2 { "Email verified" }
+                    p { "{user.email} is confirmed and git sync is on. " a { href: "/notes", "Open your notes" } }
+                }
+            },
+        }
+    }
+}
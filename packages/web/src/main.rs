@@ -1,7 +1,7 @@
 use dioxus::prelude::*;
 
 use ui::AuthProvider;
-use views::{Login, NoteDetail, Notes, Register, Settings, SidebarLayout};
+use views::{Login, NoteDetail, Notes, Register, Settings, SharedNote, SidebarLayout, VerifyEmail};
 
 mod views;
 
@@ -14,6 +14,10 @@ enum Route {
     Login {},
     #[route("/register")]
     Register {},
+    #[route("/shared/:token")]
+    SharedNote { token: String },
+    #[route("/verify-email/:token")]
+    VerifyEmail { token: String },
     #[layout(SidebarLayout)]
         #[route("/notes")]
         Notes {},
@@ -50,6 +54,8 @@ async fn launch_server() {
 
     dotenvy::dotenv().ok();
 
+    init_tracing();
+
     // Initialize database pool
     let pool = api::db::get_pool()
         .await
@@ -64,6 +70,9 @@ async fn launch_server() {
         .await
         .expect("Failed to run migrations");
 
+    check_store_backend().await;
+    api::email::check_config().unwrap_or_else(|e| panic!("{e}"));
+
     // Create session store
     let session_store = PostgresStore::new(pool.clone());
 
@@ -77,20 +86,30 @@ async fn launch_server() {
         .with_secure(is_production)
         .with_same_site(SameSite::Lax)
         .with_expiry(Expiry::OnInactivity(
-            Duration::from_secs(60 * 60 * 24 * 7).try_into().unwrap(),
+            Duration::from_secs(api::auth::SESSION_INACTIVITY_SECS as u64)
+                .try_into()
+                .unwrap(),
         )); // 7 days
 
     // Build the Dioxus app with custom routes
     let router = axum::Router::new()
-        // Health check endpoint
-        .route("/healthz", get(|| async { "ok" }))
+        // Liveness and readiness probes (see api::health)
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        // Prometheus counters, behind METRICS_TOKEN (see api::metrics)
+        .route("/metrics", get(metrics))
         // Add custom OAuth callback routes first
         .route("/auth/github/callback", get(github_callback))
         .route("/auth/google/callback", get(google_callback))
+        .route("/auth/gitlab/callback", get(gitlab_callback))
+        // Atom feed of note changes (see api::feed)
+        .route("/feeds/{token}", get(user_feed))
         // Then serve the Dioxus application
         .serve_dioxus_application(ServeConfig::new(), App)
         // Add session layer to all routes
-        .layer(session_layer);
+        .layer(session_layer)
+        // Refuse larger bodies before they are read (see api::attachments)
+        .layer(axum::extract::DefaultBodyLimit::max(api::attachments::MAX_REQUEST_BODY_BYTES));
 
     // Use IP/PORT env vars (set in production), falling back to dioxus default for local dev
     let addr = {
@@ -113,10 +132,78 @@ async fn launch_server() {
         .unwrap();
 }
 
+/// Read `store_backend` from the server's `typednotes.toml` (path in
+/// `TYPEDNOTES_CONFIG`, default `./typednotes.toml`) and make sure the chosen
+/// backend is usable before accepting requests.
+#[cfg(feature = "server")]
+async fn check_store_backend() {
+    use store::{StoreBackend, TypedNotesConfig};
+
+    let path = std::env::var("TYPEDNOTES_CONFIG").unwrap_or_else(|_| TypedNotesConfig::filename().to_string());
+    let config = match std::fs::read_to_string(&path) {
+        Ok(text) => TypedNotesConfig::from_toml(&text).unwrap_or_else(|e| panic!("{path}: {e}")),
+        Err(_) => TypedNotesConfig::default(),
+    };
+
+    match config.store_backend {
+        StoreBackend::Memory => {}
+        StoreBackend::Sqlite => panic!("{path}: the sqlite store backend is only available in the desktop app"),
+        #[cfg(feature = "s3")]
+        StoreBackend::S3 => {
+            let bucket = api::fetch_cache::init()
+                .await
+                .unwrap_or_else(|e| panic!("S3 store backend: {e}"));
+            tracing::info!("Caching fetched packs in S3 bucket {bucket}");
+        }
+        #[cfg(not(feature = "s3"))]
+        StoreBackend::S3 => panic!("{path}: the s3 store backend needs the server built with the `s3` feature"),
+    }
+}
+
+/// Install the tracing subscriber: log lines on stdout and, with the `otel`
+/// feature and `OTEL_EXPORTER_OTLP_ENDPOINT` set, spans exported over OTLP/gRPC.
+#[cfg(feature = "server")]
+fn init_tracing() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let registry = tracing_subscriber::registry().with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "otel")]
+    if let Ok(endpoint) = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        use opentelemetry::trace::TracerProvider as _;
+        use opentelemetry_otlp::WithExportConfig;
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .expect("Failed to build OTLP exporter");
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+            .with_resource(opentelemetry_sdk::Resource::new([opentelemetry::KeyValue::new(
+                "service.name",
+                "typednotes",
+            )]))
+            .build();
+        let tracer = provider.tracer("typednotes");
+        opentelemetry::global::set_tracer_provider(provider);
+
+        // Another subscriber may already be installed (e.g. by `dx serve`)
+        let _ = registry
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
+            .try_init();
+        return;
+    }
+
+    let _ = registry.try_init();
+}
+
 #[cfg(feature = "server")]
 async fn github_callback(
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
     session: tower_sessions::Session,
+    headers: axum::http::HeaderMap,
 ) -> axum::response::Redirect {
     use axum::response::Redirect;
 
@@ -129,12 +216,19 @@ async fn github_callback(
         return Redirect::to("/login?error=missing_state");
     };
 
+    // Single use: a replayed callback finds no verifier in the session
+    let code_verifier = session
+        .remove::<String>(api::auth::SESSION_OAUTH_VERIFIER_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
     match api::auth::GitHubOAuth::new() {
-        Ok(oauth) => match oauth.exchange_code(code, state).await {
+        Ok(oauth) => match oauth.exchange_code(code, state, &code_verifier).await {
             Ok(user) => {
-                if let Err(e) = session
-                    .insert(api::auth::SESSION_USER_ID_KEY, user.id.to_string())
-                    .await
+                if let Err(e) =
+                    api::auth::start_user_session(&session, &user.id.to_string(), &headers).await
                 {
                     tracing::error!("Failed to set session: {}", e);
                     return Redirect::to("/login?error=session_error");
@@ -157,6 +251,7 @@ async fn github_callback(
 async fn google_callback(
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
     session: tower_sessions::Session,
+    headers: axum::http::HeaderMap,
 ) -> axum::response::Redirect {
     use axum::response::Redirect;
 
@@ -169,12 +264,19 @@ async fn google_callback(
         return Redirect::to("/login?error=missing_state");
     };
 
+    // Single use: a replayed callback finds no verifier in the session
+    let code_verifier = session
+        .remove::<String>(api::auth::SESSION_OAUTH_VERIFIER_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
     match api::auth::GoogleOAuth::new() {
-        Ok(oauth) => match oauth.exchange_code(code, state).await {
+        Ok(oauth) => match oauth.exchange_code(code, state, &code_verifier).await {
             Ok(user) => {
-                if let Err(e) = session
-                    .insert(api::auth::SESSION_USER_ID_KEY, user.id.to_string())
-                    .await
+                if let Err(e) =
+                    api::auth::start_user_session(&session, &user.id.to_string(), &headers).await
                 {
                     tracing::error!("Failed to set session: {}", e);
                     return Redirect::to("/login?error=session_error");
@@ -197,15 +299,131 @@ async fn google_callback(
     }
 }
 
+#[cfg(feature = "server")]
+async fn gitlab_callback(
+    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+    session: tower_sessions::Session,
+    headers: axum::http::HeaderMap,
+) -> axum::response::Redirect {
+    use axum::response::Redirect;
+
+    let Some(code) = params.get("code") else {
+        tracing::error!("GitLab callback missing code");
+        return Redirect::to("/login?error=missing_code");
+    };
+    let Some(state) = params.get("state") else {
+        tracing::error!("GitLab callback missing state");
+        return Redirect::to("/login?error=missing_state");
+    };
+
+    // Single use: a replayed callback finds no verifier in the session
+    let code_verifier = session
+        .remove::<String>(api::auth::SESSION_OAUTH_VERIFIER_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+    match api::auth::GitLabOAuth::new() {
+        Ok(oauth) => match oauth.exchange_code(code, state, &code_verifier).await {
+            Ok(user) => {
+                if let Err(e) =
+                    api::auth::start_user_session(&session, &user.id.to_string(), &headers).await
+                {
+                    tracing::error!("Failed to set session: {}", e);
+                    return Redirect::to("/login?error=session_error");
+                }
+                if let Err(e) = session.save().await {
+                    tracing::error!("Failed to save session: {}", e);
+                    return Redirect::to("/login?error=session_save_error");
+                }
+                Redirect::to("/notes")
+            }
+            Err(e) => {
+                tracing::error!("GitLab OAuth exchange error: {}", e);
+                Redirect::to("/login?error=oauth_error")
+            }
+        },
+        Err(e) => {
+            tracing::error!("Failed to create GitLab OAuth: {}", e);
+            Redirect::to("/login?error=config_error")
+        }
+    }
+}
+
+/// `GET /healthz`: every check in [`api::health::health`]; 503 when the
+/// database is down.
+#[cfg(feature = "server")]
+async fn healthz() -> axum::response::Response {
+    use api::health::Status;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    let report = api::health::health().await;
+    let code = match report.status {
+        Status::Down => StatusCode::SERVICE_UNAVAILABLE,
+        Status::Ok | Status::Degraded => StatusCode::OK,
+    };
+    (code, axum::Json(report)).into_response()
+}
+
+/// `GET /readyz`: the database only. The server starts listening after
+/// migrations have run, so a successful answer also means they are applied.
+#[cfg(feature = "server")]
+async fn readyz() -> axum::response::Response {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    let check = api::health::readiness().await;
+    let code = if check.is_ok() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (code, axum::Json(check)).into_response()
+}
+
+/// `GET /metrics`: the Prometheus counters, for requests bearing
+/// `METRICS_TOKEN`; 404 while it is unset.
+#[cfg(feature = "server")]
+async fn metrics(headers: axum::http::HeaderMap) -> axum::response::Response {
+    use axum::http::{header, StatusCode};
+    use axum::response::IntoResponse;
+
+    let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    match api::metrics::authorize(authorization) {
+        None => StatusCode::NOT_FOUND.into_response(),
+        Some(false) => StatusCode::UNAUTHORIZED.into_response(),
+        Some(true) => (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            api::metrics::render_prometheus(),
+        )
+            .into_response(),
+    }
+}
+
+/// `GET /feeds/{token}`: the user's recent commits as Atom XML.
+#[cfg(feature = "server")]
+async fn user_feed(axum::extract::Path(token): axum::extract::Path<String>) -> axum::response::Response {
+    use axum::http::{header, StatusCode};
+    use axum::response::IntoResponse;
+
+    match api::feed::user_feed(&token).await {
+        Ok(Some(xml)) => ([(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")], xml).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!("Failed to build feed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 #[component]
 fn App() -> Element {
-    use_context_provider(|| Signal::new(ui::ActivityLog::default()));
+    let mut activity_log = use_context_provider(|| Signal::new(ui::ActivityLog::default()));
 
     // Theme context: None = system, Some("dark"), Some("light")
     let mut theme: ui::ThemeSignal = use_context_provider(|| Signal::new(Option::<String>::None));
     // Load persisted theme from localStorage on startup
     use_effect(move || {
         ui::load_theme_from_storage(&mut theme);
+        ui::load_activity_log_from_storage(&mut activity_log);
     });
 
     rsx! {
@@ -217,7 +435,9 @@ fn App() -> Element {
 
         AuthProvider {
             ui::components::ToastProvider {
-                Router::<Route> {}
+                ui::components::CommandPaletteProvider {
+                    Router::<Route> {}
+                }
             }
         }
     }
@@ -14,3 +14,9 @@ pub use ui::views::NotesPlaceholder as Notes;
 
 mod settings;
 pub use settings::Settings;
+
+mod shared_note;
+pub use shared_note::SharedNote;
+
+mod verify_email;
+pub use verify_email::VerifyEmail;
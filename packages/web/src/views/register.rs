@@ -12,6 +12,9 @@ pub fn Register() -> Element {
     let mut email = use_signal(String::new);
     let mut password = use_signal(String::new);
     let mut confirm_password = use_signal(String::new);
+    let mut invite_code = use_signal(String::new);
+    // Invite-only servers ask for a code; assume open registration until known
+    let invite_required = use_resource(|| async { api::invite_code_required().await.unwrap_or(false) });
     let mut error = use_signal(|| Option::<String>::None);
     let mut loading = use_signal(|| false);
 
@@ -34,6 +37,7 @@ pub fn Register() -> Element {
             let e = email().trim().to_string();
             let p = password();
             let cp = confirm_password();
+            let code = invite_code().trim().to_string();
 
             if n.is_empty() {
                 error.set(Some("Name is required".to_string()));
@@ -51,9 +55,14 @@ pub fn Register() -> Element {
                 error.set(Some("Passwords do not match".to_string()));
                 return;
             }
+            let needs_code = invite_required().unwrap_or(false);
+            if needs_code && code.is_empty() {
+                error.set(Some("An invite code is required".to_string()));
+                return;
+            }
 
             loading.set(true);
-            match api::register(e, p, n).await {
+            match api::register(e, p, n, needs_code.then_some(code)).await {
                 Ok(user) => {
                     let mut state = auth();
                     state.user = Some(user);
@@ -131,6 +140,17 @@ pub fn Register() -> Element {
                     oninput: move |evt: FormEvent| confirm_password.set(evt.value()),
                 }
 
+                if invite_required().unwrap_or(false) {
+                    Input {
+                        class: "w-full",
+                        r#type: "text",
+                        placeholder: "Invite code",
+                        autocomplete: "off",
+                        value: invite_code(),
+                        oninput: move |evt: FormEvent| invite_code.set(evt.value()),
+                    }
+                }
+
                 Button {
                     variant: ButtonVariant::Primary,
                     class: "w-full text-[0.9375rem] font-medium",
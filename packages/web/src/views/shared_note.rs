@@ -0,0 +1,9 @@
+use dioxus::prelude::*;
+
+/// Public page for a note shared by link; no sign-in required.
+#[component]
+pub fn SharedNote(token: String) -> Element {
+    rsx! {
+        ui::views::SharedNoteView { token }
+    }
+}
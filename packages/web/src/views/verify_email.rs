@@ -0,0 +1,9 @@
+use dioxus::prelude::*;
+
+/// Landing page of the verification link mailed on registration.
+#[component]
+pub fn VerifyEmail(token: String) -> Element {
+    rsx! {
+        ui::views::VerifyEmailView { token }
+    }
+}